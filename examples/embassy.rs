@@ -0,0 +1,38 @@
+//! A sensor that publishes readings from an interrupt handler, here stood in
+//! for by a thread, and an embassy task that reacts to each new one.
+//!
+//! Run with `cargo run --example embassy --features embedded-async`. On a
+//! microcontroller the same task runs on the executor of its HAL, and the
+//! channel is built with `--no-default-features --features
+//! critical-section,embedded-async`.
+use embassy_executor::Spawner;
+use std::{thread, time::Duration};
+use watch::{WatchReceiver, WatchSender};
+
+/// Stands in for the interrupt handler of the sensor.
+fn sensor_interrupts(sender: WatchSender<u16>) {
+    thread::spawn(move || {
+        for reading in [512, 530, 547, 561] {
+            thread::sleep(Duration::from_millis(10));
+            sender.send(reading);
+        }
+    });
+}
+
+#[embassy_executor::task]
+async fn display(mut readings: WatchReceiver<u16>) {
+    while let Ok(reading) = readings.changed().await {
+        println!("reading: {}", reading);
+    }
+    println!("sensor stopped");
+    std::process::exit(0);
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    let (sender, mut receiver) = watch::channel(0);
+    // Only show actual readings.
+    receiver.get();
+    sensor_interrupts(sender);
+    spawner.spawn(display(receiver)).unwrap();
+}
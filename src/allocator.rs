@@ -0,0 +1,106 @@
+//! The allocator that the state of a channel is allocated with.
+//!
+//! With the `allocator_api` feature, these are the allocator API of the
+//! standard library, which requires nightly Rust. Otherwise they are
+//! stand-ins, and every channel uses the global allocator.
+#[cfg(feature = "allocator_api")]
+pub use alloc::alloc::{Allocator, Global};
+#[cfg(feature = "allocator_api")]
+pub(crate) use alloc::sync::Arc as SharedArc;
+
+#[cfg(not(feature = "allocator_api"))]
+use alloc::sync::Arc;
+#[cfg(not(feature = "allocator_api"))]
+use core::{marker::PhantomData, ops::Deref};
+
+/// An allocator for the state of a channel.
+///
+/// This stands in for the trait of the allocator API, which is only
+/// available with the `allocator_api` feature. It cannot be implemented
+/// outside of this crate, and [`Global`] is the only implementation.
+#[cfg(not(feature = "allocator_api"))]
+pub trait Allocator: sealed::Sealed {}
+
+/// The global allocator.
+#[cfg(not(feature = "allocator_api"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+#[cfg(not(feature = "allocator_api"))]
+impl Allocator for Global {}
+
+#[cfg(not(feature = "allocator_api"))]
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Global {}
+}
+
+/// An `Arc` that names the allocator it was allocated with, like the `Arc`
+/// of the allocator API.
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) struct SharedArc<T, A: Allocator = Global> {
+    inner: Arc<T>,
+    _alloc: PhantomData<A>,
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T, A: Allocator> SharedArc<T, A> {
+    pub(crate) fn new_in(value: T, _alloc: A) -> Self {
+        SharedArc {
+            inner: Arc::new(value),
+            _alloc: PhantomData,
+        }
+    }
+
+    pub(crate) fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.inner, &other.inner)
+    }
+
+    pub(crate) fn as_ptr(this: &Self) -> *const T {
+        Arc::as_ptr(&this.inner)
+    }
+
+    pub(crate) fn strong_count(this: &Self) -> usize {
+        Arc::strong_count(&this.inner)
+    }
+
+    pub(crate) fn try_unwrap(this: Self) -> Result<T, Self> {
+        Arc::try_unwrap(this.inner).map_err(|inner| SharedArc {
+            inner,
+            _alloc: PhantomData,
+        })
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T, A: Allocator> Clone for SharedArc<T, A> {
+    fn clone(&self) -> Self {
+        SharedArc {
+            inner: self.inner.clone(),
+            _alloc: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T, A: Allocator> Deref for SharedArc<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+/// A weak reference to the state of a channel in the global allocator.
+#[cfg(all(
+    feature = "std",
+    not(target_family = "wasm"),
+    any(feature = "registry", not(loom))
+))]
+pub(crate) fn downgrade<T>(this: &SharedArc<T, Global>) -> alloc::sync::Weak<T> {
+    #[cfg(feature = "allocator_api")]
+    return alloc::sync::Arc::downgrade(this);
+    #[cfg(not(feature = "allocator_api"))]
+    return Arc::downgrade(&this.inner);
+}
@@ -0,0 +1,278 @@
+//! Choosing the mutex and condition variable that protect a channel.
+//!
+//! The value of a channel is protected by a [`lock_api`] rwlock, so that
+//! receivers can read it at the same time, while waiting uses a mutex and a
+//! condition variable. The condition variable implements [`RawCondvar`],
+//! which also names the raw mutex and rwlock to use. The
+//! channel types take the condition variable as their last type parameter,
+//! which defaults to [`DefaultCondvar`], the backend selected by the crate
+//! features.
+//!
+//! The built-in backends are available when their feature is enabled:
+//! [`StdCondvar`] with `std`, [`ParkingLotCondvar`] with `parking_lot`,
+//! `FutexCondvar` with `futex` on Linux and Android, [`SpinCondvar`] with
+//! `spin`, and [`CriticalSectionCondvar`] with `critical-section` when std is
+//! disabled. Building with `--cfg loom` replaces the default with
+//! `LoomCondvar`, built from the primitives of [`loom`], so that the channel
+//! can be model-checked. To use another mutex, implement
+//! [`RawCondvar`] for a condition variable that works with it, and create
+//! the channel with [`channel`], [`scoped`], [`copy_channel`] or
+//! [`triple_channel`].
+//!
+//! [`lock_api`]: https://docs.rs/lock_api
+//! [`loom`]: https://docs.rs/loom
+use crate::{
+    channel_at_version, CopyReceiver, CopySender, ScopedChannel, TripleReceiver, TripleSender,
+    WatchReceiver, WatchSender,
+};
+use core::time::Duration;
+
+pub use lock_api::{self, MutexGuard, RawMutex, RawRwLock, RwLockWriteGuard};
+
+#[cfg(feature = "std")]
+pub use crate::sync_std::{
+    Condvar as StdCondvar, RawMutex as StdRawMutex, RawRwLock as StdRawRwLock,
+};
+
+#[cfg(feature = "parking_lot")]
+pub use crate::sync_parking_lot::Condvar as ParkingLotCondvar;
+
+#[cfg(all(feature = "futex", any(target_os = "linux", target_os = "android")))]
+pub use crate::sync_futex::{
+    Condvar as FutexCondvar, RawMutex as FutexRawMutex, RawRwLock as FutexRawRwLock,
+};
+
+#[cfg(feature = "spin")]
+pub use crate::sync_spin::Condvar as SpinCondvar;
+
+#[cfg(loom)]
+pub use crate::sync_loom::{
+    Condvar as LoomCondvar, RawMutex as LoomRawMutex, RawRwLock as LoomRawRwLock,
+};
+
+#[cfg(all(not(feature = "std"), feature = "critical-section"))]
+pub use crate::sync_critical_section::{
+    Condvar as CriticalSectionCondvar, RawMutex as CriticalSectionRawMutex,
+};
+
+/// The backend selected by the crate features.
+#[cfg(loom)]
+pub type DefaultCondvar = LoomCondvar;
+/// The backend selected by the crate features.
+#[cfg(all(feature = "parking_lot", not(loom)))]
+pub type DefaultCondvar = ParkingLotCondvar;
+/// The backend selected by the crate features.
+#[cfg(all(
+    feature = "futex",
+    not(feature = "parking_lot"),
+    any(target_os = "linux", target_os = "android"),
+    not(loom)
+))]
+pub type DefaultCondvar = FutexCondvar;
+/// The backend selected by the crate features.
+#[cfg(all(
+    feature = "std",
+    not(feature = "parking_lot"),
+    not(all(feature = "futex", any(target_os = "linux", target_os = "android"))),
+    not(loom)
+))]
+pub type DefaultCondvar = StdCondvar;
+/// The backend selected by the crate features.
+#[cfg(all(not(feature = "std"), feature = "critical-section"))]
+pub type DefaultCondvar = CriticalSectionCondvar;
+/// The backend selected by the crate features.
+#[cfg(all(
+    not(feature = "std"),
+    not(feature = "critical-section"),
+    feature = "spin"
+))]
+pub type DefaultCondvar = SpinCondvar;
+
+/// A condition variable that works with the raw mutex `RawMutex`.
+///
+/// # Safety
+///
+/// `wait` must unlock the mutex while it waits and lock it again before
+/// returning. A thread that started waiting before a call to `notify_all`
+/// must eventually return from `wait`. Spurious wakeups are allowed.
+pub unsafe trait RawCondvar {
+    /// The raw mutex that waiting threads hold.
+    type RawMutex: RawMutex;
+
+    /// The raw rwlock that protects the value.
+    type RawRwLock: RawRwLock;
+
+    /// Create a new condition variable.
+    fn new() -> Self;
+
+    /// Block until notified.
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, Self::RawMutex, T>);
+
+    /// Wake every thread that is waiting.
+    fn notify_all(&self);
+
+    /// Unlock the mutex fairly, handing it to a waiting thread if there is
+    /// one.
+    ///
+    /// This is used by channels built with
+    /// [`fair_lock`](crate::ChannelBuilder::fair_lock). The default
+    /// implementation unlocks the mutex normally.
+    fn unlock_fair<T>(guard: MutexGuard<'_, Self::RawMutex, T>) {
+        drop(guard);
+    }
+
+    /// Unlock the rwlock fairly after writing, handing it to the waiting
+    /// threads if there are any.
+    ///
+    /// See [`unlock_fair`](RawCondvar::unlock_fair).
+    fn unlock_write_fair<T>(guard: RwLockWriteGuard<'_, Self::RawRwLock, T>) {
+        drop(guard);
+    }
+}
+
+/// A condition variable that supports timed waits.
+///
+/// The methods of the channel that take a timeout require this.
+///
+/// # Safety
+///
+/// `wait_timeout` must follow the same rules as [`RawCondvar::wait`].
+pub unsafe trait RawCondvarTimeout: RawCondvar {
+    /// Block until notified or until `timeout` has passed.
+    ///
+    /// Returns `true` if the timeout expired.
+    fn wait_timeout<T>(
+        &self,
+        guard: &mut MutexGuard<'_, Self::RawMutex, T>,
+        timeout: Duration,
+    ) -> bool;
+}
+
+/// Creates a new watch channel that uses the given backend.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn channel<C: RawCondvar, T>(value: T) -> (WatchSender<T, C>, WatchReceiver<T, C>) {
+    channel_at_version(value, 1)
+}
+
+/// Creates a new scoped watch channel that uses the given backend.
+///
+/// See [`scoped`](crate::scoped).
+pub fn scoped<C: RawCondvar, T>(value: T) -> ScopedChannel<T, C> {
+    ScopedChannel::new(value)
+}
+
+/// Creates a new watch channel for `Copy` values that uses the given backend.
+///
+/// See [`copy_channel`](crate::copy_channel).
+pub fn copy_channel<C: RawCondvar, T: Copy>(value: T) -> (CopySender<T, C>, CopyReceiver<T, C>) {
+    crate::copy::new(value)
+}
+
+/// Creates a new triple-buffered watch channel that uses the given backend.
+///
+/// See [`triple_channel`](crate::triple_channel).
+pub fn triple_channel<C: RawCondvar, T>(value: T) -> (TripleSender<T, C>, TripleReceiver<T, C>) {
+    crate::triple::new(value)
+}
+
+/// Creates a new notification channel that uses the given backend.
+///
+/// See [`event`](crate::event).
+pub fn event<C: RawCondvar>() -> (crate::EventSender<C>, crate::EventListener<C>) {
+    crate::event::new()
+}
+
+/// Creates a new flag that uses the given backend.
+///
+/// See [`flag`](crate::flag).
+pub fn flag<C: RawCondvar>(initial: bool) -> (crate::FlagSetter<C>, crate::FlagWatcher<C>) {
+    crate::flag::new(initial)
+}
+
+/// Creates a new counter that uses the given backend.
+///
+/// See [`counter`](crate::counter).
+pub fn counter<C: RawCondvar>(initial: u64) -> (crate::CounterSender<C>, crate::CounterWatcher<C>) {
+    crate::counter::new(initial)
+}
+
+/// Creates a new folding channel that uses the given backend.
+///
+/// See [`folding_channel`](crate::folding_channel).
+pub fn folding_channel<T, D, C, F>(
+    initial: T,
+    fold: F,
+) -> (crate::FoldSender<T, D, C>, crate::FoldReceiver<T, C>)
+where
+    C: RawCondvar,
+    F: Fn(&mut T, D) + Send + Sync + 'static,
+{
+    crate::fold::new(initial, fold)
+}
+
+/// Creates a new write-once channel that uses the given backend.
+///
+/// See [`once_channel`](crate::once_channel).
+pub fn once_channel<T, C: RawCondvar>() -> (crate::OnceSender<T, C>, crate::OnceReceiver<T, C>) {
+    crate::once::new()
+}
+
+/// Creates a new monotonic channel that uses the given backend.
+///
+/// See [`monotonic_channel`](crate::monotonic_channel).
+pub fn monotonic_channel<T: PartialOrd, C: RawCondvar>(
+    initial: T,
+) -> (crate::MonotonicSender<T, C>, crate::WatchReceiver<T, C>) {
+    crate::monotonic::new(initial)
+}
+
+/// Creates a new patch channel that uses the given backend.
+///
+/// See [`patch_channel`](crate::patch_channel).
+pub fn patch_channel<T: crate::ApplyPatch<P>, P, C: RawCondvar>(
+    initial: T,
+) -> (crate::PatchSender<T, P, C>, crate::WatchReceiver<T, C>) {
+    crate::patch::new(initial)
+}
+
+/// Creates a new pair of channels in opposite directions that uses the
+/// given backend.
+///
+/// See [`channel_pair`](crate::channel_pair).
+pub fn channel_pair<A, B, C: RawCondvar>(
+    a: A,
+    b: B,
+) -> (crate::Endpoint<A, B, C>, crate::Endpoint<B, A, C>) {
+    crate::pair::new(a, b)
+}
+
+/// Creates a new, empty map of watch channels that uses the given backend.
+///
+/// See [`watch_map`](crate::watch_map).
+#[cfg(feature = "std")]
+pub fn watch_map<C: RawCondvar, K: Eq + core::hash::Hash, T>() -> crate::MapSender<K, T, C> {
+    crate::map::new()
+}
+
+/// Creates a new watch channel backed by `arc-swap` that uses the given
+/// backend.
+///
+/// See [`arc_channel`](crate::arc_channel).
+#[cfg(feature = "arc-swap")]
+pub fn arc_channel<C: RawCondvar, T>(
+    value: T,
+) -> (crate::ArcWatchSender<T, C>, crate::ArcWatchReceiver<T, C>) {
+    crate::swap::new(value)
+}
+
+/// Creates a new left-right watch channel that uses the given backend.
+///
+/// See [`lr_channel`](crate::lr_channel).
+#[cfg(feature = "left-right")]
+pub fn lr_channel<C: RawCondvar, T: Clone>(
+    value: T,
+) -> (crate::LrWatchSender<T, C>, crate::LrWatchReceiver<T, C>) {
+    crate::left_right::new(value)
+}
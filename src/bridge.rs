@@ -0,0 +1,187 @@
+use crate::{channel, stop::Stop, WatchReceiver};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvError, RecvTimeoutError, Sender, TryRecvError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How often a bridge thread blocked on an mpsc receiver checks whether it
+/// has been asked to stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Handle to the thread spawned by [`from_mpsc`].
+///
+/// Dropping the handle asks the thread to stop without waiting for it.
+pub struct BridgeHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Publish the items of an mpsc receiver on a new watch channel.
+///
+/// This blocks until the first item arrives, which becomes the initial value
+/// of the channel. It then spawns a thread that forwards the rest of the
+/// items. When several items are queued at once, only the most recent one is
+/// sent. The watch channel is closed once the mpsc channel disconnects or the
+/// returned handle is stopped.
+///
+/// Returns an error if the mpsc channel disconnects before sending anything.
+pub fn from_mpsc<T>(rx: Receiver<T>) -> Result<(WatchReceiver<T>, BridgeHandle), RecvError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let first = rx.recv()?;
+    let (sender, receiver) = channel(latest(first, &rx).0);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                let next = match rx.recv_timeout(POLL_INTERVAL) {
+                    Ok(next) => next,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                let (next, disconnected) = latest(next, &rx);
+                sender.send(next);
+                if disconnected {
+                    break;
+                }
+            }
+        })
+    };
+
+    let handle = BridgeHandle {
+        stop,
+        thread: Some(thread),
+    };
+    Ok((receiver, handle))
+}
+
+/// Drain every item that is already queued, keeping only the last one.
+///
+/// Also reports whether the channel turned out to be disconnected.
+fn latest<T>(mut value: T, rx: &Receiver<T>) -> (T, bool) {
+    loop {
+        match rx.try_recv() {
+            Ok(next) => value = next,
+            Err(TryRecvError::Empty) => return (value, false),
+            Err(TryRecvError::Disconnected) => return (value, true),
+        }
+    }
+}
+
+/// Handle to the thread spawned by [`WatchReceiver::forward_to`].
+///
+/// Dropping the handle asks the thread to stop without waiting for it.
+pub struct ForwarderHandle {
+    /// Asks the thread to stop.
+    stop: Box<dyn Fn() + Send + Sync>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T> WatchReceiver<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Spawn a thread that sends every value this receiver sees into an mpsc
+    /// channel.
+    ///
+    /// The thread waits like [`wait`] does, so values that are replaced before
+    /// the thread wakes up are skipped, and a slow mpsc consumer never blocks
+    /// the senders of the watch channel. It exits once the watch channel is
+    /// closed, the mpsc receiver is dropped, or the returned handle is stopped.
+    ///
+    /// [`wait`]: WatchReceiver::wait
+    pub fn forward_to(mut self, tx: Sender<T>) -> ForwarderHandle {
+        let stop = Arc::new(Stop::new());
+        let shared = self.shared.clone();
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || loop {
+                {
+                    let seen = self.last_seen_version;
+                    let state = self.shared.state.lock();
+                    let state = self
+                        .shared
+                        .wait_while_unless_stopped(state, &stop, |state| {
+                            state.version == seen && state.is_open()
+                        });
+                    if state.version == seen || stop.is_stopped() {
+                        return;
+                    }
+                }
+                if tx.send(self.get()).is_err() {
+                    return;
+                }
+            })
+        };
+
+        ForwarderHandle {
+            stop: Box::new(move || {
+                // Taking the lock ensures that the forwarder is either parked
+                // or has not yet checked the stop flag.
+                let _state = shared.state.lock();
+                stop.stop();
+            }),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl ForwarderHandle {
+    /// Stop the forwarder thread and wait for it to exit.
+    pub fn stop(mut self) {
+        self.interrupt();
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    /// Returns `true` if the forwarder thread has exited.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    fn interrupt(&self) {
+        (self.stop)();
+    }
+}
+
+impl Drop for ForwarderHandle {
+    fn drop(&mut self) {
+        self.interrupt();
+    }
+}
+
+impl BridgeHandle {
+    /// Stop the bridge thread and wait for it to exit.
+    ///
+    /// Items still queued in the mpsc channel are not published.
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    /// Returns `true` if the bridge thread has exited.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+}
+
+impl Drop for BridgeHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{backend::RawCondvar, channel_from_shared, Shared, WatchReceiver, WatchSender};
+#[cfg(all(
+    feature = "test-clock",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{clock::Clock, MockClock};
+#[cfg(feature = "metrics")]
+use alloc::borrow::Cow;
+#[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+use core::time::Duration;
+
+/// Configures a watch channel before creating it.
+///
+/// The defaults create the same channel as [`channel`](crate::channel).
+/// Every option is set through a method, so new options can be added
+/// without breaking existing builders.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelBuilder {
+    initial_seen: bool,
+    history: usize,
+    #[cfg(target_has_atomic = "64")]
+    track_lag: bool,
+    fair_lock: bool,
+    manual_notify: bool,
+    undo: bool,
+    keep_previous: bool,
+    max_receivers: Option<usize>,
+    #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+    slow_lock_threshold: Option<Duration>,
+    #[cfg(feature = "metrics")]
+    metrics_label: Option<Cow<'static, str>>,
+    #[cfg(feature = "tracing")]
+    trace_spans: bool,
+    #[cfg(all(
+        feature = "test-clock",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    clock: Option<MockClock>,
+}
+
+/// Creates a builder for a watch channel.
+pub fn builder() -> ChannelBuilder {
+    ChannelBuilder::default()
+}
+
+impl ChannelBuilder {
+    /// Consider the starting value seen by the receiver.
+    ///
+    /// The receiver then only returns from a wait once a value has been
+    /// sent. The default is `false`, like [`channel`](crate::channel).
+    pub fn initial_seen(mut self, seen: bool) -> Self {
+        self.initial_seen = seen;
+        self
+    }
+
+    /// Keep the latest `capacity` values, so that receivers can read the
+    /// values they missed with
+    /// [`WatchReceiver::missed_values`](crate::WatchReceiver::missed_values).
+    ///
+    /// The values sent are shared with the history rather than copied, but
+    /// [`WatchSender::update`](crate::WatchSender::update) then clones the
+    /// value before changing it. The default is `0`, which keeps no history
+    /// and allocates nothing for it.
+    pub fn history(mut self, capacity: usize) -> Self {
+        self.history = capacity;
+        self
+    }
+
+    /// Keep track of the version each receiver has seen, so that the senders
+    /// can tell how far behind the slowest receiver is, see
+    /// [`WatchSender::max_lag`](crate::WatchSender::max_lag), and be told
+    /// when a receiver falls behind, see
+    /// [`WatchSender::on_lagging`](crate::WatchSender::on_lagging).
+    ///
+    /// Every receiver then reports the version it has seen after each read,
+    /// and creating or cloning a receiver takes a lock. The default is
+    /// `false`, which tracks nothing and costs nothing. Only available on
+    /// targets with 64-bit atomics.
+    #[cfg(target_has_atomic = "64")]
+    pub fn track_lag(mut self, track: bool) -> Self {
+        self.track_lag = track;
+        self
+    }
+
+    /// Release the locks of the channel fairly after every send.
+    ///
+    /// A fair unlock hands the lock straight to a thread that is waiting for
+    /// it, so a sender that sends in a tight loop cannot keep the receivers
+    /// from reading the value. This makes every send slower when there is
+    /// contention, and only has an effect with the `parking_lot` backend:
+    /// the other built-in backends ignore it. The default is `false`.
+    pub fn fair_lock(mut self, fair: bool) -> Self {
+        self.fair_lock = fair;
+        self
+    }
+
+    /// Keep the value that each send or update replaces, so that
+    /// [`WatchSender::undo`](crate::WatchSender::undo) can put it back.
+    ///
+    /// This keeps one more value alive, and
+    /// [`WatchSender::update`](crate::WatchSender::update) then clones the
+    /// value before changing it, with the value before the closure ran
+    /// becoming the one that undo goes back to. The default is `false`,
+    /// which allocates nothing for it.
+    pub fn undo(mut self, undo: bool) -> Self {
+        self.undo = undo;
+        self
+    }
+
+    /// Keep the value before the latest version, so that receivers can read
+    /// both with
+    /// [`WatchReceiver::get_with_previous`](crate::WatchReceiver::get_with_previous),
+    /// such as to tell what changed.
+    ///
+    /// This keeps one more value alive, and
+    /// [`WatchSender::update`](crate::WatchSender::update) then clones the
+    /// value before changing it. The default is `false`, which allocates
+    /// nothing for it.
+    pub fn keep_previous(mut self, keep: bool) -> Self {
+        self.keep_previous = keep;
+        self
+    }
+
+    /// Allow at most `max` receivers to exist at once, counting the one
+    /// created with the channel.
+    ///
+    /// Creating another receiver then fails:
+    /// [`WatchSender::try_subscribe`](crate::WatchSender::try_subscribe) and
+    /// [`WatchReceiver::try_clone`](crate::WatchReceiver::try_clone) return
+    /// an error, and the methods that cannot fail, such as `subscribe` and
+    /// `clone`, panic. Dropping a receiver makes room for another. Readers
+    /// are not counted. The default is no limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    pub fn max_receivers(mut self, max: usize) -> Self {
+        assert!(
+            max > 0,
+            "a watch channel needs room for at least one receiver"
+        );
+        self.max_receivers = Some(max);
+        self
+    }
+
+    /// Only wake waiting receivers when a sender calls
+    /// [`WatchSender::pump`](crate::WatchSender::pump), such as in a test
+    /// that decides when every thread runs.
+    ///
+    /// Sending still stores the value and gives it a new version right away,
+    /// so reads such as
+    /// [`WatchReceiver::get_if_new`](crate::WatchReceiver::get_if_new) see
+    /// it, and a wait that starts after the send returns at once. Only the
+    /// threads and tasks that are already waiting stay asleep until the next
+    /// pump, although a timed wait that times out before then returns the
+    /// new value rather than timing out. Closing the channel still wakes
+    /// everyone at once. The default is `false`.
+    pub fn manual_notify(mut self, manual: bool) -> Self {
+        self.manual_notify = manual;
+        self
+    }
+
+    /// Warn when an operation keeps the value locked for longer than
+    /// `threshold`.
+    ///
+    /// Sends, updates, transactions and reads, including the read at the
+    /// end of a wait, measure how long they hold the value, and emit a
+    /// `tracing` warning with the `watch` target, the name of the operation
+    /// and how long it held the value. This finds closures passed to
+    /// [`WatchSender::update`](crate::WatchSender::update) that block other
+    /// threads for too long, such as ones doing I/O. Times are read on the
+    /// clock of the channel. The default is 5 milliseconds. Only available
+    /// with the `lock-timing` feature.
+    #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+    pub fn slow_lock_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_lock_threshold = Some(threshold);
+        self
+    }
+
+    /// Keep the [`tracing`] span that each send or update ran in with the
+    /// value it wrote, so that receivers can process the value in the span
+    /// of the producer, see
+    /// [`WatchReceiver::wait_traced`](crate::WatchReceiver::wait_traced).
+    ///
+    /// The span is replaced together with the value, and a value that was
+    /// not written in a span, such as the starting value, has a disabled
+    /// span. This asks the subscriber for the current span on every write.
+    /// The default is `false`, which keeps no span. Only available with the
+    /// `tracing` feature.
+    #[cfg(feature = "tracing")]
+    pub fn trace_spans(mut self, trace: bool) -> Self {
+        self.trace_spans = trace;
+        self
+    }
+
+    /// Report what happens on the channel through the [`metrics`] facade,
+    /// with `label` as the `channel` label of every series.
+    ///
+    /// The channel registers these series when it is created:
+    ///
+    /// - `watch_sends`, a counter of the values published,
+    /// - `watch_wakeups`, a counter of the threads that woke up from a
+    ///   blocking or timed wait,
+    /// - `watch_receivers`, a gauge of the number of receivers,
+    /// - `watch_max_lag`, a gauge of how many versions the slowest receiver
+    ///   is behind, if the channel was created with
+    ///   [`track_lag`](ChannelBuilder::track_lag).
+    ///
+    /// Sends and wakeups are counted in an atomic and handed to the
+    /// recorder in batches, when the channel is dropped, and by
+    /// [`WatchSender::flush_metrics`](crate::WatchSender::flush_metrics),
+    /// which also updates the lag gauge. The receiver gauge is set whenever
+    /// the count changes. The default is to report nothing. Only available
+    /// with the `metrics` feature.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_label(mut self, label: impl Into<Cow<'static, str>>) -> Self {
+        self.metrics_label = Some(label.into());
+        self
+    }
+
+    /// Measure the timeouts of the timed waits on `clock` rather than the
+    /// system clock.
+    ///
+    /// See [`MockClock`].
+    #[cfg(all(
+        feature = "test-clock",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    pub fn clock(mut self, clock: MockClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Creates the channel with the given starting value.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn channel<T>(self, value: T) -> (WatchSender<T>, WatchReceiver<T>) {
+        self.channel_with(value)
+    }
+
+    /// Creates the channel with the given starting value, using the given
+    /// backend.
+    ///
+    /// See [`backend`](crate::backend).
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn channel_with<C: RawCondvar, T>(
+        self,
+        value: T,
+    ) -> (WatchSender<T, C>, WatchReceiver<T, C>) {
+        let mut shared = Shared::new(value, 1);
+        shared.fair = self.fair_lock;
+        #[cfg(feature = "tracing")]
+        {
+            shared.trace_spans = self.trace_spans;
+        }
+        shared.state.get_mut().manual_notify = self.manual_notify;
+        if let Some(max) = self.max_receivers {
+            shared.state.get_mut().max_receivers = max;
+        }
+        shared.enable_history(self.history);
+        if self.undo {
+            shared.enable_undo();
+        }
+        if self.keep_previous {
+            shared.enable_previous();
+        }
+        #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+        if let Some(threshold) = self.slow_lock_threshold {
+            shared.slow_lock = threshold;
+        }
+        #[cfg(target_has_atomic = "64")]
+        if self.track_lag {
+            shared.enable_lag_tracking();
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(label) = self.metrics_label {
+            let (metrics, receivers) = crate::metrics_export::ChannelMetrics::register(label);
+            // The receiver that comes with the channel.
+            receivers.set(1.0);
+            shared.metrics = Some(Box::new(metrics));
+            shared.state.get_mut().receivers_gauge = Some(receivers);
+        }
+        #[cfg(all(
+            feature = "test-clock",
+            any(not(target_family = "wasm"), target_feature = "atomics")
+        ))]
+        if let Some(clock) = self.clock {
+            shared.clock = Clock::Mock(clock);
+        }
+        let (sender, mut receiver) = channel_from_shared(shared);
+        if self.initial_seen {
+            receiver.last_seen_version = receiver.shared.version();
+            receiver.cursor.skip_to(receiver.last_seen_version);
+        }
+        (sender, receiver)
+    }
+}
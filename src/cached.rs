@@ -0,0 +1,70 @@
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    Allocator, Global, WatchReceiver,
+};
+use alloc::sync::Arc;
+
+/// A receiver that keeps its own copy of the value, so reading it never
+/// waits for the channel.
+///
+/// This is created by [`WatchReceiver::into_cached`]. The copy is an `Arc`
+/// handle as returned by [`WatchReceiver::get_shared`], so keeping it does
+/// not clone the value.
+pub struct CachedWatchReceiver<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    receiver: WatchReceiver<T, C, A>,
+    cached: Arc<T>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Turn this receiver into one that keeps its own copy of the value.
+    ///
+    /// This fetches the latest value, waiting for the lock if needed.
+    pub fn into_cached(mut self) -> CachedWatchReceiver<T, C, A> {
+        let cached = self.get_shared();
+        CachedWatchReceiver {
+            receiver: self,
+            cached,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> CachedWatchReceiver<T, C, A> {
+    /// Get the latest value that could be fetched without waiting.
+    ///
+    /// If a new value has been sent and the channel is not locked, the copy
+    /// is refreshed first. Otherwise the copy is returned as it is, which
+    /// may be stale: use [`is_stale`] to find out. Replacing the copy drops
+    /// the old value here if no other handle holds it. The critical-section
+    /// backend is the exception, as checking its lock enters a critical
+    /// section, which waits for the one that holds it.
+    ///
+    /// [`is_stale`]: CachedWatchReceiver::is_stale
+    pub fn get_cached(&mut self) -> &T {
+        if let Some(value) = self
+            .receiver
+            .track(|shared, seen| shared.try_get_if_new_shared(seen))
+        {
+            self.cached = value;
+        }
+        &self.cached
+    }
+
+    /// Returns `true` if a value newer than the copy has been sent.
+    pub fn is_stale(&self) -> bool {
+        self.receiver.has_changed()
+    }
+
+    /// Refresh the copy with the latest value, waiting for the lock if
+    /// needed.
+    pub fn refresh_blocking(&mut self) -> &T {
+        self.cached = self.receiver.get_shared();
+        &self.cached
+    }
+
+    /// Get back the receiver, dropping the copy.
+    ///
+    /// The value in the copy counts as seen by the receiver.
+    pub fn into_inner(self) -> WatchReceiver<T, C, A> {
+        self.receiver
+    }
+}
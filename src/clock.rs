@@ -0,0 +1,185 @@
+//! The time source of the timed waits.
+#[cfg(feature = "test-clock")]
+use alloc::sync::Arc;
+#[cfg(feature = "test-clock")]
+use core::convert::TryFrom;
+use core::time::Duration;
+#[cfg(feature = "test-clock")]
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+#[cfg(any(
+    feature = "test-clock",
+    not(all(target_family = "wasm", target_os = "unknown"))
+))]
+use std::time::Instant;
+
+/// How long a timed wait on a [`MockClock`] sleeps before reading the clock
+/// again.
+#[cfg(feature = "test-clock")]
+const MOCK_POLL: Duration = Duration::from_millis(1);
+
+/// Where a channel reads the time for its timed waits.
+#[derive(Clone)]
+pub(crate) enum Clock {
+    /// `Instant::now`.
+    System,
+    #[cfg(feature = "test-clock")]
+    Mock(MockClock),
+}
+
+impl Clock {
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    pub(crate) fn now(&self) -> Instant {
+        match self {
+            Clock::System => Instant::now(),
+            #[cfg(feature = "test-clock")]
+            Clock::Mock(clock) => clock.now(),
+        }
+    }
+
+    /// The longest a timed wait may sleep for, when `remaining` is left
+    /// until its deadline.
+    ///
+    /// Advancing a mock clock does not wake anyone, so waits on one sleep
+    /// briefly and then read the clock again.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    fn sleep_limit(&self, remaining: Duration) -> Duration {
+        match self {
+            Clock::System => remaining,
+            #[cfg(feature = "test-clock")]
+            Clock::Mock(_) => remaining.min(MOCK_POLL),
+        }
+    }
+}
+
+/// The point in time at which a timed wait gives up.
+#[derive(Clone)]
+pub(crate) struct Deadline {
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    at: Instant,
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    clock: Clock,
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    timeout: Duration,
+}
+
+impl Deadline {
+    /// The deadline `timeout` from now on the system clock.
+    pub(crate) fn after(timeout: Duration) -> Deadline {
+        Deadline::on(Clock::System, timeout)
+    }
+
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    pub(crate) fn on(clock: Clock, timeout: Duration) -> Deadline {
+        Deadline {
+            at: clock.now() + timeout,
+            clock,
+        }
+    }
+
+    /// How long to sleep for before checking the deadline again.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    pub(crate) fn sleep_time(&self) -> Duration {
+        let remaining = self.at.saturating_duration_since(self.clock.now());
+        self.clock.sleep_limit(remaining)
+    }
+
+    /// Whether a wait that timed out after sleeping for `sleep_time` has
+    /// reached the deadline.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    pub(crate) fn expired(&self) -> bool {
+        self.clock.now() >= self.at
+    }
+
+    /// `Instant::now` panics on `wasm32-unknown-unknown`, so there each wait
+    /// is given the full timeout, and the clock is ignored.
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    pub(crate) fn on(clock: Clock, timeout: Duration) -> Deadline {
+        drop(clock);
+        Deadline { timeout }
+    }
+
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    pub(crate) fn sleep_time(&self) -> Duration {
+        self.timeout
+    }
+
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    pub(crate) fn expired(&self) -> bool {
+        true
+    }
+}
+
+/// A clock for tests, which only moves when it is advanced.
+///
+/// A channel created with [`channel_with_clock`](crate::channel_with_clock)
+/// or [`ChannelBuilder::clock`](crate::ChannelBuilder::clock) measures the
+/// timeouts of [`WatchReceiver::wait_timeout`](crate::WatchReceiver::wait_timeout)
+/// and [`WatchReceiver::recv_timeout`](crate::WatchReceiver::recv_timeout)
+/// on the mock clock, so they only time out once a test has advanced the
+/// clock past their deadline, however long they really wait. A test can
+/// check that a wait times out by advancing the clock from another thread,
+/// and that a value sent in time is received by sending it before advancing
+/// the clock, without sleeping.
+///
+/// Clones of a mock clock share the same time. A waiting thread notices
+/// that the clock was advanced within about a millisecond.
+#[cfg(feature = "test-clock")]
+#[derive(Clone)]
+pub struct MockClock {
+    inner: Arc<MockInner>,
+}
+
+#[cfg(feature = "test-clock")]
+struct MockInner {
+    start: Instant,
+    /// Nanoseconds since `start`.
+    elapsed: AtomicU64,
+}
+
+#[cfg(feature = "test-clock")]
+impl MockClock {
+    /// Creates a mock clock that starts at the current time.
+    pub fn new() -> MockClock {
+        MockClock {
+            inner: Arc::new(MockInner {
+                start: Instant::now(),
+                elapsed: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// The current time of the clock.
+    pub fn now(&self) -> Instant {
+        self.inner.start + self.elapsed()
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.inner.elapsed.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// How far the clock has been advanced since it was created.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.inner.elapsed.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(feature = "test-clock")]
+impl Default for MockClock {
+    fn default() -> MockClock {
+        MockClock::new()
+    }
+}
+
+#[cfg(feature = "test-clock")]
+impl fmt::Debug for MockClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockClock")
+            .field("elapsed", &self.elapsed())
+            .finish()
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{backend::RawCondvarTimeout, Allocator, WatchReceiver};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Wait for up to `n` values that this receiver has not seen, and return
+    /// clones of them, oldest first.
+    ///
+    /// A value counts once it is delivered to this receiver rather than when
+    /// it is sent, so values that are replaced before the receiver gets to
+    /// them are not returned. A value that the receiver has not seen when
+    /// this is called, such as the starting value of a new channel, counts
+    /// as the first one. `timeout` covers the whole call, and the values
+    /// that arrived before it passed are returned.
+    pub fn collect_updates(&mut self, n: usize, timeout: Duration) -> Vec<T> {
+        let deadline = self.shared.deadline(timeout);
+        let mut values = Vec::new();
+        while values.len() < n {
+            match self.wait_shared_until(&deadline) {
+                Some(value) => values.push(T::clone(&value)),
+                None => break,
+            }
+        }
+        values
+    }
+
+    /// Return clones of every value delivered to this receiver for
+    /// `duration`, oldest first.
+    ///
+    /// See [`collect_updates`](WatchReceiver::collect_updates).
+    pub fn collect_for(&mut self, duration: Duration) -> Vec<T> {
+        self.collect_updates(usize::MAX, duration)
+    }
+}
@@ -0,0 +1,340 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{backend::RawCondvarTimeout, park_while_until, Deadline, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    SharedState,
+};
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{park_while, RecvError};
+use alloc::sync::Arc;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+use core::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{fence, AtomicUsize, Ordering},
+};
+use lock_api::Mutex;
+
+/// A version that no value has, since versions are always even.
+const UNSEEN: u64 = u64::MAX;
+
+/// The sender for a channel created by [`copy_channel`].
+///
+/// The sender can be cloned to obtain multiple senders for the same channel.
+pub struct CopySender<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<CopyShared<T, C>>,
+}
+
+/// The receiver for a channel created by [`copy_channel`].
+///
+/// The receiver can be cloned. Each clone will yield a new receiver that
+/// receives the same messages.
+pub struct CopyReceiver<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<CopyShared<T, C>>,
+    last_seen_version: u64,
+}
+
+/// A channel whose value is guarded by a sequence counter.
+///
+/// Writers hold `state`, which serializes them, and make the sequence odd
+/// while they write. Readers copy the value without locking, and retry if
+/// the sequence was odd or changed while they copied.
+struct CopyShared<T, C: RawCondvar> {
+    /// The version of the value, or an odd number while it is written.
+    sequence: AtomicUsize,
+    value: UnsafeCell<T>,
+    state: Mutex<C::RawMutex, SharedState>,
+    _condvar: PhantomData<C>,
+}
+
+// SAFETY: Readers only ever copy the value out, which moves a `T` to their
+// thread, and writers are serialized by `state`. The remaining fields need
+// the same bounds as they would without the `UnsafeCell`.
+unsafe impl<T: Send, C: RawCondvar + Sync> Sync for CopyShared<T, C> where C::RawMutex: Sync {}
+
+/// Creates a new watch channel for small `Copy` values.
+///
+/// Receivers read the value without taking a lock, so they never block the
+/// senders and are never blocked by them, apart from retrying when a read
+/// overlaps a send. Waiting for a new value still parks the thread.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+pub fn copy_channel<T: Copy>(value: T) -> (CopySender<T>, CopyReceiver<T>) {
+    new(value)
+}
+
+pub(crate) fn new<T: Copy, C: RawCondvar>(value: T) -> (CopySender<T, C>, CopyReceiver<T, C>) {
+    let shared = Arc::new(CopyShared {
+        sequence: AtomicUsize::new(0),
+        value: UnsafeCell::new(value),
+        state: Mutex::new(SharedState::new(0)),
+        _condvar: PhantomData,
+    });
+    (
+        CopySender {
+            shared: shared.clone(),
+        },
+        CopyReceiver {
+            shared,
+            last_seen_version: UNSEEN,
+        },
+    )
+}
+
+impl<T: Copy, C: RawCondvar> CopyShared<T, C> {
+    /// Copy the value and return it with its version.
+    fn read(&self) -> (T, u64) {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before & 1 == 0 {
+                // SAFETY: The pointer is valid for reads. A writer may be
+                // writing at the same time, so the copy is kept as
+                // `MaybeUninit` until it is known to be intact.
+                let value =
+                    unsafe { ptr::read_volatile(self.value.get() as *const MaybeUninit<T>) };
+                fence(Ordering::Acquire);
+                if self.sequence.load(Ordering::Relaxed) == before {
+                    // SAFETY: The sequence did not change, so no write
+                    // overlapped the copy and it holds the value written by
+                    // the last writer.
+                    return (unsafe { value.assume_init() }, before as u64);
+                }
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Replace the value and wake everyone waiting for it.
+    ///
+    /// `state` must be the locked `self.state`.
+    fn write(&self, state: &mut SharedState, value: T) {
+        let sequence = self.sequence.load(Ordering::Relaxed);
+        self.sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        // SAFETY: Holding `state` makes this the only writer, and readers
+        // discard anything they copy while the sequence is odd.
+        unsafe { ptr::write_volatile(self.value.get(), value) };
+        let sequence = sequence.wrapping_add(2);
+        self.sequence.store(sequence, Ordering::Release);
+
+        state.version = sequence as u64;
+        state.waiters.wake(state.version);
+        state.wake_tasks();
+    }
+
+    fn get(&self, seen: &mut u64) -> T {
+        let (value, version) = self.read();
+        *seen = version;
+        value
+    }
+
+    fn get_if_new(&self, seen: &mut u64) -> Option<T> {
+        if self.sequence.load(Ordering::Acquire) as u64 == *seen {
+            return None;
+        }
+        let (value, version) = self.read();
+        if version == *seen {
+            return None;
+        }
+        *seen = version;
+        Some(value)
+    }
+}
+
+impl<T: Copy, C: RawCondvar> CopySender<T, C> {
+    /// Send a new message and notify all receivers currently waiting for a
+    /// message.
+    pub fn send(&self, value: T) {
+        let mut state = self.shared.state.lock();
+        self.shared.write(&mut state, value);
+    }
+
+    /// Update the message by a closure and notify all receivers currently waiting for a message.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut state = self.shared.state.lock();
+        // SAFETY: Holding `state` excludes the other writers, so the value
+        // cannot change while it is read.
+        let mut value = unsafe { *self.shared.value.get() };
+        f(&mut value);
+        self.shared.write(&mut state, value);
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    pub fn subscribe(&self) -> CopyReceiver<T, C> {
+        CopyReceiver {
+            shared: self.shared.clone(),
+            last_seen_version: self.shared.read().1,
+        }
+    }
+}
+
+impl<T: Copy, C: RawCondvar> CopyReceiver<T, C> {
+    /// Get a copy of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        self.shared.get(&mut self.last_seen_version)
+    }
+
+    /// Get a copy of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        self.shared.get_if_new(&mut self.last_seen_version)
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.sequence.load(Ordering::Acquire) as u64 != self.last_seen_version
+    }
+
+    /// Wait for a new value by polling the channel, calling `idle` whenever
+    /// there is nothing new.
+    ///
+    /// See [`WatchReceiver::wait_with`](crate::WatchReceiver::wait_with).
+    pub fn wait_with<F>(&mut self, mut idle: F) -> T
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Some(value) = self.get_if_new() {
+                return value;
+            }
+            idle();
+        }
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Copy, C: RawCondvar> CopyReceiver<T, C> {
+    /// This method waits until a new value becomes available and return a copy
+    /// of it.
+    ///
+    /// If every sender has been dropped, this waits forever. Use [`recv`] to
+    /// detect that case.
+    ///
+    /// [`recv`]: CopyReceiver::recv
+    pub fn wait(&mut self) -> T {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(park_while::<C, _>(state, |state| state.version == seen));
+
+        self.get()
+    }
+
+    /// Like [`wait`], but fails once every sender has been dropped.
+    ///
+    /// [`wait`]: CopyReceiver::wait
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        let state = park_while::<C, _>(state, |state| state.version == seen && state.senders > 0);
+        if state.version == seen {
+            return Err(RecvError);
+        }
+        drop(state);
+
+        Ok(self.get())
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Copy, C: RawCondvarTimeout> CopyReceiver<T, C> {
+    /// This method waits until a new value becomes available and return a copy
+    /// of it, timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
+        let seen = self.last_seen_version;
+        let deadline = Deadline::after(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) =
+            park_while_until::<C, _>(state, deadline, |state| state.version == seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.get())
+    }
+
+    /// Like [`wait_timeout`], but fails once every sender has been dropped.
+    ///
+    /// [`wait_timeout`]: CopyReceiver::wait_timeout
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        let seen = self.last_seen_version;
+        let deadline = Deadline::after(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) = park_while_until::<C, _>(state, deadline, |state| {
+            state.version == seen && state.senders > 0
+        });
+        if !ready {
+            return Err(RecvTimeoutError::Timeout);
+        }
+        if state.version == seen {
+            return Err(RecvTimeoutError::Closed);
+        }
+        drop(state);
+
+        Ok(self.get())
+    }
+}
+
+impl<T, C: RawCondvar> CopyReceiver<T, C> {
+    /// Create a new sender for this channel.
+    ///
+    /// This reopens the channel if every other sender has been dropped.
+    pub fn new_sender(&self) -> CopySender<T, C> {
+        self.shared.state.lock().senders += 1;
+        CopySender {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().senders == 0
+    }
+}
+
+impl<T, C: RawCondvar> Clone for CopySender<T, C> {
+    fn clone(&self) -> CopySender<T, C> {
+        self.shared.state.lock().senders += 1;
+        CopySender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Clone for CopyReceiver<T, C> {
+    fn clone(&self) -> CopyReceiver<T, C> {
+        CopyReceiver {
+            shared: self.shared.clone(),
+            last_seen_version: self.last_seen_version,
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Drop for CopySender<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            state.notify_all();
+        }
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// The sender for a counter created by [`counter`].
+///
+/// The sender can be cloned to obtain multiple senders for the same counter.
+pub struct CounterSender<C: RawCondvar = DefaultCondvar> {
+    inner: WatchSender<u64, C>,
+}
+
+/// The watcher for a counter created by [`counter`].
+///
+/// The watcher can be cloned, and each clone keeps track of the increments
+/// it has seen on its own.
+pub struct CounterWatcher<C: RawCondvar = DefaultCondvar> {
+    inner: WatchReceiver<u64, C>,
+}
+
+/// Creates a new counter, such as for reporting progress.
+///
+/// This is a watch channel of `u64` that never goes down. Its watchers can
+/// wait for it to reach a threshold.
+pub fn counter(initial: u64) -> (CounterSender, CounterWatcher) {
+    new(initial)
+}
+
+pub(crate) fn new<C: RawCondvar>(initial: u64) -> (CounterSender<C>, CounterWatcher<C>) {
+    let (sender, receiver) = builder().initial_seen(true).channel_with(initial);
+    (
+        CounterSender { inner: sender },
+        CounterWatcher { inner: receiver },
+    )
+}
+
+impl<C: RawCondvar> CounterSender<C> {
+    /// Add `n` to the counter, and return the new count.
+    ///
+    /// The count stops at `u64::MAX` rather than wrapping around.
+    pub fn add(&self, n: u64) -> u64 {
+        self.advance(|count| count.saturating_add(n))
+    }
+
+    /// Raise the counter to `n`, and return the new count.
+    ///
+    /// The counter never goes down, so if it is already past `n`, this
+    /// leaves it as it is.
+    pub fn set(&self, n: u64) -> u64 {
+        self.advance(|count| count.max(n))
+    }
+
+    /// Replace the count by `f(count)`, notifying the watchers if that
+    /// raised it.
+    fn advance<F>(&self, f: F) -> u64
+    where
+        F: FnOnce(u64) -> u64,
+    {
+        let shared = &self.inner.shared;
+        let lock = shared.value.write();
+        let count = *lock.value;
+        let next = f(count);
+        if next <= count {
+            shared.unlock_value(lock);
+            return count;
+        }
+        shared.publish(lock, Arc::new(next), self.inner.id);
+        next
+    }
+
+    /// The current count.
+    pub fn get(&self) -> u64 {
+        *self.inner.shared.value.read().value
+    }
+
+    /// Create a new watcher for the counter.
+    ///
+    /// The current count is considered seen by the new watcher.
+    pub fn subscribe(&self) -> CounterWatcher<C> {
+        CounterWatcher {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+impl<C: RawCondvar> CounterWatcher<C> {
+    /// The current count, which is then marked seen.
+    pub fn get(&mut self) -> u64 {
+        self.inner.get()
+    }
+
+    /// Returns `true` if every sender for this counter has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<C: RawCondvar> CounterWatcher<C> {
+    /// Wait until the count is at least `target`, and return it.
+    ///
+    /// Returns at once if it already is. If every sender has been dropped
+    /// before the count reaches `target`, this waits forever.
+    pub fn wait_gte(&mut self, target: u64) -> u64 {
+        let count = self.inner.get();
+        if count >= target {
+            return count;
+        }
+        self.inner
+            .wait_accepted(|&count| (count >= target).then_some(count))
+    }
+
+    /// Wait until the count is higher than when this watcher last saw it,
+    /// and return it.
+    ///
+    /// The new watcher from [`counter`] has seen the initial count.
+    pub fn wait_increment(&mut self) -> u64 {
+        self.inner.wait()
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<C: RawCondvarTimeout> CounterWatcher<C> {
+    /// Like [`wait_gte`], but gives up after `duration`.
+    ///
+    /// [`wait_gte`]: CounterWatcher::wait_gte
+    pub fn wait_gte_timeout(&mut self, target: u64, duration: Duration) -> Option<u64> {
+        let count = self.inner.get();
+        if count >= target {
+            return Some(count);
+        }
+        self.inner
+            .wait_accepted_timeout(duration, |&count| (count >= target).then_some(count))
+    }
+}
+
+impl<C: RawCondvar> Clone for CounterSender<C> {
+    fn clone(&self) -> CounterSender<C> {
+        CounterSender {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: RawCondvar> Clone for CounterWatcher<C> {
+    fn clone(&self) -> CounterWatcher<C> {
+        CounterWatcher {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: RawCondvar> fmt::Debug for CounterSender<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CounterSender").field(&self.inner).finish()
+    }
+}
+
+impl<C: RawCondvar> fmt::Debug for CounterWatcher<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CounterWatcher").field(&self.inner).finish()
+    }
+}
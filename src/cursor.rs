@@ -0,0 +1,95 @@
+use crate::{backend::RawCondvar, Allocator, Shared, WatchReceiver};
+use core::fmt;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// How far a receiver has read a channel, to be saved and installed again
+/// with [`WatchReceiver::set_cursor`].
+///
+/// A cursor is taken by [`WatchReceiver::cursor`]. Besides the version that
+/// the receiver saw last, it holds a random number that the channel drew
+/// when its first cursor was taken, so that a cursor is only accepted by
+/// the channel it came from. A channel created again, such as after a
+/// restart, is a different channel, and rejects the cursors of the old one.
+/// With the `serde` feature, a cursor can be saved and loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cursor {
+    generation: u64,
+    version: u64,
+}
+
+impl Cursor {
+    /// The version that the receiver had seen when the cursor was taken.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+/// Error returned by [`WatchReceiver::set_cursor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorError {
+    /// The cursor was taken from another channel.
+    OtherChannel,
+    /// The cursor is at a version that the channel has not reached, which
+    /// the channel it came from cannot have given out.
+    Ahead,
+}
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CursorError::OtherChannel => f.write_str("cursor is from another watch channel"),
+            CursorError::Ahead => f.write_str("cursor is ahead of the watch channel"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// The number that identifies the channel in its cursors, drawn the
+    /// first time it is needed.
+    fn generation(&self) -> u64 {
+        let mut state = self.state.lock();
+        if state.generation == 0 {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_usize(self as *const Self as usize);
+            // Zero means that no number has been drawn yet.
+            state.generation = hasher.finish().max(1);
+        }
+        state.generation
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Get a cursor at the version that this receiver saw last.
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            generation: self.shared.generation(),
+            version: self.last_seen_version,
+        }
+    }
+
+    /// Move this receiver to a cursor, as if it had just seen the version
+    /// of the cursor.
+    ///
+    /// The values sent after that version count as unseen, and the ones
+    /// before as seen, so [`get_if_new`](WatchReceiver::get_if_new) and
+    /// [`wait`](WatchReceiver::wait) only return a value if one was sent
+    /// after the cursor was taken. Versions wrap around, so a cursor counts
+    /// as ahead of the channel if it is less than `2^63` versions past the
+    /// latest one.
+    pub fn set_cursor(&mut self, cursor: Cursor) -> Result<(), CursorError> {
+        if cursor.generation != self.shared.generation() {
+            return Err(CursorError::OtherChannel);
+        }
+        if (cursor.version.wrapping_sub(self.shared.version()) as i64) > 0 {
+            return Err(CursorError::Ahead);
+        }
+        self.track(|_, seen| *seen = cursor.version);
+        Ok(())
+    }
+}
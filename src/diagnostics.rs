@@ -0,0 +1,50 @@
+//! The diagnostic events of the channels, which go to `tracing` with the
+//! `tracing` feature and to `log` with the `log` feature.
+//!
+//! Every event is written once, with [`diagnostic!`], so that the two
+//! facades get the same events with the same fields.
+
+/// Emit an event with the `watch` target at `$level`, such as `trace` or
+/// `warn`, to each facade that is enabled.
+///
+/// The fields are written as for `tracing`, each followed by a comma, with
+/// `?` in front of those that are recorded with `Debug`. A `log` record
+/// gets them after the message, as `name=value`.
+macro_rules! diagnostic {
+    ($level:ident, $($fields:tt)*) => {
+        #[cfg(feature = "tracing")]
+        diagnostic!(@tracing $level [] $($fields)*);
+        #[cfg(feature = "log")]
+        diagnostic!(@log $level [] [] $($fields)*);
+    };
+
+    (@tracing $level:ident [$($fields:tt)*] $message:literal) => {
+        tracing::$level!(target: "watch", $($fields)* $message)
+    };
+    (@tracing $level:ident [$($fields:tt)*] $name:ident = ?$value:expr, $($rest:tt)*) => {
+        diagnostic!(@tracing $level [$($fields)* $name = ?$value,] $($rest)*)
+    };
+    (@tracing $level:ident [$($fields:tt)*] $name:ident = $value:expr, $($rest:tt)*) => {
+        diagnostic!(@tracing $level [$($fields)* $name = $value,] $($rest)*)
+    };
+
+    (@log $level:ident [$($format:tt)*] [$($args:tt)*] $message:literal) => {
+        log::$level!(target: "watch", concat!($message $($format)*), $($args)*)
+    };
+    (@log $level:ident [$($format:tt)*] [$($args:tt)*] $name:ident = ?$value:expr, $($rest:tt)*) => {
+        diagnostic!(
+            @log $level
+            [$($format)*, " ", stringify!($name), "={:?}"]
+            [$($args)* $value,]
+            $($rest)*
+        )
+    };
+    (@log $level:ident [$($format:tt)*] [$($args:tt)*] $name:ident = $value:expr, $($rest:tt)*) => {
+        diagnostic!(
+            @log $level
+            [$($format)*, " ", stringify!($name), "={}"]
+            [$($args)* $value,]
+            $($rest)*
+        )
+    };
+}
@@ -0,0 +1,74 @@
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    Allocator, Global, WatchReceiver,
+};
+use alloc::sync::Arc;
+
+/// A receiver that keeps returning the same value until it is told to
+/// move on, such as once per frame of a render loop.
+///
+/// This is created by [`WatchReceiver::into_epochal`]. Every read between
+/// two calls to [`advance`] returns the same value, even if new values are
+/// sent in between, and reading never touches the channel. The value is an
+/// `Arc` handle as returned by [`WatchReceiver::get_shared`], so pinning it
+/// does not clone it.
+///
+/// [`advance`]: EpochWatchReceiver::advance
+pub struct EpochWatchReceiver<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    receiver: WatchReceiver<T, C, A>,
+    pinned: Arc<T>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Turn this receiver into one that pins the latest value until it is
+    /// advanced.
+    ///
+    /// This pins the latest value, waiting for the lock if needed.
+    pub fn into_epochal(mut self) -> EpochWatchReceiver<T, C, A> {
+        let pinned = self.get_shared();
+        EpochWatchReceiver {
+            receiver: self,
+            pinned,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> EpochWatchReceiver<T, C, A> {
+    /// Get the pinned value.
+    pub fn get(&self) -> &T {
+        &self.pinned
+    }
+
+    /// Get a handle to the pinned value.
+    pub fn get_shared(&self) -> Arc<T> {
+        self.pinned.clone()
+    }
+
+    /// Pin the latest value, and return `true` if it is newer than the one
+    /// that was pinned.
+    ///
+    /// Values sent in between are skipped. Replacing the pinned value drops
+    /// the old one here if no other handle holds it.
+    pub fn advance(&mut self) -> bool {
+        match self.receiver.get_if_new_shared() {
+            Some(value) => {
+                self.pinned = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if a value newer than the pinned one has been sent,
+    /// without pinning it.
+    pub fn has_newer(&self) -> bool {
+        self.receiver.has_changed()
+    }
+
+    /// Get back the receiver, dropping the pinned value.
+    ///
+    /// The pinned value counts as seen by the receiver.
+    pub fn into_inner(self) -> WatchReceiver<T, C, A> {
+        self.receiver
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::RecvError;
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// The sender for a channel created by [`event`].
+///
+/// The sender can be cloned to obtain multiple senders for the same channel.
+pub struct EventSender<C: RawCondvar = DefaultCondvar> {
+    inner: WatchSender<(), C>,
+}
+
+/// The listener for a channel created by [`event`].
+///
+/// Notifications coalesce: a listener that was notified several times since
+/// it last looked is woken once, and [`notified_count`] tells how many
+/// notifications that covered. The listener can be cloned, and each clone
+/// is told about the same notifications.
+///
+/// [`notified_count`]: EventListener::notified_count
+pub struct EventListener<C: RawCondvar = DefaultCondvar> {
+    inner: WatchReceiver<(), C>,
+}
+
+/// Creates a new channel that carries notifications without a value.
+///
+/// This is a watch channel of `()` whose listener starts without a pending
+/// notification. Notifying bumps the version of the channel without
+/// allocating.
+pub fn event() -> (EventSender, EventListener) {
+    new()
+}
+
+pub(crate) fn new<C: RawCondvar>() -> (EventSender<C>, EventListener<C>) {
+    let (sender, receiver) = builder().initial_seen(true).channel_with(());
+    (
+        EventSender { inner: sender },
+        EventListener { inner: receiver },
+    )
+}
+
+impl<C: RawCondvar> EventSender<C> {
+    /// Notify every listener, waking those that are waiting.
+    pub fn notify(&self) {
+        self.inner.shared.touch();
+    }
+
+    /// Create a new listener for the channel.
+    ///
+    /// Any notifications made before this method was called are considered
+    /// seen by the new listener.
+    pub fn subscribe(&self) -> EventListener<C> {
+        EventListener {
+            inner: self.inner.subscribe(),
+        }
+    }
+
+    /// The number of threads that are blocked waiting for a notification.
+    ///
+    /// See [`WatchSender::waiting_receivers`].
+    pub fn waiting_listeners(&self) -> usize {
+        self.inner.waiting_receivers()
+    }
+}
+
+impl<C: RawCondvar> EventListener<C> {
+    /// Returns `true` if there was a notification that this listener has not
+    /// seen, and marks it seen.
+    pub fn poll(&mut self) -> bool {
+        self.notified_count() > 0
+    }
+
+    /// The number of notifications since this listener last saw one, which
+    /// are then marked seen.
+    pub fn notified_count(&mut self) -> u64 {
+        self.inner.track(|shared, seen| {
+            let version = shared.version();
+            let count = version.wrapping_sub(*seen);
+            *seen = version;
+            count
+        })
+    }
+
+    /// Returns `true` if there was a notification that this listener has not
+    /// seen, without marking it seen.
+    pub fn has_changed(&self) -> bool {
+        self.inner.has_changed()
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<C: RawCondvar> EventListener<C> {
+    /// Wait until there is a notification that this listener has not seen,
+    /// and mark it seen.
+    ///
+    /// Returns at once if there already is one. If every sender has been
+    /// dropped, this waits forever. Use [`recv`] to detect that case.
+    ///
+    /// [`recv`]: EventListener::recv
+    pub fn wait(&mut self) {
+        self.inner.wait();
+    }
+
+    /// Like [`wait`], but fails once every sender has been dropped.
+    ///
+    /// [`wait`]: EventListener::wait
+    pub fn recv(&mut self) -> Result<(), RecvError> {
+        self.inner.recv()
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<C: RawCondvarTimeout> EventListener<C> {
+    /// Like [`wait`], but gives up after `duration`.
+    ///
+    /// Returns `true` if there was a notification.
+    ///
+    /// [`wait`]: EventListener::wait
+    pub fn wait_timeout(&mut self, duration: Duration) -> bool {
+        self.inner.wait_timeout(duration).is_some()
+    }
+}
+
+impl<C: RawCondvar> Clone for EventSender<C> {
+    fn clone(&self) -> EventSender<C> {
+        EventSender {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: RawCondvar> Clone for EventListener<C> {
+    fn clone(&self) -> EventListener<C> {
+        EventListener {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: RawCondvar> fmt::Debug for EventSender<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventSender").field(&self.inner).finish()
+    }
+}
+
+impl<C: RawCondvar> fmt::Debug for EventListener<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventListener").field(&self.inner).finish()
+    }
+}
@@ -0,0 +1,329 @@
+//! A C interface for channels of byte buffers.
+//!
+//! Channels are exposed through the opaque [`Sender`] and [`Receiver`]
+//! handles. Every handle returned by these functions is owned by the caller
+//! and must be released exactly once with [`watch_sender_free`] or
+//! [`watch_receiver_free`]. The free functions take a pointer to the handle
+//! and set it to null, so freeing the same variable twice is harmless.
+//! Passing a handle that was freed through a different variable is undefined
+//! behavior.
+//!
+//! Every function returns one of the `WATCH_*` status codes. Negative codes
+//! are errors. Panics never cross the boundary and are reported as
+//! [`WATCH_ERR_PANIC`].
+//!
+//! To call the functions from C, link this crate into a `cdylib` or
+//! `staticlib`.
+use crate::{channel, SharedValue, WatchReceiver, WatchSender};
+use std::{
+    os::raw::c_int,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+    sync::Arc,
+    time::Duration,
+};
+
+/// The call succeeded.
+pub const WATCH_OK: c_int = 0;
+/// There was no new value, or the timeout expired.
+pub const WATCH_EMPTY: c_int = 1;
+/// Every sender has been dropped and no unseen value remains.
+pub const WATCH_CLOSED: c_int = 2;
+/// A required pointer argument was null.
+pub const WATCH_ERR_NULL: c_int = -1;
+/// The output buffer is too small. The required length has been written to
+/// the length argument and the value is still considered unseen.
+pub const WATCH_ERR_BUFFER_TOO_SMALL: c_int = -2;
+/// The call panicked.
+pub const WATCH_ERR_PANIC: c_int = -3;
+
+/// Opaque handle to the sending half of a channel.
+pub struct Sender {
+    inner: WatchSender<Vec<u8>>,
+}
+
+/// Opaque handle to the receiving half of a channel.
+pub struct Receiver {
+    inner: WatchReceiver<Vec<u8>>,
+}
+
+fn guard<F>(f: F) -> c_int
+where
+    F: FnOnce() -> c_int,
+{
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(WATCH_ERR_PANIC)
+}
+
+/// Copy `len` bytes starting at `data` into a vector.
+///
+/// # Safety
+///
+/// `data` must be valid for reading `len` bytes unless `len` is zero.
+unsafe fn to_vec(data: *const u8, len: usize) -> Option<Vec<u8>> {
+    if len == 0 {
+        return Some(Vec::new());
+    }
+    if data.is_null() {
+        return None;
+    }
+    Some(slice::from_raw_parts(data, len).to_vec())
+}
+
+/// Copy the value into the caller's buffer and mark it as seen.
+///
+/// # Safety
+///
+/// `out_len` must be valid for reads and writes, and `out_buf` must be valid
+/// for writing `*out_len` bytes.
+unsafe fn deliver(
+    last_seen_version: &mut u64,
+    (value, version): (Arc<Vec<u8>>, u64),
+    out_buf: *mut u8,
+    out_len: *mut usize,
+) -> c_int {
+    if value.len() > *out_len {
+        *out_len = value.len();
+        return WATCH_ERR_BUFFER_TOO_SMALL;
+    }
+    if !value.is_empty() {
+        ptr::copy_nonoverlapping(value.as_ptr(), out_buf, value.len());
+    }
+    *out_len = value.len();
+    *last_seen_version = version;
+    WATCH_OK
+}
+
+/// Take the value and its version, so that it can be copied without holding
+/// the lock.
+fn latest(value: &SharedValue<Arc<Vec<u8>>>) -> (Arc<Vec<u8>>, u64) {
+    (value.value.clone(), value.version)
+}
+
+/// Create a channel whose initial value is a copy of `len` bytes at `data`.
+///
+/// On success the new handles are written to `sender_out` and `receiver_out`.
+/// The initial value is not considered seen by the receiver.
+///
+/// # Safety
+///
+/// `data` must be valid for reading `len` bytes unless `len` is zero, and the
+/// output pointers must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn watch_channel_new(
+    data: *const u8,
+    len: usize,
+    sender_out: *mut *mut Sender,
+    receiver_out: *mut *mut Receiver,
+) -> c_int {
+    guard(|| {
+        if sender_out.is_null() || receiver_out.is_null() {
+            return WATCH_ERR_NULL;
+        }
+        let value = match to_vec(data, len) {
+            Some(value) => value,
+            None => return WATCH_ERR_NULL,
+        };
+        let (sender, receiver) = channel(value);
+        *sender_out = Box::into_raw(Box::new(Sender { inner: sender }));
+        *receiver_out = Box::into_raw(Box::new(Receiver { inner: receiver }));
+        WATCH_OK
+    })
+}
+
+/// Send a copy of `len` bytes at `data`.
+///
+/// # Safety
+///
+/// `sender` must be a live handle, and `data` must be valid for reading `len`
+/// bytes unless `len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn watch_sender_send(
+    sender: *const Sender,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    guard(|| {
+        if sender.is_null() {
+            return WATCH_ERR_NULL;
+        }
+        let value = match to_vec(data, len) {
+            Some(value) => value,
+            None => return WATCH_ERR_NULL,
+        };
+        (*sender).inner.send(value);
+        WATCH_OK
+    })
+}
+
+/// Create a new sender for the same channel, written to `sender_out`.
+///
+/// # Safety
+///
+/// `sender` must be a live handle and `sender_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn watch_sender_clone(
+    sender: *const Sender,
+    sender_out: *mut *mut Sender,
+) -> c_int {
+    guard(|| {
+        if sender.is_null() || sender_out.is_null() {
+            return WATCH_ERR_NULL;
+        }
+        let inner = (*sender).inner.clone();
+        *sender_out = Box::into_raw(Box::new(Sender { inner }));
+        WATCH_OK
+    })
+}
+
+/// Release the sender that `sender` points to and set it to null.
+///
+/// Does nothing if `*sender` is already null.
+///
+/// # Safety
+///
+/// `sender` must be valid for reads and writes, and `*sender` must be null or
+/// a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn watch_sender_free(sender: *mut *mut Sender) -> c_int {
+    guard(|| {
+        if sender.is_null() {
+            return WATCH_ERR_NULL;
+        }
+        let handle = ptr::replace(sender, ptr::null_mut());
+        if !handle.is_null() {
+            drop(Box::from_raw(handle));
+        }
+        WATCH_OK
+    })
+}
+
+/// Copy the latest value into `out_buf` if this receiver has not seen it.
+///
+/// On input `*out_len` is the capacity of `out_buf`, and on success it is
+/// set to the length of the value. Returns [`WATCH_EMPTY`] if there is no new
+/// value.
+///
+/// # Safety
+///
+/// `receiver` must be a live handle that is not used by another thread at the
+/// same time, `out_len` must be valid for reads and writes, and `out_buf` must
+/// be valid for writing `*out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn watch_receiver_get_if_new(
+    receiver: *mut Receiver,
+    out_buf: *mut u8,
+    out_len: *mut usize,
+) -> c_int {
+    guard(|| {
+        if receiver.is_null() || out_len.is_null() || (out_buf.is_null() && *out_len > 0) {
+            return WATCH_ERR_NULL;
+        }
+        let WatchReceiver {
+            shared,
+            last_seen_version,
+            cursor,
+            ..
+        } = &mut (*receiver).inner;
+        let latest = {
+            let lock = shared.value.read();
+            if lock.version == *last_seen_version {
+                return WATCH_EMPTY;
+            }
+            latest(&lock)
+        };
+        let result = deliver(last_seen_version, latest, out_buf, out_len);
+        cursor.report(*last_seen_version);
+        result
+    })
+}
+
+/// Wait up to `timeout_ms` milliseconds for a value that this receiver has
+/// not seen, and copy it into `out_buf`.
+///
+/// The buffer is used as in [`watch_receiver_get_if_new`]. Returns
+/// [`WATCH_EMPTY`] if the timeout expires and [`WATCH_CLOSED`] once every
+/// sender has been dropped.
+///
+/// # Safety
+///
+/// Same as [`watch_receiver_get_if_new`].
+#[no_mangle]
+pub unsafe extern "C" fn watch_receiver_wait_timeout_ms(
+    receiver: *mut Receiver,
+    timeout_ms: u64,
+    out_buf: *mut u8,
+    out_len: *mut usize,
+) -> c_int {
+    guard(|| {
+        if receiver.is_null() || out_len.is_null() || (out_buf.is_null() && *out_len > 0) {
+            return WATCH_ERR_NULL;
+        }
+        let WatchReceiver {
+            shared,
+            last_seen_version,
+            cursor,
+            ..
+        } = &mut (*receiver).inner;
+        let seen = *last_seen_version;
+        let deadline = shared.deadline(Duration::from_millis(timeout_ms));
+        let state = shared.state.lock();
+        let (state, ready) = shared.wait_while_until(state, deadline, |state| {
+            state.version == seen && state.is_open()
+        });
+        if !ready {
+            return WATCH_EMPTY;
+        }
+        if state.version == seen {
+            return WATCH_CLOSED;
+        }
+        drop(state);
+        let latest = latest(&shared.value.read());
+        let result = deliver(last_seen_version, latest, out_buf, out_len);
+        cursor.report(*last_seen_version);
+        result
+    })
+}
+
+/// Create a new receiver with the same seen state, written to
+/// `receiver_out`.
+///
+/// # Safety
+///
+/// `receiver` must be a live handle and `receiver_out` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn watch_receiver_clone(
+    receiver: *const Receiver,
+    receiver_out: *mut *mut Receiver,
+) -> c_int {
+    guard(|| {
+        if receiver.is_null() || receiver_out.is_null() {
+            return WATCH_ERR_NULL;
+        }
+        let inner = (*receiver).inner.clone();
+        *receiver_out = Box::into_raw(Box::new(Receiver { inner }));
+        WATCH_OK
+    })
+}
+
+/// Release the receiver that `receiver` points to and set it to null.
+///
+/// Does nothing if `*receiver` is already null.
+///
+/// # Safety
+///
+/// `receiver` must be valid for reads and writes, and `*receiver` must be
+/// null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn watch_receiver_free(receiver: *mut *mut Receiver) -> c_int {
+    guard(|| {
+        if receiver.is_null() {
+            return WATCH_ERR_NULL;
+        }
+        let handle = ptr::replace(receiver, ptr::null_mut());
+        if !handle.is_null() {
+            drop(Box::from_raw(handle));
+        }
+        WATCH_OK
+    })
+}
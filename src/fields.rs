@@ -0,0 +1,44 @@
+use crate::WatchSender;
+use alloc::sync::Arc;
+
+/// A struct whose fields each have their own watch channel.
+///
+/// With the `derive` feature, `#[derive(WatchFields)]` implements this for a
+/// struct with named fields, such as `Settings`. It generates two types:
+///
+/// * `SettingsChannels`, with a [`WatchReceiver`](crate::WatchReceiver) for
+///   each field, so that every part of a program can wait for the fields it
+///   cares about.
+/// * `SettingsSenders`, with a `set_<field>` method for each field, and
+///   `set_all`, which takes a whole new `Settings`. They only send the fields
+///   whose value changed, so the fields must be `PartialEq`.
+///
+/// A field marked `#[watch(skip)]` gets no channel, and is dropped by
+/// [`into_channels`](WatchFields::into_channels). A field marked
+/// `#[watch(nested)]` must derive `WatchFields` itself, and each of its
+/// fields gets a channel.
+pub trait WatchFields: Sized {
+    /// The senders for the fields, named after the struct with a `Senders`
+    /// suffix.
+    type Senders;
+    /// The receivers for the fields, named after the struct with a
+    /// `Channels` suffix.
+    type Channels;
+
+    /// Create a channel for every field, starting at the value of that field.
+    ///
+    /// As with [`channel`](crate::channel), the receivers have not seen the
+    /// starting values.
+    fn into_channels(self) -> (Self::Senders, Self::Channels);
+}
+
+/// Send `value` if it differs from the value in the channel.
+pub fn send_if_changed<T: PartialEq>(sender: &WatchSender<T>, value: T) {
+    let shared = &sender.shared;
+    let lock = shared.value.write();
+    if *lock.value == value {
+        shared.unlock_value(lock);
+        return;
+    }
+    shared.publish(lock, Arc::new(value), sender.id);
+}
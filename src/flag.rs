@@ -0,0 +1,179 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// The setter for a flag created by [`flag`].
+///
+/// The setter can be cloned to obtain multiple setters for the same flag.
+pub struct FlagSetter<C: RawCondvar = DefaultCondvar> {
+    inner: WatchSender<bool, C>,
+}
+
+/// The watcher for a flag created by [`flag`].
+///
+/// Unlike a [`WatchReceiver`], the watcher waits for the flag to be in a
+/// given state rather than for it to change, so [`wait_true`] returns at
+/// once if the flag is already set, whether or not the watcher has seen it
+/// being set. The watcher can be cloned.
+///
+/// [`wait_true`]: FlagWatcher::wait_true
+pub struct FlagWatcher<C: RawCondvar = DefaultCondvar> {
+    inner: WatchReceiver<bool, C>,
+}
+
+/// Creates a new flag, such as for telling threads to shut down or that a
+/// service is ready.
+///
+/// This is a watch channel of `bool` that only notifies its watchers when
+/// the flag changes.
+pub fn flag(initial: bool) -> (FlagSetter, FlagWatcher) {
+    new(initial)
+}
+
+pub(crate) fn new<C: RawCondvar>(initial: bool) -> (FlagSetter<C>, FlagWatcher<C>) {
+    let (sender, receiver) = builder().initial_seen(true).channel_with(initial);
+    (
+        FlagSetter { inner: sender },
+        FlagWatcher { inner: receiver },
+    )
+}
+
+impl<C: RawCondvar> FlagSetter<C> {
+    /// Set the flag to `value`.
+    ///
+    /// The watchers are only notified if this changes the flag.
+    pub fn set(&self, value: bool) {
+        let shared = &self.inner.shared;
+        let lock = shared.value.write();
+        if *lock.value == value {
+            shared.unlock_value(lock);
+            return;
+        }
+        shared.publish(lock, Arc::new(value), self.inner.id);
+    }
+
+    /// Returns `true` if the flag is set.
+    pub fn is_set(&self) -> bool {
+        *self.inner.shared.value.read().value
+    }
+
+    /// Create a new watcher for the flag.
+    pub fn subscribe(&self) -> FlagWatcher<C> {
+        FlagWatcher {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+impl<C: RawCondvar> FlagWatcher<C> {
+    /// Returns `true` if the flag is set.
+    pub fn is_set(&self) -> bool {
+        *self.inner.shared.value.read().value
+    }
+
+    /// Returns `true` if every setter for this flag has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<C: RawCondvar> FlagWatcher<C> {
+    /// Wait until the flag is set.
+    ///
+    /// Returns at once if it already is. If the flag is set and cleared again
+    /// before the watcher wakes up, the watcher may keep waiting. If every
+    /// setter has been dropped while the flag is not set, this waits forever.
+    pub fn wait_true(&mut self) {
+        self.wait_for(true);
+    }
+
+    /// Wait until the flag is not set.
+    ///
+    /// See [`wait_true`](FlagWatcher::wait_true).
+    pub fn wait_false(&mut self) {
+        self.wait_for(false);
+    }
+
+    fn wait_for(&mut self, value: bool) {
+        // Reading the flag marks it seen, so any change after this wakes the
+        // wait below.
+        if self.inner.get() != value {
+            self.inner
+                .wait_accepted(|flag| (*flag == value).then_some(()));
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<C: RawCondvarTimeout> FlagWatcher<C> {
+    /// Like [`wait_true`], but gives up after `duration`.
+    ///
+    /// Returns `true` if the flag was set.
+    ///
+    /// [`wait_true`]: FlagWatcher::wait_true
+    pub fn wait_true_timeout(&mut self, duration: Duration) -> bool {
+        self.wait_for_timeout(true, duration)
+    }
+
+    /// Like [`wait_false`], but gives up after `duration`.
+    ///
+    /// Returns `true` if the flag was not set.
+    ///
+    /// [`wait_false`]: FlagWatcher::wait_false
+    pub fn wait_false_timeout(&mut self, duration: Duration) -> bool {
+        self.wait_for_timeout(false, duration)
+    }
+
+    fn wait_for_timeout(&mut self, value: bool, duration: Duration) -> bool {
+        self.inner.get() == value
+            || self
+                .inner
+                .wait_accepted_timeout(duration, |flag| (*flag == value).then_some(()))
+                .is_some()
+    }
+}
+
+impl<C: RawCondvar> Clone for FlagSetter<C> {
+    fn clone(&self) -> FlagSetter<C> {
+        FlagSetter {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: RawCondvar> Clone for FlagWatcher<C> {
+    fn clone(&self) -> FlagWatcher<C> {
+        FlagWatcher {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: RawCondvar> fmt::Debug for FlagSetter<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FlagSetter").field(&self.inner).finish()
+    }
+}
+
+impl<C: RawCondvar> fmt::Debug for FlagWatcher<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FlagWatcher").field(&self.inner).finish()
+    }
+}
@@ -0,0 +1,206 @@
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::RecvError;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{backend::RawCondvarTimeout, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// The sender for a folding channel created by [`folding_channel`].
+///
+/// The sender can be cloned, and the deltas sent by every clone are folded
+/// into the same value.
+pub struct FoldSender<T, D, C: RawCondvar = DefaultCondvar> {
+    inner: WatchSender<T, C>,
+    fold: Arc<Fold<T, D>>,
+}
+
+/// How a [`FoldSender`] folds a delta into the value.
+type Fold<T, D> = dyn Fn(&mut T, D) + Send + Sync;
+
+/// The receiver for a folding channel created by [`folding_channel`].
+///
+/// Taking the value resets it, so there is only one receiver, and it
+/// cannot be cloned.
+pub struct FoldReceiver<T, C: RawCondvar = DefaultCondvar> {
+    inner: WatchReceiver<T, C>,
+}
+
+/// Creates a new channel that folds the deltas sent on it into a value,
+/// such as for counting bytes or events faster than they are read.
+///
+/// Each delta is folded into the value with `fold` while it is locked, so
+/// no delta is lost however many senders there are. The receiver takes the
+/// value, which resets it, and can wait for any delta sent after the last
+/// time it did.
+pub fn folding_channel<T, D, F>(initial: T, fold: F) -> (FoldSender<T, D>, FoldReceiver<T>)
+where
+    F: Fn(&mut T, D) + Send + Sync + 'static,
+{
+    new(initial, fold)
+}
+
+pub(crate) fn new<T, D, C, F>(initial: T, fold: F) -> (FoldSender<T, D, C>, FoldReceiver<T, C>)
+where
+    C: RawCondvar,
+    F: Fn(&mut T, D) + Send + Sync + 'static,
+{
+    let (sender, receiver) = builder().initial_seen(true).channel_with(initial);
+    (
+        FoldSender {
+            inner: sender,
+            fold: Arc::new(fold),
+        },
+        FoldReceiver { inner: receiver },
+    )
+}
+
+impl<T: Clone, D, C: RawCondvar> FoldSender<T, D, C> {
+    /// Fold `delta` into the value, and notify the receiver.
+    ///
+    /// This runs `fold` like [`WatchSender::update`] runs its closure, so
+    /// `fold` must not use the same channel.
+    pub fn send(&self, delta: D) {
+        self.inner.update(|value| (self.fold)(value, delta));
+    }
+}
+
+impl<T, D, C: RawCondvar> FoldSender<T, D, C> {
+    /// Returns `true` if the receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.receiver_count() == 0
+    }
+}
+
+impl<T: Clone, C: RawCondvar> FoldReceiver<T, C> {
+    /// Get a clone of the value folded so far, without resetting it.
+    pub fn get(&self) -> T {
+        let value = self.inner.shared.value.read().value.clone();
+        T::clone(&value)
+    }
+
+    /// Take the value folded so far, and reset it to the default.
+    pub fn take(&mut self) -> T
+    where
+        T: Default,
+    {
+        self.take_with(T::default)
+    }
+
+    /// Take the value folded so far, and reset it to the result of `reset`.
+    ///
+    /// The value is locked while `reset` runs, so it must not use the same
+    /// channel.
+    pub fn take_with<F>(&mut self, reset: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.inner.track(|shared, seen| {
+            let mut lock = shared.value.write();
+            let taken = {
+                let _scope = shared.value.enter("take_with");
+                core::mem::replace(Arc::make_mut(&mut lock.value), reset())
+            };
+            // The reset keeps the version: the receiver is the only one that
+            // reads the value, and the deltas it has not seen are the ones
+            // sent after this.
+            *seen = lock.version;
+            shared.unlock_value(lock);
+            taken
+        })
+    }
+}
+
+impl<T, C: RawCondvar> FoldReceiver<T, C> {
+    /// Returns `true` if a delta has been sent since the value was last
+    /// taken.
+    pub fn has_changed(&self) -> bool {
+        self.inner.has_changed()
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone + Default, C: RawCondvar> FoldReceiver<T, C> {
+    /// Wait until a delta has been sent since the value was last taken, and
+    /// then take it.
+    ///
+    /// Returns at once if one has. Fails if every sender has been dropped
+    /// without sending one.
+    pub fn wait_take(&mut self) -> Result<T, RecvError> {
+        let seen = self.inner.last_seen_version;
+        let shared = &self.inner.shared;
+        let state = shared.state.lock();
+        let state = shared.wait_while(state, |state| state.version == seen && state.is_open());
+        if state.version == seen {
+            return Err(RecvError);
+        }
+        drop(state);
+
+        Ok(self.take())
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone + Default, C: RawCondvarTimeout> FoldReceiver<T, C> {
+    /// Like [`wait_take`], but gives up after `duration`.
+    ///
+    /// [`wait_take`]: FoldReceiver::wait_take
+    pub fn wait_take_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        let seen = self.inner.last_seen_version;
+        let shared = &self.inner.shared;
+        let deadline = shared.deadline(duration);
+        let state = shared.state.lock();
+        let (state, ready) = shared.wait_while_until(state, deadline, |state| {
+            state.version == seen && state.is_open()
+        });
+        if !ready {
+            return Err(RecvTimeoutError::Timeout);
+        }
+        if state.version == seen {
+            return Err(RecvTimeoutError::Closed);
+        }
+        drop(state);
+
+        Ok(self.take())
+    }
+}
+
+impl<T, D, C: RawCondvar> Clone for FoldSender<T, D, C> {
+    fn clone(&self) -> FoldSender<T, D, C> {
+        FoldSender {
+            inner: self.inner.clone(),
+            fold: self.fold.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, D, C: RawCondvar> fmt::Debug for FoldSender<T, D, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FoldSender").field(&self.inner).finish()
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar> fmt::Debug for FoldReceiver<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FoldReceiver").field(&self.inner).finish()
+    }
+}
@@ -0,0 +1,337 @@
+use crate::{backend::RawCondvar, Allocator, Global, RecvError, WatchReceiver, WatchSender};
+#[cfg(all(feature = "timer", not(target_family = "wasm")))]
+use crate::{timer::Sleep, RecvTimeoutError};
+use alloc::vec::Vec;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+#[cfg(all(feature = "timer", not(target_family = "wasm")))]
+use std::time::{Duration, Instant};
+
+/// The wakers of the tasks waiting for a channel to change.
+///
+/// Every pending future owns a slot, which it keeps until it is dropped, so
+/// the storage only grows with the number of futures that exist at once.
+pub(crate) struct WakerSet {
+    slots: Vec<Option<Waker>>,
+    free: Vec<usize>,
+}
+
+impl WakerSet {
+    pub(crate) const fn new() -> Self {
+        WakerSet {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub(crate) fn register(&mut self, slot: &mut Option<usize>, waker: &Waker) {
+        let index = match *slot {
+            Some(index) => index,
+            None => {
+                let index = match self.free.pop() {
+                    Some(index) => index,
+                    None => {
+                        self.slots.push(None);
+                        self.slots.len() - 1
+                    }
+                };
+                *slot = Some(index);
+                index
+            }
+        };
+        match &mut self.slots[index] {
+            Some(old) if old.will_wake(waker) => {}
+            entry => *entry = Some(waker.clone()),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, slot: usize) {
+        self.slots[slot] = None;
+        self.free.push(slot);
+    }
+
+    /// Wake every registered task.
+    ///
+    /// This is called with the channel locked so that sending never needs to
+    /// allocate, which matters when sending from an interrupt handler.
+    pub(crate) fn wake_all(&mut self) {
+        for waker in self.slots.iter_mut().filter_map(Option::take) {
+            waker.wake();
+        }
+    }
+}
+
+/// Future returned by [`WatchReceiver::changed`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Changed<'a, T, C: RawCondvar = crate::backend::DefaultCondvar, A: Allocator = Global> {
+    receiver: &'a mut WatchReceiver<T, C, A>,
+    slot: Option<usize>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Wait for a value that this receiver has not seen and return a clone of
+    /// it.
+    ///
+    /// This is the async version of [`recv`]. It does not need std or an
+    /// executor with timers, and senders wake the task directly, including
+    /// when sending from an interrupt handler.
+    ///
+    /// [`recv`]: WatchReceiver::recv
+    pub fn changed(&mut self) -> Changed<'_, T, C, A> {
+        Changed {
+            receiver: self,
+            slot: None,
+        }
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> Future for Changed<'_, T, C, A> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        {
+            let mut state = this.receiver.shared.state.lock();
+
+            if state.version == this.receiver.last_seen_version && state.is_open() {
+                state.wakers.register(&mut this.slot, cx.waker());
+                return Poll::Pending;
+            }
+
+            if let Some(slot) = this.slot.take() {
+                state.wakers.remove(slot);
+            }
+            if state.version == this.receiver.last_seen_version {
+                return Poll::Ready(Err(RecvError));
+            }
+        }
+        Poll::Ready(Ok(this.receiver.get()))
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Drop for Changed<'_, T, C, A> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.receiver.shared.state.lock().wakers.remove(slot);
+        }
+    }
+}
+
+/// Future returned by [`WatchReceiver::changed_timeout`].
+#[cfg(all(feature = "timer", not(target_family = "wasm")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ChangedTimeout<
+    'a,
+    T,
+    C: RawCondvar = crate::backend::DefaultCondvar,
+    A: Allocator = Global,
+> {
+    changed: Changed<'a, T, C, A>,
+    deadline: Instant,
+    /// Started on the first poll that has to wait.
+    sleep: Option<Sleep>,
+}
+
+#[cfg(all(feature = "timer", not(target_family = "wasm")))]
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Like [`changed`](WatchReceiver::changed), but gives up once
+    /// `duration` has passed.
+    ///
+    /// The timeout is kept by the timer thread of
+    /// [`WatchSender::send_after`], so this works on any executor. A value
+    /// that is available when the timeout fires is still returned, and
+    /// nothing is marked seen if this times out or is dropped.
+    pub fn changed_timeout(&mut self, duration: Duration) -> ChangedTimeout<'_, T, C, A> {
+        ChangedTimeout {
+            changed: self.changed(),
+            deadline: Instant::now() + duration,
+            sleep: None,
+        }
+    }
+}
+
+#[cfg(all(feature = "timer", not(target_family = "wasm")))]
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> Future for ChangedTimeout<'_, T, C, A> {
+    type Output = Result<T, RecvTimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        // The value is checked first, so that it wins over the timeout.
+        match Pin::new(&mut this.changed).poll(cx) {
+            Poll::Ready(Ok(value)) => return Poll::Ready(Ok(value)),
+            Poll::Ready(Err(RecvError)) => return Poll::Ready(Err(RecvTimeoutError::Closed)),
+            Poll::Pending => {}
+        }
+        let deadline = this.deadline;
+        let sleep = this.sleep.get_or_insert_with(|| Sleep::until(deadline));
+        if Instant::now() < deadline && !sleep.poll_fired(cx) {
+            return Poll::Pending;
+        }
+
+        if let Some(slot) = this.changed.slot.take() {
+            this.changed
+                .receiver
+                .shared
+                .state
+                .lock()
+                .wakers
+                .remove(slot);
+        }
+        Poll::Ready(Err(RecvTimeoutError::Timeout))
+    }
+}
+
+/// Future returned by [`WatchReceiver::wait_for`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitFor<'a, T, F, C: RawCondvar = crate::backend::DefaultCondvar, A: Allocator = Global>
+{
+    receiver: &'a mut WatchReceiver<T, C, A>,
+    condition: F,
+    slot: Option<usize>,
+    /// Set until the latest value has been checked, whether or not it was
+    /// seen before.
+    first: bool,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Wait until the value of the channel satisfies `condition`, and return
+    /// a clone of it.
+    ///
+    /// The latest value is checked first, even if this receiver has seen it,
+    /// so this completes at once if it already satisfies `condition`.
+    /// Otherwise every new value is checked as it arrives, and marked seen.
+    /// Values that are replaced before the task runs are skipped. Fails once
+    /// every sender has been dropped and the last value did not satisfy
+    /// `condition`.
+    ///
+    /// Dropping the future leaves the receiver as it was after the last
+    /// value that was checked.
+    pub fn wait_for<F>(&mut self, condition: F) -> WaitFor<'_, T, F, C, A>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        WaitFor {
+            receiver: self,
+            condition,
+            slot: None,
+            first: true,
+        }
+    }
+}
+
+// The future does not rely on being pinned, so it may move between polls.
+impl<T, F, C: RawCondvar, A: Allocator> Unpin for WaitFor<'_, T, F, C, A> {}
+
+impl<T, F, C, A> Future for WaitFor<'_, T, F, C, A>
+where
+    T: Clone,
+    F: FnMut(&T) -> bool,
+    C: RawCondvar,
+    A: Allocator + Clone,
+{
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            {
+                let mut state = this.receiver.shared.state.lock();
+                let seen = state.version == this.receiver.last_seen_version;
+
+                if seen && !this.first {
+                    if state.is_open() {
+                        state.wakers.register(&mut this.slot, cx.waker());
+                        return Poll::Pending;
+                    }
+                    if let Some(slot) = this.slot.take() {
+                        state.wakers.remove(slot);
+                    }
+                    return Poll::Ready(Err(RecvError));
+                }
+            }
+            this.first = false;
+
+            // The condition runs without the channel locked, as in `wait`.
+            let value = this.receiver.get_shared();
+            if (this.condition)(&value) {
+                if let Some(slot) = this.slot.take() {
+                    this.receiver.shared.state.lock().wakers.remove(slot);
+                }
+                return Poll::Ready(Ok(T::clone(&value)));
+            }
+        }
+    }
+}
+
+impl<T, F, C: RawCondvar, A: Allocator> Drop for WaitFor<'_, T, F, C, A> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.receiver.shared.state.lock().wakers.remove(slot);
+        }
+    }
+}
+
+/// Future returned by [`WatchSender::closed`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Closed<'a, T, C: RawCondvar = crate::backend::DefaultCondvar, A: Allocator = Global> {
+    sender: &'a WatchSender<T, C, A>,
+    slot: Option<usize>,
+    /// The number of times the last receiver was dropped when this was
+    /// created.
+    closings: u64,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Wait until every receiver of the channel has been dropped.
+    ///
+    /// This completes at once if there are no receivers. A receiver created
+    /// while this is pending, such as by [`subscribe`], keeps it pending
+    /// until that receiver is dropped too. Readers created by
+    /// [`reader`](WatchSender::reader) are not counted, as in
+    /// [`receiver_count`](WatchSender::receiver_count).
+    ///
+    /// Once the last receiver has been dropped after this was called, the
+    /// future completes, even if the channel was reopened by a new receiver
+    /// before it was polled again. A future created after the channel was
+    /// reopened waits for the new receivers to be dropped.
+    ///
+    /// [`subscribe`]: WatchSender::subscribe
+    pub fn closed(&self) -> Closed<'_, T, C, A> {
+        let closings = self.shared.state.lock().closings;
+        Closed {
+            sender: self,
+            slot: None,
+            closings,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Future for Closed<'_, T, C, A> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let mut state = this.sender.shared.state.lock();
+
+        if state.receivers > 0 && state.closings == this.closings {
+            state.closed_wakers.register(&mut this.slot, cx.waker());
+            return Poll::Pending;
+        }
+
+        if let Some(slot) = this.slot.take() {
+            state.closed_wakers.remove(slot);
+        }
+        Poll::Ready(())
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Drop for Closed<'_, T, C, A> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.sender.shared.state.lock().closed_wakers.remove(slot);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    allocator, backend::RawCondvar, channel, RecvTimeoutError, Shared, WatchReceiver, WatchSender,
+};
+use alloc::{sync::Weak, vec::Vec};
+use core::fmt;
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// Channels that are shut down together, such as those of a session.
+///
+/// The channels created by [`Group::channel`] are closed at once by
+/// [`close_all`](Group::close_all), and
+/// [`wait_all_closed`](Group::wait_all_closed) waits for their receivers
+/// to notice and go away. The group does not keep its channels alive: a
+/// channel leaves it once every sender and receiver has been dropped.
+#[derive(Default)]
+pub struct Group {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    members: Vec<Member>,
+    closed: bool,
+}
+
+struct Member {
+    channel: Weak<dyn Close>,
+    /// The receiver count of the channel, see
+    /// [`WatchSender::subscriber_count_watch`].
+    receivers: WatchReceiver<usize>,
+}
+
+/// What the group does to a channel without knowing its type.
+trait Close: Send + Sync {
+    fn close(&self);
+}
+
+impl<T, C: RawCondvar> Close for Shared<T, C>
+where
+    Shared<T, C>: Send + Sync,
+{
+    fn close(&self) {
+        Shared::close(self);
+    }
+}
+
+impl Group {
+    /// Creates an empty group.
+    pub fn new() -> Group {
+        Group::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        if inner.members.len() == inner.members.capacity() {
+            inner
+                .members
+                .retain(|member| member.channel.strong_count() > 0);
+        }
+        inner
+    }
+
+    /// Creates a new watch channel in this group.
+    ///
+    /// Once [`close_all`](Group::close_all) has been called, the channel is
+    /// closed right away, so that a channel created while a session shuts
+    /// down does not keep its receivers waiting.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn channel<T>(&self, value: T) -> (WatchSender<T>, WatchReceiver<T>)
+    where
+        T: Send + Sync + 'static,
+    {
+        let (sender, receiver) = channel(value);
+        let receivers = sender.subscriber_count_watch();
+        let mut inner = self.lock();
+        if inner.closed {
+            sender.close();
+        }
+        inner.members.push(Member {
+            channel: allocator::downgrade(&sender.shared) as Weak<dyn Close>,
+            receivers,
+        });
+        (sender, receiver)
+    }
+
+    /// Close every live channel of the group, as with
+    /// [`WatchSender::close`], and every channel created in it from now on.
+    pub fn close_all(&self) {
+        // The channels are released after the group is unlocked, as this
+        // may hold the last handle to one, whose value could use the group
+        // when dropped.
+        let mut channels = Vec::new();
+        {
+            let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+            inner.closed = true;
+            inner
+                .members
+                .retain(|member| match member.channel.upgrade() {
+                    Some(channel) => {
+                        channels.push(channel);
+                        true
+                    }
+                    None => false,
+                });
+        }
+        for channel in &channels {
+            channel.close();
+        }
+    }
+
+    /// Wait until no channel of the group has a receiver left, and return
+    /// `false` if some still have one after `timeout`.
+    ///
+    /// Readers are not receivers, and are not waited for, as in
+    /// [`WatchSender::is_closed`]. Channels that have been dropped count as
+    /// done.
+    pub fn wait_all_closed(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        // The counts are waited for with the group unlocked, so that it can
+        // still be used meanwhile, such as by a receiver that creates a
+        // channel before it goes away.
+        let counts: Vec<_> = self
+            .lock()
+            .members
+            .iter()
+            .map(|member| member.receivers.clone())
+            .collect();
+        for mut count in counts {
+            while count.get() > 0 {
+                let left = deadline.saturating_duration_since(Instant::now());
+                match count.recv_timeout(left) {
+                    Ok(_) => {}
+                    // The channel is gone, and its receivers with it.
+                    Err(RecvTimeoutError::Closed) => break,
+                    Err(RecvTimeoutError::Timeout) => return false,
+                }
+            }
+        }
+        true
+    }
+}
+
+impl fmt::Debug for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Group").finish_non_exhaustive()
+    }
+}
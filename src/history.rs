@@ -0,0 +1,63 @@
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+
+/// The latest values of a channel, kept for receivers that want to see the
+/// values they missed.
+///
+/// See [`ChannelBuilder::history`](crate::ChannelBuilder::history).
+pub(crate) struct History<T> {
+    /// The values together with their versions, oldest first. The newest is
+    /// the current value of the channel.
+    values: VecDeque<(u64, Arc<T>)>,
+    capacity: usize,
+}
+
+impl<T> History<T> {
+    pub(crate) fn new(capacity: usize, version: u64, value: Arc<T>) -> History<T> {
+        let mut values = VecDeque::with_capacity(capacity);
+        values.push_back((version, value));
+        History { values, capacity }
+    }
+
+    /// Record a new current value, dropping the oldest one if the history is
+    /// full.
+    ///
+    /// The dropped value is returned, so that it can be destroyed after the
+    /// locks are released.
+    pub(crate) fn push(&mut self, version: u64, value: Arc<T>) -> Option<Arc<T>> {
+        let evicted = if self.values.len() == self.capacity {
+            self.values.pop_front().map(|(_, value)| value)
+        } else {
+            None
+        };
+        self.values.push_back((version, value));
+        evicted
+    }
+
+    /// How many of the kept values are `value`.
+    pub(crate) fn holds(&self, value: &Arc<T>) -> usize {
+        self.values
+            .iter()
+            .filter(|(_, kept)| Arc::ptr_eq(kept, value))
+            .count()
+    }
+
+    /// Get the values newer than `seen`, oldest first, and mark them seen.
+    ///
+    /// Also returns how many newer values are no longer kept.
+    pub(crate) fn missed(&self, seen: &mut u64) -> (Vec<Arc<T>>, u64) {
+        let latest = match self.values.back() {
+            Some(&(version, _)) => version,
+            None => return (Vec::new(), 0),
+        };
+        let behind = latest.wrapping_sub(*seen);
+        let missed: Vec<Arc<T>> = self
+            .values
+            .iter()
+            .filter(|(version, _)| latest.wrapping_sub(*version) < behind)
+            .map(|(_, value)| value.clone())
+            .collect();
+        *seen = latest;
+        let evicted = behind - missed.len() as u64;
+        (missed, evicted)
+    }
+}
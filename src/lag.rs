@@ -0,0 +1,387 @@
+//! How far the receivers of a channel are behind, see
+//! [`ChannelBuilder::track_lag`](crate::ChannelBuilder::track_lag).
+#[cfg(all(target_has_atomic = "64", feature = "std", not(target_family = "wasm")))]
+use crate::clock::Clock;
+use crate::{backend::RawCondvar, Shared};
+#[cfg(target_has_atomic = "64")]
+use crate::{Allocator, AtomicU64, WatchReceiver, WatchSender};
+#[cfg(target_has_atomic = "64")]
+use alloc::{
+    boxed::Box,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+#[cfg(all(target_has_atomic = "64", feature = "std", not(target_family = "wasm")))]
+use core::convert::TryFrom;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::Ordering;
+#[cfg(target_has_atomic = "64")]
+use lock_api::Mutex;
+#[cfg(all(target_has_atomic = "64", feature = "std", not(target_family = "wasm")))]
+use std::time::Instant;
+
+/// The identity of a receiver within a channel that tracks lag, as
+/// returned by [`WatchReceiver::id`].
+///
+/// Every receiver of such a channel has an id of its own, including clones,
+/// and no two receivers of the same channel ever share one.
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReceiverId(u64);
+
+/// A receiver that fell behind, as passed to the callback of
+/// [`WatchSender::on_lagging`].
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LagReport {
+    /// The receiver that fell behind.
+    pub receiver: ReceiverId,
+    /// The version that the receiver has seen.
+    pub seen: u64,
+    /// The latest version of the channel.
+    pub version: u64,
+}
+
+/// A live receiver of a channel that tracks lag, as listed by
+/// [`WatchSender::receiver_report`].
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReceiverInfo {
+    /// The identity of the receiver.
+    pub id: ReceiverId,
+    /// The version that the receiver has seen.
+    pub seen_version: u64,
+    /// How many versions the receiver is behind the latest value.
+    pub lag: u64,
+    /// When the receiver last read the channel, on the clock of the
+    /// channel, or `None` if it never has.
+    #[cfg(all(feature = "std", not(target_family = "wasm")))]
+    pub last_read_at: Option<Instant>,
+}
+
+/// The versions last seen by the receivers of a channel.
+///
+/// A receiver owns its cursor, so that it can report a read without taking
+/// a lock, and the channel only keeps a weak reference to it. Cursors of
+/// dropped receivers are removed whenever the list is read or grows.
+#[cfg(target_has_atomic = "64")]
+pub(crate) struct Cursors {
+    cursors: Vec<Tracked>,
+    next_id: u64,
+    hook: Option<LagHook>,
+}
+
+/// The channel's side of a cursor.
+#[cfg(target_has_atomic = "64")]
+struct Tracked {
+    position: Weak<Position>,
+    id: ReceiverId,
+    /// Set once the hook was called for the receiver, until it catches up.
+    lagging: bool,
+}
+
+/// What a receiver reports through its cursor.
+#[cfg(target_has_atomic = "64")]
+struct Position {
+    seen: AtomicU64,
+    #[cfg(all(feature = "std", not(target_family = "wasm")))]
+    read: ReadTime,
+}
+
+/// When a receiver last read the channel.
+#[cfg(all(target_has_atomic = "64", feature = "std", not(target_family = "wasm")))]
+struct ReadTime {
+    clock: Clock,
+    since: Instant,
+    /// The nanoseconds from `since` to the last read, plus one, or zero if
+    /// the receiver never read.
+    at: AtomicU64,
+}
+
+#[cfg(all(target_has_atomic = "64", feature = "std", not(target_family = "wasm")))]
+impl ReadTime {
+    fn new(clock: &Clock) -> ReadTime {
+        ReadTime {
+            clock: clock.clone(),
+            since: clock.now(),
+            at: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self) {
+        let nanos = self
+            .clock
+            .now()
+            .saturating_duration_since(self.since)
+            .as_nanos();
+        let at = u64::try_from(nanos).unwrap_or(u64::MAX - 1) + 1;
+        self.at.store(at, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> Option<Instant> {
+        match self.at.load(Ordering::Relaxed) {
+            0 => None,
+            at => Some(self.since + core::time::Duration::from_nanos(at - 1)),
+        }
+    }
+}
+
+/// The callback of [`WatchSender::on_lagging`].
+#[cfg(target_has_atomic = "64")]
+struct LagHook {
+    threshold: u64,
+    f: Arc<dyn Fn(LagReport) + Send + Sync>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Cursors {
+    pub(crate) fn new() -> Cursors {
+        Cursors {
+            cursors: Vec::new(),
+            next_id: 0,
+            hook: None,
+        }
+    }
+
+    fn add(&mut self, position: Position) -> Cursor {
+        if self.cursors.len() == self.cursors.capacity() {
+            self.cursors
+                .retain(|cursor| cursor.position.strong_count() > 0);
+        }
+        let id = ReceiverId(self.next_id);
+        self.next_id += 1;
+        let cursor = Arc::new(position);
+        self.cursors.push(Tracked {
+            position: Arc::downgrade(&cursor),
+            id,
+            lagging: false,
+        });
+        Cursor(Some((cursor, id)))
+    }
+
+    /// The largest number of versions that a live receiver is behind
+    /// `latest`.
+    pub(crate) fn max_lag(&mut self, latest: u64) -> Option<u64> {
+        let mut max = None;
+        self.cursors
+            .retain(|cursor| match cursor.position.upgrade() {
+                Some(position) => {
+                    let lag = latest.wrapping_sub(position.seen.load(Ordering::Relaxed));
+                    max = max.max(Some(lag));
+                    true
+                }
+                None => false,
+            });
+        max
+    }
+
+    /// Mark the receivers that are more than the threshold of the hook
+    /// behind `latest` as lagging, and return the hook with a report for
+    /// each receiver that was not lagging before.
+    fn newly_lagging(&mut self, latest: u64) -> Option<(LagHook, Vec<LagReport>)> {
+        let hook = self.hook.as_ref()?;
+        let threshold = hook.threshold;
+        let mut reports = Vec::new();
+        self.cursors
+            .retain_mut(|cursor| match cursor.position.upgrade() {
+                Some(position) => {
+                    let seen = position.seen.load(Ordering::Relaxed);
+                    let lagging = latest.wrapping_sub(seen) > threshold;
+                    if lagging && !cursor.lagging {
+                        reports.push(LagReport {
+                            receiver: cursor.id,
+                            seen,
+                            version: latest,
+                        });
+                    }
+                    cursor.lagging = lagging;
+                    true
+                }
+                None => false,
+            });
+        if reports.is_empty() {
+            return None;
+        }
+        let hook = LagHook {
+            threshold,
+            f: hook.f.clone(),
+        };
+        Some((hook, reports))
+    }
+
+    /// List the live receivers, oldest first.
+    fn report(&mut self, latest: u64) -> Vec<ReceiverInfo> {
+        let mut infos = Vec::with_capacity(self.cursors.len());
+        self.cursors
+            .retain(|cursor| match cursor.position.upgrade() {
+                Some(position) => {
+                    let seen = position.seen.load(Ordering::Relaxed);
+                    infos.push(ReceiverInfo {
+                        id: cursor.id,
+                        seen_version: seen,
+                        lag: latest.wrapping_sub(seen),
+                        #[cfg(all(feature = "std", not(target_family = "wasm")))]
+                        last_read_at: position.read.get(),
+                    });
+                    true
+                }
+                None => false,
+            });
+        infos
+    }
+}
+
+/// Where a receiver reports the version it has seen, if its channel tracks
+/// lag.
+pub(crate) struct Cursor(#[cfg(target_has_atomic = "64")] Option<(Arc<Position>, ReceiverId)>);
+
+impl Cursor {
+    /// Report that the receiver read the channel, and has now seen `seen`.
+    pub(crate) fn report(&self, seen: u64) {
+        #[cfg(target_has_atomic = "64")]
+        if let Some((cursor, _)) = &self.0 {
+            cursor.seen.store(seen, Ordering::Relaxed);
+            #[cfg(all(feature = "std", not(target_family = "wasm")))]
+            cursor.read.record();
+        }
+        #[cfg(not(target_has_atomic = "64"))]
+        let _ = seen;
+    }
+
+    /// Report that the receiver has seen `seen` without reading the channel.
+    pub(crate) fn skip_to(&self, seen: u64) {
+        #[cfg(target_has_atomic = "64")]
+        if let Some((cursor, _)) = &self.0 {
+            cursor.seen.store(seen, Ordering::Relaxed);
+        }
+        #[cfg(not(target_has_atomic = "64"))]
+        let _ = seen;
+    }
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// Start tracking the versions seen by the receivers.
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn enable_lag_tracking(&mut self) {
+        self.cursors = Some(Box::new(Mutex::new(Cursors::new())));
+    }
+
+    /// Create the cursor of a new receiver that has seen `seen`.
+    pub(crate) fn cursor(&self, seen: u64) -> Cursor {
+        #[cfg(target_has_atomic = "64")]
+        return match &self.cursors {
+            Some(cursors) => cursors.lock().add(Position {
+                seen: AtomicU64::new(seen),
+                #[cfg(all(feature = "std", not(target_family = "wasm")))]
+                read: ReadTime::new(&self.clock),
+            }),
+            None => Cursor(None),
+        };
+        #[cfg(not(target_has_atomic = "64"))]
+        {
+            let _ = seen;
+            Cursor()
+        }
+    }
+
+    /// Call the hook of [`WatchSender::on_lagging`] for the receivers that
+    /// just fell behind.
+    ///
+    /// This must be called without holding the value.
+    pub(crate) fn report_lagging(&self) {
+        #[cfg(target_has_atomic = "64")]
+        if let Some(cursors) = &self.cursors {
+            let latest = self.version();
+            let lagging = cursors.lock().newly_lagging(latest);
+            if let Some((hook, reports)) = lagging {
+                for report in reports {
+                    (hook.f)(report);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// The oldest version that a live receiver has seen.
+    ///
+    /// A receiver counts from the version it was subscribed at until it
+    /// reads a value, and the initial receiver of a channel has not seen its
+    /// starting value. Returns `None` if there are no receivers, or if the
+    /// channel was not created with
+    /// [`ChannelBuilder::track_lag`](crate::ChannelBuilder::track_lag).
+    pub fn min_seen_version(&self) -> Option<u64> {
+        let latest = self.shared.version();
+        self.max_lag().map(|lag| latest.wrapping_sub(lag))
+    }
+
+    /// How many versions the slowest live receiver is behind the latest
+    /// value.
+    ///
+    /// See [`min_seen_version`](WatchSender::min_seen_version).
+    pub fn max_lag(&self) -> Option<u64> {
+        let cursors = self.shared.cursors.as_ref()?;
+        let latest = self.shared.version();
+        cursors.lock().max_lag(latest)
+    }
+
+    /// List every live receiver with the version it has seen, how far it is
+    /// behind, and when it last read the channel.
+    ///
+    /// The receivers are listed in the order they were created. The list is
+    /// taken with the receivers' cursors locked, which neither senders nor
+    /// receivers wait for, except senders that report lagging receivers and
+    /// new receivers. It is empty unless the channel was created with
+    /// [`ChannelBuilder::track_lag`](crate::ChannelBuilder::track_lag).
+    pub fn receiver_report(&self) -> Vec<ReceiverInfo> {
+        match &self.shared.cursors {
+            Some(cursors) => {
+                let latest = self.shared.version();
+                cursors.lock().report(latest)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Call `f` whenever a send finds a live receiver that is more than
+    /// `threshold` versions behind.
+    ///
+    /// A receiver is reported once when it falls behind, and only again once
+    /// a later send finds that it caught up and then fell behind once more,
+    /// so a stalled receiver does not trigger a report for every send. The
+    /// callback runs on the thread that sent, after the value is unlocked.
+    /// It replaces the callback set by an earlier call on any sender of the
+    /// channel. Does nothing unless the channel was created with
+    /// [`ChannelBuilder::track_lag`](crate::ChannelBuilder::track_lag).
+    pub fn on_lagging<F>(&self, threshold: u64, f: F)
+    where
+        F: Fn(LagReport) + Send + Sync + 'static,
+    {
+        if let Some(cursors) = &self.shared.cursors {
+            let mut cursors = cursors.lock();
+            cursors.hook = Some(LagHook {
+                threshold,
+                f: Arc::new(f),
+            });
+            // Receivers that lag already are reported by the next send.
+            for cursor in &mut cursors.cursors {
+                cursor.lagging = false;
+            }
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T, C: RawCondvar, A: Allocator> WatchReceiver<T, C, A> {
+    /// Get the identity of this receiver within its channel, as reported by
+    /// [`WatchSender::on_lagging`].
+    ///
+    /// Returns `None` unless the channel was created with
+    /// [`ChannelBuilder::track_lag`](crate::ChannelBuilder::track_lag).
+    pub fn id(&self) -> Option<ReceiverId> {
+        self.cursor.0.as_ref().map(|(_, id)| *id)
+    }
+}
@@ -0,0 +1,479 @@
+//! A channel that keeps two copies of its value, so that receivers read one
+//! while the senders write the other.
+//!
+//! Every receiver owns an epoch counter, which it makes odd while it reads
+//! and even again afterwards. A send writes the copy that no receiver reads,
+//! points the receivers at it, and then waits until every receiver that was
+//! reading the old copy has left it before making the old copy equal to the
+//! new one. The copies are only ever written while holding `writer`, and the
+//! copy being written is never the one that `active` points at, nor one that
+//! a receiver is still reading.
+//!
+//! The receivers make their counter odd before loading `active`, and the
+//! sender loads the counters after storing it, all with `SeqCst`. So either
+//! the sender sees that a receiver is reading, and waits for it, or that
+//! receiver's load of `active` comes after the store and finds the new copy.
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{backend::RawCondvarTimeout, park_while_until, Deadline, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    CachePadded, SharedState,
+};
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{park_while, RecvError};
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use lock_api::Mutex;
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use std::time::Duration;
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// The sender for a channel created by [`lr_channel`].
+///
+/// The sender can be cloned to obtain multiple senders for the same channel.
+pub struct LrWatchSender<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<LrShared<T, C>>,
+}
+
+/// The receiver for a channel created by [`lr_channel`].
+///
+/// The receiver can be cloned. Each clone will yield a new receiver that
+/// receives the same messages, with its own epoch counter.
+pub struct LrWatchReceiver<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<LrShared<T, C>>,
+    epoch: Arc<Epoch>,
+    last_seen_version: u64,
+}
+
+/// Odd while its receiver reads a copy of the value.
+type Epoch = CachePadded<AtomicUsize>;
+
+struct LrShared<T, C: RawCondvar> {
+    copies: [UnsafeCell<Entry<T>>; 2],
+    /// The index of the copy that receivers read.
+    active: CachePadded<AtomicUsize>,
+    /// Held by the sender that writes the copies.
+    writer: Mutex<C::RawMutex, ()>,
+    /// The epoch counters of every receiver.
+    epochs: Mutex<C::RawMutex, Vec<Arc<Epoch>>>,
+    state: Mutex<C::RawMutex, SharedState>,
+    _condvar: PhantomData<C>,
+}
+
+// SAFETY: The copies are only written by the sender that holds `writer`, and
+// only while no receiver reads them, see the module documentation. Shared
+// references to them are handed out to any thread, and the value is moved
+// to the thread that drops the channel. The remaining fields need the same
+// bounds as they would without the `UnsafeCell`.
+unsafe impl<T: Send + Sync, C: RawCondvar + Sync> Sync for LrShared<T, C> where
+    C::RawMutex: Send + Sync
+{
+}
+// SAFETY: See above.
+unsafe impl<T: Send + Sync, C: RawCondvar + Send> Send for LrShared<T, C> where
+    C::RawMutex: Send + Sync
+{
+}
+
+/// A copy of the value together with its version.
+struct Entry<T> {
+    value: T,
+    version: u64,
+}
+
+/// Creates a new watch channel whose receivers read the value without
+/// waiting, for values read by many threads at once.
+///
+/// The channel keeps two copies of the value, and the receivers read one of
+/// them without taking a lock or touching a reference count. Each receiver
+/// only writes its own counter, and never a cache line that the other
+/// receivers read. In exchange, every send writes both copies, cloning the
+/// new value into the second one, and waits until no receiver is still
+/// reading the copy it replaces, so sends are slower, and a receiver that
+/// holds a read for a long time holds up the senders. Waiting for a new
+/// value still parks the thread.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+pub fn lr_channel<T: Clone>(value: T) -> (LrWatchSender<T>, LrWatchReceiver<T>) {
+    new(value)
+}
+
+pub(crate) fn new<T: Clone, C: RawCondvar>(
+    value: T,
+) -> (LrWatchSender<T, C>, LrWatchReceiver<T, C>) {
+    let epoch = Arc::new(CachePadded(AtomicUsize::new(0)));
+    let shared = Arc::new(LrShared {
+        copies: [
+            UnsafeCell::new(Entry {
+                value: value.clone(),
+                version: 1,
+            }),
+            UnsafeCell::new(Entry { value, version: 1 }),
+        ],
+        active: CachePadded(AtomicUsize::new(0)),
+        writer: Mutex::new(()),
+        epochs: Mutex::new(alloc::vec![epoch.clone()]),
+        state: Mutex::new(SharedState::new(1)),
+        _condvar: PhantomData,
+    });
+    (
+        LrWatchSender {
+            shared: shared.clone(),
+        },
+        LrWatchReceiver {
+            shared,
+            epoch,
+            last_seen_version: 0,
+        },
+    )
+}
+
+impl<T: Clone, C: RawCondvar> LrShared<T, C> {
+    /// Change the copy that receivers do not read with `f`, make it the one
+    /// they read, and then make the other copy equal to it.
+    ///
+    /// If `f` panics, the copy it changed is put back as it was, and the
+    /// receivers never see it.
+    fn write<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        let _writer = self.writer.lock();
+        let active = self.active.load(Ordering::SeqCst);
+        let standby = 1 - active;
+        {
+            // Puts the standby copy back if `f` panics.
+            struct Restore<'a, T: Clone, C: RawCondvar> {
+                shared: &'a LrShared<T, C>,
+                active: usize,
+                done: bool,
+            }
+            impl<T: Clone, C: RawCondvar> Drop for Restore<'_, T, C> {
+                fn drop(&mut self) {
+                    if !self.done {
+                        // SAFETY: The writer lock is held, and no receiver
+                        // reads the standby copy, as below.
+                        unsafe { self.shared.resync(self.active) };
+                    }
+                }
+            }
+            let mut restore = Restore {
+                shared: self,
+                active,
+                done: false,
+            };
+            // SAFETY: The writer lock is held, and every receiver that read
+            // the standby copy left it before the previous write returned.
+            let entry = unsafe { &mut *self.copies[standby].get() };
+            f(&mut entry.value);
+            entry.version = entry.version.wrapping_add(1);
+            restore.done = true;
+        }
+        self.active.store(standby, Ordering::SeqCst);
+        self.wait_for_readers();
+        // SAFETY: The writer lock is held, and every receiver that read the
+        // old copy has left it, while the new ones read the standby copy.
+        unsafe { self.resync(standby) };
+
+        // SAFETY: Only the writer changes the copies, and it holds the lock.
+        let version = unsafe { (*self.copies[standby].get()).version };
+        // Receivers can read the new copy before the version is published
+        // here, so a receiver may have seen a newer version than `state`
+        // holds, and waits until `state` is past the version it has seen.
+        let mut state = self.state.lock();
+        state.version = version;
+        state.waiters.wake(version);
+        state.wake_tasks();
+    }
+
+    /// Make the copy that `source` does not point at equal to the one that
+    /// it does.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the writer lock, and no receiver may be reading
+    /// the other copy.
+    unsafe fn resync(&self, source: usize) {
+        let target = &mut *self.copies[1 - source].get();
+        let source = &*self.copies[source].get();
+        target.value.clone_from(&source.value);
+        target.version = source.version;
+    }
+
+    /// Wait until every receiver that was reading when this was called has
+    /// finished that read.
+    ///
+    /// The receivers that are reading are collected first, and `epochs` is
+    /// not held while waiting for them, so a receiver may be created or
+    /// dropped during a read without waiting for this.
+    fn wait_for_readers(&self) {
+        let reading: Vec<(Arc<Epoch>, usize)> = self
+            .epochs
+            .lock()
+            .iter()
+            .map(|epoch| (epoch.clone(), epoch.load(Ordering::SeqCst)))
+            .filter(|&(_, start)| start % 2 == 1)
+            .collect();
+        for (epoch, start) in reading {
+            let mut spins = 0u32;
+            while epoch.load(Ordering::SeqCst) == start {
+                if spins < 64 {
+                    core::hint::spin_loop();
+                    spins += 1;
+                } else {
+                    std::thread::yield_now();
+                }
+            }
+        }
+    }
+}
+
+impl<T, C: RawCondvar> LrShared<T, C> {
+    /// Run `f` on the copy that receivers read, marking `epoch` as reading
+    /// meanwhile.
+    fn read<R, F>(&self, epoch: &Epoch, f: F) -> R
+    where
+        F: FnOnce(&Entry<T>) -> R,
+    {
+        // Marks the end of the read even if `f` panics.
+        struct Leave<'a>(&'a Epoch);
+        impl Drop for Leave<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Release);
+            }
+        }
+
+        epoch.fetch_add(1, Ordering::SeqCst);
+        let _leave = Leave(epoch);
+        let active = self.active.load(Ordering::SeqCst);
+        // SAFETY: No sender writes the active copy, nor the other one until
+        // this read has ended, see the module documentation.
+        f(unsafe { &*self.copies[active].get() })
+    }
+
+    fn register(&self) -> Arc<Epoch> {
+        let epoch = Arc::new(CachePadded(AtomicUsize::new(0)));
+        self.epochs.lock().push(epoch.clone());
+        epoch
+    }
+}
+
+impl<T: Clone, C: RawCondvar> LrWatchSender<T, C> {
+    /// Send a new message and notify all receivers currently waiting for a
+    /// message.
+    ///
+    /// The value is cloned into the other copy with [`Clone::clone_from`].
+    pub fn send(&self, value: T) {
+        let mut value = Some(value);
+        self.shared.write(|copy| *copy = value.take().unwrap());
+    }
+
+    /// Update the message by a closure and notify all receivers currently
+    /// waiting for a message.
+    ///
+    /// The closure changes one copy, which is then cloned into the other
+    /// with [`Clone::clone_from`]. If `f` panics, the value is left as it
+    /// was. The closure must not send on the same channel, which would
+    /// deadlock.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        self.shared.write(f);
+    }
+}
+
+impl<T, C: RawCondvar> LrWatchSender<T, C> {
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    pub fn subscribe(&self) -> LrWatchReceiver<T, C> {
+        let epoch = self.shared.register();
+        let last_seen_version = self.shared.read(&epoch, |entry| entry.version);
+        LrWatchReceiver {
+            shared: self.shared.clone(),
+            epoch,
+            last_seen_version,
+        }
+    }
+}
+
+impl<T, C: RawCondvar> LrWatchReceiver<T, C> {
+    /// Run `f` on the latest value sent on the channel, and mark it seen.
+    ///
+    /// This never waits for the senders, but a sender waits for `f` to
+    /// return before it finishes sending, so `f` should be short, and must
+    /// not send on the same channel, which would deadlock. It may create and
+    /// drop receivers.
+    pub fn read<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let seen = &mut self.last_seen_version;
+        self.shared.read(&self.epoch, |entry| {
+            *seen = entry.version;
+            f(&entry.value)
+        })
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.read(&self.epoch, |entry| entry.version) != self.last_seen_version
+    }
+
+    /// Create a new sender for this channel.
+    ///
+    /// This reopens the channel if every other sender has been dropped.
+    pub fn new_sender(&self) -> LrWatchSender<T, C> {
+        self.shared.state.lock().senders += 1;
+        LrWatchSender {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().senders == 0
+    }
+}
+
+impl<T: Clone, C: RawCondvar> LrWatchReceiver<T, C> {
+    /// Get a clone of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        self.read(T::clone)
+    }
+
+    /// Get a clone of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        let seen = &mut self.last_seen_version;
+        self.shared.read(&self.epoch, |entry| {
+            if entry.version == *seen {
+                return None;
+            }
+            *seen = entry.version;
+            Some(entry.value.clone())
+        })
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar> LrWatchReceiver<T, C> {
+    /// This method waits until a new value becomes available and return a
+    /// clone of it.
+    ///
+    /// If every sender has been dropped, this waits forever. Use [`recv`] to
+    /// detect that case.
+    ///
+    /// [`recv`]: LrWatchReceiver::recv
+    pub fn wait(&mut self) -> T {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(park_while::<C, _>(state, |state| state.version <= seen));
+
+        self.get()
+    }
+
+    /// Like [`wait`], but fails once every sender has been dropped.
+    ///
+    /// [`wait`]: LrWatchReceiver::wait
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        let state = park_while::<C, _>(state, |state| state.version <= seen && state.senders > 0);
+        if state.version <= seen {
+            return Err(RecvError);
+        }
+        drop(state);
+
+        Ok(self.get())
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvarTimeout> LrWatchReceiver<T, C> {
+    /// This method waits until a new value becomes available and return a
+    /// clone of it, timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
+        let seen = self.last_seen_version;
+        let deadline = Deadline::after(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) =
+            park_while_until::<C, _>(state, deadline, |state| state.version <= seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.get())
+    }
+
+    /// Like [`wait_timeout`], but fails once every sender has been dropped.
+    ///
+    /// [`wait_timeout`]: LrWatchReceiver::wait_timeout
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        let seen = self.last_seen_version;
+        let deadline = Deadline::after(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) = park_while_until::<C, _>(state, deadline, |state| {
+            state.version <= seen && state.senders > 0
+        });
+        if !ready {
+            return Err(RecvTimeoutError::Timeout);
+        }
+        if state.version <= seen {
+            return Err(RecvTimeoutError::Closed);
+        }
+        drop(state);
+
+        Ok(self.get())
+    }
+}
+
+impl<T, C: RawCondvar> Clone for LrWatchSender<T, C> {
+    fn clone(&self) -> LrWatchSender<T, C> {
+        self.shared.state.lock().senders += 1;
+        LrWatchSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Clone for LrWatchReceiver<T, C> {
+    fn clone(&self) -> LrWatchReceiver<T, C> {
+        LrWatchReceiver {
+            shared: self.shared.clone(),
+            epoch: self.shared.register(),
+            last_seen_version: self.last_seen_version,
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Drop for LrWatchSender<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            state.notify_all();
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Drop for LrWatchReceiver<T, C> {
+    fn drop(&mut self) {
+        let mut epochs = self.shared.epochs.lock();
+        if let Some(i) = epochs
+            .iter()
+            .position(|epoch| Arc::ptr_eq(epoch, &self.epoch))
+        {
+            epochs.swap_remove(i);
+        }
+    }
+}
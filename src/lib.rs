@@ -3,187 +3,3330 @@
 //!
 //! This crate provides a `parking_lot` feature. When enabled, the crate will
 //! use the mutex from the `parking_lot` crate rather than the one from std.
-use std::{
-    sync::Arc,
-    time::{Duration, Instant},
-};
+//!
+//! On Linux and Android, the `futex` feature makes the channel sleep on
+//! futexes directly rather than through a mutex and condition variable,
+//! which makes waking a receiver cheaper than with the mutex from std. The
+//! `parking_lot` feature takes precedence over it, and it has no effect on
+//! other targets.
+//!
+//! Other mutexes can be used through the [`backend`] module, and [`builder`]
+//! configures how a channel uses them.
+//!
+//! On `wasm32-unknown-unknown` the blocking methods such as
+//! [`WatchReceiver::wait`] are only available when compiling with the
+//! `atomics` target feature, since the main thread cannot block otherwise.
+//! The timed waits there are given their full timeout on every wakeup, as
+//! the target has no clock. The helpers that spawn threads are not available
+//! on wasm.
+//!
+//! The crate can be used without std by disabling the default `std` feature
+//! and enabling the `spin` feature instead. The channel then only needs
+//! `alloc`, uses a spinlock from the `spin` crate, and [`WatchReceiver::wait`]
+//! spins until a new value arrives. The methods that take a timeout, and the
+//! helpers that spawn threads, require std.
+//!
+//! For interrupt-driven embedded targets, the `critical-section` feature
+//! protects the channel with [`critical-section`] rather than a spinlock, so
+//! that values can be sent from interrupt handlers. It takes precedence over
+//! the `spin` feature when std is disabled. Waiting then polls the channel, so
+//! prefer [`WatchReceiver::wait_with`] with a hook such as
+//! `cortex_m::asm::wfe`, and never wait from an interrupt handler.
+//!
+//! The `embedded-async` feature adds [`WatchReceiver::changed`], which waits
+//! for a new value from async code, [`WatchReceiver::wait_for`], which waits
+//! for a value that satisfies a condition, and [`WatchSender::closed`], which
+//! waits for every receiver to be dropped. They work without std on executors such
+//! as embassy, provided an allocator is available.
+//!
+//! The `macros` feature adds [`select!`], which waits for the first of
+//! a few receivers to get a new value, with an optional timeout.
+//!
+//! The `futures-signals` feature adds [`WatchReceiver::into_signal`], which
+//! turns a receiver into a `Signal` of its values.
+//!
+//! The channel keeps its value in an `Arc`, so [`WatchReceiver::get_shared`]
+//! returns the latest value without cloning it, and `T` only needs to be
+//! `Clone` for the methods that return a clone.
+//!
+//! When every handle lives inside a `std::thread::scope`, [`scoped`]
+//! creates a channel that the handles borrow instead of sharing through an
+//! `Arc`, so the value may borrow data from the enclosing scope.
+//!
+//! When a channel only says that something happened, [`event`] creates one
+//! that carries notifications without a value.
+//!
+//! For a shutdown or ready flag, [`flag`] creates a channel of `bool` whose
+//! watchers wait for the flag to be set rather than for it to change.
+//!
+//! For progress reports, [`counter`] creates a channel of a count that only
+//! goes up, whose watchers can wait for it to reach a threshold.
+//!
+//! When many small deltas, such as bytes transferred, are sent faster than
+//! they are read, [`folding_channel`] folds each of them into a value that
+//! the receiver takes and resets.
+//!
+//! For a value that is set once and never changes, such as a port that is
+//! bound at startup, [`once_channel`] creates a channel whose first send is
+//! the only one that succeeds.
+//!
+//! For a generation number or logical timestamp, [`monotonic_channel`]
+//! creates a channel whose sender rejects values older than the current one.
+//!
+//! For a large state that producers change a little at a time, such as a
+//! map, [`patch_channel`] creates a channel whose senders send patches that
+//! the channel applies, see [`ApplyPatch`].
+//!
+//! When two sides each publish their latest state to the other, such as a
+//! controller and a worker, [`channel_pair`] creates a channel in each
+//! direction and bundles each sender with the receiver for the other side.
+//!
+//! [`StaticWatch`] is a channel that can be created in a `static`, without
+//! allocating.
+//!
+//! For small `Copy` values, [`copy_channel`] creates a channel whose
+//! receivers read the value without taking a lock.
+//!
+//! With a single sender and a single receiver, [`triple_channel`] creates a
+//! triple buffer whose receiver borrows the latest value without ever
+//! blocking, for use on real-time threads.
+//!
+//! For many values that are each watched on their own, [`watch_map`]
+//! creates a map with a channel for every key.
+//!
+//! When a state is split across several channels, [`transaction`] updates
+//! them together, and [`get_all`] reads them without seeing half of an
+//! update. [`get_all_if_any_new`] only reads them once one of them changed.
+//!
+//! When many channels end together, such as those of a session, a
+//! [`Group`] creates them, closes them all at once, and waits for their
+//! receivers to go away.
+//!
+//! Within a single thread, the [`local`] module provides a channel without
+//! atomic operations or locks.
+//!
+//! With exactly one sender and one receiver, the [`spsc`] module provides a
+//! channel that guards its value with a flag and wakes the receiver by
+//! unparking its thread, rather than through a mutex and a list of waiters.
+//!
+//! Code that should work with more than one kind of channel, or with a fake
+//! in tests, can take the [`WatchPublish`] and [`WatchObserve`] traits. The
+//! senders and receivers of [`channel`], [`copy_channel`], [`spsc`] and
+//! [`lr_channel`] implement them.
+//!
+//! The `arc-swap` feature adds [`arc_channel`], whose receivers get the
+//! value as an `Arc` without taking a lock.
+//!
+//! The `left-right` feature adds [`lr_channel`], which keeps two copies of
+//! the value so that its receivers read one without waiting while the
+//! senders write the other.
+//!
+//! The `serde` feature adds [`Snapshot`], which captures the value and
+//! version of a channel so that it can be restored later.
+//!
+//! The `persist` feature adds [`persistent_channel`], whose values are saved
+//! to a file as they are sent and restored from it when it is created again.
+//!
+//! The `ffi` feature adds the [`ffi`] module, a C interface for channels of
+//! byte buffers.
+//!
+//! On Linux, the `shm` feature adds the [`shm`] module, whose channels carry
+//! plain values from one process to others over shared memory.
+//!
+//! On Unix, the `signals` feature adds [`signals`], which publishes the
+//! process signals it is given, such as `SIGHUP` to reload a configuration,
+//! on a channel of [`SignalEvent`]s.
+//!
+//! On Windows, the `windows-event` feature adds
+//! `WatchReceiver::readiness_event`, an event object that is signaled
+//! when the channel changes, so that it can be waited on together with other
+//! handles.
+//!
+//! The `zeroize` feature adds [`zeroizing_channel`] for secrets, which
+//! zeroizes every value that the channel replaces or drops.
+//!
+//! The `derive` feature adds `#[derive(WatchFields)]`, which gives every
+//! field of a struct its own channel, see [`WatchFields`].
+//!
+//! On nightly Rust, the `allocator_api` feature adds [`channel_in`], which
+//! allocates the state of a channel with the given allocator.
+//!
+//! The `stats` feature adds [`WatchSender::stats`] and
+//! [`WatchReceiver::stats`], which count what happened on a channel. With
+//! `std`, they also keep a histogram of how long waiting receivers took to
+//! get each new value.
+//!
+//! The `metrics` feature adds [`ChannelBuilder::metrics_label`], which makes a
+//! channel report its sends, wakeups, receivers and lag through the
+//! [`metrics`] facade.
+//!
+//! The `tracing` feature emits [`tracing`] events with the `watch` target
+//! when a value is published, when a waiting receiver wakes up and when a
+//! channel closes. Each event has the channel as a `channel` field, which
+//! matches the value inside its [`ChannelId`].
+//! With [`ChannelBuilder::trace_spans`], the channel also keeps the span
+//! that each value was sent in, for receivers to process it in, see
+//! [`WatchReceiver::wait_traced`].
+//!
+//! The `log` feature emits the same events through the [`log`] facade,
+//! at the trace level, and the warnings of `lock-timing` at the warn
+//! level, with the fields after the message as `name=value`. Both features
+//! can be enabled, in which case every event goes to both, so a
+//! `tracing` subscriber that also collects `log` records sees them twice.
+//!
+//! The `lock-timing` feature measures how long each operation keeps the
+//! value of a channel locked, and emits a `tracing` warning when that is
+//! longer than [`ChannelBuilder::slow_lock_threshold`].
+//!
+//! The `timer` feature adds [`WatchSender::send_after`], which sends a
+//! value after a delay unless the send is cancelled first, and
+//! [`WatchSender::keepalive`], which notifies the receivers again while the
+//! value stays the same. With `embedded-async`, it also adds
+//! [`WatchReceiver::changed_timeout`], whose timeout works on any executor.
+//!
+//! The `registry` feature adds [`channel_named`], which creates a channel
+//! with a name and lists it in the [`registry`] for as long as it lives, so
+//! that the channels of a process can be inspected when debugging.
+//!
+//! The `debug-handles` feature adds [`WatchSender::live_receiver_origins`],
+//! which lists the source location where each live receiver of a channel
+//! was created, to find the one that keeps a channel from closing.
+//!
+//! The `test-util` feature adds [`WatchSender::record`], which captures
+//! every value published on a channel so that tests can assert on the
+//! values a producer sent, and [`WatchReceiver::collect_updates`], which
+//! gathers the values delivered to a receiver. A [`Recording`] of the
+//! values can be replayed into another channel with the same timing.
+//!
+//! The `test-clock` feature adds `MockClock`, a clock that tests advance by
+//! hand, so that they can check the timed waits of a channel without
+//! sleeping.
+//!
+//! [`critical-section`]: https://docs.rs/critical-section
+//! [`tracing`]: https://docs.rs/tracing
+//! [`log`]: https://docs.rs/log
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+#[cfg(not(loom))]
+use core::sync::atomic::AtomicBool;
+#[cfg(all(target_has_atomic = "64", not(loom)))]
+use core::sync::atomic::AtomicU64;
+use core::{fmt, marker::PhantomData, ptr, sync::atomic::Ordering};
+// Under loom, the atomics of the channel are modelled along with its locks.
+#[cfg(loom)]
+use loom::sync::atomic::AtomicBool;
+#[cfg(all(target_has_atomic = "64", loom))]
+use loom::sync::atomic::AtomicU64;
 
-#[cfg(not(feature = "parking_lot"))]
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use clock::{Clock, Deadline};
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+#[macro_use]
+mod diagnostics;
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+mod clock;
+#[cfg(all(
+    feature = "test-clock",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+pub use clock::MockClock;
+
+#[cfg(feature = "std")]
 mod sync_std;
-#[cfg(not(feature = "parking_lot"))]
-use sync_std::{Condvar, Mutex};
 
 #[cfg(feature = "parking_lot")]
 mod sync_parking_lot;
-#[cfg(feature = "parking_lot")]
-use sync_parking_lot::{Condvar, Mutex};
+
+#[cfg(all(feature = "futex", any(target_os = "linux", target_os = "android")))]
+mod sync_futex;
+
+#[cfg(all(not(feature = "std"), feature = "critical-section"))]
+mod sync_critical_section;
+
+#[cfg(feature = "spin")]
+mod sync_spin;
+
+#[cfg(loom)]
+mod sync_loom;
+
+#[cfg(not(any(feature = "std", feature = "spin", feature = "critical-section")))]
+compile_error!(
+    "the `watch` crate requires one of the `std`, `spin` or `critical-section` features"
+);
+
+pub mod backend;
+
+mod allocator;
+use allocator::SharedArc;
+pub use allocator::{Allocator, Global};
+// Nothing parks when the target cannot block.
+#[cfg_attr(
+    all(target_family = "wasm", not(target_feature = "atomics")),
+    allow(dead_code)
+)]
+mod waiters;
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+mod stop;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use backend::RawCondvarTimeout;
+use backend::{DefaultCondvar, RawCondvar};
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use lock_api::MutexGuard;
+use lock_api::{Mutex, RwLockWriteGuard};
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+mod bridge;
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+pub use bridge::{from_mpsc, BridgeHandle, ForwarderHandle};
+// The channels that loom models have no timed waits.
+#[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+mod watchdog;
+#[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+pub use watchdog::WatchdogHandle;
+#[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+mod poller;
+#[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+pub use poller::{from_fn, from_fn_distinct, PollerHandle};
+
+mod reentrancy;
+use reentrancy::ValueLock;
+
+#[cfg(feature = "tracing")]
+mod traced;
+
+mod lock_timing;
+use lock_timing::LockTimer;
+
+mod builder;
+pub use builder::{builder, ChannelBuilder};
+
+mod cached;
+pub use cached::CachedWatchReceiver;
+
+mod epoch;
+pub use epoch::EpochWatchReceiver;
+
+mod pairwise;
+pub use pairwise::PairwiseReceiver;
+
+#[cfg(feature = "std")]
+mod cursor;
+#[cfg(feature = "std")]
+pub use cursor::{Cursor, CursorError};
+
+mod owned_ref;
+pub use owned_ref::OwnedWatchRef;
+
+mod override_guard;
+pub use override_guard::OverrideGuard;
+
+mod pause;
+pub use pause::PauseGuard;
+
+#[cfg(feature = "debug-handles")]
+mod origins;
+#[cfg(feature = "debug-handles")]
+pub use origins::HandleOrigin;
+
+mod event;
+pub use event::{event, EventListener, EventSender};
+
+mod flag;
+pub use flag::{flag, FlagSetter, FlagWatcher};
+
+mod counter;
+pub use counter::{counter, CounterSender, CounterWatcher};
+
+mod fold;
+pub use fold::{folding_channel, FoldReceiver, FoldSender};
+
+mod once;
+pub use once::{once_channel, AlreadySet, OnceReceiver, OnceSender};
+
+mod monotonic;
+pub use monotonic::{monotonic_channel, MonotonicSender, Regression};
+
+mod patch;
+pub use patch::{patch_channel, ApplyPatch, PatchSender};
+
+mod pair;
+pub use pair::{channel_pair, Endpoint};
+
+#[cfg(feature = "derive")]
+mod fields;
+#[cfg(feature = "derive")]
+pub use fields::WatchFields;
+#[cfg(feature = "derive")]
+pub use watch_derive::WatchFields;
+
+/// Used by the code that `#[derive(WatchFields)]` and [`select!`]
+/// generate.
+#[cfg(any(
+    feature = "derive",
+    all(
+        feature = "macros",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    )
+))]
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "derive")]
+    pub use crate::fields::send_if_changed;
+    #[cfg(all(
+        feature = "macros",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    pub mod select {
+        pub use crate::select::{run, AtomicUsize, Branch, Branches};
+    }
+}
+
+mod transaction;
+pub use transaction::{
+    get_all, get_all_if_any_new, transaction, TransactionReceivers, TransactionSenders,
+};
+
+mod option;
+
+mod result;
+
+mod read_only;
+pub use read_only::ReadOnlyWatchReceiver;
+
+mod reader;
+pub use reader::WatchReader;
+
+mod poison;
+pub use poison::Poisoned;
+
+#[cfg(feature = "metrics")]
+mod metrics_export;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "stats")]
+pub use stats::ChannelStats;
+#[cfg(all(feature = "stats", feature = "std", not(target_family = "wasm")))]
+pub use stats::LatencyHistogram;
+
+mod history;
+use history::History;
+
+mod previous;
+
+mod lag;
+#[cfg(target_has_atomic = "64")]
+pub use lag::{LagReport, ReceiverId, ReceiverInfo};
+
+#[cfg(all(feature = "test-util", not(target_family = "wasm")))]
+mod collect;
+#[cfg(all(feature = "test-util", not(target_family = "wasm")))]
+mod recorder;
+#[cfg(all(feature = "test-util", not(target_family = "wasm")))]
+pub use recorder::{Recorded, Recorder};
+#[cfg(all(feature = "test-util", not(target_family = "wasm"), not(loom)))]
+mod replay;
+#[cfg(all(feature = "test-util", not(target_family = "wasm"), not(loom)))]
+pub use replay::{Recording, ReplayHandle};
+
+// The channels that loom models have no timed waits.
+#[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+mod group;
+#[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+pub use group::Group;
+
+#[cfg(all(feature = "registry", not(target_family = "wasm")))]
+mod registry;
+#[cfg(all(feature = "registry", not(target_family = "wasm")))]
+pub use registry::{channel_named, registry, ChannelInfo, Registry};
+
+#[cfg(all(feature = "timer", not(target_family = "wasm")))]
+mod timer;
+#[cfg(all(feature = "timer", not(target_family = "wasm")))]
+pub use timer::{KeepaliveHandle, ScheduledSend};
+
+#[cfg(feature = "std")]
+mod map;
+#[cfg(feature = "std")]
+pub use map::{watch_map, MapReceiver, MapSender};
+
+mod scoped;
+pub use scoped::{scoped, ScopedChannel, ScopedReceiver, ScopedSender};
+
+mod static_watch;
+pub use static_watch::{StaticReceiver, StaticWatch};
+
+mod copy;
+pub use copy::{copy_channel, CopyReceiver, CopySender};
+
+mod triple;
+pub use triple::{triple_channel, TripleReceiver, TripleSender};
+
+pub mod local;
+
+#[cfg(feature = "std")]
+pub mod spsc;
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+mod observe;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+pub use observe::{WatchObserve, WatchPublish};
+
+#[cfg(feature = "arc-swap")]
+mod swap;
+#[cfg(feature = "arc-swap")]
+pub use swap::{arc_channel, ArcWatchReceiver, ArcWatchSender};
+
+#[cfg(feature = "left-right")]
+mod left_right;
+#[cfg(feature = "left-right")]
+pub use left_right::{lr_channel, LrWatchReceiver, LrWatchSender};
+
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "serde")]
+pub use snapshot::{channel_from_snapshot, Snapshot};
+
+#[cfg(all(feature = "persist", not(target_family = "wasm")))]
+mod persist;
+#[cfg(all(feature = "persist", not(target_family = "wasm")))]
+pub use persist::{persistent_channel, PersistHandle};
+
+#[cfg(all(feature = "ffi", not(target_family = "wasm")))]
+pub mod ffi;
+
+#[cfg(all(feature = "shm", any(target_os = "linux", target_os = "android")))]
+pub mod shm;
+
+#[cfg(all(feature = "signals", unix))]
+mod os_signals;
+#[cfg(all(feature = "signals", unix))]
+pub use os_signals::{signals, SignalEvent};
+
+#[cfg(all(feature = "windows-event", windows))]
+mod windows_event;
+#[cfg(all(feature = "windows-event", windows))]
+pub use windows_event::WindowsEventHandle;
+
+#[cfg(feature = "zeroize")]
+mod zeroizing;
+#[cfg(feature = "zeroize")]
+pub use zeroizing::zeroizing_channel;
+
+#[cfg(feature = "embedded-async")]
+mod future;
+
+#[cfg(all(
+    feature = "macros",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+mod select;
+#[cfg(all(
+    feature = "embedded-async",
+    feature = "timer",
+    not(target_family = "wasm")
+))]
+pub use future::ChangedTimeout;
+#[cfg(feature = "embedded-async")]
+pub use future::{Changed, Closed, WaitFor};
+
+#[cfg(feature = "futures-signals")]
+mod signal;
+#[cfg(feature = "futures-signals")]
+pub use signal::WatchSignal;
 
 /// The sender for the watch channel.
 ///
 /// The sender can be cloned to obtain multiple senders for the same channel.
-pub struct WatchSender<T> {
-    shared: Arc<Shared<T>>,
+pub struct WatchSender<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    shared: SharedArc<Shared<T, C>, A>,
+    id: SenderId,
 }
 
 /// The receiver for the watch channel.
 ///
 /// The receiver can be cloned. Each clone will yield a new receiver that
 /// receives the same messages.
-pub struct WatchReceiver<T> {
-    shared: Arc<Shared<T>>,
+pub struct WatchReceiver<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    shared: SharedArc<Shared<T, C>, A>,
     last_seen_version: u64,
+    cursor: lag::Cursor,
+    /// See [`WatchReceiver::set_interest`].
+    interest: Option<waiters::Interest<T>>,
+    /// The id of the origin of this receiver in the state.
+    #[cfg(feature = "debug-handles")]
+    origin: u64,
 }
 
-impl<T> Clone for WatchSender<T> {
-    fn clone(&self) -> WatchSender<T> {
-        WatchSender {
-            shared: self.shared.clone(),
-        }
+impl<T, C: RawCondvar, A: Allocator + Clone> Clone for WatchSender<T, C, A> {
+    fn clone(&self) -> WatchSender<T, C, A> {
+        new_sender(&self.shared)
     }
 }
-impl<T> Clone for WatchReceiver<T> {
-    fn clone(&self) -> WatchReceiver<T> {
-        WatchReceiver {
-            shared: self.shared.clone(),
-            last_seen_version: self.last_seen_version,
-        }
+/// # Panics
+///
+/// Cloning panics if the channel already has as many receivers as
+/// [`ChannelBuilder::max_receivers`] allows, see
+/// [`WatchReceiver::try_clone`].
+impl<T, C: RawCondvar, A: Allocator + Clone> Clone for WatchReceiver<T, C, A> {
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    fn clone(&self) -> WatchReceiver<T, C, A> {
+        new_receiver(&self.shared, self.last_seen_version)
     }
 }
 
-struct Shared<T> {
-    lock: Mutex<SharedValue<T>>,
-    on_update: Condvar,
+// These only try to lock the channel, so that a handle can be formatted
+// from within the closure passed to `update`.
+impl<T: fmt::Debug, C: RawCondvar, A: Allocator> fmt::Debug for WatchSender<T, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug(f, |d| self.shared.debug_value(d))
+    }
 }
-struct SharedValue<T> {
-    value: T,
-    version: u64,
+impl<T: fmt::Debug, C: RawCondvar, A: Allocator> fmt::Debug for WatchReceiver<T, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug(f, |d| self.shared.debug_value(d))
+    }
 }
 
-/// Creates a new watch channel.
+impl<T, C: RawCondvar, A: Allocator> WatchSender<T, C, A> {
+    /// Format the sender like its `Debug` implementation does, but without
+    /// the value, so that it can be printed whatever the type of the value.
+    pub fn debug_without_value(&self) -> WithoutValue<'_, Self> {
+        WithoutValue(self)
+    }
+
+    fn debug(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        value: impl FnOnce(&mut fmt::DebugStruct<'_, '_>),
+    ) -> fmt::Result {
+        let mut d = f.debug_struct("WatchSender");
+        d.field("id", &self.id);
+        value(&mut d);
+        self.shared.debug_counts(&mut d);
+        d.finish()
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> WatchReceiver<T, C, A> {
+    /// Format the receiver like its `Debug` implementation does, but
+    /// without the value, so that it can be printed whatever the type of
+    /// the value.
+    pub fn debug_without_value(&self) -> WithoutValue<'_, Self> {
+        WithoutValue(self)
+    }
+
+    fn debug(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        value: impl FnOnce(&mut fmt::DebugStruct<'_, '_>),
+    ) -> fmt::Result {
+        let mut d = f.debug_struct("WatchReceiver");
+        value(&mut d);
+        let version = self.shared.debug_counts(&mut d);
+        d.field("last_seen_version", &self.last_seen_version);
+        match version {
+            Some(version) => d.field("has_changed", &(version != self.last_seen_version)),
+            None => d.field("has_changed", &Locked),
+        };
+        d.finish()
+    }
+}
+
+/// Formats a handle without its value, see
+/// [`WatchSender::debug_without_value`] and
+/// [`WatchReceiver::debug_without_value`].
+pub struct WithoutValue<'a, H>(&'a H);
+
+impl<T, C: RawCondvar, A: Allocator> fmt::Debug for WithoutValue<'_, WatchSender<T, C, A>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.debug(f, |_| {})
+    }
+}
+impl<T, C: RawCondvar, A: Allocator> fmt::Debug for WithoutValue<'_, WatchReceiver<T, C, A>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.debug(f, |_| {})
+    }
+}
+
+/// Shown in place of a part of a channel that is locked.
+struct Locked;
+
+impl fmt::Debug for Locked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<locked>")
+    }
+}
+
+/// The state of a channel.
 ///
-/// The starting value in the channel is not initially considered seen by the receiver.
-pub fn channel<T: Clone>(value: T) -> (WatchSender<T>, WatchReceiver<T>) {
-    let shared = Arc::new(Shared {
-        lock: Mutex::new(SharedValue { value, version: 1 }),
-        on_update: Condvar::new(),
-    });
-    (
-        WatchSender {
-            shared: shared.clone(),
-        },
-        WatchReceiver {
-            shared,
-            last_seen_version: 0,
-        },
-    )
+/// Lock order: `value` may be write-locked before `state` is locked, but
+/// `value` must never be locked while holding `state`.
+struct Shared<T, C: RawCondvar> {
+    /// Receivers may hold on to the value after the lock is released, so it
+    /// is shared rather than cloned.
+    value: CachePadded<ValueLock<C::RawRwLock, SharedValue<Arc<T>>>>,
+    /// A copy of the version of the value, so that receivers can check for a
+    /// new value without locking.
+    ///
+    /// It is stored while the new value is still write-locked, so once a
+    /// send has returned, every load that happens after it sees the new
+    /// version. A load that races with a send may see the old version, in
+    /// which case the value counts as not yet sent. A load that sees a new
+    /// version is always confirmed under the lock, and since versions are
+    /// only compared for equality, wrapping around is harmless.
+    #[cfg(target_has_atomic = "64")]
+    latest: CachePadded<AtomicU64>,
+    state: Mutex<C::RawMutex, SharedState>,
+    /// Set when a closure passed to `update` panics, see [`Poisoned`]. It is
+    /// only changed while the value is write-locked.
+    poisoned: AtomicBool,
+    /// The latest values, if the channel keeps them, see
+    /// [`ChannelBuilder::history`]. The history is locked while the value is
+    /// write-locked, but the value is never locked while holding the history.
+    history: Option<Box<Mutex<C::RawMutex, History<T>>>>,
+    /// The value before the latest change, if the channel keeps it, see
+    /// [`ChannelBuilder::undo`]. It is locked like the history.
+    undo: Option<Box<Mutex<C::RawMutex, Undo<T>>>>,
+    /// The value before the latest version, if the channel keeps it, see
+    /// [`ChannelBuilder::keep_previous`]. It is locked like the history.
+    previous: Option<Box<Mutex<C::RawMutex, previous::Previous<T>>>>,
+    #[cfg(feature = "stats")]
+    stats: stats::Stats,
+    /// The series the channel reports to, see
+    /// [`ChannelBuilder::metrics_label`].
+    #[cfg(feature = "metrics")]
+    metrics: Option<Box<metrics_export::ChannelMetrics>>,
+    /// The versions seen by the receivers, if the channel tracks them, see
+    /// [`ChannelBuilder::track_lag`].
+    #[cfg(target_has_atomic = "64")]
+    cursors: Option<Box<Mutex<C::RawMutex, lag::Cursors>>>,
+    /// The logs of the recorders of the channel, see [`Recorder`].
+    #[cfg(all(feature = "test-util", not(target_family = "wasm")))]
+    recorders: recorder::Recorders<C::RawMutex, T>,
+    /// Whether senders release the locks fairly, see
+    /// [`ChannelBuilder::fair_lock`].
+    fair: bool,
+    /// The clock that timed waits measure their timeouts on.
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    clock: Clock,
+    /// Whether writes keep the current span with the value, see
+    /// [`ChannelBuilder::trace_spans`].
+    #[cfg(feature = "tracing")]
+    trace_spans: bool,
+    /// How long the value may stay locked before a warning, see
+    /// [`ChannelBuilder::slow_lock_threshold`].
+    #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+    slow_lock: Duration,
+    /// Waiting threads park on condvars of this type.
+    _condvar: PhantomData<C>,
 }
 
-impl<T> WatchSender<T> {
-    /// Send a new message and notify all receivers currently waiting for a
-    /// message.
-    pub fn send(&self, mut value: T) {
-        {
-            let mut lock = self.shared.lock.lock();
-            std::mem::swap(&mut lock.value, &mut value);
-            lock.version = lock.version.wrapping_add(1);
+/// The value that [`WatchSender::undo`] puts back, if there is one.
+type Undo<T> = Option<Arc<T>>;
+
+/// Gives a value a cache line of its own.
+///
+/// The receivers poll the version and lock the value while the senders lock
+/// the state, so keeping them apart stops each of them from slowing down the
+/// others. Some x86_64 and aarch64 processors fetch cache lines in pairs, so
+/// these use 128 bytes. Bare-metal targets rarely have a data cache, so
+/// nothing is padded there.
+#[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
+#[cfg_attr(
+    not(any(target_arch = "x86_64", target_arch = "aarch64", target_os = "none")),
+    repr(align(64))
+)]
+struct CachePadded<T>(T);
+
+impl<T> core::ops::Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// A value together with its version.
+///
+/// This holds the versioning logic shared by every channel that keeps its
+/// value behind a lock or a `RefCell`.
+struct SharedValue<V> {
+    value: V,
+    version: u64,
+    /// The sender that wrote the value, see [`WatchReceiver::last_writer`].
+    writer: SenderId,
+    /// The span the value was written in, see
+    /// [`ChannelBuilder::trace_spans`].
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+struct SharedState {
+    /// A copy of the version of the value, updated before the new value can
+    /// be read.
+    version: u64,
+    senders: usize,
+    /// Set by [`WatchSender::close`], after which the channel stays closed
+    /// whatever the number of senders.
+    closed: bool,
+    /// The id of the next sender, see [`SenderId`].
+    next_sender: u64,
+    /// The number of `WatchReceiver` handles, which does not include readers.
+    receivers: usize,
+    /// How many receivers may exist at once, see
+    /// [`ChannelBuilder::max_receivers`].
+    max_receivers: usize,
+    waiters: waiters::WaitList,
+    /// Whether waiters are only woken by [`WatchSender::pump`], see
+    /// [`ChannelBuilder::manual_notify`].
+    manual_notify: bool,
+    /// Set when a value was published on a channel with `manual_notify`,
+    /// until the next pump, or while notifications are paused, until they
+    /// resume.
+    notify_pending: bool,
+    /// The number of live [`PauseGuard`]s.
+    pauses: usize,
+    /// When the value last changed, once a keepalive or the registry needs
+    /// to know.
+    #[cfg(all(
+        any(feature = "timer", feature = "registry"),
+        not(target_family = "wasm")
+    ))]
+    changed_at: Option<std::time::Instant>,
+    #[cfg(feature = "embedded-async")]
+    wakers: future::WakerSet,
+    /// The tasks waiting for every receiver to be dropped, see
+    /// [`WatchSender::closed`].
+    #[cfg(feature = "embedded-async")]
+    closed_wakers: future::WakerSet,
+    /// How many times the last receiver was dropped, so that a
+    /// [`future::Closed`] completes even if the channel reopened before it
+    /// was polled again.
+    #[cfg(feature = "embedded-async")]
+    closings: u64,
+    /// See [`WatchReceiver::readiness_event`].
+    #[cfg(all(feature = "windows-event", windows))]
+    events: windows_event::Events,
+    /// Publishes `receivers`, once [`WatchSender::subscriber_count_watch`]
+    /// has been called.
+    audience: Option<WatchSender<usize>>,
+    /// The gauge of `receivers`, see [`ChannelBuilder::metrics_label`].
+    #[cfg(feature = "metrics")]
+    receivers_gauge: Option<metrics::Gauge>,
+    /// Identifies this channel in a [`Cursor`], or is zero until the first
+    /// cursor is taken.
+    #[cfg(feature = "std")]
+    generation: u64,
+    /// Where each live receiver was created, see
+    /// [`WatchSender::live_receiver_origins`].
+    #[cfg(feature = "debug-handles")]
+    origins: origins::Origins,
+}
+
+impl SharedState {
+    const fn new(version: u64) -> SharedState {
+        SharedState {
+            version,
+            senders: 1,
+            closed: false,
+            next_sender: 1,
+            receivers: 1,
+            max_receivers: usize::MAX,
+            waiters: waiters::WaitList::new(),
+            manual_notify: false,
+            notify_pending: false,
+            pauses: 0,
+            #[cfg(all(
+                any(feature = "timer", feature = "registry"),
+                not(target_family = "wasm")
+            ))]
+            changed_at: None,
+            #[cfg(feature = "embedded-async")]
+            wakers: future::WakerSet::new(),
+            #[cfg(feature = "embedded-async")]
+            closed_wakers: future::WakerSet::new(),
+            #[cfg(feature = "embedded-async")]
+            closings: 0,
+            #[cfg(all(feature = "windows-event", windows))]
+            events: windows_event::Events::new(),
+            audience: None,
+            #[cfg(feature = "metrics")]
+            receivers_gauge: None,
+            #[cfg(feature = "std")]
+            generation: 0,
+            #[cfg(feature = "debug-handles")]
+            origins: origins::Origins::new(),
         }
-        self.shared.on_update.notify_all();
+    }
 
-        // Destroy old value after releasing lock.
-        drop(value);
+    /// Whether the receivers can still expect new values, which ends once
+    /// every sender is dropped or one of them closes the channel.
+    fn is_open(&self) -> bool {
+        self.senders > 0 && !self.closed
     }
 
-    /// Update the message by a closure and notify all receivers currently waiting for a message.
-    pub fn update<F>(&self, f: F)
-    where
-        F: FnOnce(&mut T),
-    {
-        {
-            let mut lock = self.shared.lock.lock();
-            f(&mut lock.value);
-            lock.version = lock.version.wrapping_add(1);
+    /// Wake every thread and task waiting on the channel, such as when it
+    /// closes.
+    fn notify_all(&mut self) {
+        self.waiters.wake_all();
+        self.wake_tasks();
+    }
+
+    /// Publish the number of receivers, if anyone watches it.
+    ///
+    /// This is called with the state locked, so that the counts are sent in
+    /// the order in which they changed.
+    fn receivers_changed(&self) {
+        if let Some(audience) = &self.audience {
+            audience.send(self.receivers);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(gauge) = &self.receivers_gauge {
+            gauge.set(self.receivers as f64);
         }
-        self.shared.on_update.notify_all();
     }
 
-    /// Create a new receiver for the channel.
+    fn wake_tasks(&mut self) {
+        #[cfg(feature = "embedded-async")]
+        self.wakers.wake_all();
+        #[cfg(all(feature = "windows-event", windows))]
+        self.events.set_all();
+    }
+
+    /// Wake the threads and tasks waiting for the latest version, which is
+    /// `value`, or leave them for the next pump if the channel has
+    /// `manual_notify`, or until notifications resume if they are paused.
     ///
-    /// Any messages sent before this method was called are considered seen by
-    /// the new receiver.
-    pub fn subscribe(&self) -> WatchReceiver<T> {
-        let version = {
-            let lock = self.shared.lock.lock();
-            lock.version
-        };
+    /// Threads whose receiver is not interested in `value` stay parked. A
+    /// later pump or resume wakes them all, and those threads check their
+    /// interest themselves.
+    ///
+    /// # Safety
+    ///
+    /// `value` must have the type of the values of the channel, as the
+    /// interests of its receivers are run on it.
+    unsafe fn notify_version<T>(&mut self, value: &T) {
+        if self.manual_notify || self.pauses > 0 {
+            self.notify_pending = true;
+            return;
+        }
+        let version = self.version;
+        self.waiters.wake_interested(version, value);
+        self.wake_tasks();
+    }
 
-        WatchReceiver {
-            shared: self.shared.clone(),
-            last_seen_version: version,
+    /// Wake the threads and tasks waiting for the latest version.
+    fn wake_version(&mut self) {
+        let version = self.version;
+        self.waiters.wake(version);
+        self.wake_tasks();
+    }
+
+    /// Wake one more thread that parked before the latest version, as every
+    /// thread does when it wakes up.
+    ///
+    /// Nothing is woken while a notification waits for a pump or for
+    /// notifications to resume, so a thread that notices the new version by
+    /// itself does not wake the others.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wake_next(&mut self) {
+        if !self.notify_pending {
+            let version = self.version;
+            self.waiters.wake_next(version);
         }
     }
 }
 
-impl<T: Clone> WatchReceiver<T> {
-    /// Get a clone of the latest value sent on the channel.
-    pub fn get(&mut self) -> T {
-        let lock = self.shared.lock.lock();
-        self.last_seen_version = lock.version;
-        lock.value.clone()
+impl<V> SharedValue<V> {
+    const fn new(value: V, version: u64) -> Self {
+        SharedValue {
+            value,
+            version,
+            writer: SenderId(0),
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+        }
     }
 
-    /// Get a clone of the latest value if that value has not previously been
-    /// seen by this receiver.
-    pub fn get_if_new(&mut self) -> Option<T> {
-        let lock = self.shared.lock.lock();
-        if self.last_seen_version == lock.version {
+    /// Get the value and mark it as seen.
+    fn get(&self, seen: &mut u64) -> &V {
+        *seen = self.version;
+        &self.value
+    }
+
+    /// Get the value if it has not been seen, and mark it as seen.
+    fn get_if_new(&self, seen: &mut u64) -> Option<&V> {
+        if *seen == self.version {
             return None;
         }
-        self.last_seen_version = lock.version;
-        Some(lock.value.clone())
+        Some(self.get(seen))
     }
 
-    /// This method waits until a new value becomes available and return a clone
-    /// of it.
-    pub fn wait(&mut self) -> T {
-        let mut lock = self.shared.lock.lock();
+    /// Replace the value with a new version, and return the old value.
+    fn replace(&mut self, value: V) -> V {
+        self.changed();
+        core::mem::replace(&mut self.value, value)
+    }
 
-        while lock.version == self.last_seen_version {
-            lock = self.shared.on_update.wait(lock);
-        }
+    /// Replace the value with a new version written by `writer`, and return
+    /// the old value.
+    fn replace_by(&mut self, value: V, writer: SenderId) -> V {
+        self.writer = writer;
+        self.replace(value)
+    }
 
-        self.last_seen_version = lock.version;
-        lock.value.clone()
+    /// Give the value a new version after it was changed in place.
+    fn changed(&mut self) {
+        self.version = self.version.wrapping_add(1);
     }
+}
 
-    /// This method waits until a new value becomes available and return a clone
-    /// of it, timing out after specified duration.
-    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
-        let mut lock = self.shared.lock.lock();
+impl<T, C: RawCondvar> Shared<T, C> {
+    fn new(value: T, version: u64) -> Shared<T, C> {
+        Shared {
+            value: CachePadded(ValueLock::new(SharedValue::new(Arc::new(value), version))),
+            #[cfg(target_has_atomic = "64")]
+            latest: CachePadded(AtomicU64::new(version)),
+            state: Mutex::new(SharedState::new(version)),
+            poisoned: AtomicBool::new(false),
+            history: None,
+            undo: None,
+            previous: None,
+            #[cfg(feature = "stats")]
+            stats: stats::Stats::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            #[cfg(target_has_atomic = "64")]
+            cursors: None,
+            #[cfg(all(feature = "test-util", not(target_family = "wasm")))]
+            recorders: recorder::Recorders::new(),
+            fair: false,
+            #[cfg(feature = "tracing")]
+            trace_spans: false,
+            #[cfg(all(
+                feature = "std",
+                any(not(target_family = "wasm"), target_feature = "atomics")
+            ))]
+            clock: Clock::System,
+            #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+            slow_lock: lock_timing::DEFAULT_THRESHOLD,
+            _condvar: PhantomData,
+        }
+    }
 
-        let deadline = Instant::now() + duration;
+    fn send(&self, value: T, writer: SenderId) {
+        self.send_arc(Arc::new(value), writer);
+    }
 
-        while lock.version == self.last_seen_version {
-            let timeout = deadline.saturating_duration_since(Instant::now());
+    fn send_arc(&self, value: Arc<T>, writer: SenderId) {
+        let lock = self.value.write();
+        let timer = self.lock_timer("send");
+        let old = self.publish_replace(lock, value, writer);
+        self.lock_released(timer);
+        drop(old);
+    }
 
-            lock = self.shared.on_update.wait_timeout(lock, timeout)?;
+    fn send_if_version(
+        &self,
+        expected: u64,
+        value: T,
+        writer: SenderId,
+    ) -> Result<u64, VersionConflict<T>> {
+        let lock = self.value.write();
+        if lock.version != expected {
+            let version = lock.version;
+            drop(lock);
+            return Err(VersionConflict { value, version });
+        }
+        let timer = self.lock_timer("send_if_version");
+        let old = self.publish_replace(lock, Arc::new(value), writer);
+        self.lock_released(timer);
+        drop(old);
+        Ok(expected.wrapping_add(1))
+    }
 
-            // Note: checking after `on_update.wait_timeout` to call it at least once,
-            // event when `duration` was zero.
-            if timeout.is_zero() && lock.version == self.last_seen_version {
-                return None;
-            }
+    /// Send `value` only if `condition` holds for the latest value, which
+    /// is checked with it write-locked, and give it back otherwise.
+    fn send_if<P>(
+        &self,
+        condition: P,
+        value: T,
+        writer: SenderId,
+        operation: &'static str,
+    ) -> Result<(), T>
+    where
+        P: FnOnce(&T) -> bool,
+    {
+        let lock = self.value.write();
+        if !condition(&lock.value) {
+            drop(lock);
+            return Err(value);
         }
+        let timer = self.lock_timer(operation);
+        let old = self.publish_replace(lock, Arc::new(value), writer);
+        self.lock_released(timer);
+        drop(old);
+        Ok(())
+    }
 
-        self.last_seen_version = lock.version;
-        Some(lock.value.clone())
+    fn update_with<F>(&self, f: F, writer: SenderId)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let lock = self.value.write();
+        let timer = self.lock_timer("update_with");
+        let value = {
+            let _scope = self.value.enter("update_with");
+            Arc::new(f(&lock.value))
+        };
+        let old = self.publish_replace(lock, value, writer);
+        self.lock_released(timer);
+        drop(old);
     }
-}
 
-impl<T> WatchReceiver<T> {
-    /// Create a new sender for this channel.
-    pub fn new_sender(&self) -> WatchSender<T> {
-        WatchSender {
-            shared: self.shared.clone(),
+    fn send_in_place<F>(&self, init: F, writer: SenderId)
+    where
+        F: FnOnce(&mut T),
+        T: Default,
+    {
+        let old = {
+            let mut guard = self.begin_update(writer, "send_in_place");
+            let _scope = self.value.enter("send_in_place");
+            let lock = guard.lock.as_mut().unwrap();
+            // Writing into a value that someone else holds would change it
+            // under them, so this starts from a new one instead.
+            let old = match Arc::get_mut(&mut lock.value) {
+                Some(_) => None,
+                None => Some(core::mem::replace(&mut lock.value, Arc::new(T::default()))),
+            };
+            init(Arc::get_mut(&mut lock.value).unwrap());
+            guard.finished = true;
+            old
+        };
+        // The value it replaced is dropped after the lock is released.
+        drop(old);
+    }
+
+    fn replace_with<F>(&self, f: F, writer: SenderId) -> Result<(), F>
+    where
+        F: FnOnce(T) -> T,
+    {
+        let mut lock = self.value.write();
+        let Some(value) = Arc::get_mut(&mut lock.value) else {
+            self.unlock_value(lock);
+            return Err(f);
+        };
+        let timer = self.lock_timer("replace_with");
+        {
+            let _scope = self.value.enter("replace_with");
+            let abort = AbortOnUnwind;
+            let value: *mut T = value;
+            // SAFETY: The value is moved out and the result moved back in
+            // before anyone can see the value again: it is write-locked and
+            // not shared, and if `f` unwinds the process aborts.
+            unsafe { ptr::write(value, f(ptr::read(value))) };
+            core::mem::forget(abort);
+        }
+        lock.writer = writer;
+        self.capture_span(&mut lock);
+        lock.changed();
+        // The old value is gone, so it can be neither undone to nor returned
+        // as the previous one, and the values kept before it are outdated.
+        let undone = self.undo.as_ref().and_then(|undo| undo.lock().take());
+        let replaced = self
+            .previous
+            .as_ref()
+            .and_then(|previous| previous.lock().take());
+        let evicted = self.notify_changed(&lock);
+        self.unlock_value(lock);
+        self.lock_released(timer);
+        drop(evicted);
+        drop(undone);
+        drop(replaced);
+        Ok(())
+    }
+
+    /// Keep the current span with the value that is being written, if the
+    /// channel captures spans.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn capture_span(&self, value: &mut SharedValue<Arc<T>>) {
+        #[cfg(feature = "tracing")]
+        if self.trace_spans {
+            value.span = tracing::Span::current();
         }
     }
+
+    /// Give the value a new version without replacing it.
+    fn touch(&self) {
+        let mut lock = self.value.write();
+        lock.changed();
+        let replaced = self.keep_previous(&lock.value);
+        let evicted = self.notify_changed(&lock);
+        self.unlock_value(lock);
+        drop(replaced);
+        drop(evicted);
+    }
+
+    /// Replace the locked value and notify everyone waiting for it.
+    fn publish(
+        &self,
+        lock: RwLockWriteGuard<'_, C::RawRwLock, SharedValue<Arc<T>>>,
+        value: Arc<T>,
+        writer: SenderId,
+    ) {
+        // Destroy the old value after releasing the lock.
+        drop(self.publish_replace(lock, value, writer));
+    }
+
+    /// Like `publish`, but returns the value that was replaced.
+    fn publish_replace(
+        &self,
+        mut lock: RwLockWriteGuard<'_, C::RawRwLock, SharedValue<Arc<T>>>,
+        value: Arc<T>,
+        writer: SenderId,
+    ) -> Arc<T> {
+        let old = lock.replace_by(value, writer);
+        self.capture_span(&mut lock);
+        let undone = self.keep_for_undo(&old);
+        let replaced = self.keep_previous(&old);
+        let evicted = self.notify_changed(&lock);
+        self.unlock_value(lock);
+
+        // Destroy old values after releasing lock.
+        drop(undone);
+        drop(replaced);
+        drop(evicted);
+        old
+    }
+
+    /// Release the value after changing it, and report the receivers that
+    /// fell behind, see [`WatchSender::on_lagging`].
+    fn unlock_value(&self, lock: RwLockWriteGuard<'_, C::RawRwLock, SharedValue<Arc<T>>>) {
+        if self.fair {
+            C::unlock_write_fair(lock);
+        } else {
+            drop(lock);
+        }
+        self.report_lagging();
+    }
+
+    /// Wake the threads and tasks waiting for the value to change.
+    ///
+    /// This must be called with the new value still write-locked, so that
+    /// nobody can read that value before the waiters are told about it.
+    ///
+    /// Returns the value that no longer fits in the history, if any, so that
+    /// it can be destroyed after the lock is released.
+    fn notify_changed(&self, value: &SharedValue<Arc<T>>) -> Option<Arc<T>> {
+        let version = value.version;
+        #[cfg(all(feature = "test-util", not(target_family = "wasm")))]
+        self.recorders
+            .record(version, self.clock.now(), &value.value);
+        #[cfg(feature = "stats")]
+        self.stats.sent();
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.sent();
+        }
+        diagnostic!(
+            trace,
+            channel = self.id(),
+            version = version,
+            "value published"
+        );
+        let evicted = self
+            .history
+            .as_ref()
+            .and_then(|history| history.lock().push(version, value.value.clone()));
+        #[cfg(target_has_atomic = "64")]
+        self.latest.store(version, Ordering::Release);
+        let mut state = self.state.lock();
+        state.version = version;
+        #[cfg(all(feature = "stats", feature = "std", not(target_family = "wasm")))]
+        state.waiters.latency.sent();
+        #[cfg(all(
+            any(feature = "timer", feature = "registry"),
+            not(target_family = "wasm")
+        ))]
+        if let Some(changed_at) = &mut state.changed_at {
+            *changed_at = std::time::Instant::now();
+        }
+        // SAFETY: The state belongs to this channel, of values of type `T`.
+        unsafe { state.notify_version::<T>(&*value.value) };
+        if self.fair {
+            C::unlock_fair(state);
+        } else {
+            drop(state);
+        }
+        evicted
+    }
+
+    /// The address of the channel, which identifies it in events, like
+    /// [`ChannelId`].
+    #[cfg(any(feature = "tracing", feature = "log"))]
+    fn id(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    fn version(&self) -> u64 {
+        #[cfg(target_has_atomic = "64")]
+        return self.latest.load(Ordering::Acquire);
+        #[cfg(not(target_has_atomic = "64"))]
+        return self.value.read().version;
+    }
+
+    fn has_changed(&self, seen: u64) -> bool {
+        self.version() != seen
+    }
+
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_while<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        condition: F,
+    ) -> MutexGuard<'a, C::RawMutex, SharedState>
+    where
+        F: FnMut(&SharedState) -> bool,
+    {
+        self.wait_while_filtered(lock, None, condition)
+    }
+
+    /// Like [`wait_while`](Shared::wait_while), for a receiver with an
+    /// interest, see [`park_while_filtered`].
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_while_filtered<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        filter: Option<waiters::Filter>,
+        condition: F,
+    ) -> MutexGuard<'a, C::RawMutex, SharedState>
+    where
+        F: FnMut(&SharedState) -> bool,
+    {
+        self.wait_while_on(lock, &C::new(), filter, condition)
+    }
+
+    /// Like [`wait_while`](Shared::wait_while), but also stop waiting once
+    /// `stop` is stopped, without waking anyone else.
+    #[cfg(all(feature = "std", not(target_family = "wasm")))]
+    fn wait_while_unless_stopped<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        stop: &stop::Stop<C>,
+        mut condition: F,
+    ) -> MutexGuard<'a, C::RawMutex, SharedState>
+    where
+        F: FnMut(&SharedState) -> bool,
+    {
+        self.wait_while_on(lock, stop.condvar(), None, |state| {
+            !stop.is_stopped() && condition(state)
+        })
+    }
+
+    /// Like [`wait_while_filtered`](Shared::wait_while_filtered), parked on
+    /// `condvar`.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_while_on<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        condvar: &C,
+        filter: Option<waiters::Filter>,
+        mut condition: F,
+    ) -> MutexGuard<'a, C::RawMutex, SharedState>
+    where
+        F: FnMut(&SharedState) -> bool,
+    {
+        #[cfg(any(feature = "tracing", feature = "log", feature = "metrics"))]
+        let parks = condition(&lock);
+        let lock = park_while_filtered::<C, _>(lock, condvar, filter, &mut condition);
+        #[cfg(feature = "metrics")]
+        if let (true, Some(metrics)) = (parks, &self.metrics) {
+            metrics.woke();
+        }
+        #[cfg(any(feature = "tracing", feature = "log"))]
+        if parks {
+            diagnostic!(
+                trace,
+                channel = self.id(),
+                version = lock.version,
+                "receiver woke"
+            );
+        }
+        lock
+    }
+
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn wait_while_until<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        deadline: Deadline,
+        condition: F,
+    ) -> (MutexGuard<'a, C::RawMutex, SharedState>, bool)
+    where
+        F: FnMut(&SharedState) -> bool,
+        C: RawCondvarTimeout,
+    {
+        self.wait_while_until_filtered(lock, deadline, None, condition)
+    }
+
+    /// Like [`wait_while_until`](Shared::wait_while_until), for a receiver
+    /// with an interest, see [`park_while_filtered`].
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn wait_while_until_filtered<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        deadline: Deadline,
+        filter: Option<waiters::Filter>,
+        condition: F,
+    ) -> (MutexGuard<'a, C::RawMutex, SharedState>, bool)
+    where
+        F: FnMut(&SharedState) -> bool,
+        C: RawCondvarTimeout,
+    {
+        self.wait_while_until_on(lock, &C::new(), deadline, filter, condition)
+    }
+
+    /// Like [`wait_while_until`](Shared::wait_while_until), but also stop
+    /// waiting once `stop` is stopped, without waking anyone else.
+    #[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+    fn wait_while_until_unless_stopped<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        deadline: Deadline,
+        stop: &stop::Stop<C>,
+        mut condition: F,
+    ) -> (MutexGuard<'a, C::RawMutex, SharedState>, bool)
+    where
+        F: FnMut(&SharedState) -> bool,
+        C: RawCondvarTimeout,
+    {
+        self.wait_while_until_on(lock, stop.condvar(), deadline, None, |state| {
+            !stop.is_stopped() && condition(state)
+        })
+    }
+
+    /// Like [`wait_while_until_filtered`](Shared::wait_while_until_filtered),
+    /// parked on `condvar`.
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn wait_while_until_on<'a, F>(
+        &self,
+        lock: MutexGuard<'a, C::RawMutex, SharedState>,
+        condvar: &C,
+        deadline: Deadline,
+        filter: Option<waiters::Filter>,
+        mut condition: F,
+    ) -> (MutexGuard<'a, C::RawMutex, SharedState>, bool)
+    where
+        F: FnMut(&SharedState) -> bool,
+        C: RawCondvarTimeout,
+    {
+        #[cfg(any(feature = "tracing", feature = "log", feature = "metrics"))]
+        let parks = condition(&lock);
+        let (lock, ready) =
+            park_while_until_filtered::<C, _>(lock, condvar, deadline, filter, &mut condition);
+        #[cfg(feature = "metrics")]
+        if let (true, Some(metrics)) = (parks, &self.metrics) {
+            metrics.woke();
+        }
+        #[cfg(any(feature = "tracing", feature = "log"))]
+        if parks {
+            diagnostic!(
+                trace,
+                channel = self.id(),
+                version = lock.version,
+                timed_out = !ready,
+                "receiver woke"
+            );
+        }
+        (lock, ready)
+    }
+
+    /// The deadline `timeout` from now on the clock of the channel.
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn deadline(&self, timeout: Duration) -> Deadline {
+        Deadline::on(self.clock.clone(), timeout)
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar> Shared<T, C> {
+    /// Add the value to `d`.
+    fn debug_value(&self, d: &mut fmt::DebugStruct<'_, '_>) {
+        match self.value.try_read() {
+            Some(value) => d.field("value", &value.value),
+            None => d.field("value", &Locked),
+        };
+    }
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// Add the version and the handle counts to `d`, and return the version
+    /// if the channel was not locked.
+    fn debug_counts(&self, d: &mut fmt::DebugStruct<'_, '_>) -> Option<u64> {
+        match self.state.try_lock() {
+            Some(state) => {
+                d.field("version", &state.version);
+                d.field("senders", &state.senders);
+                d.field("receivers", &state.receivers);
+                Some(state.version)
+            }
+            None => {
+                d.field("version", &Locked);
+                d.field("senders", &Locked);
+                d.field("receivers", &Locked);
+                None
+            }
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    fn get_shared(&self, seen: &mut u64) -> Arc<T> {
+        let lock = self.value.read();
+        let timer = self.lock_timer("get");
+        let value = lock.get(seen).clone();
+        drop(lock);
+        self.lock_released(timer);
+        value
+    }
+
+    /// Like `get_if_new_shared`, but gives up if the value is locked.
+    fn try_get_if_new_shared(&self, seen: &mut u64) -> Option<Arc<T>> {
+        if self.version() == *seen {
+            return None;
+        }
+        self.value.try_read()?.get_if_new(seen).cloned()
+    }
+
+    fn get_if_new_shared(&self, seen: &mut u64) -> Option<Arc<T>> {
+        #[cfg(target_has_atomic = "64")]
+        if self.latest.load(Ordering::Acquire) == *seen {
+            #[cfg(feature = "stats")]
+            self.stats.empty_poll();
+            return None;
+        }
+        let lock = self.value.read();
+        let timer = self.lock_timer("get");
+        let value = lock.get_if_new(seen).cloned();
+        drop(lock);
+        self.lock_released(timer);
+        #[cfg(feature = "stats")]
+        if value.is_none() {
+            self.stats.empty_poll();
+        }
+        value
+    }
+
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_shared(&self, seen: &mut u64, filter: Option<waiters::Filter>) -> Arc<T> {
+        let state = self.state.lock();
+        drop(self.wait_while_filtered(state, filter, |state| state.version == *seen));
+
+        self.get_shared(seen)
+    }
+
+    /// Run `f` on the latest value while it is read-locked, and mark it seen
+    /// once `f` returns.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn map<R, F>(&self, seen: &mut u64, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let lock = self.value.read();
+        let timer = self.lock_timer("wait_map");
+        let result = {
+            let _scope = self.value.enter("wait_map");
+            f(&lock.value)
+        };
+        *seen = lock.version;
+        drop(lock);
+        self.lock_released(timer);
+        result
+    }
+
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_map<R, F>(&self, seen: &mut u64, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let state = self.state.lock();
+        drop(self.wait_while(state, |state| state.version == *seen));
+
+        self.map(seen, f)
+    }
+
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn wait_map_timeout<R, F>(&self, seen: &mut u64, duration: Duration, f: F) -> Option<R>
+    where
+        C: RawCondvarTimeout,
+        F: FnOnce(&T) -> R,
+    {
+        let deadline = self.deadline(duration);
+        let state = self.state.lock();
+        let (state, ready) = self.wait_while_until(state, deadline, |state| state.version == *seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.map(seen, f))
+    }
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// Start keeping the latest `capacity` values.
+    fn enable_history(&mut self, capacity: usize) {
+        if capacity > 0 {
+            let value = self.value.0.get_mut();
+            let history = History::new(capacity, value.version, value.value.clone());
+            self.history = Some(Box::new(Mutex::new(history)));
+        }
+    }
+
+    /// Start keeping the value before the latest change.
+    fn enable_undo(&mut self) {
+        self.undo = Some(Box::new(Mutex::new(None)));
+    }
+
+    /// Keep `value` as the value to go back to on undo, if the channel keeps
+    /// one.
+    ///
+    /// This must be called with the value write-locked. Returns the value
+    /// kept before, so that it can be destroyed after the lock is released.
+    fn keep_for_undo(&self, value: &Arc<T>) -> Option<Arc<T>> {
+        self.undo
+            .as_ref()
+            .and_then(|undo| undo.lock().replace(value.clone()))
+    }
+
+    /// Put back the value before the latest change as a new value written by
+    /// `writer`.
+    fn undo(&self, writer: SenderId) -> Result<(), NothingToUndo> {
+        let mut lock = self.value.write();
+        let previous = match self.undo.as_ref().and_then(|undo| undo.lock().take()) {
+            Some(previous) => previous,
+            None => {
+                self.unlock_value(lock);
+                return Err(NothingToUndo);
+            }
+        };
+        // Unlike `publish`, this does not keep the value it replaces, so a
+        // second undo fails rather than going back and forth.
+        let old = lock.replace_by(previous, writer);
+        self.capture_span(&mut lock);
+        let replaced = self.keep_previous(&old);
+        let evicted = self.notify_changed(&lock);
+        self.unlock_value(lock);
+        drop(old);
+        drop(replaced);
+        drop(evicted);
+        Ok(())
+    }
+
+    /// Write-lock the value for an update in place by `writer`, which is
+    /// timed as `operation`.
+    fn begin_update(&self, writer: SenderId, operation: &'static str) -> UpdateGuard<'_, T, C> {
+        self.begin_update_if(writer, operation, |_| true).unwrap()
+    }
+
+    /// Like [`begin_update`](Shared::begin_update), but only if `condition`
+    /// holds for the value, which is checked with it write-locked.
+    fn begin_update_if<P>(
+        &self,
+        writer: SenderId,
+        operation: &'static str,
+        condition: P,
+    ) -> Option<UpdateGuard<'_, T, C>>
+    where
+        P: FnOnce(&T) -> bool,
+    {
+        let mut lock = self.value.write();
+        if !condition(&lock.value) {
+            drop(lock);
+            return None;
+        }
+        let timer = self.lock_timer(operation);
+        lock.writer = writer;
+        self.capture_span(&mut lock);
+        let undone = self.keep_for_undo(&lock.value);
+        let replaced = self.keep_previous(&lock.value);
+        Some(UpdateGuard {
+            shared: self,
+            lock: Some(lock),
+            undone,
+            replaced,
+            timer: Some(timer),
+            finished: false,
+        })
+    }
+
+    fn missed_values_shared(&self, seen: &mut u64) -> (Vec<Arc<T>>, u64) {
+        match &self.history {
+            Some(history) => history.lock().missed(seen),
+            None => {
+                let lock = self.value.read();
+                let behind = lock.version.wrapping_sub(*seen);
+                match lock.get_if_new(seen) {
+                    Some(value) => (alloc::vec![value.clone()], behind - 1),
+                    None => (Vec::new(), 0),
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if nothing but the channel and its history holds the
+    /// latest value.
+    fn value_is_unshared(&self) -> bool {
+        let lock = self.value.read();
+        let kept = match &self.history {
+            Some(history) => history.lock().holds(&lock.value),
+            None => 0,
+        };
+        Arc::strong_count(&lock.value) == 1 + kept + self.previous_holds(&lock.value)
+    }
+
+    /// Destroy the channel, and return its latest value.
+    ///
+    /// This must only be called once [`value_is_unshared`] has returned
+    /// `true` for the last handle.
+    ///
+    /// [`value_is_unshared`]: Shared::value_is_unshared
+    fn into_value(self) -> T {
+        let value = self.value.0.into_inner().value;
+        drop(self.history);
+        drop(self.previous);
+        match Arc::try_unwrap(value) {
+            Ok(value) => value,
+            Err(_) => unreachable!("the value of a channel without handles was shared"),
+        }
+    }
+}
+
+impl<T: Clone, C: RawCondvar> Shared<T, C> {
+    /// Like [`update`](Shared::update), but only if `condition` holds for
+    /// the value, and returns whether it did.
+    fn update_if<P, F>(&self, condition: P, f: F, writer: SenderId) -> bool
+    where
+        P: FnOnce(&T) -> bool,
+        F: FnOnce(&mut T),
+    {
+        let Some(mut guard) = self.begin_update_if(writer, "update", condition) else {
+            return false;
+        };
+        let _scope = self.value.enter("update");
+        let lock = guard.lock.as_mut().unwrap();
+        f(Arc::make_mut(&mut lock.value));
+        guard.finished = true;
+        true
+    }
+
+    fn update<F>(&self, f: F, writer: SenderId)
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut guard = self.begin_update(writer, "update");
+        let _scope = self.value.enter("update");
+        let lock = guard.lock.as_mut().unwrap();
+        // This clones the value if a receiver still holds on to it, such as
+        // one that is cloning it right now, or if it is kept for undo.
+        f(Arc::make_mut(&mut lock.value));
+        guard.finished = true;
+    }
+
+    fn send_from(&self, src: &T, writer: SenderId) {
+        let mut guard = self.begin_update(writer, "send_from");
+        let lock = guard.lock.as_mut().unwrap();
+        match Arc::get_mut(&mut lock.value) {
+            Some(value) => value.clone_from(src),
+            // Cloning into a value that someone else holds would change it
+            // under them, so this sends a new one instead.
+            None => lock.value = Arc::new(src.clone()),
+        }
+        guard.finished = true;
+    }
+
+    // These clone the value after releasing the lock, so that a slow clone
+    // holds up neither the senders nor the other receivers. The handle taken
+    // under the lock keeps the value and its version together.
+    fn get(&self, seen: &mut u64) -> T {
+        T::clone(&self.get_shared(seen))
+    }
+
+    fn get_if_new(&self, seen: &mut u64) -> Option<T> {
+        self.get_if_new_shared(seen).map(|value| T::clone(&value))
+    }
+
+    fn get_into(&self, seen: &mut u64, dst: &mut T) {
+        dst.clone_from(&self.get_shared(seen));
+    }
+
+    /// Move the latest value out, leave the default in its place with the
+    /// same version, and mark it seen.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn take(&self, seen: &mut u64) -> T
+    where
+        T: Default,
+    {
+        let mut lock = self.value.write();
+        let timer = self.lock_timer("wait_take");
+        let taken = {
+            let _scope = self.value.enter("wait_take");
+            core::mem::replace(&mut lock.value, Arc::new(T::default()))
+        };
+        // The version is read under the same lock as the value, so a value
+        // sent after it is left unseen rather than lost.
+        *seen = lock.version;
+        self.unlock_value(lock);
+        self.lock_released(timer);
+        // This only clones the value if someone else still holds it.
+        Arc::try_unwrap(taken).unwrap_or_else(|taken| T::clone(&taken))
+    }
+
+    fn get_if_new_into(&self, seen: &mut u64, dst: &mut T) -> bool {
+        match self.get_if_new_shared(seen) {
+            Some(value) => {
+                dst.clone_from(&value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait(&self, seen: &mut u64) -> T {
+        let state = self.state.lock();
+        drop(self.wait_while(state, |state| state.version == *seen));
+
+        self.get(seen)
+    }
+
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_into(&self, seen: &mut u64, dst: &mut T) {
+        let state = self.state.lock();
+        drop(self.wait_while(state, |state| state.version == *seen));
+
+        self.get_into(seen, dst);
+    }
+
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn wait_timeout(&self, seen: &mut u64, duration: Duration) -> Option<T>
+    where
+        C: RawCondvarTimeout,
+    {
+        let deadline = self.deadline(duration);
+        let state = self.state.lock();
+        let (state, ready) = self.wait_while_until(state, deadline, |state| state.version == *seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.get(seen))
+    }
+}
+
+/// Gives the value a new version when an update ends, even if the closure
+/// that changed it panicked.
+///
+/// The value then keeps whatever changes the closure made before it
+/// panicked, the channel is poisoned, and the receivers are told to read the
+/// value again.
+struct UpdateGuard<'a, T, C: RawCondvar> {
+    shared: &'a Shared<T, C>,
+    lock: Option<RwLockWriteGuard<'a, C::RawRwLock, SharedValue<Arc<T>>>>,
+    /// The value that was kept for undo before this update, to be destroyed
+    /// after the lock is released.
+    undone: Option<Arc<T>>,
+    /// The value that was kept as the previous one before this update, to be
+    /// destroyed after the lock is released.
+    replaced: Option<Arc<T>>,
+    /// Times how long the update holds the value.
+    timer: Option<LockTimer>,
+    /// Set once the closure has returned.
+    finished: bool,
+}
+
+impl<T, C: RawCondvar> Drop for UpdateGuard<'_, T, C> {
+    fn drop(&mut self) {
+        if let Some(mut lock) = self.lock.take() {
+            if !self.finished {
+                self.shared.poisoned.store(true, Ordering::Relaxed);
+            }
+            lock.changed();
+            let evicted = self.shared.notify_changed(&lock);
+            self.shared.unlock_value(lock);
+            if let Some(timer) = self.timer.take() {
+                self.shared.lock_released(timer);
+            }
+            drop(evicted);
+            drop(self.undone.take());
+            drop(self.replaced.take());
+        }
+    }
+}
+
+/// Aborts the process if it is dropped, which is only when the closure of
+/// `replace_with` unwinds.
+struct AbortOnUnwind;
+
+impl Drop for AbortOnUnwind {
+    fn drop(&mut self) {
+        // Panicking while already unwinding aborts, also without std.
+        panic!("the closure passed to `WatchSender::replace_with` panicked, aborting");
+    }
+}
+
+/// Park the thread for as long as `condition` returns true.
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+fn park_while<'a, C, F>(
+    lock: MutexGuard<'a, C::RawMutex, SharedState>,
+    condition: F,
+) -> MutexGuard<'a, C::RawMutex, SharedState>
+where
+    C: RawCondvar,
+    F: FnMut(&SharedState) -> bool,
+{
+    park_while_filtered::<C, _>(lock, &C::new(), None, condition)
+}
+
+/// Like [`park_while`], but park on `condvar`, and only be woken by senders
+/// for the values that `filter` passes, if any.
+///
+/// Whoever owns `condvar` can also wake the thread by notifying it, without
+/// waking the other waiters of the channel.
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+fn park_while_filtered<'a, C, F>(
+    mut lock: MutexGuard<'a, C::RawMutex, SharedState>,
+    condvar: &C,
+    filter: Option<waiters::Filter>,
+    mut condition: F,
+) -> MutexGuard<'a, C::RawMutex, SharedState>
+where
+    C: RawCondvar,
+    F: FnMut(&SharedState) -> bool,
+{
+    if !condition(&lock) {
+        return lock;
+    }
+    #[cfg(all(feature = "stats", feature = "std", not(target_family = "wasm")))]
+    let parked_at = lock.version;
+    loop {
+        // Registered under the same lock as the condition, so a sender
+        // that changes the channel after this check wakes the thread.
+        let version = lock.version;
+        let node = lock.waiters.insert(condvar, filter, version);
+        condvar.wait(&mut lock);
+        lock.waiters.remove(node);
+        // The wake is passed on also when the thread parks again, or the
+        // threads behind it would only be woken by a later change.
+        lock.wake_next();
+        if !condition(&lock) {
+            break;
+        }
+    }
+    #[cfg(all(feature = "stats", feature = "std", not(target_family = "wasm")))]
+    if lock.version != parked_at {
+        lock.waiters.latency.received();
+    }
+    lock
+}
+
+/// Park the thread for as long as `condition` returns true, giving up at
+/// the deadline.
+///
+/// Returns `false` if the deadline expired while the condition still held.
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+fn park_while_until<'a, C, F>(
+    lock: MutexGuard<'a, C::RawMutex, SharedState>,
+    deadline: Deadline,
+    condition: F,
+) -> (MutexGuard<'a, C::RawMutex, SharedState>, bool)
+where
+    C: RawCondvarTimeout,
+    F: FnMut(&SharedState) -> bool,
+{
+    park_while_until_filtered::<C, _>(lock, &C::new(), deadline, None, condition)
+}
+
+/// Like [`park_while_until`], but park on `condvar` and only be woken by
+/// senders for the values that `filter` passes, as in
+/// [`park_while_filtered`].
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+fn park_while_until_filtered<'a, C, F>(
+    mut lock: MutexGuard<'a, C::RawMutex, SharedState>,
+    condvar: &C,
+    deadline: Deadline,
+    filter: Option<waiters::Filter>,
+    mut condition: F,
+) -> (MutexGuard<'a, C::RawMutex, SharedState>, bool)
+where
+    C: RawCondvarTimeout,
+    F: FnMut(&SharedState) -> bool,
+{
+    if !condition(&lock) {
+        return (lock, true);
+    }
+    #[cfg(all(feature = "stats", not(target_family = "wasm")))]
+    let parked_at = lock.version;
+    let ready = loop {
+        let timeout = deadline.sleep_time();
+        let version = lock.version;
+        let node = lock.waiters.insert(condvar, filter, version);
+        let timed_out = condvar.wait_timeout(&mut lock, timeout);
+        lock.waiters.remove(node);
+        // As in `park_while_filtered`.
+        lock.wake_next();
+
+        // Note: checking after `condvar.wait_timeout` to call it at least once,
+        // even when the timeout was zero.
+        if !condition(&lock) {
+            break true;
+        }
+        if timed_out && deadline.expired() {
+            break false;
+        }
+    };
+    #[cfg(all(feature = "stats", not(target_family = "wasm")))]
+    if ready && lock.version != parked_at {
+        lock.waiters.latency.received();
+    }
+    (lock, ready)
+}
+
+/// The identity of a channel, as returned by [`WatchSender::channel_id`] and
+/// [`WatchReceiver::channel_id`].
+///
+/// Every handle of a channel has the same id, so ids can key the channels in
+/// a map or set. The id comes from the address of the state of the channel,
+/// so a channel that has been dropped may share its id with a later one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChannelId(usize);
+
+impl ChannelId {
+    fn of<T, A: Allocator>(shared: &SharedArc<T, A>) -> ChannelId {
+        ChannelId(SharedArc::as_ptr(shared) as usize)
+    }
+}
+
+/// The identity of a sender within its channel, as returned by
+/// [`WatchSender::id`].
+///
+/// Every sender of a channel has an id of its own, including clones and the
+/// senders from [`WatchReceiver::new_sender`], and no two senders of the
+/// same channel ever share one. Ids are small numbers that count up from the
+/// sender that was created with the channel, which also counts as the
+/// writer of the starting value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SenderId(u64);
+
+/// Error returned by [`WatchSender::undo`] when there is no change to undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NothingToUndo;
+
+impl fmt::Display for NothingToUndo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("nothing to undo on watch channel")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NothingToUndo {}
+
+/// Error returned by [`WatchSender::try_subscribe`] and
+/// [`WatchReceiver::try_clone`] when the channel already has as many
+/// receivers as [`ChannelBuilder::max_receivers`] allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyReceivers;
+
+impl fmt::Display for TooManyReceivers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("watch channel has too many receivers")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooManyReceivers {}
+
+/// Error returned by [`WatchSender::send_if_version`] when the channel has
+/// a newer version than the expected one.
+pub struct VersionConflict<T> {
+    /// The value that was not sent.
+    pub value: T,
+    /// The latest version of the channel.
+    pub version: u64,
+}
+
+impl<T> fmt::Debug for VersionConflict<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VersionConflict")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for VersionConflict<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "watch channel has moved on to version {}", self.version)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for VersionConflict<T> {}
+
+/// Error returned by [`WatchReceiver::recv`] when every sender has been
+/// dropped, or one called [`WatchSender::close`], and no unseen value
+/// remains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("watch channel closed")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecvError {}
+
+/// Error returned by [`WatchReceiver::recv_timeout`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// No new value was sent before the timeout expired.
+    Timeout,
+    /// Every sender has been dropped, or one closed the channel, and no
+    /// unseen value remains.
+    Closed,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.write_str("timed out waiting on watch channel"),
+            RecvTimeoutError::Closed => f.write_str("watch channel closed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecvTimeoutError {}
+
+/// Creates a new watch channel.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn channel<T>(value: T) -> (WatchSender<T>, WatchReceiver<T>) {
+    channel_at_version(value, 1)
+}
+
+/// Creates a new watch channel that starts without a value.
+///
+/// The channel holds an `Option<T>` that starts out as `None`, and the
+/// senders send `Some` values. The starting `None` counts as seen, so
+/// [`WatchReceiver::get_if_new`] returns `None` and [`WatchReceiver::wait`]
+/// blocks until the first value is sent, while [`WatchReceiver::get`]
+/// returns `None` until then. Receivers created with
+/// [`WatchSender::subscribe`] before the first send receive it as a new
+/// value too.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn channel_empty<T>() -> (WatchSender<Option<T>>, WatchReceiver<Option<T>>) {
+    builder().initial_seen(true).channel(None)
+}
+
+/// Creates a receiver that always holds `value`, for code that takes a
+/// receiver when the value never changes, such as in tests.
+///
+/// The receiver belongs to a channel whose only sender has been dropped, so
+/// it is closed from the start, and nothing else is kept alive for it. Like
+/// with [`channel`], the value is not initially considered seen: the first
+/// [`WatchReceiver::get_if_new`] or [`WatchReceiver::recv`] returns it, so
+/// that code that reacts to new values runs once, and after that
+/// `get_if_new` returns `None`.
+///
+/// Code that waits on a receiver that may come from here should use
+/// [`WatchReceiver::recv`] or [`WatchReceiver::recv_timeout`], which fail at
+/// once with [`RecvError`] or [`RecvTimeoutError::Closed`] after the value
+/// has been seen. [`WatchReceiver::wait`] is not the way to wait on it:
+/// like on any closed channel, it returns the value once and then waits
+/// forever, and [`WatchReceiver::wait_timeout`] waits for the whole timeout.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn constant<T>(value: T) -> WatchReceiver<T> {
+    channel(value).1
+}
+
+/// Creates a new watch channel whose state is allocated with `alloc`.
+///
+/// The handles keep the allocator, and the senders and receivers created
+/// from them share the same state. The values sent on the channel are
+/// still allocated with the global allocator, as [`WatchReceiver::get_shared`]
+/// hands them out as an `Arc<T>`.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+#[cfg(feature = "allocator_api")]
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn channel_in<T, A: Allocator + Clone>(
+    value: T,
+    alloc: A,
+) -> (
+    WatchSender<T, DefaultCondvar, A>,
+    WatchReceiver<T, DefaultCondvar, A>,
+) {
+    channel_from_shared_in(Shared::new(value, 1), alloc)
+}
+
+/// Creates a new watch channel whose timed waits measure their timeouts on
+/// `clock`.
+///
+/// See [`MockClock`].
+#[cfg(all(
+    feature = "test-clock",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn channel_with_clock<T>(value: T, clock: MockClock) -> (WatchSender<T>, WatchReceiver<T>) {
+    builder().clock(clock).channel(value)
+}
+
+/// Creates a new watch channel whose current value has the given version.
+///
+/// The value is not initially considered seen by the receiver.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+fn channel_at_version<T, C: RawCondvar>(
+    value: T,
+    version: u64,
+) -> (WatchSender<T, C>, WatchReceiver<T, C>) {
+    channel_from_shared(Shared::new(value, version))
+}
+
+/// Creates the handles of a new channel.
+///
+/// The value is not initially considered seen by the receiver.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+fn channel_from_shared<T, C: RawCondvar>(
+    shared: Shared<T, C>,
+) -> (WatchSender<T, C>, WatchReceiver<T, C>) {
+    channel_from_shared_in(shared, Global)
+}
+
+/// Creates the handles of a new channel, allocating its state with `alloc`.
+///
+/// The value is not initially considered seen by the receiver.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+fn channel_from_shared_in<T, C: RawCondvar, A: Allocator + Clone>(
+    #[cfg_attr(not(feature = "debug-handles"), allow(unused_mut))] mut shared: Shared<T, C>,
+    alloc: A,
+) -> (WatchSender<T, C, A>, WatchReceiver<T, C, A>) {
+    let last_seen_version = shared.version().wrapping_sub(1);
+    let cursor = shared.cursor(last_seen_version);
+    #[cfg(feature = "debug-handles")]
+    let origin = shared.state.get_mut().origins.add();
+    let shared = SharedArc::new_in(shared, alloc);
+    (
+        WatchSender {
+            shared: shared.clone(),
+            id: SenderId(0),
+        },
+        WatchReceiver {
+            shared,
+            last_seen_version,
+            cursor,
+            interest: None,
+            #[cfg(feature = "debug-handles")]
+            origin,
+        },
+    )
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Send a new message and notify all receivers currently waiting for a
+    /// message.
+    pub fn send(&self, value: T) {
+        self.shared.send(value, self.id);
+    }
+
+    /// Send a value that is already in an `Arc`, without copying it.
+    ///
+    /// The channel shares the value with any other clones of `value`, see
+    /// [`WatchReceiver::get_shared`].
+    pub fn send_arc(&self, value: Arc<T>) {
+        self.shared.send_arc(value, self.id);
+    }
+
+    /// Send `value` only if the latest version of the channel is still
+    /// `expected`, and return the version of the new value.
+    ///
+    /// This is for senders that compute the new value from one they read,
+    /// such as with [`WatchReceiver::get_versioned`], and must not overwrite
+    /// a value that another sender published since. If the version has
+    /// moved on, nothing is sent, and the error gives back `value` together
+    /// with the latest version, so that the caller can read again and retry.
+    pub fn send_if_version(&self, expected: u64, value: T) -> Result<u64, VersionConflict<T>> {
+        self.shared.send_if_version(expected, value, self.id)
+    }
+
+    /// Replace the message by the result of a closure and notify all receivers
+    /// currently waiting for a message.
+    ///
+    /// Unlike [`update`], this does not need `T: Clone`. Like `update`, the
+    /// closure must not use the same channel.
+    ///
+    /// [`update`]: WatchSender::update
+    pub fn update_with<F>(&self, f: F)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        self.shared.update_with(f, self.id);
+    }
+
+    /// Send a new value that `init` writes into the storage of the current
+    /// one, so that a large value is not moved through the stack.
+    ///
+    /// If nothing else holds the current value, such as a handle from
+    /// [`WatchReceiver::get_shared`] or the undo history, `init` is given
+    /// that value to overwrite, and its allocation is reused. Otherwise it
+    /// is given `T::default()` in a new allocation. Either way, `init` must
+    /// write every part of the value that the receivers read. It runs with
+    /// the value locked, so like [`update`], it must not use the same
+    /// channel. To send a value that is already on the heap, use
+    /// [`send_arc`](WatchSender::send_arc) instead.
+    ///
+    /// [`update`]: WatchSender::update
+    pub fn send_in_place<F>(&self, init: F)
+    where
+        F: FnOnce(&mut T),
+        T: Default,
+    {
+        self.shared.send_in_place(init, self.id);
+    }
+
+    /// Replace the message by the result of a closure that takes the old one
+    /// by value, and notify all receivers currently waiting for a message.
+    ///
+    /// This is for values that can neither be cloned nor left empty, such as
+    /// a connection that is consumed to build the next one. The old value
+    /// can only be moved out if nothing else holds it, so this gives `f`
+    /// back without calling it if a receiver still holds the latest value,
+    /// such as through [`WatchReceiver::get_shared`] or while cloning it, or
+    /// if the channel keeps it in its history. The replaced value is gone,
+    /// so afterwards there is nothing to [`undo`](WatchSender::undo), and
+    /// [`get_with_previous`](WatchReceiver::get_with_previous) returns no
+    /// previous value until the next change.
+    ///
+    /// # Aborts
+    ///
+    /// The value is gone while `f` runs, so if `f` panics, there is nothing
+    /// left to put back in the channel, and the process aborts. Like for
+    /// [`update`](WatchSender::update), `f` must not use the same channel.
+    pub fn replace_with<F>(&self, f: F) -> Result<(), F>
+    where
+        F: FnOnce(T) -> T,
+    {
+        self.shared.replace_with(f, self.id)
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver. If every other receiver has been dropped, this
+    /// reopens the channel, see [`is_closed`](WatchSender::is_closed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel already has as many receivers as
+    /// [`ChannelBuilder::max_receivers`] allows, see
+    /// [`try_subscribe`](WatchSender::try_subscribe).
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn subscribe(&self) -> WatchReceiver<T, C, A> {
+        new_receiver(&self.shared, self.shared.version())
+    }
+
+    /// Like [`subscribe`](WatchSender::subscribe), but fails rather than
+    /// panicking if the channel already has as many receivers as
+    /// [`ChannelBuilder::max_receivers`] allows.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn try_subscribe(&self) -> Result<WatchReceiver<T, C, A>, TooManyReceivers> {
+        try_new_receiver(&self.shared, self.shared.version())
+    }
+
+    /// Returns `true` if both senders belong to the same channel.
+    pub fn same_channel(&self, other: &WatchSender<T, C, A>) -> bool {
+        SharedArc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    /// Get the identity of the channel that this sender belongs to.
+    ///
+    /// See [`ChannelId`].
+    pub fn channel_id(&self) -> ChannelId {
+        ChannelId::of(&self.shared)
+    }
+
+    /// Get the identity of this sender within its channel.
+    ///
+    /// See [`SenderId`].
+    pub fn id(&self) -> SenderId {
+        self.id
+    }
+
+    /// The number of threads that are blocked waiting for a new value.
+    ///
+    /// This counts every receiver parked in a blocking or timed wait, but not
+    /// receivers that poll the channel or tasks waiting in
+    /// `WatchReceiver::changed`. A thread
+    /// stops counting once it wakes up, and the count may be out of date by
+    /// the time it is returned.
+    pub fn waiting_receivers(&self) -> usize {
+        self.shared.state.lock().waiters.len()
+    }
+
+    /// The number of receivers of the channel.
+    ///
+    /// Readers created by [`WatchSender::reader`] are not counted.
+    pub fn receiver_count(&self) -> usize {
+        self.shared.state.lock().receivers
+    }
+
+    /// Returns `true` if every receiver of the channel has been dropped.
+    ///
+    /// A channel without receivers still works: it keeps the values that
+    /// are sent, and creating a receiver, such as with
+    /// [`subscribe`](WatchSender::subscribe), reopens it, so this returns
+    /// `false` again. Readers are not counted, as in
+    /// [`receiver_count`](WatchSender::receiver_count).
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().receivers == 0
+    }
+
+    /// Close the channel for its receivers, as if every sender had been
+    /// dropped, and wake everyone waiting on it.
+    ///
+    /// The receivers can still read the latest value, but a wait such as
+    /// [`WatchReceiver::recv`] fails once they have seen it. The channel
+    /// stays closed, also for senders cloned later. Values sent after this
+    /// are still stored, and a receiver that has not seen one gets it
+    /// before it fails. This is about the receivers' side of the channel,
+    /// unlike [`is_closed`](WatchSender::is_closed), which tells whether the
+    /// receivers are gone.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// Get a receiver for the number of receivers of this channel, which is
+    /// updated whenever a receiver is created or dropped.
+    ///
+    /// This is for producers that only work while someone listens, such as
+    /// by waiting for the count to become nonzero, and stopping when it
+    /// drops to zero. The count is sent on a channel of its own, created by
+    /// the first call, so that channels nobody watches pay nothing for it.
+    /// Its receivers are not counted, and its senders are not counted either,
+    /// so it closes once this channel is gone. As with
+    /// [`subscribe`](WatchSender::subscribe), the count when this is called
+    /// counts as seen. Like any other channel, a receiver that does not
+    /// keep up only sees the latest count.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn subscriber_count_watch(&self) -> WatchReceiver<usize> {
+        let mut state = self.shared.state.lock();
+        let receivers = state.receivers;
+        state
+            .audience
+            .get_or_insert_with(|| channel(receivers).0)
+            .subscribe()
+    }
+
+    /// Put back the value from before the latest change, as a new value.
+    ///
+    /// This needs a channel created with [`ChannelBuilder::undo`], which
+    /// keeps the value that each send or update replaced. The latest change
+    /// is undone whichever sender made it, and the receivers are notified as
+    /// for any new value. Only one step is kept, so undoing again fails until
+    /// the value changes again, as does undoing on a channel that keeps
+    /// nothing.
+    pub fn undo(&self) -> Result<(), NothingToUndo> {
+        self.shared.undo(self.id)
+    }
+
+    /// Wake the receivers waiting for the values sent since the last pump.
+    ///
+    /// This only does anything on channels created with
+    /// [`ChannelBuilder::manual_notify`]. Returns `true` if a value had been
+    /// sent since the last pump.
+    pub fn pump(&self) -> bool {
+        let mut state = self.shared.state.lock();
+        if !state.notify_pending {
+            return false;
+        }
+        state.notify_pending = false;
+        state.wake_version();
+        true
+    }
+
+    /// Take the latest value out of the channel, if this is its last handle.
+    ///
+    /// This fails and gives the sender back if another sender, receiver or
+    /// reader of the channel still exists, or if the value is still shared,
+    /// such as by an `Arc` from [`WatchReceiver::get_shared`]. Otherwise the
+    /// channel is destroyed, and the value is moved out of it rather than
+    /// cloned.
+    pub fn try_into_inner(self) -> Result<T, WatchSender<T, C, A>> {
+        // The channel is destroyed without running `drop`, since there are no
+        // other handles to tell that it closed.
+        let id = self.id;
+        try_into_inner(self.into_shared()).map_err(|shared| WatchSender { shared, id })
+    }
+
+    /// Turn this sender into a receiver of the same channel.
+    ///
+    /// As with [`subscribe`], the receiver has seen the latest value. If
+    /// this was the last sender, the channel closes. The sender is replaced
+    /// by the receiver under the same lock, so the channel never closes
+    /// while another sender exists, and no moment passes without either
+    /// handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel already has as many receivers as
+    /// [`ChannelBuilder::max_receivers`] allows. The sender is then dropped.
+    ///
+    /// [`subscribe`]: WatchSender::subscribe
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn into_receiver(self) -> WatchReceiver<T, C, A> {
+        let id = self.id;
+        let shared = self.into_shared();
+        let mut state = shared.state.lock();
+        if state.receivers >= state.max_receivers {
+            let max = state.max_receivers;
+            drop(state);
+            drop(WatchSender { shared, id });
+            too_many_receivers(max);
+        }
+        state.receivers += 1;
+        state.receivers_changed();
+        #[cfg(feature = "debug-handles")]
+        let origin = state.origins.add();
+        let seen = state.version;
+        shared.release_sender(&mut state);
+        drop(state);
+        WatchReceiver {
+            cursor: shared.cursor(seen),
+            shared,
+            last_seen_version: seen,
+            interest: None,
+            #[cfg(feature = "debug-handles")]
+            origin,
+        }
+    }
+
+    /// Take the channel out of this sender without counting it as dropped.
+    fn into_shared(self) -> SharedArc<Shared<T, C>, A> {
+        let this = core::mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped afterwards.
+        unsafe { core::ptr::read(&this.shared) }
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Update the message by a closure and notify all receivers currently waiting for a message.
+    ///
+    /// If a receiver still holds the current value from
+    /// [`WatchReceiver::get_shared`], or is in the middle of cloning it, the
+    /// value is cloned first so that the receiver's copy does not change.
+    ///
+    /// If `f` panics, the value keeps the changes made before the panic, and
+    /// it still counts as a new value so that the receivers read it again.
+    /// The channel is also poisoned, which the checked methods such as
+    /// [`WatchReceiver::get_checked`] report.
+    ///
+    /// The value is locked while `f` runs, so `f` must not read or send on
+    /// the same channel, which would deadlock. Debug builds with the `std`
+    /// feature panic instead.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        self.shared.update(f, self.id);
+    }
+
+    /// Send a clone of `src`, made with `clone_from` into the current value
+    /// so that its allocations are reused.
+    ///
+    /// This only reuses the current value if nothing else holds it, such as
+    /// a handle from [`WatchReceiver::get_shared`] or the undo history.
+    /// Otherwise it sends `src.clone()` like [`send`](WatchSender::send).
+    pub fn send_from(&self, src: &T) {
+        self.shared.send_from(src, self.id);
+    }
+
+    /// Create a new receiver for the channel, together with a clone of the
+    /// latest value.
+    ///
+    /// The receiver has seen exactly the returned value, so it is notified of
+    /// every value sent after it, even one sent while this method runs.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn subscribe_with_value(&self) -> (WatchReceiver<T, C, A>, T) {
+        subscribe_with_value(&self.shared)
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Get a clone of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        self.track(|shared, seen| shared.get(seen))
+    }
+
+    /// Get a clone of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        self.get_if_new_shared().map(|value| T::clone(&value))
+    }
+
+    /// Get a clone of the latest value together with the sender that wrote
+    /// it.
+    ///
+    /// See [`last_writer`](WatchReceiver::last_writer).
+    pub fn get_with_writer(&mut self) -> (T, SenderId) {
+        let (value, writer) = self.track(|shared, seen| {
+            let lock = shared.value.read();
+            (lock.get(seen).clone(), lock.writer)
+        });
+        // The value is cloned after releasing the lock, like in `get`.
+        (T::clone(&value), writer)
+    }
+
+    /// Get a clone of the latest value together with its version.
+    ///
+    /// See [`WatchSender::send_if_version`].
+    pub fn get_versioned(&mut self) -> (T, u64) {
+        let value = self.get_shared();
+        (T::clone(&value), self.last_seen_version)
+    }
+
+    /// Overwrite `dst` with the latest value sent on the channel.
+    ///
+    /// This uses [`Clone::clone_from`], so types such as `Vec` can reuse the
+    /// allocation of `dst` instead of allocating a new one.
+    pub fn get_into(&mut self, dst: &mut T) {
+        self.track(|shared, seen| shared.get_into(seen, dst));
+    }
+
+    /// Create a new receiver for the channel, together with a clone of the
+    /// latest value.
+    ///
+    /// Unlike [`clone`](Clone::clone), the new receiver has seen the returned
+    /// value whether or not this receiver has. This receiver is unaffected.
+    /// See [`WatchSender::subscribe_with_value`].
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn clone_with_value(&self) -> (WatchReceiver<T, C, A>, T) {
+        subscribe_with_value(&self.shared)
+    }
+
+    /// Overwrite `dst` with the latest value if that value has not previously
+    /// been seen by this receiver.
+    ///
+    /// Returns `false` and leaves `dst` untouched if there is no new value.
+    /// See [`get_into`](WatchReceiver::get_into).
+    pub fn get_if_new_into(&mut self, dst: &mut T) -> bool {
+        self.track(|shared, seen| shared.get_if_new_into(seen, dst))
+    }
+
+    /// Get clones of the values sent since this receiver last saw one,
+    /// oldest first, and mark them seen.
+    ///
+    /// Only the values that the channel still keeps are returned, see
+    /// [`ChannelBuilder::history`]. The second element counts the newer
+    /// values that are no longer kept. Without a history, only the latest
+    /// value is kept, so this returns the same value as
+    /// [`get_if_new`](WatchReceiver::get_if_new).
+    pub fn missed_values(&mut self) -> (Vec<T>, u64) {
+        let (values, evicted) = self.track(|shared, seen| shared.missed_values_shared(seen));
+        let values = values.iter().map(|value| T::clone(value)).collect();
+        (values, evicted)
+    }
+
+    /// Wait for a new value by polling the channel, calling `idle` whenever
+    /// there is nothing new.
+    ///
+    /// This is meant for targets without a real condition variable, where
+    /// `idle` can put the processor to sleep until the next interrupt.
+    pub fn wait_with<F>(&mut self, mut idle: F) -> T
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Some(value) = self.get_if_new() {
+                return value;
+            }
+            idle();
+        }
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// This method waits until a new value becomes available and return a clone
+    /// of it.
+    ///
+    /// If every sender has been dropped, this waits forever, which includes
+    /// the receivers of [`constant`] once they have seen the value. Use
+    /// [`recv`] to detect that case.
+    ///
+    /// [`recv`]: WatchReceiver::recv
+    pub fn wait(&mut self) -> T {
+        T::clone(&self.wait_shared())
+    }
+
+    /// Wait until a new value becomes available, and move it out of the
+    /// channel, leaving `T::default()` in its place.
+    ///
+    /// This is meant for channels with a single receiver that are used as a
+    /// slot for the latest job: senders overwrite the pending job, and the
+    /// receiver takes it. The default left behind keeps the version of the
+    /// value it replaced, so it is not a new value, and a value sent while
+    /// this takes one is left for the next call. Other receivers of the
+    /// channel would see the default instead of the value.
+    pub fn wait_take(&mut self) -> T
+    where
+        T: Default,
+    {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(self.shared.wait_while(state, |state| state.version == seen));
+
+        self.track(|shared, seen| shared.take(seen))
+    }
+
+    /// This method waits until a new value becomes available and overwrites
+    /// `dst` with it.
+    ///
+    /// See [`wait`](WatchReceiver::wait) and
+    /// [`get_into`](WatchReceiver::get_into).
+    pub fn wait_into(&mut self, dst: &mut T) {
+        self.track(|shared, seen| shared.wait_into(seen, dst));
+    }
+
+    /// Wait until at least `n` values have been sent since the one this
+    /// receiver saw last, and return a clone of the latest value.
+    ///
+    /// Values that were sent before this call but that the receiver has not
+    /// seen count towards `n`, so this returns at once if there already are
+    /// `n` of them, and `n == 0` never waits. If every sender has been
+    /// dropped first, this waits forever.
+    pub fn wait_n_updates(&mut self, n: u64) -> T {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(
+            self.shared
+                .wait_while(state, |state| state.version.wrapping_sub(seen) < n),
+        );
+
+        self.get()
+    }
+
+    /// Like [`wait`], but fails once every sender has been dropped.
+    ///
+    /// A value sent before the last sender was dropped is still returned if
+    /// it has not been seen.
+    ///
+    /// [`wait`]: WatchReceiver::wait
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        let filter = self.filter();
+        loop {
+            let seen = self.last_seen_version;
+            let state = self.shared.state.lock();
+            let state = self.shared.wait_while_filtered(state, filter, |state| {
+                state.version == seen && state.is_open()
+            });
+            if state.version == seen {
+                return Err(RecvError);
+            }
+            drop(state);
+
+            let value = self.get_shared();
+            if self.interested(&value) {
+                return Ok(T::clone(&value));
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// This method waits until a new value becomes available and return a clone
+    /// of it, timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
+        let deadline = self.shared.deadline(duration);
+        let value = self.wait_shared_until(&deadline)?;
+        Some(T::clone(&value))
+    }
+
+    /// Like [`wait_take`](WatchReceiver::wait_take), but gives up after
+    /// `duration`, without taking anything.
+    pub fn wait_take_timeout(&mut self, duration: Duration) -> Option<T>
+    where
+        T: Default,
+    {
+        let seen = self.last_seen_version;
+        let deadline = self.shared.deadline(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) = self
+            .shared
+            .wait_while_until(state, deadline, |state| state.version == seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.track(|shared, seen| shared.take(seen)))
+    }
+
+    /// Like [`wait_timeout`], but fails once every sender has been dropped.
+    ///
+    /// [`wait_timeout`]: WatchReceiver::wait_timeout
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        let filter = self.filter();
+        let deadline = self.shared.deadline(duration);
+        loop {
+            let seen = self.last_seen_version;
+            let state = self.shared.state.lock();
+            let (state, ready) =
+                self.shared
+                    .wait_while_until_filtered(state, deadline.clone(), filter, |state| {
+                        state.version == seen && state.is_open()
+                    });
+            if !ready {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            if state.version == seen {
+                return Err(RecvTimeoutError::Closed);
+            }
+            drop(state);
+
+            let value = self.get_shared();
+            if self.interested(&value) {
+                return Ok(T::clone(&value));
+            }
+        }
+    }
+
+    /// Like [`wait_n_updates`], but gives up after `duration`.
+    ///
+    /// Nothing is marked seen if this times out.
+    ///
+    /// [`wait_n_updates`]: WatchReceiver::wait_n_updates
+    pub fn wait_n_updates_timeout(&mut self, n: u64, duration: Duration) -> Option<T> {
+        let seen = self.last_seen_version;
+        let deadline = self.shared.deadline(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) = self.shared.wait_while_until(state, deadline, |state| {
+            state.version.wrapping_sub(seen) < n
+        });
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.get())
+    }
+
+    /// Wait until the value differs from `old`, and return a clone of it.
+    ///
+    /// The latest value is compared first, even if this receiver has seen
+    /// it, so this returns at once if it already differs. Otherwise new
+    /// values that are equal to `old` are marked seen while waiting. Returns
+    /// `None` if `timeout` passes before a different value arrives.
+    pub fn wait_until_distinct_from(&mut self, old: &T, timeout: Duration) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        let latest = self.get_shared();
+        if *latest != *old {
+            return Some(T::clone(&latest));
+        }
+        drop(latest);
+        self.wait_accepted_timeout(timeout, |value| (value != old).then(|| value.clone()))
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Get a shared handle to the latest value sent on the channel.
+    ///
+    /// This only clones an `Arc`, however large the value is, and does not
+    /// need `T: Clone`. The handle is a snapshot: later messages replace the
+    /// value in the channel rather than changing the one behind the handle.
+    /// The value is only dropped once every handle to it is gone, and any
+    /// interior mutability in `T` is visible to every holder of a handle.
+    pub fn get_shared(&mut self) -> Arc<T> {
+        self.track(|shared, seen| shared.get_shared(seen))
+    }
+
+    /// Get a shared handle to the latest value if that value has not
+    /// previously been seen by this receiver.
+    ///
+    /// See [`get_shared`](WatchReceiver::get_shared).
+    pub fn get_if_new_shared(&mut self) -> Option<Arc<T>> {
+        let value = self.track(|shared, seen| shared.get_if_new_shared(seen))?;
+        self.interested(&value).then_some(value)
+    }
+
+    /// This method waits until a new value becomes available and returns a
+    /// shared handle to it.
+    ///
+    /// See [`get_shared`](WatchReceiver::get_shared) and
+    /// [`wait`](WatchReceiver::wait).
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    pub fn wait_shared(&mut self) -> Arc<T> {
+        let filter = self.filter();
+        loop {
+            let value = self.track(|shared, seen| shared.wait_shared(seen, filter));
+            if self.interested(&value) {
+                return value;
+            }
+        }
+    }
+
+    /// Wait until a new value becomes available, and return what `f` makes
+    /// of it.
+    ///
+    /// Unlike [`wait`](WatchReceiver::wait), this does not clone the value:
+    /// `f` runs while the value is read-locked, so senders wait for it, and
+    /// it must not use the same channel. The value is marked seen once `f`
+    /// returns, so if `f` panics, the channel is left as it was and the value
+    /// stays unseen.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    pub fn wait_map<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.track(|shared, seen| shared.wait_map(seen, f))
+    }
+
+    /// Like [`wait_map`](WatchReceiver::wait_map), but gives up after
+    /// `duration`, without calling `f`.
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    pub fn wait_map_timeout<R, F>(&mut self, duration: Duration, f: F) -> Option<R>
+    where
+        C: RawCondvarTimeout,
+        F: FnOnce(&T) -> R,
+    {
+        self.track(|shared, seen| shared.wait_map_timeout(seen, duration, f))
+    }
+
+    /// Like [`clone`](Clone::clone), but fails rather than panicking if the
+    /// channel already has as many receivers as
+    /// [`ChannelBuilder::max_receivers`] allows.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn try_clone(&self) -> Result<WatchReceiver<T, C, A>, TooManyReceivers> {
+        try_new_receiver(&self.shared, self.last_seen_version)
+    }
+
+    /// Create a new sender for this channel.
+    ///
+    /// This reopens the channel if every other sender has been dropped.
+    pub fn new_sender(&self) -> WatchSender<T, C, A> {
+        new_sender(&self.shared)
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.has_changed(self.last_seen_version)
+    }
+
+    /// Only wake this receiver for the values that `f` returns `true` for.
+    ///
+    /// While this receiver waits in [`wait`](WatchReceiver::wait),
+    /// [`recv`](WatchReceiver::recv), their timed forms or
+    /// [`wait_shared`](WatchReceiver::wait_shared), the senders run `f` on
+    /// each new value and leave the thread asleep if it returns `false`, so
+    /// a receiver that is interested in few values is not woken for the
+    /// others. Those waits, [`get_if_new`](WatchReceiver::get_if_new) and
+    /// [`get_if_new_shared`](WatchReceiver::get_if_new_shared) mark such
+    /// values seen without returning them. Other reads, such as
+    /// [`get`](WatchReceiver::get) or `changed`, return the latest value
+    /// whatever `f` says.
+    ///
+    /// The senders run `f` with the channel locked, so it should be quick,
+    /// and it must not use the same channel. Setting another interest
+    /// replaces this one, and clones of the receiver have none.
+    pub fn set_interest<F>(&mut self, f: F)
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.interest = Some(Arc::new(f));
+    }
+
+    /// Returns `false` if the interest of this receiver passes over
+    /// `value`.
+    fn interested(&self, value: &T) -> bool {
+        match &self.interest {
+            Some(interest) => interest(value),
+            None => true,
+        }
+    }
+
+    /// The interest of this receiver, for the senders to check while it
+    /// waits.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn filter(&self) -> Option<waiters::Filter> {
+        self.interest.as_ref().map(waiters::Filter::new)
+    }
+
+    /// Run a read that may mark a new value seen, and report the version
+    /// seen afterwards if the channel tracks lag.
+    fn track<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&Shared<T, C>, &mut u64) -> R,
+    {
+        let result = f(&self.shared, &mut self.last_seen_version);
+        self.cursor.report(self.last_seen_version);
+        result
+    }
+
+    /// Wait for new values until `accept` returns something for one of
+    /// them.
+    ///
+    /// Every value that is passed over is marked seen.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_accepted<U, F>(&mut self, mut accept: F) -> U
+    where
+        F: FnMut(&T) -> Option<U>,
+    {
+        loop {
+            if let Some(value) = accept(&self.wait_shared()) {
+                return value;
+            }
+        }
+    }
+
+    /// Like [`wait_accepted`](WatchReceiver::wait_accepted), but gives up
+    /// after `duration`.
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn wait_accepted_timeout<U, F>(&mut self, duration: Duration, mut accept: F) -> Option<U>
+    where
+        C: RawCondvarTimeout,
+        F: FnMut(&T) -> Option<U>,
+    {
+        let deadline = self.shared.deadline(duration);
+        loop {
+            let shared = self.wait_shared_until(&deadline)?;
+            if let Some(value) = accept(&shared) {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Wait for a new value until `deadline`, and return a shared handle to
+    /// it.
+    ///
+    /// Waits that share a deadline give up together, however many values
+    /// each of them returns.
+    #[cfg(all(
+        feature = "std",
+        any(not(target_family = "wasm"), target_feature = "atomics")
+    ))]
+    fn wait_shared_until(&mut self, deadline: &Deadline) -> Option<Arc<T>>
+    where
+        C: RawCondvarTimeout,
+    {
+        let filter = self.filter();
+        loop {
+            let seen = self.last_seen_version;
+            let state = self.shared.state.lock();
+            let (state, ready) =
+                self.shared
+                    .wait_while_until_filtered(state, deadline.clone(), filter, |state| {
+                        state.version == seen
+                    });
+            if !ready {
+                return None;
+            }
+            drop(state);
+
+            let value = self.get_shared();
+            if self.interested(&value) {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        !self.shared.state.lock().is_open()
+    }
+
+    /// The sender that wrote the latest value.
+    ///
+    /// This does not mark the value seen, and it may have been replaced by
+    /// the time this returns. Use [`get_with_writer`] to get a value together
+    /// with its writer.
+    ///
+    /// [`get_with_writer`]: WatchReceiver::get_with_writer
+    pub fn last_writer(&self) -> SenderId {
+        self.shared.value.read().writer
+    }
+
+    /// Returns `true` if both receivers belong to the same channel.
+    pub fn same_channel(&self, other: &WatchReceiver<T, C, A>) -> bool {
+        SharedArc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    /// Returns `true` if `sender` sends on the channel of this receiver.
+    pub fn same_channel_as(&self, sender: &WatchSender<T, C, A>) -> bool {
+        SharedArc::ptr_eq(&self.shared, &sender.shared)
+    }
+
+    /// Get the identity of the channel that this receiver belongs to.
+    ///
+    /// See [`ChannelId`].
+    pub fn channel_id(&self) -> ChannelId {
+        ChannelId::of(&self.shared)
+    }
+
+    /// Take the latest value out of the channel, if this is its last handle.
+    ///
+    /// See [`WatchSender::try_into_inner`]. On failure, the receiver is given
+    /// back with the same values seen.
+    pub fn try_into_inner(self) -> Result<T, WatchReceiver<T, C, A>> {
+        let this = core::mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped afterwards, and its fields
+        // are either moved into the result or dropped here.
+        let (shared, cursor, interest) = unsafe {
+            (
+                core::ptr::read(&this.shared),
+                core::ptr::read(&this.cursor),
+                core::ptr::read(&this.interest),
+            )
+        };
+        match try_into_inner(shared) {
+            Ok(value) => Ok(value),
+            Err(shared) => Err(WatchReceiver {
+                shared,
+                last_seen_version: this.last_seen_version,
+                cursor,
+                interest,
+                #[cfg(feature = "debug-handles")]
+                origin: this.origin,
+            }),
+        }
+    }
+}
+
+/// Creates another receiver for the channel, which has seen
+/// `last_seen_version`.
+///
+/// Panics if the channel already has as many receivers as it allows.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+fn new_receiver<T, C: RawCondvar, A: Allocator + Clone>(
+    shared: &SharedArc<Shared<T, C>, A>,
+    last_seen_version: u64,
+) -> WatchReceiver<T, C, A> {
+    match try_new_receiver(shared, last_seen_version) {
+        Ok(receiver) => receiver,
+        Err(TooManyReceivers) => too_many_receivers(shared.state.lock().max_receivers),
+    }
+}
+
+/// Like `new_receiver`, but fails if the channel already has as many
+/// receivers as it allows.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+fn try_new_receiver<T, C: RawCondvar, A: Allocator + Clone>(
+    shared: &SharedArc<Shared<T, C>, A>,
+    last_seen_version: u64,
+) -> Result<WatchReceiver<T, C, A>, TooManyReceivers> {
+    let mut state = shared.state.lock();
+    if state.receivers >= state.max_receivers {
+        return Err(TooManyReceivers);
+    }
+    state.receivers += 1;
+    state.receivers_changed();
+    #[cfg(feature = "debug-handles")]
+    let origin = state.origins.add();
+    drop(state);
+    Ok(WatchReceiver {
+        shared: shared.clone(),
+        last_seen_version,
+        cursor: shared.cursor(last_seen_version),
+        interest: None,
+        #[cfg(feature = "debug-handles")]
+        origin,
+    })
+}
+
+#[cold]
+fn too_many_receivers(max: usize) -> ! {
+    panic!(
+        "a watch channel that allows at most {} receivers was asked for another",
+        max
+    );
+}
+
+/// Creates another receiver for the channel that has seen the latest value,
+/// and returns it with a clone of that value.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+fn subscribe_with_value<T: Clone, C: RawCondvar, A: Allocator + Clone>(
+    shared: &SharedArc<Shared<T, C>, A>,
+) -> (WatchReceiver<T, C, A>, T) {
+    // The value and its version are taken under the same lock.
+    let mut seen = 0;
+    let value = shared.get_shared(&mut seen);
+    (new_receiver(shared, seen), T::clone(&value))
+}
+
+/// Takes the latest value out of the channel if `shared` is its last handle,
+/// or gives `shared` back.
+fn try_into_inner<T, C: RawCondvar, A: Allocator>(
+    shared: SharedArc<Shared<T, C>, A>,
+) -> Result<T, SharedArc<Shared<T, C>, A>> {
+    // Only handles can share the value, so once this is the last one, no new
+    // `Arc` of the value can appear. The registry may still upgrade its weak
+    // reference for a moment, in which case the unwrap fails.
+    if SharedArc::strong_count(&shared) != 1 || !shared.value_is_unshared() {
+        return Err(shared);
+    }
+    SharedArc::try_unwrap(shared).map(Shared::into_value)
+}
+
+/// Creates another sender for the channel.
+fn new_sender<T, C: RawCondvar, A: Allocator + Clone>(
+    shared: &SharedArc<Shared<T, C>, A>,
+) -> WatchSender<T, C, A> {
+    let mut state = shared.state.lock();
+    state.senders += 1;
+    let id = SenderId(state.next_sender);
+    state.next_sender += 1;
+    drop(state);
+    WatchSender {
+        shared: shared.clone(),
+        id,
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Drop for WatchReceiver<T, C, A> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.receivers -= 1;
+        #[cfg(feature = "debug-handles")]
+        state.origins.remove(self.origin);
+        state.receivers_changed();
+        #[cfg(feature = "embedded-async")]
+        if state.receivers == 0 {
+            state.closings = state.closings.wrapping_add(1);
+            state.closed_wakers.wake_all();
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Drop for WatchSender<T, C, A> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        self.shared.release_sender(&mut state);
+    }
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// Count a sender as gone, and close the channel if it was the last one.
+    fn release_sender(&self, state: &mut SharedState) {
+        state.senders -= 1;
+        if state.senders == 0 && !state.closed {
+            diagnostic!(trace, channel = self.id(), "channel closed");
+            state.notify_all();
+        }
+    }
+
+    /// Close the channel while senders are left, see [`WatchSender::close`].
+    fn close(&self) {
+        let mut state = self.state.lock();
+        if state.is_open() {
+            diagnostic!(trace, channel = self.id(), "channel closed");
+            state.notify_all();
+        }
+        state.closed = true;
+    }
+}
+
+#[cfg(all(test, target_has_atomic = "64"))]
+mod tests {
+    use super::*;
+    use core::mem::{align_of_val, size_of};
+
+    fn line(address: *const u8, size: usize) -> usize {
+        address as usize / size
+    }
+
+    #[test]
+    fn hot_fields_have_lines_of_their_own() {
+        let shared = Shared::<u8, backend::DefaultCondvar>::new(0, 1);
+        let size = align_of_val(&shared.latest);
+        let value = &*shared.value as *const _ as *const u8;
+        let latest = &*shared.latest as *const _ as *const u8;
+        let state = &shared.state as *const _ as *const u8;
+        assert_ne!(line(value, size), line(latest, size));
+        assert_ne!(line(latest, size), line(state, size));
+        assert_ne!(line(value, size), line(state, size));
+    }
+
+    #[test]
+    fn tiny_channels_stay_small() {
+        // Two padded fields and the rest, which is a few lines at most.
+        let size = size_of::<Shared<u8, backend::DefaultCondvar>>();
+        assert!(size <= 8 * 128, "{} bytes", size);
+    }
 }
@@ -0,0 +1,166 @@
+//! A watch channel for use within a single thread.
+//!
+//! The handles share the channel through an `Rc` and a `RefCell` rather than
+//! an `Arc` and a mutex, so they cost no atomic operations. They are neither
+//! `Send` nor `Sync`, and since no other thread can send a value while the
+//! receiver waits, there are no blocking methods.
+//!
+//! Reading or sending a value from within the closure passed to
+//! [`LocalSender::update`] panics, as does sending a value from within the
+//! closure passed to [`LocalSender::update_with`].
+use crate::SharedValue;
+use alloc::rc::Rc;
+use core::cell::{Cell, RefCell, RefMut};
+
+/// The sender for a local watch channel.
+///
+/// The sender can be cloned to obtain multiple senders for the same channel.
+pub struct LocalSender<T> {
+    shared: Rc<LocalShared<T>>,
+}
+
+/// The receiver for a local watch channel.
+///
+/// The receiver can be cloned. Each clone will yield a new receiver that
+/// receives the same messages.
+pub struct LocalReceiver<T> {
+    shared: Rc<LocalShared<T>>,
+    last_seen_version: u64,
+}
+
+struct LocalShared<T> {
+    value: RefCell<SharedValue<T>>,
+    senders: Cell<usize>,
+}
+
+/// Creates a new local watch channel.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+pub fn channel<T>(value: T) -> (LocalSender<T>, LocalReceiver<T>) {
+    let shared = Rc::new(LocalShared {
+        value: RefCell::new(SharedValue::new(value, 1)),
+        senders: Cell::new(1),
+    });
+    (
+        LocalSender {
+            shared: shared.clone(),
+        },
+        LocalReceiver {
+            shared,
+            last_seen_version: 0,
+        },
+    )
+}
+
+impl<T> LocalSender<T> {
+    /// Send a new message.
+    pub fn send(&self, value: T) {
+        let old = self.shared.value.borrow_mut().replace(value);
+
+        // Destroy old value after releasing the borrow.
+        drop(old);
+    }
+
+    /// Update the message by a closure.
+    ///
+    /// If `f` panics, the value keeps the changes made before the panic, and
+    /// it still counts as a new value.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut value = UpdateGuard(self.shared.value.borrow_mut());
+        f(&mut value.0.value);
+    }
+
+    /// Replace the message by the result of a closure.
+    pub fn update_with<F>(&self, f: F)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let new = f(&self.shared.value.borrow().value);
+        self.send(new);
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    pub fn subscribe(&self) -> LocalReceiver<T> {
+        LocalReceiver {
+            shared: self.shared.clone(),
+            last_seen_version: self.shared.value.borrow().version,
+        }
+    }
+}
+
+/// Gives the value a new version when an update ends, even if the closure
+/// that changed it panicked.
+struct UpdateGuard<'a, T>(RefMut<'a, SharedValue<T>>);
+
+impl<T> Drop for UpdateGuard<'_, T> {
+    fn drop(&mut self) {
+        self.0.changed();
+    }
+}
+
+impl<T: Clone> LocalReceiver<T> {
+    /// Get a clone of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        let value = self.shared.value.borrow();
+        value.get(&mut self.last_seen_version).clone()
+    }
+
+    /// Get a clone of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        let value = self.shared.value.borrow();
+        value.get_if_new(&mut self.last_seen_version).cloned()
+    }
+}
+
+impl<T> LocalReceiver<T> {
+    /// Create a new sender for this channel.
+    ///
+    /// This reopens the channel if every other sender has been dropped.
+    pub fn new_sender(&self) -> LocalSender<T> {
+        self.shared.senders.set(self.shared.senders.get() + 1);
+        LocalSender {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.value.borrow().version != self.last_seen_version
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.senders.get() == 0
+    }
+}
+
+impl<T> Clone for LocalSender<T> {
+    fn clone(&self) -> LocalSender<T> {
+        self.shared.senders.set(self.shared.senders.get() + 1);
+        LocalSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Clone for LocalReceiver<T> {
+    fn clone(&self) -> LocalReceiver<T> {
+        LocalReceiver {
+            shared: self.shared.clone(),
+            last_seen_version: self.last_seen_version,
+        }
+    }
+}
+
+impl<T> Drop for LocalSender<T> {
+    fn drop(&mut self) {
+        self.shared.senders.set(self.shared.senders.get() - 1);
+    }
+}
@@ -0,0 +1,59 @@
+//! Warns when the value of a channel stays locked for too long, see
+//! [`ChannelBuilder::slow_lock_threshold`](crate::ChannelBuilder::slow_lock_threshold).
+//!
+//! Without the `lock-timing` feature, the timer holds nothing and reading
+//! the clock is compiled out.
+use crate::{backend::RawCondvar, Shared};
+#[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+use std::time::Instant;
+
+/// How long a channel allows its value to stay locked before warning,
+/// unless its builder says otherwise.
+#[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+pub(crate) const DEFAULT_THRESHOLD: core::time::Duration = core::time::Duration::from_millis(5);
+
+/// When an operation locked the value, see [`Shared::lock_timer`].
+pub(crate) struct LockTimer {
+    #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+    operation: &'static str,
+    #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+    locked_at: Instant,
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// Start timing how long `operation` holds the value, which it has just
+    /// locked.
+    #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+    pub(crate) fn lock_timer(&self, operation: &'static str) -> LockTimer {
+        LockTimer {
+            operation,
+            locked_at: self.clock.now(),
+        }
+    }
+
+    #[cfg(not(all(feature = "lock-timing", not(target_family = "wasm"))))]
+    #[inline(always)]
+    pub(crate) fn lock_timer(&self, _operation: &'static str) -> LockTimer {
+        LockTimer {}
+    }
+
+    /// Warn if the value was locked for longer than the threshold of the
+    /// channel, once the operation that `timer` times has released it.
+    #[cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+    pub(crate) fn lock_released(&self, timer: LockTimer) {
+        let held = self.clock.now().saturating_duration_since(timer.locked_at);
+        if held > self.slow_lock {
+            diagnostic!(
+                warn,
+                channel = self.id(),
+                operation = timer.operation,
+                held = ?held,
+                "value locked for too long"
+            );
+        }
+    }
+
+    #[cfg(not(all(feature = "lock-timing", not(target_family = "wasm"))))]
+    #[inline(always)]
+    pub(crate) fn lock_released(&self, _timer: LockTimer) {}
+}
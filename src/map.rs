@@ -0,0 +1,272 @@
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{backend::RawCondvarTimeout, RecvError, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use alloc::{boxed::Box, sync::Arc};
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use core::time::Duration;
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+};
+use lock_api::RwLock;
+use std::collections::{hash_map::RandomState, HashMap};
+
+/// How many parts the keys of a map are split into. Each part has a lock of
+/// its own, which is only write-locked while keys are added or removed.
+const SHARDS: usize = 16;
+
+type Shard<K, T, C> = RwLock<<C as RawCondvar>::RawRwLock, HashMap<K, Slot<T, C>>>;
+
+/// The channel of a key. It starts out with `None` if the key was subscribed
+/// to before a value was sent for it, and that value always counts as seen,
+/// so receivers only ever get `Some` as a new value.
+type Slot<T, C> = WatchSender<Option<T>, C>;
+
+/// The sender for a map of channels created by [`watch_map`].
+///
+/// Every key of the map has a channel of its own, with its own version, which
+/// is created once the key is first sent to or subscribed to. Sends to
+/// different keys do not wait for each other, apart from briefly when a key
+/// is added or removed.
+///
+/// The sender can be cloned to obtain multiple senders for the same map.
+pub struct MapSender<K, T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<MapShared<K, T, C>>,
+}
+
+/// The receiver for one key of a map created by [`watch_map`].
+///
+/// This is created by [`MapSender::subscribe`]. Once its key is removed from
+/// the map, the receiver keeps the last value of the key but receives no
+/// more values, even if the key is added again. It can be cloned, and each
+/// clone receives the same values.
+pub struct MapReceiver<T, C: RawCondvar = DefaultCondvar> {
+    inner: WatchReceiver<Option<T>, C>,
+}
+
+struct MapShared<K, T, C: RawCondvar> {
+    hasher: RandomState,
+    shards: Box<[Shard<K, T, C>]>,
+}
+
+/// Creates a new, empty map of watch channels.
+///
+/// See [`MapSender`].
+pub fn watch_map<K: Eq + Hash, T>() -> MapSender<K, T> {
+    new()
+}
+
+pub(crate) fn new<K: Eq + Hash, T, C: RawCondvar>() -> MapSender<K, T, C> {
+    let shards = (0..SHARDS).map(|_| RwLock::new(HashMap::new())).collect();
+    MapSender {
+        shared: Arc::new(MapShared {
+            hasher: RandomState::new(),
+            shards,
+        }),
+    }
+}
+
+impl<K: Eq + Hash, T, C: RawCondvar> MapShared<K, T, C> {
+    fn shard<Q>(&self, key: &Q) -> &Shard<K, T, C>
+    where
+        Q: Hash + ?Sized,
+    {
+        &self.shards[self.hasher.hash_one(key) as usize % SHARDS]
+    }
+}
+
+impl<K: Eq + Hash, T, C: RawCondvar> MapSender<K, T, C> {
+    /// Send a new value for `key` and notify the receivers of that key that
+    /// are currently waiting for a value.
+    ///
+    /// The key is added to the map if it is not in it.
+    pub fn send(&self, key: K, value: T) {
+        let shard = self.shared.shard(&key);
+        if let Some(slot) = shard.read().get(&key) {
+            slot.send(Some(value));
+            return;
+        }
+        let mut slots = shard.write();
+        match slots.get(&key) {
+            Some(slot) => slot.send(Some(value)),
+            None => {
+                let (slot, _) = builder().channel_with(Some(value));
+                slots.insert(key, slot);
+            }
+        }
+    }
+
+    /// Create a new receiver for `key`.
+    ///
+    /// Like [`WatchSender::subscribe`], the value of the key when this is
+    /// called is considered seen by the new receiver. If the key is not in
+    /// the map, it is added without a value, and the receiver receives the
+    /// first value sent for it.
+    pub fn subscribe(&self, key: K) -> MapReceiver<T, C> {
+        let shard = self.shared.shard(&key);
+        if let Some(slot) = shard.read().get(&key) {
+            return MapReceiver {
+                inner: slot.subscribe(),
+            };
+        }
+        let slot = shard
+            .write()
+            .entry(key)
+            .or_insert_with(|| builder().initial_seen(true).channel_with(None).0)
+            .subscribe();
+        MapReceiver { inner: slot }
+    }
+
+    /// Remove `key` from the map and return its last value.
+    ///
+    /// The receivers of the key see it as closed, see
+    /// [`MapReceiver::is_removed`]. Returns `None` if the key is not in the
+    /// map or was never sent a value.
+    pub fn remove<Q>(&self, key: &Q) -> Option<T>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        T: Clone,
+    {
+        let slot = self.shared.shard(key).write().remove(key)?;
+        let value = slot.subscribe().get();
+        // The slot is dropped after the lock is released, which closes it.
+        drop(slot);
+        value
+    }
+
+    /// Get a clone of the latest value of `key`, without subscribing to it.
+    ///
+    /// Returns `None` if the key is not in the map or was never sent a value.
+    pub fn get<Q>(&self, key: &Q) -> Option<T>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        T: Clone,
+    {
+        let mut receiver = self.shared.shard(key).read().get(key)?.subscribe();
+        receiver.get()
+    }
+
+    /// Returns `true` if `key` is in the map.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.shared.shard(key).read().contains_key(key)
+    }
+
+    /// The number of keys in the map.
+    ///
+    /// The parts of the map are counted one after another, so the count may
+    /// be out of date if keys are added or removed at the same time.
+    pub fn len(&self) -> usize {
+        self.shared
+            .shards
+            .iter()
+            .map(|shard| shard.read().len())
+            .sum()
+    }
+
+    /// Returns `true` if the map has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, T, C: RawCondvar> Clone for MapSender<K, T, C> {
+    fn clone(&self) -> MapSender<K, T, C> {
+        MapSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<K, T, C: RawCondvar> fmt::Debug for MapSender<K, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapSender").finish_non_exhaustive()
+    }
+}
+
+impl<T: Clone, C: RawCondvar> MapReceiver<T, C> {
+    /// Get a clone of the latest value of the key, or `None` if no value has
+    /// been sent for it.
+    pub fn get(&mut self) -> Option<T> {
+        self.inner.get()
+    }
+
+    /// Get a clone of the latest value of the key if it has not previously
+    /// been seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        self.inner.get_if_new().flatten()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar> MapReceiver<T, C> {
+    /// Wait until a new value is sent for the key and return a clone of it.
+    ///
+    /// If the key has been removed, this waits forever. Use [`recv`] to
+    /// detect that case.
+    ///
+    /// [`recv`]: MapReceiver::recv
+    pub fn wait(&mut self) -> T {
+        sent(self.inner.wait())
+    }
+
+    /// Like [`wait`], but fails once the key has been removed or every
+    /// sender of the map has been dropped.
+    ///
+    /// [`wait`]: MapReceiver::wait
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        self.inner.recv().map(sent)
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvarTimeout> MapReceiver<T, C> {
+    /// Like [`recv`], but gives up after `duration`.
+    ///
+    /// [`recv`]: MapReceiver::recv
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        self.inner.recv_timeout(duration).map(sent)
+    }
+}
+
+/// Unwrap a value that a receiver got as new, see [`Slot`].
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+fn sent<T>(value: Option<T>) -> T {
+    value.expect("the starting `None` of a key counts as seen")
+}
+
+impl<T, C: RawCondvar> MapReceiver<T, C> {
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.inner.has_changed()
+    }
+
+    /// Returns `true` if the key has been removed from the map, or every
+    /// sender of the map has been dropped.
+    pub fn is_removed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+impl<T, C: RawCondvar> Clone for MapReceiver<T, C> {
+    fn clone(&self) -> MapReceiver<T, C> {
+        MapReceiver {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar> fmt::Debug for MapReceiver<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MapReceiver").field(&self.inner).finish()
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{backend::RawCondvar, Allocator, Shared, WatchSender};
+use alloc::borrow::Cow;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use metrics::{Counter, Gauge, Label};
+
+/// How many events a counter collects before it is flushed to the recorder.
+const BATCH: usize = 64;
+
+/// The series that a channel created with
+/// [`ChannelBuilder::metrics_label`](crate::ChannelBuilder::metrics_label)
+/// updates. The receiver count is kept by the state, next to the count.
+pub(crate) struct ChannelMetrics {
+    sends: Batched,
+    wakeups: Batched,
+    /// Only updated by [`WatchSender::flush_metrics`], if the channel tracks
+    /// lag.
+    max_lag: Gauge,
+}
+
+/// A counter whose increments are collected in an atomic, and handed to the
+/// recorder in batches, so that the hot paths do not call into it.
+struct Batched {
+    pending: AtomicUsize,
+    counter: Counter,
+}
+
+impl Batched {
+    fn new(counter: Counter) -> Batched {
+        Batched {
+            pending: AtomicUsize::new(0),
+            counter,
+        }
+    }
+
+    /// Count one event, and flush the batch if it is full.
+    fn add(&self) {
+        if self.pending.fetch_add(1, Ordering::Relaxed) + 1 >= BATCH {
+            self.flush();
+        }
+    }
+
+    fn flush(&self) {
+        let pending = self.pending.swap(0, Ordering::Relaxed);
+        if pending > 0 {
+            self.counter.increment(pending as u64);
+        }
+    }
+}
+
+impl ChannelMetrics {
+    /// Register the series labeled with `label`, and the gauge of the
+    /// receiver count, which the state keeps.
+    pub(crate) fn register(label: Cow<'static, str>) -> (ChannelMetrics, Gauge) {
+        let labels = [Label::new("channel", label)];
+        let metrics = ChannelMetrics {
+            sends: Batched::new(metrics::counter!("watch_sends", labels.iter())),
+            wakeups: Batched::new(metrics::counter!("watch_wakeups", labels.iter())),
+            max_lag: metrics::gauge!("watch_max_lag", labels.iter()),
+        };
+        let receivers = metrics::gauge!("watch_receivers", labels.iter());
+        (metrics, receivers)
+    }
+
+    /// Count a new value.
+    pub(crate) fn sent(&self) {
+        self.sends.add();
+    }
+
+    /// Count a thread waking up from a wait.
+    pub(crate) fn woke(&self) {
+        self.wakeups.add();
+    }
+}
+
+impl Drop for ChannelMetrics {
+    fn drop(&mut self) {
+        self.sends.flush();
+        self.wakeups.flush();
+    }
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// Hand the counted events to the recorder, and update the lag gauge.
+    fn flush_metrics(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.sends.flush();
+            metrics.wakeups.flush();
+            #[cfg(target_has_atomic = "64")]
+            if let Some(cursors) = &self.cursors {
+                let lag = cursors.lock().max_lag(self.version()).unwrap_or(0);
+                metrics.max_lag.set(lag as f64);
+            }
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Hand the sends and wakeups counted so far to the `metrics` recorder,
+    /// and update the gauge of the largest lag.
+    ///
+    /// The counters are otherwise flushed in batches, by the sends and
+    /// wakeups themselves, and when the channel is dropped, so a recorder
+    /// may lag behind by a few events. This does nothing unless the channel
+    /// was created with
+    /// [`ChannelBuilder::metrics_label`](crate::ChannelBuilder::metrics_label).
+    pub fn flush_metrics(&self) {
+        self.shared.flush_metrics();
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use alloc::sync::Arc;
+use core::fmt;
+
+/// The sender for a channel created by [`monotonic_channel`].
+///
+/// The sender can be cloned to obtain multiple senders for the same channel.
+pub struct MonotonicSender<T, C: RawCondvar = DefaultCondvar> {
+    inner: WatchSender<T, C>,
+    allow_equal: bool,
+}
+
+/// Error returned by a [`MonotonicSender`] for a value that is not newer
+/// than the one in the channel.
+///
+/// It holds the rejected value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Regression<T>(pub T);
+
+impl<T> fmt::Display for Regression<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value is older than the one in the monotonic channel")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> std::error::Error for Regression<T> {}
+
+/// Creates a new channel whose value only moves forward, such as a
+/// generation number or a logical timestamp.
+///
+/// The sender rejects any value that is not greater than the one in the
+/// channel, so receivers never see the value go back, even if a producer
+/// that lags behind sends an older one. Values that cannot be compared,
+/// such as `NaN`, are rejected too. The receiver is a plain
+/// [`WatchReceiver`], which has not seen the starting value.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn monotonic_channel<T: PartialOrd>(initial: T) -> (MonotonicSender<T>, WatchReceiver<T>) {
+    new(initial)
+}
+
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub(crate) fn new<T: PartialOrd, C: RawCondvar>(
+    initial: T,
+) -> (MonotonicSender<T, C>, WatchReceiver<T, C>) {
+    let (sender, receiver) = builder().channel_with(initial);
+    (
+        MonotonicSender {
+            inner: sender,
+            allow_equal: false,
+        },
+        receiver,
+    )
+}
+
+impl<T: PartialOrd, C: RawCondvar> MonotonicSender<T, C> {
+    /// Also accept values equal to the one in the channel.
+    ///
+    /// An equal value is sent like any other, so it gets a new version and
+    /// notifies the receivers. This applies to this sender and to the clones
+    /// made of it afterwards.
+    pub fn allow_equal(mut self, allow: bool) -> Self {
+        self.allow_equal = allow;
+        self
+    }
+
+    /// Send `value` if it is newer than the value in the channel.
+    ///
+    /// Otherwise the value is given back, and the channel is left as it is,
+    /// without notifying anyone. The comparison is made while the value is
+    /// locked, so senders that race cannot move the value back either.
+    pub fn send(&self, value: T) -> Result<(), Regression<T>> {
+        self.send_if_newer("send", |_| value)
+    }
+
+    /// Replace the value by the result of a closure, if that is newer than
+    /// the value in the channel.
+    ///
+    /// Otherwise the result is given back as with [`send`], and the value is
+    /// kept.
+    ///
+    /// [`send`]: MonotonicSender::send
+    pub fn update_with<F>(&self, f: F) -> Result<(), Regression<T>>
+    where
+        F: FnOnce(&T) -> T,
+    {
+        self.send_if_newer("update_with", f)
+    }
+
+    fn send_if_newer<F>(&self, operation: &'static str, f: F) -> Result<(), Regression<T>>
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let shared = &self.inner.shared;
+        let lock = shared.value.write();
+        let value = {
+            let _scope = shared.value.enter(operation);
+            f(&lock.value)
+        };
+        let newer = match value.partial_cmp(&lock.value) {
+            Some(core::cmp::Ordering::Greater) => true,
+            Some(core::cmp::Ordering::Equal) => self.allow_equal,
+            _ => false,
+        };
+        if !newer {
+            shared.unlock_value(lock);
+            return Err(Regression(value));
+        }
+        shared.publish(lock, Arc::new(value), self.inner.id);
+        Ok(())
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn subscribe(&self) -> WatchReceiver<T, C> {
+        self.inner.subscribe()
+    }
+}
+
+impl<T: PartialOrd + Clone, C: RawCondvar> MonotonicSender<T, C> {
+    /// Update the value by a closure, if that makes it newer.
+    ///
+    /// The closure changes a clone of the value, which replaces the value
+    /// only if it is newer, so a closure that moves the value back is
+    /// reverted and its result given back as with [`send`]. If `f` panics,
+    /// the value is left as it was.
+    ///
+    /// [`send`]: MonotonicSender::send
+    pub fn update<F>(&self, f: F) -> Result<(), Regression<T>>
+    where
+        F: FnOnce(&mut T),
+    {
+        self.send_if_newer("update", |value| {
+            let mut value = value.clone();
+            f(&mut value);
+            value
+        })
+    }
+}
+
+impl<T, C: RawCondvar> Clone for MonotonicSender<T, C> {
+    fn clone(&self) -> MonotonicSender<T, C> {
+        MonotonicSender {
+            inner: self.inner.clone(),
+            allow_equal: self.allow_equal,
+        }
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar> fmt::Debug for MonotonicSender<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MonotonicSender")
+            .field("inner", &self.inner)
+            .field("allow_equal", &self.allow_equal)
+            .finish()
+    }
+}
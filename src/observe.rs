@@ -0,0 +1,306 @@
+use crate::{
+    backend::{RawCondvar, RawCondvarTimeout},
+    spsc::{SpscReceiver, SpscSender},
+    Allocator, CopyReceiver, CopySender, ReadOnlyWatchReceiver, WatchReceiver, WatchSender,
+};
+#[cfg(feature = "left-right")]
+use crate::{LrWatchReceiver, LrWatchSender};
+use alloc::{boxed::Box, sync::Arc};
+use core::time::Duration;
+
+/// The sending half of a watch channel, for code that should work with any
+/// of them.
+///
+/// This is implemented by the senders of this crate whose methods take
+/// `&self`, and can be implemented for the channels of other crates, or for
+/// a fake in tests. A library that only sends can take `impl
+/// WatchPublish<T>` rather than a [`WatchSender`]. Only
+/// [`send`](WatchPublish::send) can be called on a `dyn WatchPublish<T>`.
+pub trait WatchPublish<T> {
+    /// Send a new value, and notify the receivers that are waiting for one.
+    fn send(&self, value: T);
+
+    /// Change the value by a closure, and notify the receivers that are
+    /// waiting for a new one.
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+        Self: Sized;
+}
+
+/// The receiving half of a watch channel, for code that should work with
+/// any of them.
+///
+/// This is implemented by the receivers of this crate that return clones of
+/// the value, and can be implemented for the channels of other crates, or
+/// for a fake in tests. Every method but [`boxed`](WatchObserve::boxed) can
+/// be called on a `dyn WatchObserve<T>`, so receivers of different types can
+/// be kept together as `Box<dyn WatchObserve<T> + Send>`.
+///
+/// A value counts as seen once one of these methods returns it, as with
+/// the methods of the same name on [`WatchReceiver`].
+pub trait WatchObserve<T> {
+    /// Get the latest value, and mark it seen.
+    fn get(&mut self) -> T;
+
+    /// Get the latest value if it has not been seen yet, and mark it seen.
+    fn get_if_new(&mut self) -> Option<T>;
+
+    /// Returns `true` if a value that has not been seen is available.
+    fn has_changed(&self) -> bool;
+
+    /// Wait until a value that has not been seen is available and return
+    /// it.
+    fn wait(&mut self) -> T;
+
+    /// Wait until a value that has not been seen is available and return
+    /// it, giving up after `timeout`.
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T>;
+
+    /// Box the receiver, so that it can be kept together with receivers of
+    /// other types.
+    fn boxed(self) -> Box<dyn WatchObserve<T> + Send>
+    where
+        Self: Sized + Send + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchPublish<T> for WatchSender<T, C, A> {
+    fn send(&self, value: T) {
+        WatchSender::send(self, value);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        WatchSender::update(self, f);
+    }
+}
+
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> WatchObserve<T>
+    for WatchReceiver<T, C, A>
+{
+    fn get(&mut self) -> T {
+        WatchReceiver::get(self)
+    }
+
+    fn get_if_new(&mut self) -> Option<T> {
+        WatchReceiver::get_if_new(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        WatchReceiver::has_changed(self)
+    }
+
+    fn wait(&mut self) -> T {
+        WatchReceiver::wait(self)
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T> {
+        WatchReceiver::wait_timeout(self, timeout)
+    }
+}
+
+impl<T: Copy, C: RawCondvar> WatchPublish<T> for CopySender<T, C> {
+    fn send(&self, value: T) {
+        CopySender::send(self, value);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        CopySender::update(self, f);
+    }
+}
+
+impl<T: Copy, C: RawCondvarTimeout> WatchObserve<T> for CopyReceiver<T, C> {
+    fn get(&mut self) -> T {
+        CopyReceiver::get(self)
+    }
+
+    fn get_if_new(&mut self) -> Option<T> {
+        CopyReceiver::get_if_new(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        CopyReceiver::has_changed(self)
+    }
+
+    fn wait(&mut self) -> T {
+        CopyReceiver::wait(self)
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T> {
+        CopyReceiver::wait_timeout(self, timeout)
+    }
+}
+
+impl<T> WatchPublish<T> for SpscSender<T> {
+    fn send(&self, value: T) {
+        SpscSender::send(self, value);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        SpscSender::update(self, f);
+    }
+}
+
+impl<T: Clone> WatchObserve<T> for SpscReceiver<T> {
+    fn get(&mut self) -> T {
+        SpscReceiver::get(self)
+    }
+
+    fn get_if_new(&mut self) -> Option<T> {
+        SpscReceiver::get_if_new(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        SpscReceiver::has_changed(self)
+    }
+
+    fn wait(&mut self) -> T {
+        SpscReceiver::wait(self)
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T> {
+        SpscReceiver::wait_timeout(self, timeout)
+    }
+}
+
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> WatchObserve<T>
+    for ReadOnlyWatchReceiver<T, C, A>
+{
+    fn get(&mut self) -> T {
+        ReadOnlyWatchReceiver::get(self)
+    }
+
+    fn get_if_new(&mut self) -> Option<T> {
+        ReadOnlyWatchReceiver::get_if_new(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        ReadOnlyWatchReceiver::has_changed(self)
+    }
+
+    fn wait(&mut self) -> T {
+        ReadOnlyWatchReceiver::wait(self)
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T> {
+        ReadOnlyWatchReceiver::wait_timeout(self, timeout)
+    }
+}
+
+#[cfg(feature = "left-right")]
+impl<T: Clone, C: RawCondvar> WatchPublish<T> for LrWatchSender<T, C> {
+    fn send(&self, value: T) {
+        LrWatchSender::send(self, value);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        LrWatchSender::update(self, f);
+    }
+}
+
+#[cfg(feature = "left-right")]
+impl<T: Clone, C: RawCondvarTimeout> WatchObserve<T> for LrWatchReceiver<T, C> {
+    fn get(&mut self) -> T {
+        LrWatchReceiver::get(self)
+    }
+
+    fn get_if_new(&mut self) -> Option<T> {
+        LrWatchReceiver::get_if_new(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        LrWatchReceiver::has_changed(self)
+    }
+
+    fn wait(&mut self) -> T {
+        LrWatchReceiver::wait(self)
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T> {
+        LrWatchReceiver::wait_timeout(self, timeout)
+    }
+}
+
+impl<T, P: WatchPublish<T>> WatchPublish<T> for &P {
+    fn send(&self, value: T) {
+        P::send(self, value);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        P::update(self, f);
+    }
+}
+
+impl<T, P: WatchPublish<T>> WatchPublish<T> for Arc<P> {
+    fn send(&self, value: T) {
+        P::send(self, value);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        P::update(self, f);
+    }
+}
+
+impl<T, O: WatchObserve<T> + ?Sized> WatchObserve<T> for &mut O {
+    fn get(&mut self) -> T {
+        O::get(self)
+    }
+
+    fn get_if_new(&mut self) -> Option<T> {
+        O::get_if_new(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        O::has_changed(self)
+    }
+
+    fn wait(&mut self) -> T {
+        O::wait(self)
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T> {
+        O::wait_timeout(self, timeout)
+    }
+}
+
+impl<T, O: WatchObserve<T> + ?Sized> WatchObserve<T> for Box<O> {
+    fn get(&mut self) -> T {
+        O::get(self)
+    }
+
+    fn get_if_new(&mut self) -> Option<T> {
+        O::get_if_new(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        O::has_changed(self)
+    }
+
+    fn wait(&mut self) -> T {
+        O::wait(self)
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<T> {
+        O::wait_timeout(self, timeout)
+    }
+}
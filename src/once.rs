@@ -0,0 +1,177 @@
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::RecvError;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{backend::RawCondvarTimeout, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// The sender for a write-once channel created by [`once_channel`].
+///
+/// The sender can be cloned, and only the first value sent by any of the
+/// clones is kept.
+pub struct OnceSender<T, C: RawCondvar = DefaultCondvar> {
+    inner: WatchSender<Option<T>, C>,
+}
+
+/// The receiver for a write-once channel created by [`once_channel`].
+///
+/// Once the value is set, every wait returns it at once. The receiver can
+/// be cloned.
+pub struct OnceReceiver<T, C: RawCondvar = DefaultCondvar> {
+    inner: WatchReceiver<Option<T>, C>,
+}
+
+/// Error returned by [`OnceSender::send`] when the value was already set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadySet;
+
+impl fmt::Display for AlreadySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("once channel already set")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AlreadySet {}
+
+/// Creates a new write-once channel, such as for a value that is only known
+/// after startup and never changes afterwards.
+///
+/// The channel starts out empty, and the first value sent is the only one it
+/// ever holds.
+pub fn once_channel<T>() -> (OnceSender<T>, OnceReceiver<T>) {
+    new()
+}
+
+pub(crate) fn new<T, C: RawCondvar>() -> (OnceSender<T, C>, OnceReceiver<T, C>) {
+    let (sender, receiver) = builder().initial_seen(true).channel_with(None);
+    (
+        OnceSender { inner: sender },
+        OnceReceiver { inner: receiver },
+    )
+}
+
+impl<T, C: RawCondvar> OnceSender<T, C> {
+    /// Set the value, unless it was already set.
+    ///
+    /// When several senders race, exactly one of them succeeds.
+    pub fn send(&self, value: T) -> Result<(), AlreadySet> {
+        let shared = &self.inner.shared;
+        let lock = shared.value.write();
+        if lock.value.is_some() {
+            shared.unlock_value(lock);
+            return Err(AlreadySet);
+        }
+        shared.publish(lock, Arc::new(Some(value)), self.inner.id);
+        Ok(())
+    }
+
+    /// Returns `true` if the value is set.
+    pub fn is_set(&self) -> bool {
+        self.inner.shared.value.read().value.is_some()
+    }
+
+    /// Create a new receiver for the channel.
+    pub fn subscribe(&self) -> OnceReceiver<T, C> {
+        OnceReceiver {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+impl<T: Clone, C: RawCondvar> OnceReceiver<T, C> {
+    /// Get a clone of the value, or `None` if it is not set yet.
+    pub fn get(&self) -> Option<T> {
+        let value = self.inner.shared.value.read().value.clone();
+        Option::clone(&value)
+    }
+}
+
+impl<T, C: RawCondvar> OnceReceiver<T, C> {
+    /// Returns `true` if the value is set.
+    pub fn is_set(&self) -> bool {
+        self.inner.shared.value.read().value.is_some()
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    ///
+    /// The value may still be set, if a sender set it before being dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar> OnceReceiver<T, C> {
+    /// Wait until the value is set, and return a clone of it.
+    ///
+    /// Returns at once if it already is. Fails if every sender has been
+    /// dropped without setting the value.
+    pub fn wait(&mut self) -> Result<T, RecvError> {
+        // Reading the value marks it seen, so the only value that the wait
+        // below can return is the one that is set.
+        match self.inner.get() {
+            Some(value) => Ok(value),
+            None => Ok(self.inner.recv()?.expect("a once channel was emptied")),
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout> OnceReceiver<T, C> {
+    /// Like [`wait`], but gives up after `duration`.
+    ///
+    /// [`wait`]: OnceReceiver::wait
+    pub fn wait_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        match self.inner.get() {
+            Some(value) => Ok(value),
+            None => Ok(self
+                .inner
+                .recv_timeout(duration)?
+                .expect("a once channel was emptied")),
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Clone for OnceSender<T, C> {
+    fn clone(&self) -> OnceSender<T, C> {
+        OnceSender {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Clone for OnceReceiver<T, C> {
+    fn clone(&self) -> OnceReceiver<T, C> {
+        OnceReceiver {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar> fmt::Debug for OnceSender<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnceSender").field(&self.inner).finish()
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar> fmt::Debug for OnceReceiver<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnceReceiver").field(&self.inner).finish()
+    }
+}
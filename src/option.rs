@@ -0,0 +1,101 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+use crate::{backend::RawCondvar, Allocator, WatchReceiver, WatchSender};
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<Option<T>, C, A> {
+    /// Send `Some(value)`.
+    pub fn set(&self, value: T) {
+        self.send(Some(value));
+    }
+
+    /// Send `None`, unless the channel already holds `None`.
+    ///
+    /// Clearing an empty channel does not count as a new value, so it wakes
+    /// nobody.
+    pub fn clear(&self) {
+        // Being given back `None` only means that the channel was empty.
+        let _ = self.shared.send_if(Option::is_some, None, self.id, "clear");
+    }
+
+    /// Send `Some(value)` only if the channel holds `None`, and give `value`
+    /// back otherwise.
+    ///
+    /// The check and the send happen with the value locked, so when several
+    /// senders race to fill an empty channel, exactly one of them succeeds.
+    pub fn set_if_none(&self, value: T) -> Result<(), T> {
+        self.shared
+            .send_if(Option::is_none, Some(value), self.id, "set_if_none")
+            // What is given back is the `Some(value)` that was not sent.
+            .map_err(|value| value.unwrap())
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchSender<Option<T>, C, A> {
+    /// Change the value that the channel holds in place, if it holds one,
+    /// and return whether it did.
+    ///
+    /// This works like [`update`](WatchSender::update), but if the channel
+    /// holds `None`, `f` is not called and nothing is sent, which is checked
+    /// with the value locked.
+    pub fn update_some<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&mut T),
+    {
+        self.shared.update_if(
+            Option::is_some,
+            |value| {
+                if let Some(value) = value {
+                    f(value);
+                }
+            },
+            self.id,
+        )
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<Option<T>, C, A> {
+    /// Get a clone of the latest value if it is `Some` and has not previously
+    /// been seen by this receiver.
+    ///
+    /// A new `None` is marked seen too, so this returns `None` both when
+    /// nothing was sent and when `None` was.
+    pub fn get_some(&mut self) -> Option<T> {
+        self.get_if_new().flatten()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<Option<T>, C, A> {
+    /// Wait until a new value is `Some`, and return a clone of what it holds.
+    ///
+    /// This returns at once if the latest value is `Some` and has not been
+    /// seen. New `None` values are marked seen while waiting, and the same
+    /// value is never returned twice.
+    pub fn wait_some(&mut self) -> T {
+        self.wait_accepted(Option::clone)
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> WatchReceiver<Option<T>, C, A> {
+    /// Like [`wait_some`], but gives up after `duration`.
+    ///
+    /// The timeout covers the whole wait, however many `None` values arrive
+    /// in the meantime.
+    ///
+    /// [`wait_some`]: WatchReceiver::wait_some
+    pub fn wait_some_timeout(&mut self, duration: Duration) -> Option<T> {
+        self.wait_accepted_timeout(duration, Option::clone)
+    }
+}
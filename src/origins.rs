@@ -0,0 +1,81 @@
+//! Remembers where each receiver of a channel was created, see
+//! [`WatchSender::live_receiver_origins`].
+//!
+//! Without the `debug-handles` feature, none of this is compiled, and the
+//! functions that create receivers do not take the location of their caller.
+use crate::{backend::RawCondvar, Allocator, WatchSender};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{fmt, panic::Location};
+
+/// Where a receiver that is still alive was created, as listed by
+/// [`WatchSender::live_receiver_origins`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandleOrigin {
+    location: &'static Location<'static>,
+}
+
+impl HandleOrigin {
+    /// The call that created the receiver, such as to [`channel`](crate::channel),
+    /// [`WatchSender::subscribe`] or `WatchReceiver::clone`.
+    ///
+    /// Functions of this crate that create receivers for their own use,
+    /// such as adapters that spawn a thread, may report a location inside
+    /// this crate.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl fmt::Display for HandleOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.location, f)
+    }
+}
+
+/// The origins of the live receivers of a channel, kept in its state.
+pub(crate) struct Origins {
+    /// By the id that the receiver keeps, so that they are listed in the
+    /// order they were created.
+    live: BTreeMap<u64, HandleOrigin>,
+    next_id: u64,
+}
+
+impl Origins {
+    pub(crate) const fn new() -> Origins {
+        Origins {
+            live: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Remember a receiver created by the caller of the function that calls
+    /// this, and return the id for it to keep.
+    #[track_caller]
+    pub(crate) fn add(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let origin = HandleOrigin {
+            location: Location::caller(),
+        };
+        self.live.insert(id, origin);
+        id
+    }
+
+    /// Forget the receiver with the given id, once it is dropped.
+    pub(crate) fn remove(&mut self, id: u64) {
+        self.live.remove(&id);
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// List where each receiver of the channel that is still alive was
+    /// created, oldest first.
+    ///
+    /// This is meant for finding the receiver that keeps a channel open,
+    /// such as one that a cache forgot to drop. Readers are not receivers,
+    /// and are not listed.
+    pub fn live_receiver_origins(&self) -> Vec<HandleOrigin> {
+        let state = self.shared.state.lock();
+        state.origins.live.values().copied().collect()
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{channel, WatchReceiver};
+use alloc::vec::Vec;
+use signal_hook::{consts::FORBIDDEN, iterator::Signals};
+use std::{io, thread, time::Instant};
+
+/// A process signal, as published by [`signals`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SignalEvent {
+    /// The number of the signal, such as `SIGHUP`, or zero if no signal has
+    /// arrived yet.
+    pub signal: i32,
+    /// How many times this signal has arrived since [`signals`] was called.
+    ///
+    /// A receiver that does not keep up only sees the latest event, and the
+    /// operating system merges a signal that arrives again before it was
+    /// handled, so a jump in the count tells how many were coalesced.
+    pub count: u64,
+    /// When the signal was handled by the thread that publishes them.
+    pub at: Instant,
+}
+
+/// Publish the process signals in `signals` on a new channel.
+///
+/// A thread waits for the signals with `signal_hook`, and sends an event
+/// each time one of them arrives. Until the first one does, the channel
+/// holds an event for signal zero, which the returned receiver has seen,
+/// so that a wait such as [`WatchReceiver::recv`] returns the first signal.
+/// Once every receiver is dropped, the signals are unregistered and the
+/// thread exits.
+///
+/// Fails if the signals cannot be registered or the threads cannot be
+/// spawned, and with [`io::ErrorKind::InvalidInput`] if one of them is a
+/// signal that cannot be handled, such as `SIGKILL`.
+pub fn signals(signals: &[i32]) -> io::Result<WatchReceiver<SignalEvent>> {
+    // `signal_hook` panics on those, and then aborts as it cleans up.
+    if let Some(signal) = signals.iter().find(|signal| FORBIDDEN.contains(signal)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            alloc::format!("signal {} cannot be handled", signal),
+        ));
+    }
+    let mut iterator = Signals::new(signals)?;
+    let handle = iterator.handle();
+    let (sender, mut receiver) = channel(SignalEvent {
+        signal: 0,
+        count: 0,
+        at: Instant::now(),
+    });
+    receiver.get_shared();
+
+    // This is spawned first, so that if the other thread cannot be, the
+    // signals are unregistered when its closure is dropped, and this one
+    // exits once the receiver is dropped below.
+    let mut receivers = sender.subscriber_count_watch();
+    thread::Builder::new()
+        .name("watch-signals-reaper".into())
+        .spawn(move || {
+            // The count only reaches zero for good, as nothing but the
+            // receivers can create another one.
+            while receivers.get() > 0 {
+                if receivers.recv().is_err() {
+                    break;
+                }
+            }
+            handle.close();
+        })?;
+
+    thread::Builder::new()
+        .name("watch-signals".into())
+        .spawn(move || {
+            let mut counts: Vec<(i32, u64)> = Vec::new();
+            for signal in iterator.forever() {
+                let at = Instant::now();
+                let count = match counts.iter_mut().find(|(known, _)| *known == signal) {
+                    Some((_, count)) => {
+                        *count += 1;
+                        *count
+                    }
+                    None => {
+                        counts.push((signal, 1));
+                        1
+                    }
+                };
+                sender.send(SignalEvent { signal, count, at });
+            }
+        })?;
+
+    Ok(receiver)
+}
@@ -0,0 +1,76 @@
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    Allocator, Global, WatchSender,
+};
+use alloc::sync::Arc;
+use core::fmt;
+
+/// Puts back the value that [`WatchSender::scoped_override`] replaced when
+/// it is dropped.
+#[must_use = "the value is put back as soon as the guard is dropped"]
+pub struct OverrideGuard<'a, T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    sender: &'a WatchSender<T, C, A>,
+    /// The value to put back.
+    previous: Option<Arc<T>>,
+    /// The value sent by the override. Holding on to it makes `update` clone
+    /// it rather than change it in place, so any change gives the channel a
+    /// different `Arc`.
+    current: Arc<T>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Send `value` until the returned guard is dropped, and then put back
+    /// the value it replaced.
+    ///
+    /// Both sends notify the receivers like any other. The last writer wins:
+    /// if the value is replaced or updated while the guard is alive, the
+    /// guard leaves the new value in place instead of putting the old one
+    /// back, see [`OverrideGuard::will_restore`]. Since dropping the guard
+    /// restores the value, it is also restored if the scope that holds the
+    /// guard panics.
+    pub fn scoped_override(&self, value: T) -> OverrideGuard<'_, T, C, A> {
+        let current = Arc::new(value);
+        let previous =
+            self.shared
+                .publish_replace(self.shared.value.write(), current.clone(), self.id);
+        OverrideGuard {
+            sender: self,
+            previous: Some(previous),
+            current,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> OverrideGuard<'_, T, C, A> {
+    /// Returns `true` if the channel still holds the value of the override,
+    /// so that dropping the guard now would put back the old value.
+    ///
+    /// A new value may be sent right after this returns.
+    pub fn will_restore(&self) -> bool {
+        Arc::ptr_eq(&self.sender.shared.value.read().value, &self.current)
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Drop for OverrideGuard<'_, T, C, A> {
+    fn drop(&mut self) {
+        let previous = self.previous.take().unwrap();
+        let shared = &self.sender.shared;
+        let lock = shared.value.write();
+        if Arc::ptr_eq(&lock.value, &self.current) {
+            shared.publish(lock, previous, self.sender.id);
+        } else {
+            shared.unlock_value(lock);
+            // The old value is destroyed after releasing the lock.
+            drop(previous);
+        }
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar, A: Allocator> fmt::Debug for OverrideGuard<'_, T, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OverrideGuard")
+            .field("sender", self.sender)
+            .field("previous", &self.previous)
+            .finish()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{backend::RawCondvar, Allocator, WatchReceiver};
+use alloc::sync::Arc;
+use core::{fmt, ops::Deref};
+
+/// An owned handle to a value of a channel, that can be stored or moved to
+/// another thread.
+///
+/// This is created by [`WatchReceiver::borrow_owned`]. It does not borrow
+/// the receiver, and it is `Send` and `Sync` when `T: Send + Sync`.
+///
+/// Unlike a lock guard, it does not hold the lock of the channel: the
+/// channel keeps its values in an `Arc`, and this holds one of them. Senders
+/// are never blocked by it, and a value sent while it exists replaces the
+/// value in the channel rather than changing the one behind the handle,
+/// which is only dropped once every handle to it is gone.
+pub struct OwnedWatchRef<T> {
+    value: Arc<T>,
+    version: u64,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Get an owned handle to the latest value, marking it seen.
+    ///
+    /// See [`OwnedWatchRef`].
+    pub fn borrow_owned(&mut self) -> OwnedWatchRef<T> {
+        let value = self.get_shared();
+        OwnedWatchRef {
+            value,
+            version: self.last_seen_version,
+        }
+    }
+}
+
+impl<T> OwnedWatchRef<T> {
+    /// The version of the value, as counted by the channel it came from.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Turn this into the `Arc` that holds the value.
+    pub fn into_shared(self) -> Arc<T> {
+        self.value
+    }
+}
+
+impl<T> Deref for OwnedWatchRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Clone for OwnedWatchRef<T> {
+    fn clone(&self) -> Self {
+        OwnedWatchRef {
+            value: self.value.clone(),
+            version: self.version,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OwnedWatchRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedWatchRef")
+            .field("value", &self.value)
+            .field("version", &self.version)
+            .finish()
+    }
+}
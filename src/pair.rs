@@ -0,0 +1,137 @@
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::RecvError;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{backend::RawCondvarTimeout, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// One side of a pair of channels created by [`channel_pair`].
+///
+/// It publishes its own state of type `S`, and watches the state of type `P`
+/// that the other endpoint publishes. The endpoint can be cloned, and its
+/// side stays open as long as any of the clones is alive.
+pub struct Endpoint<S, P, C: RawCondvar = DefaultCondvar> {
+    sender: WatchSender<S, C>,
+    receiver: WatchReceiver<P, C>,
+}
+
+/// Creates two endpoints that each publish their latest state to the other,
+/// such as a controller that sends the desired state and a worker that
+/// sends back what it observes.
+///
+/// The first endpoint publishes `a` and watches the second, which publishes
+/// `b`. Dropping an endpoint closes the direction it publishes in, which the
+/// other endpoint sees with [`Endpoint::is_peer_closed`], while it can keep
+/// publishing to a peer that is gone. Neither endpoint has seen the starting
+/// state of its peer.
+pub fn channel_pair<A, B>(a: A, b: B) -> (Endpoint<A, B>, Endpoint<B, A>) {
+    new(a, b)
+}
+
+pub(crate) fn new<A, B, C: RawCondvar>(a: A, b: B) -> (Endpoint<A, B, C>, Endpoint<B, A, C>) {
+    let (a_sender, a_receiver) = builder().channel_with(a);
+    let (b_sender, b_receiver) = builder().channel_with(b);
+    (
+        Endpoint {
+            sender: a_sender,
+            receiver: b_receiver,
+        },
+        Endpoint {
+            sender: b_sender,
+            receiver: a_receiver,
+        },
+    )
+}
+
+impl<S, P, C: RawCondvar> Endpoint<S, P, C> {
+    /// Publish a new state to the peer, and notify it if it is waiting.
+    pub fn publish(&self, state: S) {
+        self.sender.send(state);
+    }
+
+    /// Returns `true` if the peer has published a state that has not been
+    /// seen yet.
+    pub fn peer_changed(&self) -> bool {
+        self.receiver.has_changed()
+    }
+
+    /// Returns `true` if every clone of the peer endpoint has been dropped.
+    ///
+    /// Its latest state can still be read.
+    pub fn is_peer_closed(&self) -> bool {
+        self.receiver.is_closed()
+    }
+
+    /// Split the endpoint into the sender of its own state and the receiver
+    /// of the state of its peer.
+    pub fn split(self) -> (WatchSender<S, C>, WatchReceiver<P, C>) {
+        (self.sender, self.receiver)
+    }
+}
+
+impl<S, P: Clone, C: RawCondvar> Endpoint<S, P, C> {
+    /// Get a clone of the latest state of the peer, and mark it seen.
+    pub fn peer(&mut self) -> P {
+        self.receiver.get()
+    }
+
+    /// Get a clone of the latest state of the peer, if it has not been seen
+    /// yet.
+    pub fn peer_if_new(&mut self) -> Option<P> {
+        self.receiver.get_if_new()
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<S, P: Clone, C: RawCondvar> Endpoint<S, P, C> {
+    /// Wait until the peer publishes a state that has not been seen yet, and
+    /// return a clone of it.
+    ///
+    /// Fails once the peer endpoint is dropped and its latest state has been
+    /// seen.
+    pub fn wait_peer(&mut self) -> Result<P, RecvError> {
+        self.receiver.recv()
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<S, P: Clone, C: RawCondvarTimeout> Endpoint<S, P, C> {
+    /// Like [`wait_peer`], but gives up after `duration`.
+    ///
+    /// [`wait_peer`]: Endpoint::wait_peer
+    pub fn wait_peer_timeout(&mut self, duration: Duration) -> Result<P, RecvTimeoutError> {
+        self.receiver.recv_timeout(duration)
+    }
+}
+
+impl<S, P, C: RawCondvar> Clone for Endpoint<S, P, C> {
+    fn clone(&self) -> Endpoint<S, P, C> {
+        Endpoint {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+impl<S: fmt::Debug, P: fmt::Debug, C: RawCondvar> fmt::Debug for Endpoint<S, P, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Endpoint")
+            .field("sender", &self.sender)
+            .field("receiver", &self.receiver)
+            .finish()
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::RecvError;
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    Allocator, Global, WatchReceiver,
+};
+use alloc::sync::Arc;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// A receiver that returns each new value together with the one it
+/// returned before.
+///
+/// This is created by [`WatchReceiver::pairwise`]. The previous value is
+/// the one this receiver delivered last, not the one before the latest
+/// version of the channel: values that it skipped because newer ones were
+/// sent in the meantime never become the previous value. It is kept here,
+/// as an `Arc` handle like the one [`WatchReceiver::get_shared`] returns,
+/// so other receivers and the channel are not affected by it.
+pub struct PairwiseReceiver<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    receiver: WatchReceiver<T, C, A>,
+    previous: Option<Arc<T>>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Turn this receiver into one that returns each new value together
+    /// with the value it returned before.
+    ///
+    /// The first value it returns comes with `None`. Values that this
+    /// receiver has already seen are not returned again.
+    pub fn pairwise(self) -> PairwiseReceiver<T, C, A> {
+        PairwiseReceiver {
+            receiver: self,
+            previous: None,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> PairwiseReceiver<T, C, A> {
+    /// Returns `true` if a value that has not been returned yet is
+    /// available.
+    pub fn has_changed(&self) -> bool {
+        self.receiver.has_changed()
+    }
+
+    /// Get back the receiver, dropping the previous value.
+    pub fn into_inner(self) -> WatchReceiver<T, C, A> {
+        self.receiver
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> PairwiseReceiver<T, C, A> {
+    /// Remember `value` as the previous value, and return clones of it and
+    /// of the value it replaces.
+    fn deliver(&mut self, value: Arc<T>) -> (Option<T>, T) {
+        let previous = self.previous.replace(value.clone());
+        // The values are cloned after releasing the lock, like in `get`.
+        (
+            previous.map(|previous| T::clone(&previous)),
+            T::clone(&value),
+        )
+    }
+
+    /// Get the latest value together with the previous one, if the latest
+    /// value has not been returned yet.
+    pub fn get_if_new(&mut self) -> Option<(Option<T>, T)> {
+        let value = self.receiver.get_if_new_shared()?;
+        Some(self.deliver(value))
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> PairwiseReceiver<T, C, A> {
+    /// Wait until a new value becomes available, and return it together
+    /// with the previous one.
+    ///
+    /// If every sender has been dropped, this waits forever. Use
+    /// [`recv`](PairwiseReceiver::recv) to detect that case.
+    pub fn wait(&mut self) -> (Option<T>, T) {
+        let value = self.receiver.wait_shared();
+        self.deliver(value)
+    }
+
+    /// Like [`wait`](PairwiseReceiver::wait), but fails once every sender
+    /// has been dropped.
+    pub fn recv(&mut self) -> Result<(Option<T>, T), RecvError> {
+        let shared = &self.receiver.shared;
+        let seen = self.receiver.last_seen_version;
+        let state = shared.state.lock();
+        let state = shared.wait_while(state, |state| state.version == seen && state.is_open());
+        if state.version == seen {
+            return Err(RecvError);
+        }
+        drop(state);
+
+        let value = self.receiver.get_shared();
+        Ok(self.deliver(value))
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> PairwiseReceiver<T, C, A> {
+    /// Like [`wait`](PairwiseReceiver::wait), but gives up after
+    /// `duration`.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<(Option<T>, T)> {
+        let shared = &self.receiver.shared;
+        let seen = self.receiver.last_seen_version;
+        let deadline = shared.deadline(duration);
+        let state = shared.state.lock();
+        let (state, ready) =
+            shared.wait_while_until(state, deadline, |state| state.version == seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        let value = self.receiver.get_shared();
+        Some(self.deliver(value))
+    }
+}
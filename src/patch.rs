@@ -0,0 +1,130 @@
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    builder, WatchReceiver, WatchSender,
+};
+use alloc::collections::BTreeMap;
+use core::{fmt, marker::PhantomData};
+
+/// A state that can be changed by applying patches to it, see
+/// [`patch_channel`].
+///
+/// The maps of the standard library take patches of a key and an optional
+/// value, which insert the value or, if it is `None`, remove the key.
+pub trait ApplyPatch<P> {
+    /// Change the state by `patch`.
+    fn apply(&mut self, patch: P);
+}
+
+impl<K: Ord, V> ApplyPatch<(K, Option<V>)> for BTreeMap<K, V> {
+    fn apply(&mut self, (key, value): (K, Option<V>)) {
+        match value {
+            Some(value) => drop(self.insert(key, value)),
+            None => drop(self.remove(&key)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> ApplyPatch<(K, Option<V>)> for std::collections::HashMap<K, V, S>
+where
+    K: Eq + core::hash::Hash,
+    S: core::hash::BuildHasher,
+{
+    fn apply(&mut self, (key, value): (K, Option<V>)) {
+        match value {
+            Some(value) => drop(self.insert(key, value)),
+            None => drop(self.remove(&key)),
+        }
+    }
+}
+
+/// The sender for a channel created by [`patch_channel`].
+///
+/// The sender can be cloned to obtain multiple senders for the same channel.
+pub struct PatchSender<T, P, C: RawCondvar = DefaultCondvar> {
+    inner: WatchSender<T, C>,
+    _patch: PhantomData<fn(P)>,
+}
+
+/// Creates a new channel whose senders send patches rather than whole
+/// values, such as the keys of a large map that changed.
+///
+/// Each patch is applied to the value in the channel, which the receivers
+/// read like that of any other channel. The receiver is a plain
+/// [`WatchReceiver`], which has not seen the starting value.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn patch_channel<T: ApplyPatch<P>, P>(initial: T) -> (PatchSender<T, P>, WatchReceiver<T>) {
+    new(initial)
+}
+
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub(crate) fn new<T: ApplyPatch<P>, P, C: RawCondvar>(
+    initial: T,
+) -> (PatchSender<T, P, C>, WatchReceiver<T, C>) {
+    let (sender, receiver) = builder().channel_with(initial);
+    (
+        PatchSender {
+            inner: sender,
+            _patch: PhantomData,
+        },
+        receiver,
+    )
+}
+
+impl<T: ApplyPatch<P> + Clone, P, C: RawCondvar> PatchSender<T, P, C> {
+    /// Apply `patch` to the value, and notify the receivers.
+    ///
+    /// The patch is applied like [`WatchSender::update`] runs its closure,
+    /// so the value is only cloned first if a receiver still holds it, and
+    /// `apply` must not use the same channel. Patches from different senders
+    /// are applied in the order in which they lock the value.
+    pub fn send_patch(&self, patch: P) {
+        self.inner.update(|value| value.apply(patch));
+    }
+
+    /// Apply every patch of `patches` in order, and notify the receivers
+    /// once.
+    ///
+    /// The receivers see either none of the patches or all of them.
+    pub fn send_patches<I>(&self, patches: I)
+    where
+        I: IntoIterator<Item = P>,
+    {
+        self.inner.update(|value| {
+            for patch in patches {
+                value.apply(patch);
+            }
+        });
+    }
+}
+
+impl<T, P, C: RawCondvar> PatchSender<T, P, C> {
+    /// Replace the whole value, and notify the receivers.
+    pub fn send(&self, value: T) {
+        self.inner.send(value);
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn subscribe(&self) -> WatchReceiver<T, C> {
+        self.inner.subscribe()
+    }
+}
+
+impl<T, P, C: RawCondvar> Clone for PatchSender<T, P, C> {
+    fn clone(&self) -> PatchSender<T, P, C> {
+        PatchSender {
+            inner: self.inner.clone(),
+            _patch: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, P, C: RawCondvar> fmt::Debug for PatchSender<T, P, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PatchSender").field(&self.inner).finish()
+    }
+}
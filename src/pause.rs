@@ -0,0 +1,56 @@
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    Allocator, Global, Shared, SharedArc, WatchSender,
+};
+use core::fmt;
+
+/// Holds back the notifications of a channel until it is dropped, see
+/// [`WatchSender::pause_notifications`].
+#[must_use = "notifications resume as soon as the guard is dropped"]
+pub struct PauseGuard<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    shared: SharedArc<Shared<T, C>, A>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Stop waking the receivers until the returned guard is dropped, such
+    /// as while many values are sent to load a state in bulk.
+    ///
+    /// Sending still stores each value and gives it a new version right
+    /// away, so reads such as
+    /// [`WatchReceiver::get_if_new`](crate::WatchReceiver::get_if_new) see
+    /// it, and a wait that starts after a send returns at once. Only the
+    /// threads and tasks that are already waiting stay asleep. Pauses are
+    /// counted, so that the guards of several senders can overlap, and once
+    /// the last one is dropped, the waiting receivers are woken once if a
+    /// value was sent in the meantime. Closing the channel still wakes
+    /// everyone at once.
+    ///
+    /// The guard does not borrow the sender and does not count as one. On
+    /// a channel with [`ChannelBuilder::manual_notify`], the notification
+    /// is left for the next [`pump`](WatchSender::pump) instead.
+    ///
+    /// [`ChannelBuilder::manual_notify`]: crate::ChannelBuilder::manual_notify
+    pub fn pause_notifications(&self) -> PauseGuard<T, C, A> {
+        self.shared.state.lock().pauses += 1;
+        PauseGuard {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Drop for PauseGuard<T, C, A> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.pauses -= 1;
+        if state.pauses == 0 && state.notify_pending && !state.manual_notify {
+            state.notify_pending = false;
+            state.wake_version();
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> fmt::Debug for PauseGuard<T, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PauseGuard").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{channel, stop::Stop, WatchReader, WatchReceiver, WatchSender};
+use alloc::{boxed::Box, sync::Arc};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
+    thread::{self, JoinHandle},
+};
+
+/// Handle to the thread that saves the values of a channel created by
+/// [`persistent_channel`].
+///
+/// Dropping the handle asks the thread to save the latest value and stop,
+/// without waiting for it.
+pub struct PersistHandle {
+    /// Reads the version of the latest value of the channel.
+    version: Box<dyn Fn() -> u64 + Send + Sync>,
+    /// Asks the thread to stop.
+    stop: Box<dyn Fn() + Send + Sync>,
+    status: Arc<Status>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// What the writer thread has saved so far.
+struct Status {
+    saved: Mutex<Saved>,
+    changed: Condvar,
+}
+
+struct Saved {
+    /// The version of the value in the file.
+    version: u64,
+    /// The error of the latest save that failed, until it is reported.
+    error: Option<io::Error>,
+    /// Set once the writer thread has exited.
+    finished: bool,
+}
+
+/// Creates a watch channel whose values are saved to the file at `path`.
+///
+/// The channel starts with the value saved in the file, or with `default`
+/// if there is no file. A file that cannot be read or parsed is an error,
+/// with the kind [`InvalidData`](io::ErrorKind::InvalidData) if it holds
+/// something other than a value of `T`, rather than being overwritten.
+///
+/// The values are saved as JSON by a thread, so sending never waits for the
+/// disk. When values are sent faster than they can be saved, the thread only
+/// saves the latest one. Each value is written to a temporary file next to
+/// `path`, which then replaces the file, so a crash leaves either the old
+/// value or the new one in the file. Use [`PersistHandle::flush`] to wait
+/// for a value to be saved. The thread saves the last value and exits once
+/// every sender is dropped or the handle is stopped or dropped.
+pub fn persistent_channel<T>(
+    path: impl AsRef<Path>,
+    default: T,
+) -> io::Result<(WatchSender<T>, WatchReceiver<T>, PersistHandle)>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let path = path.as_ref().to_path_buf();
+    let value = match fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => default,
+        Err(error) => return Err(error),
+    };
+    let (sender, receiver) = channel(value);
+    let reader = sender.reader();
+    // The starting value is already in the file, or is the default.
+    let initial = reader.version();
+
+    let stop = Arc::new(Stop::new());
+    let status = Arc::new(Status {
+        saved: Mutex::new(Saved {
+            version: initial,
+            error: None,
+            finished: false,
+        }),
+        changed: Condvar::new(),
+    });
+
+    let thread = {
+        let stop = stop.clone();
+        let status = status.clone();
+        let reader = sender.reader();
+        thread::Builder::new()
+            .name("watch-persist".into())
+            .spawn(move || {
+                write_values(&reader, initial, &path, &stop, &status);
+                status.saved.lock().unwrap().finished = true;
+                status.changed.notify_all();
+            })
+            .expect("failed to spawn thread")
+    };
+
+    let shared = reader.shared.clone();
+    let handle = PersistHandle {
+        version: Box::new(move || reader.version()),
+        stop: Box::new(move || {
+            // Taking the lock ensures that the writer is either parked or has
+            // not yet checked the stop flag.
+            let _state = shared.state.lock();
+            stop.stop();
+        }),
+        status,
+        thread: Some(thread),
+    };
+    Ok((sender, receiver, handle))
+}
+
+/// Save every new value of the channel until it is closed or `stop` is set.
+fn write_values<T: Serialize>(
+    reader: &WatchReader<T>,
+    mut seen: u64,
+    path: &Path,
+    stop: &Stop,
+    status: &Status,
+) {
+    loop {
+        {
+            let shared = &reader.shared;
+            let state = shared.state.lock();
+            let state = shared.wait_while_unless_stopped(state, stop, |state| {
+                state.version == seen && state.is_open()
+            });
+            if state.version == seen {
+                return;
+            }
+        }
+        let (value, version) = reader.get_shared();
+        seen = version;
+        let result = save(path, &*value);
+        drop(value);
+
+        let mut saved = status.saved.lock().unwrap();
+        saved.version = version;
+        if let Err(error) = result {
+            saved.error = Some(error);
+        }
+        drop(saved);
+        status.changed.notify_all();
+    }
+}
+
+/// Write `value` to a temporary file, and then move that over `path`.
+fn save<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let temporary = temporary_path(path);
+    let mut file = BufWriter::new(File::create(&temporary)?);
+    serde_json::to_writer(&mut file, value)?;
+    let file = file.into_inner().map_err(io::IntoInnerError::into_error)?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut temporary = OsString::from(path.as_os_str());
+    temporary.push(".tmp");
+    PathBuf::from(temporary)
+}
+
+impl PersistHandle {
+    /// Wait until the value that the channel holds now has been saved.
+    ///
+    /// Returns the error of the latest save that failed since the last call,
+    /// if any, or an error if the writer thread has stopped before saving
+    /// the value.
+    pub fn flush(&self) -> io::Result<()> {
+        let version = (self.version)();
+        let mut saved = self.status.saved.lock().unwrap();
+        // The versions count up from the starting one, so they do not wrap
+        // around.
+        while saved.version < version && !saved.finished {
+            saved = self.status.changed.wait(saved).unwrap();
+        }
+        if let Some(error) = saved.error.take() {
+            return Err(error);
+        }
+        if saved.version < version {
+            return Err(io::Error::other("the persistence thread has stopped"));
+        }
+        Ok(())
+    }
+
+    /// Save the latest value, stop the writer thread, and wait for it to
+    /// exit.
+    ///
+    /// Returns the error of the latest save that failed, if any, like
+    /// [`flush`](PersistHandle::flush).
+    pub fn stop(mut self) -> io::Result<()> {
+        self.interrupt();
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+        match self.status.saved.lock().unwrap().error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the writer thread has exited.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    fn interrupt(&self) {
+        (self.stop)();
+    }
+}
+
+impl Drop for PersistHandle {
+    fn drop(&mut self) {
+        self.interrupt();
+    }
+}
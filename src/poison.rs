@@ -0,0 +1,100 @@
+use crate::{backend::RawCondvar, Allocator, WatchReceiver, WatchSender};
+use alloc::sync::Arc;
+use core::{fmt, sync::atomic::Ordering};
+
+/// Error returned by the checked methods when a closure passed to
+/// [`WatchSender::update`] panicked.
+///
+/// The channel stays poisoned until [`WatchSender::clear_poison`] or
+/// [`WatchReceiver::clear_poison`] is called. The other methods ignore the
+/// poisoning, so a poisoned channel can still be read and sent to.
+///
+/// The channel keeps the poisoning itself rather than leaving it to its
+/// locks, so it works the same on every backend, including those whose
+/// locks are never poisoned. Only `update` closures can poison the channel:
+/// the closures that waits such as [`WatchReceiver::wait_with`] take run
+/// without holding a lock, so a panic in them leaves the channel as it was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Poisoned;
+
+impl fmt::Display for Poisoned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("watch channel poisoned by a panic during an update")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Poisoned {}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Like [`send`](WatchSender::send), but fails without sending if the
+    /// channel is poisoned.
+    ///
+    /// The value is dropped if the channel is poisoned.
+    pub fn send_checked(&self, value: T) -> Result<(), Poisoned> {
+        let shared = &self.shared;
+        let lock = shared.value.write();
+        if shared.poisoned.load(Ordering::Relaxed) {
+            return Err(Poisoned);
+        }
+        shared.publish(lock, Arc::new(value), self.id);
+        Ok(())
+    }
+
+    /// Returns `true` if a closure passed to [`update`](WatchSender::update)
+    /// panicked since the poisoning was last cleared.
+    pub fn is_poisoned(&self) -> bool {
+        self.shared.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// Clear the poisoning of the channel.
+    pub fn clear_poison(&self) {
+        let _lock = self.shared.value.write();
+        self.shared.poisoned.store(false, Ordering::Relaxed);
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Like [`get`](WatchReceiver::get), but fails if the channel is
+    /// poisoned.
+    ///
+    /// The value does not count as seen if this fails.
+    pub fn get_checked(&mut self) -> Result<T, Poisoned> {
+        let value = self.track(|shared, seen| {
+            let lock = shared.value.read();
+            if shared.poisoned.load(Ordering::Relaxed) {
+                return Err(Poisoned);
+            }
+            Ok(lock.get(seen).clone())
+        })?;
+        Ok(T::clone(&value))
+    }
+
+    /// Like [`wait`](WatchReceiver::wait), but fails if the channel is
+    /// poisoned once a new value is available.
+    ///
+    /// An update that panics counts as a new value, so this wakes up to
+    /// report it.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    pub fn wait_checked(&mut self) -> Result<T, Poisoned> {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(self.shared.wait_while(state, |state| state.version == seen));
+
+        self.get_checked()
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Returns `true` if a closure passed to [`WatchSender::update`]
+    /// panicked since the poisoning was last cleared.
+    pub fn is_poisoned(&self) -> bool {
+        self.shared.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// Clear the poisoning of the channel.
+    pub fn clear_poison(&self) {
+        let _lock = self.shared.value.write();
+        self.shared.poisoned.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{channel, stop::Stop, WatchReceiver, WatchSender};
+use alloc::sync::Arc;
+use std::{
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Handle to the thread spawned by [`from_fn`].
+///
+/// Dropping the handle asks the thread to stop without waiting for it.
+pub struct PollerHandle {
+    stop: Arc<Stop>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Publish the result of `f` on a new channel every `interval`.
+///
+/// The first call to `f` is made before this returns, and its result is the
+/// initial value of the channel. A thread then waits `interval` after every
+/// call before making the next one, and sends each result. It exits, closing
+/// the channel, once the returned handle is stopped or dropped, or once an
+/// interval ends with every receiver dropped.
+///
+/// Use [`from_fn_distinct`] to only send results that differ from the
+/// value in the channel.
+pub fn from_fn<T, F>(interval: Duration, f: F) -> (WatchReceiver<T>, PollerHandle)
+where
+    T: Send + Sync + 'static,
+    F: FnMut() -> T + Send + 'static,
+{
+    spawn_poller(interval, f, WatchSender::send)
+}
+
+/// Like [`from_fn`], but only sends the results of `f` that differ from
+/// the value in the channel, so the receivers are not woken up for nothing.
+pub fn from_fn_distinct<T, F>(interval: Duration, f: F) -> (WatchReceiver<T>, PollerHandle)
+where
+    T: PartialEq + Send + Sync + 'static,
+    F: FnMut() -> T + Send + 'static,
+{
+    spawn_poller(interval, f, |sender, value| {
+        let shared = &sender.shared;
+        let lock = shared.value.write();
+        if *lock.value == value {
+            shared.unlock_value(lock);
+            return;
+        }
+        shared.publish(lock, Arc::new(value), sender.id);
+    })
+}
+
+fn spawn_poller<T, F, S>(interval: Duration, mut f: F, send: S) -> (WatchReceiver<T>, PollerHandle)
+where
+    T: Send + Sync + 'static,
+    F: FnMut() -> T + Send + 'static,
+    S: Fn(&WatchSender<T>, T) + Send + 'static,
+{
+    let (sender, receiver) = channel(f());
+    let stop = Arc::new(Stop::new());
+
+    let thread = {
+        let stop = stop.clone();
+        thread::Builder::new()
+            .name("watch-poller".into())
+            .spawn(move || loop {
+                // The poller sleeps on a stop of its own, so the receivers
+                // of the channel do not count it as one of them.
+                if !stop.sleep_until(sender.shared.deadline(interval))
+                    || sender.shared.state.lock().receivers == 0
+                {
+                    return;
+                }
+                let value = f();
+                if stop.is_stopped() {
+                    return;
+                }
+                send(&sender, value);
+            })
+            .expect("failed to spawn thread")
+    };
+
+    let handle = PollerHandle {
+        stop,
+        thread: Some(thread),
+    };
+    (receiver, handle)
+}
+
+impl PollerHandle {
+    /// Stop the poller thread and wait for it to exit.
+    ///
+    /// If `f` is running, this waits for it to return, and its result is not
+    /// sent.
+    pub fn stop(mut self) {
+        self.interrupt();
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    /// Returns `true` if the poller thread has exited.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    fn interrupt(&self) {
+        self.stop.stop();
+    }
+}
+
+impl Drop for PollerHandle {
+    fn drop(&mut self) {
+        self.interrupt();
+    }
+}
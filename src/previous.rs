@@ -0,0 +1,90 @@
+//! Keeps the value before the latest version, see
+//! [`ChannelBuilder::keep_previous`](crate::ChannelBuilder::keep_previous).
+use crate::{backend::RawCondvar, Allocator, Shared, WatchReceiver};
+use alloc::sync::Arc;
+use lock_api::Mutex;
+
+/// The value before the latest version, once there is one.
+pub(crate) type Previous<T> = Option<Arc<T>>;
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    /// Start keeping the value before the latest version.
+    pub(crate) fn enable_previous(&mut self) {
+        self.previous = Some(alloc::boxed::Box::new(Mutex::new(None)));
+    }
+
+    /// Keep `value` as the value before the latest version, if the channel
+    /// keeps one.
+    ///
+    /// This must be called with the value write-locked. Returns the value
+    /// kept before, so that it can be destroyed after the lock is released.
+    pub(crate) fn keep_previous(&self, value: &Arc<T>) -> Option<Arc<T>> {
+        self.previous
+            .as_ref()
+            .and_then(|previous| previous.lock().replace(value.clone()))
+    }
+
+    /// Returns `1` if `value` is kept as the previous value, and `0`
+    /// otherwise.
+    pub(crate) fn previous_holds(&self, value: &Arc<T>) -> usize {
+        match &self.previous {
+            Some(previous) => match &*previous.lock() {
+                Some(kept) if Arc::ptr_eq(kept, value) => 1,
+                _ => 0,
+            },
+            None => 0,
+        }
+    }
+
+    /// Get the latest value together with the value before it, and mark it
+    /// seen.
+    fn get_with_previous_shared(&self, seen: &mut u64) -> (Option<Arc<T>>, Arc<T>) {
+        let lock = self.value.read();
+        let timer = self.lock_timer("get");
+        let previous = self
+            .previous
+            .as_ref()
+            .and_then(|previous| previous.lock().clone());
+        let value = lock.get(seen).clone();
+        drop(lock);
+        self.lock_released(timer);
+        (previous, value)
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Get a clone of the latest value, together with a clone of the value
+    /// that it replaced.
+    ///
+    /// This needs a channel created with [`ChannelBuilder::keep_previous`].
+    /// The previous value is the one right before the latest version, not
+    /// the one this receiver saw last: if this receiver skipped some
+    /// versions, the values in between are not returned, see
+    /// [`missed_values`](WatchReceiver::missed_values) for those. It is
+    /// `None` if nothing has been sent yet, or if the channel does not keep
+    /// it.
+    ///
+    /// [`ChannelBuilder::keep_previous`]: crate::ChannelBuilder::keep_previous
+    pub fn get_with_previous(&mut self) -> (Option<T>, T) {
+        let (previous, value) = self.track(|shared, seen| shared.get_with_previous_shared(seen));
+        // The values are cloned after releasing the lock, like in `get`.
+        (
+            previous.map(|previous| T::clone(&previous)),
+            T::clone(&value),
+        )
+    }
+
+    /// Wait until a new value becomes available, and return clones of it and
+    /// of the value it replaced.
+    ///
+    /// See [`get_with_previous`](WatchReceiver::get_with_previous) and
+    /// [`wait`](WatchReceiver::wait).
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    pub fn wait_with_previous(&mut self) -> (Option<T>, T) {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(self.shared.wait_while(state, |state| state.version == seen));
+
+        self.get_with_previous()
+    }
+}
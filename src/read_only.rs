@@ -0,0 +1,169 @@
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::RecvError;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{backend::RawCondvarTimeout, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    Allocator, ChannelId, Global, WatchReceiver, WatchSender,
+};
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// A receiver that can read the channel but cannot create senders for it.
+///
+/// This is created by [`WatchReceiver::into_read_only`] or
+/// [`WatchSender::subscribe_read_only`]. It has the reading and waiting
+/// methods of [`WatchReceiver`], but no `new_sender`, and there is no way to
+/// get the inner receiver back. A component that is handed one can follow
+/// the channel without being able to send on it.
+pub struct ReadOnlyWatchReceiver<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    inner: WatchReceiver<T, C, A>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Turn this receiver into one that cannot create senders.
+    ///
+    /// The receiver keeps the values it has seen.
+    pub fn into_read_only(self) -> ReadOnlyWatchReceiver<T, C, A> {
+        ReadOnlyWatchReceiver { inner: self }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Create a new receiver for the channel that cannot create senders.
+    ///
+    /// See [`subscribe`](WatchSender::subscribe).
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn subscribe_read_only(&self) -> ReadOnlyWatchReceiver<T, C, A> {
+        self.subscribe().into_read_only()
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> ReadOnlyWatchReceiver<T, C, A> {
+    /// Get a clone of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        self.inner.get()
+    }
+
+    /// Get a clone of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        self.inner.get_if_new()
+    }
+
+    /// See [`WatchReceiver::get_into`].
+    pub fn get_into(&mut self, dst: &mut T) {
+        self.inner.get_into(dst);
+    }
+
+    /// See [`WatchReceiver::get_if_new_into`].
+    pub fn get_if_new_into(&mut self, dst: &mut T) -> bool {
+        self.inner.get_if_new_into(dst)
+    }
+
+    /// See [`WatchReceiver::missed_values`].
+    pub fn missed_values(&mut self) -> (Vec<T>, u64) {
+        self.inner.missed_values()
+    }
+
+    /// See [`WatchReceiver::wait_with`].
+    pub fn wait_with<F>(&mut self, idle: F) -> T
+    where
+        F: FnMut(),
+    {
+        self.inner.wait_with(idle)
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> ReadOnlyWatchReceiver<T, C, A> {
+    /// See [`WatchReceiver::wait`].
+    pub fn wait(&mut self) -> T {
+        self.inner.wait()
+    }
+
+    /// See [`WatchReceiver::wait_into`].
+    pub fn wait_into(&mut self, dst: &mut T) {
+        self.inner.wait_into(dst);
+    }
+
+    /// See [`WatchReceiver::recv`].
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        self.inner.recv()
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> ReadOnlyWatchReceiver<T, C, A> {
+    /// See [`WatchReceiver::wait_timeout`].
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
+        self.inner.wait_timeout(duration)
+    }
+
+    /// See [`WatchReceiver::recv_timeout`].
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        self.inner.recv_timeout(duration)
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> ReadOnlyWatchReceiver<T, C, A> {
+    /// See [`WatchReceiver::get_shared`].
+    pub fn get_shared(&mut self) -> Arc<T> {
+        self.inner.get_shared()
+    }
+
+    /// See [`WatchReceiver::get_if_new_shared`].
+    pub fn get_if_new_shared(&mut self) -> Option<Arc<T>> {
+        self.inner.get_if_new_shared()
+    }
+
+    /// See [`WatchReceiver::wait_shared`].
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    pub fn wait_shared(&mut self) -> Arc<T> {
+        self.inner.wait_shared()
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.inner.has_changed()
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Get the identity of the channel that this receiver belongs to.
+    ///
+    /// See [`ChannelId`].
+    pub fn channel_id(&self) -> ChannelId {
+        self.inner.channel_id()
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> Clone for ReadOnlyWatchReceiver<T, C, A> {
+    fn clone(&self) -> ReadOnlyWatchReceiver<T, C, A> {
+        ReadOnlyWatchReceiver {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar, A: Allocator> fmt::Debug for ReadOnlyWatchReceiver<T, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadOnlyWatchReceiver")
+            .field(&self.inner)
+            .finish()
+    }
+}
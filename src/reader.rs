@@ -0,0 +1,116 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+use crate::{
+    allocator::SharedArc,
+    backend::{DefaultCondvar, RawCondvar},
+    Allocator, Global, Shared, WatchReceiver, WatchSender,
+};
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// A handle that reads a channel by version, without keeping track of what
+/// it has seen.
+///
+/// This is created by [`WatchSender::reader`] or [`WatchReceiver::reader`].
+/// Every method takes `&self`, so one reader can be shared by many threads,
+/// such as the handlers of long-polling requests that each bring the version
+/// their client saw last. The versions are the same numbers as those in a
+/// `Snapshot`, and they change with every new value.
+pub struct WatchReader<T, C: RawCondvar = DefaultCondvar, A: Allocator = Global> {
+    pub(crate) shared: SharedArc<Shared<T, C>, A>,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Create a reader for the channel.
+    pub fn reader(&self) -> WatchReader<T, C, A> {
+        WatchReader {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Create a reader for the channel.
+    pub fn reader(&self) -> WatchReader<T, C, A> {
+        WatchReader {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReader<T, C, A> {
+    /// The version of the latest value.
+    pub fn version(&self) -> u64 {
+        self.shared.version()
+    }
+
+    /// Get a shared handle to the latest value, together with its version.
+    pub fn get_shared(&self) -> (Arc<T>, u64) {
+        let lock = self.shared.value.read();
+        (lock.value.clone(), lock.version)
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        !self.shared.state.lock().is_open()
+    }
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReader<T, C, A> {
+    /// Get a clone of the latest value, together with its version.
+    pub fn get(&self) -> (T, u64) {
+        let (value, version) = self.get_shared();
+        (T::clone(&value), version)
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout, A: Allocator + Clone> WatchReader<T, C, A> {
+    /// Wait until the channel holds a value whose version is not `version`,
+    /// and return a clone of it together with its version.
+    ///
+    /// This returns at once if the channel has already moved past `version`,
+    /// and returns `None` if `timeout` passes first. Pass the returned
+    /// version to the next call to wait for the value after it.
+    pub fn wait_newer_than(&self, version: u64, timeout: Duration) -> Option<(T, u64)> {
+        let deadline = self.shared.deadline(timeout);
+        let state = self.shared.state.lock();
+        let (state, ready) = self
+            .shared
+            .wait_while_until(state, deadline, |state| state.version == version);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.get())
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> Clone for WatchReader<T, C, A> {
+    fn clone(&self) -> WatchReader<T, C, A> {
+        WatchReader {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar, A: Allocator> fmt::Debug for WatchReader<T, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("WatchReader");
+        self.shared.debug_value(&mut d);
+        self.shared.debug_counts(&mut d);
+        d.finish()
+    }
+}
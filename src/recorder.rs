@@ -0,0 +1,185 @@
+use crate::{backend::RawCondvar, Allocator, WatchSender};
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::fmt;
+use lock_api::{Mutex, RawMutex};
+use std::{
+    sync::{Mutex as StdMutex, MutexGuard as StdMutexGuard, PoisonError},
+    time::Instant,
+};
+
+/// A value that was published on a channel, as captured by a [`Recorder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Recorded<T> {
+    /// The value that was published.
+    pub value: T,
+    /// The version that the value was published with.
+    pub version: u64,
+    /// When the value was published, on the clock of the channel.
+    pub at: Instant,
+}
+
+struct Entry<T> {
+    value: T,
+    version: u64,
+    at: Instant,
+}
+
+type Log<T> = StdMutex<Vec<Entry<T>>>;
+
+/// The logs of the recorders of a channel.
+///
+/// A log is removed once its recorder has been dropped. The list is locked
+/// while the value is write-locked, so the values are logged in the order in
+/// which they were published.
+///
+/// The logs keep clones of the values rather than the values themselves,
+/// so that a recorder never counts as holding the value of the channel, as
+/// in [`WatchSender::replace_with`]. Each log comes with the function that
+/// clones them, as the channel does not require `T: Clone`.
+pub(crate) struct Recorders<R: RawMutex, T> {
+    logs: Mutex<R, Vec<LogRef<T>>>,
+}
+
+struct LogRef<T> {
+    log: Weak<Log<T>>,
+    clone: fn(&T) -> T,
+}
+
+impl<R: RawMutex, T> Recorders<R, T> {
+    pub(crate) fn new() -> Recorders<R, T> {
+        Recorders {
+            logs: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn add(&self) -> Arc<Log<T>>
+    where
+        T: Clone,
+    {
+        let log = Arc::new(StdMutex::new(Vec::new()));
+        self.logs.lock().push(LogRef {
+            log: Arc::downgrade(&log),
+            clone: T::clone,
+        });
+        log
+    }
+
+    pub(crate) fn record(&self, version: u64, at: Instant, value: &T) {
+        self.logs
+            .lock()
+            .retain(|LogRef { log, clone }| match log.upgrade() {
+                Some(log) => {
+                    lock(&log).push(Entry {
+                        value: clone(value),
+                        version,
+                        at,
+                    });
+                    true
+                }
+                None => false,
+            });
+    }
+}
+
+fn lock<T>(log: &Log<T>) -> StdMutexGuard<'_, Vec<Entry<T>>> {
+    log.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Captures every value published on a channel, for assertions in tests.
+///
+/// This is created by [`WatchSender::record`]. Unlike a receiver, it does
+/// not miss values that are replaced before it looks: every value sent by
+/// any sender of the channel after the recorder was created is kept, along
+/// with its version and the time it was published, until the recorder is
+/// dropped or the values are taken.
+pub struct Recorder<T> {
+    log: Arc<Log<T>>,
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Start recording every value published on the channel.
+    ///
+    /// The recorder captures the sends of every sender of the channel, not
+    /// just this one, and never changes which receivers are woken. The value
+    /// that the channel holds when this is called is not recorded.
+    ///
+    /// Each value is cloned as it is published, so that the recorder does
+    /// not share it with the channel, and updates in place such as
+    /// [`replace_with`](WatchSender::replace_with) still work.
+    pub fn record(&self) -> Recorder<T> {
+        Recorder {
+            log: self.shared.recorders.add(),
+        }
+    }
+}
+
+impl<T: Clone> Recorder<T> {
+    /// Get the recorded values, oldest first.
+    pub fn values(&self) -> Vec<T> {
+        lock(&self.log)
+            .iter()
+            .map(|entry| T::clone(&entry.value))
+            .collect()
+    }
+
+    /// Get the recorded values with their versions and timestamps, oldest
+    /// first.
+    pub fn records(&self) -> Vec<Recorded<T>> {
+        lock(&self.log).iter().map(Entry::to_recorded).collect()
+    }
+
+    /// Remove the recorded values and return them, oldest first.
+    ///
+    /// The recorder keeps recording the values published afterwards.
+    pub fn take(&self) -> Vec<Recorded<T>> {
+        let entries = core::mem::take(&mut *lock(&self.log));
+        entries
+            .into_iter()
+            .map(|entry| Recorded {
+                value: entry.value,
+                version: entry.version,
+                at: entry.at,
+            })
+            .collect()
+    }
+}
+
+impl<T: Clone + PartialEq + fmt::Debug> Recorder<T> {
+    /// Assert that exactly `expected` has been recorded, in that order.
+    ///
+    /// # Panics
+    ///
+    /// Panics with both sequences of values if they differ.
+    #[track_caller]
+    pub fn assert_published(&self, expected: &[T]) {
+        let values = self.values();
+        assert!(
+            values == expected,
+            "recorded values differ from the expected ones\n  recorded: {:?}\n  expected: {:?}",
+            values,
+            expected,
+        );
+    }
+}
+
+impl<T: Clone> Entry<T> {
+    fn to_recorded(&self) -> Recorded<T> {
+        Recorded {
+            value: T::clone(&self.value),
+            version: self.version,
+            at: self.at,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Recorder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("len", &lock(&self.log).len())
+            .finish()
+    }
+}
@@ -0,0 +1,127 @@
+//! Turns the deadlock of a closure that uses the channel it is running on
+//! into a panic.
+//!
+//! While a closure passed to `update` and its variants runs, the value of
+//! the channel is write-locked, so reading or sending on the same channel
+//! from inside the closure blocks forever. In debug builds with `std`, each
+//! thread keeps a list of the channels that it runs such a closure on, and
+//! [`ValueLock`] checks that list before locking. In other builds the check
+//! is compiled out.
+use lock_api::{RawRwLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The lock around the value of a channel.
+///
+/// It has the methods of [`RwLock`] that the channel uses, and the blocking
+/// ones check that this thread is not running a closure on the channel.
+pub(crate) struct ValueLock<R, V> {
+    lock: RwLock<R, V>,
+}
+
+impl<R: RawRwLock, V> ValueLock<R, V> {
+    pub(crate) fn new(value: V) -> Self {
+        ValueLock {
+            lock: RwLock::new(value),
+        }
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, R, V> {
+        check(self.id(), "read");
+        self.lock.read()
+    }
+
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, R, V> {
+        check(self.id(), "written");
+        self.lock.write()
+    }
+
+    pub(crate) fn try_read(&self) -> Option<RwLockReadGuard<'_, R, V>> {
+        self.lock.try_read()
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut V {
+        self.lock.get_mut()
+    }
+
+    pub(crate) fn into_inner(self) -> V {
+        self.lock.into_inner()
+    }
+
+    /// Mark the value as locked by `operation` on this thread, until the
+    /// returned scope is dropped.
+    ///
+    /// This must be called after locking the value, and the scope dropped
+    /// before it is unlocked.
+    pub(crate) fn enter(&self, operation: &'static str) -> Scope {
+        Scope::enter(self.id(), operation)
+    }
+
+    fn id(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+#[cfg(all(feature = "std", debug_assertions, not(loom)))]
+std::thread_local! {
+    /// The locks that this thread holds while running a closure, with the
+    /// name of the operation that runs it.
+    static HELD: core::cell::RefCell<alloc::vec::Vec<(usize, &'static str)>> =
+        const { core::cell::RefCell::new(alloc::vec::Vec::new()) };
+}
+
+/// Panic if this thread runs a closure on the lock `id`.
+#[cfg(all(feature = "std", debug_assertions, not(loom)))]
+fn check(id: usize, access: &str) {
+    let operation = HELD
+        .try_with(|held| {
+            held.borrow()
+                .iter()
+                .find(|(held, _)| *held == id)
+                .map(|(_, operation)| *operation)
+        })
+        .ok()
+        .flatten();
+    if let Some(operation) = operation {
+        panic!(
+            "a watch channel was {} from inside the closure passed to `{}` \
+             on the same channel, which would deadlock",
+            access, operation
+        );
+    }
+}
+
+#[cfg(not(all(feature = "std", debug_assertions, not(loom))))]
+#[inline(always)]
+fn check(_id: usize, _access: &str) {}
+
+/// Marks a lock as held by a closure on this thread while it is alive, see
+/// [`ValueLock::enter`].
+pub(crate) struct Scope {
+    #[cfg(all(feature = "std", debug_assertions, not(loom)))]
+    id: usize,
+}
+
+impl Scope {
+    #[cfg(all(feature = "std", debug_assertions, not(loom)))]
+    fn enter(id: usize, operation: &'static str) -> Scope {
+        let _ = HELD.try_with(|held| held.borrow_mut().push((id, operation)));
+        Scope { id }
+    }
+
+    #[cfg(not(all(feature = "std", debug_assertions, not(loom))))]
+    #[inline(always)]
+    fn enter(_id: usize, _operation: &'static str) -> Scope {
+        Scope {}
+    }
+}
+
+#[cfg(all(feature = "std", debug_assertions, not(loom)))]
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let _ = HELD.try_with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(i) = held.iter().rposition(|(held, _)| *held == self.id) {
+                held.remove(i);
+            }
+        });
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{allocator, backend::RawCondvar, channel, Shared, WatchReceiver, WatchSender};
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// The channels created by [`channel_named`], see [`registry`].
+pub struct Registry {
+    entries: Mutex<Vec<Entry>>,
+}
+
+struct Entry {
+    name: Arc<str>,
+    channel: Weak<dyn Inspect>,
+}
+
+/// What the registry reads from a channel without knowing its type.
+trait Inspect: Send + Sync {
+    fn info(&self, name: &Arc<str>, now: Instant) -> ChannelInfo;
+}
+
+/// What [`Registry::dump`] tells about a channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelInfo {
+    /// The name given to [`channel_named`].
+    pub name: Arc<str>,
+    /// The version of the latest value.
+    pub version: u64,
+    /// The number of receivers, see [`WatchSender::receiver_count`].
+    pub receiver_count: usize,
+    /// The number of senders, which is zero once the channel is closed.
+    pub sender_count: usize,
+    /// How long ago the value last changed, or the channel was created if it
+    /// has not changed since.
+    pub last_update_age: Duration,
+}
+
+static REGISTRY: Registry = Registry {
+    entries: Mutex::new(Vec::new()),
+};
+
+/// The registry of the channels created by [`channel_named`].
+pub fn registry() -> &'static Registry {
+    &REGISTRY
+}
+
+/// Creates a new watch channel with a name, and adds it to the
+/// [`registry`].
+///
+/// The registry does not keep the channel alive, and the channel leaves it
+/// once every sender and receiver has been dropped. Names are only for
+/// diagnostics, so several channels may share one.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn channel_named<T, N>(name: N, value: T) -> (WatchSender<T>, WatchReceiver<T>)
+where
+    T: Send + Sync + 'static,
+    N: Into<Arc<str>>,
+{
+    let (sender, receiver) = channel(value);
+    sender.shared.state.lock().changed_at = Some(Instant::now());
+    let channel = allocator::downgrade(&sender.shared);
+    REGISTRY.lock().push(Entry {
+        name: name.into(),
+        channel: channel as Weak<dyn Inspect>,
+    });
+    (sender, receiver)
+}
+
+impl Registry {
+    fn lock(&self) -> MutexGuard<'_, Vec<Entry>> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() == entries.capacity() {
+            entries.retain(|entry| entry.channel.strong_count() > 0);
+        }
+        entries
+    }
+
+    /// Describe every live channel in the registry, in the order they were
+    /// created.
+    pub fn dump(&self) -> Vec<ChannelInfo> {
+        // The channels are released after the registry is unlocked, as this
+        // may hold the last handle to one, whose value could create a
+        // channel of its own when dropped.
+        let mut channels = Vec::new();
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|entry| match entry.channel.upgrade() {
+                Some(channel) => {
+                    channels.push((entry.name.clone(), channel));
+                    true
+                }
+                None => false,
+            });
+        let now = Instant::now();
+        channels
+            .iter()
+            .map(|(name, channel)| channel.info(name, now))
+            .collect()
+    }
+}
+
+impl<T, C: RawCondvar> Inspect for Shared<T, C>
+where
+    Shared<T, C>: Send + Sync,
+{
+    fn info(&self, name: &Arc<str>, now: Instant) -> ChannelInfo {
+        let state = self.state.lock();
+        ChannelInfo {
+            name: name.clone(),
+            version: state.version,
+            receiver_count: state.receivers,
+            sender_count: state.senders,
+            last_update_age: state
+                .changed_at
+                .map_or(Duration::ZERO, |at| now.saturating_duration_since(at)),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{Recorder, WatchSender};
+use alloc::{sync::Arc, vec::Vec};
+use std::{
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// A sequence of values with the time at which each was published, that can
+/// be replayed into a channel.
+///
+/// A recording is taken from a [`Recorder`] with [`Recorder::recording`], or
+/// built by hand with [`push`](Recording::push). With the `serde` feature, it
+/// can be saved and loaded, so that traces captured elsewhere can be
+/// replayed in tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording<T> {
+    /// The values, each with its time since the start of the recording,
+    /// oldest first.
+    entries: Vec<(Duration, T)>,
+}
+
+impl<T> Recording<T> {
+    /// Create an empty recording.
+    pub const fn new() -> Recording<T> {
+        Recording {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add `value`, published `at` after the start of the recording.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is earlier than the time of the last value.
+    pub fn push(&mut self, at: Duration, value: T) {
+        if let Some((last, _)) = self.entries.last() {
+            assert!(
+                at >= *last,
+                "values must be added in the order they were published"
+            );
+        }
+        self.entries.push((at, value));
+    }
+
+    /// The values with their times since the start of the recording, oldest
+    /// first.
+    pub fn entries(&self) -> &[(Duration, T)] {
+        &self.entries
+    }
+
+    /// The number of values in the recording.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the recording has no values.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T> Default for Recording<T> {
+    fn default() -> Recording<T> {
+        Recording::new()
+    }
+}
+
+impl<T: Clone> Recorder<T> {
+    /// Get the recorded values as a [`Recording`], with their times counted
+    /// from the first value.
+    pub fn recording(&self) -> Recording<T> {
+        let records = self.records();
+        let start = records.first().map(|record| record.at);
+        let entries = records
+            .into_iter()
+            .map(|record| {
+                let at = record.at.saturating_duration_since(start.unwrap());
+                (at, record.value)
+            })
+            .collect();
+        Recording { entries }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Recording<T> {
+    /// Send the values of the recording on `sender` from a thread, with the
+    /// same gaps between them as when they were recorded.
+    ///
+    /// The gaps are divided by `speed`, so `2.0` replays twice as fast, and
+    /// a speed of `0.0` sends the values one after the other without
+    /// waiting. The first value is sent right away. The thread exits once
+    /// every value is sent, or once the returned handle is stopped or
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is negative or NaN.
+    pub fn replay_into(&self, sender: &WatchSender<T>, speed: f32) -> ReplayHandle {
+        assert!(speed >= 0.0, "the replay speed must not be negative");
+        let entries = self.entries.clone();
+        let sender = sender.clone();
+        let control = Arc::new(Control {
+            state: Mutex::new(State {
+                paused: false,
+                stopped: false,
+                replayed: 0,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let thread = {
+            let control = control.clone();
+            thread::Builder::new()
+                .name("watch-replay".into())
+                .spawn(move || replay(&entries, &sender, speed, &control))
+                .expect("failed to spawn thread")
+        };
+
+        ReplayHandle {
+            control,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Handle to the thread spawned by [`Recording::replay_into`].
+///
+/// Dropping the handle asks the thread to stop without waiting for it.
+pub struct ReplayHandle {
+    control: Arc<Control>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Shared between a handle and its thread.
+struct Control {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+struct State {
+    paused: bool,
+    stopped: bool,
+    /// The number of values sent so far.
+    replayed: usize,
+}
+
+impl Control {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Send every entry at its time, scaled by `speed`, until stopped.
+fn replay<T: Clone>(
+    entries: &[(Duration, T)],
+    sender: &WatchSender<T>,
+    speed: f32,
+    control: &Control,
+) {
+    let mut start = Instant::now();
+    for (at, value) in entries {
+        let due = scale(*at, speed);
+        let mut state = control.lock();
+        loop {
+            if state.stopped {
+                return;
+            }
+            if state.paused {
+                // The time spent paused does not count towards the gaps.
+                let paused_at = Instant::now();
+                while state.paused && !state.stopped {
+                    state = control
+                        .changed
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+                start += paused_at.elapsed();
+                continue;
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= due {
+                break;
+            }
+            state = control
+                .changed
+                .wait_timeout(state, due - elapsed)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        drop(state);
+
+        sender.send(value.clone());
+        control.lock().replayed += 1;
+    }
+}
+
+/// The time at which to send a value recorded `at`, when replaying at
+/// `speed`.
+fn scale(at: Duration, speed: f32) -> Duration {
+    if speed == 0.0 {
+        return Duration::ZERO;
+    }
+    Duration::try_from_secs_f64(at.as_secs_f64() / f64::from(speed)).unwrap_or(Duration::MAX)
+}
+
+impl ReplayHandle {
+    /// Stop sending values until [`resume`](ReplayHandle::resume) is called.
+    ///
+    /// The time spent paused does not count towards the gap before the next
+    /// value.
+    pub fn pause(&self) {
+        self.control.lock().paused = true;
+        self.control.changed.notify_all();
+    }
+
+    /// Continue a replay that was paused.
+    pub fn resume(&self) {
+        self.control.lock().paused = false;
+        self.control.changed.notify_all();
+    }
+
+    /// Returns `true` if the replay is paused.
+    pub fn is_paused(&self) -> bool {
+        self.control.lock().paused
+    }
+
+    /// The number of values sent so far.
+    pub fn replayed(&self) -> usize {
+        self.control.lock().replayed
+    }
+
+    /// Returns `true` if the thread has exited, because every value was sent
+    /// or the replay was stopped.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Wait until every value has been sent, and return how many were.
+    ///
+    /// A paused replay must be resumed from another thread for this to
+    /// return.
+    pub fn join(mut self) -> usize {
+        self.wait();
+        self.replayed()
+    }
+
+    /// Stop the replay without sending the remaining values, wait for the
+    /// thread to exit, and return how many values were sent.
+    pub fn stop(mut self) -> usize {
+        self.interrupt();
+        self.wait();
+        self.replayed()
+    }
+
+    fn wait(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    fn interrupt(&self) {
+        self.control.lock().stopped = true;
+        self.control.changed.notify_all();
+    }
+}
+
+impl Drop for ReplayHandle {
+    fn drop(&mut self) {
+        self.interrupt();
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+use crate::{backend::RawCondvar, Allocator, WatchReceiver, WatchSender};
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+impl<T, E, C: RawCondvar, A: Allocator + Clone> WatchSender<Result<T, E>, C, A> {
+    /// Send `Ok(value)`.
+    pub fn send_ok(&self, value: T) {
+        self.send(Ok(value));
+    }
+
+    /// Send `Err(error)`.
+    pub fn send_err(&self, error: E) {
+        self.send(Err(error));
+    }
+}
+
+impl<T, E, C: RawCondvar, A: Allocator + Clone> WatchReceiver<Result<T, E>, C, A> {
+    /// Returns `true` if the latest value is an `Err`, whether or not this
+    /// receiver has seen it.
+    pub fn last_is_err(&self) -> bool {
+        self.shared.value.read().value.is_err()
+    }
+}
+
+impl<T, E: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<Result<T, E>, C, A> {
+    /// Get a clone of the error if the latest value is an `Err`, and mark it
+    /// seen.
+    ///
+    /// This returns the error even if it has been seen, such as when
+    /// [`wait_ok`](WatchReceiver::wait_ok) passed over it. An `Ok` value is
+    /// left unseen, so that a wait for one still returns it.
+    pub fn get_latest_err(&mut self) -> Option<E> {
+        let latest = self.track(|shared, seen| {
+            let lock = shared.value.read();
+            if lock.value.is_ok() {
+                return None;
+            }
+            Some(lock.get(seen).clone())
+        })?;
+        match &*latest {
+            Ok(_) => None,
+            Err(error) => Some(error.clone()),
+        }
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, E, C: RawCondvar, A: Allocator + Clone> WatchReceiver<Result<T, E>, C, A> {
+    /// Wait until a new value is `Ok`, and return a clone of what it holds.
+    ///
+    /// This returns at once if the latest value is `Ok` and has not been
+    /// seen. New `Err` values are marked seen while waiting, but can still be
+    /// read with [`get_latest_err`](WatchReceiver::get_latest_err) for as
+    /// long as they are the latest value.
+    pub fn wait_ok(&mut self) -> T {
+        self.wait_accepted(|value| value.as_ref().ok().cloned())
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, E, C: RawCondvarTimeout, A: Allocator + Clone> WatchReceiver<Result<T, E>, C, A> {
+    /// Like [`wait_ok`], but gives up after `duration`.
+    ///
+    /// The timeout covers the whole wait, however many `Err` values arrive
+    /// in the meantime.
+    ///
+    /// [`wait_ok`]: WatchReceiver::wait_ok
+    pub fn wait_ok_timeout(&mut self, duration: Duration) -> Option<T> {
+        self.wait_accepted_timeout(duration, |value| value.as_ref().ok().cloned())
+    }
+}
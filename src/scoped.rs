@@ -0,0 +1,223 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::backend::RawCondvarTimeout;
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    SenderId, Shared,
+};
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+
+/// A watch channel whose handles borrow it rather than sharing ownership.
+///
+/// This avoids the allocation made by [`channel`], and lets the value borrow
+/// data that only lives as long as a `std::thread::scope`. Since the
+/// channel outlives every handle, it is never closed.
+///
+/// [`channel`]: crate::channel
+pub struct ScopedChannel<T, C: RawCondvar = DefaultCondvar> {
+    shared: Shared<T, C>,
+}
+
+/// The sender for a [`ScopedChannel`].
+pub struct ScopedSender<'a, T, C: RawCondvar = DefaultCondvar> {
+    shared: &'a Shared<T, C>,
+}
+
+/// The receiver for a [`ScopedChannel`].
+///
+/// The receiver can be cloned. Each clone will yield a new receiver that
+/// receives the same messages.
+pub struct ScopedReceiver<'a, T, C: RawCondvar = DefaultCondvar> {
+    shared: &'a Shared<T, C>,
+    last_seen_version: u64,
+}
+
+/// The id that every scoped sender writes with.
+///
+/// Scoped senders are copies of each other, so they cannot be told apart.
+const SCOPED_SENDER: SenderId = SenderId(0);
+
+/// Creates a new scoped watch channel.
+///
+/// The handles are obtained from [`ScopedChannel::sender`] and
+/// [`ScopedChannel::receiver`].
+pub fn scoped<T>(value: T) -> ScopedChannel<T> {
+    ScopedChannel::new(value)
+}
+
+impl<T, C: RawCondvar> ScopedChannel<T, C> {
+    pub(crate) fn new(value: T) -> Self {
+        ScopedChannel {
+            shared: Shared::new(value, 1),
+        }
+    }
+
+    /// Create a new sender for the channel.
+    pub fn sender(&self) -> ScopedSender<'_, T, C> {
+        ScopedSender {
+            shared: &self.shared,
+        }
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// The current value is not initially considered seen by the receiver.
+    pub fn receiver(&self) -> ScopedReceiver<'_, T, C> {
+        ScopedReceiver {
+            shared: &self.shared,
+            last_seen_version: self.shared.version().wrapping_sub(1),
+        }
+    }
+}
+
+impl<'a, T, C: RawCondvar> ScopedSender<'a, T, C> {
+    /// Send a new message and notify all receivers currently waiting for a
+    /// message.
+    pub fn send(&self, value: T) {
+        self.shared.send(value, SCOPED_SENDER);
+    }
+
+    /// Replace the message by the result of a closure and notify all receivers
+    /// currently waiting for a message.
+    ///
+    /// See [`WatchSender::update_with`](crate::WatchSender::update_with).
+    pub fn update_with<F>(&self, f: F)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        self.shared.update_with(f, SCOPED_SENDER);
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    pub fn subscribe(&self) -> ScopedReceiver<'a, T, C> {
+        ScopedReceiver {
+            shared: self.shared,
+            last_seen_version: self.shared.version(),
+        }
+    }
+}
+
+impl<'a, T: Clone, C: RawCondvar> ScopedSender<'a, T, C> {
+    /// Update the message by a closure and notify all receivers currently waiting for a message.
+    ///
+    /// See [`WatchSender::update`](crate::WatchSender::update).
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        self.shared.update(f, SCOPED_SENDER);
+    }
+}
+
+impl<T: Clone, C: RawCondvar> ScopedReceiver<'_, T, C> {
+    /// Get a clone of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        self.shared.get(&mut self.last_seen_version)
+    }
+
+    /// Get a clone of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        self.shared.get_if_new(&mut self.last_seen_version)
+    }
+
+    /// Overwrite `dst` with the latest value sent on the channel.
+    ///
+    /// See [`WatchReceiver::get_into`](crate::WatchReceiver::get_into).
+    pub fn get_into(&mut self, dst: &mut T) {
+        self.shared.get_into(&mut self.last_seen_version, dst);
+    }
+
+    /// Overwrite `dst` with the latest value if that value has not previously
+    /// been seen by this receiver.
+    ///
+    /// See [`WatchReceiver::get_if_new_into`](crate::WatchReceiver::get_if_new_into).
+    pub fn get_if_new_into(&mut self, dst: &mut T) -> bool {
+        self.shared
+            .get_if_new_into(&mut self.last_seen_version, dst)
+    }
+
+    /// Wait for a new value by polling the channel, calling `idle` whenever
+    /// there is nothing new.
+    ///
+    /// See [`WatchReceiver::wait_with`](crate::WatchReceiver::wait_with).
+    pub fn wait_with<F>(&mut self, mut idle: F) -> T
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Some(value) = self.get_if_new() {
+                return value;
+            }
+            idle();
+        }
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar> ScopedReceiver<'_, T, C> {
+    /// This method waits until a new value becomes available and return a clone
+    /// of it.
+    pub fn wait(&mut self) -> T {
+        self.shared.wait(&mut self.last_seen_version)
+    }
+
+    /// This method waits until a new value becomes available and overwrites
+    /// `dst` with it.
+    pub fn wait_into(&mut self, dst: &mut T) {
+        self.shared.wait_into(&mut self.last_seen_version, dst);
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout> ScopedReceiver<'_, T, C> {
+    /// This method waits until a new value becomes available and return a clone
+    /// of it, timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
+        self.shared
+            .wait_timeout(&mut self.last_seen_version, duration)
+    }
+}
+
+impl<'a, T, C: RawCondvar> ScopedReceiver<'a, T, C> {
+    /// Create a new sender for this channel.
+    pub fn new_sender(&self) -> ScopedSender<'a, T, C> {
+        ScopedSender {
+            shared: self.shared,
+        }
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.has_changed(self.last_seen_version)
+    }
+}
+
+impl<T, C: RawCondvar> Clone for ScopedSender<'_, T, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, C: RawCondvar> Copy for ScopedSender<'_, T, C> {}
+
+impl<T, C: RawCondvar> Clone for ScopedReceiver<'_, T, C> {
+    fn clone(&self) -> Self {
+        ScopedReceiver {
+            shared: self.shared,
+            last_seen_version: self.last_seen_version,
+        }
+    }
+}
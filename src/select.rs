@@ -0,0 +1,283 @@
+use crate::{clock::Deadline, RecvError};
+use alloc::sync::Arc;
+pub use core::sync::atomic::AtomicUsize;
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::{
+    task::Wake,
+    thread::{self, Thread},
+};
+
+/// Wait for the first of several receivers to get a value that it has not
+/// seen, and run the arm of that receiver.
+///
+/// Each arm has the form `pattern = receiver => body`, where `receiver` is a
+/// [`WatchReceiver`](crate::WatchReceiver) or a `&mut` borrow of one, and
+/// the body runs with the new value bound to `pattern`. A body that is a
+/// block may leave out the comma after it. Only the value of the arm that
+/// runs is marked seen, so the other receivers still have their values
+/// when the next select starts. The bodies run in the enclosing function,
+/// so `break`, `continue`, `return` and `?` act on it, and every body must
+/// have the same type, as with the arms of a `match`.
+///
+/// An optional last arm `default(timeout) => body` runs if no receiver got
+/// a value within `timeout`, a [`Duration`]. Without it, the select waits
+/// for as long as it takes, and panics if every receiver was closed.
+///
+/// When several receivers have a value, each time a select runs it starts
+/// looking at the arm after the one the previous run at the same place in
+/// the code started with, so that a busy receiver does not keep the others
+/// from ever being picked.
+///
+/// An arm is disabled for the rest of the select once its channel is
+/// closed, or once it gets a value that does not match its pattern, which
+/// is then marked seen and dropped.
+///
+/// This is added by the `macros` feature.
+#[macro_export]
+macro_rules! select {
+    // `default` arms come first, as `default(timeout)` also parses as a
+    // pattern.
+    (@parse [$($arm:tt)*] [$($default:tt)+] default $($rest:tt)*) => {
+        ::core::compile_error!("`select!` can only have one `default` arm")
+    };
+    (@parse [$($arm:tt)*] [] default ($timeout:expr) => $body:block , $($rest:tt)*) => {
+        $crate::select!(@parse [$($arm)*] [($timeout) ($body)] $($rest)*)
+    };
+    (@parse [$($arm:tt)*] [] default ($timeout:expr) => $body:block $($rest:tt)*) => {
+        $crate::select!(@parse [$($arm)*] [($timeout) ($body)] $($rest)*)
+    };
+    (@parse [$($arm:tt)*] [] default ($timeout:expr) => $body:expr , $($rest:tt)*) => {
+        $crate::select!(@parse [$($arm)*] [($timeout) ($body)] $($rest)*)
+    };
+    (@parse [$($arm:tt)*] [] default ($timeout:expr) => $body:expr) => {
+        $crate::select!(@parse [$($arm)*] [($timeout) ($body)])
+    };
+    (@parse [$($arm:tt)*] [$($default:tt)+] $pat:pat = $($rest:tt)*) => {
+        ::core::compile_error!("the `default` arm of `select!` must be the last one")
+    };
+    (@parse [$($arm:tt)*] [] $pat:pat = $receiver:expr => $body:block , $($rest:tt)*) => {
+        $crate::select!(@parse [$($arm)* [($pat) ($receiver) ($body)]] [] $($rest)*)
+    };
+    (@parse [$($arm:tt)*] [] $pat:pat = $receiver:expr => $body:block $($rest:tt)*) => {
+        $crate::select!(@parse [$($arm)* [($pat) ($receiver) ($body)]] [] $($rest)*)
+    };
+    (@parse [$($arm:tt)*] [] $pat:pat = $receiver:expr => $body:expr , $($rest:tt)*) => {
+        $crate::select!(@parse [$($arm)* [($pat) ($receiver) ($body)]] [] $($rest)*)
+    };
+    (@parse [$($arm:tt)*] [] $pat:pat = $receiver:expr => $body:expr) => {
+        $crate::select!(@parse [$($arm)* [($pat) ($receiver) ($body)]] [])
+    };
+    (@parse [] [$($default:tt)*]) => {
+        ::core::compile_error!("`select!` needs at least one `pattern = receiver => body` arm")
+    };
+    (@parse [$($arm:tt)+] [$($default:tt)*]) => {
+        $crate::select!(@run [$($arm)+] [$($default)*])
+    };
+    (@parse [$($arm:tt)*] [$($default:tt)*] $($rest:tt)+) => {
+        ::core::compile_error!(
+            "expected a `pattern = receiver => body` or `default(timeout) => body` arm"
+        )
+    };
+
+    (@run [$([($pat:pat) ($receiver:expr) ($body:expr)])+] [$(($timeout:expr) ($default:expr))?]) => {{
+        static START: $crate::__private::select::AtomicUsize =
+            $crate::__private::select::AtomicUsize::new(0);
+        let mut branches = $crate::select!(@branches $([($pat) ($receiver)])+);
+        $crate::__private::select::run(
+            &mut branches,
+            &START,
+            $crate::select!(@timeout $($timeout)?),
+        );
+        let values = $crate::__private::select::Branches::into_values(branches);
+        $crate::select!(@bodies values $([($pat) ($body)])+ [$($default)?])
+    }};
+
+    (@branches [($pat:pat) ($receiver:expr)] $($rest:tt)*) => {
+        (
+            $crate::__private::select::Branch::new(($receiver).changed(), |value| {
+                #[allow(unused_variables, unused_mut, unreachable_patterns)]
+                let matched = match value {
+                    $pat => true,
+                    _ => false,
+                };
+                matched
+            }),
+            $crate::select!(@branches $($rest)*),
+        )
+    };
+    (@branches) => {
+        ()
+    };
+
+    (@timeout $timeout:expr) => {
+        ::core::option::Option::Some::<::core::time::Duration>($timeout)
+    };
+    (@timeout) => {
+        ::core::option::Option::None
+    };
+
+    (@bodies $values:ident [($pat:pat) ($body:expr)] $($rest:tt)+) => {{
+        let (value, rest) = $values;
+        if let ::core::option::Option::Some($pat) = value {
+            $body
+        } else {
+            $crate::select!(@bodies rest $($rest)+)
+        }
+    }};
+    (@bodies $values:ident [$default:expr]) => {{
+        let () = $values;
+        $default
+    }};
+    (@bodies $values:ident []) => {{
+        let () = $values;
+        ::core::unreachable!()
+    }};
+
+    ($($input:tt)*) => {
+        $crate::select!(@parse [] [] $($input)*)
+    };
+}
+
+/// Wakes the thread that runs a select.
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// An arm of [`select!`]: the future that waits for the receiver, and the
+/// value it got once it is picked.
+pub struct Branch<F, T, M> {
+    /// `None` once the arm is disabled.
+    future: Option<F>,
+    matches: M,
+    value: Option<T>,
+}
+
+impl<F, T, M> Branch<F, T, M>
+where
+    F: Future<Output = Result<T, RecvError>> + Unpin,
+    M: FnMut(&T) -> bool,
+{
+    pub fn new(future: F, matches: M) -> Self {
+        Branch {
+            future: Some(future),
+            matches,
+            value: None,
+        }
+    }
+}
+
+/// The arms of a select, as a list of nested pairs that ends with `()`.
+pub trait Branches {
+    /// The value of each arm, nested the same way.
+    type Values;
+
+    const LEN: usize;
+
+    /// Poll the arm `index` places down the list.
+    ///
+    /// Returns `Ready(true)` if the arm got a value, and `Ready(false)` if
+    /// it is disabled.
+    fn poll_branch(&mut self, index: usize, cx: &mut Context<'_>) -> Poll<bool>;
+
+    /// Drop the futures, which borrow the receivers, and keep the values.
+    fn into_values(self) -> Self::Values;
+}
+
+impl Branches for () {
+    type Values = ();
+
+    const LEN: usize = 0;
+
+    fn poll_branch(&mut self, _: usize, _: &mut Context<'_>) -> Poll<bool> {
+        unreachable!()
+    }
+
+    fn into_values(self) {}
+}
+
+impl<F, T, M, R> Branches for (Branch<F, T, M>, R)
+where
+    F: Future<Output = Result<T, RecvError>> + Unpin,
+    M: FnMut(&T) -> bool,
+    R: Branches,
+{
+    type Values = (Option<T>, R::Values);
+
+    const LEN: usize = R::LEN + 1;
+
+    fn poll_branch(&mut self, index: usize, cx: &mut Context<'_>) -> Poll<bool> {
+        if index > 0 {
+            return self.1.poll_branch(index - 1, cx);
+        }
+        let branch = &mut self.0;
+        let Some(future) = &mut branch.future else {
+            return Poll::Ready(false);
+        };
+        match Pin::new(future).poll(cx) {
+            Poll::Ready(Ok(value)) if (branch.matches)(&value) => {
+                branch.value = Some(value);
+                Poll::Ready(true)
+            }
+            Poll::Ready(_) => {
+                branch.future = None;
+                Poll::Ready(false)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn into_values(self) -> Self::Values {
+        (self.0.value, self.1.into_values())
+    }
+}
+
+/// Poll the arms, starting from the one after where the previous run from
+/// `start` started, until one of them gets a value or `timeout` passes.
+///
+/// The futures wake this thread by unparking it. A value that is available
+/// when the timeout passes still wins over the `default` arm.
+pub fn run<B: Branches>(branches: &mut B, start: &AtomicUsize, timeout: Option<Duration>) {
+    let first = start.fetch_add(1, Ordering::Relaxed) % B::LEN;
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let deadline = timeout.map(Deadline::after);
+    let mut timed_out = false;
+    loop {
+        let mut disabled = 0;
+        for offset in 0..B::LEN {
+            match branches.poll_branch((first + offset) % B::LEN, &mut cx) {
+                Poll::Ready(true) => return,
+                Poll::Ready(false) => disabled += 1,
+                Poll::Pending => {}
+            }
+        }
+        match &deadline {
+            _ if timed_out => return,
+            Some(deadline) => {
+                thread::park_timeout(deadline.sleep_time());
+                timed_out = deadline.expired();
+            }
+            None => {
+                assert!(
+                    disabled < B::LEN,
+                    "every arm of `select!` is disabled, and it has no `default` arm"
+                );
+                thread::park();
+            }
+        }
+    }
+}
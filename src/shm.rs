@@ -0,0 +1,577 @@
+//! Watch channels between processes on the same host, over shared memory.
+//!
+//! A producer creates a channel with [`channel`], which places the value in
+//! a POSIX shared memory object with the given name, and other processes
+//! attach to it with [`open_receiver`]. Receivers read the value without
+//! taking a lock and wait for a new one on a futex in the shared memory, so
+//! they behave like the receivers of [`copy_channel`](crate::copy_channel).
+//!
+//! Only the process that created the channel sends. The value is kept in
+//! two slots that the sender writes in turn, and receivers only read the
+//! slot of the latest value, so a sender that dies halfway through a send
+//! leaves the previous value intact rather than a torn one. Receivers treat
+//! the channel as closed once the sender is dropped or its process is gone,
+//! which they check whenever they wait. A process that died still counts as
+//! alive until its parent has reaped it.
+//!
+//! Dropping the sender removes the name of the shared memory object, unless
+//! it has been given to another object since, and the memory itself is
+//! freed once every process has dropped its receivers. If the producer dies
+//! instead, the object stays behind until it is removed with [`remove`] or
+//! replaced by the next call to [`channel`] with the same name.
+//!
+//! Versions are 32 bits wide, so a receiver that misses exactly `2^32`
+//! sends cannot tell that the value changed.
+use crate::{RecvError, RecvTimeoutError};
+use std::{
+    cell::UnsafeCell,
+    ffi::CString,
+    fmt, io,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+    sync::{
+        atomic::{fence, AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+/// Marks an initialized region, and changes whenever its layout does.
+const MAGIC: u64 = u64::from_le_bytes(*b"watchsh1");
+
+/// How often a waiting receiver checks that the sender is still alive.
+const LIVENESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A type whose values can be copied between processes.
+///
+/// # Safety
+///
+/// Every bit pattern of the size of the type must be a valid value, and a
+/// value must not refer to memory, so the type has no references, pointers
+/// or handles such as file descriptors. The sender and the receivers must
+/// use the same definition of the type: the channel only checks that its
+/// size and alignment match.
+pub unsafe trait ShmValue: Copy + Send + 'static {}
+
+macro_rules! shm_values {
+    ($($t:ty)*) => {$(
+        // SAFETY: Every bit pattern is a valid value of a primitive number.
+        unsafe impl ShmValue for $t {}
+    )*};
+}
+
+shm_values!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize f32 f64);
+
+// SAFETY: An array is valid for every bit pattern if its elements are.
+unsafe impl<T: ShmValue, const N: usize> ShmValue for [T; N] {}
+
+/// The layout of the shared memory object.
+#[repr(C)]
+struct Region<T> {
+    /// Set to `MAGIC` once the rest is initialized.
+    magic: AtomicU64,
+    size: AtomicU32,
+    align: AtomicU32,
+    /// The version of the latest value, whose slot is `version % 2`. This
+    /// is also the futex word that receivers wait on.
+    version: AtomicU32,
+    /// Set when the sender is dropped.
+    closed: AtomicU32,
+    /// The process ID of the sender.
+    sender: AtomicU32,
+    slots: [Slot<T>; 2],
+}
+
+/// A copy of the value guarded by a sequence counter, which is odd while
+/// the sender writes it.
+#[repr(C)]
+struct Slot<T> {
+    sequence: AtomicU32,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// The shared memory object mapped into this process.
+struct Mapping<T> {
+    region: NonNull<Region<T>>,
+}
+
+// SAFETY: The region is only accessed through atomics and volatile copies of
+// values of `T`, which move between threads as they would through a
+// channel.
+unsafe impl<T: Send> Send for Mapping<T> {}
+unsafe impl<T: Send> Sync for Mapping<T> {}
+
+impl<T> Region<T> {
+    /// Returns `true` if the sender has been dropped, or its process is
+    /// gone.
+    fn is_closed(&self) -> bool {
+        if self.closed.load(Ordering::Acquire) != 0 {
+            return true;
+        }
+        let pid = self.sender.load(Ordering::Relaxed) as libc::pid_t;
+        // SAFETY: Signal 0 only checks that the process exists.
+        let exists = unsafe { libc::kill(pid, 0) } == 0
+            || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+        !exists
+    }
+}
+
+impl<T> Mapping<T> {
+    fn region(&self) -> &Region<T> {
+        // SAFETY: The mapping stays valid until it is dropped.
+        unsafe { self.region.as_ref() }
+    }
+}
+
+impl<T> Drop for Mapping<T> {
+    fn drop(&mut self) {
+        // SAFETY: Nothing refers to the mapping any more.
+        unsafe { libc::munmap(self.region.as_ptr().cast(), mem::size_of::<Region<T>>()) };
+    }
+}
+
+/// The sender for a channel created by [`channel`].
+///
+/// Senders in other threads of the process can share it by reference.
+pub struct ShmSender<T> {
+    mapping: Arc<Mapping<T>>,
+    name: CString,
+    /// The device and inode of the shared memory object, to tell whether
+    /// the name still refers to it.
+    object: (libc::dev_t, libc::ino_t),
+    /// Serializes the sends of this process.
+    lock: Mutex<()>,
+}
+
+/// A receiver for a channel created by [`channel`], in this or another
+/// process.
+///
+/// The receiver can be cloned. Each clone will yield a new receiver that
+/// receives the same messages.
+pub struct ShmReceiver<T> {
+    mapping: Arc<Mapping<T>>,
+    /// The version last seen, or `None` before the first read.
+    last_seen_version: Option<u32>,
+}
+
+/// Turn the name of a channel into the name of a shared memory object.
+fn object_name(name: &str) -> io::Result<CString> {
+    let name = if name.starts_with('/') {
+        name.to_owned()
+    } else {
+        format!("/{}", name)
+    };
+    CString::new(name).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "name contains NUL"))
+}
+
+fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
+
+fn fstat(fd: libc::c_int) -> io::Result<libc::stat> {
+    // SAFETY: `stat` is plain data, and `fstat` fills it in.
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+    // SAFETY: The descriptor is open and `stat` is valid for writes.
+    check(unsafe { libc::fstat(fd, &mut stat) })?;
+    Ok(stat)
+}
+
+/// Map the shared memory object `fd` into this process.
+fn map<T>(fd: libc::c_int, writable: bool) -> io::Result<Mapping<T>> {
+    let protection = if writable {
+        libc::PROT_READ | libc::PROT_WRITE
+    } else {
+        libc::PROT_READ
+    };
+    // SAFETY: Mapping a file descriptor does not touch any memory of this
+    // process.
+    let region = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            mem::size_of::<Region<T>>(),
+            protection,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        )
+    };
+    if region == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Mapping {
+        region: NonNull::new(region.cast()).unwrap(),
+    })
+}
+
+/// Sleep while `futex` holds `expected`, for at most `timeout`. Spurious
+/// wakeups are possible.
+fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Duration) {
+    let timespec = libc::timespec {
+        tv_sec: timeout.as_secs() as libc::time_t,
+        tv_nsec: timeout.subsec_nanos() as _,
+    };
+    // SAFETY: The futex word and the timeout are valid for the duration of
+    // the call, and `FUTEX_WAIT` only reads them. The futex is shared with
+    // other processes, so it is not private.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex as *const AtomicU32,
+            libc::FUTEX_WAIT,
+            expected,
+            &timespec as *const libc::timespec,
+        );
+    }
+}
+
+/// Wake every receiver sleeping on `futex`, in any process.
+fn futex_wake(futex: &AtomicU32) {
+    // SAFETY: `FUTEX_WAKE` does not access any memory.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex as *const AtomicU32,
+            libc::FUTEX_WAKE,
+            libc::c_int::MAX,
+        );
+    }
+}
+
+/// Closes the file descriptor of a shared memory object when dropped.
+struct Fd(libc::c_int);
+
+impl Drop for Fd {
+    fn drop(&mut self) {
+        // SAFETY: The descriptor is owned, and not used after this.
+        unsafe { libc::close(self.0) };
+    }
+}
+
+/// Returns `true` if the shared memory object `name` holds a channel whose
+/// sender is gone, so that it can be replaced.
+fn is_left_behind(name: &CString) -> io::Result<bool> {
+    // SAFETY: The name is a valid C string.
+    let fd = Fd(check(unsafe {
+        libc::shm_open(name.as_ptr(), libc::O_RDONLY, 0)
+    })?);
+    // The header of the region does not depend on the type of the value,
+    // and the region of `()` is the smallest there is.
+    if (fstat(fd.0)?.st_size as u64) < mem::size_of::<Region<()>>() as u64 {
+        return Ok(false);
+    }
+    let mapping = map::<()>(fd.0, false)?;
+    let region = mapping.region();
+    Ok(region.magic.load(Ordering::Acquire) == MAGIC && region.is_closed())
+}
+
+/// Creates a new channel in the shared memory object `name`, which other
+/// processes can attach to with [`open_receiver`].
+///
+/// A channel left behind under the same name by a sender that was dropped
+/// or a producer that died is replaced, and receivers still attached to it
+/// keep seeing it closed. Fails with [`io::ErrorKind::AlreadyExists`] if the
+/// name is taken by a channel whose sender is still alive, or by an object
+/// that does not hold a channel.
+///
+/// The starting value in the channel is not initially considered seen by
+/// the receiver.
+pub fn channel<T: ShmValue>(name: &str, initial: T) -> io::Result<(ShmSender<T>, ShmReceiver<T>)> {
+    let name = object_name(name)?;
+    let create = || {
+        // SAFETY: The name is a valid C string.
+        check(unsafe {
+            libc::shm_open(
+                name.as_ptr(),
+                libc::O_RDWR | libc::O_CREAT | libc::O_EXCL,
+                0o600,
+            )
+        })
+    };
+    let fd = match create() {
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+            if !is_left_behind(&name)? {
+                return Err(error);
+            }
+            // SAFETY: As above.
+            unsafe { libc::shm_unlink(name.as_ptr()) };
+            // Another process may have replaced it first, and then the name
+            // is taken again.
+            create()?
+        }
+        result => result?,
+    };
+    let fd = Fd(fd);
+    let stat = fstat(fd.0)?;
+    let size = mem::size_of::<Region<T>>();
+    // SAFETY: Resizing the object does not touch any memory of this process.
+    check(unsafe { libc::ftruncate(fd.0, size as libc::off_t) })?;
+    let mapping = map::<T>(fd.0, true)?;
+
+    // The object starts out zeroed, so the first value goes to slot 0 with
+    // version 0.
+    let region = mapping.region();
+    // SAFETY: Nobody can read the slot before the magic number is set.
+    unsafe { ptr::write_volatile(region.slots[0].value.get(), MaybeUninit::new(initial)) };
+    region
+        .size
+        .store(mem::size_of::<T>() as u32, Ordering::Relaxed);
+    region
+        .align
+        .store(mem::align_of::<T>() as u32, Ordering::Relaxed);
+    // SAFETY: `getpid` has no preconditions.
+    let pid = unsafe { libc::getpid() };
+    region.sender.store(pid as u32, Ordering::Relaxed);
+    region.magic.store(MAGIC, Ordering::Release);
+
+    let mapping = Arc::new(mapping);
+    let receiver = ShmReceiver {
+        mapping: mapping.clone(),
+        last_seen_version: None,
+    };
+    let sender = ShmSender {
+        mapping,
+        name,
+        object: (stat.st_dev, stat.st_ino),
+        lock: Mutex::new(()),
+    };
+    Ok((sender, receiver))
+}
+
+/// Attaches to the channel in the shared memory object `name`, which was
+/// created with [`channel`] in this or another process.
+///
+/// Fails if there is no such object, or if it does not hold a channel of a
+/// type with the size and alignment of `T`. The value in the channel is not
+/// initially considered seen by the receiver.
+pub fn open_receiver<T: ShmValue>(name: &str) -> io::Result<ShmReceiver<T>> {
+    let name = object_name(name)?;
+    // SAFETY: The name is a valid C string.
+    let fd = Fd(check(unsafe {
+        libc::shm_open(name.as_ptr(), libc::O_RDONLY, 0)
+    })?);
+    let stat = fstat(fd.0)?;
+    let invalid = |message| Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    if (stat.st_size as u64) < mem::size_of::<Region<T>>() as u64 {
+        return invalid("shared memory object is too small for a channel of this type");
+    }
+    let mapping = map::<T>(fd.0, false)?;
+    let region = mapping.region();
+    if region.magic.load(Ordering::Acquire) != MAGIC {
+        return invalid("shared memory object does not hold a channel");
+    }
+    if region.size.load(Ordering::Relaxed) != mem::size_of::<T>() as u32
+        || region.align.load(Ordering::Relaxed) != mem::align_of::<T>() as u32
+    {
+        return invalid("shared memory object holds a channel of another type");
+    }
+    Ok(ShmReceiver {
+        mapping: Arc::new(mapping),
+        last_seen_version: None,
+    })
+}
+
+/// Removes the shared memory object `name`, such as one left behind by a
+/// producer that died.
+///
+/// Receivers that are attached to it keep working, and see the channel
+/// closed.
+pub fn remove(name: &str) -> io::Result<()> {
+    let name = object_name(name)?;
+    // SAFETY: The name is a valid C string.
+    check(unsafe { libc::shm_unlink(name.as_ptr()) })?;
+    Ok(())
+}
+
+impl<T: ShmValue> ShmSender<T> {
+    /// Send a new message and notify all receivers currently waiting for a
+    /// message, in every process.
+    pub fn send(&self, value: T) {
+        let _lock = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let region = self.mapping.region();
+        let version = region.version.load(Ordering::Relaxed).wrapping_add(1);
+        // Receivers only read the slot of the latest version, so they never
+        // read this one while it is written.
+        let slot = &region.slots[(version % 2) as usize];
+        let sequence = slot.sequence.load(Ordering::Relaxed);
+        slot.sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        // SAFETY: The lock makes this the only writer, and receivers discard
+        // anything they copy while the sequence is odd.
+        unsafe { ptr::write_volatile(slot.value.get(), MaybeUninit::new(value)) };
+        slot.sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+        region.version.store(version, Ordering::Release);
+        futex_wake(&region.version);
+    }
+
+    /// Create a new receiver for the channel in this process.
+    ///
+    /// Any messages sent before this method was called are considered seen
+    /// by the new receiver.
+    pub fn subscribe(&self) -> ShmReceiver<T> {
+        ShmReceiver {
+            mapping: self.mapping.clone(),
+            last_seen_version: Some(self.mapping.region().version.load(Ordering::Acquire)),
+        }
+    }
+}
+
+impl<T> Drop for ShmSender<T> {
+    fn drop(&mut self) {
+        let region = self.mapping.region();
+        region.closed.store(1, Ordering::Release);
+        futex_wake(&region.version);
+        // The name may have been given to another channel once this one was
+        // closed, which must not lose it.
+        // SAFETY: The name is a valid C string.
+        let fd = unsafe { libc::shm_open(self.name.as_ptr(), libc::O_RDONLY, 0) };
+        if fd < 0 {
+            return;
+        }
+        let fd = Fd(fd);
+        if let Ok(stat) = fstat(fd.0) {
+            if (stat.st_dev, stat.st_ino) == self.object {
+                // SAFETY: As above.
+                unsafe { libc::shm_unlink(self.name.as_ptr()) };
+            }
+        }
+    }
+}
+
+impl<T: ShmValue> ShmReceiver<T> {
+    /// Copy the latest value and return it with its version.
+    fn read(&self) -> (T, u32) {
+        let region = self.mapping.region();
+        loop {
+            let version = region.version.load(Ordering::Acquire);
+            let slot = &region.slots[(version % 2) as usize];
+            let before = slot.sequence.load(Ordering::Acquire);
+            if before & 1 == 0 {
+                // SAFETY: The pointer is valid for reads. The copy is kept as
+                // `MaybeUninit` until it is known to be intact.
+                let value = unsafe { ptr::read_volatile(slot.value.get()) };
+                fence(Ordering::Acquire);
+                // The slot may have been written again for a later version,
+                // which the sequence tells, or be about to be, once the
+                // version has moved on twice.
+                if slot.sequence.load(Ordering::Relaxed) == before
+                    && region.version.load(Ordering::Relaxed).wrapping_sub(version) < 2
+                {
+                    // SAFETY: No write overlapped the copy, so it holds the
+                    // value the sender wrote, and every bit pattern is valid
+                    // for `T`.
+                    return (unsafe { value.assume_init() }, version);
+                }
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Get a copy of the latest value, and mark it seen.
+    pub fn get(&mut self) -> T {
+        let (value, version) = self.read();
+        self.last_seen_version = Some(version);
+        value
+    }
+
+    /// Get a copy of the latest value if it has not been seen yet.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        if !self.has_changed() {
+            return None;
+        }
+        let (value, version) = self.read();
+        if Some(version) == self.last_seen_version {
+            return None;
+        }
+        self.last_seen_version = Some(version);
+        Some(value)
+    }
+
+    /// Returns `true` if there is a value that has not been seen yet.
+    pub fn has_changed(&self) -> bool {
+        Some(self.mapping.region().version.load(Ordering::Acquire)) != self.last_seen_version
+    }
+
+    /// Returns `true` if the sender has been dropped, or its process is
+    /// gone.
+    pub fn is_closed(&self) -> bool {
+        self.mapping.region().is_closed()
+    }
+
+    /// Wait until a new value becomes available and return a copy of it.
+    ///
+    /// Fails once the channel is closed and the latest value has been seen.
+    pub fn wait(&mut self) -> Result<T, RecvError> {
+        self.wait_until(None).map_err(|_| RecvError)
+    }
+
+    /// Like [`wait`], but gives up after `duration`.
+    ///
+    /// [`wait`]: ShmReceiver::wait
+    pub fn wait_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        self.wait_until(Some(Instant::now() + duration))
+    }
+
+    fn wait_until(&mut self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let mapping = self.mapping.clone();
+        let region = mapping.region();
+        loop {
+            let version = region.version.load(Ordering::Acquire);
+            if let Some(value) = self.get_if_new() {
+                return Ok(value);
+            }
+            if self.is_closed() {
+                return Err(RecvTimeoutError::Closed);
+            }
+            // Wake up now and then to notice a sender that died without
+            // waking anyone.
+            let mut timeout = LIVENESS_INTERVAL;
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+                timeout = timeout.min(deadline - now);
+            }
+            futex_wait(&region.version, version, timeout);
+        }
+    }
+}
+
+impl<T> Clone for ShmReceiver<T> {
+    fn clone(&self) -> ShmReceiver<T> {
+        ShmReceiver {
+            mapping: self.mapping.clone(),
+            last_seen_version: self.last_seen_version,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ShmSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShmSender")
+            .field("name", &self.name)
+            .field(
+                "version",
+                &self.mapping.region().version.load(Ordering::Relaxed),
+            )
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for ShmReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShmReceiver")
+            .field(
+                "version",
+                &self.mapping.region().version.load(Ordering::Relaxed),
+            )
+            .field("last_seen_version", &self.last_seen_version)
+            .finish()
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{backend::RawCondvar, Allocator, Global, WatchReceiver};
+use core::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_signals::signal::Signal;
+
+/// A [`Signal`] of the values of a channel, created by
+/// [`WatchReceiver::into_signal`].
+///
+/// As every signal does, it yields the latest value on its first poll, even
+/// if the receiver has seen it. After that, it yields the latest value
+/// whenever there is one it has not seen, skipping the values replaced in
+/// between, and ends once every sender is dropped.
+#[must_use = "signals do nothing unless polled"]
+pub struct WatchSignal<T, C: RawCondvar = crate::backend::DefaultCondvar, A: Allocator = Global> {
+    receiver: WatchReceiver<T, C, A>,
+    slot: Option<usize>,
+    first: bool,
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Turn this receiver into a [`Signal`], for use with `futures-signals`.
+    ///
+    /// See [`WatchSignal`].
+    pub fn into_signal(self) -> WatchSignal<T, C, A> {
+        WatchSignal {
+            receiver: self,
+            slot: None,
+            first: true,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> WatchSignal<T, C, A> {
+    /// Turn this back into the receiver.
+    pub fn into_inner(mut self) -> WatchReceiver<T, C, A> {
+        self.unregister();
+        let this = core::mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped afterwards, and the slot
+        // has been given back.
+        unsafe { core::ptr::read(&this.receiver) }
+    }
+
+    fn unregister(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            self.receiver.shared.state.lock().wakers.remove(slot);
+        }
+    }
+}
+
+// The signal does not rely on being pinned, so it may move between polls.
+impl<T, C: RawCondvar, A: Allocator> Unpin for WatchSignal<T, C, A> {}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> Signal for WatchSignal<T, C, A> {
+    type Item = T;
+
+    fn poll_change(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        if !core::mem::take(&mut this.first) {
+            let mut state = this.receiver.shared.state.lock();
+
+            if state.version == this.receiver.last_seen_version && state.is_open() {
+                state.wakers.register(&mut this.slot, cx.waker());
+                return Poll::Pending;
+            }
+
+            if let Some(slot) = this.slot.take() {
+                state.wakers.remove(slot);
+            }
+            if state.version == this.receiver.last_seen_version {
+                return Poll::Ready(None);
+            }
+        }
+        Poll::Ready(Some(this.receiver.get()))
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator> Drop for WatchSignal<T, C, A> {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+impl<T: fmt::Debug, C: RawCondvar, A: Allocator> fmt::Debug for WatchSignal<T, C, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WatchSignal").field(&self.receiver).finish()
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{backend::RawCondvar, channel_at_version, Allocator, WatchReceiver, WatchSender};
+use alloc::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+/// The value of a channel together with its version.
+///
+/// Restoring a snapshot keeps the version, so version numbers saved elsewhere
+/// remain meaningful.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot<T> {
+    /// The value of the channel.
+    pub value: T,
+    /// The version of the value.
+    pub version: u64,
+}
+
+/// Creates a new watch channel from a snapshot.
+///
+/// The value in the snapshot is not initially considered seen by the receiver.
+#[cfg_attr(feature = "debug-handles", track_caller)]
+pub fn channel_from_snapshot<T: Clone>(
+    snapshot: Snapshot<T>,
+) -> (WatchSender<T>, WatchReceiver<T>) {
+    channel_at_version(snapshot.value, snapshot.version)
+}
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Take a snapshot of the current value and its version.
+    pub fn snapshot(&self) -> Snapshot<T> {
+        let (value, version) = {
+            let lock = self.shared.value.read();
+            (lock.value.clone(), lock.version)
+        };
+        Snapshot {
+            value: T::clone(&value),
+            version,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Replace the value with the one in the snapshot if the snapshot is newer
+    /// than the current value, and notify all receivers currently waiting for
+    /// a message.
+    ///
+    /// The channel takes on the version of the snapshot. Versions wrap around,
+    /// so a snapshot counts as newer if it is less than `2^63` versions ahead.
+    ///
+    /// Returns `false` if the snapshot was not newer.
+    pub fn restore(&self, snapshot: Snapshot<T>) -> bool {
+        let mut lock = self.shared.value.write();
+        if (snapshot.version.wrapping_sub(lock.version) as i64) <= 0 {
+            self.shared.unlock_value(lock);
+            return false;
+        }
+        let timer = self.shared.lock_timer("restore");
+        // Publishing gives the value the next version, which is then the one
+        // of the snapshot.
+        lock.version = snapshot.version.wrapping_sub(1);
+        let old = self
+            .shared
+            .publish_replace(lock, Arc::new(snapshot.value), self.id);
+        self.shared.lock_released(timer);
+        drop(old);
+        true
+    }
+}
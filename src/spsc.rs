@@ -0,0 +1,396 @@
+//! A watch channel with exactly one sender and one receiver.
+//!
+//! Neither handle can be cloned, which lets the channel drop the parts of
+//! [`channel`](crate::channel) that only exist for many handles. The value is
+//! guarded by a single flag that the two handles spin on, rather than by a
+//! lock that queues any number of threads, and a waiting receiver parks its
+//! thread in a single slot rather than joining a list of waiters on a
+//! condition variable. The handles share the channel through an `Arc` that
+//! is only touched when they are dropped.
+//!
+//! The parking slot is owned by whichever handle last changed `waiter`:
+//!
+//! - While it is `EMPTY`, only the receiver touches the slot, to store its
+//!   thread before moving to `PARKED`.
+//! - While it is `PARKED`, the receiver may be asleep, and nobody touches the
+//!   slot.
+//! - The sender moves it from `PARKED` to `WAKING`, takes a handle to the
+//!   thread from the slot, and moves it back to `EMPTY` before unparking the
+//!   thread.
+//!
+//! The receiver stores `PARKED` before checking the version again, and the
+//! sender stores the version before checking `waiter`, all with `SeqCst`.
+//! So either the receiver sees the new version and does not sleep, or the
+//! sender sees that it parked and wakes it.
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{clock::Deadline, RecvError, RecvTimeoutError};
+use alloc::sync::Arc;
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+};
+use std::thread::{self, Thread};
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use std::time::Duration;
+
+/// The receiver is not parked, and owns the parking slot.
+const EMPTY: u8 = 0;
+/// The receiver is parked, or about to be.
+const PARKED: u8 = 1;
+/// The sender is taking the thread out of the parking slot.
+const WAKING: u8 = 2;
+
+/// The sender for a channel created by [`channel`].
+///
+/// There is only ever one sender, so it cannot be cloned.
+pub struct SpscSender<T> {
+    shared: Arc<SpscShared<T>>,
+}
+
+/// The receiver for a channel created by [`channel`].
+///
+/// There is only ever one receiver, so it cannot be cloned.
+pub struct SpscReceiver<T> {
+    shared: Arc<SpscShared<T>>,
+    last_seen_version: usize,
+}
+
+struct SpscShared<T> {
+    value: UnsafeCell<T>,
+    /// Set while one of the handles accesses `value`.
+    locked: AtomicBool,
+    /// Changed with every write to `value`, while holding it.
+    version: AtomicUsize,
+    /// Who owns `thread`, see the module documentation.
+    waiter: AtomicU8,
+    /// The thread of the parked receiver.
+    thread: UnsafeCell<Option<Thread>>,
+    /// Set once the sender is dropped.
+    sender_dropped: AtomicBool,
+    /// Set once the receiver is dropped.
+    receiver_dropped: AtomicBool,
+}
+
+// SAFETY: The value is only accessed while holding `locked`, and the slot is
+// only accessed by the handle that owns it through `waiter`. Values are
+// therefore moved between the threads but never shared by them.
+unsafe impl<T: Send> Sync for SpscShared<T> {}
+// SAFETY: See above.
+unsafe impl<T: Send> Send for SpscShared<T> {}
+
+/// Creates a new watch channel with one sender and one receiver.
+///
+/// This is lighter than [`channel`](crate::channel) when there is only one
+/// of each: the two handles never contend with other threads, so the value
+/// is guarded by a flag that they spin on, and waking the receiver unparks
+/// its thread directly. As the handles spin on each other, a closure passed
+/// to [`SpscSender::update`] holds up the receiver while it runs, and should
+/// be short.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+pub fn channel<T>(value: T) -> (SpscSender<T>, SpscReceiver<T>) {
+    let shared = Arc::new(SpscShared {
+        value: UnsafeCell::new(value),
+        locked: AtomicBool::new(false),
+        version: AtomicUsize::new(1),
+        waiter: AtomicU8::new(EMPTY),
+        thread: UnsafeCell::new(None),
+        sender_dropped: AtomicBool::new(false),
+        receiver_dropped: AtomicBool::new(false),
+    });
+    (
+        SpscSender {
+            shared: shared.clone(),
+        },
+        SpscReceiver {
+            shared,
+            last_seen_version: 0,
+        },
+    )
+}
+
+/// Spin for a little while, then give up the rest of the time slice.
+fn backoff(spins: &mut u32) {
+    if *spins < 64 {
+        core::hint::spin_loop();
+        *spins += 1;
+    } else {
+        thread::yield_now();
+    }
+}
+
+impl<T> SpscShared<T> {
+    /// Run `f` on the value, waiting for the other handle to let go of it.
+    fn with<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        // Lets go of the value even if `f` panics.
+        struct Unlock<'a>(&'a AtomicBool);
+        impl Drop for Unlock<'_> {
+            fn drop(&mut self) {
+                self.0.store(false, Ordering::Release);
+            }
+        }
+
+        let mut spins = 0;
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            backoff(&mut spins);
+        }
+        let _unlock = Unlock(&self.locked);
+        // SAFETY: `locked` is held, so the other handle does not access the
+        // value until it is released.
+        f(unsafe { &mut *self.value.get() })
+    }
+
+    /// Wake the receiver if it is parked.
+    fn wake(&self) {
+        if self
+            .waiter
+            .compare_exchange(PARKED, WAKING, Ordering::SeqCst, Ordering::Relaxed)
+            .is_ok()
+        {
+            // SAFETY: The sender owns the slot while `waiter` is `WAKING`,
+            // and the receiver stored its thread before storing `PARKED`.
+            let thread = unsafe { (*self.thread.get()).clone() };
+            self.waiter.store(EMPTY, Ordering::Release);
+            if let Some(thread) = thread {
+                thread.unpark();
+            }
+        }
+    }
+
+    /// Wait until the sender is done with the parking slot.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn wait_for_sender(&self) {
+        let mut spins = 0;
+        while self.waiter.load(Ordering::Acquire) != EMPTY {
+            backoff(&mut spins);
+        }
+    }
+
+    /// Take the parking slot back after parking, or after deciding not to.
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    fn unpark(&self) {
+        if self
+            .waiter
+            .compare_exchange(PARKED, EMPTY, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            // The sender is waking the thread, and may still be reading the
+            // slot. Its unpark may make a later park return early, which the
+            // loops that park allow for.
+            self.wait_for_sender();
+        }
+    }
+}
+
+impl<T> SpscSender<T> {
+    /// Send a new message and wake the receiver if it is waiting for one.
+    ///
+    /// The old value is dropped after the receiver has been woken.
+    pub fn send(&self, value: T) {
+        let shared = &*self.shared;
+        let old = shared.with(|current| {
+            shared.version.fetch_add(1, Ordering::SeqCst);
+            core::mem::replace(current, value)
+        });
+        shared.wake();
+        drop(old);
+    }
+
+    /// Update the message by a closure and wake the receiver if it is
+    /// waiting for one.
+    ///
+    /// The receiver cannot read the value while the closure runs. If `f`
+    /// panics, the changes it made so far are kept, and they count as a new
+    /// value.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        // Change the version and wake the receiver even if `f` panics.
+        struct Bump<'a>(&'a AtomicUsize);
+        impl Drop for Bump<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        struct Wake<'a, T>(&'a SpscShared<T>);
+        impl<T> Drop for Wake<'_, T> {
+            fn drop(&mut self) {
+                self.0.wake();
+            }
+        }
+
+        let shared = &*self.shared;
+        let _wake = Wake(shared);
+        shared.with(|value| {
+            let _bump = Bump(&shared.version);
+            f(value);
+        });
+    }
+
+    /// Returns `true` if the receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.receiver_dropped.load(Ordering::Acquire)
+    }
+}
+
+impl<T> SpscReceiver<T> {
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.version.load(Ordering::SeqCst) != self.last_seen_version
+    }
+
+    /// Returns `true` if the sender has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.sender_dropped.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T> SpscReceiver<T> {
+    /// Park until `condition` returns false, or until `deadline` expires.
+    ///
+    /// Returns `false` if the deadline expired first.
+    fn park_while<F>(&self, deadline: Option<Deadline>, mut condition: F) -> bool
+    where
+        F: FnMut(&SpscShared<T>) -> bool,
+    {
+        let shared = &*self.shared;
+        loop {
+            if !condition(shared) {
+                return true;
+            }
+            // SAFETY: `waiter` is `EMPTY`, as every earlier park took the
+            // slot back, so the sender does not touch the slot.
+            unsafe { *shared.thread.get() = Some(thread::current()) };
+            shared.waiter.store(PARKED, Ordering::SeqCst);
+            if !condition(shared) {
+                shared.unpark();
+                return true;
+            }
+            let timed_out = match &deadline {
+                Some(deadline) => {
+                    thread::park_timeout(deadline.sleep_time());
+                    deadline.expired()
+                }
+                None => {
+                    thread::park();
+                    false
+                }
+            };
+            shared.unpark();
+            if timed_out && condition(shared) {
+                return false;
+            }
+        }
+    }
+}
+
+impl<T: Clone> SpscReceiver<T> {
+    /// Get a clone of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        let seen = &mut self.last_seen_version;
+        let shared = &*self.shared;
+        shared.with(|value| {
+            // The sender changes the version while holding the value, so
+            // this is the version of the value cloned below.
+            *seen = shared.version.load(Ordering::SeqCst);
+            value.clone()
+        })
+    }
+
+    /// Get a clone of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        if !self.has_changed() {
+            return None;
+        }
+        Some(self.get())
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone> SpscReceiver<T> {
+    /// This method waits until a new value becomes available and return a
+    /// clone of it.
+    ///
+    /// If the sender has been dropped, this waits forever. Use [`recv`] to
+    /// detect that case.
+    ///
+    /// [`recv`]: SpscReceiver::recv
+    pub fn wait(&mut self) -> T {
+        let seen = self.last_seen_version;
+        self.park_while(None, |shared| shared.version.load(Ordering::SeqCst) == seen);
+
+        self.get()
+    }
+
+    /// Like [`wait`], but fails once the sender has been dropped.
+    ///
+    /// [`wait`]: SpscReceiver::wait
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        let seen = self.last_seen_version;
+        self.park_while(None, |shared| {
+            shared.version.load(Ordering::SeqCst) == seen
+                && !shared.sender_dropped.load(Ordering::SeqCst)
+        });
+        if !self.has_changed() {
+            return Err(RecvError);
+        }
+
+        Ok(self.get())
+    }
+
+    /// This method waits until a new value becomes available and return a
+    /// clone of it, timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
+        let seen = self.last_seen_version;
+        let ready = self.park_while(Some(Deadline::after(duration)), |shared| {
+            shared.version.load(Ordering::SeqCst) == seen
+        });
+        if !ready {
+            return None;
+        }
+
+        Some(self.get())
+    }
+
+    /// Like [`wait_timeout`], but fails once the sender has been dropped.
+    ///
+    /// [`wait_timeout`]: SpscReceiver::wait_timeout
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<T, RecvTimeoutError> {
+        let seen = self.last_seen_version;
+        let ready = self.park_while(Some(Deadline::after(duration)), |shared| {
+            shared.version.load(Ordering::SeqCst) == seen
+                && !shared.sender_dropped.load(Ordering::SeqCst)
+        });
+        if !ready {
+            return Err(RecvTimeoutError::Timeout);
+        }
+        if !self.has_changed() {
+            return Err(RecvTimeoutError::Closed);
+        }
+
+        Ok(self.get())
+    }
+}
+
+impl<T> Drop for SpscSender<T> {
+    fn drop(&mut self) {
+        self.shared.sender_dropped.store(true, Ordering::SeqCst);
+        self.shared.wake();
+    }
+}
+
+impl<T> Drop for SpscReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_dropped.store(true, Ordering::Release);
+    }
+}
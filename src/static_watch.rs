@@ -0,0 +1,221 @@
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::park_while;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{backend::RawCondvarTimeout, park_while_until, Deadline};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    SharedState, SharedValue,
+};
+use core::marker::PhantomData;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+use lock_api::{Mutex, RawMutex, RawRwLock, RwLock, RwLockWriteGuard};
+
+/// A watch channel that can be created in a `static`.
+///
+/// Creating one allocates nothing, so it works without a heap, and its
+/// receivers borrow it rather than sharing it through an `Arc`. Anything with
+/// access to the channel can send on it, and since it is never dropped while
+/// a receiver borrows it, it is never closed.
+///
+/// The value is kept in the channel itself, so unlike [`channel`], the
+/// receivers clone it while it is read-locked. Parking a thread in
+/// [`StaticReceiver::wait`] may still allocate room for it in the list of
+/// waiting threads.
+///
+/// [`channel`]: crate::channel
+pub struct StaticWatch<T, C: RawCondvar = DefaultCondvar> {
+    value: RwLock<C::RawRwLock, SharedValue<T>>,
+    state: Mutex<C::RawMutex, SharedState>,
+    _condvar: PhantomData<C>,
+}
+
+/// The receiver for a [`StaticWatch`].
+///
+/// The receiver can be cloned. Each clone will yield a new receiver that
+/// receives the same messages.
+pub struct StaticReceiver<T: 'static, C: RawCondvar + 'static = DefaultCondvar> {
+    watch: &'static StaticWatch<T, C>,
+    last_seen_version: u64,
+}
+
+impl<T, C: RawCondvar> StaticWatch<T, C> {
+    /// Creates a new static watch channel.
+    pub const fn new(value: T) -> Self {
+        StaticWatch {
+            value: RwLock::const_new(
+                <C::RawRwLock as RawRwLock>::INIT,
+                SharedValue::new(value, 1),
+            ),
+            state: Mutex::const_new(<C::RawMutex as RawMutex>::INIT, SharedState::new(1)),
+            _condvar: PhantomData,
+        }
+    }
+
+    /// Send a new message and notify all receivers currently waiting for a
+    /// message.
+    pub fn send(&self, value: T) {
+        let mut lock = self.value.write();
+        let old = lock.replace(value);
+        self.notify_changed(&lock);
+        drop(lock);
+
+        // Destroy the old value after releasing the lock.
+        drop(old);
+    }
+
+    /// Update the message by a closure and notify all receivers currently
+    /// waiting for a message.
+    ///
+    /// The value is changed in place, so this does not need `T: Clone`. If
+    /// the closure panics, the value keeps the changes it made before then,
+    /// and the receivers are told to read it again.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut guard = StaticUpdate {
+            watch: self,
+            lock: self.value.write(),
+        };
+        f(&mut guard.lock.value);
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    pub fn subscribe(&'static self) -> StaticReceiver<T, C> {
+        StaticReceiver {
+            watch: self,
+            last_seen_version: self.version(),
+        }
+    }
+
+    /// Wake the threads and tasks waiting for the value to change.
+    ///
+    /// This must be called with the new value still write-locked.
+    fn notify_changed(&self, value: &SharedValue<T>) {
+        let mut state = self.state.lock();
+        state.version = value.version;
+        state.waiters.wake(value.version);
+        state.wake_tasks();
+    }
+
+    fn version(&self) -> u64 {
+        self.value.read().version
+    }
+}
+
+/// Notifies the receivers once an update has changed the value, including
+/// when the closure that changed it panicked.
+struct StaticUpdate<'a, T, C: RawCondvar> {
+    watch: &'a StaticWatch<T, C>,
+    lock: RwLockWriteGuard<'a, C::RawRwLock, SharedValue<T>>,
+}
+
+impl<T, C: RawCondvar> Drop for StaticUpdate<'_, T, C> {
+    fn drop(&mut self) {
+        self.lock.changed();
+        self.watch.notify_changed(&self.lock);
+    }
+}
+
+impl<T: Clone, C: RawCondvar> StaticReceiver<T, C> {
+    /// Get a clone of the latest value sent on the channel.
+    pub fn get(&mut self) -> T {
+        self.watch
+            .value
+            .read()
+            .get(&mut self.last_seen_version)
+            .clone()
+    }
+
+    /// Get a clone of the latest value if that value has not previously been
+    /// seen by this receiver.
+    pub fn get_if_new(&mut self) -> Option<T> {
+        self.watch
+            .value
+            .read()
+            .get_if_new(&mut self.last_seen_version)
+            .cloned()
+    }
+
+    /// Wait for a new value by polling the channel, calling `idle` whenever
+    /// there is nothing new.
+    ///
+    /// See [`WatchReceiver::wait_with`](crate::WatchReceiver::wait_with).
+    pub fn wait_with<F>(&mut self, mut idle: F) -> T
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Some(value) = self.get_if_new() {
+                return value;
+            }
+            idle();
+        }
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T: Clone, C: RawCondvar> StaticReceiver<T, C> {
+    /// This method waits until a new value becomes available and return a clone
+    /// of it.
+    pub fn wait(&mut self) -> T {
+        let seen = self.last_seen_version;
+        let state = self.watch.state.lock();
+        drop(park_while::<C, _>(state, |state| state.version == seen));
+
+        self.get()
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T: Clone, C: RawCondvarTimeout> StaticReceiver<T, C> {
+    /// This method waits until a new value becomes available and return a clone
+    /// of it, timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<T> {
+        let seen = self.last_seen_version;
+        let deadline = Deadline::after(duration);
+        let state = self.watch.state.lock();
+        let (state, ready) =
+            park_while_until::<C, _>(state, deadline, |state| state.version == seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.get())
+    }
+}
+
+impl<T, C: RawCondvar> StaticReceiver<T, C> {
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.watch.version() != self.last_seen_version
+    }
+
+    /// Get the channel that this receiver belongs to.
+    pub fn channel(&self) -> &'static StaticWatch<T, C> {
+        self.watch
+    }
+}
+
+impl<T, C: RawCondvar> Clone for StaticReceiver<T, C> {
+    fn clone(&self) -> Self {
+        StaticReceiver {
+            watch: self.watch,
+            last_seen_version: self.last_seen_version,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{backend::RawCondvar, Allocator, Shared, WatchReceiver, WatchSender};
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+use core::time::Duration;
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+use std::time::Instant;
+
+/// Counters of what happened on a channel, as returned by
+/// [`WatchSender::stats`] and [`WatchReceiver::stats`].
+///
+/// The counters start at zero when the channel is created and count the
+/// whole channel, not just the handle they were read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelStats {
+    /// How many new values were published, by sends, updates or restores.
+    pub sends: u64,
+    /// How many times a waiting thread was notified.
+    pub notifications: u64,
+    /// How many times a waiting thread woke up, including spurious wakeups.
+    pub wakeups: u64,
+    /// How many calls to `get_if_new` and its variants found no new value.
+    pub empty_polls: u64,
+    /// How many threads are waiting right now.
+    pub waiters: u64,
+    #[cfg(all(feature = "std", not(target_family = "wasm")))]
+    wakeup_latency: LatencyHistogram,
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+impl ChannelStats {
+    /// How long it took waiting receivers to get a new value, from the
+    /// moment it was sent until their wait returned.
+    ///
+    /// Only available with the `std` feature.
+    pub fn wakeup_latency_histogram(&self) -> &LatencyHistogram {
+        &self.wakeup_latency
+    }
+}
+
+/// The number of buckets of a [`LatencyHistogram`].
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+const LATENCY_BUCKETS: usize = 24;
+
+/// A histogram of latencies with buckets that double in width, as returned
+/// by [`ChannelStats::wakeup_latency_histogram`].
+///
+/// The first bucket counts latencies under a microsecond, and each bucket
+/// after it counts those under twice the limit of the one before, so bucket
+/// `i` holds the latencies from `2^(i - 1)` up to `2^i` microseconds. The
+/// last bucket also counts everything longer, from about 4 seconds up.
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS],
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+impl LatencyHistogram {
+    /// The number of samples in each bucket.
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// The latency that the samples in bucket `index` are shorter than, or
+    /// `None` for the last bucket, which has no limit.
+    pub fn bucket_limit(index: usize) -> Option<Duration> {
+        if index + 1 < LATENCY_BUCKETS {
+            Some(Duration::from_micros(1 << index))
+        } else {
+            None
+        }
+    }
+
+    /// The number of samples.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros();
+        let bucket = (128 - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(LATENCY_BUCKETS - 1)] += 1;
+    }
+}
+
+/// When the latest value was sent, and how long waiting receivers took to
+/// get the values before it. This is kept by the wait list.
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+pub(crate) struct WakeupLatency {
+    sent_at: Option<Instant>,
+    histogram: LatencyHistogram,
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+impl WakeupLatency {
+    pub(crate) const fn new() -> WakeupLatency {
+        WakeupLatency {
+            sent_at: None,
+            histogram: LatencyHistogram {
+                buckets: [0; LATENCY_BUCKETS],
+            },
+        }
+    }
+
+    /// Note that a new value was sent just now.
+    pub(crate) fn sent(&mut self) {
+        self.sent_at = Some(Instant::now());
+    }
+
+    /// Note that a waiting thread just got the latest value.
+    pub(crate) fn received(&mut self) {
+        if let Some(sent_at) = self.sent_at {
+            self.histogram
+                .record(Instant::now().saturating_duration_since(sent_at));
+        }
+    }
+}
+
+/// The counters that are updated outside of the state lock. The others are
+/// kept by the wait list.
+#[derive(Default)]
+pub(crate) struct Stats {
+    sends: AtomicUsize,
+    empty_polls: AtomicUsize,
+}
+
+impl Stats {
+    pub(crate) fn sent(&self) {
+        self.sends.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn empty_poll(&self) {
+        self.empty_polls.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<T, C: RawCondvar> Shared<T, C> {
+    fn stats(&self) -> ChannelStats {
+        let state = self.state.lock();
+        ChannelStats {
+            sends: self.stats.sends.load(Ordering::Relaxed) as u64,
+            notifications: state.waiters.notifications,
+            wakeups: state.waiters.wakeups,
+            empty_polls: self.stats.empty_polls.load(Ordering::Relaxed) as u64,
+            waiters: state.waiters.len() as u64,
+            #[cfg(all(feature = "std", not(target_family = "wasm")))]
+            wakeup_latency: state.waiters.latency.histogram,
+        }
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Read the counters of the channel.
+    pub fn stats(&self) -> ChannelStats {
+        self.shared.stats()
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Read the counters of the channel.
+    pub fn stats(&self) -> ChannelStats {
+        self.shared.stats()
+    }
+}
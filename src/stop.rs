@@ -0,0 +1,69 @@
+//! The stop flag of the threads that the handles of this crate own.
+use crate::backend::{DefaultCondvar, RawCondvar};
+#[cfg(not(loom))]
+use crate::{backend::RawCondvarTimeout, clock::Deadline};
+use core::sync::atomic::{AtomicBool, Ordering};
+use lock_api::Mutex;
+
+/// Asks a thread to stop, waking that thread alone.
+///
+/// The thread either sleeps on the stop by itself with
+/// [`sleep_until`](Stop::sleep_until), or parks on the condvar of the stop
+/// in the wait list of a channel, with
+/// [`Shared::wait_while_unless_stopped`](crate::Shared::wait_while_unless_stopped).
+/// Stopping the thread only notifies that condvar, so the other waiters of
+/// the channel are not disturbed.
+pub(crate) struct Stop<C: RawCondvar = DefaultCondvar> {
+    stopped: AtomicBool,
+    /// Held to stop a thread that sleeps by itself.
+    lock: Mutex<C::RawMutex, ()>,
+    condvar: C,
+}
+
+impl<C: RawCondvar> Stop<C> {
+    pub(crate) fn new() -> Stop<C> {
+        Stop {
+            stopped: AtomicBool::new(false),
+            lock: Mutex::new(()),
+            condvar: C::new(),
+        }
+    }
+
+    /// Returns `true` once the thread has been asked to stop.
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Ask the thread to stop, and wake it.
+    ///
+    /// A thread that parks in the wait list of a channel checks the flag
+    /// with the channel locked, so the state of that channel must be locked
+    /// here too, or the thread could park right after it checked.
+    pub(crate) fn stop(&self) {
+        let _lock = self.lock.lock();
+        self.stopped.store(true, Ordering::Relaxed);
+        self.condvar.notify_all();
+    }
+
+    /// The condvar that the thread parks on.
+    pub(crate) fn condvar(&self) -> &C {
+        &self.condvar
+    }
+}
+
+#[cfg(not(loom))]
+impl<C: RawCondvarTimeout> Stop<C> {
+    /// Sleep until the deadline, or until the thread is asked to stop.
+    ///
+    /// Returns `false` if it was asked to stop.
+    pub(crate) fn sleep_until(&self, deadline: Deadline) -> bool {
+        let mut lock = self.lock.lock();
+        while !self.is_stopped() {
+            let timed_out = self.condvar.wait_timeout(&mut lock, deadline.sleep_time());
+            if timed_out && deadline.expired() {
+                return true;
+            }
+        }
+        false
+    }
+}
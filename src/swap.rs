@@ -0,0 +1,290 @@
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{backend::RawCondvarTimeout, park_while_until, Deadline, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    SharedState,
+};
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{park_while, RecvError};
+use arc_swap::ArcSwap;
+use lock_api::Mutex;
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use std::time::Duration;
+use std::{marker::PhantomData, sync::Arc};
+
+/// The sender for a channel created by [`arc_channel`].
+///
+/// The sender can be cloned to obtain multiple senders for the same channel.
+pub struct ArcWatchSender<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<ArcShared<T, C>>,
+}
+
+/// The receiver for a channel created by [`arc_channel`].
+///
+/// The receiver can be cloned. Each clone will yield a new receiver that
+/// receives the same messages.
+pub struct ArcWatchReceiver<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<ArcShared<T, C>>,
+    last_seen_version: u64,
+}
+
+struct ArcShared<T, C: RawCondvar> {
+    /// Only replaced while holding `state`, which serializes the senders.
+    value: ArcSwap<Entry<T>>,
+    state: Mutex<C::RawMutex, SharedState>,
+    _condvar: PhantomData<C>,
+}
+
+/// A value together with its version, so that both are loaded at once.
+struct Entry<T> {
+    value: Arc<T>,
+    version: u64,
+}
+
+/// Creates a new watch channel whose value can be read without locking.
+///
+/// The value is kept in an [`ArcSwap`], so reading it only bumps a reference
+/// count and never waits for senders or other readers. The receivers return
+/// the value as an `Arc`, which also means that `T` does not need to be
+/// `Clone`. Waiting for a new value still parks the thread.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+///
+/// [`ArcSwap`]: https://docs.rs/arc-swap
+pub fn arc_channel<T>(value: T) -> (ArcWatchSender<T>, ArcWatchReceiver<T>) {
+    new(value)
+}
+
+pub(crate) fn new<T, C: RawCondvar>(value: T) -> (ArcWatchSender<T, C>, ArcWatchReceiver<T, C>) {
+    let shared = Arc::new(ArcShared {
+        value: ArcSwap::from_pointee(Entry {
+            value: Arc::new(value),
+            version: 1,
+        }),
+        state: Mutex::new(SharedState::new(1)),
+        _condvar: PhantomData,
+    });
+    (
+        ArcWatchSender {
+            shared: shared.clone(),
+        },
+        ArcWatchReceiver {
+            shared,
+            last_seen_version: 0,
+        },
+    )
+}
+
+impl<T, C: RawCondvar> ArcShared<T, C> {
+    /// Replace the value and wake everyone waiting for it.
+    ///
+    /// `state` must be the locked `self.state`. Returns the old value so
+    /// that it can be dropped after unlocking.
+    fn store(&self, state: &mut SharedState, value: Arc<T>) -> Arc<Entry<T>> {
+        let version = state.version.wrapping_add(1);
+        let old = self.value.swap(Arc::new(Entry { value, version }));
+        state.version = version;
+        state.waiters.wake(version);
+        state.wake_tasks();
+        old
+    }
+
+    fn get(&self, seen: &mut u64) -> Arc<T> {
+        let entry = self.value.load();
+        *seen = entry.version;
+        entry.value.clone()
+    }
+
+    fn get_if_new(&self, seen: &mut u64) -> Option<Arc<T>> {
+        let entry = self.value.load();
+        if entry.version == *seen {
+            return None;
+        }
+        *seen = entry.version;
+        Some(entry.value.clone())
+    }
+}
+
+impl<T, C: RawCondvar> ArcWatchSender<T, C> {
+    /// Send a new message and notify all receivers currently waiting for a
+    /// message.
+    pub fn send(&self, value: T) {
+        self.send_arc(Arc::new(value));
+    }
+
+    /// Like [`send`], but for a value that is already in an `Arc`.
+    ///
+    /// [`send`]: ArcWatchSender::send
+    pub fn send_arc(&self, value: Arc<T>) {
+        let old = {
+            let mut state = self.shared.state.lock();
+            self.shared.store(&mut state, value)
+        };
+
+        // Destroy old value after releasing lock.
+        drop(old);
+    }
+
+    /// Update the message by a closure and notify all receivers currently waiting for a message.
+    ///
+    /// The closure is given a clone of the current value, which replaces it
+    /// once the closure returns.
+    pub fn update<F>(&self, f: F)
+    where
+        T: Clone,
+        F: FnOnce(&mut T),
+    {
+        let old = {
+            let mut state = self.shared.state.lock();
+            let mut value = T::clone(&self.shared.value.load().value);
+            f(&mut value);
+            self.shared.store(&mut state, Arc::new(value))
+        };
+        drop(old);
+    }
+
+    /// Create a new receiver for the channel.
+    ///
+    /// Any messages sent before this method was called are considered seen by
+    /// the new receiver.
+    pub fn subscribe(&self) -> ArcWatchReceiver<T, C> {
+        ArcWatchReceiver {
+            shared: self.shared.clone(),
+            last_seen_version: self.shared.value.load().version,
+        }
+    }
+}
+
+impl<T, C: RawCondvar> ArcWatchReceiver<T, C> {
+    /// Get the latest value sent on the channel.
+    pub fn get(&mut self) -> Arc<T> {
+        self.shared.get(&mut self.last_seen_version)
+    }
+
+    /// Get the latest value if that value has not previously been seen by
+    /// this receiver.
+    pub fn get_if_new(&mut self) -> Option<Arc<T>> {
+        self.shared.get_if_new(&mut self.last_seen_version)
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.value.load().version != self.last_seen_version
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T, C: RawCondvar> ArcWatchReceiver<T, C> {
+    /// This method waits until a new value becomes available and return it.
+    ///
+    /// If every sender has been dropped, this waits forever. Use [`recv`] to
+    /// detect that case.
+    ///
+    /// [`recv`]: ArcWatchReceiver::recv
+    pub fn wait(&mut self) -> Arc<T> {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(park_while::<C, _>(state, |state| state.version == seen));
+
+        self.get()
+    }
+
+    /// Like [`wait`], but fails once every sender has been dropped.
+    ///
+    /// [`wait`]: ArcWatchReceiver::wait
+    pub fn recv(&mut self) -> Result<Arc<T>, RecvError> {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        let state = park_while::<C, _>(state, |state| state.version == seen && state.senders > 0);
+        if state.version == seen {
+            return Err(RecvError);
+        }
+        drop(state);
+
+        Ok(self.get())
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T, C: RawCondvarTimeout> ArcWatchReceiver<T, C> {
+    /// This method waits until a new value becomes available and return it, timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<Arc<T>> {
+        let seen = self.last_seen_version;
+        let deadline = Deadline::after(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) =
+            park_while_until::<C, _>(state, deadline, |state| state.version == seen);
+        if !ready {
+            return None;
+        }
+        drop(state);
+
+        Some(self.get())
+    }
+
+    /// Like [`wait_timeout`], but fails once every sender has been dropped.
+    ///
+    /// [`wait_timeout`]: ArcWatchReceiver::wait_timeout
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<Arc<T>, RecvTimeoutError> {
+        let seen = self.last_seen_version;
+        let deadline = Deadline::after(duration);
+        let state = self.shared.state.lock();
+        let (state, ready) = park_while_until::<C, _>(state, deadline, |state| {
+            state.version == seen && state.senders > 0
+        });
+        if !ready {
+            return Err(RecvTimeoutError::Timeout);
+        }
+        if state.version == seen {
+            return Err(RecvTimeoutError::Closed);
+        }
+        drop(state);
+
+        Ok(self.get())
+    }
+}
+
+impl<T, C: RawCondvar> ArcWatchReceiver<T, C> {
+    /// Create a new sender for this channel.
+    ///
+    /// This reopens the channel if every other sender has been dropped.
+    pub fn new_sender(&self) -> ArcWatchSender<T, C> {
+        self.shared.state.lock().senders += 1;
+        ArcWatchSender {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns `true` if every sender for this channel has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().senders == 0
+    }
+}
+
+impl<T, C: RawCondvar> Clone for ArcWatchSender<T, C> {
+    fn clone(&self) -> ArcWatchSender<T, C> {
+        self.shared.state.lock().senders += 1;
+        ArcWatchSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Clone for ArcWatchReceiver<T, C> {
+    fn clone(&self) -> ArcWatchReceiver<T, C> {
+        ArcWatchReceiver {
+            shared: self.shared.clone(),
+            last_seen_version: self.last_seen_version,
+        }
+    }
+}
+
+impl<T, C: RawCondvar> Drop for ArcWatchSender<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            state.notify_all();
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::backend::RawCondvar;
+use core::cell::Cell;
+use critical_section::RestoreState;
+use lock_api::{GuardNoSend, MutexGuard};
+
+/// A raw mutex that is held by entering a critical section.
+///
+/// Nothing else can run on the current core while the lock is held, so the
+/// only way to observe a locked mutex is to lock it again from inside the
+/// critical section, which panics instead of aliasing the value.
+///
+/// It also serves as the rwlock, where readers exclude each other as well.
+pub struct RawMutex {
+    locked: Cell<bool>,
+    restore: Cell<RestoreState>,
+}
+
+// SAFETY: The cells are only accessed inside the critical section.
+unsafe impl Sync for RawMutex {}
+
+unsafe impl lock_api::RawMutex for RawMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawMutex {
+        locked: Cell::new(false),
+        restore: Cell::new(RestoreState::invalid()),
+    };
+
+    // The critical section must be released where it was acquired.
+    type GuardMarker = GuardNoSend;
+
+    fn lock(&self) {
+        if !self.try_lock() {
+            panic!("watch channel locked re-entrantly");
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        // SAFETY: The matching `release` happens in `unlock`. Guards are
+        // never leaked and are dropped in the reverse order of creation.
+        let restore = unsafe { critical_section::acquire() };
+        if self.locked.replace(true) {
+            // SAFETY: Releases the critical section acquired above.
+            unsafe { critical_section::release(restore) };
+            return false;
+        }
+        self.restore.set(restore);
+        true
+    }
+
+    unsafe fn unlock(&self) {
+        self.locked.set(false);
+        // SAFETY: Releases the critical section acquired in `try_lock`.
+        unsafe { critical_section::release(self.restore.get()) };
+    }
+}
+
+unsafe impl lock_api::RawRwLock for RawMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = <Self as lock_api::RawMutex>::INIT;
+
+    type GuardMarker = GuardNoSend;
+
+    fn lock_shared(&self) {
+        lock_api::RawMutex::lock(self);
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        lock_api::RawMutex::try_lock(self)
+    }
+
+    unsafe fn unlock_shared(&self) {
+        lock_api::RawMutex::unlock(self);
+    }
+
+    fn lock_exclusive(&self) {
+        lock_api::RawMutex::lock(self);
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        lock_api::RawMutex::try_lock(self)
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        lock_api::RawMutex::unlock(self);
+    }
+}
+
+/// Waiting polls the mutex, leaving the critical section in between so that
+/// interrupts get a chance to send a new value.
+pub struct Condvar {}
+
+unsafe impl RawCondvar for Condvar {
+    type RawMutex = RawMutex;
+    type RawRwLock = RawMutex;
+
+    fn new() -> Self {
+        Self {}
+    }
+
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>) {
+        MutexGuard::unlocked(guard, core::hint::spin_loop);
+    }
+
+    fn notify_all(&self) {}
+}
@@ -0,0 +1,252 @@
+use crate::backend::{RawCondvar, RawCondvarTimeout};
+use lock_api::{GuardSend, MutexGuard};
+use std::{
+    convert::TryInto,
+    ptr,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+/// Sleep while `futex` holds `expected`, for at most `timeout`.
+///
+/// Returns `true` if the timeout expired. Spurious wakeups are possible.
+fn wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -> bool {
+    let timespec = timeout.map(|timeout| libc::timespec {
+        tv_sec: timeout.as_secs().try_into().unwrap_or(libc::time_t::MAX),
+        tv_nsec: timeout.subsec_nanos() as _,
+    });
+    let timespec_ptr = match &timespec {
+        Some(timespec) => timespec as *const libc::timespec,
+        None => ptr::null(),
+    };
+    // SAFETY: The futex word and the timeout are valid for the duration of
+    // the call, and `FUTEX_WAIT` only reads them.
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex as *const AtomicU32,
+            libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+            expected,
+            timespec_ptr,
+        )
+    };
+    result < 0 && std::io::Error::last_os_error().raw_os_error() == Some(libc::ETIMEDOUT)
+}
+
+/// Wake up to `count` threads sleeping on `futex`.
+fn wake(futex: &AtomicU32, count: i32) {
+    // SAFETY: `FUTEX_WAKE` does not access any memory.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex as *const AtomicU32,
+            libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+            count,
+        );
+    }
+}
+
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+/// Locked, and another thread may be sleeping on the lock.
+const CONTENDED: u32 = 2;
+
+/// A raw mutex that sleeps on a futex.
+pub struct RawMutex {
+    state: AtomicU32,
+}
+
+unsafe impl lock_api::RawMutex for RawMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawMutex {
+        state: AtomicU32::new(UNLOCKED),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        if self.try_lock() {
+            return;
+        }
+        // The channel is only ever locked briefly, so spin for a little while
+        // before going to sleep.
+        for _ in 0..100 {
+            if self.state.load(Ordering::Relaxed) == UNLOCKED && self.try_lock() {
+                return;
+            }
+            std::hint::spin_loop();
+        }
+        // Marking the lock contended for as long as anyone may be sleeping
+        // makes the unlocking thread issue a wakeup.
+        while self.state.swap(CONTENDED, Ordering::Acquire) != UNLOCKED {
+            wait(&self.state, CONTENDED, None);
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.state
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    unsafe fn unlock(&self) {
+        if self.state.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+            wake(&self.state, 1);
+        }
+    }
+}
+
+/// The bits of a rwlock state that count the readers.
+const READERS: u32 = (1 << 29) - 1;
+const WRITE_LOCKED: u32 = 1 << 29;
+const READERS_WAITING: u32 = 1 << 30;
+const WRITERS_WAITING: u32 = 1 << 31;
+
+/// A raw rwlock that sleeps on a futex.
+///
+/// Readers hold off while a writer is waiting, so that rare writes are not
+/// starved by frequent reads. Whoever releases the lock with threads waiting
+/// clears the waiting flags and wakes all of them, and those that still
+/// cannot take the lock set their flag again.
+pub struct RawRwLock {
+    state: AtomicU32,
+}
+
+impl RawRwLock {
+    /// Wake every waiting thread if `state`, the state before releasing the
+    /// lock, had any.
+    fn wake_waiting(&self, state: u32) {
+        if state & (READERS_WAITING | WRITERS_WAITING) != 0 {
+            self.state
+                .fetch_and(!(READERS_WAITING | WRITERS_WAITING), Ordering::Relaxed);
+            wake(&self.state, i32::MAX);
+        }
+    }
+
+    /// Sleep until the state changes from `state`, after setting `flag` in
+    /// it. Returns right away if the state changed in the meantime.
+    fn wait_with(&self, state: u32, flag: u32) {
+        if state & flag == 0
+            && self
+                .state
+                .compare_exchange(state, state | flag, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        wait(&self.state, state | flag, None);
+    }
+}
+
+unsafe impl lock_api::RawRwLock for RawRwLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawRwLock {
+        state: AtomicU32::new(0),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock_shared(&self) {
+        while !self.try_lock_shared() {
+            let state = self.state.load(Ordering::Relaxed);
+            if state & (WRITE_LOCKED | WRITERS_WAITING) != 0 {
+                self.wait_with(state, READERS_WAITING);
+            }
+        }
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        let mut state = self.state.load(Ordering::Relaxed);
+        while state & (WRITE_LOCKED | WRITERS_WAITING) == 0 {
+            assert!(
+                state & READERS != READERS,
+                "too many readers of a watch channel"
+            );
+            match self.state.compare_exchange_weak(
+                state,
+                state + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => state = actual,
+            }
+        }
+        false
+    }
+
+    unsafe fn unlock_shared(&self) {
+        let state = self.state.fetch_sub(1, Ordering::Release);
+        if state & READERS == 1 {
+            self.wake_waiting(state);
+        }
+    }
+
+    fn lock_exclusive(&self) {
+        while !self.try_lock_exclusive() {
+            let state = self.state.load(Ordering::Relaxed);
+            if state & (WRITE_LOCKED | READERS) != 0 {
+                self.wait_with(state, WRITERS_WAITING);
+            }
+        }
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        let mut state = self.state.load(Ordering::Relaxed);
+        while state & (WRITE_LOCKED | READERS) == 0 {
+            match self.state.compare_exchange_weak(
+                state,
+                state | WRITE_LOCKED,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => state = actual,
+            }
+        }
+        false
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        let state = self.state.fetch_and(!WRITE_LOCKED, Ordering::Release);
+        self.wake_waiting(state);
+    }
+}
+
+/// Waiters sleep on a generation counter, which changes on every
+/// notification.
+pub struct Condvar {
+    generation: AtomicU32,
+}
+
+unsafe impl RawCondvar for Condvar {
+    type RawMutex = RawMutex;
+    type RawRwLock = RawRwLock;
+
+    fn new() -> Self {
+        Self {
+            generation: AtomicU32::new(0),
+        }
+    }
+
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>) {
+        // Read before the channel is unlocked, so a notification for a
+        // change made after that changes the generation and cannot be missed.
+        let seen = self.generation.load(Ordering::Relaxed);
+        MutexGuard::unlocked(guard, || {
+            wait(&self.generation, seen, None);
+        });
+    }
+
+    fn notify_all(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        wake(&self.generation, i32::MAX);
+    }
+}
+
+unsafe impl RawCondvarTimeout for Condvar {
+    fn wait_timeout<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>, timeout: Duration) -> bool {
+        let seen = self.generation.load(Ordering::Relaxed);
+        MutexGuard::unlocked(guard, || wait(&self.generation, seen, Some(timeout)))
+    }
+}
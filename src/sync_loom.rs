@@ -0,0 +1,165 @@
+//! A backend built from the primitives of `loom`, so that the channel can be
+//! model-checked.
+//!
+//! `lock_api` needs locks that can be created in a constant, which `loom`
+//! does not allow, so the `loom` primitives are created when a lock is first
+//! used. Timed waits are not supported.
+use crate::backend::RawCondvar;
+use lock_api::{GuardSend, MutexGuard};
+use loom::sync::{Condvar as LoomCondvar, Mutex};
+use std::sync::OnceLock;
+
+/// A raw mutex built from the mutex and condvar in `loom`.
+pub struct RawMutex {
+    inner: OnceLock<(Mutex<bool>, LoomCondvar)>,
+}
+
+impl RawMutex {
+    fn inner(&self) -> &(Mutex<bool>, LoomCondvar) {
+        self.inner
+            .get_or_init(|| (Mutex::new(false), LoomCondvar::new()))
+    }
+}
+
+unsafe impl lock_api::RawMutex for RawMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawMutex {
+        inner: OnceLock::new(),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        let (locked, unlocked) = self.inner();
+        let mut locked = locked.lock().unwrap();
+        while *locked {
+            locked = unlocked.wait(locked).unwrap();
+        }
+        *locked = true;
+    }
+
+    fn try_lock(&self) -> bool {
+        let mut locked = self.inner().0.lock().unwrap();
+        !std::mem::replace(&mut *locked, true)
+    }
+
+    unsafe fn unlock(&self) {
+        let (locked, unlocked) = self.inner();
+        *locked.lock().unwrap() = false;
+        unlocked.notify_one();
+    }
+}
+
+/// A raw rwlock built from the mutex and condvar in `loom`.
+pub struct RawRwLock {
+    inner: OnceLock<(Mutex<RwState>, LoomCondvar)>,
+}
+
+#[derive(Default)]
+struct RwState {
+    readers: usize,
+    writer: bool,
+}
+
+impl RawRwLock {
+    fn inner(&self) -> &(Mutex<RwState>, LoomCondvar) {
+        self.inner
+            .get_or_init(|| (Mutex::new(RwState::default()), LoomCondvar::new()))
+    }
+}
+
+unsafe impl lock_api::RawRwLock for RawRwLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawRwLock {
+        inner: OnceLock::new(),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock_shared(&self) {
+        let (state, changed) = self.inner();
+        let mut state = state.lock().unwrap();
+        while state.writer {
+            state = changed.wait(state).unwrap();
+        }
+        state.readers += 1;
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        let mut state = self.inner().0.lock().unwrap();
+        if state.writer {
+            return false;
+        }
+        state.readers += 1;
+        true
+    }
+
+    unsafe fn unlock_shared(&self) {
+        let (state, changed) = self.inner();
+        let mut state = state.lock().unwrap();
+        state.readers -= 1;
+        if state.readers == 0 {
+            changed.notify_all();
+        }
+    }
+
+    fn lock_exclusive(&self) {
+        let (state, changed) = self.inner();
+        let mut state = state.lock().unwrap();
+        while state.writer || state.readers > 0 {
+            state = changed.wait(state).unwrap();
+        }
+        state.writer = true;
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        let mut state = self.inner().0.lock().unwrap();
+        if state.writer || state.readers > 0 {
+            return false;
+        }
+        state.writer = true;
+        true
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        let (state, changed) = self.inner();
+        state.lock().unwrap().writer = false;
+        changed.notify_all();
+    }
+}
+
+/// Waiters sleep until the generation changes, which happens on every
+/// notification.
+pub struct Condvar {
+    generation: Mutex<u64>,
+    inner: LoomCondvar,
+}
+
+unsafe impl RawCondvar for Condvar {
+    type RawMutex = RawMutex;
+    type RawRwLock = RawRwLock;
+
+    fn new() -> Self {
+        Self {
+            generation: Mutex::new(0),
+            inner: LoomCondvar::new(),
+        }
+    }
+
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>) {
+        let generation = self.generation.lock().unwrap();
+        let seen = *generation;
+        MutexGuard::unlocked(guard, || {
+            let mut generation = generation;
+            while *generation == seen {
+                generation = self.inner.wait(generation).unwrap();
+            }
+        });
+    }
+
+    fn notify_all(&self) {
+        let mut generation = self.generation.lock().unwrap();
+        *generation = generation.wrapping_add(1);
+        self.inner.notify_all();
+    }
+}
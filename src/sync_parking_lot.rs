@@ -1,50 +1,41 @@
-use parking_lot::MutexGuard;
+use crate::backend::{RawCondvar, RawCondvarTimeout};
+use lock_api::{MutexGuard, RwLockWriteGuard};
+use parking_lot::{RawMutex, RawRwLock};
 use std::time::Duration;
 
-pub struct Mutex<T> {
-    inner: parking_lot::Mutex<T>,
+pub struct Condvar {
+    inner: parking_lot::Condvar,
 }
 
-impl<T> Mutex<T> {
-    pub fn new(value: T) -> Self {
+unsafe impl RawCondvar for Condvar {
+    type RawMutex = RawMutex;
+    type RawRwLock = RawRwLock;
+
+    fn new() -> Self {
         Self {
-            inner: parking_lot::Mutex::new(value),
+            inner: parking_lot::Condvar::new(),
         }
     }
 
-    pub fn lock(&self) -> MutexGuard<'_, T> {
-        self.inner.lock()
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>) {
+        self.inner.wait(guard);
     }
-}
 
-pub struct Condvar {
-    inner: parking_lot::Condvar,
-}
-impl Condvar {
-    pub fn new() -> Self {
-        Self {
-            inner: parking_lot::Condvar::new(),
-        }
+    fn notify_all(&self) {
+        self.inner.notify_all();
     }
 
-    pub fn wait<'a, T>(&self, mut guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
-        self.inner.wait(&mut guard);
-        guard
+    fn unlock_fair<T>(guard: MutexGuard<'_, RawMutex, T>) {
+        MutexGuard::unlock_fair(guard);
     }
 
-    pub fn wait_timeout<'a, T>(
-        &self,
-        mut guard: MutexGuard<'a, T>,
-        duration: Duration,
-    ) -> Option<MutexGuard<'a, T>> {
-        if self.inner.wait_for(&mut guard, duration).timed_out() {
-            None
-        } else {
-            Some(guard)
-        }
+    fn unlock_write_fair<T>(guard: RwLockWriteGuard<'_, RawRwLock, T>) {
+        RwLockWriteGuard::unlock_fair(guard);
     }
+}
 
-    pub fn notify_all(&self) {
-        self.inner.notify_all();
+unsafe impl RawCondvarTimeout for Condvar {
+    fn wait_timeout<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>, timeout: Duration) -> bool {
+        self.inner.wait_for(guard, timeout).timed_out()
     }
 }
@@ -0,0 +1,36 @@
+use crate::backend::RawCondvar;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use lock_api::MutexGuard;
+use spin::{mutex::SpinMutex, RwLock};
+
+/// Waiters spin until the generation changes, which happens on every
+/// notification.
+pub struct Condvar {
+    generation: AtomicUsize,
+}
+
+unsafe impl RawCondvar for Condvar {
+    type RawMutex = SpinMutex<()>;
+    type RawRwLock = RwLock<()>;
+
+    fn new() -> Self {
+        Self {
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, SpinMutex<()>, T>) {
+        // The generation is read while the lock is held, so any notification
+        // for a change made after the lock is released bumps it.
+        let generation = self.generation.load(Ordering::Acquire);
+        MutexGuard::unlocked(guard, || {
+            while self.generation.load(Ordering::Acquire) == generation {
+                core::hint::spin_loop();
+            }
+        });
+    }
+
+    fn notify_all(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+}
@@ -1,52 +1,192 @@
-use std::{sync::MutexGuard, time::Duration};
+use crate::backend::{RawCondvar, RawCondvarTimeout};
+use lock_api::{GuardSend, MutexGuard};
+use std::{
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
 
-pub struct Mutex<T> {
-    inner: std::sync::Mutex<T>,
+/// A raw mutex built from the mutex and condvar in std.
+///
+/// No user code runs while the inner mutex is held, so it is never
+/// poisoned in practice.
+pub struct RawMutex {
+    locked: Mutex<bool>,
+    unlocked: std::sync::Condvar,
 }
 
-impl<T> Mutex<T> {
-    pub fn new(value: T) -> Self {
-        Self {
-            inner: std::sync::Mutex::new(value),
+unsafe impl lock_api::RawMutex for RawMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawMutex {
+        locked: Mutex::new(false),
+        unlocked: std::sync::Condvar::new(),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        let locked = self.locked.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut locked = self
+            .unlocked
+            .wait_while(locked, |locked| *locked)
+            .unwrap_or_else(PoisonError::into_inner);
+        *locked = true;
+    }
+
+    fn try_lock(&self) -> bool {
+        let mut locked = self.locked.lock().unwrap_or_else(PoisonError::into_inner);
+        !std::mem::replace(&mut *locked, true)
+    }
+
+    unsafe fn unlock(&self) {
+        *self.locked.lock().unwrap_or_else(PoisonError::into_inner) = false;
+        self.unlocked.notify_one();
+    }
+}
+
+/// A raw rwlock built from the mutex and condvar in std.
+///
+/// Readers hold the inner mutex only briefly, and they hold off while a
+/// writer is waiting so that rare writes are not starved by frequent reads.
+pub struct RawRwLock {
+    state: Mutex<RwState>,
+    changed: std::sync::Condvar,
+}
+
+struct RwState {
+    readers: usize,
+    writer: bool,
+    waiting_writers: usize,
+}
+
+impl RawRwLock {
+    fn state(&self) -> std::sync::MutexGuard<'_, RwState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+unsafe impl lock_api::RawRwLock for RawRwLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawRwLock {
+        state: Mutex::new(RwState {
+            readers: 0,
+            writer: false,
+            waiting_writers: 0,
+        }),
+        changed: std::sync::Condvar::new(),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock_shared(&self) {
+        let state = self.state();
+        let mut state = self
+            .changed
+            .wait_while(state, |state| state.writer || state.waiting_writers > 0)
+            .unwrap_or_else(PoisonError::into_inner);
+        state.readers += 1;
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        let mut state = self.state();
+        if state.writer || state.waiting_writers > 0 {
+            return false;
         }
+        state.readers += 1;
+        true
     }
 
-    pub fn lock(&self) -> MutexGuard<'_, T> {
-        match self.inner.lock() {
-            Ok(guard) => guard,
-            Err(poison) => poison.into_inner(),
+    unsafe fn unlock_shared(&self) {
+        let mut state = self.state();
+        state.readers -= 1;
+        if state.readers == 0 {
+            drop(state);
+            self.changed.notify_all();
         }
     }
+
+    fn lock_exclusive(&self) {
+        let mut state = self.state();
+        state.waiting_writers += 1;
+        let mut state = self
+            .changed
+            .wait_while(state, |state| state.writer || state.readers > 0)
+            .unwrap_or_else(PoisonError::into_inner);
+        state.waiting_writers -= 1;
+        state.writer = true;
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        let mut state = self.state();
+        if state.writer || state.readers > 0 {
+            return false;
+        }
+        state.writer = true;
+        true
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        self.state().writer = false;
+        self.changed.notify_all();
+    }
 }
 
+/// Waiters sleep until the generation changes, which happens on every
+/// notification.
 pub struct Condvar {
+    generation: Mutex<u64>,
     inner: std::sync::Condvar,
 }
-impl Condvar {
-    pub fn new() -> Self {
+
+unsafe impl RawCondvar for Condvar {
+    type RawMutex = RawMutex;
+    type RawRwLock = RawRwLock;
+
+    fn new() -> Self {
         Self {
+            generation: Mutex::new(0),
             inner: std::sync::Condvar::new(),
         }
     }
 
-    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
-        match self.inner.wait(guard) {
-            Ok(guard) => guard,
-            Err(poison) => poison.into_inner(),
-        }
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>) {
+        // The generation is locked before the channel is unlocked, so a
+        // notification for a change made after that cannot be missed.
+        let generation = self
+            .generation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let seen = *generation;
+        MutexGuard::unlocked(guard, || {
+            let _generation = self
+                .inner
+                .wait_while(generation, |generation| *generation == seen)
+                .unwrap_or_else(PoisonError::into_inner);
+        });
     }
 
-    pub fn wait_timeout<'a, T>(
-        &self,
-        guard: MutexGuard<'a, T>,
-        duration: Duration,
-    ) -> Option<MutexGuard<'a, T>> {
-        match self.inner.wait_timeout(guard, duration) {
-            Ok((guard, _)) => Some(guard),
-            Err(_) => None,
-        }
-    }
-    pub fn notify_all(&self) {
+    fn notify_all(&self) {
+        let mut generation = self
+            .generation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *generation = generation.wrapping_add(1);
         self.inner.notify_all();
     }
 }
+
+unsafe impl RawCondvarTimeout for Condvar {
+    fn wait_timeout<T>(&self, guard: &mut MutexGuard<'_, RawMutex, T>, timeout: Duration) -> bool {
+        let generation = self
+            .generation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let seen = *generation;
+        MutexGuard::unlocked(guard, || {
+            let (_generation, result) = self
+                .inner
+                .wait_timeout_while(generation, timeout, |generation| *generation == seen)
+                .unwrap_or_else(PoisonError::into_inner);
+            result.timed_out()
+        })
+    }
+}
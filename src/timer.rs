@@ -0,0 +1,311 @@
+use crate::{backend::RawCondvar, Allocator, WatchReader, WatchSender};
+#[cfg(feature = "embedded-async")]
+use alloc::sync::Arc;
+use alloc::{boxed::Box, collections::BTreeMap};
+#[cfg(feature = "embedded-async")]
+use core::task::{Context, Waker};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Something for the timer thread to do. It returns when to run it again,
+/// if it should be.
+type Job = Box<dyn FnMut() -> Option<Instant> + Send>;
+
+/// When a job is due, followed by the order in which the jobs were
+/// scheduled, so that jobs due at the same instant run in that order.
+type Key = (Instant, u64);
+
+/// The scheduled sends and keepalives of every channel.
+///
+/// They are run by a single timer thread, which is spawned when a job is
+/// scheduled while there is none, and exits once no job is left.
+struct Timer {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+struct Queue {
+    jobs: BTreeMap<Key, Job>,
+    next_id: u64,
+    running: bool,
+    /// The job that the timer thread is running, which is not in `jobs`.
+    current: Option<u64>,
+    /// Set when the current job is cancelled, so that it is not run again.
+    cancelled: bool,
+}
+
+static TIMER: Timer = Timer {
+    queue: Mutex::new(Queue {
+        jobs: BTreeMap::new(),
+        next_id: 0,
+        running: false,
+        current: None,
+        cancelled: false,
+    }),
+    changed: Condvar::new(),
+};
+
+impl Timer {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `job` at `at`, and return its id.
+    fn schedule(&'static self, at: Instant, job: Job) -> u64 {
+        let mut queue = self.lock();
+        let id = queue.next_id;
+        queue.next_id += 1;
+        self.insert(&mut queue, (at, id), job);
+        if !queue.running {
+            queue.running = true;
+            thread::spawn(move || self.run());
+        }
+        id
+    }
+
+    fn insert(&self, queue: &mut Queue, key: Key, job: Job) {
+        if queue.jobs.keys().next().is_none_or(|first| key < *first) {
+            // The thread may be sleeping until a later job.
+            self.changed.notify_one();
+        }
+        queue.jobs.insert(key, job);
+    }
+
+    /// Remove the job with the given id, and return it if it had not started
+    /// running.
+    fn cancel(&self, id: u64) -> Option<Job> {
+        let mut queue = self.lock();
+        if queue.current == Some(id) {
+            queue.cancelled = true;
+        }
+        let key = *queue.jobs.keys().find(|key| key.1 == id)?;
+        if queue.jobs.keys().next() == Some(&key) {
+            // The thread may be sleeping until this job, and should exit if
+            // it was the last one.
+            self.changed.notify_one();
+        }
+        queue.jobs.remove(&key)
+    }
+
+    fn is_pending(&self, id: u64) -> bool {
+        self.lock().jobs.keys().any(|key| key.1 == id)
+    }
+
+    fn run(&self) {
+        let mut queue = self.lock();
+        while let Some(&(at, id)) = queue.jobs.keys().next() {
+            let now = Instant::now();
+            if at > now {
+                queue = self
+                    .changed
+                    .wait_timeout(queue, at - now)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+                continue;
+            }
+
+            let (_, mut job) = queue.jobs.pop_first().unwrap();
+            queue.current = Some(id);
+            drop(queue);
+            // A panic, such as from dropping the value that a send replaced,
+            // must not stop the jobs of other channels.
+            let next = panic::catch_unwind(AssertUnwindSafe(&mut job)).unwrap_or(None);
+
+            queue = self.lock();
+            queue.current = None;
+            let cancelled = core::mem::take(&mut queue.cancelled);
+            match next {
+                Some(at) if !cancelled => self.insert(&mut queue, (at, id), job),
+                _ => {
+                    // Destroy the job after releasing the lock.
+                    drop(queue);
+                    drop(job);
+                    queue = self.lock();
+                }
+            }
+        }
+        queue.running = false;
+    }
+}
+
+/// Wakes a task once an instant has passed, for the timeouts of futures.
+///
+/// Dropping it cancels the wakeup.
+#[cfg(feature = "embedded-async")]
+pub(crate) struct Sleep {
+    id: u64,
+    state: Arc<Mutex<SleepState>>,
+}
+
+#[cfg(feature = "embedded-async")]
+struct SleepState {
+    fired: bool,
+    waker: Option<Waker>,
+}
+
+#[cfg(feature = "embedded-async")]
+impl Sleep {
+    pub(crate) fn until(at: Instant) -> Sleep {
+        let state = Arc::new(Mutex::new(SleepState {
+            fired: false,
+            waker: None,
+        }));
+        let id = {
+            let state = state.clone();
+            TIMER.schedule(
+                at,
+                Box::new(move || {
+                    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                    state.fired = true;
+                    let waker = state.waker.take();
+                    drop(state);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                    None
+                }),
+            )
+        };
+        Sleep { id, state }
+    }
+
+    /// Returns `true` once the instant has passed, and otherwise wakes the
+    /// task of `cx` when it does.
+    pub(crate) fn poll_fired(&self, cx: &Context<'_>) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.fired {
+            return true;
+        }
+        match &state.waker {
+            Some(old) if old.will_wake(cx.waker()) => {}
+            _ => state.waker = Some(cx.waker().clone()),
+        }
+        false
+    }
+}
+
+#[cfg(feature = "embedded-async")]
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        drop(TIMER.cancel(self.id));
+    }
+}
+
+/// A send scheduled by [`WatchSender::send_after`].
+///
+/// Dropping the handle does not cancel the send.
+#[derive(Debug)]
+pub struct ScheduledSend {
+    id: u64,
+}
+
+impl ScheduledSend {
+    /// Prevent the send from happening.
+    ///
+    /// Returns `false` if it was too late, because the value has already
+    /// been sent or is being sent.
+    pub fn cancel(self) -> bool {
+        TIMER.cancel(self.id).is_some()
+    }
+
+    /// Returns `true` if the value has not been sent yet.
+    pub fn is_pending(&self) -> bool {
+        TIMER.is_pending(self.id)
+    }
+}
+
+/// The heartbeat started by [`WatchSender::keepalive`].
+///
+/// Dropping the handle stops the heartbeat.
+#[derive(Debug)]
+pub struct KeepaliveHandle {
+    id: u64,
+}
+
+impl KeepaliveHandle {
+    /// Stop the heartbeat.
+    ///
+    /// A touch that the timer thread is already making still completes.
+    pub fn stop(self) {}
+}
+
+impl Drop for KeepaliveHandle {
+    fn drop(&mut self) {
+        drop(TIMER.cancel(self.id));
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchSender<T, C, A> {
+    /// Send `value` once `delay` has passed.
+    ///
+    /// The send is made by a timer thread that is shared by every channel,
+    /// so a send that is due while another one is running waits for it.
+    /// Sends due at the same instant are made in the order they were
+    /// scheduled. The delay is measured on the system clock, even if the
+    /// channel was created with a different one.
+    ///
+    /// A value sent in the meantime does not cancel the scheduled send,
+    /// which then replaces it. The scheduled send holds a sender, so the
+    /// channel is not closed before it has been made or cancelled.
+    pub fn send_after(&self, value: T, delay: Duration) -> ScheduledSend
+    where
+        T: Send + 'static,
+        WatchSender<T, C, A>: Send + 'static,
+    {
+        let sender = self.clone();
+        let mut value = Some(value);
+        let id = TIMER.schedule(
+            Instant::now() + delay,
+            Box::new(move || {
+                if let Some(value) = value.take() {
+                    sender.send(value);
+                }
+                None
+            }),
+        );
+        ScheduledSend { id }
+    }
+
+    /// Notify the receivers again whenever `interval` passes without a new
+    /// value.
+    ///
+    /// The heartbeat gives the current value a new version without replacing
+    /// it, so waiting receivers wake up and read it again. Every new value
+    /// restarts the interval, and nothing is sent while the channel has no
+    /// receivers. The heartbeat is made by the timer thread of
+    /// [`send_after`](WatchSender::send_after), and it stops once the handle
+    /// is dropped or the channel is closed.
+    pub fn keepalive(&self, interval: Duration) -> KeepaliveHandle
+    where
+        WatchReader<T, C, A>: Send + 'static,
+    {
+        let reader = self.reader();
+        let now = Instant::now();
+        let changed_at = *self.shared.state.lock().changed_at.get_or_insert(now);
+        let id = TIMER.schedule(
+            changed_at + interval,
+            Box::new(move || {
+                let shared = &reader.shared;
+                let state = shared.state.lock();
+                if !state.is_open() {
+                    return None;
+                }
+                let now = Instant::now();
+                let due = state.changed_at.unwrap_or(now) + interval;
+                if due > now {
+                    return Some(due);
+                }
+                if state.receivers > 0 {
+                    drop(state);
+                    shared.touch();
+                }
+                Some(now + interval)
+            }),
+        );
+        KeepaliveHandle { id }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{backend::RawCondvar, Allocator, WatchReceiver};
+use tracing::Span;
+
+impl<T: Clone, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Get a clone of the latest value together with the span it was written
+    /// in.
+    ///
+    /// The span is disabled unless the channel was created with
+    /// [`ChannelBuilder::trace_spans`](crate::ChannelBuilder::trace_spans).
+    /// Enter it while processing the value, or link the current span to it
+    /// with [`Span::follows_from`].
+    pub fn get_traced(&mut self) -> (T, Span) {
+        let (value, span) = self.track(|shared, seen| {
+            let lock = shared.value.read();
+            (lock.get(seen).clone(), lock.span.clone())
+        });
+        // The value is cloned after releasing the lock, like in `get`.
+        (T::clone(&value), span)
+    }
+
+    /// Like [`get_traced`](WatchReceiver::get_traced), but only returns a
+    /// value that has not previously been seen by this receiver.
+    pub fn get_if_new_traced(&mut self) -> Option<(T, Span)> {
+        let (value, span) = self.track(|shared, seen| {
+            let lock = shared.value.read();
+            let value = lock.get_if_new(seen)?.clone();
+            Some((value, lock.span.clone()))
+        })?;
+        Some((T::clone(&value), span))
+    }
+
+    /// Like [`wait`](WatchReceiver::wait), but also returns the span that
+    /// the value was written in.
+    ///
+    /// See [`get_traced`](WatchReceiver::get_traced).
+    #[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+    pub fn wait_traced(&mut self) -> (T, Span) {
+        let seen = self.last_seen_version;
+        let state = self.shared.state.lock();
+        drop(self.shared.wait_while(state, |state| state.version == seen));
+
+        self.get_traced()
+    }
+}
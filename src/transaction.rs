@@ -0,0 +1,180 @@
+use crate::{backend::RawCondvar, Allocator, ChannelId, WatchReceiver, WatchSender};
+use alloc::sync::Arc;
+
+/// Update the values of several channels at once.
+///
+/// `senders` is a tuple of two to four senders of different channels, and
+/// `f` is given the value of each of them in the same order. The values are
+/// locked for as long as `f` runs, and they all get new versions before any
+/// of them is unlocked, so [`get_all`] reads either every value from before
+/// the transaction or every value from after it. Reading the channels one at
+/// a time, such as with [`WatchReceiver::get`], may still see a mix.
+///
+/// The channels are always locked in the order of their [`ChannelId`], so
+/// transactions that name the same channels in different orders do not
+/// deadlock each other. If `f` panics, every channel is updated as if
+/// [`WatchSender::update`] had panicked.
+///
+/// # Panics
+///
+/// Panics if two of the senders belong to the same channel.
+pub fn transaction<S, F>(senders: S, f: F)
+where
+    S: TransactionSenders<F>,
+{
+    senders.run(f);
+}
+
+/// Get a clone of the latest value of several channels at once.
+///
+/// `receivers` is a tuple of two to four receivers of different channels.
+/// The values are read while every one of them is locked, so they are never
+/// torn by a [`transaction`]. Every value is marked seen by its receiver.
+///
+/// This is not `WatchSender::snapshot`, which saves the value of a single
+/// channel to be restored later.
+///
+/// # Panics
+///
+/// Panics if two of the receivers belong to the same channel.
+pub fn get_all<R>(receivers: R) -> R::Values
+where
+    R: TransactionReceivers,
+{
+    receivers.read_all(false).unwrap()
+}
+
+/// Like [`get_all`], but returns `None` without reading anything if none of
+/// the receivers has a value that it has not seen.
+///
+/// The versions are checked while every channel is locked, so if any of the
+/// values is new, every value is cloned and marked seen from the same
+/// moment, as with [`get_all`].
+///
+/// # Panics
+///
+/// Panics if two of the receivers belong to the same channel.
+pub fn get_all_if_any_new<R>(receivers: R) -> Option<R::Values>
+where
+    R: TransactionReceivers,
+{
+    receivers.read_all(true)
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A tuple of senders that [`transaction`] can update together.
+///
+/// This is implemented for tuples of references to two to four senders, and
+/// cannot be implemented outside of this crate.
+pub trait TransactionSenders<F>: sealed::Sealed {
+    #[doc(hidden)]
+    fn run(self, f: F);
+}
+
+/// A tuple of receivers that [`get_all`] and [`get_all_if_any_new`] can
+/// read together.
+///
+/// This is implemented for tuples of mutable references to two to four
+/// receivers, and cannot be implemented outside of this crate.
+pub trait TransactionReceivers: sealed::Sealed {
+    /// The values of the channels.
+    type Values;
+
+    /// Read every value, or nothing if `only_if_new` is set and none of
+    /// them is new.
+    #[doc(hidden)]
+    fn read_all(self, only_if_new: bool) -> Option<Self::Values>;
+}
+
+/// The order in which to lock the channels with the given ids.
+fn lock_order<const N: usize>(ids: [ChannelId; N]) -> [usize; N] {
+    let mut order = [0; N];
+    for (i, slot) in order.iter_mut().enumerate() {
+        *slot = i;
+    }
+    order.sort_unstable_by_key(|&i| ids[i].0);
+    for pair in order.windows(2) {
+        // Locking a channel twice would deadlock.
+        assert!(
+            ids[pair[0]] != ids[pair[1]],
+            "the same channel was given twice"
+        );
+    }
+    order
+}
+
+macro_rules! tuples {
+    ($(($($T:ident $C:ident $A:ident $handle:ident $guard:ident $i:tt),+))+) => {$(
+        impl<$($T, $C: RawCondvar, $A: Allocator,)+> sealed::Sealed
+            for ($(&WatchSender<$T, $C, $A>,)+)
+        {
+        }
+
+        impl<$($T: Clone, $C: RawCondvar, $A: Allocator + Clone,)+ F> TransactionSenders<F>
+            for ($(&WatchSender<$T, $C, $A>,)+)
+        where
+            F: FnOnce($(&mut $T),+),
+        {
+            fn run(self, f: F) {
+                let ($($handle,)+) = self;
+                $(let mut $guard = None;)+
+                for i in lock_order([$($handle.channel_id()),+]) {
+                    match i {
+                        $($i => $guard = Some($handle.shared.begin_update($handle.id, "transaction")),)+
+                        _ => unreachable!(),
+                    }
+                }
+                $(let mut $guard = $guard.unwrap();)+
+                let _scopes = [$($handle.shared.value.enter("transaction")),+];
+                // This clones a value if a receiver still holds on to it,
+                // as in `update`.
+                f($(Arc::make_mut(&mut $guard.lock.as_mut().unwrap().value)),+);
+                $($guard.finished = true;)+
+                // Dropping the guards gives the values new versions and
+                // notifies the receivers of each channel.
+            }
+        }
+
+        impl<$($T, $C: RawCondvar, $A: Allocator,)+> sealed::Sealed
+            for ($(&mut WatchReceiver<$T, $C, $A>,)+)
+        {
+        }
+
+        impl<$($T: Clone, $C: RawCondvar, $A: Allocator + Clone,)+> TransactionReceivers
+            for ($(&mut WatchReceiver<$T, $C, $A>,)+)
+        {
+            type Values = ($($T,)+);
+
+            fn read_all(self, only_if_new: bool) -> Option<Self::Values> {
+                let ($($handle,)+) = self;
+                $(let mut $guard = None;)+
+                for i in lock_order([$($handle.channel_id()),+]) {
+                    match i {
+                        $($i => $guard = Some($handle.shared.value.read()),)+
+                        _ => unreachable!(),
+                    }
+                }
+                $(let $guard = $guard.unwrap();)+
+                if only_if_new $(&& $guard.version == $handle.last_seen_version)+ {
+                    return None;
+                }
+                // Each channel is unlocked once its value has been taken,
+                // which is only after every channel was locked.
+                $(let $guard = $guard.get(&mut $handle.last_seen_version).clone();)+
+                $($handle.cursor.report($handle.last_seen_version);)+
+                // The values are cloned after releasing the locks, like in
+                // `WatchReceiver::get`.
+                Some(($($T::clone(&$guard),)+))
+            }
+        }
+    )+};
+}
+
+tuples! {
+    (T1 C1 A1 h1 g1 0, T2 C2 A2 h2 g2 1)
+    (T1 C1 A1 h1 g1 0, T2 C2 A2 h2 g2 1, T3 C3 A3 h3 g3 2)
+    (T1 C1 A1 h1 g1 0, T2 C2 A2 h2 g2 1, T3 C3 A3 h3 g3 2, T4 C4 A4 h4 g4 3)
+}
@@ -0,0 +1,329 @@
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use crate::{backend::RawCondvarTimeout, park_while_until, Deadline, RecvTimeoutError};
+use crate::{
+    backend::{DefaultCondvar, RawCondvar},
+    SharedState,
+};
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+use crate::{park_while, RecvError};
+use alloc::sync::Arc;
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+use core::time::Duration;
+use core::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use lock_api::Mutex;
+
+/// The bits of `middle` that hold a slot index.
+const INDEX: usize = 0b011;
+/// Set in `middle` when the middle slot holds a value the receiver has not
+/// taken yet.
+const DIRTY: usize = 0b100;
+
+/// The sender for a channel created by [`triple_channel`].
+///
+/// There is only ever one sender, so it cannot be cloned.
+pub struct TripleSender<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<TripleShared<T, C>>,
+    /// The slot that only the sender touches.
+    back: usize,
+}
+
+/// The receiver for a channel created by [`triple_channel`].
+///
+/// There is only ever one receiver, so it cannot be cloned.
+pub struct TripleReceiver<T, C: RawCondvar = DefaultCondvar> {
+    shared: Arc<TripleShared<T, C>>,
+    /// The slot that only the receiver touches.
+    front: usize,
+}
+
+/// A triple buffer.
+///
+/// Each of the three slots is owned by exactly one party at a time: the
+/// sender owns the back slot, the receiver owns the front slot, and the
+/// middle slot is owned by `middle`. The sender publishes by writing its back
+/// slot and swapping it for the middle one, and the receiver takes a value by
+/// swapping its front slot for the middle one. Neither ever waits for the
+/// other.
+///
+/// Waiting receivers set `parked` while holding `state`, and the sender only
+/// locks `state` to wake them when it sees `parked` after publishing.
+struct TripleShared<T, C: RawCondvar> {
+    slots: [UnsafeCell<Option<T>>; 3],
+    /// The index of the middle slot, together with the `DIRTY` flag.
+    middle: AtomicUsize,
+    parked: AtomicBool,
+    state: Mutex<C::RawMutex, SharedState>,
+    _condvar: PhantomData<C>,
+}
+
+// SAFETY: A slot is only accessed through the handle that owns it, and a
+// slot changes owner through `middle`, whose swaps order the accesses of the
+// old owner before those of the new one. Values therefore move between the
+// threads but are never shared by them.
+unsafe impl<T: Send, C: RawCondvar + Sync> Sync for TripleShared<T, C> where C::RawMutex: Sync {}
+
+/// Creates a new watch channel with one sender and one receiver that never
+/// block each other.
+///
+/// The channel is a triple buffer, so sending and receiving only swap a slot
+/// index. The receiver borrows the value in its own slot rather than cloning
+/// it, so `T` does not need to be `Clone`. This suits real-time threads,
+/// provided they do not call the methods that wait. A value that is replaced
+/// by the sender before the receiver took it is dropped by the sender.
+///
+/// The starting value in the channel is not initially considered seen by the receiver.
+pub fn triple_channel<T>(value: T) -> (TripleSender<T>, TripleReceiver<T>) {
+    new(value)
+}
+
+pub(crate) fn new<T, C: RawCondvar>(value: T) -> (TripleSender<T, C>, TripleReceiver<T, C>) {
+    let shared = Arc::new(TripleShared {
+        slots: [
+            UnsafeCell::new(Some(value)),
+            UnsafeCell::new(None),
+            UnsafeCell::new(None),
+        ],
+        middle: AtomicUsize::new(DIRTY),
+        parked: AtomicBool::new(false),
+        state: Mutex::new(SharedState::new(0)),
+        _condvar: PhantomData,
+    });
+    (
+        TripleSender {
+            shared: shared.clone(),
+            back: 2,
+        },
+        TripleReceiver { shared, front: 1 },
+    )
+}
+
+impl<T, C: RawCondvar> TripleShared<T, C> {
+    fn is_dirty(&self) -> bool {
+        self.middle.load(Ordering::SeqCst) & DIRTY != 0
+    }
+
+    /// Wake the receiver if it is parked.
+    fn notify(&self) {
+        let mut state = self.state.lock();
+        state.version = state.version.wrapping_add(1);
+        let version = state.version;
+        state.waiters.wake(version);
+    }
+}
+
+impl<T, C: RawCondvar> TripleSender<T, C> {
+    /// Send a new message and wake the receiver if it is waiting for one.
+    ///
+    /// This does not block unless the receiver is waiting, in which case it
+    /// briefly locks the channel to wake it.
+    pub fn send(&mut self, value: T) {
+        // SAFETY: The back slot belongs to the sender, and the receiver's
+        // last access to it happened before the swap that handed it over.
+        unsafe { *self.shared.slots[self.back].get() = Some(value) };
+        // This swap releases the write above to the receiver. It is `SeqCst`
+        // so that either the receiver sees the slot as dirty before it parks,
+        // or the load of `parked` below sees that it parked.
+        let middle = self.shared.middle.swap(self.back | DIRTY, Ordering::SeqCst);
+        self.back = middle & INDEX;
+        if self.shared.parked.load(Ordering::SeqCst) {
+            self.shared.notify();
+        }
+    }
+}
+
+impl<T, C: RawCondvar> TripleReceiver<T, C> {
+    /// Take the middle slot if it holds a new value.
+    fn swap_front(&mut self) -> bool {
+        if !self.shared.is_dirty() {
+            return false;
+        }
+        // Only the receiver clears the flag, so the slot is still dirty. The
+        // swap releases the reads of the old front slot to the sender and
+        // acquires the write of the new one.
+        let middle = self.shared.middle.swap(self.front, Ordering::AcqRel);
+        self.front = middle & INDEX;
+        true
+    }
+
+    fn front(&self) -> &T {
+        // SAFETY: The front slot belongs to the receiver, and the sender's
+        // write to it happened before the swap that handed it over. The
+        // returned reference borrows the receiver, so the slot cannot be
+        // handed back while it is in use.
+        let slot = unsafe { &*self.shared.slots[self.front].get() };
+        // A slot is only dirty after a value was written to it, and the front
+        // slot was once dirty.
+        slot.as_ref()
+            .expect("front slot of a triple buffer is empty")
+    }
+
+    /// Get the latest value sent on the channel.
+    ///
+    /// This never blocks.
+    pub fn get(&mut self) -> &T {
+        self.swap_front();
+        self.front()
+    }
+
+    /// Get the latest value if that value has not previously been seen by
+    /// this receiver.
+    ///
+    /// This never blocks.
+    pub fn get_if_new(&mut self) -> Option<&T> {
+        if self.swap_front() {
+            Some(self.front())
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if a value that this receiver has not seen is available.
+    pub fn has_changed(&self) -> bool {
+        self.shared.is_dirty()
+    }
+
+    /// Wait for a new value by polling the channel, calling `idle` whenever
+    /// there is nothing new.
+    ///
+    /// See [`WatchReceiver::wait_with`](crate::WatchReceiver::wait_with).
+    pub fn wait_with<F>(&mut self, mut idle: F) -> &T
+    where
+        F: FnMut(),
+    {
+        while !self.swap_front() {
+            idle();
+        }
+        self.front()
+    }
+
+    /// Returns `true` if the sender has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().senders == 0
+    }
+}
+
+#[cfg(any(not(target_family = "wasm"), target_feature = "atomics"))]
+impl<T, C: RawCondvar> TripleReceiver<T, C> {
+    /// Park until `condition` returns false for a channel without a new
+    /// value.
+    fn park_while<F>(&self, mut condition: F) -> bool
+    where
+        F: FnMut(&SharedState) -> bool,
+    {
+        let shared = &*self.shared;
+        let state = shared.state.lock();
+        // Set under the lock, so a sender that sees it cannot wake the
+        // receiver before it is registered as a waiter.
+        shared.parked.store(true, Ordering::SeqCst);
+        let state = park_while::<C, _>(state, |state| !shared.is_dirty() && condition(state));
+        shared.parked.store(false, Ordering::Relaxed);
+        drop(state);
+
+        self.shared.is_dirty()
+    }
+
+    /// This method waits until a new value becomes available and returns it.
+    ///
+    /// This blocks, so it must not be called from a real-time thread. If the
+    /// sender has been dropped, this waits forever. Use [`recv`] to detect
+    /// that case.
+    ///
+    /// [`recv`]: TripleReceiver::recv
+    pub fn wait(&mut self) -> &T {
+        if !self.swap_front() {
+            self.park_while(|_| true);
+            self.swap_front();
+        }
+        self.front()
+    }
+
+    /// Like [`wait`], but fails once the sender has been dropped.
+    ///
+    /// [`wait`]: TripleReceiver::wait
+    pub fn recv(&mut self) -> Result<&T, RecvError> {
+        if !self.swap_front() {
+            if !self.park_while(|state| state.senders > 0) {
+                return Err(RecvError);
+            }
+            self.swap_front();
+        }
+        Ok(self.front())
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(not(target_family = "wasm"), target_feature = "atomics")
+))]
+impl<T, C: RawCondvarTimeout> TripleReceiver<T, C> {
+    /// Park until `condition` returns false for a channel without a new
+    /// value, giving up after `duration`.
+    ///
+    /// Returns `Err(true)` if the deadline expired, and `Err(false)` if the
+    /// condition stopped holding without a new value.
+    fn park_while_timeout<F>(&self, duration: Duration, mut condition: F) -> Result<(), bool>
+    where
+        F: FnMut(&SharedState) -> bool,
+    {
+        let shared = &*self.shared;
+        let deadline = Deadline::after(duration);
+        let state = shared.state.lock();
+        shared.parked.store(true, Ordering::SeqCst);
+        let (state, ready) = park_while_until::<C, _>(state, deadline, |state| {
+            !shared.is_dirty() && condition(state)
+        });
+        shared.parked.store(false, Ordering::Relaxed);
+        drop(state);
+
+        if !ready {
+            Err(true)
+        } else if !shared.is_dirty() {
+            Err(false)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// This method waits until a new value becomes available and returns it,
+    /// timing out after specified duration.
+    pub fn wait_timeout(&mut self, duration: Duration) -> Option<&T> {
+        if !self.swap_front() {
+            self.park_while_timeout(duration, |_| true).ok()?;
+            self.swap_front();
+        }
+        Some(self.front())
+    }
+
+    /// Like [`wait_timeout`], but fails once the sender has been dropped.
+    ///
+    /// [`wait_timeout`]: TripleReceiver::wait_timeout
+    pub fn recv_timeout(&mut self, duration: Duration) -> Result<&T, RecvTimeoutError> {
+        if !self.swap_front() {
+            match self.park_while_timeout(duration, |state| state.senders > 0) {
+                Ok(()) => {}
+                Err(true) => return Err(RecvTimeoutError::Timeout),
+                Err(false) => return Err(RecvTimeoutError::Closed),
+            }
+            self.swap_front();
+        }
+        Ok(self.front())
+    }
+}
+
+impl<T, C: RawCondvar> Drop for TripleSender<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+        state.notify_all();
+    }
+}
@@ -0,0 +1,212 @@
+use crate::backend::RawCondvar;
+use alloc::{sync::Arc, vec::Vec};
+
+/// How many parked threads a single change wakes at once.
+///
+/// Every thread that wakes up wakes one more, so all of them get to see the
+/// change while only a few compete for the lock at a time.
+const WAKE_BATCH: usize = 4;
+
+/// The values that a receiver is interested in, see
+/// [`WatchReceiver::set_interest`](crate::WatchReceiver::set_interest).
+pub(crate) type Interest<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// The interest of a parked thread, which the senders check before waking
+/// it.
+#[derive(Clone, Copy)]
+pub(crate) struct Filter {
+    interest: *const (),
+    test: unsafe fn(*const (), *const ()) -> bool,
+}
+
+unsafe fn test<T>(interest: *const (), value: *const ()) -> bool {
+    (*(interest as *const Interest<T>))(&*(value as *const T))
+}
+
+impl Filter {
+    /// Refer to `interest`, which must outlive every node that the filter
+    /// is registered with.
+    pub(crate) fn new<T>(interest: &Interest<T>) -> Filter {
+        Filter {
+            interest: interest as *const Interest<T> as *const (),
+            test: test::<T>,
+        }
+    }
+}
+
+/// A thread parked on its own condvar.
+struct WaitNode {
+    condvar: *const (),
+    notify: unsafe fn(*const ()),
+    filter: Option<Filter>,
+    /// The version of the channel when the thread parked, or of the latest
+    /// value that its filter passed over.
+    since: u64,
+    woken: bool,
+}
+
+// SAFETY: The condvar and the interest are only used under the channel
+// lock while their thread is parked, the channel is only shared between
+// threads if the condvar type is `Sync`, and an interest is always `Sync`.
+unsafe impl Send for WaitNode {}
+
+/// The threads waiting for a channel to change.
+///
+/// Each thread parks on a condvar of its own that lives on its stack, so
+/// waking it never disturbs the others. A node is only ever touched with
+/// the channel locked, and its thread removes it before returning.
+pub(crate) struct WaitList {
+    nodes: Vec<Option<WaitNode>>,
+    free: Vec<usize>,
+    /// The number of parked threads that have a filter.
+    filtered: usize,
+    /// How many times a parked thread was notified.
+    #[cfg(feature = "stats")]
+    pub(crate) notifications: u64,
+    /// How many times a parked thread woke up.
+    #[cfg(feature = "stats")]
+    pub(crate) wakeups: u64,
+    /// How long parked threads took to get a new value.
+    #[cfg(all(feature = "stats", feature = "std", not(target_family = "wasm")))]
+    pub(crate) latency: crate::stats::WakeupLatency,
+}
+
+unsafe fn notify<C: RawCondvar>(condvar: *const ()) {
+    (*(condvar as *const C)).notify_all();
+}
+
+impl WaitList {
+    pub(crate) const fn new() -> Self {
+        WaitList {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            filtered: 0,
+            #[cfg(feature = "stats")]
+            notifications: 0,
+            #[cfg(feature = "stats")]
+            wakeups: 0,
+            #[cfg(all(feature = "stats", feature = "std", not(target_family = "wasm")))]
+            latency: crate::stats::WakeupLatency::new(),
+        }
+    }
+
+    /// Register a thread that is about to park on `condvar`, and that only
+    /// wants to be woken for the values that `filter` passes, if any.
+    ///
+    /// The node must be removed before `condvar` is dropped.
+    pub(crate) fn insert<C: RawCondvar>(
+        &mut self,
+        condvar: &C,
+        filter: Option<Filter>,
+        since: u64,
+    ) -> usize {
+        self.filtered += filter.is_some() as usize;
+        let node = WaitNode {
+            condvar: condvar as *const C as *const (),
+            notify: notify::<C>,
+            filter,
+            since,
+            woken: false,
+        };
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Unregister a thread that woke up.
+    pub(crate) fn remove(&mut self, index: usize) {
+        if let Some(node) = self.nodes[index].take() {
+            self.filtered -= node.filter.is_some() as usize;
+        }
+        self.free.push(index);
+        #[cfg(feature = "stats")]
+        {
+            self.wakeups += 1;
+        }
+    }
+
+    /// The number of parked threads.
+    pub(crate) fn len(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    /// Wake the first batch of threads that parked before the channel
+    /// reached `version`.
+    pub(crate) fn wake(&mut self, version: u64) {
+        self.wake_some(version, WAKE_BATCH, None);
+    }
+
+    /// Like [`wake`](WaitList::wake), but leave the threads whose filter
+    /// passes over `value` parked.
+    ///
+    /// # Safety
+    ///
+    /// Every filter in the list must have been made for `T`.
+    pub(crate) unsafe fn wake_interested<T>(&mut self, version: u64, value: &T) {
+        self.wake_some(version, WAKE_BATCH, Some(value as *const T as *const ()));
+    }
+
+    /// Wake one more thread that parked before the channel reached
+    /// `version`, if any are left.
+    ///
+    /// Every thread calls this when it wakes up, including one that goes
+    /// back to sleep, which carries a change through the whole list one
+    /// thread at a time.
+    pub(crate) fn wake_next(&mut self, version: u64) {
+        self.wake_some(version, 1, None);
+    }
+
+    fn wake_some(&mut self, version: u64, mut budget: usize, value: Option<*const ()>) {
+        // With a value, every filter is checked now, as the threads woken
+        // later by `wake_next` are not checked again.
+        let check_all = value.is_some() && self.filtered > 0;
+        for node in self.nodes.iter_mut().flatten() {
+            if budget == 0 && !check_all {
+                return;
+            }
+            if !node.woken && node.since != version {
+                if let (Some(filter), Some(value)) = (node.filter, value) {
+                    // SAFETY: The node is still registered, so its interest
+                    // is alive, and the caller made sure that it is for the
+                    // type of `value`.
+                    if !unsafe { (filter.test)(filter.interest, value) } {
+                        node.since = version;
+                        continue;
+                    }
+                }
+                if budget == 0 {
+                    continue;
+                }
+                node.woken = true;
+                // SAFETY: The node is still registered, so its thread is
+                // parked and its condvar is alive.
+                unsafe { (node.notify)(node.condvar) };
+                budget -= 1;
+                #[cfg(feature = "stats")]
+                {
+                    self.notifications += 1;
+                }
+            }
+        }
+    }
+
+    /// Wake every parked thread.
+    pub(crate) fn wake_all(&mut self) {
+        for node in self.nodes.iter_mut().flatten() {
+            node.woken = true;
+            // SAFETY: As in `wake_some`.
+            unsafe { (node.notify)(node.condvar) };
+            #[cfg(feature = "stats")]
+            {
+                self.notifications += 1;
+            }
+        }
+    }
+}
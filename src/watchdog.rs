@@ -0,0 +1,124 @@
+use crate::{stop::Stop, WatchReceiver};
+use alloc::{boxed::Box, sync::Arc};
+use std::{
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Handle to the thread spawned by [`WatchReceiver::watchdog`].
+///
+/// Dropping the handle asks the thread to stop without waiting for it.
+pub struct WatchdogHandle {
+    /// Asks the thread to stop.
+    stop: Box<dyn Fn() + Send + Sync>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T> WatchReceiver<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Spawn a thread that calls `on_stale` whenever no new value has
+    /// arrived for `quiet`.
+    ///
+    /// The callback is given the latest value, which is the one that went
+    /// stale. While the channel stays quiet, the callback is called again
+    /// every `quiet`, and every new value restarts the wait. The thread exits
+    /// once the channel is closed or the returned handle is stopped.
+    ///
+    /// Use [`watchdog_once`] to only be told once until the next value.
+    ///
+    /// [`watchdog_once`]: WatchReceiver::watchdog_once
+    pub fn watchdog<F>(self, quiet: Duration, on_stale: F) -> WatchdogHandle
+    where
+        F: FnMut(&T) + Send + 'static,
+    {
+        self.spawn_watchdog(quiet, true, on_stale)
+    }
+
+    /// Like [`watchdog`], but calls `on_stale` only once each time the
+    /// channel goes quiet, and not again until a new value has arrived.
+    ///
+    /// [`watchdog`]: WatchReceiver::watchdog
+    pub fn watchdog_once<F>(self, quiet: Duration, on_stale: F) -> WatchdogHandle
+    where
+        F: FnMut(&T) + Send + 'static,
+    {
+        self.spawn_watchdog(quiet, false, on_stale)
+    }
+
+    fn spawn_watchdog<F>(mut self, quiet: Duration, repeat: bool, mut on_stale: F) -> WatchdogHandle
+    where
+        F: FnMut(&T) + Send + 'static,
+    {
+        let stop = Arc::new(Stop::new());
+        let shared = self.shared.clone();
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut fired = false;
+                loop {
+                    let seen = self.last_seen_version;
+                    let deadline = self.shared.deadline(quiet);
+                    let state = self.shared.state.lock();
+                    let (state, ready) = self.shared.wait_while_until_unless_stopped(
+                        state,
+                        deadline,
+                        &stop,
+                        |state| state.version == seen && state.is_open(),
+                    );
+                    if !state.is_open() || stop.is_stopped() {
+                        return;
+                    }
+                    drop(state);
+
+                    if ready {
+                        // Mark the new value seen.
+                        drop(self.get_shared());
+                        fired = false;
+                    } else if repeat || !fired {
+                        on_stale(&self.get_shared());
+                        fired = true;
+                    }
+                }
+            })
+        };
+
+        WatchdogHandle {
+            stop: Box::new(move || {
+                // Taking the lock ensures that the watchdog is either parked
+                // or has not yet checked the stop flag.
+                let _state = shared.state.lock();
+                stop.stop();
+            }),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl WatchdogHandle {
+    /// Stop the watchdog thread and wait for it to exit.
+    pub fn stop(mut self) {
+        self.interrupt();
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    /// Returns `true` if the watchdog thread has exited.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    fn interrupt(&self) {
+        (self.stop)();
+    }
+}
+
+impl Drop for WatchdogHandle {
+    fn drop(&mut self) {
+        self.interrupt();
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{backend::RawCondvar, Allocator, WatchReceiver};
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::{fmt, ptr};
+use std::{
+    io,
+    os::windows::io::{AsHandle, AsRawHandle, BorrowedHandle, RawHandle},
+};
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, HANDLE, WAIT_OBJECT_0},
+    System::Threading::{CreateEventW, ResetEvent, SetEvent, WaitForSingleObject},
+};
+
+/// A Windows event object that is signaled whenever a channel changes, for
+/// waiting on a channel together with other handles in
+/// `WaitForMultipleObjects`.
+///
+/// This is created by [`WatchReceiver::readiness_event`]. The event is
+/// manual-reset: it stays signaled until [`reset`](WindowsEventHandle::reset)
+/// is called, however many values are sent meanwhile. To not miss a value,
+/// reset it before reading the channel:
+///
+/// 1. wait for the handle,
+/// 2. call `reset`,
+/// 3. read every new value, such as with [`WatchReceiver::get_if_new`].
+///
+/// A value sent after step 2 signals the event again, even if step 3 already
+/// returned it, so the next wait may find nothing new.
+///
+/// The event is closed once the handle is dropped. The channel only keeps a
+/// weak reference to it.
+pub struct WindowsEventHandle {
+    event: Arc<Event>,
+}
+
+/// An owned event object.
+pub(crate) struct Event {
+    handle: HANDLE,
+}
+
+// SAFETY: Event objects can be used from any thread.
+unsafe impl Send for Event {}
+// SAFETY: See above.
+unsafe impl Sync for Event {}
+
+impl Event {
+    fn new(signaled: bool) -> io::Result<Event> {
+        // SAFETY: There are no security attributes and no name, and the
+        // returned handle is owned by the event.
+        let handle = unsafe { CreateEventW(ptr::null(), 1, i32::from(signaled), ptr::null()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Event { handle })
+    }
+
+    fn set(&self) {
+        // SAFETY: The handle is open until the event is dropped. This can
+        // only fail for an invalid handle.
+        unsafe { SetEvent(self.handle) };
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        // SAFETY: The handle is open and owned by the event.
+        unsafe { CloseHandle(self.handle) };
+    }
+}
+
+/// The events created for a channel, see [`WindowsEventHandle`].
+pub(crate) struct Events {
+    events: Vec<Weak<Event>>,
+}
+
+impl Events {
+    pub(crate) const fn new() -> Events {
+        Events { events: Vec::new() }
+    }
+
+    /// Signal every event that still has a handle, and forget the others.
+    ///
+    /// This is called with the state of the channel locked, when its waiters
+    /// are woken.
+    pub(crate) fn set_all(&mut self) {
+        self.events.retain(|event| match event.upgrade() {
+            Some(event) => {
+                event.set();
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+impl<T, C: RawCondvar, A: Allocator + Clone> WatchReceiver<T, C, A> {
+    /// Create an event object that is signaled whenever the channel changes,
+    /// see [`WindowsEventHandle`].
+    ///
+    /// The event starts signaled if this receiver has not seen the latest
+    /// value. Like the wakeups of [`wait`](WatchReceiver::wait), it is also
+    /// signaled when the channel closes. Each call creates a new event.
+    pub fn readiness_event(&self) -> io::Result<WindowsEventHandle> {
+        let mut state = self.shared.state.lock();
+        // Created with the state locked, so that no send falls between the
+        // check and the registration.
+        let event = Arc::new(Event::new(state.version != self.last_seen_version)?);
+        state.events.events.push(Arc::downgrade(&event));
+        Ok(WindowsEventHandle { event })
+    }
+}
+
+impl WindowsEventHandle {
+    /// Make the event unsignaled, until the channel changes again.
+    pub fn reset(&self) {
+        // SAFETY: The handle is open until the event is dropped.
+        unsafe { ResetEvent(self.event.handle) };
+    }
+
+    /// Returns `true` if the event is signaled, without waiting.
+    pub fn is_set(&self) -> bool {
+        // SAFETY: The handle is open until the event is dropped.
+        unsafe { WaitForSingleObject(self.event.handle, 0) == WAIT_OBJECT_0 }
+    }
+}
+
+impl AsRawHandle for WindowsEventHandle {
+    fn as_raw_handle(&self) -> RawHandle {
+        self.event.handle
+    }
+}
+
+impl AsHandle for WindowsEventHandle {
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        // SAFETY: The handle is open for as long as `self` is borrowed.
+        unsafe { BorrowedHandle::borrow_raw(self.event.handle) }
+    }
+}
+
+impl fmt::Debug for WindowsEventHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WindowsEventHandle")
+            .field(&self.event.handle)
+            .finish()
+    }
+}
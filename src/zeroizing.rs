@@ -0,0 +1,29 @@
+use crate::{backend::RawCondvar, channel, Allocator, WatchReceiver, WatchSender};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Creates a new channel for a secret, such as a private key, that scrubs
+/// every copy of the value when it is dropped.
+///
+/// The channel carries the value in a [`Zeroizing`] wrapper, so each copy
+/// is zeroized wherever it ends up being dropped: the value that a send
+/// replaces, the copies kept by the history or for undo, the clones that
+/// receivers get, and the last value when the channel is destroyed. Copies
+/// of the value that the wrapper cannot see, such as the old buffer of a
+/// `Vec` that grew inside [`WatchSender::update`], are not scrubbed. To
+/// configure the channel, pass a `Zeroizing` value to a [`ChannelBuilder`].
+///
+/// [`ChannelBuilder`]: crate::ChannelBuilder
+pub fn zeroizing_channel<T: Zeroize>(
+    value: T,
+) -> (WatchSender<Zeroizing<T>>, WatchReceiver<Zeroizing<T>>) {
+    channel(Zeroizing::new(value))
+}
+
+impl<T: Zeroize, C: RawCondvar, A: Allocator + Clone> WatchSender<Zeroizing<T>, C, A> {
+    /// Send a new secret, which the channel zeroizes when it is dropped.
+    ///
+    /// See [`zeroizing_channel`].
+    pub fn send_zeroizing(&self, value: T) {
+        self.send(Zeroizing::new(value));
+    }
+}
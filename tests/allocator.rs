@@ -0,0 +1,94 @@
+//! Channels whose state is allocated with an allocator of their own.
+//!
+//! Run with `cargo +nightly test --features allocator_api`.
+#![cfg(feature = "allocator_api")]
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Counts the bytes that are allocated through it and not yet freed.
+#[derive(Clone, Default)]
+struct Counting {
+    live: Arc<AtomicUsize>,
+    allocations: Arc<AtomicUsize>,
+}
+
+impl Counting {
+    fn live(&self) -> usize {
+        self.live.load(Ordering::SeqCst)
+    }
+
+    fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.live.fetch_add(layout.size(), Ordering::SeqCst);
+        self.allocations.fetch_add(1, Ordering::SeqCst);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.fetch_sub(layout.size(), Ordering::SeqCst);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn the_state_comes_from_the_allocator() {
+    let alloc = Counting::default();
+    let (tx, mut rx) = watch::channel_in([0u8; 16], alloc.clone());
+    assert_eq!(alloc.allocations(), 1);
+    assert!(alloc.live() > 16);
+
+    tx.send([1; 16]);
+    assert_eq!(rx.get(), [1; 16]);
+
+    drop(tx);
+    assert!(alloc.live() > 0);
+    drop(rx);
+    assert_eq!(alloc.live(), 0);
+}
+
+#[test]
+fn new_handles_share_the_state() {
+    let alloc = Counting::default();
+    let (tx, rx) = watch::channel_in(0u32, alloc.clone());
+    let allocated = alloc.live();
+
+    let other_tx = tx.clone();
+    let other_rx = rx.clone();
+    let subscribed = tx.subscribe();
+    let new_tx = rx.new_sender();
+    // None of these allocate a second state.
+    assert_eq!(alloc.live(), allocated);
+    assert_eq!(alloc.allocations(), 1);
+
+    new_tx.send(2);
+    let mut subscribed = subscribed;
+    assert_eq!(subscribed.get_if_new(), Some(2));
+
+    drop((tx, rx, other_tx, other_rx, subscribed));
+    assert!(alloc.live() > 0);
+    drop(new_tx);
+    assert_eq!(alloc.live(), 0);
+}
+
+#[test]
+fn channels_keep_to_their_own_allocator() {
+    let first = Counting::default();
+    let second = Counting::default();
+    let (_a, _b) = watch::channel_in(0u8, first.clone());
+    let (_c, _d) = watch::channel_in(0u8, second.clone());
+    assert_eq!(first.allocations(), 1);
+    assert_eq!(second.allocations(), 1);
+}
@@ -0,0 +1,95 @@
+//! The channel backed by `arc-swap`.
+#![cfg(all(feature = "arc-swap", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{
+    hint::black_box,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+use util::join_all;
+
+#[test]
+fn semantics() {
+    let (tx, mut rx) = watch::arc_channel(String::from("a"));
+    assert_eq!(*rx.get_if_new().unwrap(), "a");
+    assert!(rx.get_if_new().is_none());
+    tx.update(|value| value.push('b'));
+    assert!(rx.has_changed());
+    assert_eq!(*rx.get(), "ab");
+    assert!(!rx.has_changed());
+
+    let value = Arc::new(String::from("c"));
+    let sender = {
+        let value = value.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx.send_arc(value);
+        })
+    };
+    assert!(Arc::ptr_eq(&rx.wait(), &value));
+    sender.join().unwrap();
+    assert!(rx.recv().is_err());
+    assert!(rx.is_closed());
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+}
+
+#[test]
+fn get_if_new_matches_the_version() {
+    const SENDS: u64 = 100_000;
+    let (tx, rx) = watch::arc_channel(0u64);
+    let readers = (0..4)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut last = None;
+                while last != Some(SENDS) {
+                    if let Some(value) = rx.get_if_new() {
+                        // A value is only new once, so it always grows.
+                        assert!(last < Some(*value));
+                        last = Some(*value);
+                    }
+                }
+            })
+        })
+        .collect();
+    for i in 1..=SENDS {
+        tx.send(i);
+    }
+    join_all(readers);
+}
+
+#[test]
+fn read_throughput_against_the_locked_channel() {
+    /// Time 8 threads that each read 100 000 times with a reader from
+    /// `reader`.
+    fn run<R: FnMut() + Send + 'static>(reader: impl Fn() -> R) -> Duration {
+        let start = Instant::now();
+        let threads = (0..8)
+            .map(|_| {
+                let mut read = reader();
+                thread::spawn(move || {
+                    for _ in 0..100_000 {
+                        read();
+                    }
+                })
+            })
+            .collect();
+        join_all(threads);
+        start.elapsed()
+    }
+
+    let (_tx, rx) = watch::arc_channel(vec![1u8; 64]);
+    let swapped = run(|| {
+        let mut rx = rx.clone();
+        move || drop(black_box(rx.get()))
+    });
+    let (_tx, rx) = watch::channel(vec![1u8; 64]);
+    let locked = run(|| {
+        let mut rx = rx.clone();
+        move || drop(black_box(rx.get_shared()))
+    });
+    eprintln!("arc-swap: {:?}, rwlock: {:?}", swapped, locked);
+}
@@ -0,0 +1,263 @@
+//! The same tests against every built-in backend and a minimal one that is
+//! only defined here, to show that the channel only relies on the traits.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+use watch::{
+    backend::{
+        self,
+        lock_api::{self, GuardSend, MutexGuard},
+        RawCondvar, RawCondvarTimeout,
+    },
+    RecvError, RecvTimeoutError,
+};
+
+/// A spinlock, which also serves as the rwlock.
+pub struct TestMutex(AtomicBool);
+
+unsafe impl lock_api::RawMutex for TestMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: TestMutex = TestMutex(AtomicBool::new(false));
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        while !self.try_lock() {
+            thread::yield_now();
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        !self.0.swap(true, Ordering::Acquire)
+    }
+
+    unsafe fn unlock(&self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+unsafe impl lock_api::RawRwLock for TestMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: TestMutex = <TestMutex as lock_api::RawMutex>::INIT;
+    type GuardMarker = GuardSend;
+
+    fn lock_shared(&self) {
+        lock_api::RawMutex::lock(self);
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        lock_api::RawMutex::try_lock(self)
+    }
+
+    unsafe fn unlock_shared(&self) {
+        lock_api::RawMutex::unlock(self);
+    }
+
+    fn lock_exclusive(&self) {
+        lock_api::RawMutex::lock(self);
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        lock_api::RawMutex::try_lock(self)
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        lock_api::RawMutex::unlock(self);
+    }
+}
+
+/// Waiters yield until the generation changes.
+pub struct TestCondvar(AtomicUsize);
+
+unsafe impl RawCondvar for TestCondvar {
+    type RawMutex = TestMutex;
+    type RawRwLock = TestMutex;
+
+    fn new() -> Self {
+        TestCondvar(AtomicUsize::new(0))
+    }
+
+    fn wait<T>(&self, guard: &mut MutexGuard<'_, TestMutex, T>) {
+        let generation = self.0.load(Ordering::Acquire);
+        MutexGuard::unlocked(guard, || {
+            while self.0.load(Ordering::Acquire) == generation {
+                thread::yield_now();
+            }
+        });
+    }
+
+    fn notify_all(&self) {
+        self.0.fetch_add(1, Ordering::Release);
+    }
+}
+
+unsafe impl RawCondvarTimeout for TestCondvar {
+    fn wait_timeout<T>(&self, guard: &mut MutexGuard<'_, TestMutex, T>, timeout: Duration) -> bool {
+        let generation = self.0.load(Ordering::Acquire);
+        let deadline = Instant::now() + timeout;
+        MutexGuard::unlocked(guard, || {
+            while self.0.load(Ordering::Acquire) == generation {
+                if Instant::now() >= deadline {
+                    return true;
+                }
+                thread::yield_now();
+            }
+            false
+        })
+    }
+}
+
+fn send_and_wait<C>()
+where
+    C: RawCondvar + Send + Sync + 'static,
+    C::RawMutex: Send + Sync,
+    C::RawRwLock: Send + Sync,
+{
+    let (tx, mut rx) = backend::channel::<C, _>(0u32);
+    assert_eq!(rx.get_if_new(), Some(0));
+    let sender = thread::spawn(move || {
+        for i in 1..=100 {
+            tx.send(i);
+        }
+    });
+    let mut last = 0;
+    while last < 100 {
+        let value = rx.wait();
+        assert!(value > last);
+        last = value;
+    }
+    sender.join().unwrap();
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+fn many_waiters<C>()
+where
+    C: RawCondvar + Send + Sync + 'static,
+    C::RawMutex: Send + Sync,
+    C::RawRwLock: Send + Sync,
+{
+    let (tx, rx) = backend::channel::<C, _>(0u32);
+    let waiters: Vec<_> = (0..8)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while let Ok(value) = rx.recv() {
+                    last = value;
+                }
+                last
+            })
+        })
+        .collect();
+    for i in 1..=1000 {
+        tx.send(i);
+    }
+    drop(tx);
+    for waiter in waiters {
+        assert_eq!(waiter.join().unwrap(), 1000);
+    }
+}
+
+fn timeouts<C>()
+where
+    C: RawCondvarTimeout + Send + Sync + 'static,
+    C::RawMutex: Send + Sync,
+    C::RawRwLock: Send + Sync,
+{
+    let (tx, mut rx) = backend::channel::<C, _>(1);
+    rx.get();
+    let start = Instant::now();
+    assert_eq!(rx.wait_timeout(Duration::from_millis(30)), None);
+    assert!(start.elapsed() >= Duration::from_millis(30));
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(2);
+    });
+    assert_eq!(rx.wait_timeout(Duration::from_secs(5)), Some(2));
+    sender.join().unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)),
+        Err(RecvTimeoutError::Closed)
+    );
+}
+
+macro_rules! backend_tests {
+    ($($(#[$attr:meta])* $name:ident: $condvar:ty,)*) => {$(
+        $(#[$attr])*
+        mod $name {
+            #[test]
+            fn send_and_wait() {
+                super::send_and_wait::<$condvar>();
+            }
+
+            #[test]
+            fn many_waiters() {
+                super::many_waiters::<$condvar>();
+            }
+
+            #[test]
+            fn timeouts() {
+                super::timeouts::<$condvar>();
+            }
+
+            #[test]
+            fn wake_latency() {
+                let latency = super::round_trip::<$condvar>();
+                eprintln!("{}: {:?} per round trip", stringify!($name), latency);
+            }
+        }
+    )*};
+}
+
+backend_tests! {
+    default_condvar: watch::backend::DefaultCondvar,
+    std_condvar: watch::backend::StdCondvar,
+    #[cfg(feature = "parking_lot")]
+    parking_lot: watch::backend::ParkingLotCondvar,
+    #[cfg(all(feature = "futex", any(target_os = "linux", target_os = "android")))]
+    futex: watch::backend::FutexCondvar,
+    test_only: super::TestCondvar,
+}
+
+#[test]
+fn the_default_handles_use_the_default_backend() {
+    let (tx, rx): (
+        watch::WatchSender<i32, backend::DefaultCondvar>,
+        watch::WatchReceiver<i32, backend::DefaultCondvar>,
+    ) = watch::channel(0);
+    let _: watch::WatchSender<i32> = tx;
+    let _: watch::WatchReceiver<i32> = rx;
+}
+
+/// The average time it takes a send to wake a waiting receiver and for it
+/// to answer, on the channel backend `C`.
+fn round_trip<C>() -> Duration
+where
+    C: RawCondvar + Send + Sync + 'static,
+    C::RawMutex: Send + Sync,
+    C::RawRwLock: Send + Sync,
+{
+    const ROUNDS: u32 = 2000;
+    let (ping, mut pings) = backend::channel::<C, _>(0u32);
+    let (pong, mut pongs) = backend::channel::<C, _>(0u32);
+    pings.get();
+    pongs.get();
+    let echo = thread::spawn(move || {
+        while let Ok(i) = pings.recv() {
+            pong.send(i);
+        }
+    });
+    let start = Instant::now();
+    for i in 1..=ROUNDS {
+        ping.send(i);
+        assert_eq!(pongs.wait(), i);
+    }
+    let elapsed = start.elapsed();
+    drop(ping);
+    echo.join().unwrap();
+    elapsed / ROUNDS
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn defaults_match_channel() {
+    let (plain_tx, mut plain) = watch::channel(1u8);
+    let (built_tx, mut built) = watch::builder().channel(1u8);
+    assert_eq!(format!("{:?}", plain), format!("{:?}", built));
+    assert_eq!(plain.has_changed(), built.has_changed());
+    assert_eq!(plain.get_if_new(), built.get_if_new());
+    assert_eq!(plain.get_if_new(), built.get_if_new());
+
+    plain_tx.send(2);
+    built_tx.send(2);
+    assert_eq!(format!("{:?}", plain_tx), format!("{:?}", built_tx));
+    assert_eq!(plain.get_if_new(), built.get_if_new());
+    assert_eq!(
+        plain_tx.subscribe().has_changed(),
+        built_tx.subscribe().has_changed()
+    );
+}
+
+#[test]
+fn default_builder_is_the_same_as_builder() {
+    let (_tx, mut rx) = watch::ChannelBuilder::default().channel(1u8);
+    assert_eq!(rx.get_if_new(), Some(1));
+}
+
+#[test]
+fn initial_seen() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(1u8);
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), 1);
+    tx.send(2);
+    assert_eq!(rx.get_if_new(), Some(2));
+
+    let (_tx, mut rx) = watch::builder().initial_seen(false).channel(1u8);
+    assert_eq!(rx.get_if_new(), Some(1));
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn initial_seen_waits_for_the_first_send() {
+    use std::{thread, time::Duration};
+
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(1u8);
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(2);
+        tx
+    });
+    assert_eq!(rx.wait(), 2);
+    drop(sender.join().unwrap());
+}
+
+#[test]
+fn a_builder_can_be_reused() {
+    let builder = watch::builder().initial_seen(true);
+    let (_a, mut first) = builder.clone().channel(1u8);
+    let (_b, mut second) = builder.channel(2u8);
+    assert_eq!(first.get_if_new(), None);
+    assert_eq!(second.get_if_new(), None);
+    assert!(!first.same_channel(&watch::builder().channel(1u8).1));
+}
+
+#[test]
+fn channel_with_a_backend() {
+    let (tx, mut rx) = watch::builder()
+        .initial_seen(true)
+        .channel_with::<watch::backend::DefaultCondvar, _>(1u8);
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(2);
+    assert_eq!(rx.get_if_new(), Some(2));
+}
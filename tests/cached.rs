@@ -0,0 +1,79 @@
+#![cfg(not(target_family = "wasm"))]
+
+use std::{sync::mpsc, thread, time::Duration};
+
+#[test]
+fn get_cached_refreshes_when_the_channel_is_free() {
+    let (tx, rx) = watch::channel(1);
+    let mut rx = rx.into_cached();
+    assert_eq!(*rx.get_cached(), 1);
+    assert!(!rx.is_stale());
+
+    tx.send(2);
+    assert!(rx.is_stale());
+    assert_eq!(*rx.get_cached(), 2);
+    assert!(!rx.is_stale());
+
+    // The copy counts as seen by the receiver it came from.
+    let mut rx = rx.into_inner();
+    assert_eq!(rx.get_if_new(), None);
+}
+
+// The critical-section backend locks by entering a critical section, which
+// even a `try_lock` has to wait for.
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+#[test]
+fn get_cached_does_not_wait_for_a_long_update() {
+    let (tx, rx) = watch::channel(1);
+    let mut rx = rx.into_cached();
+    tx.send(2);
+
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let updater = thread::spawn(move || {
+        tx.update(|value| {
+            entered_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+            *value = 3;
+        });
+        tx
+    });
+    entered_rx.recv().unwrap();
+
+    // The update holds the lock, so the copy from before the send is
+    // returned.
+    let (read_tx, read_rx) = mpsc::channel();
+    let reader = thread::spawn(move || {
+        read_tx.send(*rx.get_cached()).unwrap();
+        rx
+    });
+    let read = read_rx.recv_timeout(Duration::from_secs(10));
+    release_tx.send(()).unwrap();
+    assert_eq!(read, Ok(1));
+    let mut rx = reader.join().unwrap();
+    let _tx = updater.join().unwrap();
+
+    assert!(rx.is_stale());
+    assert_eq!(*rx.get_cached(), 3);
+    assert!(!rx.is_stale());
+}
+
+#[test]
+fn refresh_blocking_waits_for_the_update() {
+    let (tx, rx) = watch::channel(1);
+    let mut rx = rx.into_cached();
+
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let updater = thread::spawn(move || {
+        tx.update(|value| {
+            entered_tx.send(()).unwrap();
+            thread::sleep(Duration::from_millis(50));
+            *value = 2;
+        });
+        tx
+    });
+    entered_rx.recv().unwrap();
+    assert_eq!(*rx.refresh_blocking(), 2);
+    assert!(!rx.is_stale());
+    drop(updater.join().unwrap());
+}
@@ -0,0 +1,133 @@
+//! `changed_timeout`, whose timeout does not depend on the executor.
+#![cfg(all(
+    feature = "embedded-async",
+    feature = "timer",
+    not(target_family = "wasm")
+))]
+
+use std::{
+    future::Future,
+    pin::pin,
+    sync::{mpsc, Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+use watch::RecvTimeoutError;
+
+mod util;
+use util::task::{block_on, counting_waker};
+
+/// Sends a message to the executor on every wake.
+struct Notify(Mutex<mpsc::Sender<()>>);
+
+impl Wake for Notify {
+    fn wake(self: Arc<Self>) {
+        let _ = self.0.lock().unwrap().send(());
+    }
+}
+
+/// Another executor, which runs `future` on a thread of its own and sleeps
+/// on a channel rather than by parking.
+fn run_on_worker<F>(future: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    thread::spawn(move || {
+        let (notify, woken) = mpsc::channel();
+        let waker = Waker::from(Arc::new(Notify(Mutex::new(notify))));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            woken.recv().unwrap();
+        }
+    })
+    .join()
+    .unwrap()
+}
+
+async fn scenario() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+
+    let start = Instant::now();
+    assert_eq!(
+        rx.changed_timeout(Duration::from_millis(30)).await,
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert!(start.elapsed() >= Duration::from_millis(30));
+
+    let sender = {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx.send(1);
+        })
+    };
+    assert_eq!(rx.changed_timeout(Duration::from_secs(10)).await, Ok(1));
+    sender.join().unwrap();
+
+    drop(tx);
+    assert_eq!(
+        rx.changed_timeout(Duration::from_secs(10)).await,
+        Err(RecvTimeoutError::Closed)
+    );
+}
+
+#[test]
+fn on_a_parking_executor() {
+    block_on(scenario());
+}
+
+#[test]
+fn on_a_worker_thread() {
+    run_on_worker(scenario());
+}
+
+#[test]
+fn a_value_wins_over_the_timeout() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send(1);
+    assert_eq!(block_on(rx.changed_timeout(Duration::ZERO)), Ok(1));
+
+    // The timeout fires and a value arrives before the next poll.
+    let (wakes, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut changed = pin!(rx.changed_timeout(Duration::from_millis(10)));
+    assert_eq!(changed.as_mut().poll(&mut cx), Poll::Pending);
+    thread::sleep(Duration::from_millis(30));
+    tx.send(2);
+    assert!(wakes.count() >= 1);
+    assert_eq!(changed.as_mut().poll(&mut cx), Poll::Ready(Ok(2)));
+}
+
+#[test]
+fn a_timeout_marks_nothing_seen() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    assert_eq!(
+        block_on(rx.changed_timeout(Duration::from_millis(10))),
+        Err(RecvTimeoutError::Timeout)
+    );
+    tx.send(1);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), 1);
+
+    // Nor does dropping the future before it completes, which leaves no
+    // waker behind.
+    let (wakes, waker) = counting_waker();
+    {
+        let mut changed = pin!(rx.changed_timeout(Duration::from_secs(3600)));
+        let poll = changed.as_mut().poll(&mut Context::from_waker(&waker));
+        assert!(poll.is_pending());
+    }
+    tx.send(2);
+    assert_eq!(wakes.count(), 0);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), 2);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn nothing_before_the_first_send() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), None);
+
+    tx.send(Some("a".into()));
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(Some("a".into())));
+    assert_eq!(rx.get(), Some("a".into()));
+}
+
+#[test]
+fn receivers_from_before_the_first_send_get_it() {
+    let (tx, rx) = watch::channel_empty::<u32>();
+    let mut subscribed = tx.subscribe();
+    let mut cloned = rx.clone();
+    assert_eq!(subscribed.get_if_new(), None);
+    assert_eq!(cloned.get_if_new(), None);
+
+    tx.send(Some(1));
+    assert_eq!(subscribed.get_if_new(), Some(Some(1)));
+    assert_eq!(cloned.get_if_new(), Some(Some(1)));
+}
+
+#[test]
+fn receivers_from_after_the_first_send_have_seen_it() {
+    let (tx, _rx) = watch::channel_empty::<u32>();
+    tx.send(Some(1));
+    let mut late = tx.subscribe();
+    assert_eq!(late.get_if_new(), None);
+    assert_eq!(late.get(), Some(1));
+}
+
+#[test]
+fn closing_before_the_first_send() {
+    let (tx, mut rx) = watch::channel_empty::<u32>();
+    drop(tx);
+    assert!(rx.is_closed());
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), None);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn wait_blocks_until_the_first_send() {
+    use std::{thread, time::Duration};
+    use watch::RecvError;
+
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    let mut early = tx.subscribe();
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(Some("a".into()));
+        tx.send(Some("b".into()));
+    });
+    let first = rx.wait();
+    assert!(first == Some("a".into()) || first == Some("b".into()));
+    sender.join().unwrap();
+    assert_eq!(early.recv(), Ok(Some("b".into())));
+    assert_eq!(early.recv(), Err(RecvError));
+}
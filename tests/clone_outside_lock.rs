@@ -0,0 +1,145 @@
+#![cfg(not(target_family = "wasm"))]
+
+use std::{
+    collections::HashMap,
+    hint::black_box,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+mod util;
+use util::join_all;
+
+/// Lets a test hold a clone of [`Slow`] in the middle.
+#[derive(Default)]
+struct Gate {
+    armed: AtomicBool,
+    state: Mutex<(bool, bool)>,
+    changed: Condvar,
+}
+
+impl Gate {
+    fn wait_until_started(&self) {
+        let mut state = self.state.lock().unwrap();
+        while !state.0 {
+            state = self.changed.wait(state).unwrap();
+        }
+    }
+
+    fn release(&self) {
+        self.state.lock().unwrap().1 = true;
+        self.changed.notify_all();
+    }
+}
+
+struct Slow {
+    id: u32,
+    gate: Arc<Gate>,
+}
+
+impl Clone for Slow {
+    fn clone(&self) -> Self {
+        if self.gate.armed.swap(false, Ordering::SeqCst) {
+            let mut state = self.gate.state.lock().unwrap();
+            state.0 = true;
+            self.gate.changed.notify_all();
+            while !state.1 {
+                state = self.gate.changed.wait(state).unwrap();
+            }
+        }
+        Slow {
+            id: self.id,
+            gate: self.gate.clone(),
+        }
+    }
+}
+
+#[test]
+fn senders_do_not_wait_for_a_clone() {
+    let gate = Arc::new(Gate::default());
+    let (tx, mut rx) = watch::channel(Slow {
+        id: 0,
+        gate: gate.clone(),
+    });
+    tx.send(Slow {
+        id: 1,
+        gate: gate.clone(),
+    });
+
+    gate.armed.store(true, Ordering::SeqCst);
+    let reader = thread::spawn(move || {
+        let value = rx.get();
+        (value.id, rx)
+    });
+    gate.wait_until_started();
+
+    // The receiver is stuck in the middle of its clone, and the channel is
+    // not locked.
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    let sender = thread::spawn({
+        let gate = gate.clone();
+        move || {
+            tx.send(Slow { id: 2, gate });
+            done_tx.send(()).unwrap();
+            tx
+        }
+    });
+    let sent = done_rx.recv_timeout(Duration::from_secs(10));
+    gate.release();
+    assert!(sent.is_ok(), "the send waited for the clone");
+    let _tx = sender.join().unwrap();
+
+    // The clone is of the value that was seen, and the one sent during the
+    // clone is still new.
+    let (id, mut rx) = reader.join().unwrap();
+    assert_eq!(id, 1);
+    assert_eq!(rx.get_if_new().map(|value| value.id), Some(2));
+    assert!(rx.get_if_new().is_none());
+}
+
+#[test]
+fn lock_hold_time_with_a_large_value() {
+    // About 8 MB.
+    let map: HashMap<u64, u64> = (0..500_000).map(|i| (i, i)).collect();
+    let (tx, rx) = watch::channel(map.clone());
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let readers = (0..4)
+        .map(|_| {
+            let mut rx = rx.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut reads = 0u32;
+                while !stop.load(Ordering::Relaxed) {
+                    black_box(rx.get());
+                    reads += 1;
+                }
+                reads
+            })
+        })
+        .collect();
+
+    let clone_time = {
+        let start = Instant::now();
+        black_box(map.clone());
+        start.elapsed()
+    };
+    let mut longest = Duration::ZERO;
+    for _ in 0..20 {
+        let next = map.clone();
+        let start = Instant::now();
+        tx.send(next);
+        longest = longest.max(start.elapsed());
+    }
+    stop.store(true, Ordering::Relaxed);
+    let reads: u32 = join_all(readers).into_iter().sum();
+
+    eprintln!(
+        "one clone: {:?}, longest send: {:?}, reads: {}",
+        clone_time, longest, reads,
+    );
+}
@@ -0,0 +1,89 @@
+//! `WatchSender::closed`, polled by hand with a waker that counts its wakes.
+#![cfg(all(feature = "embedded-async", not(target_family = "wasm")))]
+
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll},
+    thread,
+};
+
+mod util;
+use util::task::{block_on, counting_waker};
+
+#[test]
+fn completes_at_once_without_receivers() {
+    let (tx, rx) = watch::channel(0);
+    drop(rx);
+    let (wakes, waker) = counting_waker();
+    let mut closed = pin!(tx.closed());
+    assert_eq!(
+        closed.as_mut().poll(&mut Context::from_waker(&waker)),
+        Poll::Ready(())
+    );
+    assert_eq!(wakes.count(), 0);
+}
+
+#[test]
+fn dropping_the_last_receiver_wakes_it() {
+    let (tx, rx) = watch::channel(0);
+    let other = rx.clone();
+    let (wakes, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut closed = pin!(tx.closed());
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+    drop(rx);
+    assert_eq!(wakes.count(), 0);
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+    drop(other);
+    assert_eq!(wakes.count(), 1);
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Ready(()));
+}
+
+#[test]
+fn a_new_receiver_keeps_it_pending() {
+    let (tx, rx) = watch::channel(0);
+    let (wakes, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut closed = pin!(tx.closed());
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+    let subscribed = tx.subscribe();
+    drop(rx);
+    assert_eq!(wakes.count(), 0);
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+    drop(subscribed);
+    assert_eq!(wakes.count(), 1);
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Ready(()));
+}
+
+#[test]
+fn dropping_the_future_unregisters_it() {
+    let (tx, rx) = watch::channel(0);
+    let (wakes, waker) = counting_waker();
+    {
+        let mut closed = pin!(tx.closed());
+        assert_eq!(
+            closed.as_mut().poll(&mut Context::from_waker(&waker)),
+            Poll::Pending
+        );
+    }
+    drop(rx);
+    assert_eq!(wakes.count(), 0);
+    block_on(tx.closed());
+}
+
+#[test]
+fn receivers_dropped_on_other_threads() {
+    let (tx, rx) = watch::channel(0);
+    let receivers: Vec<_> = (0..8).map(|_| rx.clone()).collect();
+    drop(rx);
+    let droppers: Vec<_> = receivers
+        .into_iter()
+        .map(|rx| thread::spawn(move || drop(rx)))
+        .collect();
+    block_on(tx.closed());
+    assert_eq!(tx.receiver_count(), 0);
+    for dropper in droppers {
+        dropper.join().unwrap();
+    }
+}
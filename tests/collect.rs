@@ -0,0 +1,100 @@
+#![cfg(all(
+    feature = "test-util",
+    target_has_atomic = "64",
+    not(target_family = "wasm")
+))]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+mod util;
+use util::eventually;
+
+#[test]
+fn the_unseen_value_is_the_first_delivery() {
+    let (_tx, mut rx) = watch::channel(0);
+    assert_eq!(rx.collect_updates(3, Duration::from_millis(20)), [0]);
+    assert!(rx.collect_updates(3, Duration::from_millis(5)).is_empty());
+}
+
+#[test]
+fn replaced_values_are_not_delivered() {
+    let (tx, mut rx) = watch::channel(0);
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.collect_updates(5, Duration::from_millis(20)), [2]);
+}
+
+#[test]
+fn asking_for_nothing_returns_at_once() {
+    let (_tx, mut rx) = watch::channel(0);
+    let start = Instant::now();
+    assert!(rx.collect_updates(0, Duration::from_secs(60)).is_empty());
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[test]
+fn values_are_collected_in_order() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    rx.get();
+    let sender = thread::spawn(move || {
+        for value in 1..=3 {
+            tx.send(value);
+            assert!(eventually(|| tx.max_lag() == Some(0)));
+        }
+    });
+    assert_eq!(rx.collect_updates(3, Duration::from_secs(60)), [1, 2, 3]);
+    sender.join().unwrap();
+}
+
+#[test]
+fn the_timeout_covers_every_wait() {
+    let (tx, mut rx) = watch::channel(0);
+    let stop = Arc::new(AtomicBool::new(false));
+    let sender = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            let mut value = 0;
+            while !stop.load(Ordering::Relaxed) {
+                value += 1;
+                tx.send(value);
+                thread::sleep(Duration::from_millis(1));
+            }
+        })
+    };
+    let start = Instant::now();
+    let values = rx.collect_for(Duration::from_millis(50));
+    let elapsed = start.elapsed();
+    stop.store(true, Ordering::Relaxed);
+    sender.join().unwrap();
+    assert!(elapsed >= Duration::from_millis(50), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+    assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[cfg(feature = "test-clock")]
+#[test]
+fn the_deadline_follows_the_clock_of_the_channel() {
+    let clock = watch::MockClock::new();
+    let (tx, mut rx) = watch::builder()
+        .clock(clock.clone())
+        .track_lag(true)
+        .channel(0);
+    rx.get();
+    let collector = thread::spawn(move || rx.collect_for(Duration::from_secs(10)));
+    for value in 1..=2 {
+        assert!(eventually(|| tx.waiting_receivers() == 1));
+        clock.advance(Duration::from_secs(4));
+        tx.send(value);
+        assert!(eventually(|| tx.max_lag() == Some(0)));
+    }
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(collector.join().unwrap(), [1, 2]);
+}
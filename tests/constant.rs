@@ -0,0 +1,102 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[cfg(not(target_family = "wasm"))]
+mod util;
+
+#[test]
+fn the_value_is_new_exactly_once() {
+    let mut rx = watch::constant(String::from("config"));
+    assert!(rx.is_closed());
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some("config".into()));
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), "config");
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[test]
+fn get_marks_the_value_seen() {
+    let mut rx = watch::constant(1);
+    assert_eq!(rx.get(), 1);
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[test]
+fn clones_are_constant_too() {
+    let mut rx = watch::constant(1);
+    rx.get();
+    let mut cloned = rx.clone();
+    assert!(cloned.is_closed());
+    assert_eq!(cloned.get_if_new(), None);
+    assert_eq!(cloned.get(), 1);
+}
+
+#[test]
+fn nothing_else_keeps_the_value() {
+    use std::sync::Arc;
+
+    let value = Arc::new(());
+    let rx = watch::constant(value.clone());
+    assert_eq!(Arc::strong_count(&value), 2);
+    drop(rx);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn recv_reports_the_closing_instead_of_blocking() {
+    use std::thread;
+    use util::join_all;
+    use watch::RecvError;
+
+    let mut rx = watch::constant(5);
+    // On a thread, so that a recv that blocks fails the test rather than
+    // hanging it.
+    let waiter = thread::spawn(move || (rx.recv(), rx.recv(), rx.recv()));
+    let results = join_all(vec![waiter]).remove(0);
+    assert_eq!(results, (Ok(5), Err(RecvError), Err(RecvError)));
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn recv_timeout_reports_the_closing_without_waiting() {
+    use std::time::{Duration, Instant};
+    use watch::RecvTimeoutError;
+
+    let mut rx = watch::constant(5);
+    let start = Instant::now();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(600)), Ok(5));
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(600)),
+        Err(RecvTimeoutError::Closed)
+    );
+    assert!(start.elapsed() < Duration::from_secs(60));
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn wait_returns_the_value_once() {
+    use std::time::Duration;
+
+    let mut rx = watch::constant(5);
+    assert_eq!(rx.wait(), 5);
+    // After that, it only returns once the timeout passes.
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+}
+
+#[cfg(all(feature = "embedded-async", not(target_family = "wasm")))]
+#[test]
+fn async_waits_report_the_closing() {
+    use util::task::block_on;
+    use watch::RecvError;
+
+    let mut rx = watch::constant(5);
+    assert_eq!(block_on(rx.changed()), Ok(5));
+    assert_eq!(block_on(rx.changed()), Err(RecvError));
+    assert_eq!(block_on(rx.wait_for(|value| *value == 5)), Ok(5));
+    assert_eq!(block_on(rx.wait_for(|value| *value == 6)), Err(RecvError));
+}
@@ -0,0 +1,98 @@
+//! The seqlock channel for `Copy` values.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{thread, time::Duration};
+use util::join_all;
+use watch::{CopyReceiver, CopySender, RecvError};
+
+#[test]
+fn semantics() {
+    let (tx, mut rx) = watch::copy_channel(1u32);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert_eq!(rx.get_if_new(), None);
+    assert!(!rx.has_changed());
+    let mut subscriber = tx.subscribe();
+    assert_eq!(subscriber.get_if_new(), None);
+
+    tx.update(|value| *value += 1);
+    assert_eq!(rx.get(), 2);
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(3);
+    });
+    assert_eq!(rx.wait(), 3);
+    sender.join().unwrap();
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(subscriber.recv_timeout(Duration::from_secs(5)), Ok(3));
+    assert!(rx.is_closed());
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+
+    // A new sender reopens the channel.
+    let tx = rx.new_sender();
+    tx.send(4);
+    assert_eq!(rx.recv(), Ok(4));
+}
+
+#[test]
+fn readers_never_see_a_torn_value() {
+    const WRITES: u64 = 100_000;
+    let (tx, rx) = watch::copy_channel([0u64; 16]);
+    let readers = (0..4)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while last < 2 * WRITES {
+                    let value = rx.get();
+                    assert!(value.iter().all(|&x| x == value[0]), "torn: {:?}", value);
+                    assert!(value[0] >= last);
+                    last = value[0];
+                }
+            })
+        })
+        .collect();
+    let writers = (0..2)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for _ in 0..WRITES {
+                    tx.update(|value| *value = [value[0] + 1; 16]);
+                }
+            })
+        })
+        .collect();
+    join_all(writers);
+    join_all(readers);
+}
+
+#[test]
+fn waiters_get_the_last_value() {
+    for _ in 0..50 {
+        let (tx, rx) = watch::copy_channel(0u64);
+        let waiters = (0..6)
+            .map(|_| {
+                let mut rx = rx.clone();
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while last < 3000 {
+                        last = rx.wait();
+                    }
+                })
+            })
+            .collect();
+        for i in 1..=3000 {
+            tx.send(i);
+        }
+        join_all(waiters);
+    }
+}
+
+#[test]
+fn handles_are_send_and_sync_for_send_values() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CopySender<[f32; 8]>>();
+    assert_send_sync::<CopyReceiver<[f32; 8]>>();
+}
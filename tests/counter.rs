@@ -0,0 +1,80 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+
+mod util;
+use util::join_all;
+
+#[test]
+fn the_counter_never_goes_down() {
+    let (tx, mut watcher) = watch::counter(5);
+    assert_eq!(tx.set(3), 5);
+    assert_eq!(tx.get(), 5);
+    assert_eq!(watcher.get(), 5);
+    assert_eq!(tx.set(8), 8);
+    assert_eq!(tx.add(u64::MAX), u64::MAX);
+    assert_eq!(tx.add(1), u64::MAX);
+    assert_eq!(watcher.get(), u64::MAX);
+}
+
+#[test]
+fn changes_that_do_not_raise_it_do_not_notify() {
+    let (tx, _watcher) = watch::counter(5);
+    let before = format!("{:?}", tx);
+    assert!(before.contains("version"), "{}", before);
+    tx.set(3);
+    tx.add(0);
+    assert_eq!(format!("{:?}", tx), before);
+    tx.add(1);
+    assert_ne!(format!("{:?}", tx), before);
+}
+
+#[test]
+fn threshold_waits() {
+    let (tx, mut watcher) = watch::counter(5);
+    assert_eq!(watcher.wait_gte(5), 5);
+    assert_eq!(watcher.wait_gte(0), 5);
+    assert_eq!(watcher.wait_gte_timeout(6, Duration::from_millis(5)), None);
+    tx.add(3);
+    assert_eq!(watcher.wait_gte_timeout(6, Duration::ZERO), Some(8));
+}
+
+#[test]
+fn wait_increment_waits_for_a_new_count() {
+    let (tx, mut watcher) = watch::counter(0);
+    let mut other = watcher.clone();
+    let waiter = thread::spawn(move || watcher.wait_increment());
+    tx.add(2);
+    assert_eq!(waiter.join().unwrap(), 2);
+    // Each clone keeps track of its own increments.
+    assert_eq!(other.wait_increment(), 2);
+    let mut subscribed = tx.subscribe();
+    tx.add(1);
+    assert_eq!(subscribed.wait_increment(), 3);
+}
+
+#[test]
+fn racing_adders() {
+    let (tx, watcher) = watch::counter(5);
+    let waiters = (0..4)
+        .map(|i| {
+            let mut watcher = watcher.clone();
+            thread::spawn(move || watcher.wait_gte(1000 * i))
+        })
+        .collect();
+    let adders = (0..4)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    tx.add(1);
+                }
+            })
+        })
+        .collect();
+    join_all(adders);
+    for (i, count) in join_all(waiters).into_iter().enumerate() {
+        assert!(count >= 1000 * i as u64, "{} for {}", count, i);
+    }
+    assert_eq!(tx.get(), 4005);
+}
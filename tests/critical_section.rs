@@ -0,0 +1,56 @@
+//! The `critical-section` backend, tested on the host with the std
+//! implementation of `critical-section`.
+#![cfg(all(not(feature = "std"), feature = "critical-section"))]
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+use watch::RecvError;
+
+#[test]
+fn wait_polls_until_a_send() {
+    let (tx, mut rx) = watch::channel(0);
+    assert_eq!(rx.get(), 0);
+    // The other thread stands in for an interrupt handler.
+    let waiter = thread::spawn(move || (rx.wait(), rx.recv()));
+    thread::sleep(Duration::from_millis(20));
+    tx.send(5);
+    drop(tx);
+    assert_eq!(waiter.join().unwrap(), (5, Err(RecvError)));
+}
+
+#[test]
+fn wait_with_calls_the_idle_hook() {
+    let (tx, mut rx) = watch::channel(0);
+    assert_eq!(rx.wait_with(|| panic!("nothing to wait for")), 0);
+
+    let waiter = thread::spawn(move || {
+        let mut idle = 0;
+        let value = rx.wait_with(|| {
+            idle += 1;
+            thread::yield_now();
+        });
+        (value, idle)
+    });
+    thread::sleep(Duration::from_millis(20));
+    tx.send(3);
+    let (value, idle) = waiter.join().unwrap();
+    assert_eq!(value, 3);
+    assert!(idle > 0);
+}
+
+#[test]
+fn locking_again_inside_the_lock_panics() {
+    let (tx, mut rx) = watch::channel(0);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|_| {
+            rx.get();
+        })
+    }));
+    assert!(result.is_err());
+    // The channel is still usable afterwards.
+    tx.send(1);
+    assert_eq!(rx.get(), 1);
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use watch::CursorError;
+
+#[test]
+fn a_cursor_moves_to_another_receiver() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let at_start = rx.cursor();
+    tx.send(1);
+
+    let mut other = tx.subscribe();
+    assert_eq!(other.get_if_new(), None);
+    other.set_cursor(at_start).unwrap();
+    assert!(other.has_changed());
+    assert_eq!(other.get_if_new(), Some(1));
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert_eq!(rx.cursor(), other.cursor());
+    assert_eq!(rx.cursor().version(), at_start.version() + 1);
+}
+
+#[test]
+fn a_cursor_can_move_a_receiver_back() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let saved = rx.cursor();
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.get_if_new(), Some(2));
+    assert_eq!(rx.get_if_new(), None);
+    rx.set_cursor(saved).unwrap();
+    assert_eq!(rx.get_if_new(), Some(2));
+
+    // And to where it already is.
+    let current = rx.cursor();
+    rx.set_cursor(current).unwrap();
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[test]
+fn clones_have_the_same_cursor() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send(1);
+    let cloned = rx.clone();
+    assert_eq!(cloned.cursor(), rx.cursor());
+}
+
+#[test]
+fn cursors_of_other_channels_are_rejected() {
+    let (_tx, mut rx) = watch::channel(0);
+    let (_other_tx, other) = watch::channel(0);
+    let cursor = other.cursor();
+    assert_eq!(rx.set_cursor(cursor), Err(CursorError::OtherChannel));
+    // The receiver is left as it was.
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(0));
+}
+
+#[test]
+fn a_channel_created_again_rejects_the_old_cursors() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let cursor = rx.cursor();
+    drop((tx, rx));
+    // The new channel may well reuse the memory of the old one.
+    for _ in 0..10 {
+        let (tx, mut rx) = watch::channel(0);
+        tx.send(1);
+        assert_eq!(rx.set_cursor(cursor), Err(CursorError::OtherChannel));
+    }
+}
+
+#[test]
+fn errors_are_displayed() {
+    assert_eq!(
+        CursorError::OtherChannel.to_string(),
+        "cursor is from another watch channel"
+    );
+    assert_eq!(
+        CursorError::Ahead.to_string(),
+        "cursor is ahead of the watch channel"
+    );
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn a_restored_cursor_makes_wait_return() {
+    use std::time::Duration;
+
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let saved = rx.cursor();
+    tx.send(1);
+    rx.get();
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+    rx.set_cursor(saved).unwrap();
+    assert_eq!(rx.wait(), 1);
+}
+
+// Saving cursors, forging them, and starting a channel near the end of the
+// versions from a snapshot all need serde.
+#[cfg(feature = "serde")]
+mod serde {
+    use super::*;
+    use watch::{Cursor, Snapshot};
+
+    #[test]
+    fn cursors_are_saved_and_loaded() {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let saved = serde_json::to_string(&rx.cursor()).unwrap();
+        tx.send(1);
+        rx.get();
+
+        let loaded: Cursor = serde_json::from_str(&saved).unwrap();
+        rx.set_cursor(loaded).unwrap();
+        assert_eq!(rx.get_if_new(), Some(1));
+    }
+
+    #[test]
+    fn versions_wrap_around() {
+        let (tx, mut rx) = watch::channel_from_snapshot(Snapshot {
+            value: 0,
+            version: u64::MAX - 1,
+        });
+        rx.get();
+        let before = rx.cursor();
+        assert_eq!(before.version(), u64::MAX - 1);
+        tx.send(1);
+        tx.send(2);
+        tx.send(3);
+
+        let mut other = tx.subscribe();
+        assert_eq!(other.cursor().version(), 1);
+        other.set_cursor(before).unwrap();
+        assert_eq!(other.get_if_new(), Some(3));
+        other.set_cursor(before).unwrap();
+        assert!(other.has_changed());
+    }
+
+    /// A cursor of the channel of `rx`, at `version`.
+    fn forged(rx: &watch::WatchReceiver<u32>, version: u64) -> Cursor {
+        let mut json: serde_json::Value = serde_json::to_value(rx.cursor()).unwrap();
+        json["version"] = version.into();
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn cursors_ahead_of_the_channel_are_rejected() {
+        let (tx, mut rx) = watch::channel(0u32);
+        let latest = tx.reader().version();
+        assert_eq!(
+            rx.set_cursor(forged(&rx, latest + 1)),
+            Err(CursorError::Ahead)
+        );
+        assert!(rx.has_changed());
+        tx.send(1);
+        rx.set_cursor(forged(&rx, latest + 1)).unwrap();
+        assert_eq!(rx.get_if_new(), None);
+    }
+
+    #[test]
+    fn cursors_ahead_across_the_wrap_are_rejected() {
+        let (_tx, mut rx) = watch::channel_from_snapshot(Snapshot {
+            value: 0u32,
+            version: u64::MAX,
+        });
+        rx.get();
+        assert_eq!(rx.set_cursor(forged(&rx, 0)), Err(CursorError::Ahead));
+        assert_eq!(rx.set_cursor(forged(&rx, 5)), Err(CursorError::Ahead));
+        // Far enough past the latest version counts as behind it.
+        rx.set_cursor(forged(&rx, u64::MAX / 2)).unwrap();
+        assert!(rx.has_changed());
+    }
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn sender_shape() {
+    let (tx, _rx) = watch::channel(vec![1u8]);
+    assert_eq!(
+        format!("{:?}", tx),
+        "WatchSender { id: SenderId(0), value: [1], version: 1, senders: 1, receivers: 1 }",
+    );
+    let other = tx.clone();
+    other.send(vec![2]);
+    assert_eq!(
+        format!("{:?}", other),
+        "WatchSender { id: SenderId(1), value: [2], version: 2, senders: 2, receivers: 1 }",
+    );
+}
+
+#[test]
+fn receiver_shape() {
+    let (tx, mut rx) = watch::channel(vec![1u8]);
+    assert_eq!(
+        format!("{:?}", rx),
+        "WatchReceiver { value: [1], version: 1, senders: 1, receivers: 1, \
+         last_seen_version: 0, has_changed: true }",
+    );
+    rx.get();
+    assert_eq!(
+        format!("{:?}", rx),
+        "WatchReceiver { value: [1], version: 1, senders: 1, receivers: 1, \
+         last_seen_version: 1, has_changed: false }",
+    );
+    drop(tx);
+    assert!(format!("{:?}", rx).contains("senders: 0"));
+}
+
+#[test]
+fn locked_parts_are_shown_as_locked() {
+    let (tx, mut rx) = watch::channel(vec![1u8]);
+    rx.get();
+    let mut formatted = String::new();
+    tx.update(|value| {
+        value.push(2);
+        formatted = format!("{:?}", rx);
+    });
+    assert_eq!(
+        formatted,
+        "WatchReceiver { value: <locked>, version: 1, senders: 1, receivers: 1, \
+         last_seen_version: 1, has_changed: false }",
+    );
+    assert!(format!("{:?}", tx).contains("value: [1, 2], version: 2"));
+}
+
+#[test]
+fn pretty_printing() {
+    let (_tx, rx) = watch::channel(3);
+    let pretty = format!("{:#?}", rx);
+    assert!(pretty.starts_with("WatchReceiver {\n    value: 3,\n"));
+    assert!(pretty.ends_with("    has_changed: true,\n}"));
+}
+
+/// A value that cannot be formatted.
+struct Opaque;
+
+#[test]
+fn handles_of_any_value_can_be_formatted_without_it() {
+    let (tx, mut rx) = watch::channel(Opaque);
+    assert_eq!(
+        format!("{:?}", tx.debug_without_value()),
+        "WatchSender { id: SenderId(0), version: 1, senders: 1, receivers: 1 }",
+    );
+    assert_eq!(
+        format!("{:?}", rx.debug_without_value()),
+        "WatchReceiver { version: 1, senders: 1, receivers: 1, \
+         last_seen_version: 0, has_changed: true }",
+    );
+    tx.send(Opaque);
+    rx.get_shared();
+    assert_eq!(
+        format!("{:?}", rx.debug_without_value()),
+        "WatchReceiver { version: 2, senders: 1, receivers: 1, \
+         last_seen_version: 2, has_changed: false }",
+    );
+}
+
+#[test]
+fn formatting_without_the_value_while_locked() {
+    let (tx, rx) = watch::channel(vec![1u8]);
+    let mut formatted = String::new();
+    tx.update(|_| formatted = format!("{:?}", rx.debug_without_value()));
+    assert_eq!(
+        formatted,
+        "WatchReceiver { version: 1, senders: 1, receivers: 1, \
+         last_seen_version: 0, has_changed: true }",
+    );
+}
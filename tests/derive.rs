@@ -0,0 +1,97 @@
+#![cfg(all(feature = "derive", not(target_family = "wasm")))]
+
+use watch::WatchFields;
+
+#[derive(WatchFields, Clone, PartialEq, Debug)]
+pub struct Tls {
+    pub cert: String,
+    pub port: u16,
+}
+
+#[derive(WatchFields)]
+pub struct Settings {
+    pub name: String,
+    pub retries: u32,
+    #[watch(skip)]
+    pub secret: Vec<u8>,
+    #[watch(nested)]
+    pub tls: Tls,
+}
+
+fn settings() -> Settings {
+    Settings {
+        name: "a".into(),
+        retries: 1,
+        secret: vec![1],
+        tls: Tls {
+            cert: "c".into(),
+            port: 1,
+        },
+    }
+}
+
+/// Mark every value seen, as the receivers start out without having seen
+/// them.
+fn see_all(channels: &mut SettingsChannels) {
+    channels.name.get();
+    channels.retries.get();
+    channels.tls.cert.get();
+    channels.tls.port.get();
+}
+
+#[test]
+fn every_field_gets_a_channel() {
+    let (_tx, mut channels) = settings().into_channels();
+    assert_eq!(channels.name.get(), "a");
+    assert_eq!(channels.retries.get(), 1);
+    assert_eq!(channels.tls.cert.get(), "c");
+    assert_eq!(channels.tls.port.get(), 1);
+}
+
+#[test]
+fn set_all_only_sends_the_changed_fields() {
+    let (tx, channels) = settings().into_channels();
+    let mut subscribed = tx.subscribe();
+    let mut channels = channels.clone();
+    see_all(&mut channels);
+    tx.set_all(Settings {
+        retries: 2,
+        secret: Vec::new(),
+        tls: Tls {
+            cert: "c".into(),
+            port: 443,
+        },
+        ..settings()
+    });
+    for channels in [&mut channels, &mut subscribed].iter_mut() {
+        assert!(!channels.name.has_changed());
+        assert_eq!(channels.retries.get_if_new(), Some(2));
+        assert!(!channels.tls.cert.has_changed());
+        assert_eq!(channels.tls.port.get_if_new(), Some(443));
+    }
+}
+
+#[test]
+fn setters_only_send_changes() {
+    let (tx, mut channels) = settings().into_channels();
+    see_all(&mut channels);
+    tx.set_retries(1);
+    assert!(!channels.retries.has_changed());
+    tx.set_name("b".into());
+    assert_eq!(channels.name.get_if_new().as_deref(), Some("b"));
+
+    tx.clone().set_tls(Tls {
+        cert: "d".into(),
+        port: 1,
+    });
+    assert_eq!(channels.tls.cert.get_if_new().as_deref(), Some("d"));
+    assert!(!channels.tls.port.has_changed());
+}
+
+#[test]
+fn dropping_the_senders_closes_every_field() {
+    let (tx, channels) = settings().into_channels();
+    drop(tx);
+    assert!(channels.name.is_closed());
+    assert!(channels.tls.port.is_closed());
+}
@@ -0,0 +1,69 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+
+mod util;
+use util::eventually;
+
+#[test]
+fn a_value_that_already_differs_is_returned_even_if_seen() {
+    let (tx, mut rx) = watch::channel(1);
+    tx.send(2);
+    assert_eq!(rx.get(), 2);
+    assert_eq!(
+        rx.wait_until_distinct_from(&1, Duration::from_millis(5)),
+        Some(2)
+    );
+    assert_eq!(
+        rx.wait_until_distinct_from(&1, Duration::from_millis(5)),
+        Some(2)
+    );
+}
+
+#[test]
+fn an_equal_value_times_out() {
+    let (tx, mut rx) = watch::channel(2);
+    assert_eq!(
+        rx.wait_until_distinct_from(&2, Duration::from_millis(5)),
+        None
+    );
+    tx.send(2);
+    assert_eq!(
+        rx.wait_until_distinct_from(&2, Duration::from_millis(5)),
+        None
+    );
+}
+
+#[test]
+fn equal_versions_are_slept_through() {
+    let (tx, mut rx) = watch::channel(2);
+    let sender = thread::spawn(move || {
+        for _ in 0..5 {
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            tx.send(2);
+        }
+        tx.send(3);
+    });
+    assert_eq!(
+        rx.wait_until_distinct_from(&2, Duration::from_secs(60)),
+        Some(3)
+    );
+    sender.join().unwrap();
+}
+
+#[test]
+fn values_may_borrow() {
+    let old = String::from("a");
+    let a = String::from("a");
+    let b = String::from("b");
+    let (tx, mut rx) = watch::channel(a.as_str());
+    assert_eq!(
+        rx.wait_until_distinct_from(&old.as_str(), Duration::from_millis(5)),
+        None
+    );
+    tx.send(&b);
+    assert_eq!(
+        rx.wait_until_distinct_from(&old.as_str(), Duration::ZERO),
+        Some("b")
+    );
+}
@@ -0,0 +1,89 @@
+//! `changed` on a minimal executor in the style of the ones used without std,
+//! which polls its future on a flag set by the waker and spins otherwise.
+#![cfg(feature = "embedded-async")]
+
+use core::{
+    future::Future,
+    pin::pin,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+use std::{thread, time::Duration};
+use watch::RecvError;
+
+/// Set by the waker, like the pending flag of an interrupt.
+static WOKEN: AtomicBool = AtomicBool::new(false);
+static WAKES: AtomicUsize = AtomicUsize::new(0);
+
+fn waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(core::ptr::null(), &VTABLE),
+        wake,
+        wake,
+        |_| {},
+    );
+    fn wake(_: *const ()) {
+        WAKES.fetch_add(1, Ordering::Relaxed);
+        WOKEN.store(true, Ordering::Release);
+    }
+    // SAFETY: The vtable does nothing with the data pointer.
+    unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) }
+}
+
+/// Run `future` to completion, polling it again only after a wake.
+fn run<F: Future>(future: F) -> F::Output {
+    let waker = waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        WOKEN.store(false, Ordering::Release);
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        while !WOKEN.load(Ordering::Acquire) {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+// The executor state is global, so everything runs in a single test.
+#[test]
+fn changed_on_a_minimal_executor() {
+    let (tx, mut rx) = watch::channel(0);
+    assert_eq!(run(rx.changed()), Ok(0));
+
+    let sender = thread::spawn(move || {
+        for i in 1..=3 {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(i);
+        }
+    });
+    let mut values = Vec::new();
+    let closed = run(async {
+        loop {
+            match rx.changed().await {
+                Ok(value) => values.push(value),
+                Err(error) => return error,
+            }
+        }
+    });
+    sender.join().unwrap();
+    assert_eq!(closed, RecvError);
+    assert_eq!(values.last(), Some(&3));
+    assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(WAKES.load(Ordering::Relaxed) >= values.len());
+
+    // A dropped future leaves nothing registered that a send would wake.
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    {
+        let waker = waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut changed = pin!(rx.changed());
+        assert!(changed.as_mut().poll(&mut cx).is_pending());
+    }
+    let before = WAKES.load(Ordering::Relaxed);
+    tx.send(1);
+    assert_eq!(WAKES.load(Ordering::Relaxed), before);
+    assert_eq!(run(rx.changed()), Ok(1));
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::sync::Arc;
+
+#[test]
+fn reads_in_a_frame_see_one_value() {
+    let (tx, rx) = watch::channel(0);
+    let mut rx = rx.into_epochal();
+    assert_eq!(*rx.get(), 0);
+    assert!(!rx.has_newer());
+    assert!(!rx.advance());
+
+    for frame in 1..=5 {
+        let first = *rx.get();
+        tx.send(frame * 10);
+        assert!(rx.has_newer());
+        let second = *rx.get();
+        tx.send(frame * 10 + 1);
+        assert_eq!(first, second);
+        assert_eq!(*rx.get(), first);
+
+        // The next frame sees the latest value, skipping the one before.
+        assert!(rx.advance());
+        assert_eq!(*rx.get(), frame * 10 + 1);
+        assert!(!rx.has_newer());
+        assert!(!rx.advance());
+    }
+}
+
+#[test]
+fn the_pin_is_shared_not_cloned() {
+    let (tx, rx) = watch::channel(vec![1, 2, 3]);
+    let rx = rx.into_epochal();
+    let a = rx.get_shared();
+    let b = rx.get_shared();
+    assert!(Arc::ptr_eq(&a, &b));
+    assert!(std::ptr::eq(rx.get(), &*a));
+    // A send does not touch the pinned value.
+    tx.send(vec![4]);
+    assert_eq!(*a, [1, 2, 3]);
+    assert_eq!(*rx.get(), [1, 2, 3]);
+}
+
+#[test]
+fn a_pin_outlives_the_sender() {
+    let (tx, rx) = watch::channel(1);
+    let mut rx = rx.into_epochal();
+    tx.send(2);
+    drop(tx);
+    assert_eq!(*rx.get(), 1);
+    assert!(rx.advance());
+    assert_eq!(*rx.get(), 2);
+    assert!(!rx.advance());
+}
+
+#[test]
+fn into_inner_has_seen_the_pin() {
+    let (tx, rx) = watch::channel(0);
+    let inner = rx.into_epochal().into_inner();
+    assert!(!inner.has_changed());
+    tx.send(1);
+    let rx = inner.into_epochal();
+    assert_eq!(*rx.get(), 1);
+    let mut inner = rx.into_inner();
+    assert_eq!(inner.get_if_new(), None);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn frames_stay_coherent_while_sending() {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+    };
+
+    let (tx, rx) = watch::channel(0u64);
+    let stop = Arc::new(AtomicBool::new(false));
+    let sender = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            let mut value = 0;
+            while !stop.load(Ordering::Relaxed) {
+                value += 1;
+                tx.send(value);
+            }
+        })
+    };
+
+    let mut rx = rx.into_epochal();
+    let mut last = 0;
+    let mut changed = 0;
+    while changed < 100 {
+        let pinned = *rx.get();
+        // Several systems read the value during the frame.
+        for _ in 0..10 {
+            assert_eq!(*rx.get(), pinned);
+        }
+        if rx.advance() {
+            assert!(*rx.get() > last);
+            changed += 1;
+        }
+        last = *rx.get();
+    }
+    stop.store(true, Ordering::Relaxed);
+    sender.join().unwrap();
+}
@@ -0,0 +1,117 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    thread,
+    time::Duration,
+};
+
+mod util;
+use util::{eventually, join_all};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations made by each thread.
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn listeners_start_without_a_notification() {
+    let (_tx, mut rx) = watch::event();
+    assert!(!rx.has_changed());
+    assert!(!rx.poll());
+    assert!(!rx.wait_timeout(Duration::from_millis(5)));
+}
+
+#[test]
+fn notifications_coalesce() {
+    let (tx, mut rx) = watch::event();
+    for _ in 0..5 {
+        tx.notify();
+    }
+    assert!(rx.has_changed());
+    // Five notifications wake a single wait.
+    rx.wait();
+    assert!(!rx.poll());
+    assert!(!rx.wait_timeout(Duration::from_millis(5)));
+
+    for _ in 0..3 {
+        tx.notify();
+    }
+    assert_eq!(rx.notified_count(), 3);
+    assert_eq!(rx.notified_count(), 0);
+    tx.notify();
+    assert!(rx.poll());
+    assert_eq!(rx.notified_count(), 0);
+}
+
+#[test]
+fn notifying_does_not_allocate() {
+    let (tx, mut rx) = watch::event();
+    let before = allocations();
+    for _ in 0..100 {
+        tx.notify();
+        assert!(rx.poll());
+    }
+    assert_eq!(allocations(), before);
+}
+
+#[test]
+fn notifications_fan_out() {
+    let (tx, rx) = watch::event();
+    let listeners = (0..4)
+        .map(|_| {
+            let mut rx = tx.subscribe();
+            thread::spawn(move || {
+                rx.wait();
+                rx
+            })
+        })
+        .collect();
+    assert!(eventually(|| tx.waiting_listeners() == 4));
+    tx.notify();
+    for mut rx in join_all(listeners) {
+        assert!(!rx.poll());
+    }
+
+    // Clones are told about the same notifications as the original.
+    let mut rx = rx;
+    let mut clone = rx.clone();
+    let mut late = tx.subscribe();
+    tx.notify();
+    assert!(rx.poll());
+    assert!(clone.poll());
+    assert!(late.poll());
+}
+
+#[test]
+fn dropping_the_sender_closes() {
+    let (tx, mut rx) = watch::event();
+    let mut waiting = rx.clone();
+    let waiter = thread::spawn(move || waiting.recv());
+    assert!(eventually(|| tx.waiting_listeners() == 1));
+    drop(tx);
+    assert!(waiter.join().unwrap().is_err());
+    assert!(rx.is_closed());
+    assert!(rx.recv().is_err());
+}
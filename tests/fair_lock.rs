@@ -0,0 +1,122 @@
+#![cfg(not(target_family = "wasm"))]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+mod util;
+use util::join_all;
+
+/// Run one sender that sends in a tight loop against 4 readers for `window`,
+/// and return how many reads each reader got done.
+fn reads_against_a_hot_sender(fair: bool, window: Duration) -> Vec<u64> {
+    let (tx, rx) = watch::builder().fair_lock(fair).channel(0u64);
+    let stop = Arc::new(AtomicBool::new(false));
+    let readers = (0..4)
+        .map(|_| {
+            let mut rx = rx.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut reads = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    rx.get();
+                    reads += 1;
+                }
+                reads
+            })
+        })
+        .collect();
+    let sender = thread::spawn({
+        let stop = stop.clone();
+        move || {
+            let mut value = 0;
+            while !stop.load(Ordering::Relaxed) {
+                value += 1;
+                tx.send(value);
+            }
+        }
+    });
+    thread::sleep(window);
+    stop.store(true, Ordering::Relaxed);
+    sender.join().unwrap();
+    join_all(readers)
+}
+
+#[test]
+fn fair_lock_is_accepted_by_every_backend() {
+    let (tx, mut rx) = watch::builder().fair_lock(true).channel(0);
+    tx.send(1);
+    tx.update(|value| *value += 1);
+    assert_eq!(rx.get_if_new(), Some(2));
+}
+
+#[cfg(feature = "parking_lot")]
+#[test]
+fn readers_make_progress_against_a_hot_sender() {
+    use std::time::Instant;
+
+    let (tx, rx) = watch::builder().fair_lock(true).channel(0u64);
+    let stop = Arc::new(AtomicBool::new(false));
+    let sender = thread::spawn({
+        let stop = stop.clone();
+        move || {
+            let mut value = 0;
+            while !stop.load(Ordering::Relaxed) {
+                value += 1;
+                tx.send(value);
+            }
+        }
+    });
+
+    // Every reader must get its reads done well within the bound, however
+    // fast the sender is.
+    let bound = Duration::from_secs(10);
+    let readers = (0..4)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let start = Instant::now();
+                let mut last = 0;
+                for _ in 0..10_000 {
+                    let value = rx.get();
+                    assert!(value >= last);
+                    last = value;
+                }
+                start.elapsed()
+            })
+        })
+        .collect();
+    let times = join_all(readers);
+    stop.store(true, Ordering::Relaxed);
+    sender.join().unwrap();
+    for time in times {
+        assert!(time < bound, "a reader took {:?}", time);
+    }
+}
+
+#[cfg(feature = "parking_lot")]
+#[test]
+fn reads_with_and_without_fair_lock() {
+    let window = Duration::from_millis(200);
+    let unfair = reads_against_a_hot_sender(false, window);
+    let fair = reads_against_a_hot_sender(true, window);
+    assert!(fair.iter().all(|&reads| reads > 0));
+    eprintln!(
+        "reads in {:?}: unfair {:?}, fair {:?}",
+        window, unfair, fair
+    );
+}
+
+#[cfg(not(feature = "parking_lot"))]
+#[test]
+fn fair_lock_is_ignored_elsewhere() {
+    let window = Duration::from_millis(50);
+    assert!(reads_against_a_hot_sender(true, window)
+        .iter()
+        .all(|&reads| reads > 0));
+}
@@ -0,0 +1,54 @@
+//! Run with `cargo test --release --test false_sharing -- --ignored
+//! --nocapture`.
+#![cfg(not(target_family = "wasm"))]
+
+use std::{
+    hint::black_box,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+mod util;
+use util::join_all;
+
+#[test]
+#[ignore = "benchmark"]
+fn polling_throughput_against_a_sender() {
+    let window = Duration::from_millis(500);
+    let (tx, rx) = watch::channel([0u64; 4]);
+    let stop = Arc::new(AtomicBool::new(false));
+    let pollers = (0..3)
+        .map(|_| {
+            let rx = rx.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut polls = 0u64;
+                while !stop.load(Ordering::Relaxed) {
+                    black_box(rx.has_changed());
+                    polls += 1;
+                }
+                polls
+            })
+        })
+        .collect();
+    let sender = thread::spawn({
+        let stop = stop.clone();
+        move || {
+            let mut sends = 0u64;
+            while !stop.load(Ordering::Relaxed) {
+                sends += 1;
+                tx.send([sends; 4]);
+            }
+            sends
+        }
+    });
+    thread::sleep(window);
+    stop.store(true, Ordering::Relaxed);
+    let sends = sender.join().unwrap();
+    let polls: u64 = join_all(pollers).into_iter().sum();
+    eprintln!("in {:?}: {} polls, {} sends", window, polls, sends);
+}
@@ -0,0 +1,242 @@
+//! Drives the C interface the way a C caller would, through raw pointers
+//! and status codes only.
+#![cfg(all(feature = "ffi", not(target_family = "wasm")))]
+
+use std::{ptr, thread, time::Duration};
+use watch::ffi::*;
+
+unsafe fn new_channel(value: &[u8]) -> (*mut Sender, *mut Receiver) {
+    let mut sender = ptr::null_mut();
+    let mut receiver = ptr::null_mut();
+    let status = watch_channel_new(value.as_ptr(), value.len(), &mut sender, &mut receiver);
+    assert_eq!(status, WATCH_OK);
+    assert!(!sender.is_null() && !receiver.is_null());
+    (sender, receiver)
+}
+
+#[test]
+fn send_and_receive() {
+    unsafe {
+        let (mut sender, mut receiver) = new_channel(b"hi");
+        let mut buf = [0u8; 8];
+        let mut len = buf.len();
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len),
+            WATCH_OK
+        );
+        assert_eq!(&buf[..len], b"hi");
+        len = buf.len();
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len),
+            WATCH_EMPTY
+        );
+
+        assert_eq!(watch_sender_send(sender, b"abc".as_ptr(), 3), WATCH_OK);
+        len = buf.len();
+        assert_eq!(
+            watch_receiver_wait_timeout_ms(receiver, 1000, buf.as_mut_ptr(), &mut len),
+            WATCH_OK
+        );
+        assert_eq!(&buf[..len], b"abc");
+
+        assert_eq!(watch_sender_free(&mut sender), WATCH_OK);
+        assert_eq!(watch_receiver_free(&mut receiver), WATCH_OK);
+    }
+}
+
+#[test]
+fn empty_values() {
+    unsafe {
+        let (mut sender, mut receiver) = new_channel(b"");
+        let mut len = 0;
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, ptr::null_mut(), &mut len),
+            WATCH_OK
+        );
+        assert_eq!(len, 0);
+        assert_eq!(watch_sender_send(sender, ptr::null(), 0), WATCH_OK);
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, ptr::null_mut(), &mut len),
+            WATCH_OK
+        );
+        watch_sender_free(&mut sender);
+        watch_receiver_free(&mut receiver);
+    }
+}
+
+#[test]
+fn small_buffers_leave_the_value_unseen() {
+    unsafe {
+        let (mut sender, mut receiver) = new_channel(b"hello");
+        let mut buf = [0u8; 8];
+        let mut len = 2;
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len),
+            WATCH_ERR_BUFFER_TOO_SMALL
+        );
+        assert_eq!(len, 5);
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len),
+            WATCH_OK
+        );
+        assert_eq!(&buf[..len], b"hello");
+        watch_sender_free(&mut sender);
+        watch_receiver_free(&mut receiver);
+    }
+}
+
+#[test]
+fn timeouts_and_closing() {
+    unsafe {
+        let (mut sender, mut receiver) = new_channel(b"x");
+        let mut buf = [0u8; 8];
+        let mut len = buf.len();
+        watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len);
+        len = buf.len();
+        assert_eq!(
+            watch_receiver_wait_timeout_ms(receiver, 10, buf.as_mut_ptr(), &mut len),
+            WATCH_EMPTY
+        );
+        assert_eq!(watch_sender_free(&mut sender), WATCH_OK);
+        assert_eq!(
+            watch_receiver_wait_timeout_ms(receiver, 1000, buf.as_mut_ptr(), &mut len),
+            WATCH_CLOSED
+        );
+        watch_receiver_free(&mut receiver);
+    }
+}
+
+#[test]
+fn waiting_for_another_thread() {
+    struct SendPtr(*mut Sender);
+    unsafe impl Send for SendPtr {}
+
+    unsafe {
+        let (sender, mut receiver) = new_channel(b"x");
+        let mut buf = [0u8; 8];
+        let mut len = buf.len();
+        watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len);
+
+        let handle = SendPtr(sender);
+        let producer = thread::spawn(move || {
+            let mut handle = handle;
+            thread::sleep(Duration::from_millis(20));
+            assert_eq!(watch_sender_send(handle.0, b"late".as_ptr(), 4), WATCH_OK);
+            assert_eq!(watch_sender_free(&mut handle.0), WATCH_OK);
+        });
+        len = buf.len();
+        assert_eq!(
+            watch_receiver_wait_timeout_ms(receiver, 5000, buf.as_mut_ptr(), &mut len),
+            WATCH_OK
+        );
+        assert_eq!(&buf[..len], b"late");
+        producer.join().unwrap();
+        watch_receiver_free(&mut receiver);
+    }
+}
+
+#[test]
+fn clones_are_separate_handles() {
+    unsafe {
+        let (mut sender, mut receiver) = new_channel(b"a");
+        let mut sender2 = ptr::null_mut();
+        let mut receiver2 = ptr::null_mut();
+        assert_eq!(watch_sender_clone(sender, &mut sender2), WATCH_OK);
+        assert_eq!(watch_receiver_clone(receiver, &mut receiver2), WATCH_OK);
+
+        // The channel stays open until both senders are gone.
+        watch_sender_free(&mut sender);
+        assert_eq!(watch_sender_send(sender2, b"b".as_ptr(), 1), WATCH_OK);
+        let mut buf = [0u8; 8];
+        for receiver in [receiver, receiver2] {
+            let mut len = buf.len();
+            assert_eq!(
+                watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len),
+                WATCH_OK
+            );
+            assert_eq!(&buf[..len], b"b");
+        }
+        watch_sender_free(&mut sender2);
+        let mut len = buf.len();
+        assert_eq!(
+            watch_receiver_wait_timeout_ms(receiver2, 1000, buf.as_mut_ptr(), &mut len),
+            WATCH_CLOSED
+        );
+        watch_receiver_free(&mut receiver);
+        watch_receiver_free(&mut receiver2);
+    }
+}
+
+#[test]
+fn null_pointers_are_rejected() {
+    unsafe {
+        let mut sender = ptr::null_mut();
+        let mut receiver = ptr::null_mut();
+        assert_eq!(
+            watch_channel_new(ptr::null(), 1, &mut sender, &mut receiver),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(
+            watch_channel_new(b"a".as_ptr(), 1, ptr::null_mut(), &mut receiver),
+            WATCH_ERR_NULL
+        );
+        assert!(sender.is_null() && receiver.is_null());
+
+        let (mut sender, mut receiver) = new_channel(b"a");
+        let mut buf = [0u8; 8];
+        let mut len = buf.len();
+        assert_eq!(
+            watch_sender_send(ptr::null(), b"a".as_ptr(), 1),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(watch_sender_send(sender, ptr::null(), 1), WATCH_ERR_NULL);
+        assert_eq!(
+            watch_sender_clone(ptr::null(), &mut ptr::null_mut()),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(watch_sender_clone(sender, ptr::null_mut()), WATCH_ERR_NULL);
+        assert_eq!(
+            watch_receiver_get_if_new(ptr::null_mut(), buf.as_mut_ptr(), &mut len),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), ptr::null_mut()),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, ptr::null_mut(), &mut len),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(
+            watch_receiver_wait_timeout_ms(receiver, 0, ptr::null_mut(), &mut len),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(
+            watch_receiver_clone(ptr::null(), &mut ptr::null_mut()),
+            WATCH_ERR_NULL
+        );
+        assert_eq!(watch_sender_free(ptr::null_mut()), WATCH_ERR_NULL);
+        assert_eq!(watch_receiver_free(ptr::null_mut()), WATCH_ERR_NULL);
+
+        // None of the rejected calls consumed the value.
+        assert_eq!(
+            watch_receiver_get_if_new(receiver, buf.as_mut_ptr(), &mut len),
+            WATCH_OK
+        );
+        watch_sender_free(&mut sender);
+        watch_receiver_free(&mut receiver);
+    }
+}
+
+#[test]
+fn freeing_twice_through_the_same_variable_is_harmless() {
+    unsafe {
+        let (mut sender, mut receiver) = new_channel(b"a");
+        assert_eq!(watch_sender_free(&mut sender), WATCH_OK);
+        assert!(sender.is_null());
+        assert_eq!(watch_sender_free(&mut sender), WATCH_OK);
+        assert_eq!(watch_receiver_free(&mut receiver), WATCH_OK);
+        assert!(receiver.is_null());
+        assert_eq!(watch_receiver_free(&mut receiver), WATCH_OK);
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{sync::mpsc, thread, time::Duration};
+
+mod util;
+use util::join_all;
+
+#[test]
+fn waits_return_at_once_when_the_level_matches() {
+    let (setter, mut watcher) = watch::flag(true);
+    assert!(watcher.is_set());
+    watcher.wait_true();
+    watcher.wait_true();
+    assert!(watcher.wait_true_timeout(Duration::ZERO));
+    assert!(!watcher.wait_false_timeout(Duration::from_millis(5)));
+
+    setter.set(false);
+    watcher.wait_false();
+    watcher.wait_false();
+    assert!(!watcher.is_set());
+    assert!(!watcher.wait_true_timeout(Duration::from_millis(5)));
+}
+
+#[test]
+fn new_watchers_see_the_level() {
+    let (setter, _watcher) = watch::flag(false);
+    setter.set(true);
+    let mut watcher = setter.subscribe();
+    assert!(watcher.is_set());
+    assert!(watcher.wait_true_timeout(Duration::ZERO));
+}
+
+#[test]
+fn setting_the_same_value_does_not_notify() {
+    let (setter, _watcher) = watch::flag(true);
+    let before = format!("{:?}", setter);
+    assert!(before.contains("version"), "{}", before);
+    setter.set(true);
+    assert_eq!(format!("{:?}", setter), before);
+    setter.set(false);
+    assert_ne!(format!("{:?}", setter), before);
+}
+
+#[test]
+fn transitions_wake_waiters() {
+    let (setter, watcher) = watch::flag(false);
+    let (woke_tx, woke) = mpsc::channel();
+    let waiters = (0..4)
+        .map(|_| {
+            let mut watcher = watcher.clone();
+            let woke = woke_tx.clone();
+            thread::spawn(move || {
+                watcher.wait_true();
+                woke.send(()).unwrap();
+                watcher.wait_false();
+            })
+        })
+        .collect();
+    let mut timed = watcher.clone();
+    let timed = thread::spawn(move || timed.wait_true_timeout(Duration::from_secs(60)));
+
+    setter.set(true);
+    assert!(timed.join().unwrap());
+    // A flag that is cleared again before a watcher wakes up may be missed,
+    // so only clear it once every waiter has seen it set.
+    for _ in 0..4 {
+        woke.recv().unwrap();
+    }
+    setter.set(false);
+    join_all(waiters);
+    assert!(!setter.is_set());
+}
+
+#[test]
+fn dropping_the_setters_closes() {
+    let (setter, watcher) = watch::flag(false);
+    let other = setter.clone();
+    drop(setter);
+    assert!(!watcher.is_closed());
+    drop(other);
+    assert!(watcher.is_closed());
+}
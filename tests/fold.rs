@@ -0,0 +1,126 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+use watch::{RecvError, RecvTimeoutError};
+
+mod util;
+use util::join_all;
+
+fn counter() -> (watch::FoldSender<u64, u64>, watch::FoldReceiver<u64>) {
+    watch::folding_channel(0u64, |total: &mut u64, delta: u64| *total += delta)
+}
+
+#[test]
+fn get_peeks_and_take_resets() {
+    let (tx, mut rx) =
+        watch::folding_channel(Vec::new(), |log: &mut Vec<u8>, delta| log.push(delta));
+    assert!(!rx.has_changed());
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.get(), [1, 2]);
+    assert!(rx.has_changed());
+    assert_eq!(rx.take(), [1, 2]);
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get(), Vec::<u8>::new());
+
+    tx.send(3);
+    assert_eq!(rx.take_with(|| vec![9]), [3]);
+    assert_eq!(rx.get(), [9]);
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn waiting_for_a_delta() {
+    let (tx, mut rx) = counter();
+    assert_eq!(
+        rx.wait_take_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    tx.send(5);
+    // A delta that is there already is taken at once.
+    assert_eq!(rx.wait_take(), Ok(5));
+
+    let waiter = thread::spawn(move || (rx.wait_take(), rx));
+    tx.send(2);
+    let (taken, mut rx) = waiter.join().unwrap();
+    // The waiter may have woken before or after a second delta.
+    assert_eq!(taken.unwrap() + rx.take(), 2);
+}
+
+#[test]
+fn closing() {
+    let (tx, mut rx) = counter();
+    tx.send(1);
+    drop(tx);
+    assert!(rx.is_closed());
+    // What was folded before the senders left can still be taken.
+    assert_eq!(rx.wait_take(), Ok(1));
+    assert_eq!(rx.wait_take(), Err(RecvError));
+    assert_eq!(
+        rx.wait_take_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Closed)
+    );
+
+    let (tx, rx) = counter();
+    let other = tx.clone();
+    assert!(!tx.is_closed());
+    drop(rx);
+    assert!(tx.is_closed());
+    assert!(other.is_closed());
+    // Sending to nobody is fine.
+    tx.send(1);
+}
+
+#[test]
+fn closing_wakes_a_waiter() {
+    let (tx, mut rx) = counter();
+    let waiter = thread::spawn(move || rx.wait_take());
+    drop(tx);
+    assert_eq!(waiter.join().unwrap(), Err(RecvError));
+}
+
+#[test]
+fn concurrent_senders_lose_nothing() {
+    let (tx, mut rx) = counter();
+    let senders = (0..8)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for _ in 0..10_000 {
+                    tx.send(1);
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let mut total = 0;
+    let mut takes = 0;
+    while let Ok(taken) = rx.wait_take() {
+        assert!(taken > 0);
+        total += taken;
+        takes += 1;
+    }
+    join_all(senders);
+    assert_eq!(total, 80_000);
+    assert!(takes >= 1);
+    assert_eq!(rx.take(), 0);
+}
+
+#[test]
+fn takes_while_sending_lose_nothing() {
+    let (tx, mut rx) = counter();
+    let sender = thread::spawn(move || {
+        for delta in 1..=20_000 {
+            tx.send(delta);
+        }
+    });
+    let mut total = 0;
+    while !sender.is_finished() {
+        total += rx.take();
+    }
+    sender.join().unwrap();
+    total += rx.take();
+    assert_eq!(total, 20_000 * 20_001 / 2);
+    assert!(!rx.has_changed());
+}
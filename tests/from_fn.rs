@@ -0,0 +1,120 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use watch::RecvError;
+
+mod util;
+use util::eventually;
+
+/// A closure that counts its calls, and the count.
+fn counter() -> (impl FnMut() -> u32 + Send + 'static, Arc<AtomicU32>) {
+    let calls = Arc::new(AtomicU32::new(0));
+    let counted = calls.clone();
+    (move || counted.fetch_add(1, Ordering::SeqCst), calls)
+}
+
+#[test]
+fn the_first_value_is_there_right_away() {
+    let (f, calls) = counter();
+    let (mut rx, _handle) = watch::from_fn(Duration::from_secs(3600), f);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(rx.get(), 0);
+}
+
+#[test]
+fn values_are_published_at_the_interval() {
+    let (f, _calls) = counter();
+    let (mut rx, handle) = watch::from_fn(Duration::from_millis(5), f);
+    assert_eq!(rx.get(), 0);
+    let first = rx.wait();
+    assert!(first >= 1);
+    assert!(rx.wait() > first);
+    assert!(!handle.is_finished());
+    handle.stop();
+}
+
+#[test]
+fn stopping_closes_the_channel_and_the_thread() {
+    let (f, calls) = counter();
+    let (mut rx, handle) = watch::from_fn(Duration::from_millis(5), f);
+    rx.wait();
+    handle.stop();
+    assert!(rx.is_closed());
+    let count = calls.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(calls.load(Ordering::SeqCst), count);
+    rx.get();
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn stopping_does_not_wait_for_the_interval() {
+    let (mut rx, handle) = watch::from_fn(Duration::from_secs(3600), || 1);
+    // The receiver is woken by the channel closing.
+    let waiter = thread::spawn(move || rx.recv());
+    handle.stop();
+    assert_eq!(waiter.join().unwrap(), Ok(1));
+}
+
+#[test]
+fn dropping_the_handle_stops_the_thread() {
+    let (mut rx, handle) = watch::from_fn(Duration::from_secs(3600), || 1);
+    drop(handle);
+    rx.get();
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn the_thread_exits_once_every_receiver_is_gone() {
+    let (rx, handle) = watch::from_fn(Duration::from_millis(5), || 1);
+    drop(rx);
+    assert!(eventually(|| handle.is_finished()));
+}
+
+#[test]
+fn the_poller_does_not_count_as_a_waiting_receiver() {
+    let (mut rx, handle) = watch::from_fn(Duration::from_secs(3600), || 1);
+    let sender = rx.new_sender();
+    rx.get();
+    // Give the poller time to go to sleep.
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(sender.waiting_receivers(), 0);
+    let waiter = thread::spawn(move || rx.wait());
+    assert!(eventually(|| sender.waiting_receivers() == 1));
+    sender.send(2);
+    assert_eq!(waiter.join().unwrap(), 2);
+    handle.stop();
+}
+
+#[test]
+fn distinct_only_sends_changes() {
+    let (mut f, _calls) = counter();
+    // Ten calls in a row give each value.
+    let (mut rx, handle) = watch::from_fn_distinct(Duration::from_millis(1), move || f() / 10);
+    let (_, first) = rx.get_versioned();
+    while rx.wait() < 3 {}
+    let (value, version) = rx.get_versioned();
+    assert_eq!(version - first, u64::from(value));
+    handle.stop();
+}
+
+#[test]
+#[should_panic(expected = "sampling failed")]
+fn a_panic_in_the_closure_reaches_stop() {
+    let (mut f, calls) = counter();
+    let (_rx, handle) = watch::from_fn(Duration::from_millis(1), move || {
+        if f() == 1 {
+            panic!("sampling failed");
+        }
+        0
+    });
+    assert!(eventually(|| calls.load(Ordering::SeqCst) == 2));
+    handle.stop();
+}
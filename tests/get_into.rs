@@ -0,0 +1,107 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::cell::Cell;
+
+/// A buffer that counts how often it had to allocate.
+#[derive(Debug, PartialEq)]
+struct Tracked {
+    samples: Vec<u32>,
+}
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+impl Tracked {
+    fn new(samples: Vec<u32>) -> Self {
+        Tracked { samples }
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        Tracked {
+            samples: self.samples.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        if self.samples.capacity() < source.samples.len() {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        }
+        self.samples.clone_from(&source.samples);
+    }
+}
+
+#[test]
+fn get_into_reuses_the_buffer() {
+    let (tx, mut rx) = watch::channel(Tracked::new(vec![1; 1000]));
+    let mut buffer = Tracked::new(Vec::with_capacity(4096));
+    let address = buffer.samples.as_ptr();
+    let before = allocations();
+
+    rx.get_into(&mut buffer);
+    assert_eq!(buffer.samples, vec![1; 1000]);
+    tx.send(Tracked::new(vec![2; 4000]));
+    rx.get_into(&mut buffer);
+    assert_eq!(buffer.samples, vec![2; 4000]);
+
+    assert_eq!(allocations(), before);
+    assert_eq!(buffer.samples.as_ptr(), address);
+}
+
+#[test]
+fn get_into_grows_a_small_buffer() {
+    let (_tx, mut rx) = watch::channel(Tracked::new(vec![1; 1000]));
+    let mut buffer = Tracked::new(Vec::new());
+    let before = allocations();
+    rx.get_into(&mut buffer);
+    assert_eq!(buffer.samples.len(), 1000);
+    assert_eq!(allocations(), before + 1);
+}
+
+#[test]
+fn get_if_new_into_leaves_the_buffer_alone_without_a_new_value() {
+    let (tx, mut rx) = watch::channel(Tracked::new(vec![1; 10]));
+    let mut buffer = Tracked::new(vec![0; 10]);
+    assert!(rx.get_if_new_into(&mut buffer));
+    assert_eq!(buffer.samples, vec![1; 10]);
+
+    buffer.samples[0] = 7;
+    assert!(!rx.get_if_new_into(&mut buffer));
+    assert_eq!(buffer.samples[0], 7);
+
+    tx.send(Tracked::new(vec![2; 10]));
+    let before = allocations();
+    assert!(rx.get_if_new_into(&mut buffer));
+    assert_eq!(buffer.samples, vec![2; 10]);
+    assert_eq!(allocations(), before);
+    assert!(!rx.has_changed());
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn wait_into_reuses_the_buffer() {
+    let (tx, mut rx) = watch::channel(vec![1u32; 10]);
+    let mut buffer = Vec::with_capacity(100);
+    let address = buffer.as_ptr();
+    rx.get_into(&mut buffer);
+
+    let sender = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        tx.send(vec![3; 50]);
+    });
+    rx.wait_into(&mut buffer);
+    assert_eq!(buffer, vec![3; 50]);
+    assert_eq!(buffer.as_ptr(), address);
+    assert!(!rx.has_changed());
+    sender.join().unwrap();
+}
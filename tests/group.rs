@@ -0,0 +1,140 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+use watch::{Group, RecvError, RecvTimeoutError};
+
+mod util;
+use util::{eventually, join_all};
+
+const SHORT: Duration = Duration::from_millis(20);
+const LONG: Duration = Duration::from_secs(10);
+
+#[test]
+fn closing_a_channel_wakes_its_waiters() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(1);
+    let mut timed = rx.clone();
+    let blocking = thread::spawn(move || rx.recv());
+    let timed = thread::spawn(move || timed.recv_timeout(LONG));
+    assert!(eventually(|| tx.waiting_receivers() == 2));
+    tx.close();
+    assert_eq!(join_all(vec![blocking]), [Err(RecvError)]);
+    assert_eq!(join_all(vec![timed]), [Err(RecvTimeoutError::Closed)]);
+}
+
+#[test]
+fn a_closed_channel_still_stores_values() {
+    let (tx, mut rx) = watch::channel(1);
+    rx.get();
+    tx.close();
+    assert!(rx.is_closed());
+    tx.send(2);
+    // The value is received once, and then the channel is closed.
+    assert_eq!(rx.recv(), Ok(2));
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(rx.get(), 2);
+}
+
+#[test]
+fn a_closed_channel_stays_closed() {
+    let (tx, rx) = watch::channel(0);
+    tx.close();
+    let other = tx.clone();
+    drop(tx);
+    assert!(rx.is_closed());
+    let mut late = other.subscribe();
+    late.get();
+    assert_eq!(late.recv(), Err(RecvError));
+}
+
+#[test]
+fn close_all_closes_every_channel() {
+    let group = Group::new();
+    let (a_tx, mut a) = group.channel(0);
+    let (_b_tx, mut b) = group.channel("b");
+    a.get();
+    b.get();
+    let waiter = thread::spawn(move || a.recv());
+    assert!(eventually(|| a_tx.waiting_receivers() == 1));
+    group.close_all();
+    assert_eq!(join_all(vec![waiter]), [Err(RecvError)]);
+    assert!(b.is_closed());
+    assert_eq!(b.recv(), Err(RecvError));
+}
+
+#[test]
+fn partial_teardown() {
+    let group = Group::new();
+    let mut consumers = Vec::new();
+    let mut senders = Vec::new();
+    for value in 0..20 {
+        let (tx, mut rx) = group.channel(value);
+        // Some channels are gone before the teardown.
+        if value % 3 == 0 {
+            continue;
+        }
+        rx.get();
+        consumers.push(thread::spawn(move || while rx.recv().is_ok() {}));
+        senders.push(tx);
+    }
+    // Others only lose their receivers.
+    let (kept, rx) = group.channel(20);
+    drop(rx);
+
+    assert!(!group.wait_all_closed(SHORT));
+    group.close_all();
+    assert!(group.wait_all_closed(LONG));
+    join_all(consumers);
+    assert!(kept.is_closed());
+    drop(senders);
+}
+
+#[test]
+fn waiting_for_receivers_that_are_still_alive() {
+    let group = Group::new();
+    let (_tx, rx) = group.channel(0);
+    group.close_all();
+    // Closing does not drop the receivers.
+    assert!(!group.wait_all_closed(SHORT));
+    let dropper = thread::spawn(move || {
+        thread::sleep(SHORT);
+        drop(rx);
+    });
+    assert!(group.wait_all_closed(LONG));
+    dropper.join().unwrap();
+}
+
+#[test]
+fn channels_created_after_close_all_start_closed() {
+    let group = Group::new();
+    group.close_all();
+    let (tx, mut rx) = group.channel(5);
+    assert!(rx.is_closed());
+    assert_eq!(rx.recv(), Ok(5));
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert!(!group.wait_all_closed(SHORT));
+    drop(rx);
+    assert!(group.wait_all_closed(SHORT));
+    drop(tx);
+}
+
+#[test]
+fn the_group_does_not_keep_its_channels_alive() {
+    use std::sync::Arc;
+
+    let group = Group::new();
+    let value = Arc::new(());
+    for _ in 0..100 {
+        drop(group.channel(value.clone()));
+    }
+    assert_eq!(Arc::strong_count(&value), 1);
+    assert!(group.wait_all_closed(Duration::ZERO));
+    group.close_all();
+}
+
+#[test]
+fn an_empty_group() {
+    let group = Group::new();
+    assert!(group.wait_all_closed(Duration::ZERO));
+    group.close_all();
+    assert!(group.wait_all_closed(Duration::ZERO));
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn missed_values_oldest_first() {
+    let (tx, mut rx) = watch::builder().history(3).channel(0u32);
+    assert_eq!(rx.missed_values(), (vec![0], 0));
+    assert_eq!(rx.missed_values(), (vec![], 0));
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.missed_values(), (vec![1, 2], 0));
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn evicted_values_are_counted() {
+    let (tx, mut rx) = watch::builder().history(3).channel(0u32);
+    rx.get();
+    tx.send(1);
+    tx.send(2);
+    tx.update(|value| *value += 1);
+    tx.send(4);
+    tx.send(5);
+    assert_eq!(rx.missed_values(), (vec![3, 4, 5], 2));
+}
+
+#[test]
+fn get_and_wait_are_unchanged() {
+    let (tx, mut rx) = watch::builder().history(4).channel(0u32);
+    let mut other = tx.subscribe();
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.get(), 2);
+    // Reading the latest value marks everything before it seen too.
+    assert_eq!(rx.missed_values(), (vec![], 0));
+    assert_eq!(other.get_if_new(), Some(2));
+    tx.send(3);
+    assert_eq!(other.missed_values(), (vec![3], 0));
+}
+
+#[test]
+fn subscribers_start_with_nothing_missed() {
+    let (tx, _rx) = watch::builder().history(4).channel(0u32);
+    tx.send(1);
+    let mut late = tx.subscribe();
+    assert_eq!(late.missed_values(), (vec![], 0));
+    tx.send(2);
+    assert_eq!(late.missed_values(), (vec![2], 0));
+}
+
+#[test]
+fn without_history_only_the_latest_is_kept() {
+    let (tx, mut rx) = watch::channel(0u32);
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.missed_values(), (vec![2], 2));
+    assert_eq!(rx.missed_values(), (vec![], 0));
+}
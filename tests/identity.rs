@@ -0,0 +1,65 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::collections::{HashMap, HashSet};
+
+#[test]
+fn handles_of_one_channel_are_the_same() {
+    let (tx, rx) = watch::channel(0u8);
+    let cloned_tx = tx.clone();
+    let subscribed = tx.subscribe();
+    let new_tx = rx.new_sender();
+    let cloned_rx = rx.clone();
+
+    assert!(tx.same_channel(&cloned_tx));
+    assert!(tx.same_channel(&new_tx));
+    assert!(rx.same_channel(&subscribed));
+    assert!(rx.same_channel(&cloned_rx));
+    assert!(rx.same_channel_as(&tx));
+    assert!(subscribed.same_channel_as(&new_tx));
+}
+
+#[test]
+fn handles_of_other_channels_differ() {
+    let (tx, rx) = watch::channel(0u8);
+    let (other_tx, other_rx) = watch::channel(0u8);
+    assert!(!tx.same_channel(&other_tx));
+    assert!(!rx.same_channel(&other_rx));
+    assert!(!rx.same_channel_as(&other_tx));
+    assert_ne!(tx.channel_id(), other_tx.channel_id());
+    assert_ne!(rx.channel_id(), other_rx.channel_id());
+}
+
+#[test]
+fn channel_ids_key_a_set() {
+    let (tx, rx) = watch::channel(0u8);
+    let (other_tx, _other_rx) = watch::channel(0u8);
+    let ids: HashSet<_> = vec![
+        tx.channel_id(),
+        tx.clone().channel_id(),
+        tx.subscribe().channel_id(),
+        rx.new_sender().channel_id(),
+        rx.channel_id(),
+        rx.clone().channel_id(),
+        other_tx.channel_id(),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(ids.len(), 2);
+}
+
+#[test]
+fn deduplicating_subscriptions() {
+    let (tx, rx) = watch::channel(1u8);
+    let (_other_tx, other_rx) = watch::channel(2u8);
+    let mut subscriptions = HashMap::new();
+    for rx in [rx.clone(), other_rx, tx.subscribe(), rx] {
+        subscriptions.entry(rx.channel_id()).or_insert(rx);
+    }
+    assert_eq!(subscriptions.len(), 2);
+    let mut values: Vec<u8> = subscriptions.values_mut().map(|rx| rx.get()).collect();
+    values.sort();
+    assert_eq!(values, [1, 2]);
+}
@@ -0,0 +1,150 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+use watch::RecvError;
+
+mod util;
+use util::{eventually, join_all};
+
+const SHORT: Duration = Duration::from_millis(30);
+
+#[test]
+fn polls_skip_values_without_interest() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.set_interest(|value| *value > 10);
+    // The first value is not interesting either, and is marked seen.
+    assert_eq!(rx.get_if_new(), None);
+    assert!(!rx.has_changed());
+    tx.send(5);
+    assert_eq!(rx.get_if_new_shared(), None);
+    assert!(!rx.has_changed());
+    tx.send(20);
+    assert_eq!(rx.get_if_new(), Some(20));
+
+    // Other reads return the latest value.
+    tx.send(1);
+    assert_eq!(rx.get(), 1);
+}
+
+#[test]
+fn waits_skip_values_without_interest() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(0u32);
+    rx.set_interest(|value| *value > 10);
+    tx.send(1);
+    assert_eq!(rx.wait_timeout(SHORT), None);
+    let sender = thread::spawn(move || {
+        for value in [2, 3, 30] {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(value);
+        }
+        tx
+    });
+    assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(30));
+    let tx = sender.join().unwrap();
+
+    // A closing still ends the wait.
+    tx.send(4);
+    drop(tx);
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn a_new_interest_replaces_the_old_one() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(0u32);
+    rx.set_interest(|value| *value == 1);
+    rx.set_interest(|value| *value == 2);
+    tx.send(1);
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(2);
+    assert_eq!(rx.get_if_new(), Some(2));
+}
+
+#[test]
+fn clones_have_no_interest() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(0u32);
+    rx.set_interest(|value| *value == 1);
+    let mut cloned = rx.clone();
+    tx.send(2);
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(cloned.get_if_new(), Some(2));
+}
+
+#[test]
+fn unfiltered_wakeups_are_checked_by_the_receiver() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(0u32);
+    rx.set_interest(|value| *value == 99);
+    let waiter = thread::spawn(move || rx.wait());
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    // Resuming wakes every waiter, whatever the value.
+    let paused = tx.pause_notifications();
+    tx.send(4);
+    drop(paused);
+    thread::sleep(SHORT);
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    assert!(!waiter.is_finished());
+    tx.send(99);
+    assert_eq!(join_all(vec![waiter]), [99]);
+}
+
+#[cfg(feature = "stats")]
+mod stats {
+    use super::*;
+    use watch::WatchSender;
+
+    fn notifications(tx: &WatchSender<u32>) -> u64 {
+        tx.stats().notifications
+    }
+
+    #[test]
+    fn only_the_interested_receivers_are_woken() {
+        let (tx, rx) = watch::builder().initial_seen(true).channel(0u32);
+        let mut waiters: Vec<_> = (0..10)
+            .map(|wanted| {
+                let mut rx = rx.clone();
+                rx.set_interest(move |value| value % 10 == wanted);
+                Some(thread::spawn(move || rx.recv()))
+            })
+            .collect();
+        drop(rx);
+        assert!(eventually(|| tx.waiting_receivers() == 10));
+        let before = notifications(&tx);
+
+        tx.send(3);
+        let woken = waiters[3].take().unwrap();
+        assert_eq!(join_all(vec![woken]), [Ok(3)]);
+        assert_eq!(notifications(&tx) - before, 1);
+        assert_eq!(tx.waiting_receivers(), 9);
+
+        // A value that no one wants wakes no one.
+        tx.send(13);
+        thread::sleep(SHORT);
+        assert_eq!(notifications(&tx) - before, 1);
+        assert_eq!(tx.waiting_receivers(), 9);
+
+        tx.send(17);
+        let woken = waiters[7].take().unwrap();
+        assert_eq!(join_all(vec![woken]), [Ok(17)]);
+        assert_eq!(notifications(&tx) - before, 2);
+
+        drop(tx);
+        let rest: Vec<_> = waiters.into_iter().flatten().collect();
+        assert!(join_all(rest).into_iter().all(|result| result.is_err()));
+    }
+
+    #[test]
+    fn a_parked_receiver_sleeps_through_values_it_does_not_want() {
+        let (tx, mut rx) = watch::builder().initial_seen(true).channel(0u32);
+        rx.set_interest(|value| *value % 2 == 0);
+        let waiter = thread::spawn(move || rx.wait());
+        assert!(eventually(|| tx.waiting_receivers() == 1));
+        let before = notifications(&tx);
+        tx.send(1);
+        thread::sleep(SHORT);
+        assert_eq!(notifications(&tx), before);
+        assert_eq!(tx.waiting_receivers(), 1);
+
+        tx.send(2);
+        assert_eq!(join_all(vec![waiter]), [2]);
+        assert_eq!(notifications(&tx) - before, 1);
+    }
+}
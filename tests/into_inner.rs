@@ -0,0 +1,127 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn the_last_receiver_takes_the_value() {
+    let (tx, rx) = watch::channel(vec![1u8, 2]);
+    drop(tx);
+    assert_eq!(rx.try_into_inner().unwrap(), [1, 2]);
+}
+
+#[test]
+fn the_last_sender_takes_the_value() {
+    let (tx, rx) = watch::channel(vec![1u8]);
+    drop(rx);
+    tx.send(vec![3]);
+    assert_eq!(tx.try_into_inner().unwrap(), [3]);
+}
+
+#[test]
+fn other_handles_make_it_fail() {
+    let (tx, rx) = watch::channel(String::from("a"));
+    // A receiver and a sender each hold the channel.
+    let tx = tx.try_into_inner().unwrap_err();
+    let cloned = rx.clone();
+    let rx = rx.try_into_inner().unwrap_err();
+    assert_eq!(tx.receiver_count(), 2);
+    let cloned = cloned.try_into_inner().unwrap_err();
+    drop(cloned);
+
+    // So does a reader.
+    let reader = tx.reader();
+    drop(tx);
+    let rx = rx.try_into_inner().unwrap_err();
+    drop(reader);
+    assert_eq!(rx.try_into_inner().unwrap(), "a");
+}
+
+#[test]
+fn a_shared_value_makes_it_fail() {
+    let (tx, mut rx) = watch::channel(String::from("a"));
+    drop(tx);
+    let held = rx.get_shared();
+    let rx = rx.try_into_inner().unwrap_err();
+    drop(held);
+    assert_eq!(rx.try_into_inner().unwrap(), "a");
+}
+
+#[test]
+fn a_failure_keeps_what_was_seen() {
+    let (tx, mut rx) = watch::channel(1);
+    rx.get();
+    let mut rx = rx.try_into_inner().unwrap_err();
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(2);
+    assert_eq!(rx.get_if_new(), Some(2));
+}
+
+#[test]
+fn with_history() {
+    let (tx, rx) = watch::builder().history(3).channel(1u32);
+    drop(rx);
+    tx.send(2);
+    assert_eq!(tx.try_into_inner().unwrap(), 2);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn a_sender_held_elsewhere() {
+    use std::{sync::mpsc, thread};
+
+    let (tx, rx) = watch::channel(5);
+    let (release, released) = mpsc::channel::<()>();
+    let holder = thread::spawn(move || {
+        released.recv().unwrap();
+        drop(tx);
+    });
+    let mut rx = rx.try_into_inner().unwrap_err();
+    release.send(()).unwrap();
+    let value = loop {
+        match rx.try_into_inner() {
+            Ok(value) => break value,
+            Err(back) => rx = back,
+        }
+        thread::yield_now();
+    };
+    assert_eq!(value, 5);
+    holder.join().unwrap();
+}
+
+#[cfg(all(feature = "registry", not(target_family = "wasm")))]
+#[test]
+fn racing_the_registry() {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    // The registry briefly upgrades its weak reference to every channel.
+    let stop = Arc::new(AtomicBool::new(false));
+    let dumper = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                watch::registry().dump();
+            }
+        })
+    };
+    for i in 0..1000 {
+        let (tx, rx) = watch::channel_named("into_inner", vec![i]);
+        drop(rx);
+        let mut tx = tx;
+        let value = loop {
+            match tx.try_into_inner() {
+                Ok(value) => break value,
+                Err(back) => tx = back,
+            }
+        };
+        assert_eq!(value, [i]);
+    }
+    stop.store(true, Ordering::Relaxed);
+    dumper.join().unwrap();
+}
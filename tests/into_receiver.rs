@@ -0,0 +1,89 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+use watch::RecvError;
+
+mod util;
+use util::{eventually, join_all};
+
+#[test]
+fn the_receiver_has_seen_the_latest_value() {
+    let (tx, _rx) = watch::channel(0);
+    let other = tx.clone();
+    tx.send(1);
+    let mut rx = tx.into_receiver();
+    assert_eq!(rx.get_if_new(), None);
+    other.send(2);
+    assert_eq!(rx.get_if_new(), Some(2));
+    assert_eq!(other.receiver_count(), 2);
+}
+
+#[test]
+fn the_last_sender_closes_the_channel() {
+    let (tx, mut rx) = watch::channel(0);
+    tx.send(1);
+    let waiter = thread::spawn(move || (rx.recv(), rx.recv()));
+    let converted = tx.into_receiver();
+    assert!(converted.is_closed());
+    // The value sent before closing is still delivered.
+    assert_eq!(waiter.join().unwrap(), (Ok(1), Err(RecvError)));
+}
+
+#[test]
+fn another_sender_keeps_the_channel_open() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let other = tx.clone();
+    let waiter = thread::spawn(move || rx.recv());
+    assert!(eventually(|| other.waiting_receivers() == 1));
+    let converted = tx.into_receiver();
+    assert!(!converted.is_closed());
+    assert_eq!(other.waiting_receivers(), 1);
+    other.send(5);
+    assert_eq!(waiter.join().unwrap(), Ok(5));
+}
+
+#[test]
+fn the_channel_never_looks_closed_while_a_sender_exists() {
+    for _ in 0..200 {
+        let (tx, rx) = watch::channel(0);
+        let other = tx.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let watcher = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    assert!(!rx.is_closed());
+                }
+            })
+        };
+        let rx = tx.into_receiver();
+        stop.store(true, Ordering::Relaxed);
+        watcher.join().unwrap();
+        assert!(!rx.is_closed());
+        drop(other);
+        assert!(rx.is_closed());
+    }
+}
+
+#[test]
+fn racing_conversions_close_the_channel_once() {
+    for _ in 0..200 {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let other = tx.clone();
+        let waiter = thread::spawn(move || rx.recv());
+        let converted = join_all(vec![
+            thread::spawn(move || tx.into_receiver()),
+            thread::spawn(move || other.into_receiver()),
+        ]);
+        assert!(converted.iter().all(|rx| rx.is_closed()));
+        assert_eq!(waiter.join().unwrap(), Err(RecvError));
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(all(feature = "timer", not(target_family = "wasm")))]
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+const INTERVAL: Duration = Duration::from_millis(50);
+
+#[test]
+fn idle_channels_are_touched() {
+    let (tx, mut rx) = watch::channel(1);
+    rx.get();
+    let _keepalive = tx.keepalive(INTERVAL);
+    let reader = tx.reader();
+    let version = reader.version();
+    let start = Instant::now();
+    // The heartbeat wakes the receiver with the same value.
+    assert_eq!(rx.wait_timeout(Duration::from_secs(60)), Some(1));
+    assert!(start.elapsed() >= INTERVAL);
+    assert!(reader.version() > version);
+    assert_eq!(rx.wait_timeout(Duration::from_secs(60)), Some(1));
+}
+
+#[test]
+fn sends_restart_the_interval() {
+    let (tx, _rx) = watch::channel(0);
+    let interval = Duration::from_millis(200);
+    let _keepalive = tx.keepalive(interval);
+    let reader = tx.reader();
+    let version = reader.version();
+    let start = Instant::now();
+    let mut sent = 0;
+    while start.elapsed() < interval * 2 {
+        tx.send(sent);
+        sent += 1;
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(reader.version(), version + sent);
+}
+
+#[test]
+fn stopping_stops_the_touches() {
+    let (tx, mut rx) = watch::channel(1);
+    rx.get();
+    let keepalive = tx.keepalive(INTERVAL);
+    rx.wait_timeout(Duration::from_secs(60)).unwrap();
+    keepalive.stop();
+    let reader = tx.reader();
+    // A touch that was already being made may still land.
+    thread::sleep(INTERVAL);
+    let version = reader.version();
+    thread::sleep(INTERVAL * 3);
+    assert_eq!(reader.version(), version);
+}
+
+#[test]
+fn there_are_no_touches_without_receivers() {
+    let (tx, rx) = watch::channel(1);
+    drop(rx);
+    let reader = tx.reader();
+    let version = reader.version();
+    let _keepalive = tx.keepalive(INTERVAL);
+    thread::sleep(INTERVAL * 3);
+    assert_eq!(reader.version(), version);
+
+    let mut rx = tx.subscribe();
+    assert_eq!(rx.wait_timeout(Duration::from_secs(60)), Some(1));
+}
+
+#[test]
+fn closing_the_channel_stops_the_touches() {
+    let (tx, mut rx) = watch::channel(1);
+    rx.get();
+    let _keepalive = tx.keepalive(INTERVAL);
+    let reader = tx.reader();
+    drop(tx);
+    let version = reader.version();
+    assert!(rx.is_closed());
+    thread::sleep(INTERVAL * 3);
+    assert_eq!(reader.version(), version);
+}
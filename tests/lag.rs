@@ -0,0 +1,179 @@
+#![cfg(all(feature = "std", target_has_atomic = "64", not(target_family = "wasm")))]
+
+use std::thread;
+
+mod util;
+use util::eventually;
+
+#[test]
+fn untracked_channels_report_nothing() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send(1);
+    assert_eq!(tx.max_lag(), None);
+    assert_eq!(tx.min_seen_version(), None);
+}
+
+#[test]
+fn lag_follows_the_slowest_receiver() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    // The starting value has not been seen.
+    assert_eq!(tx.max_lag(), Some(1));
+    rx.get();
+    assert_eq!(tx.max_lag(), Some(0));
+
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(tx.max_lag(), Some(2));
+    let mut subscribed = tx.subscribe();
+    assert_eq!(tx.max_lag(), Some(2));
+    let (_, latest) = rx.get_versioned();
+    assert_eq!(tx.max_lag(), Some(0));
+    assert_eq!(tx.min_seen_version(), Some(latest));
+
+    tx.send(3);
+    assert_eq!(tx.max_lag(), Some(1));
+    assert_eq!(tx.min_seen_version(), Some(latest));
+    subscribed.get();
+    rx.get();
+    assert_eq!(tx.max_lag(), Some(0));
+}
+
+#[test]
+fn dropped_receivers_do_not_pin_the_minimum() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    rx.get();
+    let slow = tx.subscribe();
+    let cloned = slow.clone();
+    for value in 0..10 {
+        tx.send(value);
+        rx.get();
+    }
+    assert_eq!(tx.max_lag(), Some(10));
+    drop(slow);
+    assert_eq!(tx.max_lag(), Some(10));
+    drop(cloned);
+    assert_eq!(tx.max_lag(), Some(0));
+
+    drop(rx);
+    assert_eq!(tx.max_lag(), None);
+    for _ in 0..10_000 {
+        drop(tx.subscribe());
+    }
+    assert_eq!(tx.max_lag(), None);
+}
+
+#[test]
+fn every_read_path_reports() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    rx.get();
+    let mut read_only = tx.subscribe_read_only();
+    let mut cached = tx.subscribe().into_cached();
+    tx.send(1);
+    assert_eq!(tx.max_lag(), Some(1));
+    read_only.get();
+    cached.get_cached();
+    assert_eq!(tx.max_lag(), Some(1));
+    rx.get_if_new();
+    assert_eq!(tx.max_lag(), Some(0));
+
+    let sender = {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            assert!(eventually(|| tx.waiting_receivers() == 2));
+            tx.send(2);
+        })
+    };
+    let waiter = thread::spawn(move || read_only.wait());
+    rx.wait();
+    assert_eq!(waiter.join().unwrap(), 2);
+    sender.join().unwrap();
+    cached.refresh_blocking();
+    assert_eq!(tx.max_lag(), Some(0));
+}
+
+#[test]
+fn initial_seen_starts_without_lag() {
+    let (tx, _rx) = watch::builder()
+        .track_lag(true)
+        .initial_seen(true)
+        .channel(0);
+    assert_eq!(tx.max_lag(), Some(0));
+}
+
+#[test]
+fn the_report_lists_every_live_receiver() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    let report = tx.receiver_report();
+    assert_eq!(report.len(), 1);
+    // A receiver that has never read.
+    assert_eq!(report[0].id, rx.id().unwrap());
+    assert_eq!(report[0].lag, 1);
+    assert_eq!(report[0].last_read_at, None);
+
+    let (_, seen) = rx.get_versioned();
+    let mut cloned = rx.clone();
+    let subscribed = tx.subscribe();
+    tx.send(1);
+    tx.send(2);
+    let report = tx.receiver_report();
+    let ids: Vec<_> = report.iter().map(|info| info.id).collect();
+    // Clones get ids of their own, and the list is in creation order.
+    assert_eq!(
+        ids,
+        [
+            rx.id().unwrap(),
+            cloned.id().unwrap(),
+            subscribed.id().unwrap()
+        ]
+    );
+    assert!(report
+        .iter()
+        .all(|info| info.seen_version == seen && info.lag == 2));
+    assert!(report[0].last_read_at.is_some());
+    assert_eq!(report[1].last_read_at, None);
+    assert_eq!(report[2].last_read_at, None);
+
+    // Reading keeps the id and updates the entry.
+    let (_, latest) = cloned.get_versioned();
+    let report = tx.receiver_report();
+    assert_eq!(report[1].id, cloned.id().unwrap());
+    assert_eq!((report[1].seen_version, report[1].lag), (latest, 0));
+    assert!(report[1].last_read_at.is_some());
+
+    // Dropped receivers leave the list at once.
+    drop(cloned);
+    let report = tx.receiver_report();
+    assert_eq!(
+        report.iter().map(|info| info.id).collect::<Vec<_>>(),
+        [rx.id().unwrap(), subscribed.id().unwrap()]
+    );
+    drop(rx);
+    drop(subscribed);
+    assert!(tx.receiver_report().is_empty());
+}
+
+#[test]
+fn untracked_channels_have_an_empty_report() {
+    let (tx, rx) = watch::channel(0);
+    assert!(tx.receiver_report().is_empty());
+    assert_eq!(rx.id(), None);
+}
+
+#[cfg(feature = "test-clock")]
+#[test]
+fn read_times_come_from_the_clock_of_the_channel() {
+    use std::time::Duration;
+
+    let clock = watch::MockClock::new();
+    let (tx, mut rx) = watch::builder()
+        .clock(clock.clone())
+        .track_lag(true)
+        .channel(0);
+    let start = clock.now();
+    clock.advance(Duration::from_secs(2));
+    rx.get();
+    clock.advance(Duration::from_secs(5));
+    let report = tx.receiver_report();
+    assert_eq!(report[0].last_read_at, Some(start + Duration::from_secs(2)));
+}
@@ -0,0 +1,87 @@
+//! The wakeup latency histogram of the `stats` feature.
+#![cfg(all(feature = "stats", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+use watch::LatencyHistogram;
+
+mod util;
+use util::eventually;
+
+/// The index of the only bucket with samples in it.
+fn only_bucket(histogram: &LatencyHistogram) -> usize {
+    assert_eq!(histogram.count(), 1, "{:?}", histogram);
+    histogram.buckets().iter().position(|&n| n == 1).unwrap()
+}
+
+#[test]
+fn bucket_limits_double() {
+    assert_eq!(
+        LatencyHistogram::bucket_limit(0),
+        Some(Duration::from_micros(1))
+    );
+    for index in 1..23 {
+        assert_eq!(
+            LatencyHistogram::bucket_limit(index),
+            LatencyHistogram::bucket_limit(index - 1).map(|limit| limit * 2)
+        );
+    }
+    assert_eq!(LatencyHistogram::bucket_limit(23), None);
+    assert_eq!(LatencyHistogram::bucket_limit(100), None);
+    assert_eq!(LatencyHistogram::default().buckets().len(), 24);
+}
+
+#[test]
+fn a_delayed_wakeup_lands_in_a_later_bucket() {
+    // With manual notification the waiter is only woken by the pump, so
+    // the delay between the send and the pump is part of its latency.
+    let (tx, mut rx) = watch::builder()
+        .initial_seen(true)
+        .manual_notify(true)
+        .channel(0);
+    assert_eq!(tx.stats().wakeup_latency_histogram().count(), 0);
+    let waiter = thread::spawn(move || {
+        rx.wait();
+        rx
+    });
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(1);
+    thread::sleep(Duration::from_millis(20));
+    tx.pump();
+    let rx = waiter.join().unwrap();
+
+    let histogram = *rx.stats().wakeup_latency_histogram();
+    let bucket = only_bucket(&histogram);
+    // Bucket 14 ends at about 16 ms, so the sample is in a later one.
+    assert!(bucket > 14, "{:?}", histogram);
+}
+
+#[test]
+fn every_waiter_is_a_sample() {
+    let (tx, rx) = watch::builder().initial_seen(true).channel(0);
+    let waiters: Vec<_> = (0..3)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || rx.wait_timeout(Duration::from_secs(10)))
+        })
+        .collect();
+    assert!(eventually(|| tx.waiting_receivers() == 3));
+    tx.send(1);
+    for waiter in waiters {
+        assert_eq!(waiter.join().unwrap(), Some(1));
+    }
+    assert_eq!(tx.stats().wakeup_latency_histogram().count(), 3);
+}
+
+#[test]
+fn reads_without_waiting_are_not_samples() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send(1);
+    assert_eq!(rx.get_if_new(), Some(1));
+    tx.send(2);
+    // The value is already there, so the wait does not park.
+    assert_eq!(rx.wait(), 2);
+    // Timing out records nothing.
+    assert_eq!(rx.wait_timeout(Duration::from_millis(5)), None);
+    assert_eq!(tx.stats().wakeup_latency_histogram().count(), 0);
+}
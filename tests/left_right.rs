@@ -0,0 +1,207 @@
+//! The left-right channel, which keeps two copies of its value.
+#![cfg(all(feature = "left-right", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use util::{eventually, join_all};
+use watch::{lr_channel, LrWatchReceiver, LrWatchSender, RecvError, RecvTimeoutError};
+
+#[test]
+fn semantics() {
+    let (tx, mut rx) = lr_channel(1u32);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert_eq!(rx.get_if_new(), None);
+    assert!(!rx.has_changed());
+    let mut subscriber = tx.subscribe();
+    assert_eq!(subscriber.get_if_new(), None);
+
+    tx.update(|value| *value += 1);
+    assert_eq!(rx.read(|value| *value * 10), 20);
+    assert!(!rx.has_changed());
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(3);
+    });
+    assert_eq!(rx.wait(), 3);
+    sender.join().unwrap();
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(subscriber.recv_timeout(Duration::from_secs(5)), Ok(3));
+    assert_eq!(
+        subscriber.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Closed)
+    );
+    assert!(rx.is_closed());
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+
+    // A new sender reopens the channel.
+    let tx = rx.new_sender();
+    assert!(!rx.is_closed());
+    tx.send(4);
+    assert_eq!(rx.recv(), Ok(4));
+    // A clone has seen what the receiver had seen.
+    assert_eq!(rx.clone().get_if_new(), None);
+}
+
+#[test]
+fn a_panicking_update_changes_nothing() {
+    let (tx, mut rx) = lr_channel(vec![1, 2]);
+    rx.get();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|value| {
+            value.push(3);
+            panic!("update");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get(), vec![1, 2]);
+
+    // Both copies were left as they were.
+    tx.update(|value| value.push(4));
+    assert_eq!(rx.get(), vec![1, 2, 4]);
+    tx.update(|value| value.push(5));
+    assert_eq!(rx.get(), vec![1, 2, 4, 5]);
+}
+
+#[test]
+fn receivers_come_and_go_inside_a_read() {
+    let (tx, mut rx) = lr_channel(0u32);
+    let other = rx.clone();
+    let subscriber = tx.clone();
+    let reading = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let reading = reading.clone();
+        thread::spawn(move || {
+            let seen = rx.read(|&value| {
+                reading.store(true, Ordering::SeqCst);
+                // Give the sender time to start waiting for this read.
+                thread::sleep(Duration::from_millis(20));
+                let subscribed = subscriber.subscribe();
+                drop(other);
+                drop(subscribed);
+                value
+            });
+            assert_eq!(seen, 0);
+            rx.get()
+        })
+    };
+    assert!(eventually(|| reading.load(Ordering::SeqCst)));
+    // This waits for the read to end.
+    let sender = thread::spawn(move || {
+        tx.send(1);
+        1
+    });
+    // If the sender kept every receiver from being created or dropped while
+    // it waited, neither would ever finish.
+    assert_eq!(join_all(vec![reader, sender]), [1, 1]);
+}
+
+#[test]
+fn readers_never_see_a_torn_value() {
+    const WRITES: u64 = 250;
+
+    let (tx, rx) = lr_channel(vec![0u64; 64]);
+    let readers = (0..4)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while last < 2 * WRITES {
+                    let value = rx.read(|value| {
+                        assert!(value.iter().all(|&x| x == value[0]), "torn");
+                        value[0]
+                    });
+                    assert!(value >= last);
+                    last = value;
+                }
+            })
+        })
+        .collect();
+    let writers = (0..2)
+        .map(|writer| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for _ in 0..WRITES {
+                    if writer == 0 {
+                        tx.update(|value| {
+                            let next = value[0] + 1;
+                            value.iter_mut().for_each(|x| *x = next);
+                        });
+                    } else {
+                        // Replace the value rather than change it.
+                        tx.update(|value| *value = vec![value[0] + 1; 64]);
+                    }
+                }
+            })
+        })
+        .collect();
+    join_all(writers);
+    join_all(readers);
+    let mut rx = rx;
+    assert_eq!(rx.get(), vec![2 * WRITES; 64]);
+}
+
+#[test]
+fn receivers_churn_while_sending() {
+    const WRITES: u64 = 5_000;
+
+    let (tx, rx) = lr_channel([0u64; 8]);
+    let done = Arc::new(AtomicBool::new(false));
+    let churners = (0..4)
+        .map(|_| {
+            let rx = rx.clone();
+            let tx = tx.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let mut clone = rx.clone();
+                    let mut subscribed = tx.subscribe();
+                    for receiver in [&mut clone, &mut subscribed] {
+                        let value = receiver.get();
+                        assert!(value.iter().all(|&x| x == value[0]), "torn");
+                        assert!(value[0] >= last);
+                        last = value[0];
+                    }
+                }
+            })
+        })
+        .collect();
+    let waiters = (0..2)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut last = rx.get()[0];
+                while let Ok(value) = rx.recv() {
+                    assert!(value[0] > last);
+                    last = value[0];
+                }
+                last
+            })
+        })
+        .collect();
+    for value in 1..=WRITES {
+        tx.send([value; 8]);
+    }
+    done.store(true, Ordering::Relaxed);
+    join_all(churners);
+    drop(tx);
+    assert!(join_all(waiters).into_iter().all(|last| last == WRITES));
+}
+
+#[test]
+fn handles_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<LrWatchSender<Vec<u8>>>();
+    assert_send_sync::<LrWatchReceiver<Vec<u8>>>();
+}
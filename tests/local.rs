@@ -0,0 +1,114 @@
+//! The single-threaded channel, with the same tests as the methods of the
+//! threaded one that never block.
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::{cell::Cell, rc::Rc};
+use watch::local;
+
+#[test]
+fn get_and_get_if_new() {
+    let (tx, mut rx) = local::channel(1);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(2);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), 2);
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[test]
+fn update() {
+    let (tx, mut rx) = local::channel(vec![1]);
+    rx.get();
+    tx.update(|value| value.push(2));
+    assert_eq!(rx.get_if_new(), Some(vec![1, 2]));
+    tx.update_with(|value| value.iter().map(|x| x * 10).collect());
+    assert_eq!(rx.get_if_new(), Some(vec![10, 20]));
+}
+
+// Panics abort on wasm.
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn update_that_panics_is_still_new() {
+    let (tx, mut rx) = local::channel(vec![1]);
+    rx.get();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        tx.update(|value| {
+            value.push(2);
+            panic!("in update");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(rx.get_if_new(), Some(vec![1, 2]));
+}
+
+#[test]
+fn sending_without_receivers() {
+    let (tx, rx) = local::channel(0);
+    drop(rx);
+    tx.send(1);
+    assert_eq!(tx.subscribe().get(), 1);
+}
+
+#[test]
+fn subscribe_and_clone() {
+    let (tx, mut rx) = local::channel(0);
+    tx.send(1);
+    // A new subscriber has seen everything sent before it.
+    let mut subscribed = tx.subscribe();
+    assert!(!subscribed.has_changed());
+    // A clone starts where the original is.
+    let mut cloned = rx.clone();
+    assert_eq!(cloned.get_if_new(), Some(1));
+    assert_eq!(rx.get_if_new(), Some(1));
+    let mut cloned = rx.clone();
+    assert_eq!(cloned.get_if_new(), None);
+
+    tx.send(2);
+    assert_eq!(subscribed.get_if_new(), Some(2));
+    assert_eq!(cloned.get_if_new(), Some(2));
+    assert_eq!(rx.get_if_new(), Some(2));
+}
+
+#[test]
+fn closing() {
+    let (tx, mut rx) = local::channel(0);
+    rx.get();
+    let other = tx.clone();
+    drop(tx);
+    assert!(!rx.is_closed());
+    drop(other);
+    assert!(rx.is_closed());
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), 0);
+
+    // A new sender reopens the channel.
+    let tx = rx.new_sender();
+    assert!(!rx.is_closed());
+    tx.send(1);
+    assert_eq!(rx.get_if_new(), Some(1));
+}
+
+#[test]
+fn values_that_are_not_send() {
+    let (tx, mut rx) = local::channel(Rc::new(Cell::new(5)));
+    let value = rx.get();
+    value.set(6);
+    assert_eq!(rx.get().get(), 6);
+    tx.send(Rc::new(Cell::new(7)));
+    assert_eq!(rx.get_if_new().map(|value| value.get()), Some(7));
+}
+
+#[test]
+fn old_values_are_dropped_on_send() {
+    let value = Rc::new(());
+    let (tx, mut rx) = local::channel(value.clone());
+    assert_eq!(Rc::strong_count(&value), 2);
+    tx.send(Rc::new(()));
+    assert_eq!(Rc::strong_count(&value), 1);
+    drop(rx.get());
+}
@@ -0,0 +1,121 @@
+#![cfg(all(feature = "lock-timing", not(target_family = "wasm")))]
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0 += &format!("{}={:?} ", field.name(), value);
+    }
+}
+
+/// Collects the fields of every warning.
+#[derive(Clone, Default)]
+struct Warnings(Arc<Mutex<Vec<String>>>);
+
+impl Warnings {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Subscriber for Warnings {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() == Level::WARN
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn slow_operations_warn() {
+    let warnings = Warnings::default();
+    tracing::subscriber::with_default(warnings.clone(), || {
+        let (tx, _rx) = watch::builder()
+            .slow_lock_threshold(Duration::from_millis(50))
+            .channel(0u32);
+        tx.update_with(|value| value + 1);
+        assert!(warnings.take().is_empty());
+
+        tx.update_with(|value| {
+            thread::sleep(Duration::from_millis(60));
+            value + 1
+        });
+        let (other, _other_rx) = watch::channel(0);
+        watch::transaction((&other, &tx), |value, _| {
+            thread::sleep(Duration::from_millis(60));
+            *value += 1;
+        });
+        let found = warnings.take();
+        // The transaction held both channels for too long.
+        assert_eq!(found.len(), 3, "{:?}", found);
+        assert!(
+            found[0].contains("operation=\"update_with\""),
+            "{}",
+            found[0]
+        );
+        assert!(
+            found[0].contains("message=value locked for too long"),
+            "{}",
+            found[0]
+        );
+        assert!(
+            found[1..].iter().all(|w| w.contains("transaction")),
+            "{:?}",
+            found
+        );
+    });
+}
+
+#[cfg(feature = "test-clock")]
+#[test]
+fn the_time_held_is_reported() {
+    let warnings = Warnings::default();
+    tracing::subscriber::with_default(warnings.clone(), || {
+        let clock = watch::MockClock::new();
+        let (tx, mut rx) = watch::builder().clock(clock.clone()).channel(0u32);
+        tx.update(|value| {
+            clock.advance(Duration::from_millis(40));
+            *value += 1;
+        });
+        // Below the default threshold.
+        tx.update(|value| {
+            clock.advance(Duration::from_millis(1));
+            *value += 1;
+        });
+        tx.send(1);
+        rx.get();
+
+        let found = warnings.take();
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert!(found[0].contains("operation=\"update\""), "{}", found[0]);
+        assert!(found[0].contains("held=40ms"), "{}", found[0]);
+        assert!(found[0].contains("channel="), "{}", found[0]);
+    });
+}
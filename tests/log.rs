@@ -0,0 +1,198 @@
+//! The records sent to the `log` facade with the `log` feature.
+#![cfg(all(feature = "log", not(target_family = "wasm")))]
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{
+    sync::{Mutex, MutexGuard, Once},
+    thread,
+    time::Duration,
+};
+use watch::WatchSender;
+
+mod util;
+use util::eventually;
+
+/// A record that was logged, with `watch` records only.
+#[derive(Debug, Clone, PartialEq)]
+struct Logged {
+    level: Level,
+    message: String,
+}
+
+static LOGGED: Mutex<Vec<Logged>> = Mutex::new(Vec::new());
+
+/// The logger is global, so the tests take turns.
+static TURN: Mutex<()> = Mutex::new(());
+
+struct Capture;
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if record.target() == "watch" {
+            LOGGED.lock().unwrap().push(Logged {
+                level: record.level(),
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger, and take the turn of this test with an empty log.
+fn capture() -> MutexGuard<'static, ()> {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| log::set_logger(&Capture).unwrap());
+    let turn = TURN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    log::set_max_level(LevelFilter::Trace);
+    LOGGED.lock().unwrap().clear();
+    turn
+}
+
+/// The field that names the channel of `tx` in its records.
+fn channel_field<T>(tx: &WatchSender<T>) -> String {
+    let id = format!("{:?}", tx.channel_id());
+    let id = id.trim_start_matches("ChannelId(").trim_end_matches(')');
+    format!("channel={}", id)
+}
+
+/// The records about the channel of `tx` so far.
+fn logged<T>(tx: &WatchSender<T>) -> Vec<Logged> {
+    let channel = channel_field(tx);
+    let logged = LOGGED.lock().unwrap();
+    logged
+        .iter()
+        .filter(|record| record.message.split(' ').any(|field| field == channel))
+        .cloned()
+        .collect()
+}
+
+fn trace(message: String) -> Logged {
+    Logged {
+        level: Level::Trace,
+        message,
+    }
+}
+
+#[test]
+fn sends_and_updates_are_traced() {
+    let _turn = capture();
+    let (tx, _rx) = watch::channel(0u32);
+    let channel = channel_field(&tx);
+    let version = tx.reader().version();
+    tx.send(1);
+    tx.update(|value| *value += 1);
+    assert_eq!(
+        logged(&tx),
+        [
+            trace(format!(
+                "value published {} version={}",
+                channel,
+                version + 1
+            )),
+            trace(format!(
+                "value published {} version={}",
+                channel,
+                version + 2
+            )),
+        ]
+    );
+}
+
+#[test]
+fn wakeups_are_traced() {
+    let _turn = capture();
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let waiter = thread::spawn(move || {
+        let value = rx.wait();
+        (value, rx.wait_timeout(Duration::from_millis(10)))
+    });
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(1);
+    assert_eq!(waiter.join().unwrap(), (1, None));
+
+    let version = tx.reader().version();
+    let woke: Vec<_> = logged(&tx)
+        .into_iter()
+        .filter(|record| record.message.starts_with("receiver woke"))
+        .collect();
+    assert_eq!(woke.len(), 2, "{:?}", woke);
+    assert!(woke.iter().all(|record| record.level == Level::Trace));
+    assert!(woke[0].message.ends_with(&format!(" version={}", version)));
+    assert!(woke[1]
+        .message
+        .ends_with(&format!(" version={} timed_out=true", version)));
+}
+
+#[test]
+fn closing_is_traced() {
+    let _turn = capture();
+    let (tx, _rx) = watch::channel(0u32);
+    let other = tx.clone();
+    tx.close();
+    // Closing again, or dropping the senders of a closed channel, does not
+    // log it twice.
+    tx.close();
+    let closed = |tx: &WatchSender<u32>| {
+        logged(tx)
+            .into_iter()
+            .filter(|record| record.message.starts_with("channel closed channel="))
+            .count()
+    };
+    assert_eq!(closed(&tx), 1);
+    drop(other);
+    assert_eq!(closed(&tx), 1);
+
+    let (tx, rx) = watch::channel(0u32);
+    let sender = tx.clone();
+    drop(tx);
+    assert_eq!(closed(&sender), 0);
+    // The record names the channel, so look it up before it is dropped.
+    let expected = trace(format!("channel closed {}", channel_field(&sender)));
+    drop(sender);
+    assert!(LOGGED.lock().unwrap().contains(&expected));
+    drop(rx);
+}
+
+#[test]
+fn records_follow_the_max_level() {
+    let _turn = capture();
+    log::set_max_level(LevelFilter::Warn);
+    let (tx, _rx) = watch::channel(0u32);
+    tx.send(1);
+    assert_eq!(logged(&tx), []);
+    log::set_max_level(LevelFilter::Trace);
+    tx.send(2);
+    assert_eq!(logged(&tx).len(), 1);
+}
+
+#[cfg(all(feature = "lock-timing", feature = "test-clock"))]
+#[test]
+fn values_locked_for_too_long_are_warnings() {
+    let _turn = capture();
+    let clock = watch::MockClock::new();
+    let (tx, _rx) = watch::builder().clock(clock.clone()).channel(0u32);
+    tx.update(|value| {
+        clock.advance(Duration::from_millis(40));
+        *value += 1;
+    });
+
+    let warnings: Vec<_> = logged(&tx)
+        .into_iter()
+        .filter(|record| record.level == Level::Warn)
+        .collect();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    let message = &warnings[0].message;
+    assert!(
+        message.starts_with("value locked for too long channel="),
+        "{}",
+        message
+    );
+    assert!(message.contains(" operation=update "), "{}", message);
+    assert!(message.ends_with(" held=40ms"), "{}", message);
+}
@@ -0,0 +1,108 @@
+//! Model-checked races of the channel.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --test loom`.
+#![cfg(loom)]
+
+use loom::thread;
+use watch::RecvError;
+
+#[test]
+fn send_against_wait() {
+    loom::model(|| {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let sender = thread::spawn(move || {
+            tx.send(1);
+            tx
+        });
+        assert_eq!(rx.wait(), 1);
+        drop(sender.join().unwrap());
+    });
+}
+
+#[test]
+fn two_sends_against_wait() {
+    loom::model(|| {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let sender = thread::spawn(move || {
+            tx.send(1);
+            tx.send(2);
+            tx
+        });
+        // The waiter sees either value, but never misses the last one.
+        let first = rx.wait();
+        assert!(first == 1 || first == 2);
+        if first == 1 {
+            assert_eq!(rx.wait(), 2);
+        }
+        assert!(!rx.has_changed());
+        drop(sender.join().unwrap());
+    });
+}
+
+#[test]
+fn clone_receiver_against_send() {
+    loom::model(|| {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let sender = thread::spawn(move || {
+            tx.send(1);
+            tx
+        });
+        // A clone starts where the original is, whichever side of the send
+        // it was made on.
+        let mut clone = rx.clone();
+        assert_eq!(clone.wait(), 1);
+        assert_eq!(rx.wait(), 1);
+        drop(sender.join().unwrap());
+    });
+}
+
+#[test]
+fn drop_last_sender_against_wait() {
+    loom::model(|| {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let sender = thread::spawn(move || drop(tx));
+        assert_eq!(rx.recv(), Err(RecvError));
+        assert!(rx.is_closed());
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn send_then_drop_against_recv() {
+    loom::model(|| {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let sender = thread::spawn(move || {
+            tx.send(1);
+        });
+        // The value sent before closing is never lost.
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.recv(), Err(RecvError));
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn get_if_new_against_send() {
+    loom::model(|| {
+        let (tx, mut rx) = watch::channel(0);
+        rx.get();
+        let sender = thread::spawn(move || {
+            tx.send(1);
+            tx
+        });
+        let early = rx.get_if_new();
+        let tx = sender.join().unwrap();
+        // Once the send has been joined, it is visible.
+        match early {
+            Some(value) => assert_eq!(value, 1),
+            None => assert_eq!(rx.get_if_new(), Some(1)),
+        }
+        assert_eq!(rx.get_if_new(), None);
+        drop(tx);
+    });
+}
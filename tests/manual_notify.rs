@@ -0,0 +1,112 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+use watch::{WatchReceiver, WatchSender};
+
+mod util;
+use util::eventually;
+
+const SHORT: Duration = Duration::from_millis(50);
+
+fn channel() -> (WatchSender<u32>, WatchReceiver<u32>) {
+    watch::builder()
+        .initial_seen(true)
+        .manual_notify(true)
+        .channel(0)
+}
+
+/// Park `count` threads in `wait`, and return where their values arrive.
+fn park(tx: &WatchSender<u32>, rx: &WatchReceiver<u32>, count: usize) -> Receiver<u32> {
+    let (done_tx, done) = mpsc::channel();
+    for _ in 0..count {
+        let mut rx = rx.clone();
+        let done_tx = done_tx.clone();
+        thread::spawn(move || {
+            let _ = done_tx.send(rx.wait());
+        });
+    }
+    assert!(eventually(|| tx.waiting_receivers() == count));
+    done
+}
+
+#[test]
+fn waiters_sleep_until_the_pump() {
+    let (tx, rx) = channel();
+    let done = park(&tx, &rx, 2);
+    tx.send(1);
+    tx.send(2);
+    assert!(done.recv_timeout(SHORT).is_err());
+    assert_eq!(tx.waiting_receivers(), 2);
+
+    assert!(tx.pump());
+    assert_eq!(done.recv(), Ok(2));
+    assert_eq!(done.recv(), Ok(2));
+    // Nothing is left to pump.
+    assert!(!tx.pump());
+}
+
+#[test]
+fn reads_see_the_value_at_once() {
+    let (tx, mut rx) = channel();
+    let _done = park(&tx, &rx, 1);
+    tx.send(1);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(1));
+
+    // A wait that starts after the send returns at once.
+    let mut late = tx.subscribe();
+    tx.send(2);
+    assert_eq!(late.wait(), 2);
+    assert!(tx.pump());
+}
+
+#[test]
+fn a_timed_wait_in_the_window_returns_the_value() {
+    let (tx, rx) = channel();
+    let mut waiting = rx.clone();
+    let waiter = thread::spawn(move || waiting.wait_timeout(SHORT));
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(7);
+    // The wait times out without a pump, but the value is there.
+    assert_eq!(waiter.join().unwrap(), Some(7));
+
+    let mut seen = rx.clone();
+    seen.get();
+    assert_eq!(seen.wait_timeout(Duration::from_millis(5)), None);
+}
+
+#[test]
+fn closing_is_not_deferred() {
+    let (tx, mut rx) = channel();
+    let waiter = thread::spawn(move || rx.recv());
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    drop(tx);
+    assert!(waiter.join().unwrap().is_err());
+}
+
+#[test]
+fn pumping_other_channels_does_nothing() {
+    let (tx, _rx) = watch::channel(0);
+    tx.send(1);
+    assert!(!tx.pump());
+}
+
+#[cfg(feature = "test-clock")]
+#[test]
+fn the_window_on_a_mock_clock() {
+    let clock = watch::MockClock::new();
+    let (tx, mut rx) = watch::builder()
+        .initial_seen(true)
+        .manual_notify(true)
+        .clock(clock.clone())
+        .channel(0);
+    let waiter = thread::spawn(move || rx.wait_timeout(Duration::from_secs(10)));
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(7);
+    clock.advance(Duration::from_secs(11));
+    assert_eq!(waiter.join().unwrap(), Some(7));
+}
@@ -0,0 +1,118 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Barrier},
+    thread,
+};
+use watch::TooManyReceivers;
+
+mod util;
+use util::join_all;
+
+#[test]
+fn the_cap_counts_the_first_receiver() {
+    let (tx, rx) = watch::builder().max_receivers(2).channel(0);
+    let second = tx.try_subscribe().unwrap();
+    assert_eq!(tx.try_subscribe().err(), Some(TooManyReceivers));
+    assert_eq!(rx.try_clone().err(), Some(TooManyReceivers));
+    assert_eq!(tx.receiver_count(), 2);
+
+    // Dropping a receiver makes room for another.
+    drop(second);
+    let third = rx.try_clone().unwrap();
+    assert_eq!(tx.receiver_count(), 2);
+    drop(third);
+    drop(rx);
+    assert_eq!(tx.receiver_count(), 0);
+    let _rx = tx.try_subscribe().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "at most 1 receivers")]
+fn clone_panics_at_the_cap() {
+    let (_tx, rx) = watch::builder().max_receivers(1).channel(0);
+    let _ = rx.clone();
+}
+
+#[test]
+#[should_panic(expected = "at most 1 receivers")]
+fn subscribe_panics_at_the_cap() {
+    let (tx, _rx) = watch::builder().max_receivers(1).channel(0);
+    let _ = tx.subscribe();
+}
+
+#[test]
+#[should_panic(expected = "at least one receiver")]
+fn the_cap_cannot_be_zero() {
+    let _ = watch::builder().max_receivers(0);
+}
+
+#[test]
+fn a_failed_receiver_is_not_counted() {
+    let (tx, rx) = watch::builder().max_receivers(1).channel(0);
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| rx.clone())).is_err());
+    assert_eq!(tx.receiver_count(), 1);
+
+    // Turning a sender into a receiver drops the sender when it fails.
+    let other = tx.clone();
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| other.into_receiver())).is_err());
+    assert_eq!(tx.receiver_count(), 1);
+    drop(rx);
+    let rx = tx.clone().into_receiver();
+    assert_eq!(tx.receiver_count(), 1);
+    drop(rx);
+}
+
+#[test]
+fn concurrent_subscribers_get_exactly_the_free_slots() {
+    let (tx, rx) = watch::builder().max_receivers(8).channel(0);
+    let barrier = Arc::new(Barrier::new(4));
+    let threads = (0..4)
+        .map(|_| {
+            let tx = tx.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                (0..100)
+                    .filter_map(|_| tx.try_subscribe().ok())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let receivers: Vec<_> = join_all(threads).into_iter().flatten().collect();
+    assert_eq!(receivers.len(), 7);
+    assert_eq!(tx.receiver_count(), 8);
+    drop(receivers);
+    assert_eq!(tx.receiver_count(), 1);
+    drop(rx);
+}
+
+#[test]
+fn the_count_stays_exact_under_churn() {
+    let (tx, rx) = watch::builder().max_receivers(4).channel(0);
+    let threads = (0..8)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let mut created = 0;
+                for _ in 0..1000 {
+                    if let Ok(receiver) = tx.try_subscribe() {
+                        assert!(tx.receiver_count() <= 4);
+                        created += 1;
+                        if let Ok(clone) = receiver.try_clone() {
+                            created += 1;
+                            drop(clone);
+                        }
+                    }
+                }
+                created
+            })
+        })
+        .collect();
+    let created: usize = join_all(threads).into_iter().sum();
+    assert!(created > 0);
+    assert_eq!(tx.receiver_count(), 1);
+    drop(rx);
+    assert_eq!(tx.receiver_count(), 0);
+}
@@ -0,0 +1,267 @@
+//! The series that a channel reports through the `metrics` facade. Every
+//! test registers its channels with a recorder of its own, so the tests do
+//! not see each other's series.
+#![cfg(all(feature = "metrics", not(target_family = "wasm")))]
+
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, Key, KeyName, Metadata, Recorder, SharedString,
+    Unit,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+mod util;
+use util::eventually;
+
+/// The value of a counter or a gauge.
+#[derive(Default)]
+struct Series(AtomicU64);
+
+impl CounterFn for Series {
+    fn increment(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::SeqCst);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.0.fetch_max(value, Ordering::SeqCst);
+    }
+}
+
+impl GaugeFn for Series {
+    fn increment(&self, value: f64) {
+        self.set(self.get() + value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.set(self.get() - value);
+    }
+
+    fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::SeqCst);
+    }
+}
+
+impl Series {
+    fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::SeqCst))
+    }
+}
+
+/// Keeps every series registered with it, by name and `channel` label.
+#[derive(Default)]
+struct TestRecorder {
+    series: Mutex<HashMap<(String, String), Arc<Series>>>,
+}
+
+impl TestRecorder {
+    fn series(&self, key: &Key) -> Arc<Series> {
+        let label = key
+            .labels()
+            .find(|label| label.key() == "channel")
+            .map(|label| label.value().to_owned())
+            .unwrap_or_default();
+        let name = key.name().to_owned();
+        self.series
+            .lock()
+            .unwrap()
+            .entry((name, label))
+            .or_default()
+            .clone()
+    }
+
+    fn find(&self, name: &str, label: &str) -> Option<Arc<Series>> {
+        let series = self.series.lock().unwrap();
+        series.get(&(name.to_owned(), label.to_owned())).cloned()
+    }
+
+    fn counter(&self, name: &str, label: &str) -> u64 {
+        self.find(name, label).unwrap().0.load(Ordering::SeqCst)
+    }
+
+    fn gauge(&self, name: &str, label: &str) -> f64 {
+        self.find(name, label).unwrap().get()
+    }
+
+    fn len(&self) -> usize {
+        self.series.lock().unwrap().len()
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.series(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.series(key))
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+/// Create a channel labeled `label` that reports to `recorder`.
+fn labeled<T>(
+    recorder: &TestRecorder,
+    label: &'static str,
+    builder: watch::ChannelBuilder,
+    value: T,
+) -> (watch::WatchSender<T>, watch::WatchReceiver<T>) {
+    metrics::with_local_recorder(recorder, || builder.metrics_label(label).channel(value))
+}
+
+#[test]
+fn every_series_is_registered_with_the_label() {
+    let recorder = TestRecorder::default();
+    let (_tx, _rx) = labeled(&recorder, "config.tls", watch::builder(), 0);
+    assert_eq!(recorder.len(), 4);
+    assert_eq!(recorder.counter("watch_sends", "config.tls"), 0);
+    assert_eq!(recorder.counter("watch_wakeups", "config.tls"), 0);
+    assert_eq!(recorder.gauge("watch_receivers", "config.tls"), 1.0);
+    assert_eq!(recorder.gauge("watch_max_lag", "config.tls"), 0.0);
+}
+
+#[test]
+fn channels_without_a_label_report_nothing() {
+    let recorder = TestRecorder::default();
+    let (tx, mut rx) = metrics::with_local_recorder(&recorder, || watch::channel(0));
+    for value in 0..100 {
+        tx.send(value);
+    }
+    rx.get();
+    tx.flush_metrics();
+    drop((tx, rx));
+    assert_eq!(recorder.len(), 0);
+}
+
+#[test]
+fn sends_are_counted_in_batches() {
+    let recorder = TestRecorder::default();
+    let (tx, _rx) = labeled(&recorder, "sends", watch::builder(), 0);
+    for value in 0..10 {
+        tx.send(value);
+    }
+    tx.update(|value| *value += 1);
+    // Too few to be handed over yet.
+    assert_eq!(recorder.counter("watch_sends", "sends"), 0);
+    tx.flush_metrics();
+    assert_eq!(recorder.counter("watch_sends", "sends"), 11);
+
+    // A full batch is handed over by the send that fills it.
+    for value in 0..64 {
+        tx.send(value);
+    }
+    assert_eq!(recorder.counter("watch_sends", "sends"), 75);
+
+    // Dropping the channel hands over the rest.
+    tx.send(0);
+    drop((tx, _rx));
+    assert_eq!(recorder.counter("watch_sends", "sends"), 76);
+}
+
+#[test]
+fn sends_from_every_thread_are_counted() {
+    let recorder = TestRecorder::default();
+    let (tx, _rx) = labeled(&recorder, "threads", watch::builder(), 0);
+    let senders: Vec<_> = (0..4)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for value in 0..1000 {
+                    tx.send(value);
+                }
+            })
+        })
+        .collect();
+    util::join_all(senders);
+    tx.flush_metrics();
+    assert_eq!(recorder.counter("watch_sends", "threads"), 4000);
+}
+
+#[test]
+fn only_waits_that_park_are_wakeups() {
+    let recorder = TestRecorder::default();
+    let (tx, mut rx) = labeled(&recorder, "wakeups", watch::builder(), 0);
+    // The initial value is new, so this does not wait.
+    assert_eq!(rx.wait(), 0);
+    tx.send(1);
+    assert_eq!(rx.wait(), 1);
+    tx.flush_metrics();
+    assert_eq!(recorder.counter("watch_wakeups", "wakeups"), 0);
+
+    let waiter = thread::spawn(move || rx.wait());
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(2);
+    assert_eq!(waiter.join().unwrap(), 2);
+    tx.flush_metrics();
+    assert_eq!(recorder.counter("watch_wakeups", "wakeups"), 1);
+}
+
+#[test]
+fn the_receiver_gauge_follows_the_count() {
+    let recorder = TestRecorder::default();
+    let (tx, rx) = labeled(&recorder, "receivers", watch::builder(), 0);
+    let subscribed = tx.subscribe();
+    let cloned = subscribed.clone();
+    assert_eq!(recorder.gauge("watch_receivers", "receivers"), 3.0);
+    drop((subscribed, cloned));
+    assert_eq!(recorder.gauge("watch_receivers", "receivers"), 1.0);
+    drop(rx);
+    assert_eq!(recorder.gauge("watch_receivers", "receivers"), 0.0);
+}
+
+#[test]
+fn the_lag_gauge_is_updated_on_flush() {
+    let recorder = TestRecorder::default();
+    let (tx, mut rx) = labeled(&recorder, "lag", watch::builder().track_lag(true), 0);
+    let slow = tx.subscribe();
+    for value in 1..=5 {
+        tx.send(value);
+    }
+    rx.get();
+    assert_eq!(recorder.gauge("watch_max_lag", "lag"), 0.0);
+    tx.flush_metrics();
+    assert_eq!(recorder.gauge("watch_max_lag", "lag"), 5.0);
+    assert_eq!(tx.max_lag(), Some(5));
+
+    drop(slow);
+    tx.flush_metrics();
+    assert_eq!(recorder.gauge("watch_max_lag", "lag"), 0.0);
+}
+
+#[test]
+fn the_lag_gauge_needs_lag_tracking() {
+    let recorder = TestRecorder::default();
+    let (tx, _rx) = labeled(&recorder, "untracked", watch::builder(), 0);
+    tx.send(1);
+    tx.flush_metrics();
+    assert_eq!(recorder.gauge("watch_max_lag", "untracked"), 0.0);
+}
+
+#[test]
+fn channels_have_their_own_series() {
+    let recorder = TestRecorder::default();
+    let (a, _a) = labeled(&recorder, "a", watch::builder(), 0);
+    let (b, _b) = labeled(&recorder, "b", watch::builder(), 0);
+    a.send(1);
+    a.send(2);
+    b.send(1);
+    let _c = b.subscribe();
+    a.flush_metrics();
+    b.flush_metrics();
+    assert_eq!(recorder.counter("watch_sends", "a"), 2);
+    assert_eq!(recorder.counter("watch_sends", "b"), 1);
+    assert_eq!(recorder.gauge("watch_receivers", "a"), 1.0);
+    assert_eq!(recorder.gauge("watch_receivers", "b"), 2.0);
+}
@@ -0,0 +1,91 @@
+//! Timeouts measured on a [`MockClock`], which only time out once the test
+//! advances the clock. These are the pattern to copy for testing code that
+//! waits with a timeout.
+#![cfg(all(feature = "test-clock", not(target_family = "wasm")))]
+
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+use watch::{MockClock, RecvTimeoutError};
+
+mod util;
+use util::eventually;
+
+#[test]
+fn wait_times_out_once_the_clock_passes_the_deadline() {
+    let clock = MockClock::new();
+    let (tx, mut rx) = watch::channel_with_clock(0, clock.clone());
+    rx.get();
+
+    let (done_tx, done_rx) = mpsc::channel();
+    let waiter = thread::spawn(move || {
+        done_tx
+            .send(rx.wait_timeout(Duration::from_secs(10)))
+            .unwrap();
+    });
+    // The deadline is taken from the clock when the wait starts, so the
+    // clock may only move once the waiter is parked.
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+
+    // Short of the deadline, the wait goes on.
+    clock.advance(Duration::from_secs(9));
+    assert!(done_rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(done_rx.recv_timeout(Duration::from_secs(10)), Ok(None));
+    waiter.join().unwrap();
+}
+
+#[test]
+fn value_sent_before_the_clock_moves_arrives_in_time() {
+    let clock = MockClock::new();
+    let (tx, mut rx) = watch::builder().clock(clock.clone()).channel(0);
+    rx.get();
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(5);
+    });
+    // The real wait is far longer than the timeout, but the clock stands
+    // still.
+    assert_eq!(rx.recv_timeout(Duration::from_nanos(1)), Ok(5));
+    sender.join().unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(1)),
+        Err(RecvTimeoutError::Closed)
+    );
+}
+
+#[test]
+fn counted_waits_use_the_clock() {
+    let clock = MockClock::new();
+    let (tx, mut rx) = watch::channel_with_clock(0, clock.clone());
+    rx.get();
+    tx.send(1);
+
+    let waiter = thread::spawn(move || rx.wait_n_updates_timeout(2, Duration::from_secs(60)));
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    clock.advance(Duration::from_secs(61));
+    assert_eq!(waiter.join().unwrap(), None);
+    drop(tx);
+}
+
+#[test]
+fn a_zero_timeout_does_not_wait() {
+    let (_tx, mut rx) = watch::channel_with_clock(0, MockClock::new());
+    rx.get();
+    assert_eq!(rx.wait_timeout(Duration::ZERO), None);
+}
+
+#[test]
+fn advancing_is_shared_by_clones() {
+    let clock = MockClock::new();
+    let before = clock.now();
+    let other = clock.clone();
+    other.advance(Duration::from_secs(3));
+    assert_eq!(clock.elapsed(), Duration::from_secs(3));
+    assert_eq!(clock.now() - before, Duration::from_secs(3));
+    assert!(clock.now() > Instant::now());
+}
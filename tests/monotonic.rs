@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+use watch::{monotonic_channel, Regression};
+
+#[test]
+fn equal_and_smaller_values_are_rejected() {
+    let (tx, mut rx) = monotonic_channel(5u32);
+    rx.get();
+    assert_eq!(tx.send(5), Err(Regression(5)));
+    assert_eq!(tx.send(3), Err(Regression(3)));
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(tx.send(6), Ok(()));
+    assert_eq!(rx.get_if_new(), Some(6));
+}
+
+#[test]
+fn equal_values_may_be_allowed() {
+    let (tx, mut rx) = monotonic_channel(5u32);
+    rx.get();
+    let tx = tx.allow_equal(true);
+    assert_eq!(tx.send(5), Ok(()));
+    // An equal value is a new version.
+    assert_eq!(rx.get_if_new(), Some(5));
+    // Clones keep the setting, but smaller values are still rejected.
+    assert_eq!(tx.clone().send(5), Ok(()));
+    assert_eq!(tx.send(4), Err(Regression(4)));
+}
+
+#[test]
+fn regressing_updates_are_reverted() {
+    let (tx, mut rx) = monotonic_channel(10i64);
+    rx.get();
+    assert_eq!(tx.update(|value| *value -= 1), Err(Regression(9)));
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), 10);
+
+    assert_eq!(tx.update(|value| *value += 1), Ok(()));
+    assert_eq!(tx.update_with(|value| value - 5), Err(Regression(6)));
+    assert_eq!(tx.update_with(|value| value + 1), Ok(()));
+    assert_eq!(rx.get(), 12);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn a_panicking_update_leaves_the_value() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    let (tx, mut rx) = monotonic_channel(1u32);
+    rx.get();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|value| {
+            *value = 100;
+            panic!("oops");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), 1);
+}
+
+#[test]
+fn values_that_cannot_be_compared_are_rejected() {
+    let (tx, mut rx) = monotonic_channel(1.0f64);
+    rx.get();
+    assert!(tx.send(f64::NAN).is_err());
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn interleaved_producers() {
+    use std::thread;
+
+    let (tx, mut rx) = monotonic_channel(0u64);
+    let other = tx.clone();
+    let consumer = thread::spawn(move || {
+        let mut last = rx.get();
+        while last < 20_000 {
+            let value = rx.wait();
+            assert!(value > last, "{} after {}", value, last);
+            last = value;
+        }
+    });
+    // One producer lags behind the other, so many of its sends regress.
+    let even = thread::spawn(move || {
+        for value in (0..=20_000).step_by(2) {
+            let _ = tx.send(value);
+        }
+    });
+    let odd = thread::spawn(move || {
+        for value in (1..20_000).step_by(2) {
+            let _ = other.send(value);
+        }
+    });
+    even.join().unwrap();
+    odd.join().unwrap();
+    consumer.join().unwrap();
+}
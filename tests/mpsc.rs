@@ -0,0 +1,182 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+use util::eventually;
+use watch::{RecvError, RecvTimeoutError};
+
+#[test]
+fn from_mpsc_starts_with_the_latest_queued_item() {
+    let (tx, rx) = mpsc::channel();
+    for i in 0..100 {
+        tx.send(i).unwrap();
+    }
+    let (mut receiver, _handle) = watch::from_mpsc(rx).unwrap();
+    // The burst is coalesced into the initial value.
+    assert_eq!(receiver.get_if_new(), Some(99));
+    assert_eq!(receiver.get_if_new(), None);
+}
+
+#[test]
+fn from_mpsc_coalesces_bursts() {
+    let (tx, rx) = mpsc::channel();
+    tx.send(0).unwrap();
+    let (mut receiver, _handle) = watch::from_mpsc(rx).unwrap();
+    let consumer = thread::spawn(move || {
+        let mut seen = Vec::new();
+        while let Ok(value) = receiver.recv() {
+            seen.push(value);
+        }
+        seen
+    });
+    for i in 1..=10_000 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+
+    let seen = consumer.join().unwrap();
+    assert_eq!(seen.last(), Some(&10_000));
+    assert!(seen.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn from_mpsc_fails_if_nothing_is_sent() {
+    let (tx, rx) = mpsc::channel::<i32>();
+    drop(tx);
+    assert!(matches!(watch::from_mpsc(rx), Err(mpsc::RecvError)));
+}
+
+#[test]
+fn from_mpsc_closes_when_the_mpsc_channel_disconnects() {
+    let (tx, rx) = mpsc::channel();
+    tx.send(1).unwrap();
+    let (mut receiver, handle) = watch::from_mpsc(rx).unwrap();
+    assert_eq!(receiver.get(), 1);
+    tx.send(2).unwrap();
+    drop(tx);
+    assert_eq!(receiver.recv(), Ok(2));
+    assert_eq!(receiver.recv(), Err(RecvError));
+    assert!(receiver.is_closed());
+    handle.stop();
+}
+
+#[test]
+fn from_mpsc_stop_interrupts_a_blocked_recv() {
+    let (tx, rx) = mpsc::channel();
+    tx.send(1).unwrap();
+    let (mut receiver, handle) = watch::from_mpsc(rx).unwrap();
+    let start = Instant::now();
+    handle.stop();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(receiver.recv(), Ok(1));
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Closed)
+    );
+    // Items sent after the bridge stopped go nowhere.
+    assert!(tx.send(2).is_err());
+}
+
+#[test]
+fn forward_to_sends_every_value_it_sees() {
+    let (tx, rx) = watch::channel(0);
+    let (mtx, mrx) = mpsc::channel();
+    let handle = rx.forward_to(mtx);
+    assert_eq!(mrx.recv(), Ok(0));
+    tx.send(1);
+    assert_eq!(mrx.recv(), Ok(1));
+    tx.send(2);
+    assert_eq!(mrx.recv(), Ok(2));
+    handle.stop();
+    tx.send(3);
+    assert!(mrx.recv().is_err());
+}
+
+#[test]
+fn forward_to_skips_values_while_the_consumer_is_slow() {
+    let (tx, rx) = watch::channel(0);
+    let (mtx, mrx) = mpsc::channel();
+    let _handle = rx.forward_to(mtx);
+    assert_eq!(mrx.recv(), Ok(0));
+
+    // The producer never waits for the forwarder, let alone the consumer.
+    let start = Instant::now();
+    for i in 1..=10_000 {
+        tx.send(i);
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+    drop(tx);
+
+    let forwarded: Vec<_> = mrx.iter().collect();
+    assert_eq!(forwarded.last(), Some(&10_000));
+    assert!(forwarded.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn forward_to_exits_when_the_watch_channel_closes() {
+    let (tx, rx) = watch::channel(0);
+    let (mtx, mrx) = mpsc::channel();
+    let handle = rx.forward_to(mtx);
+    assert_eq!(mrx.recv(), Ok(0));
+    drop(tx);
+    assert!(mrx.recv().is_err());
+    assert!(eventually(|| handle.is_finished()));
+}
+
+#[test]
+fn forward_to_exits_when_the_mpsc_receiver_is_dropped() {
+    let (tx, rx) = watch::channel(0);
+    let (mtx, mrx) = mpsc::channel();
+    let handle = rx.forward_to(mtx);
+    drop(mrx);
+    tx.send(1);
+    assert!(eventually(|| handle.is_finished()));
+}
+
+#[test]
+fn forward_to_stop_interrupts_a_blocked_wait() {
+    let (_tx, rx) = watch::channel(0);
+    let (mtx, mrx) = mpsc::channel();
+    let handle = rx.forward_to(mtx);
+    assert_eq!(mrx.recv(), Ok(0));
+    handle.stop();
+    assert!(mrx.recv().is_err());
+}
+
+#[test]
+fn dropping_the_forwarder_handle_stops_it() {
+    let (_tx, rx) = watch::channel(0);
+    let (mtx, mrx) = mpsc::channel();
+    drop(rx.forward_to(mtx));
+    assert!(mrx.iter().count() <= 1);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn stopping_a_forwarder_leaves_the_other_receivers_parked() {
+    let (tx, rx) = watch::channel(0);
+    let mut other = rx.clone();
+    other.get();
+    let waiter = thread::spawn(move || other.wait());
+    let (mtx, mrx) = mpsc::channel();
+    let handle = rx.forward_to(mtx);
+    assert_eq!(mrx.recv(), Ok(0));
+    assert!(eventually(|| tx.waiting_receivers() == 2));
+
+    let before = tx.stats();
+    handle.stop();
+    let after = tx.stats();
+    // Only the forwarder woke up, and nobody was notified through the
+    // channel.
+    assert_eq!(after.notifications, before.notifications);
+    assert_eq!(after.wakeups, before.wakeups + 1);
+    assert_eq!(tx.waiting_receivers(), 1);
+
+    tx.send(1);
+    assert_eq!(waiter.join().unwrap(), 1);
+}
@@ -0,0 +1,45 @@
+//! The methods that never block, which also work on single-threaded wasm.
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn get_and_get_if_new() {
+    let (tx, mut rx) = watch::channel(1);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(2);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), 2);
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[test]
+fn update() {
+    let (tx, mut rx) = watch::channel(vec![1]);
+    rx.get();
+    tx.update(|value| value.push(2));
+    assert_eq!(rx.get_if_new(), Some(vec![1, 2]));
+}
+
+#[test]
+fn sending_without_receivers() {
+    let (tx, rx) = watch::channel(0);
+    drop(rx);
+    tx.send(1);
+    assert!(tx.is_closed());
+    assert_eq!(tx.subscribe().get(), 1);
+}
+
+#[test]
+fn closing() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    drop(tx);
+    assert!(rx.is_closed());
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), 0);
+}
@@ -0,0 +1,244 @@
+//! Code that is generic over the channels, through `WatchPublish` and
+//! `WatchObserve`.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{cell::RefCell, collections::VecDeque, sync::Arc, thread, time::Duration};
+use watch::{WatchObserve, WatchPublish, WatchReceiver};
+
+/// A receiver that hands out the values queued in it, as a test of a
+/// library would.
+struct FakeReceiver {
+    queue: VecDeque<u32>,
+    last: u32,
+}
+
+impl FakeReceiver {
+    fn new(values: &[u32]) -> FakeReceiver {
+        FakeReceiver {
+            queue: values.iter().copied().collect(),
+            last: 0,
+        }
+    }
+}
+
+impl WatchObserve<u32> for FakeReceiver {
+    fn get(&mut self) -> u32 {
+        self.get_if_new().unwrap_or(self.last)
+    }
+
+    fn get_if_new(&mut self) -> Option<u32> {
+        let value = self.queue.pop_back()?;
+        self.queue.clear();
+        self.last = value;
+        Some(value)
+    }
+
+    fn has_changed(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    fn wait(&mut self) -> u32 {
+        self.get_if_new().expect("the fake ran out of values")
+    }
+
+    fn wait_timeout(&mut self, _: Duration) -> Option<u32> {
+        self.get_if_new()
+    }
+}
+
+/// A sender that records what it is sent.
+#[derive(Default)]
+struct FakeSender {
+    sent: RefCell<Vec<u32>>,
+}
+
+impl WatchPublish<u32> for FakeSender {
+    fn send(&self, value: u32) {
+        self.sent.borrow_mut().push(value);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut u32),
+    {
+        let mut value = self.sent.borrow().last().copied().unwrap_or(0);
+        f(&mut value);
+        self.send(value);
+    }
+}
+
+/// What a library would do with a receiver: take the current value, then
+/// the new ones until none come.
+fn drain(mut rx: impl WatchObserve<u32>) -> Vec<u32> {
+    let mut values = vec![rx.get()];
+    while let Some(value) = rx.wait_timeout(Duration::from_millis(10)) {
+        values.push(value);
+    }
+    values
+}
+
+/// A receiver of strings made from the numbers on a channel, as an adapter
+/// written outside the crate would be.
+struct Formatted {
+    inner: WatchReceiver<u32>,
+}
+
+impl WatchObserve<String> for Formatted {
+    fn get(&mut self) -> String {
+        self.inner.get().to_string()
+    }
+
+    fn get_if_new(&mut self) -> Option<String> {
+        self.inner.get_if_new().map(|value| value.to_string())
+    }
+
+    fn has_changed(&self) -> bool {
+        self.inner.has_changed()
+    }
+
+    fn wait(&mut self) -> String {
+        self.inner.wait().to_string()
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Option<String> {
+        self.inner
+            .wait_timeout(timeout)
+            .map(|value| value.to_string())
+    }
+}
+
+fn publish(tx: impl WatchPublish<u32>) {
+    tx.send(5);
+    tx.update(|value| *value += 1);
+}
+
+#[test]
+fn libraries_work_with_the_channels_of_the_crate() {
+    let (tx, rx) = watch::channel(1);
+    publish(&tx);
+    assert_eq!(drain(rx), vec![6]);
+
+    let (tx, rx) = watch::copy_channel(1);
+    publish(&tx);
+    assert_eq!(drain(rx), vec![6]);
+
+    let (tx, rx) = watch::spsc::channel(1);
+    publish(&tx);
+    assert_eq!(drain(rx), vec![6]);
+
+    let (tx, rx) = watch::channel(1);
+    publish(Arc::new(tx));
+    assert_eq!(drain(rx), vec![6]);
+}
+
+#[cfg(feature = "left-right")]
+#[test]
+fn libraries_work_with_left_right_channels() {
+    let (tx, rx) = watch::lr_channel(1);
+    publish(&tx);
+    assert_eq!(drain(rx), vec![6]);
+}
+
+#[test]
+fn libraries_work_with_fakes() {
+    assert_eq!(drain(FakeReceiver::new(&[1, 2, 3])), vec![3]);
+    assert_eq!(drain(FakeReceiver::new(&[])), vec![0]);
+
+    let tx = FakeSender::default();
+    publish(&tx);
+    assert_eq!(*tx.sent.borrow(), vec![5, 6]);
+}
+
+#[test]
+fn the_methods_mark_values_seen() {
+    let (tx, mut rx) = watch::channel(1);
+    let observer: &mut dyn WatchObserve<u32> = &mut rx;
+    assert!(observer.has_changed());
+    assert_eq!(observer.get_if_new(), Some(1));
+    assert!(!observer.has_changed());
+    assert_eq!(observer.wait_timeout(Duration::from_millis(10)), None);
+    tx.send(2);
+    assert_eq!(observer.wait(), 2);
+    assert_eq!(observer.get_if_new(), None);
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn receivers_of_different_types_can_be_kept_together() {
+    let (tx, rx) = watch::channel(0);
+    let (copy_tx, copy_rx) = watch::copy_channel(0);
+    let (spsc_tx, spsc_rx) = watch::spsc::channel(0);
+    let mut receivers: Vec<Box<dyn WatchObserve<u32>>> = vec![
+        Box::new(rx),
+        Box::new(copy_rx),
+        Box::new(spsc_rx),
+        Box::new(FakeReceiver::new(&[4])),
+    ];
+    for receiver in &mut receivers {
+        receiver.get();
+    }
+
+    let senders: Vec<&dyn WatchPublish<u32>> = vec![&tx, &copy_tx, &spsc_tx];
+    for (value, sender) in senders.into_iter().enumerate() {
+        sender.send(value as u32 + 1);
+    }
+    assert!(receivers[..3].iter().all(|receiver| receiver.has_changed()));
+    assert!(!receivers[3].has_changed());
+    let values: Vec<_> = receivers
+        .iter_mut()
+        .map(|receiver| receiver.get())
+        .collect();
+    assert_eq!(values, vec![1, 2, 3, 4]);
+
+    // A boxed receiver is still one, and can be passed on by reference.
+    tx.send(7);
+    assert_eq!(drain(&mut receivers[0]), vec![7]);
+    assert_eq!(receivers[0].get_if_new(), None);
+}
+
+#[test]
+fn plain_and_adapted_receivers_can_be_boxed_together() {
+    let (names, name_rx) = watch::channel(String::from("a"));
+    let (numbers, number_rx) = watch::channel(1u32);
+    let mut receivers: Vec<Box<dyn WatchObserve<String> + Send>> = vec![
+        name_rx.clone().boxed(),
+        name_rx.into_read_only().boxed(),
+        Formatted { inner: number_rx }.boxed(),
+    ];
+    let values: Vec<_> = receivers.iter_mut().map(|rx| rx.get()).collect();
+    assert_eq!(values, ["a", "a", "1"]);
+    assert!(receivers.iter().all(|rx| !rx.has_changed()));
+
+    names.send("b".into());
+    numbers.send(2);
+    assert!(receivers.iter().all(|rx| rx.has_changed()));
+    let values: Vec<_> = receivers.iter_mut().map(|rx| rx.wait()).collect();
+    assert_eq!(values, ["b", "b", "2"]);
+    assert!(receivers
+        .iter_mut()
+        .all(|rx| rx.wait_timeout(Duration::from_millis(10)).is_none()));
+
+    numbers.send(3);
+    let values: Vec<_> = receivers
+        .iter_mut()
+        .filter_map(|rx| rx.get_if_new())
+        .collect();
+    assert_eq!(values, ["3"]);
+}
+
+#[test]
+fn boxed_receivers_can_wait_on_other_threads() {
+    let (tx, rx) = watch::channel(String::from("a"));
+    let mut receivers = vec![rx.clone().boxed(), rx.into_read_only().boxed()];
+    for rx in &mut receivers {
+        rx.get();
+    }
+    let waiters: Vec<_> = receivers
+        .into_iter()
+        .map(|mut rx| thread::spawn(move || rx.wait()))
+        .collect();
+    tx.send("b".into());
+    for waiter in waiters {
+        assert_eq!(waiter.join().unwrap(), "b");
+    }
+}
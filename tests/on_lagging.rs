@@ -0,0 +1,105 @@
+#![cfg(all(feature = "std", target_has_atomic = "64"))]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::sync::{Arc, Mutex};
+use watch::LagReport;
+
+/// Set a hook on `tx` that collects its reports.
+fn collect(tx: &watch::WatchSender<u32>, threshold: u64) -> Arc<Mutex<Vec<LagReport>>> {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let collected = reports.clone();
+    tx.on_lagging(threshold, move |report| {
+        collected.lock().unwrap().push(report)
+    });
+    reports
+}
+
+#[test]
+fn a_stalled_receiver_is_reported_once() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    let (_, start) = rx.get_versioned();
+    let stalled = rx.clone();
+    let reports = collect(&tx, 3);
+    for value in 1..=10 {
+        tx.send(value);
+        rx.get();
+    }
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1, "{:?}", *reports);
+    assert_eq!(Some(reports[0].receiver), stalled.id());
+    assert_eq!(reports[0].seen, start);
+    assert_eq!(reports[0].version, start + 4);
+}
+
+#[test]
+fn catching_up_rearms_the_report() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    rx.get();
+    let mut stalled = rx.clone();
+    let reports = collect(&tx, 3);
+    for value in 1..=5 {
+        tx.send(value);
+        rx.get();
+    }
+    assert_eq!(reports.lock().unwrap().len(), 1);
+
+    stalled.get();
+    // The next send finds that it caught up.
+    tx.send(6);
+    assert_eq!(reports.lock().unwrap().len(), 1);
+    for value in 7..=10 {
+        tx.send(value);
+        rx.get();
+    }
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(Some(reports[1].receiver), stalled.id());
+}
+
+#[test]
+fn dropped_receivers_are_not_reported() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    rx.get();
+    let stalled = rx.clone();
+    let reports = collect(&tx, 3);
+    for value in 1..=5 {
+        tx.send(value);
+        rx.get();
+    }
+    drop(stalled);
+    // A clone made now has seen the latest value.
+    let _fresh = rx.clone();
+    for value in 6..=8 {
+        tx.send(value);
+        rx.get();
+    }
+    assert_eq!(reports.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn the_callback_may_use_the_channel() {
+    let (tx, mut rx) = watch::builder().track_lag(true).channel(0);
+    rx.get();
+    let other = tx.clone();
+    let _lagging = rx.clone();
+    let reports = Arc::new(Mutex::new(0));
+    let counted = reports.clone();
+    tx.on_lagging(0, move |_| {
+        *counted.lock().unwrap() += other.receiver_count();
+    });
+    tx.update(|value| *value += 1);
+    tx.send(2);
+    // Both receivers fell behind.
+    assert_eq!(*reports.lock().unwrap(), 4);
+}
+
+#[test]
+fn untracked_channels_never_report() {
+    let (tx, rx) = watch::channel(0);
+    tx.on_lagging(0, |_| panic!("reported"));
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.id(), None);
+}
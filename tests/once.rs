@@ -0,0 +1,110 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Barrier,
+    },
+    thread,
+    time::Duration,
+};
+use watch::{AlreadySet, RecvError, RecvTimeoutError};
+
+mod util;
+use util::join_all;
+
+#[test]
+fn exactly_one_racing_sender_wins() {
+    for _ in 0..100 {
+        let (tx, mut rx) = watch::once_channel::<usize>();
+        let barrier = Arc::new(Barrier::new(4));
+        let senders = (0..4)
+            .map(|i| {
+                let tx = tx.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    tx.send(i).is_ok()
+                })
+            })
+            .collect();
+        let wins = join_all(senders);
+        assert_eq!(wins.iter().filter(|&&won| won).count(), 1);
+        let winner = wins.iter().position(|&won| won).unwrap();
+        assert_eq!(rx.wait(), Ok(winner));
+        assert_eq!(tx.send(9), Err(AlreadySet));
+        assert_eq!(rx.get(), Some(winner));
+    }
+}
+
+#[test]
+fn waits_return_the_value_every_time() {
+    let (tx, mut rx) = watch::once_channel();
+    assert_eq!(rx.get(), None);
+    assert!(!rx.is_set());
+    let mut waiting = rx.clone();
+    let waiter = thread::spawn(move || waiting.wait());
+    tx.send("x").unwrap();
+    assert_eq!(waiter.join().unwrap(), Ok("x"));
+    for _ in 0..3 {
+        assert_eq!(rx.wait(), Ok("x"));
+        assert_eq!(rx.wait_timeout(Duration::ZERO), Ok("x"));
+    }
+    assert!(tx.is_set());
+    assert_eq!(tx.subscribe().get(), Some("x"));
+}
+
+#[test]
+fn dropping_the_sender_without_sending_closes() {
+    let (tx, mut rx) = watch::once_channel::<u8>();
+    let mut waiting = rx.clone();
+    let waiter = thread::spawn(move || waiting.wait());
+    drop(tx);
+    assert_eq!(waiter.join().unwrap(), Err(RecvError));
+    assert!(rx.is_closed());
+    assert_eq!(rx.wait(), Err(RecvError));
+    assert_eq!(
+        rx.wait_timeout(Duration::from_millis(5)),
+        Err(RecvTimeoutError::Closed)
+    );
+}
+
+#[test]
+fn a_value_sent_before_closing_is_kept() {
+    let (tx, mut rx) = watch::once_channel::<u8>();
+    tx.send(1).unwrap();
+    drop(tx);
+    assert!(rx.is_closed());
+    assert_eq!(rx.wait(), Ok(1));
+    assert_eq!(rx.wait_timeout(Duration::ZERO), Ok(1));
+}
+
+#[test]
+fn waiting_times_out() {
+    let (_tx, mut rx) = watch::once_channel::<u8>();
+    assert_eq!(
+        rx.wait_timeout(Duration::from_millis(5)),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, PartialEq)]
+struct Counted;
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        CLONES.fetch_add(1, Ordering::Relaxed);
+        Counted
+    }
+}
+
+#[test]
+fn cloning_a_receiver_does_not_clone_the_value() {
+    let (tx, rx) = watch::once_channel();
+    tx.send(Counted).unwrap();
+    let receivers: Vec<_> = (0..100).map(|_| rx.clone()).collect();
+    assert_eq!(CLONES.load(Ordering::Relaxed), 0);
+    drop(receivers);
+}
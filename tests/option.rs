@@ -0,0 +1,182 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Barrier,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+mod util;
+use util::{eventually, join_all};
+
+#[test]
+fn get_some_delivers_each_value_once() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    assert_eq!(rx.get_some(), None);
+    tx.set("a".into());
+    assert_eq!(rx.get_some().as_deref(), Some("a"));
+    assert_eq!(rx.get_some(), None);
+    assert_eq!(rx.get().as_deref(), Some("a"));
+}
+
+#[test]
+fn some_to_some_is_delivered() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    tx.set("a".into());
+    assert_eq!(rx.wait_some(), "a");
+    tx.set("b".into());
+    assert_eq!(
+        rx.wait_some_timeout(Duration::from_millis(5)).as_deref(),
+        Some("b")
+    );
+    assert_eq!(rx.wait_some_timeout(Duration::from_millis(5)), None);
+}
+
+#[test]
+fn some_to_none_is_not_delivered() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    tx.set("a".into());
+    rx.wait_some();
+    tx.clear();
+    assert_eq!(rx.get_some(), None);
+    assert_eq!(rx.get(), None);
+    assert_eq!(rx.wait_some_timeout(Duration::from_millis(5)), None);
+
+    // A value that was cleared before the receiver looked is missed.
+    tx.set("b".into());
+    tx.clear();
+    assert_eq!(rx.wait_some_timeout(Duration::from_millis(5)), None);
+    tx.set("c".into());
+    assert_eq!(rx.wait_some(), "c");
+}
+
+#[test]
+fn wait_some_waits_through_none() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    let setter = {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            // Clearing an empty channel would not wake it.
+            tx.send(None);
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            tx.set("a".into());
+        })
+    };
+    assert_eq!(rx.wait_some(), "a");
+    setter.join().unwrap();
+}
+
+#[test]
+fn the_timeout_covers_the_whole_wait() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    let stop = Arc::new(AtomicBool::new(false));
+    let clearer = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                tx.send(None);
+                thread::sleep(Duration::from_millis(1));
+            }
+        })
+    };
+    let start = Instant::now();
+    assert_eq!(rx.wait_some_timeout(Duration::from_millis(50)), None);
+    let elapsed = start.elapsed();
+    stop.store(true, Ordering::Relaxed);
+    clearer.join().unwrap();
+    assert!(elapsed >= Duration::from_millis(50), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+}
+
+#[test]
+fn clearing_an_empty_channel_is_not_a_new_value() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    let version = tx.reader().version();
+    tx.clear();
+    assert!(!rx.has_changed());
+    assert_eq!(tx.reader().version(), version);
+
+    tx.set("a".into());
+    rx.get();
+    tx.clear();
+    assert_eq!(rx.get_if_new(), Some(None));
+    tx.clear();
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn clearing_an_empty_channel_wakes_nobody() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    let waiter = thread::spawn(move || rx.wait_timeout(Duration::from_millis(50)));
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.clear();
+    assert_eq!(join_all(vec![waiter]), [None]);
+}
+
+#[test]
+fn set_if_none_only_fills_an_empty_channel() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    assert_eq!(tx.set_if_none("a".into()), Ok(()));
+    assert_eq!(rx.get_if_new(), Some(Some("a".into())));
+    assert_eq!(tx.set_if_none("b".into()), Err("b".into()));
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get().as_deref(), Some("a"));
+
+    tx.clear();
+    assert_eq!(tx.set_if_none("c".into()), Ok(()));
+    assert_eq!(rx.wait_some(), "c");
+}
+
+#[test]
+fn one_sender_wins_the_race_to_set_if_none() {
+    const SENDERS: usize = 8;
+
+    for _ in 0..50 {
+        let (tx, mut rx) = watch::channel_empty::<usize>();
+        let start = Arc::new(Barrier::new(SENDERS));
+        let senders = (0..SENDERS)
+            .map(|id| {
+                let (tx, start) = (tx.clone(), start.clone());
+                thread::spawn(move || {
+                    start.wait();
+                    tx.set_if_none(id)
+                })
+            })
+            .collect();
+        let results = join_all(senders);
+        let winners: Vec<_> = (0..SENDERS).filter(|&id| results[id].is_ok()).collect();
+        assert_eq!(winners.len(), 1, "{:?}", results);
+        // The losers get their own value back.
+        for (id, result) in results.into_iter().enumerate() {
+            assert!(result == Ok(()) || result == Err(id));
+        }
+        // Only the winner sent anything.
+        assert_eq!(rx.get_if_new(), Some(Some(winners[0])));
+        assert!(!rx.has_changed());
+    }
+}
+
+#[test]
+fn update_some_only_changes_a_value() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    assert!(!tx.update_some(|_| panic!("called on an empty channel")));
+    assert!(!rx.has_changed());
+
+    tx.set("a".into());
+    rx.get();
+    assert!(tx.update_some(|value| value.push('b')));
+    assert_eq!(rx.get_if_new(), Some(Some("ab".into())));
+}
+
+#[test]
+fn update_some_on_an_empty_channel_wakes_nobody() {
+    let (tx, mut rx) = watch::channel_empty::<String>();
+    let waiter = thread::spawn(move || rx.wait_timeout(Duration::from_millis(50)));
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    assert!(!tx.update_some(|value| value.push('x')));
+    assert_eq!(join_all(vec![waiter]), [None]);
+}
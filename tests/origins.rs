@@ -0,0 +1,101 @@
+#![cfg(all(feature = "debug-handles", feature = "std"))]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use watch::WatchSender;
+
+/// The lines where the live receivers of `tx` were created, all of which
+/// are in this file.
+fn lines<T>(tx: &WatchSender<T>) -> Vec<u32> {
+    tx.live_receiver_origins()
+        .iter()
+        .map(|origin| {
+            assert_eq!(origin.location().file(), file!());
+            origin.location().line()
+        })
+        .collect()
+}
+
+#[test]
+fn the_origins_are_where_the_receivers_were_created() {
+    let channel = line!() + 1;
+    let (tx, rx) = watch::channel(0);
+    let subscribed = tx.subscribe();
+    let cloned = rx.clone();
+    let tried = subscribed.try_clone().unwrap();
+    let (with_value, _) = tx.subscribe_with_value();
+    let read_only = tx.subscribe_read_only();
+    assert_eq!(
+        lines(&tx),
+        [
+            channel,
+            channel + 1,
+            channel + 2,
+            channel + 3,
+            channel + 4,
+            channel + 5
+        ]
+    );
+    drop((rx, subscribed, cloned, tried, with_value, read_only));
+    assert!(lines(&tx).is_empty());
+}
+
+#[test]
+fn dropped_receivers_are_forgotten() {
+    let first = line!() + 1;
+    let (tx, rx) = watch::channel(0);
+    let second = tx.subscribe();
+    let third = tx.subscribe();
+    drop(second);
+    assert_eq!(lines(&tx), [first, first + 2]);
+    drop(rx);
+    assert_eq!(lines(&tx), [first + 2]);
+    let fourth = tx.subscribe();
+    // Oldest first.
+    assert_eq!(lines(&tx), [first + 2, first + 7]);
+    drop((third, fourth));
+    assert!(tx.live_receiver_origins().is_empty());
+}
+
+#[test]
+fn builders_and_senders_turned_into_receivers() {
+    let built = line!() + 1;
+    let (tx, rx) = watch::builder().initial_seen(true).channel(0);
+    let other = tx.clone();
+    let from_sender = other.into_receiver();
+    assert_eq!(lines(&tx), [built, built + 2]);
+    drop((rx, from_sender));
+}
+
+#[test]
+fn a_receiver_that_is_not_taken_apart_stays_listed() {
+    let created = line!() + 1;
+    let (tx, rx) = watch::channel(0);
+    let subscribed = tx.subscribe();
+    // The channel has another receiver, so this fails and gives it back.
+    let rx = rx.try_into_inner().unwrap_err();
+    assert_eq!(lines(&tx), [created, created + 1]);
+    drop((rx, subscribed));
+}
+
+#[test]
+fn origins_are_displayed_as_the_location() {
+    let line = line!() + 1;
+    let (tx, _rx) = watch::channel(0);
+    let origins = tx.live_receiver_origins();
+    assert_eq!(
+        origins[0].to_string(),
+        format!("{}:{}:{}", file!(), line, 21)
+    );
+}
+
+#[test]
+fn each_channel_lists_its_own_receivers() {
+    let (a, _a_rx) = watch::channel(0);
+    let line = line!() + 1;
+    let (b, _b_rx) = watch::channel(0);
+    let _b_sub = b.subscribe();
+    assert_eq!(lines(&a), [line - 2]);
+    assert_eq!(lines(&b), [line, line + 1]);
+}
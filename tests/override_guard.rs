@@ -0,0 +1,73 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn the_value_is_restored_on_drop() {
+    let (tx, mut rx) = watch::channel(1);
+    rx.get();
+    let guard = tx.scoped_override(99);
+    assert!(guard.will_restore());
+    assert_eq!(rx.get_if_new(), Some(99));
+    drop(guard);
+    // The restore is a new value.
+    assert_eq!(rx.get_if_new(), Some(1));
+}
+
+#[test]
+fn a_send_during_the_override_wins() {
+    let (tx, mut rx) = watch::channel(1);
+    let other = tx.clone();
+    let guard = tx.scoped_override(99);
+    other.send(5);
+    assert!(!guard.will_restore());
+    rx.get();
+    drop(guard);
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), 5);
+}
+
+#[test]
+fn an_update_during_the_override_wins() {
+    let (tx, mut rx) = watch::channel(1);
+    let guard = tx.scoped_override(99);
+    tx.update(|value| *value += 1);
+    assert!(!guard.will_restore());
+    drop(guard);
+    assert_eq!(rx.get(), 100);
+}
+
+#[test]
+fn sending_an_equal_value_still_wins() {
+    let (tx, mut rx) = watch::channel(1);
+    let guard = tx.scoped_override(99);
+    tx.send(99);
+    assert!(!guard.will_restore());
+    drop(guard);
+    assert_eq!(rx.get(), 99);
+}
+
+#[test]
+fn receivers_can_see_both_changes() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).history(4).channel(0);
+    drop(tx.scoped_override(1));
+    assert_eq!(rx.missed_values(), (vec![1, 0], 0));
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn the_value_is_restored_during_a_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    let (tx, mut rx) = watch::channel(String::from("normal"));
+    rx.get();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _guard = tx.scoped_override("maintenance".into());
+        assert_eq!(rx.get(), "maintenance");
+        panic!("boom");
+    }));
+    assert!(result.is_err());
+    assert_eq!(rx.get_if_new().as_deref(), Some("normal"));
+    assert!(!rx.is_poisoned());
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::sync::Arc;
+use watch::OwnedWatchRef;
+
+/// A type that stores the handle, as the request needs.
+struct Holder {
+    settings: OwnedWatchRef<Vec<u32>>,
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn handles_are_send_and_sync() {
+    assert_send_sync::<OwnedWatchRef<Vec<u32>>>();
+    assert_send_sync::<Holder>();
+}
+
+#[test]
+fn a_handle_marks_the_value_seen() {
+    let (tx, mut rx) = watch::channel(vec![1u32]);
+    let holder = Holder {
+        settings: rx.borrow_owned(),
+    };
+    assert!(!rx.has_changed());
+    assert_eq!(*holder.settings, [1]);
+    tx.send(vec![2]);
+    assert!(rx.has_changed());
+    let newer = rx.borrow_owned();
+    assert_eq!(newer.version(), holder.settings.version() + 1);
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[test]
+fn senders_do_not_wait_for_handles() {
+    let (tx, mut rx) = watch::channel(String::from("first"));
+    let handle = rx.borrow_owned();
+    // Sending and updating never block on the handle, and do not change
+    // the value behind it.
+    tx.send(String::from("second"));
+    tx.update(|value| value.push('!'));
+    assert_eq!(*handle, "first");
+    assert_eq!(rx.get(), "second!");
+}
+
+#[test]
+fn the_value_lives_as_long_as_a_handle() {
+    let (tx, mut rx) = watch::channel(vec![1u32]);
+    let handle = rx.borrow_owned();
+    let clone = handle.clone();
+    assert_eq!(clone.version(), handle.version());
+    drop((tx, rx));
+    let shared = handle.into_shared();
+    assert_eq!(Arc::strong_count(&shared), 2);
+    drop(clone);
+    assert_eq!(Arc::try_unwrap(shared), Ok(vec![1]));
+}
+
+#[test]
+fn debug_shows_the_value_and_version() {
+    let (_tx, mut rx) = watch::channel(7);
+    let handle = rx.borrow_owned();
+    assert_eq!(
+        format!("{:?}", handle),
+        format!(
+            "OwnedWatchRef {{ value: 7, version: {} }}",
+            handle.version()
+        )
+    );
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn handles_move_between_threads() {
+    use std::thread;
+
+    let (tx, mut rx) = watch::channel(vec![1u32, 2, 3]);
+    let holder = Holder {
+        settings: rx.borrow_owned(),
+    };
+    let reader = thread::spawn(move || holder.settings.iter().sum::<u32>());
+    tx.send(vec![10]);
+    assert_eq!(reader.join().unwrap(), 6);
+}
@@ -0,0 +1,100 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+use watch::{RecvError, RecvTimeoutError};
+
+#[test]
+fn each_endpoint_sees_the_other() {
+    let (mut controller, mut worker) = watch::channel_pair("idle", 0u32);
+    // Neither has seen the starting state of its peer.
+    assert!(controller.peer_changed());
+    assert_eq!(controller.peer_if_new(), Some(0));
+    assert_eq!(worker.peer_if_new(), Some("idle"));
+    assert_eq!(worker.peer_if_new(), None);
+
+    controller.publish("run");
+    assert!(!controller.peer_changed());
+    assert_eq!(worker.peer(), "run");
+    worker.publish(1);
+    assert_eq!(controller.peer_if_new(), Some(1));
+    assert_eq!(controller.peer(), 1);
+}
+
+#[test]
+fn dropping_an_endpoint_closes_one_direction() {
+    let (mut a, b) = watch::channel_pair(1, 2);
+    let (b_sender, mut b_receiver) = b.split();
+    a.peer();
+    drop(b_sender);
+    assert!(a.is_peer_closed());
+    assert_eq!(a.wait_peer(), Err(RecvError));
+    assert_eq!(a.peer(), 2);
+
+    // The other direction still works.
+    assert!(!b_receiver.is_closed());
+    a.publish(3);
+    assert_eq!(b_receiver.recv(), Ok(3));
+    drop(a);
+    assert_eq!(b_receiver.recv(), Err(RecvError));
+}
+
+#[test]
+fn publishing_to_a_peer_that_is_gone() {
+    let (a, b) = watch::channel_pair(1, 2);
+    drop(b);
+    assert!(a.is_peer_closed());
+    a.publish(3);
+}
+
+#[test]
+fn clones_keep_a_side_open() {
+    let (a, mut b) = watch::channel_pair(1, 2);
+    let clone = a.clone();
+    drop(a);
+    assert!(!b.is_peer_closed());
+    clone.publish(5);
+    drop(clone);
+    assert!(b.is_peer_closed());
+    assert_eq!(b.wait_peer_timeout(Duration::from_millis(10)), Ok(5));
+    assert_eq!(
+        b.wait_peer_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Closed)
+    );
+}
+
+#[test]
+fn timing_out_on_a_quiet_peer() {
+    let (_a, mut b) = watch::channel_pair(1, 2);
+    assert_eq!(b.wait_peer_timeout(Duration::from_millis(10)), Ok(1));
+    assert_eq!(
+        b.wait_peer_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+#[test]
+fn controller_and_worker() {
+    let (mut controller, mut worker) = watch::channel_pair("idle", 0u32);
+    controller.peer();
+    let worker = thread::spawn(move || {
+        while let Ok(desired) = worker.wait_peer() {
+            match desired {
+                "run" => worker.publish(1),
+                "stop" => {
+                    worker.publish(2);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        worker
+    });
+    controller.publish("run");
+    assert_eq!(controller.wait_peer(), Ok(1));
+    controller.publish("stop");
+    assert_eq!(controller.wait_peer(), Ok(2));
+    let worker = worker.join().unwrap();
+    assert!(!controller.is_peer_closed());
+    drop(worker);
+    assert_eq!(controller.wait_peer(), Err(RecvError));
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn the_first_value_comes_without_a_previous_one() {
+    let (tx, rx) = watch::channel(0);
+    let mut rx = rx.pairwise();
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some((None, 0)));
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(1);
+    assert_eq!(rx.get_if_new(), Some((Some(0), 1)));
+    tx.update(|value| *value += 1);
+    assert_eq!(rx.get_if_new(), Some((Some(1), 2)));
+}
+
+#[test]
+fn adapters_advancing_at_different_rates() {
+    let (tx, rx) = watch::channel(0);
+    let mut fast = rx.clone().pairwise();
+    let mut slow = rx.pairwise();
+    assert_eq!(fast.get_if_new(), Some((None, 0)));
+    for value in 1..=3 {
+        tx.send(value);
+        assert_eq!(fast.get_if_new(), Some((Some(value - 1), value)));
+    }
+    // The slow one never saw the values in between.
+    assert_eq!(slow.get_if_new(), Some((None, 3)));
+
+    tx.send(4);
+    assert_eq!(fast.get_if_new(), Some((Some(3), 4)));
+    tx.send(5);
+    tx.send(6);
+    assert_eq!(slow.get_if_new(), Some((Some(3), 6)));
+    assert_eq!(fast.get_if_new(), Some((Some(4), 6)));
+}
+
+#[test]
+fn other_receivers_are_not_affected() {
+    let (tx, mut plain) = watch::channel(0);
+    let mut pairwise = plain.clone().pairwise();
+    plain.get();
+    tx.send(1);
+    assert_eq!(pairwise.get_if_new(), Some((None, 1)));
+    assert_eq!(plain.get_if_new(), Some(1));
+    assert_eq!(pairwise.get_if_new(), None);
+}
+
+#[test]
+fn a_receiver_keeps_what_it_has_seen() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let mut rx = rx.pairwise();
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(1);
+    assert_eq!(rx.get_if_new(), Some((None, 1)));
+
+    let mut rx = rx.into_inner();
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(2);
+    // The previous value went with the adapter.
+    assert_eq!(rx.pairwise().get_if_new(), Some((None, 2)));
+}
+
+#[test]
+fn the_previous_value_is_shared() {
+    use std::sync::Arc;
+
+    let first = Arc::new(());
+    let (tx, rx) = watch::channel(first.clone());
+    let mut rx = rx.pairwise();
+    rx.get_if_new();
+    tx.send(Arc::new(()));
+    // Held by this test and by the adapter.
+    assert_eq!(Arc::strong_count(&first), 2);
+    // Once it is the previous value and has been handed out, the adapter
+    // lets go of it.
+    let (previous, _) = rx.get_if_new().unwrap();
+    drop(previous);
+    assert_eq!(Arc::strong_count(&first), 1);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn waiting_for_pairs() {
+    use std::{thread, time::Duration};
+    use watch::RecvError;
+
+    let (tx, rx) = watch::channel(0);
+    let mut rx = rx.pairwise();
+    assert_eq!(rx.wait(), (None, 0));
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(1);
+        thread::sleep(Duration::from_millis(20));
+        tx.send(2);
+    });
+    assert_eq!(rx.wait(), (Some(0), 1));
+    assert_eq!(rx.recv(), Ok((Some(1), 2)));
+    sender.join().unwrap();
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn a_value_sent_before_closing_is_received() {
+    use watch::RecvError;
+
+    let (tx, rx) = watch::channel(0);
+    let mut rx = rx.pairwise();
+    rx.get_if_new();
+    tx.send(1);
+    drop(tx);
+    assert_eq!(rx.recv(), Ok((Some(0), 1)));
+    assert_eq!(rx.recv(), Err(RecvError));
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::collections::{BTreeMap, HashMap};
+use watch::ApplyPatch;
+
+/// A state whose patches can come in any order: it keeps the largest.
+#[derive(Clone, Debug, PartialEq)]
+struct Max(u32);
+
+impl ApplyPatch<u32> for Max {
+    fn apply(&mut self, patch: u32) {
+        self.0 = self.0.max(patch);
+    }
+}
+
+#[test]
+fn map_patches_insert_and_remove() {
+    let (tx, mut rx) = watch::patch_channel(HashMap::new());
+    rx.get();
+    tx.send_patch(("a", Some(1)));
+    assert_eq!(rx.get_if_new(), Some(HashMap::from([("a", 1)])));
+    tx.send_patch(("a", None));
+    assert_eq!(rx.get_if_new(), Some(HashMap::new()));
+    // Removing a missing key is still a new version.
+    tx.send_patch(("missing", None));
+    assert!(rx.has_changed());
+}
+
+#[test]
+fn overlapping_patches_apply_in_order() {
+    let (tx, mut rx) = watch::patch_channel(BTreeMap::new());
+    rx.get();
+    tx.send_patches(vec![
+        ("b", Some(2)),
+        ("a", Some(1)),
+        ("a", Some(3)),
+        ("b", None),
+    ]);
+    assert_eq!(rx.get_if_new(), Some(BTreeMap::from([("a", 3)])));
+    // The batch was one version.
+    assert_eq!(rx.get_if_new(), None);
+    tx.send_patches(Vec::new());
+    assert!(rx.has_changed());
+}
+
+#[test]
+fn out_of_order_patches() {
+    let (tx, mut rx) = watch::patch_channel(Max(0));
+    for patch in [5, 2, 9, 1] {
+        tx.send_patch(patch);
+    }
+    assert_eq!(rx.get(), Max(9));
+}
+
+#[test]
+fn a_held_value_is_not_patched() {
+    let (tx, mut rx) = watch::patch_channel(Max(1));
+    let held = rx.get_shared();
+    tx.send_patch(10);
+    assert_eq!(*held, Max(1));
+    assert_eq!(rx.get(), Max(10));
+}
+
+#[test]
+fn whole_values_and_subscribers() {
+    let (tx, mut rx) = watch::patch_channel(Max(3));
+    tx.send(Max(1));
+    let mut late = tx.subscribe();
+    assert_eq!(late.get_if_new(), None);
+    tx.send_patch(2);
+    assert_eq!(rx.get(), Max(2));
+    assert_eq!(late.get_if_new(), Some(Max(2)));
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn concurrent_senders_lose_no_patch() {
+    use std::thread;
+
+    let (tx, mut rx) = watch::patch_channel(BTreeMap::new());
+    let senders: Vec<_> = (0..4)
+        .map(|thread| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..500 {
+                    tx.send_patch((thread * 1000 + i, Some(i)));
+                    // Every sender also removes a key of its own.
+                    if i % 2 == 1 {
+                        tx.send_patch((thread * 1000 + i - 1, None));
+                    }
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.join().unwrap();
+    }
+    let map = rx.get();
+    assert_eq!(map.len(), 4 * 250);
+    assert!(map
+        .iter()
+        .all(|(key, value)| key % 1000 == *value && value % 2 == 1));
+}
@@ -0,0 +1,163 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+use watch::{WatchReceiver, WatchSender};
+
+mod util;
+use util::eventually;
+
+const SHORT: Duration = Duration::from_millis(50);
+
+/// Park a thread in `wait`, and return where its value arrives.
+fn park(tx: &WatchSender<u32>, rx: &WatchReceiver<u32>) -> Receiver<u32> {
+    let (done_tx, done) = mpsc::channel();
+    let mut rx = rx.clone();
+    let waiting = tx.waiting_receivers();
+    thread::spawn(move || {
+        let _ = done_tx.send(rx.wait());
+    });
+    assert!(eventually(|| tx.waiting_receivers() == waiting + 1));
+    done
+}
+
+#[test]
+fn a_waiter_parked_across_the_pause_wakes_once_with_the_last_value() {
+    let (tx, rx) = watch::builder().initial_seen(true).channel(0);
+    let done = park(&tx, &rx);
+    let paused = tx.pause_notifications();
+    for value in 1..=100 {
+        tx.send(value);
+    }
+    tx.update(|value| *value += 1);
+    assert!(done.recv_timeout(SHORT).is_err());
+    assert_eq!(tx.waiting_receivers(), 1);
+
+    drop(paused);
+    assert_eq!(done.recv(), Ok(101));
+}
+
+#[test]
+fn overlapping_guards_of_two_senders() {
+    let (first, rx) = watch::builder().initial_seen(true).channel(0);
+    let second = first.clone();
+    let done = park(&first, &rx);
+
+    let first_paused = first.pause_notifications();
+    first.send(1);
+    let second_paused = second.pause_notifications();
+    // The first guard going does not resume while the second is alive.
+    drop(first_paused);
+    second.send(2);
+    assert!(done.recv_timeout(SHORT).is_err());
+
+    drop(second_paused);
+    assert_eq!(done.recv(), Ok(2));
+}
+
+#[test]
+fn nested_guards_of_one_sender() {
+    let (tx, rx) = watch::builder().initial_seen(true).channel(0);
+    let done = park(&tx, &rx);
+    let outer = tx.pause_notifications();
+    let inner = tx.pause_notifications();
+    tx.send(1);
+    drop(inner);
+    assert!(done.recv_timeout(SHORT).is_err());
+    drop(outer);
+    assert_eq!(done.recv(), Ok(1));
+}
+
+#[test]
+fn reads_see_the_values_sent_while_paused() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let _paused = tx.pause_notifications();
+    let version = tx.reader().version();
+    tx.send(1);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(1));
+    tx.send(2);
+    assert_eq!(tx.reader().version(), version + 2);
+
+    // A wait that starts after the send returns at once.
+    assert_eq!(rx.wait(), 2);
+}
+
+#[test]
+fn a_pause_without_sends_wakes_no_one() {
+    let (tx, rx) = watch::builder().initial_seen(true).channel(0);
+    let done = park(&tx, &rx);
+    drop(tx.pause_notifications());
+    assert!(done.recv_timeout(SHORT).is_err());
+    assert_eq!(tx.waiting_receivers(), 1);
+    tx.send(1);
+    assert_eq!(done.recv(), Ok(1));
+}
+
+#[test]
+fn closing_while_paused_wakes_the_waiters() {
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(0);
+    let paused = tx.pause_notifications();
+    let waiter = thread::spawn(move || rx.recv());
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    // The guard does not count as a sender.
+    drop(tx);
+    assert!(waiter.join().unwrap().is_err());
+    drop(paused);
+}
+
+#[test]
+fn the_guard_outlives_the_sender() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let paused = tx.pause_notifications();
+    tx.send(1);
+    drop(tx);
+    drop(paused);
+    assert_eq!(rx.get_if_new(), Some(1));
+}
+
+#[test]
+fn a_manual_notify_channel_leaves_the_wakeup_to_the_pump() {
+    let (tx, rx) = watch::builder()
+        .initial_seen(true)
+        .manual_notify(true)
+        .channel(0);
+    let done = park(&tx, &rx);
+    let paused = tx.pause_notifications();
+    tx.send(1);
+    drop(paused);
+    assert!(done.recv_timeout(SHORT).is_err());
+    assert!(tx.pump());
+    assert_eq!(done.recv(), Ok(1));
+}
+
+#[cfg(feature = "embedded-async")]
+#[test]
+fn a_waiting_task_is_woken_once() {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll},
+    };
+    use util::task::counting_waker;
+
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(0);
+    let (wakes, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut changed = pin!(rx.changed());
+    assert_eq!(changed.as_mut().poll(&mut cx), Poll::Pending);
+
+    let paused = tx.pause_notifications();
+    for value in 1..=10 {
+        tx.send(value);
+    }
+    assert_eq!(wakes.count(), 0);
+    drop(paused);
+    assert_eq!(wakes.count(), 1);
+    assert_eq!(changed.as_mut().poll(&mut cx), Poll::Ready(Ok(10)));
+}
@@ -0,0 +1,197 @@
+#![cfg(all(feature = "persist", not(target_family = "wasm")))]
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+use watch::persistent_channel;
+
+mod util;
+use util::eventually;
+
+/// A directory of its own for each test, removed when dropped.
+struct Dir(PathBuf);
+
+impl Dir {
+    fn new(test: &str) -> Dir {
+        let path = std::env::temp_dir().join(format!("watch-persist-{}-{}", process::id(), test));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Dir(path)
+    }
+
+    fn file(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn starts_with_the_default_without_a_file() {
+    let dir = Dir::new("default");
+    let path = dir.file("value.json");
+    let (_tx, mut rx, handle) = persistent_channel(&path, 5u32).unwrap();
+    assert_eq!(rx.get(), 5);
+    handle.flush().unwrap();
+    // The default is not written until a value is sent.
+    assert!(!path.exists());
+    handle.stop().unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn values_survive_a_restart() {
+    let dir = Dir::new("restart");
+    let path = dir.file("config.json");
+    let (tx, _rx, handle) = persistent_channel(&path, BTreeMap::<String, u32>::new()).unwrap();
+    tx.update(|map| {
+        map.insert("a".into(), 1);
+    });
+    tx.update(|map| {
+        map.insert("b".into(), 2);
+    });
+    handle.flush().unwrap();
+    assert_eq!(read(&path), r#"{"a":1,"b":2}"#);
+    drop(tx);
+    handle.stop().unwrap();
+
+    let (tx, mut rx, handle) = persistent_channel(&path, BTreeMap::new()).unwrap();
+    let expected: BTreeMap<String, u32> = [("a".into(), 1), ("b".into(), 2)].into();
+    assert_eq!(rx.get(), expected);
+    tx.send(BTreeMap::new());
+    handle.stop().unwrap();
+    assert_eq!(read(&path), "{}");
+}
+
+#[test]
+fn bursts_end_with_the_latest_value() {
+    let dir = Dir::new("burst");
+    let path = dir.file("counter.json");
+    let (tx, _rx, handle) = persistent_channel(&path, 0u32).unwrap();
+    for value in 1..=1000 {
+        tx.send(value);
+    }
+    handle.flush().unwrap();
+    assert_eq!(read(&path), "1000");
+    // No temporary file is left behind.
+    assert!(!dir.file("counter.json.tmp").exists());
+}
+
+#[test]
+fn stopping_saves_the_latest_value() {
+    let dir = Dir::new("stop");
+    let path = dir.file("value.json");
+    let (tx, _rx, handle) = persistent_channel(&path, 0u32).unwrap();
+    tx.send(1);
+    tx.send(2);
+    handle.stop().unwrap();
+    assert_eq!(read(&path), "2");
+}
+
+#[test]
+fn dropping_the_handle_saves_the_latest_value() {
+    let dir = Dir::new("drop");
+    let path = dir.file("value.json");
+    let (tx, _rx, handle) = persistent_channel(&path, 0u32).unwrap();
+    tx.send(3);
+    drop(handle);
+    assert!(eventually(|| path.exists()));
+    assert_eq!(read(&path), "3");
+}
+
+#[test]
+fn dropping_the_senders_stops_the_thread() {
+    let dir = Dir::new("senders");
+    let path = dir.file("value.json");
+    let (tx, _rx, handle) = persistent_channel(&path, 0u32).unwrap();
+    tx.send(4);
+    drop(tx);
+    assert!(eventually(|| handle.is_finished()));
+    assert_eq!(read(&path), "4");
+    handle.flush().unwrap();
+}
+
+#[test]
+fn a_truncated_temporary_file_is_ignored() {
+    let dir = Dir::new("crash");
+    let path = dir.file("value.json");
+    let (tx, _rx, handle) = persistent_channel(&path, Vec::<u32>::new()).unwrap();
+    tx.send(vec![1, 2, 3]);
+    handle.stop().unwrap();
+
+    // A crash in the middle of a save leaves a partial temporary file.
+    fs::write(dir.file("value.json.tmp"), "[4,5").unwrap();
+    let (tx, mut rx, handle) = persistent_channel(&path, Vec::new()).unwrap();
+    assert_eq!(rx.get(), vec![1, 2, 3]);
+
+    // The next save replaces it.
+    tx.send(vec![6]);
+    handle.stop().unwrap();
+    assert_eq!(read(&path), "[6]");
+    assert!(!dir.file("value.json.tmp").exists());
+}
+
+#[test]
+fn a_corrupt_file_is_an_error() {
+    let dir = Dir::new("corrupt");
+    let path = dir.file("value.json");
+    fs::write(&path, "[1,").unwrap();
+    let error = persistent_channel(&path, Vec::<u32>::new()).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    // So is a value of another type.
+    fs::write(&path, r#""text""#).unwrap();
+    let error = persistent_channel(&path, 0u32).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    // The file is left as it was.
+    assert_eq!(read(&path), r#""text""#);
+}
+
+#[test]
+fn a_failed_save_is_reported_once() {
+    let dir = Dir::new("failed");
+    let path = dir.file("missing").join("value.json");
+    let (tx, _rx, handle) = persistent_channel(&path, 0u32).unwrap();
+    tx.send(1);
+    assert!(handle.flush().is_err());
+    assert!(handle.flush().is_ok());
+
+    // Once the directory exists, saves succeed again.
+    fs::create_dir(dir.file("missing")).unwrap();
+    tx.send(2);
+    handle.flush().unwrap();
+    assert_eq!(read(&path), "2");
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn stopping_leaves_the_other_receivers_parked() {
+    use std::thread;
+
+    let dir = Dir::new("parked");
+    let (tx, mut rx, handle) = persistent_channel(dir.file("value.json"), 0u32).unwrap();
+    rx.get();
+    let waiter = thread::spawn(move || rx.wait());
+    assert!(eventually(|| tx.waiting_receivers() == 2));
+
+    let before = tx.stats();
+    handle.stop().unwrap();
+    let after = tx.stats();
+    assert_eq!(after.notifications, before.notifications);
+    assert_eq!(after.wakeups, before.wakeups + 1);
+    assert_eq!(tx.waiting_receivers(), 1);
+
+    tx.send(1);
+    assert_eq!(waiter.join().unwrap(), 1);
+}
@@ -0,0 +1,136 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+use watch::{
+    backend::{self, RawCondvar},
+    Poisoned, WatchSender,
+};
+
+fn poison<T: Clone, C: RawCondvar>(tx: &WatchSender<T, C>) {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|_| panic!("in update"));
+    }));
+    assert!(result.is_err());
+}
+
+fn checked_methods_report_poisoning<C>()
+where
+    C: RawCondvar + Send + Sync + 'static,
+    C::RawMutex: Send + Sync,
+    C::RawRwLock: Send + Sync,
+{
+    let (tx, mut rx) = backend::channel::<C, _>(1);
+    assert_eq!(rx.get_checked(), Ok(1));
+    assert!(!tx.is_poisoned() && !rx.is_poisoned());
+
+    let mut waiter = rx.clone();
+    let waiter = thread::spawn(move || waiter.wait_checked());
+    thread::sleep(Duration::from_millis(20));
+    poison(&tx);
+    assert_eq!(waiter.join().unwrap(), Err(Poisoned));
+
+    assert!(tx.is_poisoned() && rx.is_poisoned());
+    assert_eq!(rx.get_checked(), Err(Poisoned));
+    // A failed read leaves the value new.
+    assert!(rx.has_changed());
+    assert_eq!(tx.send_checked(2), Err(Poisoned));
+    // The unchecked methods ignore the poisoning.
+    assert_eq!(rx.get(), 1);
+    tx.send(3);
+    assert!(tx.is_poisoned());
+
+    rx.clear_poison();
+    assert!(!tx.is_poisoned());
+    assert_eq!(rx.get_checked(), Ok(3));
+    assert_eq!(tx.send_checked(4), Ok(()));
+    assert_eq!(rx.get_checked(), Ok(4));
+
+    poison(&tx);
+    tx.clear_poison();
+    assert!(!rx.is_poisoned());
+}
+
+#[test]
+fn default_backend() {
+    checked_methods_report_poisoning::<backend::DefaultCondvar>();
+}
+
+#[test]
+fn std_backend() {
+    checked_methods_report_poisoning::<backend::StdCondvar>();
+}
+
+#[cfg(feature = "parking_lot")]
+#[test]
+fn parking_lot_backend() {
+    checked_methods_report_poisoning::<backend::ParkingLotCondvar>();
+}
+
+#[test]
+fn panics_outside_update_do_not_poison() {
+    let (tx, mut rx) = watch::channel(1);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.update_with(|_| -> i32 { panic!("in update_with") });
+    }));
+    assert!(result.is_err());
+    rx.get();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        rx.wait_with(|| panic!("while idle"));
+    }));
+    assert!(result.is_err());
+    assert!(!tx.is_poisoned());
+    assert_eq!(tx.send_checked(2), Ok(()));
+}
+
+fn a_panicking_update_keeps_its_changes<C>()
+where
+    C: RawCondvar + Send + Sync + 'static,
+    C::RawMutex: Send + Sync,
+    C::RawRwLock: Send + Sync,
+{
+    let (tx, mut rx) = backend::channel::<C, _>(vec![1]);
+    rx.get();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|value| {
+            value.push(2);
+            panic!("in update");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(rx.is_poisoned());
+    // The update counts as a new value, with the changes made before the
+    // panic.
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), [1, 2]);
+
+    // The unchecked methods go on as before, and keep the poisoning.
+    tx.update(|value| value.push(3));
+    assert_eq!(rx.get(), [1, 2, 3]);
+    assert!(tx.is_poisoned());
+}
+
+#[test]
+fn a_panicking_update_keeps_its_changes_on_every_backend() {
+    a_panicking_update_keeps_its_changes::<backend::StdCondvar>();
+    #[cfg(feature = "parking_lot")]
+    a_panicking_update_keeps_its_changes::<backend::ParkingLotCondvar>();
+}
+
+#[test]
+fn every_handle_sees_the_poisoning() {
+    let (tx, rx) = watch::channel(0);
+    let subscribed = tx.subscribe();
+    let sender = rx.new_sender();
+    let poisoner = tx.clone();
+    thread::spawn(move || poison(&poisoner)).join().unwrap();
+    assert!(rx.is_poisoned() && subscribed.is_poisoned() && sender.is_poisoned());
+    assert!(rx.clone().is_poisoned());
+
+    // Clearing it through any handle clears it for all of them.
+    subscribed.clear_poison();
+    assert!(!tx.is_poisoned() && !rx.is_poisoned() && !sender.is_poisoned());
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn nothing_before_the_first_send() {
+    let (_tx, mut rx) = watch::builder().keep_previous(true).channel(1);
+    assert_eq!(rx.get_with_previous(), (None, 1));
+}
+
+#[test]
+fn sends_and_updates_keep_what_they_replace() {
+    let (tx, mut rx) = watch::builder().keep_previous(true).channel(1);
+    tx.send(2);
+    assert_eq!(rx.get_with_previous(), (Some(1), 2));
+    tx.update(|value| *value += 1);
+    assert_eq!(rx.get_with_previous(), (Some(2), 3));
+    // Reading again gives the same pair.
+    assert_eq!(rx.get_with_previous(), (Some(2), 3));
+}
+
+#[test]
+fn previous_is_right_before_the_latest_version() {
+    let (tx, mut rx) = watch::builder().keep_previous(true).channel(1);
+    rx.get();
+    // The receiver skips 2 to 4, and is given the value right before the
+    // latest one rather than the one it saw last.
+    for value in 2..=5 {
+        tx.send(value);
+    }
+    assert_eq!(rx.get_with_previous(), (Some(4), 5));
+}
+
+#[test]
+fn channels_that_do_not_keep_it() {
+    let (tx, mut rx) = watch::channel(1);
+    tx.send(2);
+    assert_eq!(rx.get_with_previous(), (None, 2));
+    let (tx, mut rx) = watch::builder().keep_previous(false).channel(1);
+    tx.send(2);
+    assert_eq!(rx.get_with_previous(), (None, 2));
+}
+
+#[test]
+fn the_kept_value_does_not_hold_up_into_inner() {
+    let (tx, rx) = watch::builder()
+        .keep_previous(true)
+        .channel(String::from("a"));
+    tx.send(String::from("b"));
+    drop(rx);
+    assert_eq!(tx.try_into_inner().ok().as_deref(), Some("b"));
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod threads {
+    use std::thread;
+
+    #[test]
+    fn waiting_gives_the_pair() {
+        let (tx, mut rx) = watch::builder().keep_previous(true).channel(1);
+        rx.get();
+        let waiter = thread::spawn(move || rx.wait_with_previous());
+        tx.send(2);
+        let (previous, value) = waiter.join().unwrap();
+        // The waiter may only have woken after the second send.
+        assert_eq!(previous, Some(value - 1));
+        assert!(value >= 2);
+    }
+
+    #[test]
+    fn an_unseen_value_is_returned_at_once() {
+        let (tx, mut rx) = watch::builder().keep_previous(true).channel(1);
+        assert_eq!(rx.wait_with_previous(), (None, 1));
+        tx.send(2);
+        tx.send(3);
+        assert_eq!(rx.wait_with_previous(), (Some(2), 3));
+    }
+
+    #[test]
+    fn pairs_are_consistent_while_sending() {
+        let (tx, mut rx) = watch::builder().keep_previous(true).channel(0u64);
+        let sender = thread::spawn(move || {
+            for value in 1..=50_000 {
+                tx.send(value);
+            }
+        });
+        let mut last = 0;
+        while !sender.is_finished() {
+            if let (Some(previous), value) = rx.get_with_previous() {
+                assert_eq!(previous + 1, value);
+                assert!(value >= last);
+                last = value;
+            }
+        }
+        sender.join().unwrap();
+        assert_eq!(rx.get_with_previous(), (Some(49_999), 50_000));
+    }
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use watch::{ReadOnlyWatchReceiver, WatchSender};
+
+/// A component that may follow the configuration, but not change it.
+struct Consumer {
+    config: ReadOnlyWatchReceiver<u32>,
+}
+
+impl Consumer {
+    fn latest(&mut self) -> u32 {
+        self.config.get()
+    }
+}
+
+/// The component that owns the configuration.
+struct Owner {
+    config: WatchSender<u32>,
+}
+
+#[test]
+fn consumers_follow_the_owner() {
+    let (tx, rx) = watch::channel(1);
+    let owner = Owner { config: tx };
+    let mut first = Consumer {
+        config: rx.into_read_only(),
+    };
+    let mut second = Consumer {
+        config: owner.config.subscribe_read_only(),
+    };
+    assert_eq!(first.latest(), 1);
+    assert_eq!(second.config.get_if_new(), None);
+
+    owner.config.send(2);
+    assert_eq!(first.latest(), 2);
+    assert_eq!(second.config.get_if_new(), Some(2));
+}
+
+#[test]
+fn into_read_only_keeps_what_was_seen() {
+    let (tx, mut rx) = watch::channel(1);
+    rx.get();
+    let mut read_only = rx.into_read_only();
+    assert!(!read_only.has_changed());
+    tx.send(2);
+    let mut cloned = read_only.clone();
+    assert_eq!(read_only.get_if_new(), Some(2));
+    assert_eq!(cloned.get_if_new(), Some(2));
+}
+
+#[test]
+fn read_only_receivers_count_as_receivers() {
+    let (tx, rx) = watch::channel(1);
+    let read_only = rx.into_read_only();
+    assert!(!tx.is_closed());
+    let cloned = read_only.clone();
+    drop(read_only);
+    assert!(!tx.is_closed());
+    drop(cloned);
+    assert!(tx.is_closed());
+
+    let subscribed = tx.subscribe_read_only();
+    assert!(!tx.is_closed());
+    assert_eq!(subscribed.channel_id(), tx.channel_id());
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn closing_is_seen() {
+    let (tx, rx) = watch::channel(1);
+    let mut read_only = rx.into_read_only();
+    read_only.get();
+    tx.send(2);
+    drop(tx);
+    assert!(read_only.is_closed());
+    assert_eq!(read_only.recv(), Ok(2));
+    assert!(read_only.recv().is_err());
+}
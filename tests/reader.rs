@@ -0,0 +1,95 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{sync::Arc, thread, time::Duration};
+
+mod util;
+use util::{eventually, join_all};
+
+#[test]
+fn a_stale_token_returns_at_once() {
+    let (tx, _rx) = watch::channel(0u32);
+    let reader = tx.reader();
+    let (value, token) = reader.get();
+    assert_eq!(value, 0);
+    assert_eq!(reader.version(), token);
+    assert_eq!(
+        reader.wait_newer_than(token, Duration::from_millis(5)),
+        None
+    );
+
+    tx.send(1);
+    assert_eq!(
+        reader.wait_newer_than(token, Duration::from_secs(60)),
+        Some((1, token + 1))
+    );
+}
+
+#[test]
+fn tokens_are_channel_versions() {
+    let (tx, mut rx) = watch::channel(0u32);
+    tx.send(1);
+    let reader = rx.reader();
+    let (value, version) = rx.get_versioned();
+    assert_eq!(reader.get(), (value, version));
+    assert_eq!(reader.get_shared().1, version);
+    tx.send(2);
+    let (_, newer) = reader.wait_newer_than(version, Duration::ZERO).unwrap();
+    assert_eq!(rx.get_versioned(), (2, newer));
+}
+
+#[test]
+fn a_long_poll_parks_until_a_send() {
+    let (tx, _rx) = watch::channel(0u32);
+    let reader = tx.reader();
+    let token = reader.version();
+    let poll = {
+        let reader = reader.clone();
+        thread::spawn(move || reader.wait_newer_than(token, Duration::from_secs(60)))
+    };
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(5);
+    assert_eq!(poll.join().unwrap(), Some((5, token + 1)));
+}
+
+#[test]
+fn overlapping_long_polls() {
+    let (tx, _rx) = watch::channel(0u32);
+    let reader = Arc::new(tx.reader());
+    let clients = (0..16)
+        .map(|_| {
+            let reader = reader.clone();
+            thread::spawn(move || {
+                let (mut last, mut token) = reader.get();
+                while last < 500 {
+                    let (value, newer) = reader
+                        .wait_newer_than(token, Duration::from_secs(60))
+                        .expect("the long-poll timed out");
+                    assert!(newer > token);
+                    assert!(value > last);
+                    last = value;
+                    token = newer;
+                }
+                token
+            })
+        })
+        .collect();
+    for value in 1..=500 {
+        tx.send(value);
+    }
+    let version = reader.version();
+    assert!(join_all(clients).iter().all(|&token| token == version));
+}
+
+#[test]
+fn dropping_the_senders_closes_the_reader() {
+    let (tx, rx) = watch::channel(0u32);
+    let reader = rx.reader();
+    assert!(!reader.is_closed());
+    drop(tx);
+    assert!(reader.is_closed());
+    let version = reader.version();
+    assert_eq!(
+        reader.wait_newer_than(version, Duration::from_millis(5)),
+        None
+    );
+}
@@ -0,0 +1,125 @@
+//! The recorder of the `test-util` feature, in the style that tests are
+//! meant to use it.
+#![cfg(all(feature = "test-util", not(target_family = "wasm")))]
+
+use std::thread;
+
+mod util;
+use util::eventually;
+
+#[test]
+fn every_value_is_recorded() {
+    let (tx, mut rx) = watch::channel(0u32);
+    let recorder = tx.record();
+    tx.send(1);
+    tx.send(2);
+    tx.update(|value| *value += 1);
+    // The receiver only sees the latest value, but the recorder has them all.
+    assert_eq!(rx.get(), 3);
+    recorder.assert_published(&[1, 2, 3]);
+}
+
+#[test]
+fn sends_from_every_clone_are_recorded() {
+    let (tx, _rx) = watch::channel(0u32);
+    let recorder = tx.record();
+    let other = tx.clone();
+    thread::spawn(move || {
+        for value in 1..=50 {
+            other.send(value);
+        }
+    })
+    .join()
+    .unwrap();
+    tx.update(|value| *value += 100);
+
+    let expected: Vec<u32> = (1..=50).chain(Some(150)).collect();
+    recorder.assert_published(&expected);
+    let records = recorder.take();
+    assert_eq!(records.len(), 51);
+    assert!(records
+        .windows(2)
+        .all(|pair| pair[1].version == pair[0].version + 1 && pair[1].at >= pair[0].at));
+}
+
+#[test]
+fn take_starts_a_new_log() {
+    let (tx, _rx) = watch::channel(0u32);
+    let recorder = tx.record();
+    tx.send(1);
+    let taken = recorder.take();
+    recorder.assert_published(&[]);
+    tx.send(2);
+    assert_eq!(recorder.values(), vec![2]);
+    assert_eq!(recorder.records()[0].version, taken[0].version + 1);
+}
+
+#[test]
+fn recorders_only_see_values_sent_while_they_exist() {
+    let (tx, _rx) = watch::channel(0u32);
+    tx.send(1);
+    let first = tx.record();
+    tx.send(2);
+    let second = tx.record();
+    drop(first);
+    tx.send(3);
+    second.assert_published(&[3]);
+}
+
+#[cfg(feature = "test-clock")]
+#[test]
+fn timestamps_come_from_the_clock_of_the_channel() {
+    use std::time::Duration;
+
+    let clock = watch::MockClock::new();
+    let (tx, _rx) = watch::builder().clock(clock.clone()).channel(0);
+    let recorder = tx.record();
+    tx.send(1);
+    clock.advance(Duration::from_secs(5));
+    tx.send(2);
+    let records = recorder.records();
+    assert_eq!(records[1].at - records[0].at, Duration::from_secs(5));
+}
+
+#[test]
+fn recording_does_not_change_wakeups() {
+    let (tx, mut rx) = watch::channel(0);
+    let recorder = tx.record();
+    rx.get();
+    let waiter = thread::spawn(move || rx.wait());
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(1);
+    assert_eq!(waiter.join().unwrap(), 1);
+    recorder.assert_published(&[1]);
+}
+
+#[test]
+#[should_panic(expected = "recorded: [1]")]
+fn a_mismatch_shows_what_was_recorded() {
+    let (tx, _rx) = watch::channel(0);
+    let recorder = tx.record();
+    tx.send(1);
+    recorder.assert_published(&[2]);
+}
+
+#[test]
+fn a_recorder_does_not_hold_the_value() {
+    let (tx, mut rx) = watch::channel(vec![0u32]);
+    let recorder = tx.record();
+    tx.send(vec![1]);
+
+    // The value is not shared, so it can be replaced by value and changed
+    // where it is.
+    assert!(tx
+        .replace_with(|mut value| {
+            value.push(2);
+            value
+        })
+        .is_ok());
+    let before = std::sync::Arc::as_ptr(&rx.get_shared());
+    tx.update(|value| value.push(3));
+    assert_eq!(std::sync::Arc::as_ptr(&rx.get_shared()), before);
+    drop(rx);
+    assert_eq!(tx.try_into_inner().ok(), Some(vec![1, 2, 3]));
+    recorder.assert_published(&[vec![1], vec![1, 2], vec![1, 2, 3]]);
+}
@@ -0,0 +1,87 @@
+//! The check is only made in debug builds.
+#![cfg(all(debug_assertions, feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+};
+
+fn message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => panic.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+fn panic_of(f: impl FnOnce()) -> String {
+    message(panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err())
+}
+
+#[test]
+fn reading_inside_update_panics() {
+    let (tx, mut rx) = watch::channel(0);
+    let message = panic_of(|| tx.update(|value| *value = rx.get() + 1));
+    assert_eq!(
+        message,
+        "a watch channel was read from inside the closure passed to `update` on the same \
+         channel, which would deadlock"
+    );
+    // The channel still works afterwards.
+    tx.send(5);
+    assert_eq!(rx.get(), 5);
+}
+
+#[test]
+fn sending_inside_update_with_panics() {
+    let (tx, _rx) = watch::channel(0);
+    let message = panic_of(|| {
+        tx.update_with(|value| {
+            tx.send(1);
+            *value
+        })
+    });
+    assert!(
+        message.contains("written from inside the closure passed to `update_with`"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn reading_inside_a_transaction_panics() {
+    let (a, _a_rx) = watch::channel(0);
+    let (b, mut b_rx) = watch::channel(0);
+    let message = panic_of(|| watch::transaction((&a, &b), |a, _| *a = b_rx.get()));
+    assert!(message.contains("`transaction`"), "{}", message);
+}
+
+#[test]
+fn reading_inside_a_monotonic_update_panics() {
+    let (tx, mut rx) = watch::monotonic_channel(1);
+    let message = panic_of(|| {
+        let _ = tx.update(|value| *value = rx.get() + 1);
+    });
+    assert!(message.contains("`update`"), "{}", message);
+}
+
+#[test]
+fn other_channels_may_be_used() {
+    let (a, mut a_rx) = watch::channel(1);
+    let (b, mut b_rx) = watch::channel(2);
+    a.update(|a| {
+        b.update(|b| *b += *a);
+        *a += b_rx.get();
+    });
+    assert_eq!(b_rx.get(), 3);
+    assert_eq!(a_rx.get(), 4);
+}
+
+#[test]
+fn the_check_ends_with_the_closure() {
+    let (tx, mut rx) = watch::channel(0);
+    let _ = panic_of(|| tx.update(|_| panic!("inside")));
+    // A panic in the closure does not leave the channel marked.
+    let _ = rx.get_checked();
+    tx.send(1);
+    assert_eq!(rx.get(), 1);
+}
@@ -0,0 +1,71 @@
+#![cfg(all(feature = "registry", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+use watch::ChannelInfo;
+
+/// The registered channels whose names start with `prefix`, as the tests
+/// in this file run at the same time.
+fn named(prefix: &str) -> Vec<ChannelInfo> {
+    watch::registry()
+        .dump()
+        .into_iter()
+        .filter(|info| info.name.starts_with(prefix))
+        .collect()
+}
+
+#[test]
+fn channels_are_described() {
+    let (tx, rx) = watch::channel_named("describe.a", 1);
+    let _other = rx.clone();
+    tx.send(2);
+    let info = &named("describe.")[0];
+    assert_eq!(&*info.name, "describe.a");
+    assert_eq!(info.version, tx.reader().version());
+    assert_eq!(info.receiver_count, 2);
+    assert_eq!(info.sender_count, 1);
+}
+
+#[test]
+fn the_age_is_of_the_last_change() {
+    let (_old, _old_rx) = watch::channel_named("age.old", 0);
+    let (new, _new_rx) = watch::channel_named("age.new", 0);
+    thread::sleep(Duration::from_millis(30));
+    new.send(1);
+    let infos = named("age.");
+    assert_eq!(&*infos[0].name, "age.old");
+    assert!(infos[0].last_update_age >= Duration::from_millis(30));
+    assert!(infos[1].last_update_age < infos[0].last_update_age);
+}
+
+#[test]
+fn channels_leave_once_every_handle_is_dropped() {
+    let (tx, rx) = watch::channel_named("leave", 0);
+    let cloned = rx.clone();
+    drop(tx);
+    let infos = named("leave");
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].sender_count, 0);
+    drop(rx);
+    assert_eq!(named("leave").len(), 1);
+    drop(cloned);
+    assert!(named("leave").is_empty());
+
+    // A channel without receivers stays while it has a sender.
+    let (tx, rx) = watch::channel_named("leave", 0);
+    drop(rx);
+    assert_eq!(named("leave")[0].receiver_count, 0);
+    drop(tx);
+    assert!(named("leave").is_empty());
+}
+
+#[test]
+fn names_may_be_shared() {
+    let channels: Vec<_> = (0..100)
+        .map(|i| watch::channel_named(String::from("shared"), i))
+        .collect();
+    let infos = named("shared");
+    assert_eq!(infos.len(), 100);
+    assert!(infos.iter().all(|info| &*info.name == "shared"));
+    drop(channels);
+    assert!(named("shared").is_empty());
+}
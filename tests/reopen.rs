@@ -0,0 +1,168 @@
+//! Subscribing to a channel after every receiver was dropped.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{sync::Arc, thread};
+
+#[cfg(feature = "embedded-async")]
+mod util;
+
+#[test]
+fn subscribing_reopens_the_channel() {
+    let (tx, rx) = watch::channel(1);
+    assert!(!tx.is_closed());
+    drop(rx);
+    assert!(tx.is_closed());
+    assert_eq!(tx.receiver_count(), 0);
+
+    // Values sent without receivers are kept.
+    tx.send(2);
+    let mut rx = tx.subscribe();
+    assert!(!tx.is_closed());
+    assert_eq!(tx.receiver_count(), 1);
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get(), 2);
+    tx.send(3);
+    assert_eq!(rx.get_if_new(), Some(3));
+
+    drop(rx);
+    assert!(tx.is_closed());
+}
+
+#[test]
+fn readers_do_not_reopen_it() {
+    let (tx, rx) = watch::channel(1);
+    let reader = tx.reader();
+    drop(rx);
+    assert!(tx.is_closed());
+    assert_eq!(reader.get().0, 1);
+    assert!(tx.is_closed());
+}
+
+#[test]
+fn the_last_drop_racing_with_a_subscribe() {
+    for _ in 0..500 {
+        let (tx, rx) = watch::channel(0);
+        let tx = Arc::new(tx);
+        let subscriber = {
+            let tx = tx.clone();
+            thread::spawn(move || tx.subscribe())
+        };
+        let dropper = thread::spawn(move || drop(rx));
+        dropper.join().unwrap();
+        let mut rx = subscriber.join().unwrap();
+
+        // Whichever came first, the channel is open with one receiver.
+        assert!(!tx.is_closed());
+        assert_eq!(tx.receiver_count(), 1);
+        assert_eq!(rx.get_if_new(), None);
+        tx.send(1);
+        assert_eq!(rx.get_if_new(), Some(1));
+        drop(rx);
+        assert!(tx.is_closed());
+    }
+}
+
+#[test]
+fn receivers_coming_and_going_keep_the_count() {
+    let (tx, rx) = watch::channel(0);
+    let tx = Arc::new(tx);
+    drop(rx);
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for _ in 0..2000 {
+                    let rx = tx.subscribe();
+                    assert!(!tx.is_closed());
+                    drop(rx);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert!(tx.is_closed());
+    assert_eq!(tx.receiver_count(), 0);
+}
+
+#[cfg(feature = "embedded-async")]
+mod closed {
+    use super::util::task::counting_waker;
+    use std::{
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll},
+        thread,
+    };
+
+    #[test]
+    fn a_closing_is_not_missed_if_the_channel_reopens() {
+        let (tx, rx) = watch::channel(0);
+        let (_, waker) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut closed = pin!(tx.closed());
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+        drop(rx);
+        let reopened = tx.subscribe();
+        assert!(!tx.is_closed());
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Ready(()));
+        // It stays completed.
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Ready(()));
+
+        // A future created afterwards waits for the new receivers.
+        let mut closed = pin!(tx.closed());
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+        let cloned = reopened.clone();
+        drop(reopened);
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+        drop(cloned);
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn a_future_created_after_reopening_is_woken_by_the_next_closing() {
+        let (tx, rx) = watch::channel(0);
+        drop(rx);
+        let rx = tx.subscribe();
+        let (wakes, waker) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut closed = pin!(tx.closed());
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+        drop(rx);
+        assert_eq!(wakes.count(), 1);
+        assert_eq!(closed.as_mut().poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn the_last_drop_racing_with_a_subscribe() {
+        for _ in 0..500 {
+            let (tx, rx) = watch::channel(0);
+            let tx = Arc::new(tx);
+            let (wakes, waker) = counting_waker();
+            let mut cx = Context::from_waker(&waker);
+            let mut early = pin!(tx.closed());
+            assert_eq!(early.as_mut().poll(&mut cx), Poll::Pending);
+
+            let subscriber = {
+                let tx = tx.clone();
+                thread::spawn(move || tx.subscribe())
+            };
+            let dropper = thread::spawn(move || drop(rx));
+            dropper.join().unwrap();
+            let rx = subscriber.join().unwrap();
+
+            // If the drop came first, the early future was woken and is
+            // done. Otherwise the channel never closed, and it waits.
+            let early_done = early.as_mut().poll(&mut cx).is_ready();
+            assert_eq!(early_done, wakes.count() == 1);
+
+            let mut late = pin!(tx.closed());
+            assert_eq!(late.as_mut().poll(&mut cx), Poll::Pending);
+            drop(rx);
+            assert_eq!(late.as_mut().poll(&mut cx), Poll::Ready(()));
+            assert_eq!(early.as_mut().poll(&mut cx), Poll::Ready(()));
+        }
+    }
+}
@@ -0,0 +1,121 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    env,
+    panic::{catch_unwind, AssertUnwindSafe},
+    process::Command,
+    thread,
+};
+
+mod util;
+use util::eventually;
+
+/// Neither `Clone` nor `Default`, and consumed to build the next one.
+#[derive(Debug, PartialEq)]
+struct Connection {
+    generation: u32,
+}
+
+impl Connection {
+    fn rotate(self) -> Connection {
+        Connection {
+            generation: self.generation + 1,
+        }
+    }
+}
+
+#[test]
+fn the_old_value_is_moved_into_the_closure() {
+    let (tx, mut rx) = watch::channel(Connection { generation: 0 });
+    rx.get_shared();
+    let reader = tx.reader();
+    let version = reader.version();
+    assert!(tx.replace_with(Connection::rotate).is_ok());
+    // One send, with one new version.
+    assert_eq!(reader.version(), version + 1);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_shared().generation, 1);
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn a_held_value_gives_the_closure_back() {
+    let (tx, mut rx) = watch::channel(Connection { generation: 0 });
+    let held = rx.get_shared();
+    let mut called = false;
+    let f = |connection: Connection| {
+        called = true;
+        connection.rotate()
+    };
+    let f = tx.replace_with(f).err().unwrap();
+    assert!(!rx.has_changed());
+    assert_eq!(held.generation, 0);
+
+    drop(held);
+    assert!(tx.replace_with(f).is_ok());
+    assert!(called);
+    assert_eq!(rx.get_shared().generation, 1);
+}
+
+#[test]
+fn a_channel_with_history_is_not_replaced() {
+    let (tx, mut rx) = watch::builder().history(4).channel(1);
+    rx.get();
+    assert!(tx.replace_with(|value| value + 1).is_err());
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn the_previous_value_is_gone() {
+    let (tx, mut rx) = watch::builder().undo(true).keep_previous(true).channel(1);
+    tx.send(2);
+    assert!(tx.replace_with(|value| value * 10).is_ok());
+    assert_eq!(rx.get_with_previous(), (None, 20));
+    assert!(tx.undo().is_err());
+
+    // The next change keeps a previous value again.
+    tx.send(30);
+    assert_eq!(rx.get_with_previous(), (Some(20), 30));
+}
+
+#[test]
+fn a_waiting_receiver_is_woken() {
+    let (tx, mut rx) = watch::channel(Connection { generation: 0 });
+    rx.get_shared();
+    let waiter = thread::spawn(move || rx.wait_shared().generation);
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    assert!(tx.replace_with(Connection::rotate).is_ok());
+    assert_eq!(waiter.join().unwrap(), 1);
+}
+
+/// Runs a panicking closure in a process of its own, see
+/// `a_panicking_closure_aborts`.
+#[test]
+fn panicking_child() {
+    if env::var_os("WATCH_REPLACE_WITH_CHILD").is_none() {
+        return;
+    }
+    let (tx, _rx) = watch::channel(Connection { generation: 0 });
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let _ = tx.replace_with(|_| panic!("in replace_with"));
+    }));
+    // Catching the panic must not be possible.
+    std::process::exit(0);
+}
+
+#[test]
+fn a_panicking_closure_aborts() {
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["panicking_child", "--exact", "--nocapture"])
+        .env("WATCH_REPLACE_WITH_CHILD", "1")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("in replace_with"), "{}", stderr);
+    assert!(
+        stderr.contains("passed to `WatchSender::replace_with` panicked, aborting"),
+        "{}",
+        stderr
+    );
+}
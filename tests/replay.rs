@@ -0,0 +1,158 @@
+//! Replaying recordings into channels, from the `test-util` feature.
+#![cfg(all(feature = "test-util", not(target_family = "wasm")))]
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+use watch::Recording;
+
+mod util;
+use util::eventually;
+
+fn recording(entries: &[(u64, u32)]) -> Recording<u32> {
+    let mut recording = Recording::new();
+    for &(millis, value) in entries {
+        recording.push(Duration::from_millis(millis), value);
+    }
+    recording
+}
+
+#[test]
+fn a_recorder_gives_times_from_the_first_value() {
+    let (tx, _rx) = watch::channel(0);
+    let recorder = tx.record();
+    assert!(recorder.recording().is_empty());
+    tx.send(1);
+    tx.send(2);
+    let recording = recorder.recording();
+    assert_eq!(recording.len(), 2);
+    let entries = recording.entries();
+    assert_eq!(entries[0], (Duration::ZERO, 1));
+    assert_eq!(entries[1].1, 2);
+}
+
+#[cfg(feature = "test-clock")]
+#[test]
+fn a_recorder_keeps_the_gaps_of_the_channel_clock() {
+    let clock = watch::MockClock::new();
+    let (tx, _rx) = watch::builder().clock(clock.clone()).channel(0);
+    let recorder = tx.record();
+    clock.advance(Duration::from_secs(3));
+    tx.send(1);
+    clock.advance(Duration::from_millis(250));
+    tx.send(2);
+    assert_eq!(recorder.recording(), recording(&[(0, 1), (250, 2)]));
+}
+
+#[test]
+fn every_value_is_replayed_in_order() {
+    let (tx, mut rx) = watch::channel(0);
+    let recorder = tx.record();
+    // Values with no gap between them are sent one after the other.
+    let replay = recording(&[(0, 1), (0, 2), (0, 3), (10, 4)]).replay_into(&tx, 1.0);
+    assert_eq!(replay.join(), 4);
+    recorder.assert_published(&[1, 2, 3, 4]);
+    assert_eq!(rx.get(), 4);
+}
+
+#[test]
+fn a_speed_of_zero_does_not_wait() {
+    let (tx, _rx) = watch::channel(0);
+    let recorder = tx.record();
+    let start = Instant::now();
+    let replay = recording(&[(0, 1), (3_600_000, 2), (7_200_000, 3)]).replay_into(&tx, 0.0);
+    assert_eq!(replay.join(), 3);
+    assert!(start.elapsed() < Duration::from_secs(60));
+    recorder.assert_published(&[1, 2, 3]);
+}
+
+#[test]
+fn the_gaps_are_scaled_by_the_speed() {
+    let (tx, _rx) = watch::channel(0);
+    let recording = recording(&[(0, 1), (200, 2)]);
+
+    let start = Instant::now();
+    assert_eq!(recording.replay_into(&tx, 1.0).join(), 2);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    let start = Instant::now();
+    assert_eq!(recording.replay_into(&tx, 4.0).join(), 2);
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(50), "{:?}", elapsed);
+}
+
+#[test]
+fn time_spent_paused_does_not_count() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let start = Instant::now();
+    let replay = recording(&[(100, 1)]).replay_into(&tx, 1.0);
+    replay.pause();
+    assert!(replay.is_paused());
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(replay.replayed(), 0);
+    assert!(!rx.has_changed());
+
+    replay.resume();
+    assert!(!replay.is_paused());
+    assert_eq!(replay.join(), 1);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+    assert_eq!(rx.get(), 1);
+}
+
+#[test]
+fn stopping_skips_the_remaining_values() {
+    let (tx, mut rx) = watch::channel(0);
+    let replay = recording(&[(0, 1), (3_600_000, 2)]).replay_into(&tx, 1.0);
+    assert!(eventually(|| replay.replayed() == 1));
+    assert!(!replay.is_finished());
+    assert_eq!(replay.stop(), 1);
+    assert_eq!(rx.get(), 1);
+
+    // A paused replay can be stopped too.
+    let replay = recording(&[(0, 1), (10, 2)]).replay_into(&tx, 1.0);
+    replay.pause();
+    assert!(replay.stop() <= 1);
+}
+
+#[test]
+fn dropping_the_handle_stops_the_replay() {
+    let (tx, rx) = watch::channel(0);
+    let replay = recording(&[(3_600_000, 1)]).replay_into(&tx, 1.0);
+    drop(replay);
+    drop(tx);
+    // The thread drops its sender once it exits.
+    assert!(eventually(|| rx.is_closed()));
+}
+
+#[test]
+fn an_empty_recording_finishes_at_once() {
+    let (tx, _rx) = watch::channel(0);
+    let replay = Recording::new().replay_into(&tx, 1.0);
+    assert_eq!(replay.join(), 0);
+}
+
+#[test]
+fn negative_speeds_and_unordered_entries_panic() {
+    let (tx, _rx) = watch::channel(0);
+    let recording = recording(&[(0, 1)]);
+    assert!(catch_unwind(AssertUnwindSafe(|| recording.replay_into(&tx, -1.0))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| recording.replay_into(&tx, f32::NAN))).is_err());
+
+    let mut recording = recording.clone();
+    recording.push(Duration::from_millis(5), 2);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        recording.push(Duration::from_millis(4), 3)
+    }));
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn recordings_can_be_saved_and_loaded() {
+    let recording = recording(&[(0, 1), (1500, 2)]);
+    let json = serde_json::to_string(&recording).unwrap();
+    let loaded: Recording<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, recording);
+}
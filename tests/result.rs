@@ -0,0 +1,82 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+mod util;
+use util::eventually;
+
+#[test]
+fn errors_are_skipped_by_wait_ok() {
+    let (tx, mut rx) = watch::channel::<Result<u32, String>>(Err("initial".into()));
+    assert!(rx.last_is_err());
+    assert_eq!(rx.wait_ok_timeout(Duration::from_millis(5)), None);
+    assert_eq!(rx.get_latest_err().as_deref(), Some("initial"));
+
+    let sender = {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            tx.send_err("first".into());
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            tx.send_err("second".into());
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            tx.send_ok(7);
+        })
+    };
+    assert_eq!(rx.wait_ok(), 7);
+    sender.join().unwrap();
+    assert!(!rx.last_is_err());
+    assert_eq!(rx.get_latest_err(), None);
+}
+
+#[test]
+fn skipped_errors_stay_visible() {
+    let (tx, mut rx) = watch::channel::<Result<u32, String>>(Ok(0));
+    rx.get().unwrap();
+    tx.send_err("first".into());
+    tx.send_err("second".into());
+    assert_eq!(rx.wait_ok_timeout(Duration::from_millis(5)), None);
+    assert_eq!(rx.get_latest_err().as_deref(), Some("second"));
+    assert_eq!(rx.get_latest_err().as_deref(), Some("second"));
+
+    // Looking for an error does not hide an Ok.
+    tx.send_ok(8);
+    assert_eq!(rx.get_latest_err(), None);
+    assert_eq!(rx.wait_ok_timeout(Duration::from_millis(5)), Some(8));
+    assert_eq!(rx.wait_ok_timeout(Duration::from_millis(5)), None);
+}
+
+#[test]
+fn the_timeout_expires_while_only_errors_arrive() {
+    let (tx, mut rx) = watch::channel::<Result<u32, String>>(Ok(0));
+    rx.get().unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let sender = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            let mut sent = 0;
+            while !stop.load(Ordering::Relaxed) {
+                tx.send_err(format!("error {}", sent));
+                sent += 1;
+                thread::sleep(Duration::from_millis(1));
+            }
+            sent
+        })
+    };
+    let start = Instant::now();
+    assert_eq!(rx.wait_ok_timeout(Duration::from_millis(50)), None);
+    let elapsed = start.elapsed();
+    stop.store(true, Ordering::Relaxed);
+    let sent = sender.join().unwrap();
+    assert!(elapsed >= Duration::from_millis(50), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+    let last = format!("error {}", sent - 1);
+    assert_eq!(rx.get_latest_err(), Some(last));
+}
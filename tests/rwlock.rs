@@ -0,0 +1,101 @@
+//! The value is read-locked by receivers, so they do not serialize.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use util::{eventually, join_all};
+
+#[test]
+fn readers_hold_the_value_at_the_same_time() {
+    let (tx, rx) = watch::channel(0);
+    let inside = Arc::new(AtomicUsize::new(0));
+    let readers = (0..2)
+        .map(|_| {
+            let mut rx = rx.clone();
+            let inside = inside.clone();
+            thread::spawn(move || {
+                rx.wait_map(|_| {
+                    // Both closures run with the value locked, which only
+                    // works if the lock is shared.
+                    inside.fetch_add(1, Ordering::SeqCst);
+                    eventually(|| inside.load(Ordering::SeqCst) == 2)
+                })
+            })
+        })
+        .collect();
+    assert_eq!(join_all(readers), [true, true]);
+    drop(tx);
+}
+
+#[test]
+fn writers_wait_for_readers() {
+    let (tx, mut rx) = watch::channel(0);
+    let reading = Arc::new(AtomicBool::new(false));
+    let release = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let (reading, release) = (reading.clone(), release.clone());
+        thread::spawn(move || {
+            rx.wait_map(|value| {
+                reading.store(true, Ordering::SeqCst);
+                assert!(eventually(|| release.load(Ordering::SeqCst)));
+                *value
+            })
+        })
+    };
+    assert!(eventually(|| reading.load(Ordering::SeqCst)));
+    let sent = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let sent = sent.clone();
+        thread::spawn(move || {
+            tx.send(1);
+            sent.store(true, Ordering::SeqCst);
+        })
+    };
+    thread::sleep(Duration::from_millis(20));
+    assert!(!sent.load(Ordering::SeqCst));
+    release.store(true, Ordering::SeqCst);
+    assert_eq!(join_all(vec![reader]), [0]);
+    join_all(vec![writer]);
+}
+
+#[test]
+fn read_contention() {
+    const READERS: usize = 8;
+    const READS: usize = 20_000;
+    let (tx, rx) = watch::channel(vec![0u8; 4096]);
+    let start = Instant::now();
+    let readers = (0..READERS)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut total = 0;
+                for _ in 0..READS {
+                    total += rx.get().len();
+                    let _ = rx.get_if_new();
+                    let _ = rx.has_changed();
+                }
+                total
+            })
+        })
+        .collect();
+    for i in 0..50 {
+        tx.send(vec![i; 4096]);
+        thread::sleep(Duration::from_micros(200));
+    }
+    let totals = join_all(readers);
+    eprintln!(
+        "{} reads by {} threads in {:?}",
+        READERS * READS * 3,
+        READERS,
+        start.elapsed()
+    );
+    assert!(totals.iter().all(|&total| total == READS * 4096));
+}
@@ -0,0 +1,108 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Message<'a> {
+    name: &'a str,
+    count: u32,
+}
+
+#[test]
+fn scoped_threads_exchange_borrowed_values() {
+    let names = [String::from("first"), String::from("second")];
+    let channel = watch::scoped(Message {
+        name: &names[0],
+        count: 0,
+    });
+    let sender = channel.sender();
+    let mut receiver = channel.receiver();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for count in 1..=5 {
+                sender.send(Message {
+                    name: &names[count as usize % 2],
+                    count,
+                });
+                thread::sleep(Duration::from_millis(2));
+            }
+        });
+        scope.spawn(|| {
+            let mut last = receiver.get();
+            while last.count < 5 {
+                last = receiver.wait();
+                assert_eq!(last.name, names[last.count as usize % 2]);
+            }
+        });
+    });
+
+    let mut late = channel.receiver();
+    assert_eq!(
+        late.get(),
+        Message {
+            name: "second",
+            count: 5
+        }
+    );
+    assert!(!late.has_changed());
+    assert_eq!(late.wait_timeout(Duration::from_millis(10)), None);
+}
+
+#[test]
+fn receivers_have_their_own_cursor() {
+    let channel = watch::scoped(0);
+    let sender = channel.sender();
+    let mut a = channel.receiver();
+    let mut b = channel.receiver();
+    assert_eq!(a.get_if_new(), Some(0));
+    sender.send(1);
+    assert_eq!(a.get_if_new(), Some(1));
+    assert_eq!(a.get_if_new(), None);
+    assert_eq!(b.get_if_new(), Some(1));
+
+    // Clones start from the state of their origin, and subscriptions have
+    // seen everything so far.
+    let mut c = a.clone();
+    assert_eq!(c.get_if_new(), None);
+    let mut d = sender.subscribe();
+    assert_eq!(d.get_if_new(), None);
+    sender.send(2);
+    assert_eq!(d.get_if_new(), Some(2));
+}
+
+#[test]
+fn updates_and_reused_buffers() {
+    let text = String::from("borrowed");
+    let channel = watch::scoped(vec![text.as_str()]);
+    let sender = channel.sender();
+    let mut receiver = channel.receiver();
+    sender.update(|values| values.push(&text[..4]));
+    sender.update_with(|values| {
+        let mut values = values.clone();
+        values.push(&text[4..]);
+        values
+    });
+    let mut out = Vec::with_capacity(8);
+    receiver.get_into(&mut out);
+    assert_eq!(out, ["borrowed", "borr", "owed"]);
+    assert!(!receiver.get_if_new_into(&mut out));
+
+    receiver.new_sender().send(vec![]);
+    assert!(receiver.get_if_new_into(&mut out));
+    assert!(out.is_empty());
+}
+
+#[test]
+fn a_scoped_thread_wakes_a_timed_wait() {
+    let channel = watch::scoped(0);
+    let sender = channel.sender();
+    let mut receiver = channel.receiver();
+    receiver.get();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            sender.send(1);
+        });
+        assert_eq!(receiver.wait_timeout(Duration::from_secs(5)), Some(1));
+    });
+}
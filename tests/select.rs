@@ -0,0 +1,238 @@
+#![cfg(all(feature = "macros", not(target_family = "wasm")))]
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use watch::WatchReceiver;
+
+#[test]
+fn the_arm_with_a_new_value_runs() {
+    let (_first_tx, mut first) = watch::channel(0);
+    let (second_tx, mut second) = watch::channel("a");
+    first.get();
+    second.get();
+    second_tx.send("b");
+    let picked = watch::select! {
+        value = first => format!("first {}", value),
+        value = second => format!("second {}", value),
+    };
+    assert_eq!(picked, "second b");
+    assert!(!second.has_changed());
+}
+
+#[test]
+fn only_the_arm_that_runs_marks_its_value_seen() {
+    let (first_tx, mut first) = watch::channel(0);
+    let (second_tx, mut second) = watch::channel(0);
+    first_tx.send(1);
+    second_tx.send(2);
+    let picked = watch::select! {
+        value = first => value,
+        value = second => value,
+    };
+    let unpicked = if picked == 1 { &second } else { &first };
+    assert!(unpicked.has_changed());
+}
+
+#[test]
+fn borrowed_receivers_can_be_used_again_in_the_bodies() {
+    fn next(rx: &mut WatchReceiver<u32>, stop: &mut WatchReceiver<u32>) -> Option<u32> {
+        watch::select! {
+            value = &mut *rx => {
+                // The futures are gone, so the receivers can be used.
+                assert_eq!(rx.get_if_new(), None);
+                Some(value)
+            }
+            _ = &mut *stop => None,
+        }
+    }
+
+    let (tx, mut rx) = watch::channel(0);
+    let (stop_tx, mut stop) = watch::channel(0);
+    rx.get();
+    stop.get();
+    tx.send(1);
+    assert_eq!(next(&mut rx, &mut stop), Some(1));
+    stop_tx.send(1);
+    assert_eq!(next(&mut rx, &mut stop), None);
+}
+
+#[test]
+fn a_waiting_select_is_woken() {
+    let (tx, mut rx) = watch::channel(0);
+    let (_other_tx, mut other) = watch::channel(0);
+    rx.get();
+    other.get();
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(7);
+        tx
+    });
+    let picked = watch::select! {
+        value = rx => Some(value),
+        _ = other => None,
+    };
+    assert_eq!(picked, Some(7));
+    sender.join().unwrap();
+}
+
+#[test]
+fn the_arms_take_turns() {
+    let (first_tx, mut first) = watch::channel(0);
+    let (second_tx, mut second) = watch::channel(0);
+    let (mut firsts, mut seconds) = (0, 0);
+    for value in 0..100 {
+        first_tx.send(value);
+        second_tx.send(value);
+        watch::select! {
+            _ = first => firsts += 1,
+            _ = second => seconds += 1,
+        }
+    }
+    assert_eq!((firsts, seconds), (50, 50));
+}
+
+#[test]
+fn a_busy_receiver_does_not_starve_the_others() {
+    let (busy_tx, mut busy) = watch::channel(0);
+    let (quiet_tx, mut quiet) = watch::channel(0);
+    busy.get();
+    quiet.get();
+    quiet_tx.send(1);
+    let mut rounds = 0;
+    loop {
+        busy_tx.send(rounds);
+        rounds += 1;
+        watch::select! {
+            _ = busy => {}
+            value = quiet => {
+                assert_eq!(value, 1);
+                break;
+            }
+        }
+    }
+    assert!(rounds <= 2);
+}
+
+#[test]
+fn the_default_arm_runs_after_the_timeout() {
+    let (_tx, mut rx) = watch::channel(0);
+    rx.get();
+    let start = Instant::now();
+    let picked = watch::select! {
+        value = rx => Some(value),
+        default(Duration::from_millis(50)) => None,
+    };
+    assert_eq!(picked, None);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn a_value_before_the_timeout_wins() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(3);
+        tx
+    });
+    let start = Instant::now();
+    let picked = watch::select! {
+        value = rx => Some(value),
+        default(Duration::from_secs(30)) => None,
+    };
+    assert_eq!(picked, Some(3));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    sender.join().unwrap();
+}
+
+#[test]
+fn a_new_value_wins_over_an_expired_default() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send(1);
+    let picked = watch::select! {
+        value = rx => Some(value),
+        default(Duration::ZERO) => None,
+    };
+    assert_eq!(picked, Some(1));
+}
+
+#[test]
+fn a_value_that_does_not_match_disables_the_arm() {
+    let (first_tx, mut first) = watch::channel(0);
+    let (second_tx, mut second) = watch::channel(0);
+    first.get();
+    second.get();
+    first_tx.send(5);
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        second_tx.send(9);
+        second_tx
+    });
+    let picked = watch::select! {
+        3 = first => 3,
+        value = second => value,
+    };
+    assert_eq!(picked, 9);
+    // The value that did not match was marked seen.
+    assert!(!first.has_changed());
+    sender.join().unwrap();
+}
+
+#[test]
+fn a_closed_channel_disables_the_arm() {
+    let (tx, mut closed) = watch::channel(0);
+    closed.get();
+    drop(tx);
+    let (_tx, mut open) = watch::channel(0);
+    open.get();
+    let picked = watch::select! {
+        _ = closed => "closed",
+        _ = open => "open",
+        default(Duration::from_millis(10)) => "default",
+    };
+    assert_eq!(picked, "default");
+
+    // A value sent before the channel closed is still picked.
+    let (tx, mut closed) = watch::channel(0);
+    closed.get();
+    tx.send(1);
+    drop(tx);
+    let picked = watch::select! {
+        value = closed => value,
+    };
+    assert_eq!(picked, 1);
+}
+
+#[test]
+#[should_panic(expected = "every arm of `select!` is disabled")]
+fn a_select_without_a_default_panics_once_every_arm_is_disabled() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    drop(tx);
+    watch::select! { _ = rx => {} }
+}
+
+#[test]
+fn bodies_can_return_and_use_the_question_mark() {
+    fn first_even(rx: &mut WatchReceiver<u32>) -> Result<u32, String> {
+        loop {
+            watch::select! {
+                value = &mut *rx => {
+                    if value % 2 == 0 {
+                        return Ok(value);
+                    }
+                }
+                default(Duration::from_millis(10)) => Err("timed out".to_string())?,
+            }
+        }
+    }
+
+    let (tx, mut rx) = watch::channel(1);
+    assert_eq!(first_even(&mut rx), Err("timed out".to_string()));
+    tx.send(4);
+    assert_eq!(first_even(&mut rx), Ok(4));
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::cell::Cell;
+
+thread_local! {
+    static CLONES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A buffer that counts its clones, and whose address and capacity show
+/// whether `clone_from` reused it.
+#[derive(Debug, PartialEq)]
+struct Buffer(String);
+
+impl Buffer {
+    fn address(&self) -> *const u8 {
+        self.0.as_ptr()
+    }
+}
+
+impl Clone for Buffer {
+    fn clone(&self) -> Buffer {
+        CLONES.with(|clones| clones.set(clones.get() + 1));
+        Buffer(self.0.clone())
+    }
+
+    fn clone_from(&mut self, source: &Buffer) {
+        self.0.clone_from(&source.0);
+    }
+}
+
+/// The clones made on this thread since the last call. On wasm, every
+/// test runs on the same thread.
+fn clones() -> usize {
+    CLONES.with(|clones| clones.replace(0))
+}
+
+#[test]
+fn the_buffer_is_reused() {
+    clones();
+    let (tx, mut rx) = watch::channel(Buffer(String::with_capacity(64)));
+    let (address, capacity) = {
+        let value = rx.get_shared();
+        (value.address(), value.0.capacity())
+    };
+    for i in 0..100 {
+        tx.send_from(&Buffer(format!("value {}", i)));
+        assert!(rx.has_changed());
+        let value = rx.get_shared();
+        assert_eq!(value.0, format!("value {}", i));
+        assert_eq!(value.address(), address);
+        assert_eq!(value.0.capacity(), capacity);
+    }
+    assert_eq!(clones(), 0);
+}
+
+#[test]
+fn a_held_value_is_not_changed() {
+    clones();
+    let (tx, mut rx) = watch::channel(Buffer(String::from("first")));
+    let held = rx.get_shared();
+    tx.send_from(&Buffer(String::from("second")));
+    // The held value is left alone, so that one was cloned.
+    assert_eq!(held.0, "first");
+    assert_eq!(clones(), 1);
+    let value = rx.get_shared();
+    assert_eq!(value.0, "second");
+    assert_ne!(value.address(), held.address());
+
+    // Without the handle, the new value is reused in turn.
+    drop(held);
+    let address = value.address();
+    drop(value);
+    tx.send_from(&Buffer(String::from("third")));
+    assert_eq!(rx.get_shared().address(), address);
+    assert_eq!(clones(), 0);
+}
+
+#[test]
+fn the_undo_history_is_not_changed() {
+    let (tx, mut rx) = watch::builder()
+        .undo(true)
+        .channel(Buffer(String::from("first")));
+    tx.send_from(&Buffer(String::from("second")));
+    assert_eq!(rx.get_shared().0, "second");
+    tx.undo().unwrap();
+    assert_eq!(rx.get_shared().0, "first");
+}
+
+#[test]
+fn it_notifies_like_a_send() {
+    let (tx, mut rx) = watch::channel(Buffer(String::new()));
+    let (_, version) = rx.get_versioned();
+    tx.send_from(&Buffer(String::from("a")));
+    assert!(rx.has_changed());
+    let (value, next) = rx.get_versioned();
+    assert_eq!(value.0, "a");
+    assert_eq!(next, version + 1);
+}
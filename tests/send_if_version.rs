@@ -0,0 +1,79 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn sends_only_on_the_expected_version() {
+    let (tx, mut rx) = watch::channel(0);
+    let (value, version) = rx.get_versioned();
+    assert_eq!(value, 0);
+    assert!(!rx.has_changed());
+
+    let sent = tx.send_if_version(version, 1).unwrap();
+    assert_eq!(sent, version + 1);
+    assert_eq!(rx.get_versioned(), (1, sent));
+
+    // The version has moved on, so the value comes back.
+    let conflict = tx.send_if_version(version, 2).unwrap_err();
+    assert_eq!(conflict.value, 2);
+    assert_eq!(conflict.version, sent);
+    assert!(!rx.has_changed());
+    assert_eq!(
+        conflict.to_string(),
+        format!("watch channel has moved on to version {}", sent)
+    );
+
+    // So has a version from the future.
+    assert!(tx.send_if_version(sent + 5, 3).is_err());
+    assert_eq!(tx.send_if_version(conflict.version, 3).unwrap(), sent + 1);
+    assert_eq!(rx.get(), 3);
+}
+
+#[test]
+fn the_conflict_debug_output_does_not_need_the_value_to_be_debug() {
+    struct Opaque;
+
+    let (tx, _rx) = watch::channel(Opaque);
+    let conflict = tx.send_if_version(0, Opaque).unwrap_err();
+    assert_eq!(
+        format!("{:?}", conflict),
+        format!("VersionConflict {{ version: {}, .. }}", conflict.version)
+    );
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn retries_never_lose_an_update() {
+    use std::thread;
+
+    const THREADS: u64 = 4;
+    const INCREMENTS: u64 = 2000;
+
+    let (tx, mut rx) = watch::channel(0u64);
+    let (_, start) = rx.get_versioned();
+    let threads: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let tx = tx.clone();
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                for _ in 0..INCREMENTS {
+                    let (mut value, mut version) = rx.get_versioned();
+                    while let Err(conflict) = tx.send_if_version(version, value + 1) {
+                        assert!(conflict.version > version);
+                        let (latest, latest_version) = rx.get_versioned();
+                        value = latest;
+                        version = latest_version;
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    let (value, version) = rx.get_versioned();
+    assert_eq!(value, THREADS * INCREMENTS);
+    assert_eq!(version, start + THREADS * INCREMENTS);
+}
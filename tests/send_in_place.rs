@@ -0,0 +1,108 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::sync::Arc;
+
+#[cfg(not(target_family = "wasm"))]
+mod util;
+
+/// A large value that is not cloned.
+#[derive(Default)]
+struct Frame {
+    pixels: Vec<u8>,
+    number: u32,
+}
+
+fn frame(number: u32) -> Frame {
+    Frame {
+        pixels: vec![0; 1 << 16],
+        number,
+    }
+}
+
+#[test]
+fn a_value_that_nothing_else_holds_is_overwritten() {
+    let (tx, mut rx) = watch::channel(frame(0));
+    let before = Arc::as_ptr(&rx.get_shared());
+    let pixels = rx.get_shared().pixels.as_ptr();
+    tx.send_in_place(|frame| {
+        // The closure starts from the current value.
+        assert_eq!(frame.pixels.len(), 1 << 16);
+        frame.pixels[0] = 9;
+        frame.number = 1;
+    });
+    assert!(rx.has_changed());
+    let sent = rx.get_if_new_shared().unwrap();
+    assert_eq!((sent.number, sent.pixels[0]), (1, 9));
+    // Neither the value nor its buffer was moved.
+    assert_eq!(Arc::as_ptr(&sent), before);
+    assert_eq!(sent.pixels.as_ptr(), pixels);
+}
+
+#[test]
+fn a_value_that_a_receiver_holds_is_left_alone() {
+    let (tx, mut rx) = watch::channel(frame(3));
+    let held = rx.get_shared();
+    tx.send_in_place(|frame| {
+        // The closure starts from the default instead.
+        assert!(frame.pixels.is_empty());
+        frame.number = 4;
+    });
+    assert_eq!(held.number, 3);
+    assert_eq!(held.pixels.len(), 1 << 16);
+    let sent = rx.get_if_new_shared().unwrap();
+    assert_eq!(sent.number, 4);
+    assert!(!Arc::ptr_eq(&sent, &held));
+
+    // Once the receiver lets go, the new value is overwritten where it is.
+    drop((held, sent));
+    let before = Arc::as_ptr(&rx.get_shared());
+    tx.send_in_place(|frame| frame.number = 5);
+    assert_eq!(Arc::as_ptr(&rx.get_shared()), before);
+    assert_eq!(rx.get_shared().number, 5);
+}
+
+#[test]
+fn every_send_is_a_new_version() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let version = tx.reader().version();
+    tx.send_in_place(|value| *value += 1);
+    tx.send_in_place(|value| *value += 1);
+    assert_eq!(tx.reader().version(), version + 2);
+    assert_eq!(rx.get_if_new(), Some(2));
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn waiting_receivers_are_woken() {
+    use std::thread;
+    use util::{eventually, join_all};
+
+    let (tx, mut rx) = watch::builder().initial_seen(true).channel(0u32);
+    let waiter = thread::spawn(move || rx.wait());
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send_in_place(|value| *value = 7);
+    assert_eq!(join_all(vec![waiter]), [7]);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn a_panic_poisons_the_channel() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.send_in_place(|value| {
+            *value = 5;
+            panic!("in send_in_place");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(rx.has_changed());
+    assert!(rx.get_checked().is_err());
+}
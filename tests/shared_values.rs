@@ -0,0 +1,50 @@
+//! Receivers that share the values of the channel rather than cloning them.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{sync::Arc, thread, time::Duration};
+
+/// A large value that cannot be cloned.
+struct Frame(Vec<u8>);
+
+#[test]
+fn values_need_not_be_clone() {
+    let (tx, mut rx) = watch::channel(Frame(vec![1; 1 << 20]));
+    let first = rx.get_shared();
+    assert!(rx.get_if_new_shared().is_none());
+
+    tx.update_with(|frame| Frame(frame.0.iter().map(|x| x + 1).collect()));
+    let second = rx.get_if_new_shared().unwrap();
+    assert_eq!(first.0[0], 1);
+    assert_eq!(second.0[0], 2);
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(Frame(vec![3]));
+    });
+    assert_eq!(rx.wait_shared().0, [3]);
+    sender.join().unwrap();
+}
+
+#[test]
+fn every_receiver_gets_the_same_allocation() {
+    let (tx, mut a) = watch::channel(Frame(vec![0; 16]));
+    let mut b = a.clone();
+    let frame = Arc::new(Frame(vec![7; 16]));
+    tx.send_arc(frame.clone());
+    assert!(Arc::ptr_eq(&a.get_shared(), &frame));
+    assert!(Arc::ptr_eq(&b.get_shared(), &frame));
+}
+
+#[test]
+fn updates_do_not_change_values_that_receivers_hold() {
+    let (tx, mut rx) = watch::channel(vec![1]);
+    let held = rx.get_shared();
+    tx.update(|value| value.push(2));
+    assert_eq!(*held, [1]);
+    assert_eq!(rx.get(), [1, 2]);
+
+    // Without anyone holding it, the value is updated where it is.
+    let before = Arc::as_ptr(&rx.get_shared());
+    tx.update(|value| value.push(3));
+    assert_eq!(Arc::as_ptr(&rx.get_shared()), before);
+}
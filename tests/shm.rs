@@ -0,0 +1,279 @@
+//! Channels between processes. The other processes run this test binary
+//! again, with the `child` test doing what `WATCH_SHM_CHILD` asks for.
+#![cfg(all(feature = "shm", any(target_os = "linux", target_os = "android")))]
+
+use std::{
+    env,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    process::{self, Command, Stdio},
+    thread,
+    time::Duration,
+};
+use watch::{shm, RecvError, RecvTimeoutError};
+
+/// The number of values that the readers are sent.
+const SENDS: u64 = 10_000;
+
+/// A name that no other test uses.
+fn unique(test: &str) -> String {
+    format!("watch-test-{}-{}", process::id(), test)
+}
+
+fn object_exists(name: &str) -> bool {
+    Path::new("/dev/shm").join(name).exists()
+}
+
+/// Another process, running `role` on the channel `name`.
+struct Child {
+    process: process::Child,
+    stderr: BufReader<process::ChildStderr>,
+}
+
+impl Child {
+    fn spawn(role: &str, name: &str) -> Child {
+        let mut process = Command::new(env::current_exe().unwrap())
+            .args(["child", "--exact", "--nocapture"])
+            .env("WATCH_SHM_CHILD", format!("{} {}", role, name))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stderr = BufReader::new(process.stderr.take().unwrap());
+        Child { process, stderr }
+    }
+
+    /// Wait until the child has set up its end of the channel.
+    fn wait_until_ready(&mut self) {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            assert_ne!(
+                self.stderr.read_line(&mut line).unwrap(),
+                0,
+                "the child exited"
+            );
+            if line.trim_end() == "ready" {
+                return;
+            }
+        }
+    }
+
+    /// Wait for the child to exit, passing on what it printed.
+    fn succeeds(mut self) -> bool {
+        io::copy(&mut self.stderr, &mut io::stderr()).unwrap();
+        self.process.wait().unwrap().success()
+    }
+
+    /// Kill the child and reap it.
+    fn kill(mut self) {
+        self.process.kill().unwrap();
+        self.process.wait().unwrap();
+    }
+}
+
+fn ready() {
+    eprintln!("ready");
+}
+
+/// Block until the parent is done with this process, and kills it.
+fn park() -> ! {
+    io::stdin().read_line(&mut String::new()).unwrap();
+    process::exit(0)
+}
+
+#[test]
+fn child() {
+    let role = match env::var("WATCH_SHM_CHILD") {
+        Ok(role) => role,
+        Err(_) => return,
+    };
+    let (role, name) = role.split_once(' ').unwrap();
+    match role {
+        "reader" => {
+            let mut rx = shm::open_receiver::<[u64; 4]>(name).unwrap();
+            let mut last = rx.get()[0];
+            ready();
+            while let Ok(value) = rx.wait() {
+                assert!(value.iter().all(|&n| n == value[0]), "torn: {:?}", value);
+                assert!(value[0] > last);
+                last = value[0];
+            }
+            assert_eq!(last, SENDS);
+        }
+        "producer" => {
+            let (tx, _rx) = shm::channel(name, 0u32).unwrap();
+            tx.send(7);
+            ready();
+            park();
+        }
+        "spammer" => {
+            let (tx, _rx) = shm::channel(name, [0u64; 16]).unwrap();
+            ready();
+            for value in 1.. {
+                tx.send([value; 16]);
+            }
+        }
+        _ => panic!("unknown role {}", role),
+    }
+}
+
+#[test]
+fn values_reach_other_processes() {
+    let name = unique("values");
+    let (tx, _rx) = shm::channel(&name, [0u64; 4]).unwrap();
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let mut reader = Child::spawn("reader", &name);
+            reader.wait_until_ready();
+            reader
+        })
+        .collect();
+    for value in 1..=SENDS {
+        tx.send([value; 4]);
+    }
+    drop(tx);
+    for reader in readers {
+        assert!(reader.succeeds());
+    }
+    assert!(!object_exists(&name));
+}
+
+#[test]
+fn a_producer_that_dies_closes_the_channel() {
+    let name = unique("dies");
+    let mut producer = Child::spawn("producer", &name);
+    producer.wait_until_ready();
+    let mut rx = shm::open_receiver::<u32>(&name).unwrap();
+    assert_eq!(rx.wait(), Ok(7));
+    assert!(!rx.is_closed());
+
+    let mut waiting = rx.clone();
+    let waiter = thread::spawn(move || waiting.wait());
+    producer.kill();
+    assert_eq!(waiter.join().unwrap(), Err(RecvError));
+    assert!(rx.is_closed());
+    assert_eq!(rx.get(), 7);
+
+    // The object is left behind, and still holds the last value.
+    assert!(object_exists(&name));
+    let mut late = shm::open_receiver::<u32>(&name).unwrap();
+    assert_eq!(late.get(), 7);
+    assert_eq!(
+        late.wait_timeout(Duration::from_secs(10)),
+        Err(RecvTimeoutError::Closed)
+    );
+    shm::remove(&name).unwrap();
+    assert!(!object_exists(&name));
+}
+
+#[test]
+fn a_producer_killed_while_sending_leaves_an_intact_value() {
+    for attempt in 0..5 {
+        let name = unique(&format!("killed-{}", attempt));
+        let mut spammer = Child::spawn("spammer", &name);
+        spammer.wait_until_ready();
+        let mut rx = shm::open_receiver::<[u64; 16]>(&name).unwrap();
+        thread::sleep(Duration::from_millis(5));
+        spammer.kill();
+
+        // The kill most likely lands in the middle of a send, which must
+        // not leave a torn value or readers spinning on it.
+        let value = rx.get();
+        assert!(value.iter().all(|&n| n == value[0]), "torn: {:?}", value);
+        assert!(value[0] > 0);
+        assert_eq!(rx.wait(), Err(RecvError));
+        assert_eq!(rx.get(), value);
+        shm::remove(&name).unwrap();
+    }
+}
+
+#[test]
+fn a_channel_left_behind_is_replaced() {
+    let name = unique("replaced");
+    let mut producer = Child::spawn("producer", &name);
+    producer.wait_until_ready();
+    let mut old = shm::open_receiver::<u32>(&name).unwrap();
+    producer.kill();
+
+    let (tx, mut rx) = shm::channel(&name, 1u32).unwrap();
+    tx.send(2);
+    assert_eq!(rx.get(), 2);
+    assert_eq!(old.get(), 7);
+    assert!(old.is_closed());
+    assert_eq!(shm::open_receiver::<u32>(&name).unwrap().get(), 2);
+    drop(tx);
+    assert!(!object_exists(&name));
+}
+
+#[test]
+fn a_live_channel_is_not_replaced() {
+    let name = unique("live");
+    let mut producer = Child::spawn("producer", &name);
+    producer.wait_until_ready();
+    let error = shm::channel(&name, 1u32).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+    let mut rx = shm::open_receiver::<u32>(&name).unwrap();
+    assert_eq!(rx.get(), 7);
+    assert!(!rx.is_closed());
+
+    // Nor is one in this process.
+    let other = unique("live-here");
+    let (_tx, _rx) = shm::channel(&other, 1u32).unwrap();
+    let error = shm::channel(&other, 2u32).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(shm::open_receiver::<u32>(&other).unwrap().get(), 1);
+
+    producer.kill();
+    shm::remove(&name).unwrap();
+}
+
+#[test]
+fn objects_that_are_not_channels_are_not_replaced() {
+    let name = unique("file");
+    std::fs::write(Path::new("/dev/shm").join(&name), b"not a channel").unwrap();
+    let error = shm::channel(&name, 1u32).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+    let error = shm::open_receiver::<u32>(&name).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    shm::remove(&name).unwrap();
+}
+
+#[test]
+fn dropping_a_sender_keeps_a_name_given_to_another_channel() {
+    let name = unique("renamed");
+    let (first, _rx) = shm::channel(&name, 1u32).unwrap();
+    shm::remove(&name).unwrap();
+    let (_second, _rx) = shm::channel(&name, 2u32).unwrap();
+    drop(first);
+
+    let mut rx = shm::open_receiver::<u32>(&name).unwrap();
+    assert_eq!(rx.get(), 2);
+    assert!(!rx.is_closed());
+}
+
+#[test]
+fn dropping_the_sender_removes_the_name() {
+    let name = unique("dropped");
+    let (tx, mut rx) = shm::channel(&name, 1u32).unwrap();
+    assert!(object_exists(&name));
+    drop(tx);
+    assert!(!object_exists(&name));
+    let error = shm::open_receiver::<u32>(&name).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    // Receivers that were attached keep the memory.
+    assert_eq!(rx.get(), 1);
+    assert_eq!(rx.wait(), Err(RecvError));
+}
+
+#[test]
+fn receivers_check_the_type() {
+    let name = unique("type");
+    let (_tx, _rx) = shm::channel(&name, 1u32).unwrap();
+    let error = shm::open_receiver::<u64>(&name).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    let error = shm::open_receiver::<[u8; 4]>(&name).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(shm::open_receiver::<i32>(&name).is_ok());
+}
@@ -0,0 +1,142 @@
+//! Receivers as signals of `futures-signals`, polled by hand.
+#![cfg(all(feature = "futures-signals", not(target_family = "wasm")))]
+
+use futures_signals::signal::Signal;
+use std::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    thread,
+};
+use watch::{RecvError, WatchSignal};
+
+mod util;
+use util::{
+    eventually,
+    task::{counting_waker, thread_waker},
+};
+
+fn poll<T: Clone>(signal: &mut WatchSignal<T>, waker: &Waker) -> Poll<Option<T>> {
+    Pin::new(signal).poll_change(&mut Context::from_waker(waker))
+}
+
+/// Poll `signal` until it is ready, parking in between.
+fn next<T: Clone>(signal: &mut WatchSignal<T>) -> Option<T> {
+    let waker = thread_waker();
+    loop {
+        if let Poll::Ready(value) = poll(signal, &waker) {
+            return value;
+        }
+        thread::park();
+    }
+}
+
+#[test]
+fn the_first_poll_yields_the_seen_value() {
+    let (_tx, mut rx) = watch::channel(1);
+    assert_eq!(rx.get(), 1);
+    let (wakes, waker) = counting_waker();
+    let mut signal = rx.into_signal();
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(1)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+    assert_eq!(wakes.count(), 0);
+}
+
+#[test]
+fn a_send_wakes_the_signal_once() {
+    let (tx, rx) = watch::channel(0);
+    let (wakes, waker) = counting_waker();
+    let mut signal = rx.into_signal();
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(0)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+    // Polling again while pending does not register the waker twice.
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(wakes.count(), 1);
+    // The values replaced in between are skipped.
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(2)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+    tx.send(3);
+    assert_eq!(wakes.count(), 2);
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(3)));
+}
+
+#[test]
+fn the_signal_ends_after_the_last_value() {
+    let (tx, rx) = watch::channel(0);
+    let (wakes, waker) = counting_waker();
+    let mut signal = rx.into_signal();
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(0)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+
+    // A value sent just before the senders drop is still yielded.
+    tx.send(1);
+    drop(tx);
+    assert!(wakes.count() >= 1);
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(1)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(None));
+
+    // Dropping the sender wakes a pending signal.
+    let (tx, rx) = watch::channel(0);
+    let mut signal = rx.into_signal();
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(0)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+    let before = wakes.count();
+    drop(tx);
+    assert_eq!(wakes.count(), before + 1);
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(None));
+}
+
+#[test]
+fn values_from_another_thread() {
+    let (tx, rx) = watch::channel(0);
+    let mut signal = rx.into_signal();
+    assert_eq!(next(&mut signal), Some(0));
+    let sender = thread::spawn(move || {
+        for value in 1..=100 {
+            tx.send(value);
+        }
+    });
+    let mut last = 0;
+    while let Some(value) = next(&mut signal) {
+        assert!(value > last);
+        last = value;
+    }
+    assert_eq!(last, 100);
+    sender.join().unwrap();
+}
+
+#[test]
+fn into_inner_gives_back_the_receiver() {
+    let (tx, rx) = watch::channel(0);
+    let (wakes, waker) = counting_waker();
+    let mut signal = rx.into_signal();
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(0)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+
+    // The waker of the signal is unregistered.
+    let mut rx = signal.into_inner();
+    tx.send(1);
+    assert_eq!(wakes.count(), 0);
+
+    let waiter = thread::spawn(move || {
+        assert_eq!(rx.recv(), Ok(1));
+        rx.recv()
+    });
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    drop(tx);
+    assert_eq!(waiter.join().unwrap(), Err(RecvError));
+}
+
+#[test]
+fn dropping_a_pending_signal_unregisters_it() {
+    let (tx, rx) = watch::channel(0);
+    let (wakes, waker) = counting_waker();
+    let mut signal = rx.into_signal();
+    assert_eq!(poll(&mut signal, &waker), Poll::Ready(Some(0)));
+    assert_eq!(poll(&mut signal, &waker), Poll::Pending);
+    drop(signal);
+    tx.send(1);
+    assert_eq!(wakes.count(), 0);
+}
@@ -0,0 +1,137 @@
+//! Signals raised in the test process itself.
+#![cfg(all(feature = "signals", unix))]
+
+use signal_hook::{
+    consts::{SIGKILL, SIGUSR1, SIGUSR2, SIGWINCH},
+    low_level::raise,
+};
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+use watch::{RecvTimeoutError, SignalEvent, WatchReceiver};
+
+mod util;
+use util::eventually;
+
+const LONG: Duration = Duration::from_secs(10);
+
+/// The signals go to the whole process, so the tests take turns.
+static TURN: Mutex<()> = Mutex::new(());
+
+fn turn() -> MutexGuard<'static, ()> {
+    TURN.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn next(rx: &mut WatchReceiver<SignalEvent>) -> (i32, u64) {
+    let event = rx.recv_timeout(LONG).unwrap();
+    (event.signal, event.count)
+}
+
+#[test]
+fn nothing_is_new_until_a_signal_arrives() {
+    let _turn = turn();
+    let mut rx = watch::signals(&[SIGUSR1]).unwrap();
+    assert!(!rx.has_changed());
+    let event = rx.get();
+    assert_eq!((event.signal, event.count), (0, 0));
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(20)),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+#[test]
+fn signals_are_delivered() {
+    let _turn = turn();
+    let mut rx = watch::signals(&[SIGUSR1, SIGUSR2]).unwrap();
+    raise(SIGUSR1).unwrap();
+    assert_eq!(next(&mut rx), (SIGUSR1, 1));
+    raise(SIGUSR2).unwrap();
+    assert_eq!(next(&mut rx), (SIGUSR2, 1));
+    // Each signal is counted on its own.
+    raise(SIGUSR1).unwrap();
+    assert_eq!(next(&mut rx), (SIGUSR1, 2));
+}
+
+#[test]
+fn signals_that_are_not_read_are_coalesced() {
+    let _turn = turn();
+    let mut rx = watch::signals(&[SIGWINCH]).unwrap();
+    let mut peek = rx.clone();
+    for _ in 0..3 {
+        raise(SIGWINCH).unwrap();
+        // Wait for the thread, as the OS merges a signal that arrives again
+        // before it was handled.
+        let handled = peek.get().count;
+        assert!(eventually(|| peek.get().count > handled));
+    }
+    // The receiver sees a single event, with the count of all three.
+    assert_eq!(rx.get_if_new().map(|event| event.count), Some(3));
+    assert_eq!(rx.get_if_new(), None);
+}
+
+#[test]
+fn each_call_counts_on_its_own() {
+    let _turn = turn();
+    let mut first = watch::signals(&[SIGUSR2]).unwrap();
+    raise(SIGUSR2).unwrap();
+    assert_eq!(next(&mut first), (SIGUSR2, 1));
+    let mut second = watch::signals(&[SIGUSR2]).unwrap();
+    raise(SIGUSR2).unwrap();
+    assert_eq!(next(&mut first), (SIGUSR2, 2));
+    assert_eq!(next(&mut second), (SIGUSR2, 1));
+}
+
+#[test]
+fn events_are_timestamped_when_handled() {
+    use std::time::Instant;
+
+    let _turn = turn();
+    let mut rx = watch::signals(&[SIGUSR1]).unwrap();
+    let before = Instant::now();
+    raise(SIGUSR1).unwrap();
+    let event = rx.recv_timeout(LONG).unwrap();
+    assert!(event.at >= before);
+    assert!(event.at <= Instant::now());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn the_threads_stop_with_the_last_receiver() {
+    use std::fs;
+
+    /// The number of threads of this process that deliver signals.
+    fn threads() -> usize {
+        fs::read_dir("/proc/self/task")
+            .unwrap()
+            .filter_map(|task| fs::read_to_string(task.unwrap().path().join("comm")).ok())
+            .filter(|name| name.starts_with("watch-signals"))
+            .count()
+    }
+
+    let _turn = turn();
+    assert!(eventually(|| threads() == 0));
+    let rx = watch::signals(&[SIGUSR1]).unwrap();
+    let cloned = rx.clone();
+    // The threads name themselves once they run.
+    assert!(eventually(|| threads() == 2));
+    drop(rx);
+    // A clone still keeps them running.
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(threads(), 2);
+    drop(cloned);
+    assert!(eventually(|| threads() == 0));
+}
+
+#[test]
+fn signals_that_cannot_be_handled_are_rejected() {
+    use std::io::ErrorKind;
+
+    let error = watch::signals(&[SIGUSR1, SIGKILL]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+        error.to_string(),
+        format!("signal {} cannot be handled", SIGKILL)
+    );
+}
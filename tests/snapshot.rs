@@ -0,0 +1,121 @@
+#![cfg(feature = "serde")]
+
+use watch::Snapshot;
+
+#[test]
+fn snapshot_round_trips_through_json() {
+    let (tx, _rx) = watch::channel(String::from("a"));
+    tx.send("b".into());
+    let snapshot = tx.snapshot();
+    assert_eq!(snapshot.value, "b");
+    assert_eq!(snapshot.version, 2);
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let restored: Snapshot<String> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, snapshot);
+
+    let (tx, mut rx) = watch::channel_from_snapshot(restored);
+    assert_eq!(tx.snapshot(), snapshot);
+    assert_eq!(rx.get_versioned(), (String::from("b"), 2));
+}
+
+#[test]
+fn restored_versions_keep_counting() {
+    let (tx, mut rx) = watch::channel_from_snapshot(Snapshot {
+        value: 1,
+        version: 41,
+    });
+    rx.get();
+    tx.send(2);
+    assert_eq!(rx.get_if_new(), Some(2));
+    assert_eq!(tx.snapshot().version, 42);
+}
+
+#[test]
+fn restore_only_applies_newer_snapshots() {
+    let (tx, mut rx) = watch::channel(String::from("a"));
+    tx.send("b".into());
+    rx.get();
+    let old = Snapshot {
+        value: String::from("old"),
+        version: 2,
+    };
+    assert!(!tx.restore(old));
+    assert_eq!(rx.get_if_new(), None);
+
+    let new = Snapshot {
+        value: String::from("z"),
+        version: 10,
+    };
+    assert!(tx.restore(new));
+    assert_eq!(rx.get_if_new().as_deref(), Some("z"));
+    assert_eq!(tx.snapshot().version, 10);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn restore_wakes_waiting_receivers() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let waiter = std::thread::spawn(move || rx.wait());
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert!(tx.restore(Snapshot {
+        value: 7,
+        version: 5
+    }));
+    assert_eq!(waiter.join().unwrap(), 7);
+}
+
+#[test]
+fn the_restoring_sender_is_the_last_writer() {
+    let (tx, mut rx) = watch::channel(0);
+    let other = tx.clone();
+    other.send(1);
+    assert!(tx.restore(Snapshot {
+        value: 42,
+        version: 100,
+    }));
+    assert_eq!(rx.get_versioned(), (42, 100));
+    assert_eq!(rx.last_writer(), tx.id());
+}
+
+#[test]
+fn a_restore_can_be_undone() {
+    let (tx, mut rx) = watch::builder().undo(true).channel(0);
+    tx.send(1);
+    assert!(tx.restore(Snapshot {
+        value: 42,
+        version: 100,
+    }));
+    // Undoing goes back to the value before the restore, not the one
+    // before the send.
+    tx.undo().unwrap();
+    assert_eq!(rx.get_versioned(), (1, 101));
+}
+
+#[test]
+fn a_restore_keeps_the_previous_value() {
+    let (tx, mut rx) = watch::builder().keep_previous(true).channel(0);
+    tx.send(1);
+    assert!(tx.restore(Snapshot {
+        value: 42,
+        version: 100,
+    }));
+    assert_eq!(rx.get_with_previous(), (Some(1), 42));
+}
+
+#[test]
+fn a_snapshot_that_is_not_newer_changes_nothing() {
+    let (tx, mut rx) = watch::builder().undo(true).channel(0);
+    let other = tx.clone();
+    other.send(1);
+    rx.get();
+    assert!(!tx.restore(Snapshot {
+        value: 42,
+        version: 2,
+    }));
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.last_writer(), other.id());
+    tx.undo().unwrap();
+    assert_eq!(rx.get(), 0);
+}
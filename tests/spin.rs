@@ -0,0 +1,66 @@
+//! The spinlock backend, which is also the default without std.
+#![cfg(feature = "spin")]
+
+use std::{thread, time::Duration};
+use watch::{backend::SpinCondvar, RecvError};
+
+#[test]
+fn wait_spins_until_a_send() {
+    let (tx, mut rx) = watch::backend::channel::<SpinCondvar, _>(0);
+    assert_eq!(rx.get(), 0);
+    let waiter = thread::spawn(move || {
+        let value = rx.wait();
+        (value, rx.recv())
+    });
+    thread::sleep(Duration::from_millis(20));
+    tx.send(5);
+    drop(tx);
+    assert_eq!(waiter.join().unwrap(), (5, Err(RecvError)));
+}
+
+#[test]
+fn many_senders_and_receivers() {
+    let (tx, rx) = watch::backend::channel::<SpinCondvar, _>(0u32);
+    let receivers: Vec<_> = (0..4)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while let Ok(value) = rx.recv() {
+                    assert!(value >= last);
+                    last = value;
+                }
+                last
+            })
+        })
+        .collect();
+    drop(rx);
+    let senders: Vec<_> = (0..2)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    tx.update(|value| *value += 1);
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+    for sender in senders {
+        sender.join().unwrap();
+    }
+    for receiver in receivers {
+        assert_eq!(receiver.join().unwrap(), 2000);
+    }
+}
+
+#[cfg(not(feature = "std"))]
+#[test]
+fn default_backend_is_the_spinlock() {
+    let (tx, rx): (
+        watch::WatchSender<i32, SpinCondvar>,
+        watch::WatchReceiver<i32, SpinCondvar>,
+    ) = watch::channel(1);
+    tx.send(2);
+    assert_eq!(rx.clone().get(), 2);
+}
@@ -0,0 +1,180 @@
+//! The channel with exactly one sender and one receiver.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+use util::join_all;
+use watch::{
+    spsc::{self, SpscReceiver, SpscSender},
+    RecvError, RecvTimeoutError,
+};
+
+#[test]
+fn semantics() {
+    let (tx, mut rx) = spsc::channel(1u32);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert_eq!(rx.get_if_new(), None);
+    assert!(!rx.has_changed());
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+
+    tx.send(2);
+    tx.update(|value| *value += 1);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), 3);
+    assert_eq!(rx.get_if_new(), None);
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(4);
+    });
+    assert_eq!(rx.wait(), 4);
+    sender.join().unwrap();
+    assert!(rx.is_closed());
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)),
+        Err(RecvTimeoutError::Closed)
+    );
+    assert_eq!(rx.get(), 4);
+}
+
+#[test]
+fn a_value_sent_before_the_sender_is_dropped_is_received() {
+    let (tx, mut rx) = spsc::channel(0u32);
+    rx.get();
+    tx.send(1);
+    drop(tx);
+    assert_eq!(rx.recv(), Ok(1));
+    assert_eq!(rx.recv(), Err(RecvError));
+
+    let (tx, mut rx) = spsc::channel(0u32);
+    rx.get();
+    tx.send(2);
+    drop(tx);
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(2));
+}
+
+#[test]
+fn the_sender_sees_the_receiver_go() {
+    let (tx, rx) = spsc::channel(0u32);
+    assert!(!tx.is_closed());
+    drop(rx);
+    assert!(tx.is_closed());
+    // Sending still works, nobody reads it.
+    tx.send(1);
+}
+
+#[test]
+fn a_panicking_update_keeps_its_changes() {
+    let (tx, mut rx) = spsc::channel(0u32);
+    rx.get();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|value| {
+            *value = 9;
+            panic!("update failed");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(rx.get_if_new(), Some(9));
+
+    // The channel keeps working afterwards.
+    tx.send(10);
+    assert_eq!(rx.wait(), 10);
+}
+
+#[test]
+fn the_receiver_is_woken_every_time() {
+    const ROUNDS: u32 = 2000;
+    let (tx, mut rx) = spsc::channel(0u32);
+    let (back_tx, mut back_rx) = spsc::channel(0u32);
+    rx.get();
+    back_rx.get();
+    let echo = thread::spawn(move || {
+        while let Ok(value) = rx.recv() {
+            back_tx.send(value);
+        }
+    });
+    for value in 1..=ROUNDS {
+        tx.send(value);
+        assert_eq!(back_rx.recv_timeout(Duration::from_secs(10)), Ok(value));
+    }
+    drop(tx);
+    join_all(vec![echo]);
+    assert_eq!(back_rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn values_only_move_forward() {
+    const SENDS: u64 = 50_000;
+    let (tx, mut rx) = spsc::channel(0u64);
+    assert_eq!(rx.get(), 0);
+    let sender = thread::spawn(move || {
+        for value in 1..=SENDS {
+            tx.send(value);
+        }
+    });
+    let mut last = 0;
+    while let Ok(value) = rx.recv() {
+        assert!(value > last, "{} after {}", value, last);
+        last = value;
+    }
+    join_all(vec![sender]);
+    assert_eq!(last, SENDS);
+}
+
+#[test]
+fn timeouts_race_with_sends() {
+    let (tx, mut rx) = spsc::channel(0u64);
+    assert_eq!(rx.get(), 0);
+    let sender = thread::spawn(move || {
+        for value in 1..=500 {
+            tx.send(value);
+            thread::yield_now();
+        }
+    });
+    let mut last = 0;
+    loop {
+        match rx.recv_timeout(Duration::from_micros(50)) {
+            Ok(value) => {
+                assert!(value > last);
+                last = value;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Closed) => break,
+        }
+    }
+    join_all(vec![sender]);
+    assert_eq!(last, 500);
+}
+
+#[test]
+fn values_are_dropped() {
+    use std::sync::Arc;
+
+    let first = Arc::new(());
+    let second = Arc::new(());
+    let (tx, rx) = spsc::channel(first.clone());
+    tx.send(second.clone());
+    assert_eq!(Arc::strong_count(&first), 1);
+    drop(tx);
+    assert_eq!(Arc::strong_count(&second), 2);
+    drop(rx);
+    assert_eq!(Arc::strong_count(&second), 1);
+}
+
+#[test]
+fn handles_are_send_and_sync() {
+    fn send_sync<T: Send + Sync>() {}
+    send_sync::<SpscSender<Vec<u8>>>();
+    send_sync::<SpscReceiver<Vec<u8>>>();
+}
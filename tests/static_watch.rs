@@ -0,0 +1,99 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+use watch::{StaticReceiver, StaticWatch};
+
+mod util;
+use util::join_all;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Config {
+    level: u32,
+    name: &'static str,
+}
+
+impl Config {
+    const DEFAULT: Config = Config {
+        level: 0,
+        name: "default",
+    };
+}
+
+#[test]
+fn receivers_on_other_threads() {
+    static CONFIG: StaticWatch<Config> = StaticWatch::new(Config::DEFAULT);
+
+    let mut rx = CONFIG.subscribe();
+    assert_eq!(rx.get(), Config::DEFAULT);
+    assert!(!rx.has_changed());
+
+    // The receivers are subscribed here, so none of them can miss the send.
+    let waiters = (0..4)
+        .map(|_| {
+            let mut rx: StaticReceiver<Config> = CONFIG.subscribe();
+            thread::spawn(move || rx.wait())
+        })
+        .collect();
+    CONFIG.send(Config {
+        level: 1,
+        name: "one",
+    });
+    for config in join_all(waiters) {
+        assert_eq!(config.name, "one");
+    }
+    assert_eq!(rx.get_if_new().map(|config| config.level), Some(1));
+    assert_eq!(rx.wait_timeout(Duration::from_millis(10)), None);
+    assert!(std::ptr::eq(rx.channel(), &CONFIG));
+}
+
+#[test]
+fn senders_on_other_threads() {
+    static LEVEL: StaticWatch<u32> = StaticWatch::new(0);
+
+    let mut rx = LEVEL.subscribe();
+    let senders = (0..4)
+        .map(|_| {
+            thread::spawn(|| {
+                for _ in 0..1000 {
+                    LEVEL.update(|level| *level += 1);
+                }
+            })
+        })
+        .collect();
+    join_all(senders);
+    assert_eq!(rx.get(), 4000);
+}
+
+#[test]
+fn clones_keep_their_place() {
+    static VALUE: StaticWatch<u32> = StaticWatch::new(0);
+
+    let mut rx = VALUE.subscribe();
+    VALUE.send(1);
+    let mut cloned = rx.clone();
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert_eq!(cloned.get_if_new(), Some(1));
+    VALUE.send(2);
+    assert_eq!(cloned.wait_with(|| {}), 2);
+}
+
+#[test]
+fn panicking_update_is_published() {
+    static SAMPLES: StaticWatch<Vec<u32>> = StaticWatch::new(Vec::new());
+
+    let mut rx = SAMPLES.subscribe();
+    SAMPLES.update(|samples| samples.push(1));
+    assert_eq!(rx.get_if_new(), Some(vec![1]));
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        SAMPLES.update(|samples| {
+            samples.push(2);
+            panic!("in update");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(rx.get_if_new(), Some(vec![1, 2]));
+}
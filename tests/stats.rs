@@ -0,0 +1,76 @@
+#![cfg(all(feature = "stats", not(target_family = "wasm")))]
+
+use std::thread;
+use watch::ChannelStats;
+
+mod util;
+use util::eventually;
+
+#[test]
+fn counters_start_at_zero() {
+    let (tx, rx) = watch::channel(0u32);
+    assert_eq!(tx.stats(), ChannelStats::default());
+    assert_eq!(rx.stats().sends, 0);
+}
+
+#[test]
+fn sends_and_empty_polls() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    assert_eq!(rx.get_if_new(), None);
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(1);
+    tx.update(|value| *value += 1);
+    assert_eq!(rx.get_if_new(), Some(2));
+
+    let stats = tx.stats();
+    assert_eq!(stats.sends, 2);
+    assert_eq!(stats.empty_polls, 2);
+    // Nobody was waiting.
+    assert_eq!(stats.notifications, 0);
+    assert_eq!(stats.wakeups, 0);
+    assert_eq!(stats.waiters, 0);
+    // Every handle reads the counters of the whole channel.
+    assert_eq!(rx.stats(), stats);
+}
+
+#[test]
+fn a_wait_is_notified_and_wakes_up() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let waiter = thread::spawn(move || rx.wait());
+    assert!(eventually(|| tx.stats().waiters == 1));
+
+    tx.send(3);
+    assert_eq!(waiter.join().unwrap(), 3);
+    let stats = tx.stats();
+    assert_eq!(stats.sends, 1);
+    assert_eq!(stats.notifications, 1);
+    // A spurious wakeup counts too.
+    assert!(stats.wakeups >= 1);
+    assert_eq!(stats.waiters, 0);
+}
+
+#[test]
+fn closing_notifies_every_waiter() {
+    let (tx, rx) = watch::channel(0u32);
+    let waiters: Vec<_> = (0..3)
+        .map(|_| {
+            let mut rx = rx.clone();
+            rx.get();
+            thread::spawn(move || rx.recv())
+        })
+        .collect();
+    assert!(eventually(|| tx.stats().waiters == 3));
+    let stats = {
+        let rx = rx;
+        drop(tx);
+        for waiter in waiters {
+            assert!(waiter.join().unwrap().is_err());
+        }
+        rx.stats()
+    };
+    assert_eq!(stats.sends, 0);
+    assert_eq!(stats.notifications, 3);
+    assert_eq!(stats.waiters, 0);
+}
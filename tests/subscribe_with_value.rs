@@ -0,0 +1,55 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn the_value_is_seen_by_the_new_receiver() {
+    let (tx, rx) = watch::channel(0u64);
+    let (mut subscribed, value) = tx.subscribe_with_value();
+    assert_eq!(value, 0);
+    assert!(!subscribed.has_changed());
+    // The original receiver is unaffected.
+    assert!(rx.has_changed());
+
+    tx.send(1);
+    assert_eq!(subscribed.get_if_new(), Some(1));
+}
+
+#[test]
+fn clone_with_value_leaves_the_original_alone() {
+    let (tx, mut rx) = watch::channel(0u64);
+    tx.send(1);
+    let (cloned, value) = rx.clone_with_value();
+    assert_eq!(value, 1);
+    assert!(!cloned.has_changed());
+    assert!(rx.has_changed());
+    assert_eq!(tx.receiver_count(), 2);
+    assert_eq!(rx.get(), 1);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn nothing_newer_is_missed() {
+    use std::thread;
+
+    let (tx, _rx) = watch::channel(0u64);
+    let sender = {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            for value in 1..=20_000 {
+                tx.send(value);
+            }
+        })
+    };
+    let mut last = 0;
+    while last < 20_000 {
+        let (mut rx, value) = tx.subscribe_with_value();
+        assert!(value >= last);
+        if let Some(newer) = rx.get_if_new() {
+            assert!(newer > value);
+        }
+        last = value;
+    }
+    sender.join().unwrap();
+}
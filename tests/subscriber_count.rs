@@ -0,0 +1,85 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::thread;
+use watch::RecvError;
+
+#[test]
+fn the_count_follows_the_receivers() {
+    let (tx, rx) = watch::channel(0);
+    let mut count = tx.subscriber_count_watch();
+    // The count when it was created counts as seen.
+    assert!(!count.has_changed());
+    assert_eq!(count.get(), 1);
+
+    let subscribed = tx.subscribe();
+    assert_eq!(count.get_if_new(), Some(2));
+    let cloned = subscribed.clone();
+    assert_eq!(count.get_if_new(), Some(3));
+    drop(subscribed);
+    assert_eq!(count.get_if_new(), Some(2));
+    drop(cloned);
+    drop(rx);
+    assert_eq!(count.get_if_new(), Some(0));
+    let rx = tx.clone().into_receiver();
+    assert_eq!(count.get_if_new(), Some(1));
+    drop(rx);
+    assert_eq!(count.get_if_new(), Some(0));
+}
+
+#[test]
+fn watching_does_not_count_as_a_receiver() {
+    let (tx, rx) = watch::channel(0);
+    let mut first = tx.subscriber_count_watch();
+    let mut second = tx.subscriber_count_watch();
+    assert_eq!(tx.receiver_count(), 1);
+    drop(rx);
+    assert!(tx.is_closed());
+    // Both watch the same count.
+    assert_eq!(first.get_if_new(), Some(0));
+    assert_eq!(second.get_if_new(), Some(0));
+}
+
+#[test]
+fn churn_coalesces_to_the_latest_count() {
+    let (tx, _rx) = watch::channel(0);
+    let mut count = tx.subscriber_count_watch();
+    for _ in 0..100 {
+        drop(tx.subscribe());
+    }
+    assert_eq!(count.get_if_new(), Some(1));
+    assert_eq!(count.get_if_new(), None);
+
+    let held: Vec<_> = (0..10).map(|_| tx.subscribe()).collect();
+    assert_eq!(count.get_if_new(), Some(11));
+    drop(held);
+    assert_eq!(count.get_if_new(), Some(1));
+}
+
+#[test]
+fn a_producer_works_while_someone_listens() {
+    let (tx, rx) = watch::channel(0);
+    drop(rx);
+    let subscriber = tx.clone();
+    let mut count = tx.subscriber_count_watch();
+    let producer = thread::spawn(move || {
+        while count.wait() == 0 {}
+        tx.send(1);
+        while count.wait() != 0 {}
+    });
+    let mut rx = subscriber.subscribe();
+    assert_eq!(rx.recv(), Ok(1));
+    drop(rx);
+    producer.join().unwrap();
+}
+
+#[test]
+fn the_count_closes_with_the_channel() {
+    let (tx, rx) = watch::channel(0);
+    let mut count = tx.subscriber_count_watch();
+    // The senders of the channel are not senders of the count.
+    drop(tx);
+    assert!(!count.is_closed());
+    drop(rx);
+    assert_eq!(count.recv(), Ok(0));
+    assert_eq!(count.recv(), Err(RecvError));
+}
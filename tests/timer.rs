@@ -0,0 +1,91 @@
+#![cfg(all(feature = "timer", not(target_family = "wasm")))]
+
+use std::time::Duration;
+
+const LONG: Duration = Duration::from_secs(60);
+
+#[test]
+fn the_value_is_sent_after_the_delay() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let scheduled = tx.send_after(1, Duration::from_millis(20));
+    assert!(scheduled.is_pending());
+    assert_eq!(rx.recv_timeout(LONG), Ok(1));
+    assert!(!scheduled.is_pending());
+    assert!(!scheduled.cancel());
+}
+
+#[test]
+fn dropping_the_handle_does_not_cancel() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send_after(1, Duration::from_millis(10));
+    assert_eq!(rx.recv_timeout(LONG), Ok(1));
+}
+
+#[test]
+fn cancelled_sends_are_not_made() {
+    let (tx, mut rx) = watch::channel(0);
+    let (_, version) = rx.get_versioned();
+    let cancelled = tx.send_after(1, Duration::from_millis(10));
+    let _marker = tx.send_after(2, Duration::from_millis(30));
+    assert!(cancelled.cancel());
+    assert_eq!(rx.recv_timeout(LONG), Ok(2));
+    // Only the marker was sent.
+    assert_eq!(rx.get_versioned(), (2, version + 1));
+}
+
+#[test]
+fn a_normal_send_does_not_cancel() {
+    let (tx, mut rx) = watch::channel(0);
+    let scheduled = tx.send_after(1, Duration::from_millis(20));
+    tx.send(9);
+    assert_eq!(rx.get(), 9);
+    assert!(scheduled.is_pending());
+    assert_eq!(rx.recv_timeout(LONG), Ok(1));
+}
+
+#[test]
+fn sends_due_together_are_made_in_order() {
+    let (tx, mut rx) = watch::channel(0);
+    let (_, version) = rx.get_versioned();
+    let _first = tx.send_after(1, Duration::from_millis(20));
+    let _second = tx.send_after(2, Duration::from_millis(20));
+    while rx.get_versioned().1 < version + 2 {
+        rx.recv_timeout(LONG).unwrap();
+    }
+    assert_eq!(rx.get_versioned(), (2, version + 2));
+}
+
+#[test]
+fn a_sooner_send_is_not_held_up_by_a_later_one() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let later = tx.send_after(1, LONG);
+    let _sooner = tx.send_after(2, Duration::from_millis(10));
+    assert_eq!(rx.recv_timeout(LONG), Ok(2));
+    assert!(later.cancel());
+}
+
+#[test]
+fn a_pending_send_keeps_the_channel_open() {
+    let (tx, rx) = watch::channel(0);
+    let scheduled = tx.send_after(1, LONG);
+    drop(tx);
+    assert!(!rx.is_closed());
+    assert!(scheduled.cancel());
+    assert!(rx.is_closed());
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn both_sends_due_together_are_published() {
+    let (tx, mut rx) = watch::channel(0);
+    let recorder = tx.record();
+    rx.get();
+    let _first = tx.send_after(1, Duration::from_millis(20));
+    let _second = tx.send_after(2, Duration::from_millis(20));
+    drop(tx);
+    while rx.recv().is_ok() {}
+    recorder.assert_published(&[1, 2]);
+}
@@ -0,0 +1,38 @@
+//! The shared timer thread, in a test binary of its own so that no other
+//! test starts threads while it counts them.
+#![cfg(all(feature = "timer", target_os = "linux"))]
+
+use std::{fs, thread, time::Duration};
+
+mod util;
+use util::eventually;
+
+fn threads() -> usize {
+    fs::read_dir("/proc/self/task").unwrap().count()
+}
+
+#[test]
+fn the_timer_thread_exits_when_no_job_is_left() {
+    let base = threads();
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+
+    tx.send_after(1, Duration::from_millis(10));
+    assert_eq!(threads(), base + 1);
+    assert_eq!(rx.recv_timeout(Duration::from_secs(60)), Ok(1));
+    assert!(eventually(|| threads() == base));
+
+    // A cancelled send does not keep the thread around until it was due.
+    let scheduled = tx.send_after(2, Duration::from_secs(3600));
+    assert_eq!(threads(), base + 1);
+    // Give the thread time to go to sleep until the send is due.
+    thread::sleep(Duration::from_millis(50));
+    assert!(scheduled.cancel());
+    assert!(eventually(|| threads() == base));
+
+    // Several channels share the thread.
+    let (other, _rx) = watch::channel(0);
+    let _a = tx.send_after(3, Duration::from_secs(3600));
+    let _b = other.send_after(3, Duration::from_secs(3600));
+    assert_eq!(threads(), base + 1);
+}
@@ -0,0 +1,200 @@
+//! The spans that channels created with `trace_spans` keep with their
+//! values.
+#![cfg(all(feature = "tracing", not(target_family = "wasm")))]
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+};
+use tracing::{span, Dispatch, Event, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+thread_local! {
+    /// The spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<span::Id>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Default)]
+struct Spans {
+    next_id: u64,
+    /// The metadata and the number of handles of every open span.
+    open: HashMap<u64, (&'static Metadata<'static>, usize)>,
+    /// The span each event was emitted in, outside the crate's own events.
+    events: Vec<(String, Option<u64>)>,
+}
+
+/// Keeps track of the spans, and the span that each event is emitted in.
+#[derive(Clone, Default)]
+struct Collect(Arc<Mutex<Spans>>);
+
+impl Collect {
+    fn events(&self) -> Vec<(String, Option<u64>)> {
+        self.0.lock().unwrap().events.clone()
+    }
+
+    fn is_open(&self, id: &span::Id) -> bool {
+        self.0.lock().unwrap().open.contains_key(&id.into_u64())
+    }
+
+    fn dispatch(&self) -> Dispatch {
+        Dispatch::new(self.clone())
+    }
+}
+
+impl Subscriber for Collect {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+        let mut spans = self.0.lock().unwrap();
+        spans.next_id += 1;
+        let id = spans.next_id;
+        spans.open.insert(id, (attributes.metadata(), 1));
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        if event.metadata().target() == "watch" {
+            return;
+        }
+        let current = ENTERED.with(|entered| entered.borrow().last().map(span::Id::into_u64));
+        let name = event.metadata().name().to_string();
+        self.0.lock().unwrap().events.push((name, current));
+    }
+
+    fn enter(&self, id: &span::Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(id.clone()));
+    }
+
+    fn exit(&self, _: &span::Id) {
+        ENTERED.with(|entered| entered.borrow_mut().pop());
+    }
+
+    fn current_span(&self) -> Current {
+        let current = ENTERED.with(|entered| entered.borrow().last().cloned());
+        match current {
+            Some(id) => {
+                let metadata = self.0.lock().unwrap().open[&id.into_u64()].0;
+                Current::new(id, metadata)
+            }
+            None => Current::none(),
+        }
+    }
+
+    fn clone_span(&self, id: &span::Id) -> span::Id {
+        let mut spans = self.0.lock().unwrap();
+        spans.open.get_mut(&id.into_u64()).unwrap().1 += 1;
+        id.clone()
+    }
+
+    fn try_close(&self, id: span::Id) -> bool {
+        let mut spans = self.0.lock().unwrap();
+        let handles = &mut spans.open.get_mut(&id.into_u64()).unwrap().1;
+        *handles -= 1;
+        if *handles == 0 {
+            spans.open.remove(&id.into_u64());
+            return true;
+        }
+        false
+    }
+}
+
+#[test]
+fn the_consumer_runs_in_the_span_of_the_producer() {
+    let collect = Collect::default();
+    let (tx, mut rx) = watch::builder().trace_spans(true).channel(0u32);
+    rx.get();
+    let consumer = {
+        let dispatch = collect.dispatch();
+        thread::spawn(move || {
+            tracing::dispatcher::with_default(&dispatch, || {
+                let (value, span) = rx.wait_traced();
+                span.in_scope(|| tracing::info!("consumed"));
+                value
+            })
+        })
+    };
+
+    let producer = tracing::dispatcher::with_default(&collect.dispatch(), || {
+        let producer = tracing::info_span!("request");
+        producer.in_scope(|| tx.send(1));
+        producer.id().unwrap()
+    });
+    assert_eq!(consumer.join().unwrap(), 1);
+    let consumed: Vec<_> = collect.events().into_iter().map(|event| event.1).collect();
+    assert_eq!(consumed, [Some(producer.into_u64())]);
+}
+
+#[test]
+fn each_value_has_the_span_it_was_written_in() {
+    let collect = Collect::default();
+    tracing::dispatcher::with_default(&collect.dispatch(), || {
+        let (tx, mut rx) = watch::builder().trace_spans(true).channel(0u32);
+        let (value, span) = rx.get_traced();
+        assert_eq!(value, 0);
+        assert!(span.is_none());
+
+        let first = tracing::info_span!("first");
+        first.in_scope(|| tx.send(1));
+        let (value, span) = rx.get_if_new_traced().unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(span.id(), first.id());
+        assert!(rx.get_if_new_traced().is_none());
+
+        let second = tracing::info_span!("second");
+        second.in_scope(|| tx.update(|value| *value += 1));
+        let (value, span) = rx.get_traced();
+        assert_eq!(value, 2);
+        assert_eq!(span.id(), second.id());
+
+        // A write outside any span leaves none.
+        tx.send(3);
+        let (value, span) = rx.get_traced();
+        assert_eq!(value, 3);
+        assert!(span.is_none());
+    });
+}
+
+#[test]
+fn spans_are_dropped_with_their_values() {
+    let collect = Collect::default();
+    tracing::dispatcher::with_default(&collect.dispatch(), || {
+        let (tx, rx) = watch::builder().trace_spans(true).channel(0u32);
+        let first = tracing::info_span!("first");
+        first.in_scope(|| tx.send(1));
+        let first_id = first.id().unwrap();
+        drop(first);
+        // The channel keeps the span open while it has the value.
+        assert!(collect.is_open(&first_id));
+
+        let second = tracing::info_span!("second");
+        second.in_scope(|| tx.send(2));
+        assert!(!collect.is_open(&first_id));
+        let second_id = second.id().unwrap();
+        drop(second);
+        assert!(collect.is_open(&second_id));
+        drop((tx, rx));
+        assert!(!collect.is_open(&second_id));
+    });
+}
+
+#[test]
+fn channels_keep_no_span_by_default() {
+    let collect = Collect::default();
+    tracing::dispatcher::with_default(&collect.dispatch(), || {
+        let (tx, mut rx) = watch::channel(0u32);
+        let span = tracing::info_span!("request");
+        span.in_scope(|| tx.send(1));
+        let (value, kept) = rx.get_traced();
+        assert_eq!(value, 1);
+        assert!(kept.is_none());
+        assert_ne!(kept.id(), span.id());
+    });
+}
@@ -0,0 +1,150 @@
+//! The events emitted with the `tracing` feature.
+#![cfg(all(feature = "tracing", not(target_family = "wasm")))]
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    thread,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+mod util;
+use util::eventually;
+
+/// An event that was emitted, with its fields as `name=value` pairs.
+#[derive(Debug, Clone)]
+struct Emitted {
+    target: String,
+    fields: Vec<String>,
+}
+
+impl Emitted {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find_map(|field| field.strip_prefix(name)?.strip_prefix('='))
+    }
+
+    fn message(&self) -> &str {
+        self.field("message").unwrap_or("")
+    }
+}
+
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+}
+
+/// Collects every event.
+#[derive(Clone, Default)]
+struct Collect(Arc<Mutex<Vec<Emitted>>>);
+
+impl Collect {
+    fn events(&self) -> Vec<Emitted> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Subscriber for Collect {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(Vec::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(Emitted {
+            target: event.metadata().target().to_string(),
+            fields: fields.0,
+        });
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn sends_and_updates_emit_the_new_version() {
+    let collect = Collect::default();
+    tracing::subscriber::with_default(collect.clone(), || {
+        let (tx, _rx) = watch::channel(0u32);
+        tx.send(1);
+        tx.update(|value| *value += 1);
+    });
+    let events = collect.events();
+    let published: Vec<_> = events
+        .iter()
+        .filter(|event| event.message() == "value published")
+        .map(|event| event.field("version").unwrap().to_string())
+        .collect();
+    assert_eq!(published, ["2", "3"]);
+    assert!(events.iter().all(|event| event.target == "watch"));
+}
+
+#[test]
+fn wakeups_and_closing_are_emitted() {
+    let collect = Collect::default();
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let channel = format!("{:?}", rx.channel_id());
+
+    let waiter = {
+        let collect = collect.clone();
+        thread::spawn(move || tracing::subscriber::with_default(collect, || rx.wait()))
+    };
+    tracing::subscriber::with_default(collect.clone(), || {
+        assert!(eventually(|| tx.waiting_receivers() == 1));
+        tx.send(1);
+        assert_eq!(waiter.join().unwrap(), 1);
+        drop(tx);
+    });
+
+    let events = collect.events();
+    let woke = events
+        .iter()
+        .find(|event| event.message() == "receiver woke")
+        .expect("no wakeup event");
+    assert_eq!(woke.field("version"), Some("2"));
+    assert!(events
+        .iter()
+        .any(|event| event.message() == "channel closed"));
+    // Every event names the channel by the number in its id.
+    for event in &events {
+        assert_eq!(event.target, "watch");
+        let id = event.field("channel").expect("no channel field");
+        assert!(channel.contains(id), "{} is not {}", id, channel);
+    }
+}
+
+#[test]
+fn channels_have_distinct_ids() {
+    let collect = Collect::default();
+    tracing::subscriber::with_default(collect.clone(), || {
+        let (a, _a) = watch::channel(0u32);
+        let (b, _b) = watch::channel(0u32);
+        a.send(1);
+        b.send(1);
+    });
+    let events: Vec<_> = collect
+        .events()
+        .into_iter()
+        .filter(|event| event.message() == "value published")
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert_ne!(events[0].field("channel"), events[1].field("channel"));
+}
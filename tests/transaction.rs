@@ -0,0 +1,230 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    thread,
+};
+
+mod util;
+use util::join_all;
+
+#[test]
+fn every_channel_gets_a_new_version() {
+    let (a_tx, mut a) = watch::channel(0u32);
+    let (b_tx, mut b) = watch::channel(String::new());
+    watch::get_all((&mut a, &mut b));
+    watch::transaction((&a_tx, &b_tx), |a, b| {
+        *a = 1;
+        b.push('x');
+    });
+    assert!(a.has_changed());
+    assert!(b.has_changed());
+    assert_eq!(watch::get_all((&mut b, &mut a)), ("x".to_string(), 1));
+    assert!(!a.has_changed());
+}
+
+#[test]
+fn opposite_orders_do_not_deadlock_or_tear() {
+    let (a_tx, a) = watch::channel(0u64);
+    let (b_tx, b) = watch::channel(0u64);
+    let (c_tx, c) = watch::channel(String::from("0"));
+    let writers = (0..4)
+        .map(|k| {
+            let (a_tx, b_tx, c_tx) = (a_tx.clone(), b_tx.clone(), c_tx.clone());
+            thread::spawn(move || {
+                for _ in 0..2000 {
+                    if k % 2 == 0 {
+                        watch::transaction((&a_tx, &b_tx, &c_tx), |a, b, c| {
+                            *a += 1;
+                            *b += 1;
+                            *c = a.to_string();
+                        });
+                    } else {
+                        watch::transaction((&c_tx, &b_tx, &a_tx), |c, b, a| {
+                            *a += 1;
+                            *b += 1;
+                            *c = a.to_string();
+                        });
+                    }
+                }
+            })
+        })
+        .collect();
+    let readers = (0..2)
+        .map(|_| {
+            let (mut a, mut b, mut c) = (a.clone(), b.clone(), c.clone());
+            thread::spawn(move || {
+                for _ in 0..5000 {
+                    let (b, a, c) = watch::get_all((&mut b, &mut a, &mut c));
+                    assert_eq!(a, b);
+                    assert_eq!(c, a.to_string());
+                }
+            })
+        })
+        .collect();
+    join_all(writers);
+    join_all(readers);
+    let (mut a, mut b) = (a, b);
+    assert_eq!(watch::get_all((&mut a, &mut b)), (8000, 8000));
+}
+
+#[test]
+fn four_channels() {
+    let (a_tx, mut a) = watch::channel(0u8);
+    let (b_tx, mut b) = watch::channel(0u16);
+    let (c_tx, mut c) = watch::channel(0u32);
+    let (d_tx, mut d) = watch::channel(0u64);
+    watch::transaction((&d_tx, &c_tx, &b_tx, &a_tx), |d, c, b, a| {
+        *a = 1;
+        *b = 2;
+        *c = 3;
+        *d = 4;
+    });
+    assert_eq!(
+        watch::get_all((&mut a, &mut b, &mut c, &mut d)),
+        (1, 2, 3, 4)
+    );
+}
+
+#[test]
+#[should_panic(expected = "twice")]
+fn the_same_channel_twice_panics() {
+    let (tx, _rx) = watch::channel(0);
+    watch::transaction((&tx, &tx.clone()), |_, _| {});
+}
+
+#[test]
+#[should_panic(expected = "twice")]
+fn reading_the_same_channel_twice_panics() {
+    let (_tx, mut rx) = watch::channel(0);
+    let mut other = rx.clone();
+    watch::get_all((&mut rx, &mut other));
+}
+
+#[test]
+fn a_panic_leaves_the_channels_usable() {
+    let (a_tx, mut a) = watch::channel(0);
+    let (b_tx, mut b) = watch::channel(0);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        watch::transaction((&a_tx, &b_tx), |a, _| {
+            *a = 5;
+            panic!("oops");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(a.get_checked().is_err());
+    assert_eq!(b.get(), 0);
+
+    // The channels are unlocked, so a later transaction goes through.
+    watch::transaction((&b_tx, &a_tx), |b, a| {
+        *b = 1;
+        *a = 2;
+    });
+    assert_eq!(watch::get_all((&mut a, &mut b)), (2, 1));
+}
+
+#[test]
+fn nothing_is_read_unless_a_value_is_new() {
+    let (a_tx, mut a) = watch::channel(0u32);
+    let (_b_tx, mut b) = watch::channel(String::new());
+    watch::get_all((&mut a, &mut b));
+    assert_eq!(watch::get_all_if_any_new((&mut a, &mut b)), None);
+
+    // One new value is enough, and every value is read and marked seen.
+    a_tx.send(1);
+    assert_eq!(
+        watch::get_all_if_any_new((&mut b, &mut a)),
+        Some((String::new(), 1))
+    );
+    assert!(!a.has_changed());
+    assert!(!b.has_changed());
+    assert_eq!(watch::get_all_if_any_new((&mut a, &mut b)), None);
+}
+
+#[test]
+fn values_new_to_one_receiver_only() {
+    let (a_tx, mut a) = watch::channel(0u32);
+    let (b_tx, mut b) = watch::channel(0u32);
+    let mut other_a = a.clone();
+    watch::get_all((&mut a, &mut b));
+    watch::get_all((&mut other_a, &mut b));
+    a_tx.send(1);
+    b_tx.send(2);
+    assert_eq!(watch::get_all_if_any_new((&mut a, &mut b)), Some((1, 2)));
+    // The clone has not seen the new value of its channel.
+    assert_eq!(
+        watch::get_all_if_any_new((&mut other_a, &mut b)),
+        Some((1, 2))
+    );
+    assert_eq!(watch::get_all_if_any_new((&mut other_a, &mut b)), None);
+}
+
+#[test]
+fn reads_only_if_new_do_not_deadlock_or_tear() {
+    let (a_tx, a) = watch::channel(0u64);
+    let (b_tx, b) = watch::channel(0u64);
+    let (c_tx, c) = watch::channel(0u64);
+    let writers = (0..3)
+        .map(|k| {
+            let (a_tx, b_tx, c_tx) = (a_tx.clone(), b_tx.clone(), c_tx.clone());
+            thread::spawn(move || {
+                for _ in 0..2000 {
+                    match k {
+                        0 => watch::transaction((&a_tx, &b_tx, &c_tx), |a, b, c| {
+                            *a += 1;
+                            *b += 1;
+                            *c += 1;
+                        }),
+                        1 => watch::transaction((&c_tx, &a_tx, &b_tx), |c, a, b| {
+                            *a += 1;
+                            *b += 1;
+                            *c += 1;
+                        }),
+                        _ => watch::transaction((&b_tx, &c_tx, &a_tx), |b, c, a| {
+                            *a += 1;
+                            *b += 1;
+                            *c += 1;
+                        }),
+                    }
+                }
+            })
+        })
+        .collect();
+    let readers = (0..2)
+        .map(|k| {
+            let (mut a, mut b, mut c) = (a.clone(), b.clone(), c.clone());
+            thread::spawn(move || {
+                let mut last = None;
+                for _ in 0..5000 {
+                    let read = if k == 0 {
+                        watch::get_all_if_any_new((&mut c, &mut a, &mut b))
+                            .map(|(c, a, b)| (a, b, c))
+                    } else {
+                        watch::get_all_if_any_new((&mut b, &mut c, &mut a))
+                            .map(|(b, c, a)| (a, b, c))
+                    };
+                    if let Some((a, b, c)) = read {
+                        assert_eq!((a, b), (c, c));
+                        assert!(Some(a) > last);
+                        last = Some(a);
+                    }
+                }
+            })
+        })
+        .collect();
+    join_all(writers);
+    join_all(readers);
+    let (mut a, mut b, mut c) = (a, b, c);
+    assert_eq!(
+        watch::get_all_if_any_new((&mut a, &mut b, &mut c)),
+        Some((6000, 6000, 6000))
+    );
+}
+
+#[test]
+#[should_panic(expected = "twice")]
+fn reading_the_same_channel_twice_only_if_new_panics() {
+    let (_tx, mut rx) = watch::channel(0);
+    let mut other = rx.clone();
+    watch::get_all_if_any_new((&mut rx, &mut other));
+}
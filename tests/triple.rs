@@ -0,0 +1,84 @@
+//! The triple-buffered single-producer, single-consumer channel.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{sync::mpsc, thread, time::Duration};
+use util::join_all;
+
+#[test]
+fn semantics() {
+    let (mut tx, mut rx) = watch::triple_channel(String::from("a"));
+    assert_eq!(rx.get_if_new().map(String::as_str), Some("a"));
+    assert!(rx.get_if_new().is_none());
+    assert_eq!(rx.get(), "a");
+    tx.send("b".into());
+    tx.send("c".into());
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), "c");
+    assert!(rx.wait_timeout(Duration::from_millis(10)).is_none());
+    tx.send("d".into());
+    drop(tx);
+    assert_eq!(rx.recv().unwrap(), "d");
+    assert!(rx.recv().is_err());
+    assert!(rx.is_closed());
+}
+
+#[test]
+fn reads_are_never_torn() {
+    const SENDS: u64 = 1_000_000;
+    let (mut tx, mut rx) = watch::triple_channel([0u64; 32]);
+    let sender = thread::spawn(move || {
+        for i in 1..=SENDS {
+            tx.send([i; 32]);
+        }
+    });
+    let mut last = 0;
+    while last < SENDS {
+        let value = rx.get();
+        assert!(value.iter().all(|&x| x == value[0]));
+        assert!(value[0] >= last);
+        last = value[0];
+    }
+    join_all(vec![sender]);
+}
+
+#[test]
+fn recv_gets_the_last_value() {
+    for _ in 0..20 {
+        let (mut tx, mut rx) = watch::triple_channel(0u64);
+        let sender = thread::spawn(move || {
+            for i in 1..=20_000 {
+                tx.send(i);
+                if i % 64 == 0 {
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut last = None;
+        while let Ok(&value) = rx.recv() {
+            assert!(last < Some(value));
+            last = Some(value);
+        }
+        assert_eq!(last, Some(20_000));
+        join_all(vec![sender]);
+    }
+}
+
+#[test]
+fn every_send_wakes_a_waiting_receiver() {
+    let (mut tx, mut rx) = watch::triple_channel(0u64);
+    let (ack, acks) = mpsc::channel();
+    rx.get();
+    let sender = thread::spawn(move || {
+        for i in 1..=20_000 {
+            tx.send(i);
+            acks.recv().unwrap();
+        }
+    });
+    for i in 1..=20_000 {
+        assert_eq!(*rx.wait(), i);
+        ack.send(()).unwrap();
+    }
+    join_all(vec![sender]);
+}
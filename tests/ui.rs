@@ -0,0 +1,29 @@
+//! Code that must not compile.
+//!
+//! The expected errors name the default backend, so these only run with it.
+#![cfg(all(
+    feature = "std",
+    not(any(feature = "parking_lot", feature = "futex")),
+    not(target_family = "wasm")
+))]
+
+#[test]
+fn ui() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/derive/*.rs");
+    tests.pass("tests/ui/derive/pass/*.rs");
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn select() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/select/*.rs");
+}
@@ -0,0 +1,5 @@
+// A seqlock channel of values that are not `Send` must not cross threads.
+fn main() {
+    let (_tx, rx) = watch::copy_channel(std::ptr::null::<u8>());
+    std::thread::spawn(move || drop(rx));
+}
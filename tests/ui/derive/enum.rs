@@ -0,0 +1,9 @@
+use watch::WatchFields;
+
+#[derive(WatchFields)]
+enum Mode {
+    On,
+    Off,
+}
+
+fn main() {}
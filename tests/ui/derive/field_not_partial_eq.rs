@@ -0,0 +1,11 @@
+use watch::WatchFields;
+
+#[derive(Clone)]
+struct Opaque;
+
+#[derive(WatchFields)]
+struct Settings {
+    opaque: Opaque,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use watch::WatchFields;
+
+#[derive(WatchFields)]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use watch::WatchFields;
+
+#[derive(Clone, PartialEq)]
+struct Tls {
+    port: u16,
+}
+
+#[derive(WatchFields)]
+struct Settings {
+    #[watch(nested)]
+    tls: Tls,
+}
+
+fn main() {}
@@ -0,0 +1,50 @@
+//! The generated API, which is documented and used without warnings.
+#![deny(warnings, missing_docs)]
+
+use watch::WatchFields;
+
+/// The TLS settings.
+#[derive(WatchFields, Clone, PartialEq)]
+pub struct Tls {
+    /// The certificate.
+    pub cert: String,
+    /// The port.
+    pub port: u16,
+}
+
+/// Every setting.
+#[derive(WatchFields)]
+pub struct Settings {
+    /// The name.
+    pub name: String,
+    /// Never sent.
+    #[watch(skip)]
+    pub secret: Vec<u8>,
+    /// The nested settings.
+    #[watch(nested)]
+    pub tls: Tls,
+}
+
+fn main() {
+    let settings = Settings {
+        name: String::from("a"),
+        secret: Vec::new(),
+        tls: Tls {
+            cert: String::new(),
+            port: 443,
+        },
+    };
+    let (senders, channels): (SettingsSenders, SettingsChannels) = settings.into_channels();
+    let SettingsChannels { mut name, tls } = channels.clone();
+    let TlsChannels { cert, mut port } = tls;
+    senders.set_name(String::from("b"));
+    senders.tls.set_port(8443);
+    let _: String = name.get();
+    let _: u16 = port.get();
+    drop(cert);
+    let _: SettingsChannels = senders.subscribe();
+    senders.clone().set_tls(Tls {
+        cert: String::from("c"),
+        port: 8443,
+    });
+}
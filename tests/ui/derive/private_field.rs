@@ -0,0 +1,25 @@
+mod config {
+    use watch::WatchFields;
+
+    #[derive(WatchFields)]
+    pub struct Settings {
+        pub name: String,
+        retries: u32,
+    }
+
+    pub fn settings() -> Settings {
+        Settings {
+            name: String::new(),
+            retries: 0,
+        }
+    }
+}
+
+use watch::WatchFields;
+
+fn main() {
+    let (senders, channels) = config::settings().into_channels();
+    senders.set_name(String::from("ok"));
+    senders.set_retries(1);
+    drop(channels.retries);
+}
@@ -0,0 +1,18 @@
+use watch::WatchFields;
+
+#[derive(WatchFields)]
+struct Settings {
+    name: String,
+    #[watch(skip)]
+    secret: Vec<u8>,
+}
+
+fn main() {
+    let settings = Settings {
+        name: String::new(),
+        secret: Vec::new(),
+    };
+    let (senders, channels) = settings.into_channels();
+    senders.set_secret(Vec::new());
+    drop(channels.secret);
+}
@@ -0,0 +1,6 @@
+use watch::WatchFields;
+
+#[derive(WatchFields)]
+struct Pair(u32, u32);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use watch::WatchFields;
+
+#[derive(WatchFields)]
+struct Settings {
+    #[watch(rename = "other")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+// The local channel must stay on the thread that created it.
+fn main() {
+    let (_tx, rx) = watch::local::channel(0);
+    std::thread::spawn(move || drop(rx));
+}
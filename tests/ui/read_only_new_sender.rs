@@ -0,0 +1,8 @@
+// A read-only receiver can neither create a sender nor turn back into a
+// receiver that can.
+fn main() {
+    let (_tx, rx) = watch::channel(0);
+    let read_only = rx.into_read_only();
+    let _tx = read_only.new_sender();
+    let _rx: watch::WatchReceiver<i32> = read_only.into();
+}
@@ -0,0 +1,8 @@
+// The `default` arm of a select comes last.
+fn main() {
+    let (_tx, mut rx) = watch::channel(0);
+    watch::select! {
+        default(std::time::Duration::from_secs(1)) => {}
+        _ = rx => {}
+    }
+}
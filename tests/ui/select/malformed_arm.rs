@@ -0,0 +1,7 @@
+// An arm needs a pattern for the value of the receiver.
+fn main() {
+    let (_tx, mut rx) = watch::channel(0);
+    watch::select! {
+        rx => {}
+    }
+}
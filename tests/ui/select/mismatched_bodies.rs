@@ -0,0 +1,9 @@
+// The bodies of the arms have the same type, as in a `match`.
+fn main() {
+    let (_tx, mut first) = watch::channel(0);
+    let (_tx, mut second) = watch::channel(0);
+    let _ = watch::select! {
+        value = first => value,
+        _ = second => "second",
+    };
+}
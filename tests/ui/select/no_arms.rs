@@ -0,0 +1,4 @@
+// A select needs an arm that waits on a receiver.
+fn main() {
+    let _: () = watch::select! {};
+}
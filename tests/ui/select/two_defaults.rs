@@ -0,0 +1,9 @@
+// A select has at most one `default` arm.
+fn main() {
+    let (_tx, mut rx) = watch::channel(0);
+    watch::select! {
+        _ = rx => {}
+        default(std::time::Duration::from_secs(1)) => {}
+        default(std::time::Duration::from_secs(2)) => {}
+    }
+}
@@ -0,0 +1,5 @@
+// A triple-buffered channel has a single receiver.
+fn main() {
+    let (_tx, rx) = watch::triple_channel(0);
+    let _other = rx.clone();
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+use watch::NothingToUndo;
+
+#[test]
+fn send_undo_send_undo() {
+    let (tx, mut rx) = watch::builder().undo(true).channel(1);
+    assert_eq!(tx.undo(), Err(NothingToUndo));
+    tx.send(2);
+    rx.get();
+    assert_eq!(tx.undo(), Ok(()));
+    assert_eq!(rx.get_if_new(), Some(1));
+    // A second undo does not go back to 2.
+    assert_eq!(tx.undo(), Err(NothingToUndo));
+    assert_eq!(rx.get_if_new(), None);
+
+    tx.send(3);
+    tx.send(4);
+    assert_eq!(tx.undo(), Ok(()));
+    assert_eq!(rx.get_if_new(), Some(3));
+    assert_eq!(tx.undo(), Err(NothingToUndo));
+}
+
+#[test]
+fn an_update_undoes_to_the_value_before_the_closure() {
+    let (tx, mut rx) = watch::builder().undo(true).channel(3);
+    tx.update(|value| *value *= 10);
+    assert_eq!(rx.get(), 30);
+    tx.update_with(|value| value + 1);
+    assert_eq!(tx.undo(), Ok(()));
+    assert_eq!(rx.get(), 30);
+}
+
+#[test]
+fn any_sender_undoes_the_latest_change() {
+    let (tx, mut rx) = watch::builder().undo(true).channel(1);
+    let other = tx.clone();
+    tx.send(2);
+    assert_eq!(other.undo(), Ok(()));
+    assert_eq!(rx.get(), 1);
+    // The undo is a change of its own, made by the sender that undid.
+    assert_eq!(rx.last_writer(), other.id());
+    assert_eq!(tx.undo(), Err(NothingToUndo));
+}
+
+#[test]
+fn channels_without_undo_keep_nothing() {
+    let (tx, _rx) = watch::channel(1);
+    tx.send(2);
+    assert_eq!(tx.undo(), Err(NothingToUndo));
+}
+
+#[test]
+fn transactions_can_be_undone_per_channel() {
+    let (a, mut a_rx) = watch::builder().undo(true).channel(1);
+    let (b, mut b_rx) = watch::channel(1);
+    watch::transaction((&a, &b), |a, b| {
+        *a = 5;
+        *b = 5;
+    });
+    assert_eq!(a.undo(), Ok(()));
+    assert_eq!(a_rx.get(), 1);
+    assert_eq!(b_rx.get(), 5);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn a_panicking_update_can_be_undone() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    let (tx, mut rx) = watch::builder().undo(true).channel(vec![1]);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|value| {
+            value.push(2);
+            panic!("oops");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(rx.get(), [1, 2]);
+    assert_eq!(tx.undo(), Ok(()));
+    assert_eq!(rx.get(), [1]);
+}
@@ -0,0 +1,85 @@
+//! A panic in the closure passed to `update` still publishes what it
+//! changed, on every backend.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+use watch::backend::{self, RawCondvar};
+
+fn panicking_update_is_published<C>()
+where
+    C: RawCondvar + Send + Sync + 'static,
+    C::RawMutex: Send + Sync,
+    C::RawRwLock: Send + Sync,
+{
+    let (tx, mut rx) = backend::channel::<C, _>(vec![1, 2]);
+    rx.get();
+    let mut waiter = rx.clone();
+    let waiter = thread::spawn(move || waiter.wait());
+    thread::sleep(Duration::from_millis(20));
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|value| {
+            value.push(3);
+            panic!("in update");
+        })
+    }));
+    assert!(result.is_err());
+
+    // The waiter was woken, and both see the value as the closure left it.
+    assert_eq!(waiter.join().unwrap(), vec![1, 2, 3]);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get(), vec![1, 2, 3]);
+
+    // The channel keeps working.
+    tx.send(vec![4]);
+    assert_eq!(rx.get_if_new(), Some(vec![4]));
+}
+
+fn panicking_update_with_keeps_the_old_value<C: RawCondvar>() {
+    let (tx, mut rx) = backend::channel::<C, _>(1);
+    rx.get();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.update_with(|_| -> i32 { panic!("in update_with") })
+    }));
+    assert!(result.is_err());
+    // Nothing was changed, so there is nothing new.
+    assert!(!rx.has_changed());
+    assert_eq!(rx.get(), 1);
+}
+
+#[test]
+fn default_backend() {
+    panicking_update_is_published::<backend::DefaultCondvar>();
+    panicking_update_with_keeps_the_old_value::<backend::DefaultCondvar>();
+}
+
+#[test]
+fn std_backend() {
+    panicking_update_is_published::<backend::StdCondvar>();
+    panicking_update_with_keeps_the_old_value::<backend::StdCondvar>();
+}
+
+#[cfg(feature = "parking_lot")]
+#[test]
+fn parking_lot_backend() {
+    panicking_update_is_published::<backend::ParkingLotCondvar>();
+    panicking_update_with_keeps_the_old_value::<backend::ParkingLotCondvar>();
+}
+
+#[test]
+fn local_channel() {
+    let (tx, mut rx) = watch::local::channel(1);
+    rx.get();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        tx.update(|value| {
+            *value = 5;
+            panic!("in update");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(rx.get_if_new(), Some(5));
+}
@@ -0,0 +1,38 @@
+//! Helpers shared by the tests.
+#![allow(dead_code)]
+
+pub mod task;
+
+use std::{
+    sync::mpsc,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Wait up to ten seconds for `done` to return true.
+pub fn eventually(mut done: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done() {
+        if Instant::now() > deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    true
+}
+
+/// Join every thread, failing instead of hanging if one of them never
+/// finishes, such as after a lost wakeup.
+pub fn join_all<T: Send + 'static>(threads: Vec<JoinHandle<T>>) -> Vec<T> {
+    let (done, results) = mpsc::channel();
+    thread::spawn(move || {
+        let results: Vec<_> = threads.into_iter().map(JoinHandle::join).collect();
+        let _ = done.send(results);
+    });
+    results
+        .recv_timeout(Duration::from_secs(60))
+        .expect("a thread never finished")
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+        .collect()
+}
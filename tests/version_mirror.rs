@@ -0,0 +1,92 @@
+//! The check for a new value that does not lock the channel.
+#![cfg(not(target_family = "wasm"))]
+
+use std::{
+    hint::black_box,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn a_finished_send_is_never_missed() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let (sent_tx, sent_rx) = mpsc::sync_channel::<u32>(0);
+    let (checked_tx, checked_rx) = mpsc::sync_channel::<()>(0);
+    let receiver = thread::spawn(move || {
+        for value in sent_rx {
+            // The send returned before this thread was told about it.
+            assert!(rx.has_changed());
+            assert_eq!(rx.get_if_new(), Some(value));
+            assert!(!rx.has_changed());
+            assert_eq!(rx.get_if_new(), None);
+            checked_tx.send(()).unwrap();
+        }
+    });
+    for value in 1..=10_000 {
+        tx.send(value);
+        sent_tx.send(value).unwrap();
+        checked_rx.recv().unwrap();
+    }
+    drop(sent_tx);
+    receiver.join().unwrap();
+}
+
+#[test]
+fn polling_while_sending() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let sender = thread::spawn(move || {
+        for value in 1..=100_000 {
+            tx.send(value);
+        }
+    });
+    // Whatever is seen never goes backwards, and the last value is seen.
+    let mut last = 0;
+    loop {
+        if let Some(value) = rx.get_if_new() {
+            assert!(value > last);
+            last = value;
+        }
+        if last == 100_000 {
+            break;
+        }
+    }
+    sender.join().unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn versions_wrap_around() {
+    let (tx, mut rx) = watch::channel_from_snapshot(watch::Snapshot {
+        value: 1,
+        version: u64::MAX,
+    });
+    assert_eq!(rx.get_if_new(), Some(1));
+    assert!(!rx.has_changed());
+    tx.send(2);
+    assert!(rx.has_changed());
+    assert_eq!(rx.get_versioned(), (2, 0));
+    assert_eq!(rx.get_if_new(), None);
+    tx.send(3);
+    assert_eq!(rx.get_if_new(), Some(3));
+}
+
+#[test]
+fn polling_without_a_new_value() {
+    const POLLS: u32 = 1_000_000;
+    let (_tx, mut rx) = watch::channel(0u64);
+    rx.get();
+    let start = Instant::now();
+    for _ in 0..POLLS {
+        black_box(rx.get_if_new());
+        black_box(rx.has_changed());
+    }
+    let per_poll = start.elapsed() / POLLS;
+    eprintln!(
+        "get_if_new and has_changed without a new value: {:?}",
+        per_poll
+    );
+    assert!(per_poll < Duration::from_millis(1));
+}
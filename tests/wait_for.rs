@@ -0,0 +1,124 @@
+//! `WatchReceiver::wait_for`, polled by hand.
+#![cfg(all(feature = "embedded-async", not(target_family = "wasm")))]
+
+use std::{
+    future::Future,
+    pin::pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    thread,
+};
+use watch::RecvError;
+
+mod util;
+use util::{
+    eventually,
+    task::{block_on, counting_waker},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Job {
+    Idle,
+    Loading(u32),
+    Ready,
+}
+
+#[test]
+fn the_latest_value_is_checked_first() {
+    let (_tx, mut rx) = watch::channel(Job::Ready);
+    rx.get();
+    assert_eq!(
+        block_on(rx.wait_for(|job| *job == Job::Ready)),
+        Ok(Job::Ready)
+    );
+    assert!(!rx.has_changed());
+}
+
+#[test]
+fn every_update_is_checked_until_one_matches() {
+    let (tx, mut rx) = watch::channel(Job::Idle);
+    let checked = Arc::new(AtomicUsize::new(0));
+    let driver = {
+        let checked = checked.clone();
+        thread::spawn(move || {
+            // Each update is sent once the one before has been checked, so
+            // none is skipped.
+            for step in 0..5 {
+                assert!(eventually(
+                    || checked.load(Ordering::SeqCst) == step as usize + 1
+                ));
+                tx.send(Job::Loading(step));
+            }
+            assert!(eventually(|| checked.load(Ordering::SeqCst) == 6));
+            tx.send(Job::Ready);
+            tx
+        })
+    };
+    let mut seen = Vec::new();
+    let result = block_on(rx.wait_for(|job| {
+        seen.push(job.clone());
+        checked.fetch_add(1, Ordering::SeqCst);
+        *job == Job::Ready
+    }));
+    assert_eq!(result, Ok(Job::Ready));
+    let expected: Vec<_> = Some(Job::Idle)
+        .into_iter()
+        .chain((0..5).map(Job::Loading))
+        .chain(Some(Job::Ready))
+        .collect();
+    assert_eq!(seen, expected);
+    assert!(!rx.has_changed());
+    drop(driver.join().unwrap());
+}
+
+#[test]
+fn dropping_the_future_keeps_the_receiver_state() {
+    let (tx, mut rx) = watch::channel(Job::Idle);
+    let (wakes, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+    {
+        let mut wait = pin!(rx.wait_for(|job| *job == Job::Ready));
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+        tx.send(Job::Loading(0));
+        assert_eq!(wakes.count(), 1);
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+    }
+    // The values that were checked stay seen, and the waker was removed.
+    assert!(!rx.has_changed());
+    tx.send(Job::Loading(1));
+    assert_eq!(wakes.count(), 1);
+    assert!(rx.has_changed());
+    assert_eq!(
+        block_on(rx.wait_for(|job| *job == Job::Loading(1))),
+        Ok(Job::Loading(1))
+    );
+}
+
+#[test]
+fn closing_the_channel_fails_the_wait() {
+    let (tx, mut rx) = watch::channel(Job::Idle);
+    let sender = thread::spawn(move || {
+        tx.send(Job::Loading(0));
+    });
+    assert_eq!(
+        block_on(rx.wait_for(|job| *job == Job::Ready)),
+        Err(RecvError)
+    );
+    sender.join().unwrap();
+
+    // A last value that matches is still returned.
+    let (tx, mut rx) = watch::channel(Job::Idle);
+    tx.send(Job::Ready);
+    drop(tx);
+    assert_eq!(
+        block_on(rx.wait_for(|job| *job == Job::Ready)),
+        Ok(Job::Ready)
+    );
+    assert_eq!(
+        block_on(rx.wait_for(|job| *job == Job::Idle)),
+        Err(RecvError)
+    );
+}
@@ -0,0 +1,123 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+mod util;
+use util::eventually;
+
+/// A large value that cannot be cloned.
+#[derive(Debug)]
+struct Big(Vec<u32>);
+
+#[test]
+fn returns_the_projection_without_cloning() {
+    let (tx, mut rx) = watch::channel(Big(vec![1; 1000]));
+    // The starting value has not been seen.
+    assert_eq!(rx.wait_map(|big| big.0.len()), 1000);
+    assert!(!rx.has_changed());
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(Big(vec![2; 10]));
+        tx
+    });
+    assert_eq!(rx.wait_map(|big| big.0.iter().sum::<u32>()), 20);
+    assert!(!rx.has_changed());
+    drop(sender.join().unwrap());
+}
+
+#[test]
+fn a_send_during_the_closure_stays_unseen() {
+    let (tx, mut rx) = watch::channel(Big(vec![0]));
+    let started = Arc::new(AtomicBool::new(false));
+    let sent = Arc::new(AtomicBool::new(false));
+    let sender = {
+        let started = started.clone();
+        let sent = sent.clone();
+        thread::spawn(move || {
+            assert!(eventually(|| started.load(Ordering::SeqCst)));
+            tx.send(Big(vec![1]));
+            sent.store(true, Ordering::SeqCst);
+            tx
+        })
+    };
+    let first = rx.wait_map(|big| {
+        started.store(true, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(20));
+        // The value is locked, so the sender waits for this to return.
+        assert!(!sent.load(Ordering::SeqCst));
+        big.0[0]
+    });
+    assert_eq!(first, 0);
+    let _tx = sender.join().unwrap();
+    // Only the version the closure saw was marked seen.
+    assert!(rx.has_changed());
+    assert_eq!(rx.wait_map(|big| big.0[0]), 1);
+}
+
+#[test]
+fn only_the_latest_value_is_seen() {
+    let (tx, mut rx) = watch::channel(Big(vec![0]));
+    rx.wait_map(|_| ());
+    tx.send(Big(vec![1]));
+    tx.send(Big(vec![2]));
+    assert_eq!(rx.wait_map(|big| big.0[0]), 2);
+    assert_eq!(
+        rx.wait_map_timeout(Duration::from_millis(10), |big| big.0[0]),
+        None
+    );
+}
+
+#[test]
+fn a_panicking_closure_leaves_the_value_unseen() {
+    let (tx, mut rx) = watch::channel(Big(vec![0]));
+    rx.wait_map(|_| ());
+    tx.send(Big(vec![1]));
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        rx.wait_map(|_| -> u32 { panic!("in f") })
+    }));
+    assert!(result.is_err());
+    assert!(rx.has_changed());
+    assert!(!tx.is_poisoned());
+    assert_eq!(rx.wait_map(|big| big.0[0]), 1);
+
+    // The value was unlocked, so the channel can still be sent to.
+    tx.send(Big(vec![2]));
+    assert_eq!(rx.wait_map(|big| big.0[0]), 2);
+}
+
+#[test]
+fn timing_out_does_not_call_the_closure() {
+    let (tx, mut rx) = watch::channel(Big(vec![0]));
+    rx.wait_map(|_| ());
+    let result = rx.wait_map_timeout(Duration::from_millis(10), |_| -> u32 {
+        panic!("called after a timeout")
+    });
+    assert_eq!(result, None);
+
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(Big(vec![7]));
+    });
+    assert_eq!(
+        rx.wait_map_timeout(Duration::from_secs(10), |big| big.0[0]),
+        Some(7)
+    );
+    sender.join().unwrap();
+}
+
+#[test]
+fn an_unseen_value_is_mapped_at_once() {
+    let (tx, mut rx) = watch::channel(Big(vec![3; 3]));
+    assert_eq!(rx.wait_map(|big| big.0.len()), 3);
+    tx.send(Big(vec![4]));
+    assert_eq!(rx.wait_map(|big| big.0[0]), 4);
+}
@@ -0,0 +1,83 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+
+mod util;
+use util::{eventually, join_all};
+
+#[test]
+fn unseen_values_count() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(rx.wait_n_updates(2), 2);
+    assert_eq!(rx.wait_n_updates(0), 2);
+    assert_eq!(rx.wait_n_updates_timeout(1, Duration::from_millis(5)), None);
+}
+
+#[test]
+fn the_unseen_starting_value_counts_as_one() {
+    let (_tx, mut rx) = watch::channel(0);
+    assert_eq!(rx.wait_n_updates_timeout(1, Duration::ZERO), Some(0));
+    assert_eq!(rx.wait_n_updates_timeout(1, Duration::ZERO), None);
+}
+
+#[test]
+fn a_timeout_marks_nothing_seen() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    tx.send(1);
+    assert_eq!(rx.wait_n_updates_timeout(2, Duration::from_millis(5)), None);
+    assert!(rx.has_changed());
+    tx.send(2);
+    assert_eq!(rx.wait_n_updates_timeout(2, Duration::ZERO), Some(2));
+}
+
+#[test]
+fn values_are_counted_while_waiting() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let sender = thread::spawn(move || {
+        for value in 1..=3 {
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            tx.send(value);
+        }
+        tx
+    });
+    assert_eq!(rx.wait_n_updates(3), 3);
+    sender.join().unwrap();
+}
+
+#[test]
+fn counting_waiters_do_not_hold_up_plain_ones() {
+    let (tx, rx) = watch::channel(0u32);
+    let subscribe = || {
+        let mut rx = rx.clone();
+        rx.get();
+        rx
+    };
+    // More counting waiters than are woken at a time, parked ahead of the
+    // plain ones, so every wakeup goes to one of them first.
+    let counting: Vec<_> = (0..16)
+        .map(|_| {
+            let mut rx = subscribe();
+            thread::spawn(move || rx.wait_n_updates(100))
+        })
+        .collect();
+    assert!(eventually(|| tx.waiting_receivers() == 16));
+    let plain: Vec<_> = (0..4)
+        .map(|_| {
+            let mut rx = subscribe();
+            thread::spawn(move || rx.wait())
+        })
+        .collect();
+    assert!(eventually(|| tx.waiting_receivers() == 20));
+
+    tx.send(1);
+    assert_eq!(join_all(plain), [1; 4]);
+    for value in 2..=100 {
+        tx.send(value);
+    }
+    assert_eq!(join_all(counting), [100; 16]);
+}
@@ -0,0 +1,87 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+
+mod util;
+use util::{eventually, join_all};
+
+#[test]
+fn takes_the_value_and_leaves_the_default() {
+    let (tx, mut rx) = watch::channel(vec![1]);
+    // The starting value has not been seen.
+    assert_eq!(rx.wait_take(), [1]);
+    // The default is not a new value.
+    assert!(!rx.has_changed());
+    assert_eq!(rx.wait_take_timeout(Duration::from_millis(10)), None);
+    assert_eq!(rx.get(), Vec::<u32>::new());
+
+    tx.send(vec![2]);
+    assert_eq!(rx.wait_take_timeout(Duration::from_secs(10)), Some(vec![2]));
+    assert_eq!(tx.reader().get().0, Vec::<u32>::new());
+}
+
+#[test]
+fn the_worker_parks_until_a_job_arrives() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let worker = thread::spawn(move || rx.wait_take());
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    tx.send(5);
+    assert_eq!(worker.join().unwrap(), 5);
+}
+
+#[test]
+fn a_held_value_is_cloned_out() {
+    let (tx, mut rx) = watch::channel(String::from("job"));
+    let mut other = tx.subscribe();
+    tx.send(String::from("held"));
+    let held = other.get_shared();
+    assert_eq!(rx.wait_take(), "held");
+    // The holder keeps its copy.
+    assert_eq!(*held, "held");
+    assert_eq!(other.get(), "");
+}
+
+#[test]
+fn jobs_sent_while_taking_are_not_lost() {
+    const JOBS: u64 = 20_000;
+
+    let (tx, mut rx) = watch::channel(0u64);
+    rx.get();
+    let producer = thread::spawn(move || {
+        for job in 1..=JOBS {
+            tx.send(job);
+        }
+        tx
+    });
+    let mut last = 0;
+    while last < JOBS {
+        let job = rx.wait_take();
+        // A job is only taken once, and never replaced by the default.
+        assert!(job > last, "took {} after {}", job, last);
+        last = job;
+    }
+    let tx = join_all(vec![producer]).pop().unwrap();
+    assert_eq!(rx.wait_take_timeout(Duration::from_millis(10)), None);
+    drop(tx);
+}
+
+#[test]
+fn a_send_right_at_the_wakeup_is_left_for_the_next_take() {
+    for _ in 0..200 {
+        let (tx, mut rx) = watch::channel(0u32);
+        rx.get();
+        let worker = thread::spawn(move || (rx.wait_take(), rx));
+        assert!(eventually(|| tx.waiting_receivers() == 1));
+        tx.send(1);
+        // This lands either before the take, which then returns it, or
+        // after, which leaves it as a new value.
+        tx.send(2);
+        let (first, mut rx) = worker.join().unwrap();
+        match first {
+            1 => assert_eq!(rx.wait_take(), 2),
+            2 => assert!(!rx.has_changed()),
+            taken => panic!("took {}", taken),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+
+mod util;
+use util::{eventually, join_all};
+
+#[test]
+fn parked_receivers_are_counted() {
+    let (tx, rx) = watch::channel(0);
+    assert_eq!(tx.waiting_receivers(), 0);
+    let subscribe = || {
+        let mut rx = rx.clone();
+        rx.get();
+        rx
+    };
+    let (mut a, mut b, mut c, mut d) = (subscribe(), subscribe(), subscribe(), subscribe());
+    let waiters = vec![
+        thread::spawn(move || a.wait()),
+        thread::spawn(move || b.wait_timeout(Duration::from_secs(60)).unwrap()),
+        thread::spawn(move || c.recv().unwrap()),
+        thread::spawn(move || d.wait_n_updates(1)),
+    ];
+    assert!(eventually(|| tx.waiting_receivers() == 4));
+
+    // Receivers that only poll are not counted.
+    let mut polling = rx.clone();
+    polling.get_if_new();
+    assert_eq!(tx.waiting_receivers(), 4);
+
+    tx.send(1);
+    assert_eq!(join_all(waiters), [1, 1, 1, 1]);
+    assert_eq!(tx.waiting_receivers(), 0);
+}
+
+#[test]
+fn timed_out_receivers_are_not_counted() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    assert_eq!(rx.wait_timeout(Duration::from_millis(5)), None);
+    assert_eq!(tx.waiting_receivers(), 0);
+}
+
+#[test]
+fn closing_wakes_every_counted_receiver() {
+    let (tx, rx) = watch::channel(0);
+    let waiters = (0..3)
+        .map(|_| {
+            let mut rx = rx.clone();
+            rx.get();
+            thread::spawn(move || rx.recv())
+        })
+        .collect();
+    assert!(eventually(|| tx.waiting_receivers() == 3));
+    drop(tx);
+    assert!(join_all(waiters).iter().all(Result::is_err));
+}
@@ -0,0 +1,167 @@
+//! Stress tests for parking and waking receivers.
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+mod util;
+
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+use util::{eventually, join_all};
+
+#[test]
+fn concurrent_sends_and_waits_lose_no_wakeups() {
+    let mut threads = Vec::new();
+    for _ in 0..100 {
+        let (tx, rx) = watch::channel(0u64);
+        for _ in 0..4 {
+            let mut rx = rx.clone();
+            threads.push(thread::spawn(move || {
+                let mut last = 0;
+                while last < 1000 {
+                    last = rx.wait();
+                }
+            }));
+        }
+        for _ in 0..2 {
+            let tx = tx.clone();
+            threads.push(thread::spawn(move || {
+                for i in 0..=1000 {
+                    tx.update(|value| *value = (*value).max(i));
+                }
+            }));
+        }
+    }
+    join_all(threads);
+}
+
+#[test]
+fn receivers_that_poll_never_block_senders() {
+    let (tx, mut rx) = watch::channel(0u64);
+    let poller = thread::spawn(move || {
+        let mut last = 0;
+        while last < 100_000 {
+            if let Some(value) = rx.get_if_new() {
+                last = value;
+            }
+        }
+    });
+    for i in 1..=100_000 {
+        tx.send(i);
+    }
+    join_all(vec![poller]);
+}
+
+#[test]
+fn timed_waiters_are_woken() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let waiter = thread::spawn(move || rx.wait_timeout(Duration::from_secs(30)));
+    thread::sleep(Duration::from_millis(20));
+    tx.send(5);
+    assert_eq!(join_all(vec![waiter]), [Some(5)]);
+}
+
+#[test]
+fn many_receivers_get_a_value_with_a_small_latency_spread() {
+    const RECEIVERS: usize = 200;
+    let (tx, rx) = watch::channel(Instant::now());
+    let barrier = Arc::new(Barrier::new(RECEIVERS + 1));
+    let receivers = (0..RECEIVERS)
+        .map(|_| {
+            let mut rx = rx.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                rx.get();
+                barrier.wait();
+                rx.wait().elapsed()
+            })
+        })
+        .collect();
+    barrier.wait();
+    assert!(eventually(|| tx.waiting_receivers() == RECEIVERS));
+    tx.send(Instant::now());
+
+    let mut latencies = join_all(receivers);
+    latencies.sort();
+    let (first, median, last) = (
+        latencies[0],
+        latencies[RECEIVERS / 2],
+        latencies[RECEIVERS - 1],
+    );
+    eprintln!("wakeup latency: first {first:?}, median {median:?}, last {last:?}");
+    assert!(last < Duration::from_secs(10));
+}
+
+#[test]
+fn waiters_come_and_go_while_values_are_sent() {
+    for _ in 0..20 {
+        let (tx, rx) = watch::channel(0u64);
+        let waiters = (0..8)
+            .map(|i| {
+                let mut rx = rx.clone();
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while last < 2000 {
+                        // Timed waits that give up register and unregister
+                        // all the time, next to waits that stay parked.
+                        if i % 2 == 0 {
+                            if let Some(value) = rx.wait_timeout(Duration::from_micros(50)) {
+                                last = value;
+                            }
+                        } else {
+                            last = rx.wait();
+                        }
+                    }
+                })
+            })
+            .collect();
+        for i in 1..=2000 {
+            tx.send(i);
+            if i % 100 == 0 {
+                thread::yield_now();
+            }
+        }
+        join_all(waiters);
+    }
+}
+
+#[test]
+fn waiters_that_keep_waiting_pass_the_wakeup_on() {
+    let (tx, rx) = watch::channel(0);
+    // More threads than a single send wakes, which go back to sleep on every
+    // send, ahead of one that takes any new value.
+    let patient: Vec<_> = (0..6)
+        .map(|_| {
+            let mut rx = rx.clone();
+            rx.get();
+            thread::spawn(move || rx.wait_n_updates(1000))
+        })
+        .collect();
+    assert!(eventually(|| tx.waiting_receivers() == 6));
+    let mut plain = rx.clone();
+    plain.get();
+    let plain = thread::spawn(move || plain.wait());
+    assert!(eventually(|| tx.waiting_receivers() == 7));
+
+    tx.send(1);
+    assert_eq!(join_all(vec![plain]), [1]);
+    drop(tx);
+    drop(patient);
+}
+
+#[test]
+fn closing_wakes_every_waiter() {
+    let (tx, rx) = watch::channel(0);
+    let waiters = (0..50)
+        .map(|_| {
+            let mut rx = rx.clone();
+            rx.get();
+            thread::spawn(move || rx.recv())
+        })
+        .collect();
+    assert!(eventually(|| tx.waiting_receivers() == 50));
+    drop(tx);
+    assert!(join_all(waiters).iter().all(Result::is_err));
+}
@@ -0,0 +1,139 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{thread, time::Duration};
+use watch::{watch_map, RecvError, RecvTimeoutError};
+
+mod util;
+use util::join_all;
+
+#[test]
+fn slots_are_created_lazily() {
+    let map = watch_map::<String, u32>();
+    assert!(map.is_empty());
+    let mut a = map.subscribe("a".into());
+    assert!(map.contains_key("a"));
+    assert_eq!(a.get(), None);
+    assert_eq!(a.get_if_new(), None);
+    assert!(!a.has_changed());
+    assert_eq!(map.get("a"), None);
+    assert_eq!(map.get("missing"), None);
+}
+
+#[test]
+fn keys_have_their_own_versions() {
+    let map = watch_map::<String, u32>();
+    let mut a = map.subscribe("a".into());
+    map.send("b".into(), 1);
+    assert!(!a.has_changed());
+
+    // A receiver subscribed after a send has seen it.
+    let mut b = map.subscribe("b".into());
+    assert_eq!(b.get_if_new(), None);
+    assert_eq!(b.get(), Some(1));
+
+    map.send("a".into(), 5);
+    assert_eq!(a.get_if_new(), Some(5));
+    assert!(!b.has_changed());
+    assert_eq!(map.get("a"), Some(5));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn waiting_on_a_key() {
+    let map = watch_map::<u32, u32>();
+    let mut rx = map.subscribe(1);
+    let mut cloned = rx.clone();
+    let waiter = thread::spawn(move || cloned.wait());
+
+    // A send to another key does not wake the waiter.
+    map.send(2, 20);
+    map.send(1, 10);
+    assert_eq!(waiter.join().unwrap(), 10);
+    assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Ok(10));
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+#[test]
+fn removing_a_key_closes_its_receivers() {
+    let map = watch_map::<u32, u32>();
+    map.send(1, 10);
+    let mut rx = map.subscribe(1);
+    let mut waiting = rx.clone();
+    let waiter = thread::spawn(move || waiting.recv());
+
+    assert_eq!(map.remove(&1), Some(10));
+    assert_eq!(waiter.join().unwrap(), Err(RecvError));
+    assert!(rx.is_removed());
+    // The last value can still be read.
+    assert_eq!(rx.get(), Some(10));
+
+    // A new slot under the same key is a different channel.
+    map.send(1, 11);
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(map.subscribe(1).get(), Some(11));
+}
+
+#[test]
+fn removing_missing_or_empty_keys() {
+    let map = watch_map::<u32, u32>();
+    assert_eq!(map.remove(&2), None);
+    let _rx = map.subscribe(3);
+    assert_eq!(map.remove(&3), None);
+    assert!(!map.contains_key(&3));
+}
+
+#[test]
+fn a_value_sent_before_removal_is_received() {
+    let map = watch_map::<u32, u32>();
+    let mut rx = map.subscribe(4);
+    map.send(4, 40);
+    map.remove(&4);
+    assert_eq!(rx.recv(), Ok(40));
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn dropping_the_map_closes_every_key() {
+    let map = watch_map::<u32, u32>();
+    let mut rx = map.subscribe(5);
+    drop(map);
+    assert!(rx.is_removed());
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn concurrent_senders_on_different_keys() {
+    let map = watch_map::<usize, usize>();
+    let mut receivers: Vec<_> = (0..64).map(|key| map.subscribe(key)).collect();
+    let senders = (0..8)
+        .map(|thread| {
+            let map = map.clone();
+            thread::spawn(move || {
+                for value in 0..2000 {
+                    for key in (thread..64).step_by(8) {
+                        map.send(key, value);
+                    }
+                }
+            })
+        })
+        .collect();
+    join_all(senders);
+    for rx in &mut receivers {
+        assert_eq!(rx.get(), Some(1999));
+    }
+    assert_eq!(map.len(), 64);
+}
+
+#[cfg(feature = "parking_lot")]
+#[test]
+fn other_backends() {
+    use watch::backend::{self, ParkingLotCondvar};
+
+    let map = backend::watch_map::<ParkingLotCondvar, u8, u8>();
+    let mut rx = map.subscribe(1);
+    map.send(1, 2);
+    assert_eq!(rx.wait(), 2);
+}
@@ -0,0 +1,149 @@
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
+
+use std::{
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+mod util;
+use util::eventually;
+
+/// A callback that forwards the stale values to the returned receiver.
+fn alarm() -> (impl FnMut(&u32) + Send + 'static, Receiver<u32>) {
+    let (tx, rx) = mpsc::channel();
+    (move |value: &u32| tx.send(*value).unwrap(), rx)
+}
+
+#[test]
+fn fires_while_the_channel_is_quiet() {
+    let (tx, rx) = watch::channel(0);
+    let (on_stale, alarms) = alarm();
+    let handle = rx.watchdog(Duration::from_millis(10), on_stale);
+    assert_eq!(alarms.recv(), Ok(0));
+    assert_eq!(alarms.recv(), Ok(0));
+    tx.send(1);
+    while alarms.recv() != Ok(1) {}
+    handle.stop();
+}
+
+#[test]
+fn stopping_waits_for_the_thread() {
+    let (tx, rx) = watch::channel(0);
+    let handle = rx.watchdog(Duration::from_secs(3600), |_| {});
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    handle.stop();
+    assert_eq!(tx.receiver_count(), 0);
+}
+
+#[test]
+fn dropping_the_handle_stops_the_thread() {
+    let (tx, rx) = watch::channel(0);
+    let handle = rx.watchdog(Duration::from_secs(3600), |_| {});
+    assert!(eventually(|| tx.waiting_receivers() == 1));
+    drop(handle);
+    assert!(eventually(|| tx.receiver_count() == 0));
+}
+
+#[test]
+fn closing_the_channel_stops_the_thread() {
+    let (tx, rx) = watch::channel(0);
+    let handle = rx.watchdog(Duration::from_secs(3600), |_| {});
+    assert!(!handle.is_finished());
+    drop(tx);
+    assert!(eventually(|| handle.is_finished()));
+}
+
+#[cfg(all(feature = "test-clock", target_has_atomic = "64"))]
+mod mock_clock {
+    use super::*;
+    use watch::MockClock;
+
+    const SHORT: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn repeats_every_quiet_interval() {
+        let clock = MockClock::new();
+        let (tx, rx) = watch::builder().clock(clock.clone()).channel(0);
+        let (on_stale, alarms) = alarm();
+        let handle = rx.watchdog(Duration::from_secs(10), on_stale);
+        for _ in 0..3 {
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            clock.advance(Duration::from_secs(10));
+            assert_eq!(alarms.recv(), Ok(0));
+        }
+        handle.stop();
+        assert!(alarms.try_recv().is_err());
+    }
+
+    #[test]
+    fn new_values_restart_the_wait() {
+        let clock = MockClock::new();
+        let (tx, rx) = watch::builder()
+            .clock(clock.clone())
+            .track_lag(true)
+            .channel(0);
+        let (on_stale, alarms) = alarm();
+        let handle = rx.watchdog(Duration::from_secs(10), on_stale);
+        for value in 1..=3 {
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            clock.advance(Duration::from_secs(9));
+            tx.send(value);
+            // Wait for the watchdog to take the value before moving on.
+            assert!(eventually(|| tx.max_lag() == Some(0)));
+        }
+        assert!(alarms.recv_timeout(SHORT).is_err());
+        assert!(eventually(|| tx.waiting_receivers() == 1));
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(alarms.recv(), Ok(3));
+        handle.stop();
+    }
+
+    #[test]
+    fn once_fires_again_only_after_recovery() {
+        let clock = MockClock::new();
+        let (tx, rx) = watch::builder()
+            .clock(clock.clone())
+            .track_lag(true)
+            .channel(0);
+        let (on_stale, alarms) = alarm();
+        let handle = rx.watchdog_once(Duration::from_secs(10), on_stale);
+        assert!(eventually(|| tx.waiting_receivers() == 1));
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(alarms.recv(), Ok(0));
+        for _ in 0..3 {
+            assert!(eventually(|| tx.waiting_receivers() == 1));
+            clock.advance(Duration::from_secs(10));
+        }
+        assert!(alarms.recv_timeout(SHORT).is_err());
+
+        tx.send(7);
+        assert!(eventually(|| tx.max_lag() == Some(0)));
+        assert!(eventually(|| tx.waiting_receivers() == 1));
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(alarms.recv(), Ok(7));
+        handle.stop();
+    }
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn stopping_leaves_the_other_receivers_parked() {
+    use std::thread;
+
+    let (tx, rx) = watch::channel(0);
+    let mut other = rx.clone();
+    other.get();
+    let waiter = thread::spawn(move || other.wait());
+    let handle = rx.watchdog(Duration::from_secs(3600), |_| {});
+    assert!(eventually(|| tx.waiting_receivers() == 2));
+
+    let before = tx.stats();
+    handle.stop();
+    let after = tx.stats();
+    assert_eq!(after.notifications, before.notifications);
+    assert_eq!(after.wakeups, before.wakeups + 1);
+    assert_eq!(tx.waiting_receivers(), 1);
+
+    tx.send(1);
+    assert_eq!(waiter.join().unwrap(), 1);
+}
@@ -0,0 +1,135 @@
+//! Waiting on channels with `WaitForSingleObject` and
+//! `WaitForMultipleObjects`.
+#![cfg(all(windows, feature = "windows-event"))]
+
+use std::{os::windows::io::AsRawHandle, thread, time::Duration};
+use watch::WindowsEventHandle;
+use windows_sys::Win32::{
+    Foundation::{HANDLE, WAIT_OBJECT_0, WAIT_TIMEOUT},
+    System::Threading::{WaitForMultipleObjects, WaitForSingleObject},
+};
+
+/// Wait up to `millis` for the event, and return the result of the wait.
+fn wait(event: &WindowsEventHandle, millis: u32) -> u32 {
+    // SAFETY: The handle is open while `event` is borrowed.
+    unsafe { WaitForSingleObject(event.as_raw_handle() as HANDLE, millis) }
+}
+
+#[test]
+fn starts_signaled_for_an_unseen_value() {
+    let (tx, mut rx) = watch::channel(0);
+    let event = rx.readiness_event().unwrap();
+    assert!(event.is_set());
+    assert_eq!(wait(&event, 0), WAIT_OBJECT_0);
+
+    rx.get();
+    let seen = rx.readiness_event().unwrap();
+    assert!(!seen.is_set());
+    assert_eq!(wait(&seen, 10), WAIT_TIMEOUT);
+    tx.send(1);
+    // Every event of the channel is signaled.
+    assert!(seen.is_set() && event.is_set());
+}
+
+#[test]
+fn the_event_stays_set_until_reset() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let event = rx.readiness_event().unwrap();
+    tx.send(1);
+    tx.send(2);
+    assert_eq!(wait(&event, 0), WAIT_OBJECT_0);
+    assert_eq!(wait(&event, 0), WAIT_OBJECT_0);
+
+    event.reset();
+    assert!(!event.is_set());
+    assert_eq!(rx.get_if_new(), Some(2));
+    assert_eq!(wait(&event, 10), WAIT_TIMEOUT);
+}
+
+#[test]
+fn resetting_before_draining_misses_nothing() {
+    let (tx, mut rx) = watch::channel(0u32);
+    rx.get();
+    let event = rx.readiness_event().unwrap();
+    let sender = thread::spawn(move || {
+        for value in 1..=1000 {
+            tx.send(value);
+        }
+    });
+    let mut last = 0;
+    while last < 1000 {
+        assert_eq!(wait(&event, 10_000), WAIT_OBJECT_0);
+        event.reset();
+        while let Some(value) = rx.get_if_new() {
+            assert!(value > last);
+            last = value;
+        }
+    }
+    sender.join().unwrap();
+}
+
+#[test]
+fn a_send_from_another_thread_ends_the_wait() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let event = rx.readiness_event().unwrap();
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        tx.send(1);
+        tx
+    });
+    assert_eq!(wait(&event, 10_000), WAIT_OBJECT_0);
+    assert_eq!(rx.get_if_new(), Some(1));
+    drop(sender.join().unwrap());
+}
+
+#[test]
+fn waiting_on_several_channels() {
+    let (_first, mut a) = watch::channel(0);
+    let (second, mut b) = watch::channel(0);
+    a.get();
+    b.get();
+    let events = [a.readiness_event().unwrap(), b.readiness_event().unwrap()];
+    let handles = events
+        .each_ref()
+        .map(|event| event.as_raw_handle() as HANDLE);
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        second.send(1);
+        second
+    });
+    // SAFETY: The handles are open while `events` lives.
+    let woken = unsafe { WaitForMultipleObjects(2, handles.as_ptr(), 0, 10_000) };
+    assert_eq!(woken, WAIT_OBJECT_0 + 1);
+    assert_eq!(b.get_if_new(), Some(1));
+    assert_eq!(a.get_if_new(), None);
+    drop(sender.join().unwrap());
+}
+
+#[test]
+fn closing_the_channel_signals_the_event() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let event = rx.readiness_event().unwrap();
+    drop(tx);
+    assert_eq!(wait(&event, 0), WAIT_OBJECT_0);
+    assert!(rx.is_closed());
+}
+
+#[test]
+fn the_event_outlives_the_receiver() {
+    let (tx, mut rx) = watch::channel(0);
+    rx.get();
+    let event = rx.readiness_event().unwrap();
+    drop(rx);
+    let _rx = tx.subscribe();
+    tx.send(1);
+    assert_eq!(wait(&event, 0), WAIT_OBJECT_0);
+    event.reset();
+    assert!(!event.is_set());
+
+    // Sends go on after the event has been dropped.
+    drop(event);
+    tx.send(2);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "std")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn every_sender_has_its_own_id() {
+    let (a, rx) = watch::channel(0);
+    let b = a.clone();
+    let c = rx.new_sender();
+    assert_ne!(a.id(), b.id());
+    assert_ne!(b.id(), c.id());
+    assert_ne!(a.id(), c.id());
+    assert_eq!(a.id(), a.id());
+
+    // Ids are not reused by later senders.
+    let dropped = b.id();
+    drop(b);
+    assert_ne!(a.clone().id(), dropped);
+}
+
+#[test]
+fn the_first_sender_wrote_the_starting_value() {
+    let (tx, rx) = watch::channel(0);
+    assert_eq!(rx.last_writer(), tx.id());
+}
+
+#[test]
+fn alternating_writes_are_attributed() {
+    let (a, mut rx) = watch::channel(0);
+    let b = a.clone();
+    for value in 0..10 {
+        let sender = if value % 2 == 0 { &a } else { &b };
+        sender.send(value);
+        assert_eq!(rx.last_writer(), sender.id());
+        assert_eq!(rx.get_with_writer(), (value, sender.id()));
+    }
+    b.update(|value| *value += 1);
+    assert_eq!(rx.last_writer(), b.id());
+    a.update_with(|value| value + 1);
+    assert_eq!(rx.get_with_writer(), (11, a.id()));
+}
+
+#[test]
+fn transactions_are_attributed() {
+    let (a, mut rx) = watch::channel(0);
+    let b = a.clone();
+    let (other, _other_rx) = watch::channel(0);
+    watch::transaction((&b, &other), |value, _| *value = 5);
+    assert_eq!(rx.get_with_writer(), (5, b.id()));
+    assert_ne!(rx.last_writer(), a.id());
+}
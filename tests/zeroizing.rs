@@ -0,0 +1,89 @@
+#![cfg(feature = "zeroize")]
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Counts how many copies were dropped, and how many of those without
+/// being zeroized first.
+#[derive(Default)]
+struct Counts {
+    dropped: AtomicUsize,
+    unscrubbed: AtomicUsize,
+}
+
+#[derive(Clone)]
+struct Key {
+    bytes: Vec<u8>,
+    scrubbed: bool,
+    counts: Arc<Counts>,
+}
+
+impl Key {
+    fn new(byte: u8, counts: &Arc<Counts>) -> Key {
+        Key {
+            bytes: vec![byte; 4],
+            scrubbed: false,
+            counts: counts.clone(),
+        }
+    }
+}
+
+impl Zeroize for Key {
+    fn zeroize(&mut self) {
+        self.bytes.zeroize();
+        self.scrubbed = true;
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        self.counts.dropped.fetch_add(1, Ordering::SeqCst);
+        if !self.scrubbed {
+            self.counts.unscrubbed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn replaced_values_are_scrubbed() {
+    let counts = Arc::new(Counts::default());
+    let (tx, mut rx) = watch::zeroizing_channel(Key::new(1, &counts));
+    tx.send_zeroizing(Key::new(2, &counts));
+    assert_eq!(counts.dropped.load(Ordering::SeqCst), 1);
+
+    // So are the clones that receivers get.
+    let key = rx.get();
+    assert_eq!(key.bytes, [2; 4]);
+    drop(key);
+    assert_eq!(counts.dropped.load(Ordering::SeqCst), 2);
+
+    // And the last value.
+    drop(tx);
+    drop(rx);
+    assert_eq!(counts.dropped.load(Ordering::SeqCst), 3);
+    assert_eq!(counts.unscrubbed.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn kept_copies_are_scrubbed() {
+    let counts = Arc::new(Counts::default());
+    let (tx, rx) = watch::builder()
+        .history(2)
+        .undo(true)
+        .channel(Zeroizing::new(Key::new(1, &counts)));
+    for byte in 2..6 {
+        tx.send_zeroizing(Key::new(byte, &counts));
+    }
+    tx.undo().unwrap();
+    tx.update(|key| key.bytes[0] = 0);
+    drop(tx);
+    drop(rx);
+    assert!(counts.dropped.load(Ordering::SeqCst) >= 5);
+    assert_eq!(counts.unscrubbed.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,188 @@
+//! The derive macro of the [`watch`] crate.
+//!
+//! Use it through the `derive` feature of `watch`, which re-exports it as
+//! `watch::WatchFields`.
+//!
+//! [`watch`]: https://docs.rs/watch
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Field, Fields};
+
+/// Derive `watch::WatchFields`, which gives every field of a struct its own
+/// watch channel.
+///
+/// See the documentation of the trait in the `watch` crate.
+#[proc_macro_derive(WatchFields, attributes(watch))]
+pub fn derive_watch_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How a field is watched, as set by its `#[watch(...)]` attribute.
+enum Kind {
+    /// The field has a channel of its own.
+    Channel,
+    /// The field derives `WatchFields` itself, and each of its fields has a
+    /// channel.
+    Nested,
+    /// The field is not watched.
+    Skip,
+}
+
+fn kind(field: &Field) -> syn::Result<Kind> {
+    let mut kind = Kind::Channel;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("watch") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                kind = Kind::Skip;
+                Ok(())
+            } else if meta.path.is_ident("nested") {
+                kind = Kind::Nested;
+                Ok(())
+            } else {
+                Err(meta.error("expected `skip` or `nested`"))
+            }
+        })?;
+    }
+    Ok(kind)
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`WatchFields` cannot be derived for generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &data.fields,
+                    "`WatchFields` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "`WatchFields` can only be derived for structs",
+            ))
+        }
+    };
+
+    let senders = format_ident!("{}Senders", name);
+    let channels = format_ident!("{}Channels", name);
+
+    let mut sender_fields = Vec::new();
+    let mut channel_fields = Vec::new();
+    let mut create = Vec::new();
+    let mut idents = Vec::new();
+    let mut setters = Vec::new();
+    let mut set_all = Vec::new();
+    for field in fields {
+        let kind = kind(field)?;
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let field_vis = &field.vis;
+        let setter = format_ident!("set_{}", ident);
+        let (sender_ty, channel_ty, new, set, set_doc, channel_doc) = match kind {
+            Kind::Skip => continue,
+            Kind::Channel => (
+                quote!(::watch::WatchSender<#ty>),
+                quote!(::watch::WatchReceiver<#ty>),
+                quote!(::watch::channel(value.#ident)),
+                quote!(::watch::__private::send_if_changed(&self.#ident, value)),
+                format!("Send a new `{}`.", ident),
+                format!("The receiver for `{}`.", ident),
+            ),
+            Kind::Nested => (
+                quote!(<#ty as ::watch::WatchFields>::Senders),
+                quote!(<#ty as ::watch::WatchFields>::Channels),
+                quote!(::watch::WatchFields::into_channels(value.#ident)),
+                quote!(self.#ident.set_all(value)),
+                format!("Send the fields of `{}` that changed.", ident),
+                format!("The receivers for the fields of `{}`.", ident),
+            ),
+        };
+        sender_fields.push(quote!(#ident: #sender_ty));
+        channel_fields.push(quote! {
+            #[doc = #channel_doc]
+            #field_vis #ident: #channel_ty
+        });
+        create.push(quote!(let #ident = #new;));
+        setters.push(quote! {
+            #[doc = #set_doc]
+            ///
+            /// The receivers are only notified if the value changed.
+            #field_vis fn #setter(&self, value: #ty) {
+                #set;
+            }
+        });
+        set_all.push(quote!(self.#setter(value.#ident);));
+        idents.push(ident);
+    }
+
+    let senders_doc = format!(
+        "The senders for the fields of [`{}`], created by `WatchFields::into_channels`.",
+        name
+    );
+    let channels_doc = format!(
+        "A receiver for each field of [`{}`], created by `WatchFields::into_channels`.",
+        name
+    );
+    Ok(quote! {
+        #[doc = #senders_doc]
+        #[derive(Clone)]
+        #vis struct #senders {
+            #(#sender_fields,)*
+        }
+
+        #[doc = #channels_doc]
+        #[derive(Clone)]
+        #vis struct #channels {
+            #(#channel_fields,)*
+        }
+
+        impl ::watch::WatchFields for #name {
+            type Senders = #senders;
+            type Channels = #channels;
+
+            fn into_channels(self) -> (#senders, #channels) {
+                let value = self;
+                #(#create)*
+                (
+                    #senders { #(#idents: #idents.0,)* },
+                    #channels { #(#idents: #idents.1,)* },
+                )
+            }
+        }
+
+        impl #senders {
+            #(#setters)*
+
+            /// Send every field of `value` that changed.
+            ///
+            /// Skipped fields are ignored.
+            #vis fn set_all(&self, value: #name) {
+                #(#set_all)*
+            }
+
+            /// Create new receivers for every field.
+            ///
+            /// The current values are considered seen by the new receivers.
+            #vis fn subscribe(&self) -> #channels {
+                #channels { #(#idents: self.#idents.subscribe(),)* }
+            }
+        }
+    })
+}